@@ -0,0 +1,109 @@
+use node::rpc::{RpcArchiveBackfill, RpcArchiveBackfillQuery};
+use reqwest::Url;
+
+#[derive(Debug, clap::Args)]
+pub struct Archive {
+    #[command(subcommand)]
+    command: ArchiveCommand,
+}
+
+impl Archive {
+    pub fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            ArchiveCommand::Backfill(command) => command.run(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, clap::Subcommand)]
+pub enum ArchiveCommand {
+    Backfill(Backfill),
+}
+
+/// Resend blocks in the given height range to the archive sinks of a running node.
+///
+/// Only blocks still in the node's transition frontier can be resent, as
+/// their staged ledgers are needed to rebuild the archive data. Older heights
+/// are not refetched from peers: a refetched block comes without the staged
+/// ledger it was applied to, so its archive data can't be rebuilt. They are
+/// reported as unavailable and have to be imported from precomputed blocks
+/// instead, see `docs/archive-node-guide.md`.
+/// Heights missed by each sink are listed in the `archive/status` endpoint.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Backfill {
+    /// Http address of the node running in archive mode.
+    #[arg(long, default_value = "http://127.0.0.1:3000")]
+    node: Url,
+
+    /// Admin token of the node, sent as `Authorization: Bearer <TOKEN>`.
+    #[arg(long, env = "OPENMINA_HTTP_ADMIN_TOKEN", hide_env_values = true)]
+    admin_token: String,
+
+    #[arg(long)]
+    from_height: u32,
+
+    #[arg(long)]
+    to_height: u32,
+}
+
+impl Backfill {
+    pub fn run(self) -> anyhow::Result<()> {
+        let query = RpcArchiveBackfillQuery {
+            from_height: self.from_height,
+            to_height: self.to_height,
+        };
+        query.validate().map_err(anyhow::Error::msg)?;
+
+        let url = self.node.join("archive/backfill")?;
+        let response = reqwest::blocking::Client::new()
+            .post(url)
+            .bearer_auth(&self.admin_token)
+            .json(&query)
+            .send()?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error = response.text().unwrap_or_default();
+            anyhow::bail!("backfill failed ({status}): {error}");
+        }
+
+        let RpcArchiveBackfill {
+            resent,
+            unavailable,
+        } = response.json()?;
+        println!("resent:      {}", format_heights(&resent));
+        println!("unavailable: {}", format_heights(&unavailable));
+        if !unavailable.is_empty() {
+            println!(
+                "unavailable heights are below the transition frontier root, \
+                 import them from precomputed blocks"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats heights as ranges, e.g. `1-3, 5`.
+fn format_heights(heights: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &height in heights {
+        match ranges.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(height) => *end = height,
+            _ => ranges.push((height, height)),
+        }
+    }
+    if ranges.is_empty() {
+        return "-".to_owned();
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
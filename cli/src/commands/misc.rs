@@ -1,6 +1,14 @@
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use ledger::proofs::provers::BlockProver;
 use libp2p_identity::PeerId;
 use node::account::AccountSecretKey;
 use node::p2p::identity::SecretKey;
+use node::snark::{BlockVerifier, TransactionVerifier};
+use openmina_node_native::block_producer::{serve_signer, LocalSigner};
 
 #[derive(Debug, clap::Args)]
 pub struct Misc {
@@ -13,6 +21,7 @@ impl Misc {
         match self.command {
             MiscCommand::P2PKeyPair(command) => command.run(),
             MiscCommand::MinaKeyPair(command) => command.run(),
+            MiscCommand::BlockProducerSigner(command) => command.run(),
         }
     }
 }
@@ -21,6 +30,8 @@ impl Misc {
 pub enum MiscCommand {
     P2PKeyPair(P2PKeyPair),
     MinaKeyPair(MinaKeyPair),
+    /// Block producer signer for nodes started with `--producer-signer`.
+    BlockProducerSigner(BlockProducerSigner),
 }
 
 #[derive(Debug, Clone, clap::Args)]
@@ -60,3 +71,52 @@ impl MinaKeyPair {
         Ok(())
     }
 }
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct BlockProducerSigner {
+    /// Block producer key file.
+    ///
+    /// MINA_PRIVKEY_PASS must be set to decrypt the keyfile if it is password-protected
+    #[arg(long, env)]
+    producer_key: PathBuf,
+
+    /// Password used to decrypt the producer key file.
+    #[arg(env = "MINA_PRIVKEY_PASS", default_value = "")]
+    producer_key_password: String,
+
+    /// Address to listen on for requests from the node.
+    ///
+    /// Should only be reachable by the node.
+    #[arg(long, default_value = "127.0.0.1:3087")]
+    listen: SocketAddr,
+
+    /// Secret shared with the node, passed to it with
+    /// `--producer-signer-secret`. At least 16 bytes long.
+    #[arg(long, env = "OPENMINA_SIGNER_SECRET")]
+    secret: String,
+}
+
+impl BlockProducerSigner {
+    pub fn run(self) -> anyhow::Result<()> {
+        let key =
+            AccountSecretKey::from_encrypted_file(&self.producer_key, &self.producer_key_password)
+                .context("Failed to decrypt secret key file")?;
+
+        openmina_core::thread::spawn(|| {
+            node::core::info!(node::core::log::system_time(); summary = "loading provers index");
+            BlockProver::make(
+                Some(BlockVerifier::make()),
+                Some(TransactionVerifier::make()),
+            );
+            node::core::info!(node::core::log::system_time(); summary = "loaded provers index");
+        });
+
+        let listener = TcpListener::bind(self.listen)
+            .with_context(|| format!("Failed to listen on {}", self.listen))?;
+        println!("public key: {}", key.public_key());
+        println!("listening on {}", self.listen);
+
+        serve_signer(listener, Arc::new(LocalSigner::new(key, None)), self.secret)?;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+pub mod archive;
 pub mod build_info;
 pub mod misc;
 pub mod node;
@@ -36,6 +37,8 @@ pub enum Command {
     /// Miscilaneous utilities.
     Misc(misc::Misc),
     Replay(replay::Replay),
+    /// Archive utilities, talking to a running node.
+    Archive(archive::Archive),
     BuildInfo(build_info::Command),
 }
 
@@ -46,6 +49,7 @@ impl Command {
             Self::Node(v) => v.run(),
             Self::Misc(v) => v.run(),
             Self::Replay(v) => v.run(),
+            Self::Archive(v) => v.run(),
             Self::BuildInfo(v) => v.run(),
         }
     }
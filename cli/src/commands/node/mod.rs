@@ -1,9 +1,10 @@
-use std::{fs::File, path::PathBuf, sync::Arc};
+use std::{fs::File, net::SocketAddr, num::NonZeroUsize, path::PathBuf, sync::Arc};
 
 use anyhow::Context;
-use ledger::proofs::provers::BlockProver;
+use ledger::{proofs::provers::BlockProver, scan_state::currency::Fee};
 use node::{
     account::AccountSecretKey,
+    block_producer::{TransactionSelectionConfig, TransactionSelectionPolicyKind},
     snark::{BlockVerifier, TransactionVerifier},
     transition_frontier::genesis::GenesisConfig,
 };
@@ -14,10 +15,23 @@ use reqwest::Url;
 use node::core::log::inner::Level;
 use node::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
 use node::p2p::identity::SecretKey;
+use node::p2p::P2pAccessRule;
 use node::service::Recorder;
 use node::SnarkerStrategy;
 
-use openmina_node_native::{archive::config::ArchiveStorageOptions, tracing, NodeBuilder};
+use openmina_node_native::{
+    archive::{
+        aws::AwsPrecomputedSink,
+        config::{ArchiveSinkBackpressure, ArchiveSinkConfig},
+        gcp::GcpPrecomputedSink,
+        local::LocalPrecomputedSink,
+        postgres::PostgresSink,
+        rpc::ArchiverProcessSink,
+        sink::ArchiveSinks,
+    },
+    http_server::HttpAdminConfig,
+    tracing, NodeBuilder,
+};
 
 /// Openmina node
 #[derive(Debug, clap::Args)]
@@ -52,10 +66,36 @@ pub struct Node {
     #[arg(long, short, env, default_value = "3000")]
     pub port: u16,
 
+    /// Token that clients must send as `Authorization: Bearer <TOKEN>` to
+    /// use the http endpoints that manage the node. Without it, they are
+    /// disabled.
+    #[arg(long, env = "OPENMINA_HTTP_ADMIN_TOKEN", hide_env_values = true)]
+    pub http_admin_token: Option<String>,
+
+    /// Accept requests to the endpoints that manage the node from other
+    /// hosts than localhost. They still require the admin token.
+    #[arg(long, env, requires = "http_admin_token")]
+    pub http_admin_remote: bool,
+
+    /// Enable the GraphQL mutations that manage the keystore of the node
+    /// and sign with its unlocked accounts. Requires the admin token.
+    #[arg(long, env, requires = "http_admin_token")]
+    pub keystore_api: bool,
+
     /// LibP2P port to listen on
     #[arg(long, env, default_value = "8302")]
     pub libp2p_port: u16,
 
+    /// Also accept and make LibP2P connections over QUIC, on UDP port
+    /// with the same number as the LibP2P port
+    #[arg(long, env)]
+    pub libp2p_quic: bool,
+
+    /// Open the LibP2P port on the router using NAT-PMP/PCP or UPnP, so
+    /// that the node can be reached from behind NAT
+    #[arg(long, env)]
+    pub libp2p_nat_port_mapping: bool,
+
     /// Verbosity level (options: trace, debug, info, warn, error)
     #[arg(long, short, env, default_value = "info")]
     pub verbosity: Level,
@@ -107,12 +147,29 @@ pub struct Node {
     #[arg(long, env, default_value = "seq", requires = "snarker")]
     pub snarker_strategy: SnarkerStrategy,
 
+    /// Number of snark workers, proving jobs in parallel
+    #[arg(long, env, default_value = "1", requires = "snarker")]
+    pub snarker_workers: NonZeroUsize,
+
     /// Enable block producer with this key file
     ///
     /// MINA_PRIVKEY_PASS must be set to decrypt the keyfile if it is password-protected
     #[arg(long, env, group = "producer")]
     pub producer_key: Option<PathBuf>,
 
+    /// Enable block producer with the key held by the signer process
+    /// listening on this address (see `openmina misc block-producer-signer`).
+    ///
+    /// Vrf evaluation and block proving are done by the signer, so the
+    /// key is never loaded into this node.
+    #[arg(long, env, group = "producer", requires = "producer_signer_secret")]
+    pub producer_signer: Option<SocketAddr>,
+
+    /// Secret shared with the signer process, authenticating requests to
+    /// it and its responses. At least 16 bytes long.
+    #[arg(long, env = "OPENMINA_SIGNER_SECRET", requires = "producer_signer")]
+    pub producer_signer_secret: Option<String>,
+
     /// Password used to decrypt the producer key file.
     #[arg(env = "MINA_PRIVKEY_PASS", default_value = "")]
     pub producer_key_password: String,
@@ -126,6 +183,38 @@ pub struct Node {
     #[arg(long, requires = "producer")]
     pub coinbase_receiver: Option<AccountPublicKey>,
 
+    /// Also produce blocks for this key file. Can be repeated.
+    ///
+    /// Coinbase rewards for its blocks can be sent to another address
+    /// with `<PATH>:<COINBASE_RECEIVER>`. Key files are decrypted with
+    /// MINA_PRIVKEY_PASS as well.
+    #[arg(long, requires = "producer")]
+    pub additional_producer_key: Vec<AdditionalProducerKey>,
+
+    /// How transactions from the pool are picked for produced blocks:
+    /// `fee-per-weight`, `fair-per-sender` or `priority-list`.
+    #[arg(long, env, default_value = "fee-per-weight", requires = "producer")]
+    pub transaction_selection: TransactionSelectionPolicyKind,
+
+    /// Include transactions from this sender before any others, when
+    /// `--transaction-selection priority-list` is used. Can be repeated,
+    /// earlier senders go first.
+    #[arg(long, requires = "producer")]
+    pub priority_sender: Vec<AccountPublicKey>,
+
+    /// Don't include transactions from this sender. Can be repeated.
+    #[arg(long, requires = "producer")]
+    pub exclude_sender: Vec<AccountPublicKey>,
+
+    /// Don't include transactions with lower fee, in nanomina.
+    #[arg(long, env, requires = "producer")]
+    pub min_transaction_fee: Option<u64>,
+
+    /// Number of transaction slots in produced blocks, which only zkApp
+    /// commands can take.
+    #[arg(long, env, default_value_t = 0, requires = "producer")]
+    pub zkapp_reserved_slots: usize,
+
     #[arg(long, default_value = "none", env)]
     pub record: String,
 
@@ -133,6 +222,32 @@ pub struct Node {
     #[arg(long)]
     pub no_peers_discovery: bool,
 
+    /// Only allow peers with this peer id, or from this ip address range
+    /// (e.g. `10.0.0.0/8`). Can be repeated.
+    #[arg(long)]
+    pub allow_peer: Vec<P2pAccessRule>,
+
+    /// Never allow peers with this peer id, or from this ip address range
+    /// (e.g. `10.0.0.0/8`). Can be repeated.
+    #[arg(long)]
+    pub deny_peer: Vec<P2pAccessRule>,
+
+    /// Only connect to the initial peers and the peers from `--allow-peer`,
+    /// and don't advertise our address nor our peers to anyone.
+    ///
+    /// Meant for block producers running behind sentry nodes.
+    /// Implies `--no-peers-discovery`.
+    #[arg(long, env = "OPENMINA_P2P_ISOLATE")]
+    pub isolate: bool,
+
+    /// Do not keep the root snarked ledger, the epoch ledgers and the
+    /// transition frontier on disk.
+    ///
+    /// By default they are stored in `$OPENMINA_HOME/ledgers`, so that a
+    /// restarted node only needs to sync the blocks produced while it was down.
+    #[arg(long, env)]
+    pub no_ledger_persistence: bool,
+
     /// Config JSON file to load at startup.
     // TODO: make this argument required.
     #[arg(short = 'c', long, env)]
@@ -172,6 +287,26 @@ pub struct Node {
     #[arg(long, env)]
     pub archive_aws_storage: bool,
 
+    /// Enable PostgreSQL archive storage, using the Mina archive schema.
+    ///
+    /// This requires the following environment variables to be set:
+    /// - OPENMINA_ARCHIVE_POSTGRES_URL
+    #[arg(long, env)]
+    pub archive_postgres_storage: bool,
+
+    /// How many times delivery of a block to an archive storage is retried.
+    #[arg(long, env, default_value = "5")]
+    pub archive_retries: u8,
+
+    /// Maximum number of blocks waiting to be delivered to each archive storage.
+    #[arg(long, env, default_value = "64")]
+    pub archive_queue_size: usize,
+
+    /// Drop blocks for an archive storage whose queue is full, instead of
+    /// waiting for it to catch up.
+    #[arg(long, env)]
+    pub archive_drop_when_full: bool,
+
     #[arg(long, env)]
     pub rng_seed: Option<String>,
 }
@@ -283,6 +418,9 @@ impl Node {
         }
 
         node_builder.p2p_libp2p_port(self.libp2p_port);
+        self.libp2p_quic.then(|| node_builder.p2p_libp2p_quic());
+        self.libp2p_nat_port_mapping
+            .then(|| node_builder.p2p_nat_port_mapping());
 
         node_builder.external_addrs(
             self.libp2p_external_ip
@@ -294,6 +432,9 @@ impl Node {
         self.seed.then(|| node_builder.p2p_seed_node());
         self.no_peers_discovery
             .then(|| node_builder.p2p_no_discovery());
+        node_builder.p2p_allow_peers(self.allow_peer);
+        node_builder.p2p_deny_peers(self.deny_peer);
+        self.isolate.then(|| node_builder.p2p_isolate());
 
         node_builder.initial_peers(self.peers);
         if let Some(path) = self.peer_list_file {
@@ -309,70 +450,118 @@ impl Node {
             .block_verifier_index(block_verifier_index.clone())
             .work_verifier_index(work_verifier_index.clone());
 
-        if let Some(producer_key_path) = self.producer_key {
-            let password = &self.producer_key_password;
+        // Keys held by a remote signer are proven by the signer.
+        if self.producer_key.is_some() || !self.additional_producer_key.is_empty() {
             openmina_core::thread::spawn(|| {
                 node::core::info!(node::core::log::system_time(); summary = "loading provers index");
                 BlockProver::make(Some(block_verifier_index), Some(work_verifier_index));
                 node::core::info!(node::core::log::system_time(); summary = "loaded provers index");
             });
+        }
+
+        let is_producer = self.producer_key.is_some() || self.producer_signer.is_some();
+        let password = &self.producer_key_password;
+        if let Some(producer_key_path) = self.producer_key {
             node_builder.block_producer_from_file(producer_key_path, password, None)?;
+        } else if let (Some(addr), Some(secret)) =
+            (self.producer_signer, self.producer_signer_secret)
+        {
+            node_builder.block_producer_from_remote_signer(addr, secret)?;
+        }
 
-            if let Some(pub_key) = self.coinbase_receiver {
-                node_builder
-                    .custom_coinbase_receiver(pub_key.into())
-                    .unwrap();
-            }
+        if let Some(pub_key) = self.coinbase_receiver {
+            node_builder
+                .custom_coinbase_receiver(pub_key.into())
+                .unwrap();
         }
 
-        let archive_storage_options = ArchiveStorageOptions::from_iter(
-            [
-                (
-                    self.archive_local_storage,
-                    ArchiveStorageOptions::LOCAL_PRECOMPUTED_STORAGE,
-                ),
-                (
-                    self.archive_archiver_process,
-                    ArchiveStorageOptions::ARCHIVER_PROCESS,
-                ),
-                (
-                    self.archive_gcp_storage,
-                    ArchiveStorageOptions::GCP_PRECOMPUTED_STORAGE,
-                ),
-                (
-                    self.archive_aws_storage,
-                    ArchiveStorageOptions::AWS_PRECOMPUTED_STORAGE,
-                ),
-            ]
-            .iter()
-            .filter(|(enabled, _)| *enabled)
-            .map(|(_, option)| option.clone()),
-        );
+        for key in self.additional_producer_key {
+            node_builder.additional_block_producer_from_file(
+                &key.path,
+                password,
+                key.coinbase_receiver.map(Into::into),
+            )?;
+        }
+
+        if is_producer {
+            node_builder.block_producer_transaction_selection(TransactionSelectionConfig {
+                policy: self.transaction_selection,
+                priority_senders: self.priority_sender.into_iter().map(Into::into).collect(),
+                min_fee: self
+                    .min_transaction_fee
+                    .map(|fee| (&Fee::from_u64(fee)).into()),
+                excluded_senders: self.exclude_sender.into_iter().map(Into::into).collect(),
+                zkapp_reserved: self.zkapp_reserved_slots,
+            })?;
+        }
+
+        let archive_sink_config = ArchiveSinkConfig {
+            retries: self.archive_retries,
+            queue_size: self.archive_queue_size,
+            backpressure: if self.archive_drop_when_full {
+                ArchiveSinkBackpressure::Drop
+            } else {
+                ArchiveSinkBackpressure::Block
+            },
+            ..Default::default()
+        };
+        let mut archive_sinks = ArchiveSinks::default();
+        if self.archive_local_storage {
+            archive_sinks.add(
+                LocalPrecomputedSink::from_env(&work_dir),
+                archive_sink_config.clone(),
+            );
+        }
+        if self.archive_archiver_process {
+            archive_sinks.add(
+                ArchiverProcessSink::from_env()?,
+                archive_sink_config.clone(),
+            );
+        }
+        if self.archive_gcp_storage {
+            archive_sinks.add(GcpPrecomputedSink::from_env()?, archive_sink_config.clone());
+        }
+        if self.archive_aws_storage {
+            archive_sinks.add(AwsPrecomputedSink::from_env()?, archive_sink_config.clone());
+        }
+        if self.archive_postgres_storage {
+            archive_sinks.add(PostgresSink::from_env()?, archive_sink_config);
+        }
 
-        if archive_storage_options.is_enabled() {
+        if !archive_sinks.is_empty() {
             node::core::info!(
                 summary = "Archive mode enabled",
-                local_storage = archive_storage_options.uses_local_precomputed_storage(),
-                archiver_process = archive_storage_options.uses_archiver_process(),
-                gcp_storage = archive_storage_options.uses_gcp_precomputed_storage(),
-                aws_storage = archive_storage_options.uses_aws_precomputed_storage(),
+                sinks = archive_sinks.names().join(", "),
             );
 
-            archive_storage_options
-                .validate_env_vars()
-                .map_err(|e| anyhow::anyhow!(e))?;
+            node_builder.archive(archive_sinks, work_dir.clone());
+        }
 
-            node_builder.archive(archive_storage_options, work_dir.clone());
+        if !self.no_ledger_persistence {
+            node_builder.ledger_persistence(PathBuf::from(&work_dir).join("ledgers"));
         }
 
+        node_builder.p2p_trust_persistence(&work_dir)?;
+        node_builder.p2p_address_book_persistence(&work_dir)?;
+
         if let Some(sec_key) = self.run_snarker {
-            node_builder.snarker(sec_key, self.snarker_fee, self.snarker_strategy);
+            node_builder.snarker(
+                sec_key,
+                self.snarker_fee,
+                self.snarker_strategy,
+                self.snarker_workers.get(),
+            );
         }
 
         openmina_core::set_work_dir(work_dir.clone().into());
 
+        let http_admin = HttpAdminConfig {
+            token: self.http_admin_token,
+            allow_remote: self.http_admin_remote,
+            keystore: self.keystore_api,
+        };
         node_builder
-            .http_server(self.port)
+            .http_server(self.port, http_admin)
             .gather_stats()
             .record(match self.record.trim() {
                 "none" => Recorder::None,
@@ -393,3 +582,32 @@ impl Node {
         Ok(())
     }
 }
+
+/// Additional block producer key file, optionally followed by the
+/// coinbase receiver for its blocks: `<PATH>[:<COINBASE_RECEIVER>]`.
+#[derive(Debug, Clone)]
+pub struct AdditionalProducerKey {
+    pub path: PathBuf,
+    pub coinbase_receiver: Option<AccountPublicKey>,
+}
+
+impl std::str::FromStr for AdditionalProducerKey {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Path itself may contain `:`, so only treat the suffix as the
+        // coinbase receiver if it is a valid public key.
+        if let Some((path, receiver)) = s.rsplit_once(':') {
+            if let Ok(coinbase_receiver) = receiver.parse() {
+                return Ok(Self {
+                    path: path.into(),
+                    coinbase_receiver: Some(coinbase_receiver),
+                });
+            }
+        }
+        Ok(Self {
+            path: s.into(),
+            coinbase_receiver: None,
+        })
+    }
+}
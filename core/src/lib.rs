@@ -50,12 +50,16 @@ mod work_dir {
         HOME_DIR.get().expect("Work dir is not set").clone()
     }
 
+    pub fn try_get_work_dir() -> Option<PathBuf> {
+        HOME_DIR.get().cloned()
+    }
+
     pub fn get_debug_dir() -> PathBuf {
         get_work_dir().join("debug")
     }
 }
 
-pub use work_dir::{get_debug_dir, get_work_dir, set_work_dir};
+pub use work_dir::{get_debug_dir, get_work_dir, set_work_dir, try_get_work_dir};
 
 use rand::prelude::*;
 #[inline(always)]
@@ -50,7 +50,8 @@ export interface BlockProductionWonSlotTimes {
 export type BlockProductionWonSlotsDiscardReason =
   'BestTipStakingLedgerDifferent'
   | 'BestTipGlobalSlotHigher'
-  | 'BestTipSuperior';
+  | 'BestTipSuperior'
+  | 'BlockProofFailed';
 
 export enum BlockProductionWonSlotsStatus {
   Scheduled = 'Scheduled',
@@ -61,6 +61,28 @@ impl Database<V2> {
         Self::create_with_dir(depth, None)
     }
 
+    /// Opens a database backed by an `ondisk::Database` stored in `directory`.
+    ///
+    /// See [`Database::persist`]
+    pub fn open_ondisk(depth: u8, directory: PathBuf) -> std::io::Result<Self> {
+        let db = DatabaseImpl::<V2>::open_ondisk(depth, directory)?;
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(db)),
+        })
+    }
+
+    pub fn is_ondisk(&self) -> bool {
+        self.with(|this| this.is_ondisk())
+    }
+
+    /// Writes to disk the accounts modified since the last call.
+    ///
+    /// No-op for in-memory databases.
+    pub fn persist(&self) -> std::io::Result<()> {
+        self.with(|this| this.persist())
+    }
+
     pub fn create_with_token_owners(depth: u8) -> Self {
         let mut db = Self::create_with_dir(depth, None);
         db.set_token_owners();
@@ -671,6 +693,46 @@ export function performance_now() {
         assert_eq!(root_hash_1, root_hash_3);
     }
 
+    /// Accounts written with `persist` are reloaded after reopening the database
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn test_ondisk_persist_and_reload() {
+        let directory = std::env::temp_dir().join(format!("minadb-ondisk-{}", crate::next_uuid()));
+
+        let accounts = (0..16).map(|_| Account::rand()).collect::<Vec<_>>();
+
+        let (root_hash, removed) = {
+            let mut db = Database::<V2>::open_ondisk(4, directory.clone()).unwrap();
+            assert!(db.is_ondisk());
+
+            for account in &accounts {
+                db.get_or_create_account(account.id(), account.clone())
+                    .unwrap();
+            }
+            db.persist().unwrap();
+
+            let removed = accounts[15].id();
+            db.remove_accounts(&[removed.clone()]);
+            db.persist().unwrap();
+
+            (db.merkle_root(), removed)
+        };
+
+        let mut db = Database::<V2>::open_ondisk(4, directory.clone()).unwrap();
+        assert_eq!(db.num_accounts(), 15);
+        assert_eq!(db.merkle_root(), root_hash);
+        assert!(db.location_of_account(&removed).is_none());
+
+        // In-memory copies are detached from the storage
+        assert!(!db.clone_db(directory.clone()).is_ondisk());
+        drop(db);
+
+        // Opening with a different depth fails
+        assert!(Database::<V2>::open_ondisk(5, directory.clone()).is_err());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
     // /// An empty tree produces the same hash than a tree full of empty accounts
     // #[test]
     // fn test_root_hash_legacy() {
@@ -13,9 +13,8 @@ use crate::{
     V2,
 };
 
-use super::DatabaseError;
+use super::{database_ondisk::OnDiskStorage, DatabaseError};
 
-#[derive(Clone)]
 pub struct DatabaseImpl<T: TreeVersion> {
     accounts: Vec<Option<T::Account>>,
     pub hashes_matrix: HashesMatrix,
@@ -26,6 +25,26 @@ pub struct DatabaseImpl<T: TreeVersion> {
     naccounts: usize,
     uuid: Uuid,
     directory: PathBuf,
+    /// Set when the database is backed by an `ondisk::Database`
+    ondisk: Option<OnDiskStorage>,
+}
+
+impl<T: TreeVersion> Clone for DatabaseImpl<T> {
+    /// The clone lives only in memory, it is never attached to the on-disk storage
+    fn clone(&self) -> Self {
+        Self {
+            accounts: self.accounts.clone(),
+            hashes_matrix: self.hashes_matrix.clone(),
+            id_to_addr: self.id_to_addr.clone(),
+            token_owners: self.token_owners.clone(),
+            depth: self.depth,
+            last_location: self.last_location.clone(),
+            naccounts: self.naccounts,
+            uuid: self.uuid.clone(),
+            directory: self.directory.clone(),
+            ondisk: None,
+        }
+    }
 }
 
 impl<T: TreeVersion> std::fmt::Debug for DatabaseImpl<T> {
@@ -40,6 +59,7 @@ impl<T: TreeVersion> std::fmt::Debug for DatabaseImpl<T> {
             .field("naccounts", &self.naccounts)
             .field("uuid", &self.uuid)
             .field("directory", &self.directory)
+            .field("ondisk", &self.ondisk.is_some())
             .finish()
     }
 }
@@ -62,12 +82,59 @@ impl DatabaseImpl<V2> {
             uuid: next_uuid(),
             directory: new_directory,
             hashes_matrix: self.hashes_matrix.clone(),
+            ondisk: None,
             // root_hash: RefCell::new(*self.root_hash.borrow()),
         }
     }
 
+    /// Opens the database stored at `directory`, creating it when it doesn't exist.
+    ///
+    /// The database stays backed by the on-disk storage: modified accounts are
+    /// written to disk on `Self::persist`.
+    pub fn open_ondisk(depth: u8, directory: PathBuf) -> std::io::Result<Self> {
+        let (storage, accounts) = OnDiskStorage::open(&directory, depth)?;
+
+        let mut db = Self::create_with_dir(depth, Some(directory));
+        db.set_token_owners();
+
+        let max_index = 1u64.checked_shl(depth as u32).unwrap_or(u64::MAX);
+
+        for (index, account) in accounts {
+            if index.0 >= max_index {
+                return Err(std::io::ErrorKind::InvalidData.into());
+            }
+            let addr = Address::from_index(index, depth as usize);
+            db.set(addr, Box::new(account));
+        }
+
+        db.ondisk = Some(storage);
+        Ok(db)
+    }
+
+    pub fn is_ondisk(&self) -> bool {
+        self.ondisk.is_some()
+    }
+
+    /// Writes the accounts modified since the last call to disk.
+    ///
+    /// No-op when the database is not backed by an on-disk storage.
+    pub fn persist(&mut self) -> std::io::Result<()> {
+        match self.ondisk.as_mut() {
+            Some(storage) => storage.persist(&self.accounts),
+            None => Ok(()),
+        }
+    }
+
+    fn mark_dirty(&mut self, account_index: AccountIndex) {
+        if let Some(storage) = self.ondisk.as_mut() {
+            storage.mark_dirty(account_index);
+        }
+    }
+
     fn remove(&mut self, addr: Address) -> Option<Account> {
         let index = addr.to_index();
+        self.mark_dirty(index);
+
         let index: usize = index.0 as usize;
 
         if let Some(account) = self.accounts.get_mut(index) {
@@ -98,6 +165,7 @@ impl DatabaseImpl<V2> {
 
         assert_eq!(location.to_index(), self.accounts.len());
         self.accounts.push(Some(account));
+        self.mark_dirty(location.to_index());
 
         // let root = self.root.as_mut().unwrap();
         // root.add_account_on_path(account, location.iter());
@@ -353,6 +421,7 @@ impl DatabaseImpl<V2> {
             uuid,
             directory: path,
             hashes_matrix: HashesMatrix::new(depth as usize),
+            ondisk: None,
             // root_hash: Default::default(),
         }
     }
@@ -691,6 +760,7 @@ impl BaseLedger for DatabaseImpl<V2> {
         let index = addr.to_index();
 
         self.hashes_matrix.invalidate_hashes(index);
+        self.mark_dirty(index);
 
         let index: usize = index.0 as usize;
 
@@ -0,0 +1,124 @@
+use std::{collections::BTreeSet, io::ErrorKind::InvalidData, path::Path};
+
+use mina_p2p_messages::binprot::BinProtRead;
+
+use crate::{ondisk, Account, AccountIndex};
+
+const DEPTH_KEY: &[u8] = b"depth";
+const ACCOUNT_KEY_PREFIX: u8 = b'a';
+
+fn account_key(index: AccountIndex) -> Box<[u8]> {
+    let mut key = Vec::with_capacity(9);
+    key.push(ACCOUNT_KEY_PREFIX);
+    key.extend_from_slice(&index.0.to_be_bytes());
+    key.into()
+}
+
+fn parse_account_key(key: &[u8]) -> Option<AccountIndex> {
+    match key {
+        [ACCOUNT_KEY_PREFIX, index @ ..] => {
+            let index: [u8; 8] = index.try_into().ok()?;
+            Some(AccountIndex(u64::from_be_bytes(index)))
+        }
+        _ => None,
+    }
+}
+
+/// On-disk storage of the accounts of a `DatabaseImpl`
+///
+/// Accounts are keyed by their index. Modified indexes are tracked in memory,
+/// and written to disk in a single batch on `OnDiskStorage::persist`.
+pub(super) struct OnDiskStorage {
+    db: ondisk::Database,
+    /// Indexes of the accounts modified since the last `persist`
+    dirty: BTreeSet<AccountIndex>,
+}
+
+impl OnDiskStorage {
+    /// Opens (or creates) the storage at `directory`, and returns the accounts
+    /// it contains.
+    ///
+    /// Returns an error when the storage was created with a different depth.
+    pub fn open(
+        directory: &Path,
+        depth: u8,
+    ) -> std::io::Result<(Self, Vec<(AccountIndex, Account)>)> {
+        let (mut db, report) = ondisk::Database::create_with_recovery(directory)?;
+
+        if !report.corrupted_entries.is_empty() {
+            // Entries were lost in the middle of the file, not only an interrupted write
+            openmina_core::error!(
+                message = "Corrupted entries in on-disk ledger, skipped and quarantined",
+                directory = directory.display().to_string(),
+                report = format!("{report:?}")
+            );
+        } else if !report.is_clean() {
+            openmina_core::warn!(
+                message = "Recovered corrupted on-disk ledger",
+                directory = directory.display().to_string(),
+                report = format!("{report:?}")
+            );
+        }
+
+        match db.get(DEPTH_KEY)? {
+            Some(stored) if stored[..] != [depth] => {
+                return Err(std::io::Error::new(
+                    InvalidData,
+                    format!("Ledger depth mismatch: expected {depth}, stored {stored:?}"),
+                ));
+            }
+            Some(_) => {}
+            None => db.set(DEPTH_KEY.into(), [depth].into())?,
+        }
+
+        let mut accounts = db
+            .to_alist()?
+            .into_iter()
+            .filter_map(|(key, value)| Some((parse_account_key(&key)?, value)))
+            .map(|(index, value)| {
+                let account = Account::binprot_read(&mut &value[..])
+                    .map_err(|e| std::io::Error::new(InvalidData, format!("{e:?}")))?;
+                Ok((index, account))
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        accounts.sort_by_key(|(index, _)| *index);
+
+        let storage = Self {
+            db,
+            dirty: BTreeSet::new(),
+        };
+
+        Ok((storage, accounts))
+    }
+
+    pub fn mark_dirty(&mut self, index: AccountIndex) {
+        self.dirty.insert(index);
+    }
+
+    /// Writes the modified accounts to disk
+    pub fn persist(&mut self, accounts: &[Option<Account>]) -> std::io::Result<()> {
+        if self.dirty.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch = ondisk::Batch::new();
+
+        for index in self.dirty.iter().copied() {
+            let account = usize::try_from(index.0)
+                .ok()
+                .and_then(|index| accounts.get(index))
+                .and_then(Option::as_ref);
+
+            match account {
+                Some(account) => batch.set(account_key(index), account.serialize().into()),
+                None => batch.remove(account_key(index)),
+            }
+        }
+
+        self.db.run_batch(&mut batch)?;
+        self.dirty.clear();
+
+        Ok(())
+    }
+}
@@ -2,5 +2,6 @@
 
 mod database;
 mod database_impl;
+mod database_ondisk;
 
 pub use database::*;
@@ -1,7 +1,7 @@
 use std::{
     collections::HashMap,
     fs::{File, OpenOptions},
-    io::{BufReader, BufWriter, Seek, SeekFrom, Write},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
 };
 
@@ -28,12 +28,104 @@ const BUFFER_DEFAULT_CAPACITY: usize = 4096;
 const DATABASE_VERSION: u64 = 1;
 const DATABASE_VERSION_NBYTES: usize = 8;
 
+const DATABASE_FILENAME: &str = "db";
+const TEMPORARY_FILENAME: &str = "db_tmp";
+const QUARANTINE_FILENAME: &str = "db_quarantine";
+
+/// Configuration of the automatic compaction
+///
+/// Compaction runs after a write once the ratio of garbage bytes (overwritten
+/// or removed entries) to the file size reaches `garbage_ratio`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompactionConfig {
+    /// Ratio of garbage bytes to file size, between 0 and 1
+    pub garbage_ratio: f64,
+    /// Files smaller than this are never compacted automatically
+    pub min_file_size: u64,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            garbage_ratio: 0.5,
+            min_file_size: 16 * 1024 * 1024, // 16 MB
+        }
+    }
+}
+
+/// Result of a compaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// File size before compaction
+    pub size_before: u64,
+    /// File size after compaction
+    pub size_after: u64,
+    /// Number of entries copied to the new file
+    pub live_entries: usize,
+}
+
+impl CompactionReport {
+    /// Number of bytes reclaimed by the compaction
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
+
+/// Entry which failed its checksum verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorruptedEntry {
+    /// Offset of the entry header in the file
+    pub offset: u64,
+    /// Length of the entry on disk, including its header
+    pub length: u64,
+}
+
+/// Result of scanning a database file
+///
+/// Returned by `Database::verify` and `Database::create_with_recovery`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Number of entries with a valid checksum
+    pub valid_entries: usize,
+    /// Number of keys in the database
+    pub live_entries: usize,
+    /// Entries whose checksum didn't match, their content is lost
+    pub corrupted_entries: Vec<CorruptedEntry>,
+    /// Number of bytes at the end of the file which don't form a complete entry,
+    /// usually left by an interrupted write, or which follow a corrupted tombstone
+    pub torn_tail_bytes: u64,
+    /// File where the corrupted entries were copied to, when recovering
+    pub quarantine_file: Option<PathBuf>,
+}
+
+impl IntegrityReport {
+    /// Returns true when no corruption was found
+    pub fn is_clean(&self) -> bool {
+        self.corrupted_entries.is_empty() && self.torn_tail_bytes == 0
+    }
+}
+
+/// Location of a live entry in the file
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    /// Offset of the entry header
+    offset: Offset,
+    /// Length of the entry on disk, including its header
+    length: u64,
+}
+
 pub struct Database {
     uuid: Uuid,
-    /// Index of keys to their values offset
-    index: HashMap<Key, Offset>,
+    /// Index of keys to their entries
+    index: HashMap<Key, IndexEntry>,
     /// Points to end of file
     current_file_offset: Offset,
+    /// Number of bytes in the file which are not referenced by `index`
+    garbage_bytes: u64,
+    /// When `None`, compaction only runs on `Database::compact`
+    compaction: Option<CompactionConfig>,
+    /// Report of the last automatic compaction
+    last_compaction: Option<CompactionReport>,
     file: BufWriter<LockedFile>,
     /// Read buffer
     buffer: Vec<u8>,
@@ -234,8 +326,6 @@ fn read_exact_at(file: &mut File, buffer: &mut [u8], offset: Offset) -> std::io:
 
 #[cfg(not(unix))]
 fn read_exact_at(file: &mut File, buffer: &mut [u8], offset: Offset) -> std::io::Result<()> {
-    use std::io::Read;
-
     file.seek(SeekFrom::Start(offset))?;
     file.read_exact(buffer)
 }
@@ -245,6 +335,272 @@ enum CreateMode {
     Temporary,
 }
 
+/// How `scan_entries` handles corrupted data
+enum ScanMode<'a> {
+    /// Return an error on the first corrupted entry
+    Strict,
+    /// Record corrupted entries in the report
+    Report,
+    /// Record corrupted entries in the report, and copy them to `quarantine`
+    Repair { quarantine: &'a Path },
+}
+
+/// Result of `scan_entries`
+struct Scan {
+    index: HashMap<Key, IndexEntry>,
+    report: IntegrityReport,
+    /// Offset following the last complete entry
+    end_offset: Offset,
+}
+
+/// Result of `read_entry`
+enum EntryRead {
+    /// The entry doesn't fit in the rest of the file. `is_removed` is
+    /// read from its header, when the header is complete
+    Incomplete { is_removed: bool },
+    /// The entry fits in the file, but its checksum doesn't match
+    Corrupted { length: u64, is_removed: bool },
+    /// The entry is valid, its key and value are in the buffer
+    Valid { header: EntryHeader, length: u64 },
+}
+
+/// `BufReader` keeping track of its offset in the file, so it can move
+/// around without discarding its buffer
+struct ScanReader<'a, R> {
+    reader: &'a mut BufReader<R>,
+    position: Offset,
+}
+
+impl EntryRead {
+    /// Whether the header of a bad entry claims it's a tombstone
+    fn is_removed(&self) -> bool {
+        match self {
+            Self::Incomplete { is_removed } | Self::Corrupted { is_removed, .. } => *is_removed,
+            Self::Valid { .. } => false,
+        }
+    }
+}
+
+impl<R: Read + Seek> ScanReader<'_, R> {
+    fn seek_to(&mut self, offset: Offset) -> std::io::Result<()> {
+        if offset != self.position {
+            let delta = offset as i64 - self.position as i64;
+            self.reader.seek_relative(delta)?;
+            self.position = offset;
+        }
+        Ok(())
+    }
+
+    fn read_exact(&mut self, buffer: &mut [u8]) -> std::io::Result<()> {
+        self.reader.read_exact(buffer)?;
+        self.position += buffer.len() as u64;
+        Ok(())
+    }
+
+    /// Reads the entry at `offset` into `bytes`
+    fn read_entry(
+        &mut self,
+        offset: Offset,
+        eof: Offset,
+        bytes: &mut Vec<u8>,
+    ) -> std::io::Result<EntryRead> {
+        let remaining = eof - offset;
+
+        if remaining < EntryHeader::NBYTES as u64 {
+            return Ok(EntryRead::Incomplete { is_removed: false });
+        }
+
+        self.seek_to(offset)?;
+        ensure_buffer_length(bytes, EntryHeader::NBYTES);
+        self.read_exact(&mut bytes[..EntryHeader::NBYTES])?;
+        let header = EntryHeader::read(bytes)?;
+
+        // The entry must fit in the rest of the file, otherwise it was not
+        // completely written, or its header is corrupted
+        let entry_length = match header.entry_length() {
+            Ok(length) if length <= remaining - EntryHeader::NBYTES as u64 => length as usize,
+            _ => {
+                return Ok(EntryRead::Incomplete {
+                    is_removed: header.is_removed,
+                })
+            }
+        };
+
+        let length = EntryHeader::NBYTES + entry_length;
+        let key_length = header.key_length as usize;
+
+        ensure_buffer_length(bytes, length);
+        self.read_exact(&mut bytes[EntryHeader::NBYTES..length])?;
+
+        let (key_bytes, value_bytes) = bytes[EntryHeader::NBYTES..length].split_at(key_length);
+        let length = length as u64;
+
+        if header.verify_checksum(key_bytes, value_bytes).is_err() {
+            return Ok(EntryRead::Corrupted {
+                length,
+                is_removed: header.is_removed,
+            });
+        }
+
+        Ok(EntryRead::Valid { header, length })
+    }
+
+    /// Searches, byte by byte, the first valid entry starting after `offset`
+    fn find_next_entry(
+        &mut self,
+        offset: Offset,
+        eof: Offset,
+        bytes: &mut Vec<u8>,
+    ) -> std::io::Result<Option<Offset>> {
+        for candidate in offset + 1..eof {
+            if let EntryRead::Valid { .. } = self.read_entry(candidate, eof, bytes)? {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Reads all entries of a database file, from its beginning to `eof`
+///
+/// When an entry is corrupted (a bad checksum or a length not fitting in the file),
+/// the scan resumes at the next valid entry, and the bytes in between are reported
+/// as one corrupted entry. Only when no valid entry follows, the rest of the file
+/// is reported as a torn tail.
+///
+/// A corrupted entry whose header is a tombstone is not skipped: the key it removed
+/// would come back with its previous value. The log is cut at that entry instead,
+/// and everything from it to the end of the file is reported as a torn tail (and
+/// copied to the quarantine file when repairing).
+fn scan_entries<R: Read + Seek>(
+    reader: &mut BufReader<R>,
+    eof: Offset,
+    mode: ScanMode,
+) -> std::io::Result<Scan> {
+    let mut current_offset = 0;
+    let mut bytes = vec![0; BUFFER_DEFAULT_CAPACITY];
+
+    // Check if the database is the same version
+    {
+        reader.read_exact(&mut bytes[..DATABASE_VERSION_NBYTES])?;
+        let database_version = read_u64(&bytes)?;
+        if database_version != DATABASE_VERSION {
+            return Err(std::io::Error::new(Other, "Incompatible database"));
+        }
+        current_offset += DATABASE_VERSION_NBYTES as u64;
+    }
+
+    let mut reader = ScanReader {
+        reader,
+        position: current_offset,
+    };
+    let mut index = HashMap::with_capacity(256);
+    let mut report = IntegrityReport::default();
+    let mut quarantine: Option<File> = None;
+
+    while current_offset < eof {
+        let header_offset = current_offset;
+
+        let bad_entry = match reader.read_entry(header_offset, eof, &mut bytes)? {
+            EntryRead::Valid { header, length } => {
+                let key_length = header.key_length as usize;
+                let key_bytes = &bytes[EntryHeader::NBYTES..][..key_length];
+                let key = decompress(key_bytes, header.key_is_compressed)?;
+
+                if header.is_removed {
+                    index.remove(&key);
+                } else {
+                    let offset = header_offset;
+                    index.insert(key, IndexEntry { offset, length });
+                }
+
+                report.valid_entries += 1;
+                current_offset += length;
+                continue;
+            }
+            bad_entry => bad_entry,
+        };
+
+        if let ScanMode::Strict = mode {
+            return Err(match bad_entry {
+                EntryRead::Incomplete { .. } => UnexpectedEof.into(),
+                _ => InvalidData.into(),
+            });
+        }
+
+        if bad_entry.is_removed() {
+            if let ScanMode::Repair { quarantine: path } = mode {
+                copy_to_quarantine(&mut reader, &mut quarantine, path, header_offset, eof)?;
+                report.quarantine_file = Some(path.to_path_buf());
+            }
+            report.torn_tail_bytes = eof - header_offset;
+            break;
+        }
+
+        let next_offset = match (
+            reader.find_next_entry(header_offset, eof, &mut bytes)?,
+            bad_entry,
+        ) {
+            (Some(next_offset), _) => next_offset,
+            // The last entry is complete, only its content is corrupted
+            (None, EntryRead::Corrupted { length, .. }) if header_offset + length == eof => eof,
+            (None, _) => {
+                report.torn_tail_bytes = eof - header_offset;
+                break;
+            }
+        };
+
+        let length = next_offset - header_offset;
+
+        if let ScanMode::Repair { quarantine: path } = mode {
+            copy_to_quarantine(
+                &mut reader,
+                &mut quarantine,
+                path,
+                header_offset,
+                next_offset,
+            )?;
+            report.quarantine_file = Some(path.to_path_buf());
+        }
+
+        report.corrupted_entries.push(CorruptedEntry {
+            offset: header_offset,
+            length,
+        });
+        current_offset = next_offset;
+    }
+
+    if let Some(quarantine) = quarantine {
+        quarantine.sync_all()?;
+    }
+
+    report.live_entries = index.len();
+
+    Ok(Scan {
+        index,
+        report,
+        end_offset: current_offset,
+    })
+}
+
+/// Appends the bytes from `start` to `end` to the quarantine file, creating it if needed
+fn copy_to_quarantine<R: Read + Seek>(
+    reader: &mut ScanReader<'_, R>,
+    quarantine: &mut Option<File>,
+    path: &Path,
+    start: Offset,
+    end: Offset,
+) -> std::io::Result<()> {
+    let file = match quarantine.as_mut() {
+        Some(file) => file,
+        None => quarantine.insert(OpenOptions::new().append(true).create(true).open(path)?),
+    };
+    reader.seek_to(start)?;
+    std::io::copy(&mut reader.reader.by_ref().take(end - start), file)?;
+    reader.position = end;
+    Ok(())
+}
+
 impl Database {
     /// Creates a new instance of the database at the specified directory.
     /// If the directory contains an existing database, its content will be loaded.
@@ -275,15 +631,15 @@ impl Database {
         let directory = directory.as_ref();
 
         let filename = directory.join(match mode {
-            CreateMode::Regular => "db",
-            CreateMode::Temporary => "db_tmp",
+            CreateMode::Regular => DATABASE_FILENAME,
+            CreateMode::Temporary => TEMPORARY_FILENAME,
         });
 
         if filename.try_exists()? {
             if let CreateMode::Temporary = mode {
                 std::fs::remove_file(&filename)?;
             } else {
-                return Self::reload(filename);
+                return Self::reload(filename, ScanMode::Strict).map(|(db, _)| db);
             }
         }
 
@@ -306,16 +662,56 @@ impl Database {
             uuid: next_uuid(),
             index: HashMap::with_capacity(128),
             current_file_offset: DATABASE_VERSION_NBYTES as u64,
+            garbage_bytes: 0,
+            compaction: Some(CompactionConfig::default()),
+            last_compaction: None,
             file: BufWriter::with_capacity(4 * 1024 * 1024, file), // 4 MB
             buffer: Vec::with_capacity(BUFFER_DEFAULT_CAPACITY),
             filename,
         })
     }
 
-    /// Reload the database at the specified path
-    fn reload(filename: PathBuf) -> std::io::Result<Self> {
-        use std::io::Read;
+    /// Creates or opens the database at the specified directory, recovering
+    /// from corruption instead of returning an error.
+    ///
+    /// An incomplete entry at the end of the file, left by an interrupted write,
+    /// is truncated. Entries failing their checksum are skipped and copied to a
+    /// quarantine file in `directory`, the database is then compacted to remove them.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The path where the database will be created or opened.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(Self, IntegrityReport)>` - Returns an instance of the database
+    ///   and a report of what was lost, otherwise returns an error.
+    ///
+    /// # Errors
+    ///
+    /// Same as `Database::create`, except for the corruption cases.
+    pub fn create_with_recovery(
+        directory: impl AsRef<Path>,
+    ) -> std::io::Result<(Self, IntegrityReport)> {
+        let directory = directory.as_ref();
+        let filename = directory.join(DATABASE_FILENAME);
+
+        if filename.try_exists()? {
+            let quarantine = directory.join(QUARANTINE_FILENAME);
+            return Self::reload(
+                filename,
+                ScanMode::Repair {
+                    quarantine: &quarantine,
+                },
+            );
+        }
+
+        let db = Self::create_impl(directory, CreateMode::Regular)?;
+        Ok((db, IntegrityReport::default()))
+    }
 
+    /// Reload the database at the specified path
+    fn reload(filename: PathBuf, mode: ScanMode) -> std::io::Result<(Self, IntegrityReport)> {
         let mut file = LockedFile::try_open_exclusively(
             &filename,
             OpenOptions::new()
@@ -325,67 +721,78 @@ impl Database {
                 .create_new(false),
         )?;
 
-        let mut current_offset = 0;
         let eof = file.seek(SeekFrom::End(0))?;
 
         file.seek(SeekFrom::Start(0))?;
 
         let mut reader = BufReader::with_capacity(4 * 1024 * 1024, file); // 4 MB
-        let mut bytes = vec![0; BUFFER_DEFAULT_CAPACITY];
 
-        // Check if the database is the same version
-        {
-            reader.read_exact(&mut bytes[..DATABASE_VERSION_NBYTES])?;
-            let database_version = read_u64(&bytes)?;
-            if database_version != DATABASE_VERSION {
-                return Err(std::io::Error::new(Other, "Incompatible database"));
+        let Scan {
+            index,
+            report,
+            end_offset,
+        } = scan_entries(&mut reader, eof, mode)?;
+
+        let file = reader.into_inner();
+
+        // Only when recovering: drop the incomplete entry (or the corrupted
+        // tombstone and what follows it) at the end of the file.
+        // Corrupted entries in the middle of the file are skipped by the scan, and
+        // removed below by the compaction.
+        if end_offset != eof {
+            if end_offset + report.torn_tail_bytes != eof {
+                return Err(std::io::Error::new(
+                    InvalidData,
+                    format!("Refusing to truncate {filename:?} at offset {end_offset}"),
+                ));
             }
-            current_offset += DATABASE_VERSION_NBYTES as u64;
+            file.set_len(end_offset)?;
+            file.sync_all()?;
         }
 
-        let mut index = HashMap::with_capacity(256);
-
-        while current_offset < eof {
-            let header_offset = current_offset;
-
-            ensure_buffer_length(&mut bytes, EntryHeader::NBYTES);
-            reader.read_exact(&mut bytes[..EntryHeader::NBYTES])?;
+        let live_bytes: u64 = index.values().map(|entry| entry.length).sum();
+        let garbage_bytes = end_offset - DATABASE_VERSION_NBYTES as u64 - live_bytes;
 
-            let header = EntryHeader::read(&bytes)?;
-            let entry_length = header.entry_length()? as usize;
-            let key_length = header.key_length as usize;
-
-            ensure_buffer_length(&mut bytes, entry_length);
-            reader.read_exact(&mut bytes[..entry_length])?;
+        let mut db = Self {
+            uuid: next_uuid(),
+            index,
+            current_file_offset: end_offset,
+            garbage_bytes,
+            compaction: Some(CompactionConfig::default()),
+            last_compaction: None,
+            file: BufWriter::with_capacity(4 * 1024 * 1024, file), // 4 MB
+            buffer: Vec::with_capacity(BUFFER_DEFAULT_CAPACITY),
+            filename,
+        };
 
-            ensure_buffer_length(&mut bytes, entry_length);
-            let (key_bytes, value_bytes) = bytes[..entry_length].split_at(key_length);
+        // Remove corrupted entries from the file, so it can be reloaded
+        if !report.corrupted_entries.is_empty() {
+            db.compact()?;
+        }
 
-            header.verify_checksum(key_bytes, value_bytes)?;
+        Ok((db, report))
+    }
 
-            let key = decompress(key_bytes, header.key_is_compressed)?;
+    /// Scans the whole database file and verifies the checksum of every entry.
+    ///
+    /// The database is not modified.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<IntegrityReport>` - Returns a report of the corrupted entries,
+    ///   otherwise returns an error.
+    pub fn verify(&mut self) -> std::io::Result<IntegrityReport> {
+        // Entries might still be in the write buffer
+        self.file.flush()?;
 
-            if header.is_removed {
-                index.remove(&key);
-            } else {
-                index.insert(key, header_offset);
-            }
+        let mut file = File::open(&self.filename)?;
+        let eof = file.seek(SeekFrom::End(0))?;
 
-            current_offset += (EntryHeader::NBYTES + entry_length) as u64;
-        }
+        file.seek(SeekFrom::Start(0))?;
 
-        if eof != current_offset {
-            return Err(UnexpectedEof.into());
-        }
+        let mut reader = BufReader::with_capacity(4 * 1024 * 1024, file); // 4 MB
 
-        Ok(Self {
-            uuid: next_uuid(),
-            index,
-            current_file_offset: eof,
-            file: BufWriter::with_capacity(4 * 1024 * 1024, reader.into_inner()), // 4 MB
-            buffer: Vec::with_capacity(BUFFER_DEFAULT_CAPACITY),
-            filename,
-        })
+        scan_entries(&mut reader, eof, ScanMode::Report).map(|scan| scan.report)
     }
 
     /// Retrieves the UUID of the current database instance.
@@ -435,8 +842,8 @@ impl Database {
     pub fn get(&mut self, key: &[u8]) -> std::io::Result<Option<Value>> {
         // Note: `&mut self` is required for `File::seek`
 
-        let header_offset = match self.index.get(key).copied() {
-            Some(header_offset) => header_offset,
+        let header_offset = match self.index.get(key) {
+            Some(entry) => entry.offset,
             None => return Ok(None),
         };
 
@@ -474,15 +881,38 @@ impl Database {
         self.current_file_offset += buffer_len;
 
         // Update index
-        if is_removed {
-            self.index.remove(&key);
+        let previous = if is_removed {
+            // Removed entries are never read back, they are garbage right away
+            self.garbage_bytes += buffer_len;
+            self.index.remove(&key)
         } else {
-            self.index.insert(key, header_offset);
+            let entry = IndexEntry {
+                offset: header_offset,
+                length: buffer_len,
+            };
+            self.index.insert(key, entry)
+        };
+
+        if let Some(previous) = previous {
+            self.garbage_bytes += previous.length;
         }
 
         Ok(())
     }
 
+    /// Appends an entry, already serialized by another database, as-is
+    fn append_raw_entry(&mut self, key: Key, entry_bytes: &[u8]) -> std::io::Result<()> {
+        let offset = self.current_file_offset;
+        let length = entry_bytes.len() as u64;
+
+        self.file.write_all(entry_bytes)?;
+        self.current_file_offset += length;
+
+        self.index.insert(key, IndexEntry { offset, length });
+
+        Ok(())
+    }
+
     /// Adds or updates an entry (key-value pair) in the database.
     ///
     /// # Arguments
@@ -496,7 +926,7 @@ impl Database {
     pub fn set(&mut self, key: Key, value: Value) -> std::io::Result<()> {
         self.set_impl(key, Some(value))?;
         self.flush()?;
-        Ok(())
+        self.maybe_compact()
     }
 
     /// Processes multiple entries (key-value pairs) to set and keys to remove in
@@ -524,8 +954,7 @@ impl Database {
         }
 
         self.flush()?;
-
-        Ok(())
+        self.maybe_compact()
     }
 
     /// Fetches a batch of values for the given keys.
@@ -608,7 +1037,8 @@ impl Database {
     ///   otherwise returns an error.
     pub fn remove(&mut self, key: Key) -> std::io::Result<()> {
         self.remove_impl(key)?;
-        self.flush()
+        self.flush()?;
+        self.maybe_compact()
     }
 
     /// Retrieves all entries (key-value pairs) from the database.
@@ -651,7 +1081,8 @@ impl Database {
             }
         }
 
-        self.flush()
+        self.flush()?;
+        self.maybe_compact()
     }
 
     /// Triggers garbage collection for the database, cleaning up obsolete
@@ -662,27 +1093,105 @@ impl Database {
     /// * `Result<()>` - Returns () if garbage collection is successful,
     ///   otherwise returns an error.
     pub fn gc(&mut self) -> std::io::Result<()> {
+        self.compact()?;
+        Ok(())
+    }
+
+    /// Rewrites the live entries into a new file, and atomically replaces the
+    /// current file with it.
+    ///
+    /// The new file is locked before being swapped in, and the old one stays
+    /// locked until it is removed, so the database is never left unlocked.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CompactionReport>` - Returns the file sizes before and after
+    ///   the compaction, otherwise returns an error.
+    pub fn compact(&mut self) -> std::io::Result<CompactionReport> {
+        // Entries might still be in the write buffer
+        self.file.flush()?;
+
         let directory = self.filename.parent().unwrap();
         let mut new_db = Self::create_impl(directory, CreateMode::Temporary)?;
 
-        let keys: Vec<Key> = self.index.keys().cloned().collect();
+        let mut entries: Vec<(Key, IndexEntry)> = self
+            .index
+            .iter()
+            .map(|(key, entry)| (key.clone(), *entry))
+            .collect();
 
-        for key in keys {
-            let value = self.get(&key)?;
-            new_db.set_impl(key, value)?;
+        // Read the old file sequentially
+        entries.sort_by_key(|(_, entry)| entry.offset);
+
+        for (key, entry) in entries {
+            let entry_bytes = self.read_value(entry.offset, entry.length as usize)?;
+            new_db.append_raw_entry(key, entry_bytes)?;
         }
 
         new_db.flush()?;
 
         exchange_file_atomically(&self.filename, &new_db.filename)?;
 
+        let report = CompactionReport {
+            size_before: self.current_file_offset,
+            size_after: new_db.current_file_offset,
+            live_entries: new_db.index.len(),
+        };
+
         new_db.filename.clone_from(&self.filename);
         new_db.uuid.clone_from(&self.uuid);
+        new_db.compaction = self.compaction;
+        new_db.last_compaction = self.last_compaction;
 
         *self = new_db;
 
+        Ok(report)
+    }
+
+    /// Runs a compaction when the garbage ratio reaches the configured threshold
+    fn maybe_compact(&mut self) -> std::io::Result<()> {
+        let Some(config) = self.compaction else {
+            return Ok(());
+        };
+
+        if self.current_file_offset < config.min_file_size
+            || self.garbage_ratio() < config.garbage_ratio
+        {
+            return Ok(());
+        }
+
+        self.last_compaction = Some(self.compact()?);
+
         Ok(())
     }
+
+    /// Sets the automatic compaction configuration.
+    ///
+    /// `None` disables automatic compaction, `Database::compact` can still be
+    /// called manually.
+    pub fn set_compaction_config(&mut self, config: Option<CompactionConfig>) {
+        self.compaction = config;
+    }
+
+    /// Size of the database file, in bytes
+    pub fn file_size(&self) -> u64 {
+        self.current_file_offset
+    }
+
+    /// Number of bytes occupied by overwritten or removed entries
+    pub fn garbage_bytes(&self) -> u64 {
+        self.garbage_bytes
+    }
+
+    /// Ratio of garbage bytes to the file size, between 0 and 1
+    pub fn garbage_ratio(&self) -> f64 {
+        self.garbage_bytes as f64 / self.current_file_offset as f64
+    }
+
+    /// Report of the last compaction triggered automatically, if any
+    pub fn last_compaction(&self) -> Option<&CompactionReport> {
+        self.last_compaction.as_ref()
+    }
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -939,6 +1448,311 @@ mod tests {
         assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("b"));
     }
 
+    #[test]
+    fn test_compact() {
+        let db_dir = TempDir::new();
+
+        let mut rng = rand::thread_rng();
+        let nkeys: usize = rng.gen_range(1000..2000);
+        let sorted = make_random_key_values(nkeys);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set_compaction_config(None);
+        assert_eq!(db.garbage_bytes(), 0);
+
+        db.set_batch(sorted.clone(), []).unwrap();
+        assert_eq!(db.garbage_bytes(), 0);
+
+        (10..50).for_each(|index| {
+            db.remove(sorted[index].0.clone()).unwrap();
+        });
+        (50..100).for_each(|index| {
+            db.set(sorted[index].0.clone(), value("new")).unwrap();
+        });
+
+        let size_before = db.file_size();
+        let garbage_bytes = db.garbage_bytes();
+        assert!(garbage_bytes > 0);
+
+        let alist1 = sorted_vec(db.to_alist().unwrap());
+
+        let report = db.compact().unwrap();
+        assert_eq!(report.size_before, size_before);
+        assert_eq!(report.size_after, db.file_size());
+        assert_eq!(report.reclaimed_bytes(), garbage_bytes);
+        assert_eq!(report.live_entries, nkeys - 40);
+        assert_eq!(db.garbage_bytes(), 0);
+
+        let alist2 = sorted_vec(db.to_alist().unwrap());
+        assert_eq!(alist1, alist2);
+
+        db.set(key("a"), value("b")).unwrap();
+        drop(db);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(db.garbage_bytes(), 0);
+        assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("b"));
+        assert_eq!(db.to_alist().unwrap().len(), alist1.len() + 1);
+    }
+
+    #[test]
+    fn test_auto_compaction() {
+        let db_dir = TempDir::new();
+
+        let sorted = make_random_key_values(100);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set_compaction_config(Some(CompactionConfig {
+            garbage_ratio: 0.5,
+            min_file_size: 0,
+        }));
+
+        db.set_batch(sorted.clone(), []).unwrap();
+        let initial_size = db.file_size();
+        assert!(db.last_compaction().is_none());
+
+        for _ in 0..10 {
+            db.set_batch(sorted.clone(), []).unwrap();
+            assert!(db.garbage_ratio() < 0.5);
+        }
+
+        let report = db.last_compaction().copied().unwrap();
+        assert_eq!(report.live_entries, sorted.len());
+        assert!(report.reclaimed_bytes() > 0);
+        assert!(db.file_size() < initial_size * 2);
+
+        drop(db);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(sorted, sorted_vec(db.to_alist().unwrap()));
+    }
+
+    /// Creates a database with the keys "a", "b" and "c", and closes it
+    fn make_abc_database(directory: &Path) -> HashMap<Key, IndexEntry> {
+        let mut db = Database::create(directory).unwrap();
+
+        db.set(key("a"), value("aaaaaaaa")).unwrap();
+        db.set(key("b"), value("bbbbbbbb")).unwrap();
+        db.set(key("c"), value("cccccccc")).unwrap();
+
+        db.index.clone()
+    }
+
+    fn flip_bit(filename: &Path, offset: u64) {
+        let mut bytes = std::fs::read(filename).unwrap();
+        bytes[offset as usize] ^= 1;
+        std::fs::write(filename, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_recovery_torn_tail() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(DATABASE_FILENAME);
+
+        let index = make_abc_database(db_dir.as_path());
+        let c = index[&key("c")];
+
+        // Interrupted write of "c"
+        let file = OpenOptions::new().write(true).open(&filename).unwrap();
+        file.set_len(c.offset + c.length - 3).unwrap();
+        drop(file);
+
+        let err = Database::create(db_dir.as_path()).err().unwrap();
+        assert_eq!(err.kind(), UnexpectedEof);
+
+        let (mut db, report) = Database::create_with_recovery(db_dir.as_path()).unwrap();
+        assert_eq!(report.torn_tail_bytes, c.length - 3);
+        assert_eq!(report.valid_entries, 2);
+        assert_eq!(report.live_entries, 2);
+        assert!(report.corrupted_entries.is_empty());
+        assert!(report.quarantine_file.is_none());
+
+        assert_eq!(db.file_size(), c.offset);
+        assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("aaaaaaaa"));
+        assert_eq!(db.get(&key("b")).unwrap().unwrap(), value("bbbbbbbb"));
+        assert!(db.get(&key("c")).unwrap().is_none());
+
+        db.set(key("c"), value("cccccccc")).unwrap();
+        drop(db);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert!(db.verify().unwrap().is_clean());
+        assert_eq!(db.get(&key("c")).unwrap().unwrap(), value("cccccccc"));
+    }
+
+    #[test]
+    fn test_recovery_torn_header() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(DATABASE_FILENAME);
+
+        make_abc_database(db_dir.as_path());
+        let size = std::fs::metadata(&filename).unwrap().len();
+
+        // Only a few bytes of the next header were written
+        let mut file = OpenOptions::new().append(true).open(&filename).unwrap();
+        file.write_all(&[1, 0, 0]).unwrap();
+        drop(file);
+
+        assert!(Database::create(db_dir.as_path()).is_err());
+
+        let (mut db, report) = Database::create_with_recovery(db_dir.as_path()).unwrap();
+        assert_eq!(report.torn_tail_bytes, 3);
+        assert_eq!(report.live_entries, 3);
+        assert_eq!(db.file_size(), size);
+        assert_eq!(std::fs::metadata(&filename).unwrap().len(), size);
+        assert!(db.verify().unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_recovery_bit_flip() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(DATABASE_FILENAME);
+
+        let index = make_abc_database(db_dir.as_path());
+        let b = index[&key("b")];
+
+        // Corrupt the value of "b"
+        flip_bit(&filename, b.offset + b.length - 1);
+
+        let err = Database::create(db_dir.as_path()).err().unwrap();
+        assert_eq!(err.kind(), InvalidData);
+
+        let (mut db, report) = Database::create_with_recovery(db_dir.as_path()).unwrap();
+        assert_eq!(report.torn_tail_bytes, 0);
+        assert_eq!(report.valid_entries, 2);
+        assert_eq!(
+            report.corrupted_entries,
+            vec![CorruptedEntry {
+                offset: b.offset,
+                length: b.length,
+            }]
+        );
+
+        let quarantine = report.quarantine_file.unwrap();
+        assert_eq!(quarantine, db_dir.as_path().join(QUARANTINE_FILENAME));
+        assert_eq!(std::fs::metadata(&quarantine).unwrap().len(), b.length);
+
+        assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("aaaaaaaa"));
+        assert!(db.get(&key("b")).unwrap().is_none());
+        assert_eq!(db.get(&key("c")).unwrap().unwrap(), value("cccccccc"));
+        drop(db);
+
+        // The corrupted entry was removed from the file
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert!(db.verify().unwrap().is_clean());
+        assert_eq!(db.to_alist().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_recovery_corrupted_length() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(DATABASE_FILENAME);
+
+        let index = make_abc_database(db_dir.as_path());
+        let b = index[&key("b")];
+        let size = std::fs::metadata(&filename).unwrap().len();
+
+        // The value length of "b" no longer fits in the file
+        flip_bit(&filename, b.offset + 11);
+
+        let (mut db, report) = Database::create_with_recovery(db_dir.as_path()).unwrap();
+        assert_eq!(report.torn_tail_bytes, 0);
+        assert_eq!(report.valid_entries, 2);
+        assert_eq!(
+            report.corrupted_entries,
+            vec![CorruptedEntry {
+                offset: b.offset,
+                length: b.length,
+            }]
+        );
+
+        let quarantine = report.quarantine_file.unwrap();
+        assert_eq!(std::fs::metadata(&quarantine).unwrap().len(), b.length);
+
+        // Entries following the corrupted one were not truncated
+        assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("aaaaaaaa"));
+        assert!(db.get(&key("b")).unwrap().is_none());
+        assert_eq!(db.get(&key("c")).unwrap().unwrap(), value("cccccccc"));
+        assert_eq!(db.file_size(), size - b.length);
+        drop(db);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert!(db.verify().unwrap().is_clean());
+        assert_eq!(db.get(&key("c")).unwrap().unwrap(), value("cccccccc"));
+    }
+
+    #[test]
+    fn test_recovery_corrupted_tombstone() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(DATABASE_FILENAME);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set(key("a"), value("aaaaaaaa")).unwrap();
+        db.set(key("b"), value("bbbbbbbb")).unwrap();
+        let b = db.index[&key("b")];
+        db.remove(key("b")).unwrap();
+        db.set(key("c"), value("cccccccc")).unwrap();
+        let c = db.index[&key("c")];
+        drop(db);
+
+        let size = std::fs::metadata(&filename).unwrap().len();
+        let tombstone = b.offset + b.length;
+
+        // Corrupt the key of the tombstone, its header is intact
+        flip_bit(&filename, c.offset - 1);
+
+        let err = Database::create(db_dir.as_path()).err().unwrap();
+        assert_eq!(err.kind(), InvalidData);
+
+        // Skipping the tombstone would mix the old "b" with later writes: the
+        // log is cut there, back to its state before the removal
+        let (mut db, report) = Database::create_with_recovery(db_dir.as_path()).unwrap();
+        assert_eq!(report.torn_tail_bytes, size - tombstone);
+        assert_eq!(report.valid_entries, 2);
+        assert!(report.corrupted_entries.is_empty());
+
+        let quarantine = report.quarantine_file.unwrap();
+        assert_eq!(
+            std::fs::metadata(&quarantine).unwrap().len(),
+            size - tombstone
+        );
+
+        assert_eq!(db.file_size(), tombstone);
+        assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("aaaaaaaa"));
+        assert_eq!(db.get(&key("b")).unwrap().unwrap(), value("bbbbbbbb"));
+        assert!(db.get(&key("c")).unwrap().is_none());
+        drop(db);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert!(db.verify().unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_verify() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(DATABASE_FILENAME);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set(key("a"), value("aaaaaaaa")).unwrap();
+        db.set(key("b"), value("bbbbbbbb")).unwrap();
+        db.remove(key("a")).unwrap();
+
+        let report = db.verify().unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.valid_entries, 3);
+        assert_eq!(report.live_entries, 1);
+
+        let b = db.index[&key("b")];
+        flip_bit(&filename, b.offset + EntryHeader::NBYTES as u64);
+
+        let report = db.verify().unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(report.valid_entries, 2);
+        assert_eq!(report.corrupted_entries.len(), 1);
+        assert_eq!(report.corrupted_entries[0].offset, b.offset);
+        assert!(report.quarantine_file.is_none());
+    }
+
     #[test]
     fn test_to_alist() {
         let db_dir = TempDir::new();
@@ -33,6 +33,23 @@
 //! - `KEY`: The key data
 //! - `VALUE`: The value data
 //!
+//! ## Compaction
+//!
+//! Overwritten and removed entries stay in the file until it is compacted: live entries
+//! are copied to a new file, which then atomically replaces the current one.
+//! Compaction runs automatically once the ratio of garbage bytes reaches the threshold
+//! configured with `Database::set_compaction_config`, or manually with `Database::compact`.
+//!
+//! ## Recovery
+//!
+//! `Database::create` refuses to open a file containing an incomplete or corrupted entry.
+//! `Database::create_with_recovery` truncates an incomplete entry at the end of the file
+//! (an interrupted write), skips corrupted entries (a failed CRC32 check or a length not
+//! fitting in the file) up to the next valid entry, copies them to a quarantine file, and
+//! returns an `IntegrityReport` of what was lost. Only the end of the file is truncated:
+//! valid entries following a corrupted one are kept.
+//! `Database::verify` scans the whole file without modifying it.
+//!
 //! ## Example Usage
 //!
 //! Create an instance of MyDatabase:
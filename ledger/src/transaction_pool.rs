@@ -1451,6 +1451,14 @@ impl IndexedPool {
         txns
     }
 
+    /// Pending transactions of `sender` in nonce order, whatever their fee
+    fn list_sender_transactions(&self, sender: &AccountId) -> Vec<ValidCommandWithHash> {
+        self.all_by_sender
+            .get(sender)
+            .map(|(queue, _)| queue.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     // TODO(adonagy): Is it neede to remove txs from the pool directly here? If the produced block is injected
     // a BestTip update action will be dispatched and the pool can reorganize there
     /// Returns a sequence of commands in the pool in descending fee order
@@ -1633,6 +1641,10 @@ impl TransactionPool {
         self.pool.list_includable_transactions(limit)
     }
 
+    pub fn list_sender_transactions(&self, sender: &AccountId) -> Vec<ValidCommandWithHash> {
+        self.pool.list_sender_transactions(sender)
+    }
+
     pub fn get_accounts_to_revalidate_on_new_best_tip(&self) -> BTreeSet<AccountId> {
         self.pool.all_by_sender.keys().cloned().collect()
     }
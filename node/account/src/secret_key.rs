@@ -107,18 +107,21 @@ impl AccountSecretKey {
             .map_err(|err| EncryptionError::Other(err.to_string()))
     }
 
+    /// Writes the encrypted key to a new file, readable only by its owner.
+    /// Fails if the file already exists.
     pub fn to_encrypted_file(
         &self,
         path: impl AsRef<Path>,
         password: &str,
     ) -> Result<(), EncryptionError> {
-        if path.as_ref().exists() {
-            panic!("File {} already exists", path.as_ref().display())
-        }
-
-        let f = fs::File::create(path)?;
         let encrypted = Self::try_encrypt(&self.to_bytes(), password)?;
 
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let f = options.open(path)?;
+
         serde_json::to_writer(f, &encrypted)?;
         Ok(())
     }
@@ -244,6 +247,15 @@ mod tests {
             decrypted.public_key(),
             "Encrypted and decrypted public keys do not match"
         );
+
+        // never overwrites an existing key
+        assert!(new_key.to_encrypted_file(&tmp_path, password).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&tmp_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
     }
 
     #[test]
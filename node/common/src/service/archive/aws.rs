@@ -1,6 +1,9 @@
 use std::env;
 
-use super::Error;
+use super::{
+    sink::{ArchiveBlock, ArchiveSink, ArchiveSinkFuture},
+    Error,
+};
 
 pub(crate) struct ArchiveAWSClient {
     client: aws_sdk_s3::Client,
@@ -37,3 +40,53 @@ impl ArchiveAWSClient {
         Ok(())
     }
 }
+
+/// Uploads precomputed blocks to an S3 bucket.
+#[derive(Default)]
+pub struct AwsPrecomputedSink {
+    client: Option<ArchiveAWSClient>,
+}
+
+impl AwsPrecomputedSink {
+    pub fn from_env() -> Result<Self, Error> {
+        for var in [
+            "AWS_ACCESS_KEY_ID",
+            "AWS_SECRET_ACCESS_KEY",
+            "AWS_SESSION_TOKEN",
+            "AWS_DEFAULT_REGION",
+            "OPENMINA_AWS_BUCKET_NAME",
+        ] {
+            if env::var(var).is_err() {
+                return Err(Error::EnvironmentVariableNotSet(var.to_string()));
+            }
+        }
+        Ok(Self::default())
+    }
+}
+
+impl ArchiveSink for AwsPrecomputedSink {
+    fn name(&self) -> &str {
+        "aws"
+    }
+
+    fn init(&mut self) -> ArchiveSinkFuture<'_> {
+        Box::pin(async {
+            if self.client.is_none() {
+                self.client = Some(ArchiveAWSClient::new().await?);
+            }
+            Ok(())
+        })
+    }
+
+    fn send<'a>(&'a mut self, block: &'a ArchiveBlock) -> ArchiveSinkFuture<'a> {
+        Box::pin(async move {
+            let client = self
+                .client
+                .as_ref()
+                .ok_or_else(|| Error::UploadError("AWS client not initialized".to_string()))?;
+            client
+                .upload_block(&block.precomputed_key(), block.precomputed_json()?)
+                .await
+        })
+    }
+}
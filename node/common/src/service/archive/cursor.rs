@@ -0,0 +1,74 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use node::transition_frontier::archive::archive_service::ArchiveSinkCursor;
+
+/// Sink delivery cursors, persisted as json so that blocks missed while the
+/// node was down can be found and backfilled after a restart.
+pub struct ArchiveCursorFile {
+    path: PathBuf,
+}
+
+impl ArchiveCursorFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Cursors keyed by sink name. Missing file means nothing was delivered yet.
+    pub fn load(&self) -> io::Result<BTreeMap<String, ArchiveSinkCursor>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes to a temporary file first, so that a crash mid-write doesn't
+    /// leave a truncated cursor behind.
+    pub fn save<'a>(
+        &self,
+        cursors: impl IntoIterator<Item = (&'a str, &'a ArchiveSinkCursor)>,
+    ) -> io::Result<()> {
+        let cursors = cursors.into_iter().collect::<BTreeMap<_, _>>();
+        let bytes = serde_json::to_vec(&cursors)?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let dir =
+            std::env::temp_dir().join(format!("openmina-archive-cursor-{}", std::process::id()));
+        let file = ArchiveCursorFile::new(dir.join("archive-cursor.json"));
+        assert!(file.load().unwrap().is_empty());
+
+        let mut cursor = ArchiveSinkCursor::default();
+        cursor.delivered(5);
+        cursor.delivered(8);
+        file.save([("postgres", &cursor)]).unwrap();
+
+        let loaded = file.load().unwrap();
+        assert_eq!(loaded.get("postgres"), Some(&cursor));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
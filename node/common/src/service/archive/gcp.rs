@@ -2,7 +2,10 @@ use gcs::http::objects::upload as gcs_upload;
 use google_cloud_auth::credentials::CredentialsFile as GcpCredentialsFile;
 use google_cloud_storage as gcs;
 
-use super::Error;
+use super::{
+    sink::{ArchiveBlock, ArchiveSink, ArchiveSinkFuture},
+    Error,
+};
 use std::env;
 
 pub(crate) struct ArchiveGCPClient {
@@ -52,3 +55,47 @@ impl ArchiveGCPClient {
         Ok(())
     }
 }
+
+/// Uploads precomputed blocks to a GCP bucket.
+#[derive(Default)]
+pub struct GcpPrecomputedSink {
+    client: Option<ArchiveGCPClient>,
+}
+
+impl GcpPrecomputedSink {
+    pub fn from_env() -> Result<Self, Error> {
+        for var in ["GCP_CREDENTIALS_JSON", "GCP_BUCKET_NAME"] {
+            if env::var(var).is_err() {
+                return Err(Error::EnvironmentVariableNotSet(var.to_string()));
+            }
+        }
+        Ok(Self::default())
+    }
+}
+
+impl ArchiveSink for GcpPrecomputedSink {
+    fn name(&self) -> &str {
+        "gcp"
+    }
+
+    fn init(&mut self) -> ArchiveSinkFuture<'_> {
+        Box::pin(async {
+            if self.client.is_none() {
+                self.client = Some(ArchiveGCPClient::new().await?);
+            }
+            Ok(())
+        })
+    }
+
+    fn send<'a>(&'a mut self, block: &'a ArchiveBlock) -> ArchiveSinkFuture<'a> {
+        Box::pin(async move {
+            let client = self
+                .client
+                .as_ref()
+                .ok_or_else(|| Error::UploadError("GCP client not initialized".to_string()))?;
+            client
+                .upload_block(&block.precomputed_key(), block.precomputed_json()?)
+                .await
+        })
+    }
+}
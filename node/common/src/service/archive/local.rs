@@ -0,0 +1,62 @@
+use std::{
+    env,
+    fs::{create_dir_all, File},
+    io::Write,
+    path::Path,
+};
+
+use super::{
+    sink::{ArchiveBlock, ArchiveSink, ArchiveSinkFuture},
+    Error,
+};
+
+/// Writes precomputed blocks into a local directory.
+pub struct LocalPrecomputedSink {
+    path: String,
+}
+
+impl LocalPrecomputedSink {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+
+    /// Uses `OPENMINA_LOCAL_PRECOMPUTED_STORAGE_PATH`, falling back to
+    /// `archive-precomputed` inside `work_dir`.
+    pub fn from_env(work_dir: &str) -> Self {
+        let path = env::var("OPENMINA_LOCAL_PRECOMPUTED_STORAGE_PATH")
+            .unwrap_or_else(|_| format!("{}/archive-precomputed", work_dir));
+        Self::new(path)
+    }
+}
+
+impl ArchiveSink for LocalPrecomputedSink {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn send<'a>(&'a mut self, block: &'a ArchiveBlock) -> ArchiveSinkFuture<'a> {
+        Box::pin(async move {
+            write_to_local_storage(
+                &self.path,
+                &block.precomputed_key(),
+                block.precomputed_json()?,
+            )
+        })
+    }
+}
+
+fn write_to_local_storage(base_path: &str, key: &str, data: &[u8]) -> Result<(), Error> {
+    let path = Path::new(base_path).join(key);
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)
+            .map_err(|e| Error::UploadError(format!("Directory creation failed: {}", e)))?;
+    }
+
+    let mut file = File::create(&path)
+        .map_err(|e| Error::UploadError(format!("File creation failed: {}", e)))?;
+
+    file.write_all(data)
+        .map_err(|e| Error::UploadError(format!("File write failed: {}", e)))?;
+
+    Ok(())
+}
@@ -1,12 +1,12 @@
-use mina_p2p_messages::v2::{self};
 use node::core::{channels::mpsc, thread};
 use node::ledger::write::BlockApplyResult;
-use std::env;
-use std::io::Write;
-
-use mina_p2p_messages::v2::PrecomputedBlock;
-use openmina_core::NetworkConfig;
-use std::net::SocketAddr;
+use node::transition_frontier::archive::archive_service::{ArchiveSinkCursor, ArchiveSinkStatus};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, PoisonError,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 
 use super::NodeService;
 
@@ -15,15 +15,28 @@ pub mod aws;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod gcp;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod local;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod postgres;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod rpc;
 
 pub mod config;
+pub mod cursor;
+pub mod sink;
 
-use config::ArchiveStorageOptions;
+#[cfg(not(target_arch = "wasm32"))]
+use config::{ArchiveSinkBackpressure, ArchiveSinkConfig};
+use cursor::ArchiveCursorFile;
+use sink::ArchiveSinks;
+#[cfg(not(target_arch = "wasm32"))]
+use sink::{ArchiveBlock, ArchiveSink};
 
-const ARCHIVE_SEND_RETRIES: u8 = 5;
 const MAX_EVENT_COUNT: u64 = 100;
-const RETRY_INTERVAL_MS: u64 = 1000;
+
+/// How often the sink cursors are written to the cursor file, when they changed.
+#[cfg(not(target_arch = "wasm32"))]
+const CURSOR_PERSIST_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -31,243 +44,300 @@ pub enum Error {
     EnvironmentVariableNotSet(String),
     #[error("Failed to upload block to AWS: {0}")]
     UploadError(String),
+    #[error("Failed to write block to postgres: {0}")]
+    PostgresError(String),
+    #[error("Failed to convert block: {0}")]
+    ConversionError(String),
 }
 
-pub struct ArchiveService {
-    archive_sender: mpsc::UnboundedSender<BlockApplyResult>,
+impl Error {
+    /// Whether sending the same block again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Self::ConversionError(_))
+    }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-struct ArchiveServiceClients {
-    archiver_address: Option<SocketAddr>,
-    aws_client: Option<aws::ArchiveAWSClient>,
-    gcp_client: Option<gcp::ArchiveGCPClient>,
-    local_path: Option<String>,
+#[derive(Clone)]
+struct SinksStatus {
+    status: Arc<Mutex<Vec<ArchiveSinkStatus>>>,
+    cursor_file: Option<Arc<ArchiveCursorFile>>,
+    /// Set when a cursor changed since it was last persisted
+    cursor_dirty: Arc<AtomicBool>,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl ArchiveServiceClients {
-    async fn new(options: &ArchiveStorageOptions, work_dir: String) -> Result<Self, Error> {
-        let aws_client = if options.uses_aws_precomputed_storage() {
-            let client = aws::ArchiveAWSClient::new().await?;
-            Some(client)
-        } else {
-            None
-        };
-
-        let gcp_client = if options.uses_gcp_precomputed_storage() {
-            let client = gcp::ArchiveGCPClient::new().await?;
-            Some(client)
-        } else {
-            None
-        };
+impl SinksStatus {
+    fn new(names: Vec<String>, cursor_file: Option<ArchiveCursorFile>) -> Self {
+        let mut cursors = cursor_file
+            .as_ref()
+            .map(|file| {
+                file.load().unwrap_or_else(|e| {
+                    node::core::warn!(
+                        summary = "Failed to load archive cursor, starting from scratch",
+                        path = file.path().display().to_string(),
+                        error = e.to_string()
+                    );
+                    Default::default()
+                })
+            })
+            .unwrap_or_default();
 
-        let local_path = if options.uses_local_precomputed_storage() {
-            let env_path = env::var("OPENMINA_LOCAL_PRECOMPUTED_STORAGE_PATH");
-            let default = format!("{}/archive-precomputed", work_dir);
-            Some(env_path.unwrap_or(default))
-        } else {
-            None
-        };
+        let status = names
+            .into_iter()
+            .map(|name| {
+                let cursor = cursors.remove(&name).unwrap_or_default();
+                ArchiveSinkStatus::with_cursor(name, cursor)
+            })
+            .collect();
 
-        let archiver_address = if options.uses_archiver_process() {
-            let address = std::env::var("OPENMINA_ARCHIVE_ADDRESS")
-                .expect("OPENMINA_ARCHIVE_ADDRESS is not set");
-            let address = reqwest::Url::parse(&address).expect("Invalid URL");
+        Self {
+            status: Arc::new(Mutex::new(status)),
+            cursor_file: cursor_file.map(Arc::new),
+            cursor_dirty: Arc::new(AtomicBool::new(false)),
+        }
+    }
 
-            // Convert URL to SocketAddr
-            let socket_addrs = address.socket_addrs(|| None).expect("Invalid URL");
+    fn get(&self) -> Vec<ArchiveSinkStatus> {
+        self.status
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
 
-            let socket_addr = socket_addrs.first().expect("No socket address found");
+    fn update(&self, index: usize, f: impl FnOnce(&mut ArchiveSinkStatus)) {
+        let mut status = self.status.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(status) = status.get_mut(index) {
+            f(status);
+        }
+    }
 
-            Some(*socket_addr)
-        } else {
-            None
+    /// Same as [`Self::update`], for the cursor. The cursors are persisted
+    /// later, by [`Self::persist_cursors`].
+    fn update_cursor(&self, index: usize, f: impl FnOnce(&mut ArchiveSinkCursor)) {
+        let mut status = self.status.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(sink_status) = status.get_mut(index) else {
+            return;
         };
-
-        Ok(Self {
-            archiver_address,
-            aws_client,
-            gcp_client,
-            local_path,
-        })
+        f(&mut sink_status.cursor);
+        self.cursor_dirty.store(true, Ordering::Release);
     }
 
-    pub async fn send_block(&self, breadcrumb: BlockApplyResult, options: &ArchiveStorageOptions) {
-        if options.uses_archiver_process() {
-            if let Some(socket_addr) = self.archiver_address {
-                Self::handle_archiver_process(&breadcrumb, &socket_addr).await;
-            } else {
-                node::core::warn!(summary = "Archiver address not set");
-            }
+    /// Writes the cursors to the cursor file, if they changed since the last call.
+    /// The file is written after releasing the lock, deliveries aren't held back.
+    fn persist_cursors(&self) {
+        let Some(file) = self.cursor_file.as_ref() else {
+            return;
+        };
+        if !self.cursor_dirty.swap(false, Ordering::AcqRel) {
+            return;
         }
 
-        if options.requires_precomputed_block() {
-            let network_name = NetworkConfig::global().name;
-            let height = breadcrumb.block.height();
-            let state_hash = breadcrumb.block.hash();
+        let cursors = self
+            .status
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|s| (s.name.clone(), s.cursor.clone()))
+            .collect::<Vec<_>>();
+
+        let cursors = cursors.iter().map(|(name, cursor)| (name.as_str(), cursor));
+        if let Err(e) = file.save(cursors) {
+            self.cursor_dirty.store(true, Ordering::Release);
+            node::core::warn!(
+                summary = "Failed to persist archive cursor",
+                path = file.path().display().to_string(),
+                error = e.to_string()
+            );
+        }
+    }
+}
 
-            let key = format!("{network_name}-{height}-{state_hash}.json");
+pub struct ArchiveService {
+    archive_sender: mpsc::UnboundedSender<BlockApplyResult>,
+    sinks_status: SinksStatus,
+}
 
-            node::core::info!(
-                summary = "Uploading precomputed block to archive",
-                key = key.clone()
-            );
+/// Delivers blocks from the sink queue to a single sink.
+#[cfg(not(target_arch = "wasm32"))]
+struct ArchiveSinkWorker {
+    index: usize,
+    sink: Box<dyn ArchiveSink>,
+    config: ArchiveSinkConfig,
+    status: SinksStatus,
+    initialized: bool,
+}
 
-            let precomputed_block: PrecomputedBlock = match breadcrumb.try_into() {
-                Ok(block) => block,
-                Err(_) => {
-                    node::core::warn!(
-                        summary = "Failed to convert breadcrumb to precomputed block"
-                    );
-                    return;
+#[cfg(not(target_arch = "wasm32"))]
+impl ArchiveSinkWorker {
+    async fn run(mut self, mut receiver: mpsc::Receiver<Arc<ArchiveBlock>>) {
+        while let Some(block) = receiver.recv().await {
+            self.status.update(self.index, |status| {
+                status.queued = status.queued.saturating_sub(1);
+            });
+
+            match self.deliver(&block).await {
+                Ok(()) => {
+                    self.status.update(self.index, |status| {
+                        status.healthy = true;
+                        status.delivered = status.delivered.saturating_add(1);
+                    });
+                    self.status
+                        .update_cursor(self.index, |cursor| cursor.delivered(block.height()));
                 }
-            };
-
-            let data = match serde_json::to_vec(&precomputed_block) {
-                Ok(data) => data,
                 Err(e) => {
                     node::core::warn!(
-                        summary = "Failed to serialize precomputed block",
+                        summary = "Failed to deliver block to archive sink",
+                        sink = self.sink.name(),
+                        height = block.height(),
                         error = e.to_string()
                     );
-                    return;
-                }
-            };
-
-            if options.uses_local_precomputed_storage() {
-                if let Some(path) = &self.local_path {
-                    let key_clone = key.clone();
-                    match write_to_local_storage(path, &key, &data) {
-                        Ok(_) => node::core::info!(
-                            summary = "Successfully wrote precomputed block to local storage",
-                            key = key_clone
-                        ),
-                        Err(e) => node::core::warn!(
-                            summary = "Failed to write precomputed block to local storage",
-                            key = key_clone,
-                            error = e.to_string()
-                        ),
-                    }
-                } else {
-                    node::core::warn!(summary = "Local precomputed storage path not set");
-                }
-            }
-
-            if options.uses_gcp_precomputed_storage() {
-                if let Some(client) = &self.gcp_client {
-                    if let Err(e) = client.upload_block(&key, &data).await {
-                        node::core::warn!(
-                            summary = "Failed to upload precomputed block to GCP",
-                            error = e.to_string()
-                        );
-                    }
-                } else {
-                    node::core::warn!(summary = "GCP client not initialized");
-                }
-            }
-            if options.uses_aws_precomputed_storage() {
-                if let Some(client) = &self.aws_client {
-                    if let Err(e) = client.upload_block(&key, &data).await {
-                        node::core::warn!(
-                            summary = "Failed to upload precomputed block to AWS",
-                            error = e.to_string()
-                        );
-                    }
-                } else {
-                    node::core::warn!(summary = "AWS client not initialized");
+                    self.status.update(self.index, |status| {
+                        status.healthy = false;
+                        status.failed = status.failed.saturating_add(1);
+                        status.last_error = Some(e.to_string());
+                    });
+                    self.status
+                        .update_cursor(self.index, |cursor| cursor.missed(block.height()));
                 }
             }
         }
     }
 
-    async fn handle_archiver_process(breadcrumb: &BlockApplyResult, socket_addr: &SocketAddr) {
-        let mut retries = ARCHIVE_SEND_RETRIES;
-
-        let archive_transition_frontier_diff: v2::ArchiveTransitionFrontierDiff =
-            breadcrumb.clone().try_into().unwrap();
-
-        for _ in 0..ARCHIVE_SEND_RETRIES {
-            match rpc::send_diff(
-                *socket_addr,
-                v2::ArchiveRpc::SendDiff(archive_transition_frontier_diff.clone()),
-            ) {
-                Ok(result) if result.should_retry() => {
-                    node::core::warn!(summary = "Archive closed connection, retrying...");
-                    tokio::time::sleep(tokio::time::Duration::from_millis(RETRY_INTERVAL_MS)).await;
-                }
-                Ok(_) => {
-                    node::core::info!(summary = "Successfully sent diff to archive");
-                    return;
-                }
-                Err(e) => {
+    async fn deliver(&mut self, block: &ArchiveBlock) -> Result<(), Error> {
+        let mut retries = self.config.retries;
+        loop {
+            match self.try_deliver(block).await {
+                Ok(()) => return Ok(()),
+                Err(e) if retries > 0 && e.is_retryable() => {
                     node::core::warn!(
-                        summary = "Failed sending diff to archive",
+                        summary = "Failed sending block to archive sink, retrying...",
+                        sink = self.sink.name(),
                         error = e.to_string(),
                         retries = retries
                     );
-                    tokio::time::sleep(tokio::time::Duration::from_millis(RETRY_INTERVAL_MS)).await;
+                    retries -= 1;
+                    tokio::time::sleep(self.config.retry_interval).await;
                 }
+                Err(e) => return Err(e),
             }
-            retries -= 1;
         }
     }
+
+    async fn try_deliver(&mut self, block: &ArchiveBlock) -> Result<(), Error> {
+        if !self.initialized {
+            self.sink.init().await?;
+            self.initialized = true;
+        }
+        self.sink.send(block).await
+    }
 }
 
 impl ArchiveService {
-    fn new(archive_sender: mpsc::UnboundedSender<BlockApplyResult>) -> Self {
-        Self { archive_sender }
+    fn new(
+        archive_sender: mpsc::UnboundedSender<BlockApplyResult>,
+        sinks_status: SinksStatus,
+    ) -> Self {
+        Self {
+            archive_sender,
+            sinks_status,
+        }
+    }
+
+    pub fn sinks_status(&self) -> Vec<ArchiveSinkStatus> {
+        self.sinks_status.get()
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     async fn run(
         mut archive_receiver: mpsc::UnboundedReceiver<BlockApplyResult>,
-        options: ArchiveStorageOptions,
-        work_dir: String,
+        sinks: ArchiveSinks,
+        sinks_status: SinksStatus,
     ) {
-        let clients = match ArchiveServiceClients::new(&options, work_dir).await {
-            Ok(clients) => clients,
-            Err(e) => {
-                node::core::error!(
-                    summary = "Failed to initialize archive service clients",
-                    error = e.to_string()
-                );
-                return;
+        let mut queues = Vec::with_capacity(sinks.0.len());
+        for (index, (sink, config)) in sinks.0.into_iter().enumerate() {
+            let (sender, receiver) = mpsc::channel(config.queue_size.max(1));
+            queues.push((sink.name().to_owned(), sender, config.backpressure));
+
+            let worker = ArchiveSinkWorker {
+                index,
+                sink,
+                config,
+                status: sinks_status.clone(),
+                initialized: false,
+            };
+            tokio::spawn(worker.run(receiver));
+        }
+
+        let status = sinks_status.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CURSOR_PERSIST_INTERVAL);
+            loop {
+                interval.tick().await;
+                let status = status.clone();
+                let _ = tokio::task::spawn_blocking(move || status.persist_cursors()).await;
             }
-        };
+        });
 
         while let Some(breadcrumb) = archive_receiver.recv().await {
-            clients.send_block(breadcrumb, &options).await;
+            let block = Arc::new(ArchiveBlock::new(breadcrumb));
+
+            for (index, (name, sender, backpressure)) in queues.iter().enumerate() {
+                sinks_status.update(index, |status| status.queued += 1);
+
+                let queued = match backpressure {
+                    ArchiveSinkBackpressure::Block => sender.send(block.clone()).await.is_ok(),
+                    ArchiveSinkBackpressure::Drop => sender.try_send(block.clone()).is_ok(),
+                };
+                if !queued {
+                    node::core::warn!(
+                        summary = "Archive sink queue is full or closed, dropping block",
+                        sink = name,
+                        height = block.height()
+                    );
+                    sinks_status.update(index, |status| {
+                        status.queued = status.queued.saturating_sub(1);
+                        status.healthy = false;
+                        status.dropped = status.dropped.saturating_add(1);
+                        status.last_error =
+                            Some("Queue is full or closed, block dropped".to_owned());
+                    });
+                    sinks_status.update_cursor(index, |cursor| cursor.missed(block.height()));
+                }
+            }
         }
+
+        sinks_status.persist_cursors();
     }
 
     // Note: Placeholder for the wasm implementation, if we decide to include an archive mode in the future
     #[cfg(target_arch = "wasm32")]
     fn run(
         mut archive_receiver: mpsc::UnboundedReceiver<BlockApplyResult>,
-        options: ArchiveStorageOptions,
-        work_dir: String,
+        sinks: ArchiveSinks,
+        sinks_status: SinksStatus,
     ) {
         unimplemented!()
     }
 
-    pub fn start(options: ArchiveStorageOptions, work_dir: String) -> Self {
+    /// Starts delivering blocks to the `sinks`. If `cursor_file` is set,
+    /// sink cursors are restored from it and kept up to date.
+    pub fn start(sinks: ArchiveSinks, cursor_file: Option<ArchiveCursorFile>) -> Self {
         let (archive_sender, archive_receiver) = mpsc::unbounded_channel::<BlockApplyResult>();
+        let sinks_status = SinksStatus::new(sinks.names(), cursor_file);
 
         #[cfg(not(target_arch = "wasm32"))]
-        Self::start_native(archive_receiver, options, work_dir);
+        Self::start_native(archive_receiver, sinks, sinks_status.clone());
 
         #[cfg(target_arch = "wasm32")]
-        Self::start_wasm(archive_receiver, options, work_dir);
+        Self::start_wasm(archive_receiver, sinks, sinks_status.clone());
 
-        Self::new(archive_sender)
+        Self::new(archive_sender, sinks_status)
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     fn start_native(
         archive_receiver: mpsc::UnboundedReceiver<BlockApplyResult>,
-        options: ArchiveStorageOptions,
-        work_dir: String,
+        sinks: ArchiveSinks,
+        sinks_status: SinksStatus,
     ) {
         let runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
@@ -277,7 +347,7 @@ impl ArchiveService {
         thread::Builder::new()
             .name("openmina_archive".to_owned())
             .spawn(move || {
-                runtime.block_on(Self::run(archive_receiver, options, work_dir));
+                runtime.block_on(Self::run(archive_receiver, sinks, sinks_status));
             })
             .unwrap();
     }
@@ -285,13 +355,13 @@ impl ArchiveService {
     #[cfg(target_arch = "wasm32")]
     fn start_wasm(
         archive_receiver: mpsc::UnboundedReceiver<BlockApplyResult>,
-        options: ArchiveStorageOptions,
-        work_dir: String,
+        sinks: ArchiveSinks,
+        sinks_status: SinksStatus,
     ) {
         thread::Builder::new()
             .name("openmina_archive".to_owned())
             .spawn(move || {
-                Self::run(archive_receiver, options, work_dir);
+                Self::run(archive_receiver, sinks, sinks_status);
             })
             .unwrap();
     }
@@ -308,27 +378,12 @@ impl node::transition_frontier::archive::archive_service::ArchiveService for Nod
             }
         }
     }
+
+    fn archive_sinks_status(&mut self) -> Option<Vec<ArchiveSinkStatus>> {
+        self.archive.as_ref().map(ArchiveService::sinks_status)
+    }
 }
 
 // Note: Placeholder for the wasm implementation, if we decide to include an archive mode in the future
 #[cfg(target_arch = "wasm32")]
 mod rpc {}
-
-fn write_to_local_storage(base_path: &str, key: &str, data: &[u8]) -> Result<(), Error> {
-    use std::fs::{create_dir_all, File};
-    use std::path::Path;
-
-    let path = Path::new(base_path).join(key);
-    if let Some(parent) = path.parent() {
-        create_dir_all(parent)
-            .map_err(|e| Error::UploadError(format!("Directory creation failed: {}", e)))?;
-    }
-
-    let mut file = File::create(&path)
-        .map_err(|e| Error::UploadError(format!("File creation failed: {}", e)))?;
-
-    file.write_all(data)
-        .map_err(|e| Error::UploadError(format!("File write failed: {}", e)))?;
-
-    Ok(())
-}
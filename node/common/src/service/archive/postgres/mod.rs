@@ -0,0 +1,1104 @@
+//! Archive storage writing blocks into a PostgreSQL database that uses the
+//! schema of the OCaml archive node, so the tooling built around that schema
+//! keeps working.
+//!
+//! Everything belonging to a block is written in a single transaction. Blocks
+//! that are already present are skipped, so resending a block is harmless.
+
+mod zkapp;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    sync::Arc,
+};
+
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+use ledger::{
+    scan_state::transaction_logic::{
+        SingleFeeTransfer, Transaction, TransactionStatus, WithStatus,
+    },
+    staged_ledger::diff::Diff,
+    TokenId,
+};
+use mina_p2p_messages::v2::{self, NonZeroCurvePoint, TokenIdKeyHash};
+use mina_signer::CompressedPubKey;
+use openmina_core::{block::ArcBlockWithHash, constants::constraint_constants};
+use sqlx::{
+    postgres::{PgArguments, PgPoolOptions},
+    query::QueryScalar,
+    PgConnection, PgPool, Postgres,
+};
+
+use super::{
+    sink::{ArchiveBlock, ArchiveSink, ArchiveSinkFuture},
+    Error,
+};
+
+const MAX_CONNECTIONS: u32 = 4;
+
+impl From<sqlx::Error> for Error {
+    fn from(value: sqlx::Error) -> Self {
+        Error::PostgresError(value.to_string())
+    }
+}
+
+/// Writes blocks into a PostgreSQL database with the Mina archive schema.
+pub struct PostgresSink {
+    url: String,
+    client: Option<ArchivePostgresClient>,
+}
+
+impl PostgresSink {
+    pub fn new(url: String) -> Self {
+        Self { url, client: None }
+    }
+
+    pub fn from_env() -> Result<Self, Error> {
+        let url = env::var("OPENMINA_ARCHIVE_POSTGRES_URL").map_err(|_| {
+            Error::EnvironmentVariableNotSet("OPENMINA_ARCHIVE_POSTGRES_URL".to_string())
+        })?;
+        Ok(Self::new(url))
+    }
+}
+
+impl ArchiveSink for PostgresSink {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    fn init(&mut self) -> ArchiveSinkFuture<'_> {
+        Box::pin(async {
+            if self.client.is_none() {
+                self.client = Some(ArchivePostgresClient::connect(&self.url).await?);
+            }
+            Ok(())
+        })
+    }
+
+    fn send<'a>(&'a mut self, block: &'a ArchiveBlock) -> ArchiveSinkFuture<'a> {
+        Box::pin(async move {
+            let client = self
+                .client
+                .as_ref()
+                .ok_or_else(|| Error::PostgresError("Client not initialized".to_string()))?;
+            client.add_diff(block.diff()?).await
+        })
+    }
+}
+
+pub(crate) struct ArchivePostgresClient {
+    pool: PgPool,
+}
+
+impl ArchivePostgresClient {
+    pub async fn connect(url: &str) -> Result<Self, Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(MAX_CONNECTIONS)
+            .connect(url)
+            .await?;
+        Ok(Self { pool })
+    }
+
+    pub async fn add_diff(&self, diff: &v2::ArchiveTransitionFrontierDiff) -> Result<(), Error> {
+        let v2::ArchiveTransitionFrontierDiff::BreadcrumbAdded {
+            block: (block, _),
+            accounts_accessed,
+            accounts_created,
+            tokens_used,
+            ..
+        } = diff
+        else {
+            return Ok(());
+        };
+        let block = ArcBlockWithHash::try_new(Arc::new(block.clone())).map_err(invalid_data)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let Some(block_id) = insert_block(&mut tx, &block).await? else {
+            return Ok(());
+        };
+
+        insert_transactions(&mut tx, block_id, &block).await?;
+
+        for (token, owner) in tokens_used.iter() {
+            let token = TokenIdKeyHash::from(token.0.clone());
+            match owner {
+                Some(owner) => token_id_with_owner(&mut tx, &token, owner).await?,
+                None => token_id(&mut tx, &token).await?,
+            };
+        }
+
+        for (ledger_index, account) in accounts_accessed.iter() {
+            insert_account_accessed(&mut tx, block_id, ledger_index.as_u64(), account).await?;
+        }
+
+        for (account_id, creation_fee) in accounts_created.iter() {
+            let account_identifier_id = account_identifier_id(
+                &mut tx,
+                &account_id.0,
+                &TokenIdKeyHash::from(account_id.1.clone()),
+            )
+            .await?;
+            sqlx::query(
+                "INSERT INTO accounts_created (block_id, account_identifier_id, creation_fee) \
+                 VALUES ($1, $2, $3)",
+            )
+            .bind(block_id)
+            .bind(account_identifier_id)
+            .bind(creation_fee.as_u64().to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        update_chain_status(&mut tx, &block).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+fn invalid_data(error: impl std::fmt::Debug) -> Error {
+    Error::PostgresError(format!("invalid block data: {error:?}"))
+}
+
+/// Converts a value to the `int` column type, failing instead of wrapping around.
+fn to_i32(value: u64) -> Result<i32, Error> {
+    i32::try_from(value).map_err(invalid_data)
+}
+
+/// Column value used by [`find_or_insert`].
+#[derive(Debug, Clone)]
+enum Value {
+    Int(Option<i32>),
+    BigInt(Option<i64>),
+    Text(Option<String>),
+    Bool(Option<bool>),
+    /// Variant of the named postgres enum type, bound as text and cast.
+    Enum(&'static str, &'static str),
+    IntArray(Vec<i32>),
+    TextArray(Vec<String>),
+}
+
+macro_rules! value_from {
+    ($variant:ident, $ty:ty) => {
+        impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                Self::$variant(Some(value))
+            }
+        }
+
+        impl From<Option<$ty>> for Value {
+            fn from(value: Option<$ty>) -> Self {
+                Self::$variant(value)
+            }
+        }
+    };
+}
+
+value_from!(Int, i32);
+value_from!(BigInt, i64);
+value_from!(Text, String);
+value_from!(Bool, bool);
+
+impl From<Vec<i32>> for Value {
+    fn from(value: Vec<i32>) -> Self {
+        Self::IntArray(value)
+    }
+}
+
+impl From<Vec<String>> for Value {
+    fn from(value: Vec<String>) -> Self {
+        Self::TextArray(value)
+    }
+}
+
+impl Value {
+    fn is_null(&self) -> bool {
+        matches!(
+            self,
+            Self::Int(None) | Self::BigInt(None) | Self::Text(None) | Self::Bool(None)
+        )
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        match self {
+            Self::Enum(type_name, _) => format!("${index}::{type_name}"),
+            _ => format!("${index}"),
+        }
+    }
+
+    fn bind<'q, O>(
+        &self,
+        query: QueryScalar<'q, Postgres, O, PgArguments>,
+    ) -> QueryScalar<'q, Postgres, O, PgArguments> {
+        match self.clone() {
+            Self::Int(value) => query.bind(value),
+            Self::BigInt(value) => query.bind(value),
+            Self::Text(value) => query.bind(value),
+            Self::Bool(value) => query.bind(value),
+            Self::Enum(_, value) => query.bind(value),
+            Self::IntArray(value) => query.bind(value),
+            Self::TextArray(value) => query.bind(value),
+        }
+    }
+}
+
+/// Returns the id of the row in `table` holding exactly `columns`, inserting
+/// it if there is none yet.
+///
+/// Most tables of the archive schema are deduplicated this way, which is
+/// also what the OCaml archive does.
+async fn find_or_insert(
+    conn: &mut PgConnection,
+    table: &str,
+    columns: &[(&str, Value)],
+) -> Result<i32, Error> {
+    let mut conditions = Vec::with_capacity(columns.len());
+    let mut bound = Vec::with_capacity(columns.len());
+    for (column, value) in columns {
+        if value.is_null() {
+            conditions.push(format!("{column} IS NULL"));
+        } else {
+            bound.push(value);
+            conditions.push(format!("{column} = {}", value.placeholder(bound.len())));
+        }
+    }
+    let select = format!(
+        "SELECT id FROM {table} WHERE {} LIMIT 1",
+        conditions.join(" AND ")
+    );
+    let query = bound.iter().fold(
+        sqlx::query_scalar::<Postgres, i32>(&select),
+        |query, value| value.bind(query),
+    );
+    if let Some(id) = query.fetch_optional(&mut *conn).await? {
+        return Ok(id);
+    }
+
+    let names = columns
+        .iter()
+        .map(|(column, _)| *column)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = columns
+        .iter()
+        .enumerate()
+        .map(|(index, (_, value))| value.placeholder(index + 1))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let insert = format!("INSERT INTO {table} ({names}) VALUES ({placeholders}) RETURNING id");
+    let query = columns.iter().fold(
+        sqlx::query_scalar::<Postgres, i32>(&insert),
+        |query, (_, value)| value.bind(query),
+    );
+    Ok(query.fetch_one(&mut *conn).await?)
+}
+
+async fn public_key_id(
+    conn: &mut PgConnection,
+    public_key: &NonZeroCurvePoint,
+) -> Result<i32, Error> {
+    find_or_insert(
+        conn,
+        "public_keys",
+        &[("value", public_key.to_string().into())],
+    )
+    .await
+}
+
+async fn snarked_ledger_hash_id(
+    conn: &mut PgConnection,
+    hash: &v2::LedgerHash,
+) -> Result<i32, Error> {
+    find_or_insert(
+        conn,
+        "snarked_ledger_hashes",
+        &[("value", hash.to_string().into())],
+    )
+    .await
+}
+
+async fn token_symbol_id(conn: &mut PgConnection, symbol: String) -> Result<i32, Error> {
+    find_or_insert(conn, "token_symbols", &[("value", symbol.into())]).await
+}
+
+async fn voting_for_id(conn: &mut PgConnection, voting_for: &v2::StateHash) -> Result<i32, Error> {
+    find_or_insert(
+        conn,
+        "voting_for",
+        &[("value", voting_for.to_string().into())],
+    )
+    .await
+}
+
+async fn token_id(conn: &mut PgConnection, token: &TokenIdKeyHash) -> Result<i32, Error> {
+    find_or_insert(conn, "tokens", &[("value", token.to_string().into())]).await
+}
+
+/// Like [`token_id`], but also records the owner of the token if it is not
+/// known yet.
+async fn token_id_with_owner(
+    conn: &mut PgConnection,
+    token: &TokenIdKeyHash,
+    owner: &v2::MinaBaseAccountIdStableV2,
+) -> Result<i32, Error> {
+    let owner_public_key_id = public_key_id(conn, &owner.0).await?;
+    let owner_token_id = token_id(conn, &TokenIdKeyHash::from(owner.1.clone())).await?;
+    let id = sqlx::query_scalar::<Postgres, i32>(
+        "INSERT INTO tokens (value, owner_public_key_id, owner_token_id) VALUES ($1, $2, $3) \
+         ON CONFLICT (value) DO UPDATE SET \
+         owner_public_key_id = COALESCE(tokens.owner_public_key_id, EXCLUDED.owner_public_key_id), \
+         owner_token_id = COALESCE(tokens.owner_token_id, EXCLUDED.owner_token_id) \
+         RETURNING id",
+    )
+    .bind(token.to_string())
+    .bind(owner_public_key_id)
+    .bind(owner_token_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(id)
+}
+
+async fn account_identifier_id(
+    conn: &mut PgConnection,
+    public_key: &NonZeroCurvePoint,
+    token: &TokenIdKeyHash,
+) -> Result<i32, Error> {
+    let public_key_id = public_key_id(conn, public_key).await?;
+    let token_id = token_id(conn, token).await?;
+    find_or_insert(
+        conn,
+        "account_identifiers",
+        &[
+            ("public_key_id", public_key_id.into()),
+            ("token_id", token_id.into()),
+        ],
+    )
+    .await
+}
+
+async fn protocol_version_id(
+    conn: &mut PgConnection,
+    version: &v2::ProtocolVersionStableV2,
+) -> Result<i32, Error> {
+    find_or_insert(
+        conn,
+        "protocol_versions",
+        &[
+            ("transaction", to_i32(version.transaction.as_u64())?.into()),
+            ("network", to_i32(version.network.as_u64())?.into()),
+            ("patch", to_i32(version.patch.as_u64())?.into()),
+        ],
+    )
+    .await
+}
+
+async fn epoch_data_id(
+    conn: &mut PgConnection,
+    seed: &v2::EpochSeed,
+    ledger: &v2::MinaBaseEpochLedgerValueStableV1,
+    start_checkpoint: &v2::StateHash,
+    lock_checkpoint: &v2::StateHash,
+    epoch_length: &v2::UnsignedExtendedUInt32StableV1,
+) -> Result<i32, Error> {
+    let ledger_hash_id = snarked_ledger_hash_id(conn, &ledger.hash).await?;
+    find_or_insert(
+        conn,
+        "epoch_data",
+        &[
+            ("seed", seed.to_string().into()),
+            ("ledger_hash_id", ledger_hash_id.into()),
+            (
+                "total_currency",
+                ledger.total_currency.as_u64().to_string().into(),
+            ),
+            ("start_checkpoint", start_checkpoint.to_string().into()),
+            ("lock_checkpoint", lock_checkpoint.to_string().into()),
+            ("epoch_length", (epoch_length.as_u32() as i64).into()),
+        ],
+    )
+    .await
+}
+
+/// Inserts the row of the block itself, returning `None` if the block is
+/// already archived.
+async fn insert_block(
+    conn: &mut PgConnection,
+    block: &ArcBlockWithHash,
+) -> Result<Option<i32>, Error> {
+    let state_hash = block.hash().to_string();
+    let existing =
+        sqlx::query_scalar::<Postgres, i32>("SELECT id FROM blocks WHERE state_hash = $1")
+            .bind(&state_hash)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if existing.is_some() {
+        return Ok(None);
+    }
+
+    let parent_hash = block.pred_hash().to_string();
+    let parent_id =
+        sqlx::query_scalar::<Postgres, i32>("SELECT id FROM blocks WHERE state_hash = $1")
+            .bind(&parent_hash)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+    let header = block.header();
+    let consensus_state = block.consensus_state();
+
+    let creator_id = public_key_id(conn, &consensus_state.block_creator).await?;
+    let block_winner_id = public_key_id(conn, &consensus_state.block_stake_winner).await?;
+    let snarked_ledger_hash_id = snarked_ledger_hash_id(conn, block.snarked_ledger_hash()).await?;
+
+    let staking = &consensus_state.staking_epoch_data;
+    let staking_epoch_data_id = epoch_data_id(
+        conn,
+        &staking.seed,
+        &staking.ledger,
+        &staking.start_checkpoint,
+        &staking.lock_checkpoint,
+        &staking.epoch_length,
+    )
+    .await?;
+    let next = &consensus_state.next_epoch_data;
+    let next_epoch_data_id = epoch_data_id(
+        conn,
+        &next.seed,
+        &next.ledger,
+        &next.start_checkpoint,
+        &next.lock_checkpoint,
+        &next.epoch_length,
+    )
+    .await?;
+
+    let proposed_protocol_version_id = match &header.proposed_protocol_version_opt {
+        Some(version) => Some(protocol_version_id(conn, version).await?),
+        None => None,
+    };
+    let protocol_version_id = protocol_version_id(conn, &header.current_protocol_version).await?;
+
+    let sub_window_densities = consensus_state
+        .sub_window_densities
+        .iter()
+        .map(|density| density.as_u32() as i64)
+        .collect::<Vec<_>>();
+    let timestamp = header
+        .protocol_state
+        .body
+        .blockchain_state
+        .timestamp
+        .0
+        .as_u64();
+    let chain_status = if block.is_genesis() {
+        "canonical"
+    } else {
+        "pending"
+    };
+
+    let block_id = sqlx::query_scalar::<Postgres, i32>(
+        "INSERT INTO blocks (state_hash, parent_id, parent_hash, creator_id, block_winner_id, \
+         last_vrf_output, snarked_ledger_hash_id, staking_epoch_data_id, next_epoch_data_id, \
+         min_window_density, sub_window_densities, total_currency, ledger_hash, height, \
+         global_slot_since_hard_fork, global_slot_since_genesis, protocol_version_id, \
+         proposed_protocol_version_id, \"timestamp\", chain_status) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, \
+         $18, $19, $20::chain_status_type) \
+         RETURNING id",
+    )
+    .bind(&state_hash)
+    .bind(parent_id)
+    .bind(&parent_hash)
+    .bind(creator_id)
+    .bind(block_winner_id)
+    .bind(URL_SAFE.encode(consensus_state.last_vrf_output.0.as_ref()))
+    .bind(snarked_ledger_hash_id)
+    .bind(staking_epoch_data_id)
+    .bind(next_epoch_data_id)
+    .bind(consensus_state.min_window_density.as_u32() as i64)
+    .bind(sub_window_densities)
+    .bind(consensus_state.total_currency.as_u64().to_string())
+    .bind(block.merkle_root_hash().to_string())
+    .bind(block.height() as i64)
+    .bind(block.global_slot() as i64)
+    .bind(block.global_slot_since_genesis() as i64)
+    .bind(protocol_version_id)
+    .bind(proposed_protocol_version_id)
+    .bind(timestamp.to_string())
+    .bind(chain_status)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // Children received before their parent are linked now.
+    sqlx::query("UPDATE blocks SET parent_id = $1 WHERE parent_hash = $2 AND parent_id IS NULL")
+        .bind(block_id)
+        .bind(&state_hash)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(Some(block_id))
+}
+
+/// Returns the `transaction_status` column and the first failure of `status`.
+fn status_columns(status: &TransactionStatus) -> (&'static str, Option<String>) {
+    match status {
+        TransactionStatus::Applied => ("applied", None),
+        TransactionStatus::Failed(failures) => (
+            "failed",
+            failures.iter().flatten().next().map(ToString::to_string),
+        ),
+    }
+}
+
+/// Inserts the user, zkApp and internal commands of the block, numbered in
+/// the order they were applied.
+async fn insert_transactions(
+    conn: &mut PgConnection,
+    block_id: i32,
+    block: &ArcBlockWithHash,
+) -> Result<(), Error> {
+    let consensus_state = block.consensus_state();
+    let coinbase_receiver: CompressedPubKey = (&consensus_state.coinbase_receiver)
+        .try_into()
+        .map_err(invalid_data)?;
+    let transactions = Diff::try_from(&block.body().staged_ledger_diff)
+        .map_err(invalid_data)?
+        .get_transactions(
+            constraint_constants(),
+            coinbase_receiver,
+            consensus_state.supercharge_coinbase,
+        )
+        .map_err(invalid_data)?;
+
+    for (sequence_no, WithStatus { data, status }) in transactions.iter().enumerate() {
+        let sequence_no = sequence_no as i32;
+        match data {
+            Transaction::Command(command) => match v2::MinaBaseUserCommandStableV2::from(command) {
+                v2::MinaBaseUserCommandStableV2::SignedCommand(command) => {
+                    insert_user_command(conn, block_id, sequence_no, &command, status).await?
+                }
+                v2::MinaBaseUserCommandStableV2::ZkappCommand(command) => {
+                    zkapp::insert_zkapp_command(conn, block_id, sequence_no, &command, status)
+                        .await?
+                }
+            },
+            Transaction::FeeTransfer(fee_transfer) => {
+                for (secondary_sequence_no, single) in fee_transfer.iter().enumerate() {
+                    let single = v2::MinaBaseFeeTransferSingleStableV2::from(single);
+                    let command = InternalCommand {
+                        command_type: "fee_transfer",
+                        receiver: &single.receiver_pk,
+                        fee: single.fee.as_u64(),
+                        hash: single.hash().map_err(invalid_data)?,
+                    };
+                    let secondary_sequence_no = secondary_sequence_no as i32;
+                    insert_internal_command(
+                        conn,
+                        block_id,
+                        (sequence_no, secondary_sequence_no),
+                        command,
+                        status,
+                    )
+                    .await?;
+                }
+            }
+            Transaction::Coinbase(coinbase) => {
+                if let Some(fee_transfer) = &coinbase.fee_transfer {
+                    let single =
+                        v2::MinaBaseFeeTransferSingleStableV2::from(&SingleFeeTransfer::create(
+                            fee_transfer.receiver_pk.clone(),
+                            fee_transfer.fee,
+                            TokenId::default(),
+                        ));
+                    let command = InternalCommand {
+                        command_type: "fee_transfer_via_coinbase",
+                        receiver: &single.receiver_pk,
+                        fee: single.fee.as_u64(),
+                        hash: single.hash().map_err(invalid_data)?,
+                    };
+                    insert_internal_command(conn, block_id, (sequence_no, 0), command, status)
+                        .await?;
+                }
+
+                let coinbase = v2::MinaBaseCoinbaseStableV1::from(coinbase);
+                let command = InternalCommand {
+                    command_type: "coinbase",
+                    receiver: &coinbase.receiver,
+                    fee: coinbase.amount.as_u64(),
+                    hash: coinbase.hash().map_err(invalid_data)?,
+                };
+                insert_internal_command(conn, block_id, (sequence_no, 0), command, status).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn insert_user_command(
+    conn: &mut PgConnection,
+    block_id: i32,
+    sequence_no: i32,
+    command: &v2::MinaBaseSignedCommandStableV2,
+    status: &TransactionStatus,
+) -> Result<(), Error> {
+    let common = &command.payload.common;
+    let (command_type, receiver, amount) = match &command.payload.body {
+        v2::MinaBaseSignedCommandPayloadBodyStableV2::Payment(payment) => (
+            "payment",
+            &payment.receiver_pk,
+            Some(payment.amount.as_u64().to_string()),
+        ),
+        v2::MinaBaseSignedCommandPayloadBodyStableV2::StakeDelegation(
+            v2::MinaBaseStakeDelegationStableV2::SetDelegate { new_delegate },
+        ) => ("delegation", new_delegate, None),
+    };
+
+    let fee_payer_id = public_key_id(conn, &common.fee_payer_pk).await?;
+    let receiver_id = public_key_id(conn, receiver).await?;
+    let valid_until = match common.valid_until.as_u32() {
+        u32::MAX => None,
+        slot => Some(slot as i64),
+    };
+    let hash = command.hash().map_err(invalid_data)?;
+
+    let user_command_id = find_or_insert(
+        conn,
+        "user_commands",
+        &[
+            (
+                "command_type",
+                Value::Enum("user_command_type", command_type),
+            ),
+            ("fee_payer_id", fee_payer_id.into()),
+            ("source_id", fee_payer_id.into()),
+            ("receiver_id", receiver_id.into()),
+            ("nonce", (common.nonce.as_u32() as i64).into()),
+            ("amount", amount.into()),
+            ("fee", common.fee.as_u64().to_string().into()),
+            ("valid_until", valid_until.into()),
+            ("memo", common.memo.to_base58check().into()),
+            ("hash", hash.to_string().into()),
+        ],
+    )
+    .await?;
+
+    let (status, failure_reason) = status_columns(status);
+    sqlx::query(
+        "INSERT INTO blocks_user_commands \
+         (block_id, user_command_id, sequence_no, status, failure_reason) \
+         VALUES ($1, $2, $3, $4::transaction_status, $5)",
+    )
+    .bind(block_id)
+    .bind(user_command_id)
+    .bind(sequence_no)
+    .bind(status)
+    .bind(failure_reason)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+struct InternalCommand<'a> {
+    command_type: &'static str,
+    receiver: &'a NonZeroCurvePoint,
+    fee: u64,
+    hash: v2::TransactionHash,
+}
+
+async fn insert_internal_command(
+    conn: &mut PgConnection,
+    block_id: i32,
+    (sequence_no, secondary_sequence_no): (i32, i32),
+    command: InternalCommand<'_>,
+    status: &TransactionStatus,
+) -> Result<(), Error> {
+    let receiver_id = public_key_id(conn, command.receiver).await?;
+    let internal_command_id = find_or_insert(
+        conn,
+        "internal_commands",
+        &[
+            (
+                "command_type",
+                Value::Enum("internal_command_type", command.command_type),
+            ),
+            ("receiver_id", receiver_id.into()),
+            ("fee", command.fee.to_string().into()),
+            ("hash", command.hash.to_string().into()),
+        ],
+    )
+    .await?;
+
+    let (status, failure_reason) = status_columns(status);
+    sqlx::query(
+        "INSERT INTO blocks_internal_commands \
+         (block_id, internal_command_id, sequence_no, secondary_sequence_no, status, failure_reason) \
+         VALUES ($1, $2, $3, $4, $5::transaction_status, $6)",
+    )
+    .bind(block_id)
+    .bind(internal_command_id)
+    .bind(sequence_no)
+    .bind(secondary_sequence_no)
+    .bind(status)
+    .bind(failure_reason)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn timing_info_id(
+    conn: &mut PgConnection,
+    account_identifier_id: i32,
+    timing: &v2::MinaBaseAccountTimingStableV2,
+) -> Result<i32, Error> {
+    // The OCaml archive stores untimed accounts as all zeros.
+    let (initial_minimum_balance, cliff_time, cliff_amount, vesting_period, vesting_increment) =
+        match timing {
+            v2::MinaBaseAccountTimingStableV2::Untimed => (0, 0, 0, 0, 0),
+            v2::MinaBaseAccountTimingStableV2::Timed {
+                initial_minimum_balance,
+                cliff_time,
+                cliff_amount,
+                vesting_period,
+                vesting_increment,
+            } => (
+                initial_minimum_balance.0.as_u64(),
+                cliff_time.as_u32(),
+                cliff_amount.as_u64(),
+                vesting_period.as_u32(),
+                vesting_increment.as_u64(),
+            ),
+        };
+
+    find_or_insert(
+        conn,
+        "timing_info",
+        &[
+            ("account_identifier_id", account_identifier_id.into()),
+            (
+                "initial_minimum_balance",
+                initial_minimum_balance.to_string().into(),
+            ),
+            ("cliff_time", (cliff_time as i64).into()),
+            ("cliff_amount", cliff_amount.to_string().into()),
+            ("vesting_period", (vesting_period as i64).into()),
+            ("vesting_increment", vesting_increment.to_string().into()),
+        ],
+    )
+    .await
+}
+
+async fn insert_account_accessed(
+    conn: &mut PgConnection,
+    block_id: i32,
+    ledger_index: u64,
+    account: &v2::MinaBaseAccountBinableArgStableV2,
+) -> Result<(), Error> {
+    let account_identifier_id =
+        account_identifier_id(conn, &account.public_key, &account.token_id).await?;
+    let token_symbol = String::from_utf8_lossy(account.token_symbol.as_ref()).into_owned();
+    let token_symbol_id = token_symbol_id(conn, token_symbol).await?;
+    let delegate_id = match &account.delegate {
+        Some(delegate) => Some(public_key_id(conn, delegate).await?),
+        None => None,
+    };
+    let voting_for_id = voting_for_id(conn, &account.voting_for).await?;
+    let timing_id = timing_info_id(conn, account_identifier_id, &account.timing).await?;
+    let permissions_id = zkapp::permissions_id(conn, &account.permissions).await?;
+    let zkapp_id = match &account.zkapp {
+        Some(zkapp) => Some(zkapp::zkapp_account_id(conn, zkapp).await?),
+        None => None,
+    };
+    let receipt_chain_hash = v2::ReceiptChainHash::from(account.receipt_chain_hash.clone());
+    let ledger_index = to_i32(ledger_index)?;
+
+    sqlx::query(
+        "INSERT INTO accounts_accessed (ledger_index, block_id, account_identifier_id, \
+         token_symbol_id, balance, nonce, receipt_chain_hash, delegate_id, voting_for_id, \
+         timing_id, permissions_id, zkapp_id) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+    )
+    .bind(ledger_index)
+    .bind(block_id)
+    .bind(account_identifier_id)
+    .bind(token_symbol_id)
+    .bind(account.balance.0.as_u64().to_string())
+    .bind(account.nonce.as_u32() as i64)
+    .bind(receipt_chain_hash.to_string())
+    .bind(delegate_id)
+    .bind(voting_for_id)
+    .bind(timing_id)
+    .bind(permissions_id)
+    .bind(zkapp_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Updates `chain_status` after a block was inserted.
+///
+/// Blocks stay `pending` until they are `k` blocks deep in the best chain.
+/// At that point the ancestors of the best tip become `canonical` and any
+/// other block at the same heights is `orphaned`. Blocks above that depth
+/// are left pending, so reorgs within the last `k` blocks need no special
+/// handling.
+///
+/// Blocks can arrive out of order (backfill, late blocks), so the update is
+/// recomputed from every pending block rather than from the inserted one,
+/// see [`plan_chain_status`].
+async fn update_chain_status(
+    conn: &mut PgConnection,
+    block: &ArcBlockWithHash,
+) -> Result<(), Error> {
+    // Pending blocks, with the canonical blocks they can be attached to or
+    // compete with: the highest one, those at the same heights and the
+    // children of pending blocks.
+    let blocks = sqlx::query_as::<Postgres, (i32, Option<i32>, i64, bool)>(
+        "SELECT id, parent_id, height, chain_status = 'canonical' FROM blocks \
+         WHERE chain_status = 'pending' \
+            OR (chain_status = 'canonical' AND ( \
+                height = (SELECT MAX(height) FROM blocks WHERE chain_status = 'canonical') \
+                OR height IN (SELECT height FROM blocks WHERE chain_status = 'pending') \
+                OR parent_id IN (SELECT id FROM blocks WHERE chain_status = 'pending')))",
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|(id, parent_id, height, canonical)| ChainBlock {
+        id,
+        parent_id,
+        height,
+        canonical,
+    })
+    .collect::<Vec<_>>();
+
+    let update = plan_chain_status(&blocks, block.constants().k.as_u32() as i64);
+
+    for (status, ids) in [
+        ("canonical", update.canonical),
+        ("orphaned", update.orphaned),
+    ] {
+        if ids.is_empty() {
+            continue;
+        }
+        sqlx::query("UPDATE blocks SET chain_status = $1::chain_status_type WHERE id = ANY($2)")
+            .bind(status)
+            .bind(ids)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Row of `blocks` considered by [`plan_chain_status`].
+#[derive(Debug, Clone, Copy)]
+struct ChainBlock {
+    id: i32,
+    parent_id: Option<i32>,
+    height: i64,
+    canonical: bool,
+}
+
+/// Blocks whose `chain_status` changes, they are all `pending` for now.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ChainStatusUpdate {
+    canonical: Vec<i32>,
+    orphaned: Vec<i32>,
+}
+
+/// Computes which pending `blocks` become canonical or orphaned.
+///
+/// The chain of the highest block is walked down through `parent_id` until
+/// a canonical block. When a parent is missing on the way, the chain isn't
+/// known yet and it's left pending, until the parent is archived. Only when
+/// nothing is canonical yet (the archive didn't start at genesis), the lowest
+/// block is accepted as the root of the chain.
+///
+/// Pending ancestors of canonical blocks, archived late, become canonical as
+/// well. Then the other pending blocks at the heights of canonical blocks are
+/// orphaned, wherever they were attached.
+fn plan_chain_status(blocks: &[ChainBlock], k: i64) -> ChainStatusUpdate {
+    let by_id = blocks
+        .iter()
+        .map(|block| (block.id, block))
+        .collect::<BTreeMap<_, _>>();
+    let mut canonical = blocks
+        .iter()
+        .filter(|block| block.canonical)
+        .map(|block| block.id)
+        .collect::<BTreeSet<_>>();
+    let mut update = ChainStatusUpdate::default();
+
+    let Some(tip) = blocks.iter().max_by_key(|block| (block.height, block.id)) else {
+        return update;
+    };
+    let final_height = tip.height - k;
+    let canonical_height = blocks
+        .iter()
+        .filter(|block| block.canonical)
+        .map(|block| block.height)
+        .max();
+
+    let mut chain = vec![tip];
+    let connected = loop {
+        let block = chain[chain.len() - 1];
+        if block.canonical {
+            // A fork below the canonical chain can't be finalized anymore
+            break Some(block.height) == canonical_height;
+        }
+        match block.parent_id.and_then(|parent_id| by_id.get(&parent_id)) {
+            Some(parent) => chain.push(parent),
+            None => {
+                break block.parent_id.is_none()
+                    && canonical_height.is_none()
+                    && blocks.iter().all(|other| other.height >= block.height);
+            }
+        }
+    };
+
+    if connected {
+        for block in chain {
+            if !block.canonical && block.height <= final_height {
+                canonical.insert(block.id);
+                update.canonical.push(block.id);
+            }
+        }
+    }
+
+    // The parent of a canonical block is canonical
+    let mut children = canonical.iter().copied().collect::<Vec<_>>();
+    while let Some(id) = children.pop() {
+        let parent = by_id[&id]
+            .parent_id
+            .and_then(|parent_id| by_id.get(&parent_id));
+        if let Some(parent) = parent {
+            if canonical.insert(parent.id) {
+                update.canonical.push(parent.id);
+                children.push(parent.id);
+            }
+        }
+    }
+
+    let canonical_heights = canonical
+        .iter()
+        .map(|id| by_id[id].height)
+        .collect::<BTreeSet<_>>();
+    update.orphaned = blocks
+        .iter()
+        .filter(|block| !canonical.contains(&block.id))
+        .filter(|block| canonical_heights.contains(&block.height))
+        .map(|block| block.id)
+        .collect();
+
+    update.canonical.sort_unstable();
+    update
+}
+
+#[cfg(test)]
+mod tests {
+    use binprot::BinProtRead;
+
+    use super::*;
+
+    /// Requires a database initialized with the Mina archive schema, e.g.
+    /// `producer-dashboard/src/archive/sql/archive_schema.sql`.
+    #[tokio::test]
+    #[ignore = "requires OPENMINA_ARCHIVE_TEST_POSTGRES_URL"]
+    async fn test_add_diff() {
+        let url = env::var("OPENMINA_ARCHIVE_TEST_POSTGRES_URL")
+            .expect("OPENMINA_ARCHIVE_TEST_POSTGRES_URL must point to an archive database");
+        let client = ArchivePostgresClient::connect(&url).await.unwrap();
+
+        let bytes = include_bytes!("../../../../../../tests/files/archive-breadcrumb/3NK56ZbCS31qb8SvCtCCYza4beRDtKgXA2JL6s3evKouG2KkKtiy.bin");
+        let diff = v2::ArchiveTransitionFrontierDiff::binprot_read(&mut bytes.as_slice()).unwrap();
+
+        client.add_diff(&diff).await.unwrap();
+        // Adding the same block again is a no-op.
+        client.add_diff(&diff).await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM blocks WHERE state_hash = $1")
+            .bind("3NK56ZbCS31qb8SvCtCCYza4beRDtKgXA2JL6s3evKouG2KkKtiy")
+            .fetch_one(&client.pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    fn block(id: i32, parent_id: Option<i32>, height: i64, canonical: bool) -> ChainBlock {
+        ChainBlock {
+            id,
+            parent_id,
+            height,
+            canonical,
+        }
+    }
+
+    #[test]
+    fn test_chain_status_parent_gap() {
+        // Block 2 isn't archived yet, block 3 has no parent
+        let blocks = [
+            block(1, None, 1, true),
+            block(3, None, 3, false),
+            block(4, Some(3), 4, false),
+            block(5, Some(4), 5, false),
+        ];
+        assert_eq!(plan_chain_status(&blocks, 2), ChainStatusUpdate::default());
+
+        // Block 2 arrives, along with a fork at height 3
+        let blocks = [
+            block(1, None, 1, true),
+            block(2, Some(1), 2, false),
+            block(3, Some(2), 3, false),
+            block(4, Some(3), 4, false),
+            block(5, Some(4), 5, false),
+            block(6, Some(2), 3, false),
+        ];
+        assert_eq!(
+            plan_chain_status(&blocks, 2),
+            ChainStatusUpdate {
+                canonical: vec![2, 3],
+                orphaned: vec![6],
+            }
+        );
+    }
+
+    #[test]
+    fn test_chain_status_out_of_order() {
+        // The archive started at height 10, nothing is canonical yet
+        let blocks = [
+            block(10, None, 10, false),
+            block(11, Some(10), 11, false),
+            block(12, Some(11), 12, false),
+            block(13, Some(12), 13, false),
+        ];
+        assert_eq!(
+            plan_chain_status(&blocks, 2),
+            ChainStatusUpdate {
+                canonical: vec![10, 11],
+                orphaned: vec![],
+            }
+        );
+
+        // Block 9 is backfilled below the canonical chain, block 14 is a late
+        // fork at an already canonical height
+        let blocks = [
+            block(9, None, 9, false),
+            block(10, Some(9), 10, true),
+            block(11, Some(10), 11, true),
+            block(12, Some(11), 12, false),
+            block(13, Some(12), 13, false),
+            block(14, Some(10), 11, false),
+        ];
+        assert_eq!(
+            plan_chain_status(&blocks, 2),
+            ChainStatusUpdate {
+                canonical: vec![9],
+                orphaned: vec![14],
+            }
+        );
+    }
+}
@@ -0,0 +1,722 @@
+use ledger::{scan_state::transaction_logic::TransactionStatus, FpExt, VerificationKey};
+use mina_p2p_messages::{bigint::BigInt, v2};
+use sqlx::PgConnection;
+
+use super::{
+    account_identifier_id, find_or_insert, invalid_data, public_key_id, snarked_ledger_hash_id,
+    token_symbol_id, voting_for_id, Error, Value,
+};
+
+const ELEMENT_COLUMNS: [&str; 8] = [
+    "element0", "element1", "element2", "element3", "element4", "element5", "element6", "element7",
+];
+
+/// Columns of the `zkapp_states`, `zkapp_states_nullable` and
+/// `zkapp_action_states` tables.
+fn element_columns<T: Into<Value>>(elements: Vec<T>) -> Vec<(&'static str, Value)> {
+    ELEMENT_COLUMNS
+        .into_iter()
+        .zip(elements.into_iter().map(Into::into))
+        .collect()
+}
+
+pub(super) async fn insert_zkapp_command(
+    conn: &mut PgConnection,
+    block_id: i32,
+    sequence_no: i32,
+    command: &v2::MinaBaseZkappCommandTStableV1WireStableV1,
+    status: &TransactionStatus,
+) -> Result<(), Error> {
+    let fee_payer = &command.fee_payer.body;
+    let fee_payer_id = public_key_id(conn, &fee_payer.public_key).await?;
+    let fee_payer_body_id = find_or_insert(
+        conn,
+        "zkapp_fee_payer_body",
+        &[
+            ("public_key_id", fee_payer_id.into()),
+            ("fee", fee_payer.fee.as_u64().to_string().into()),
+            (
+                "valid_until",
+                fee_payer
+                    .valid_until
+                    .as_ref()
+                    .map(|slot| slot.as_u32() as i64)
+                    .into(),
+            ),
+            ("nonce", (fee_payer.nonce.as_u32() as i64).into()),
+        ],
+    )
+    .await?;
+
+    let mut account_update_ids = Vec::new();
+    for (account_update, call_depth) in account_updates_with_depth(command) {
+        let body_id = account_update_body_id(conn, &account_update.body, call_depth).await?;
+        let id =
+            find_or_insert(conn, "zkapp_account_update", &[("body_id", body_id.into())]).await?;
+        account_update_ids.push(id);
+    }
+
+    let hash = command.hash().map_err(invalid_data)?;
+    let zkapp_command_id = find_or_insert(
+        conn,
+        "zkapp_commands",
+        &[
+            ("zkapp_fee_payer_body_id", fee_payer_body_id.into()),
+            ("zkapp_account_updates_ids", account_update_ids.into()),
+            ("memo", command.memo.to_base58check().into()),
+            ("hash", hash.to_string().into()),
+        ],
+    )
+    .await?;
+
+    // Failures are indexed by account update, the fee payer being the first.
+    let (status, failure_reasons_ids) = match status {
+        TransactionStatus::Applied => ("applied", None),
+        TransactionStatus::Failed(failures) => {
+            let mut ids = Vec::new();
+            for (index, failures) in failures.iter().enumerate() {
+                if failures.is_empty() {
+                    continue;
+                }
+                let failures = failures.iter().map(ToString::to_string).collect::<Vec<_>>();
+                let id = find_or_insert(
+                    conn,
+                    "zkapp_account_update_failures",
+                    &[
+                        ("index", (index as i32).into()),
+                        ("failures", failures.into()),
+                    ],
+                )
+                .await?;
+                ids.push(id);
+            }
+            ("failed", Some(ids))
+        }
+    };
+
+    sqlx::query(
+        "INSERT INTO blocks_zkapp_commands \
+         (block_id, zkapp_command_id, sequence_no, status, failure_reasons_ids) \
+         VALUES ($1, $2, $3, $4::transaction_status, $5)",
+    )
+    .bind(block_id)
+    .bind(zkapp_command_id)
+    .bind(sequence_no)
+    .bind(status)
+    .bind(failure_reasons_ids)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Account updates of `command` in depth-first order, with their call depth.
+fn account_updates_with_depth(
+    command: &v2::MinaBaseZkappCommandTStableV1WireStableV1,
+) -> Vec<(&v2::MinaBaseAccountUpdateTStableV1, i32)> {
+    fn visit<'a>(
+        tree: &'a v2::MinaBaseZkappCommandTStableV1WireStableV1AccountUpdatesAA,
+        depth: i32,
+        out: &mut Vec<(&'a v2::MinaBaseAccountUpdateTStableV1, i32)>,
+    ) {
+        out.push((&tree.account_update, depth));
+        for call in tree.calls.iter() {
+            visit(&call.elt, depth + 1, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    for account_update in command.account_updates.iter() {
+        visit(&account_update.elt, 0, &mut out);
+    }
+    out
+}
+
+async fn account_update_body_id(
+    conn: &mut PgConnection,
+    body: &v2::MinaBaseAccountUpdateBodyStableV1,
+    call_depth: i32,
+) -> Result<i32, Error> {
+    let account_identifier_id =
+        account_identifier_id(conn, &body.public_key, &body.token_id).await?;
+    let update_id = update_id(conn, &body.update).await?;
+    let actions_id = events_id(conn, &body.actions).await?;
+    let events_id = events_id(conn, &body.events).await?;
+    let call_data_id = field_id(conn, &body.call_data).await?;
+    let network_precondition_id =
+        network_precondition_id(conn, &body.preconditions.network).await?;
+    let account_precondition_id =
+        account_precondition_id(conn, &body.preconditions.account.0).await?;
+    let valid_while_precondition_id =
+        global_slot_bounds_id(conn, &body.preconditions.valid_while).await?;
+
+    let balance_change = match body.balance_change.sgn {
+        v2::SgnStableV1::Pos => body.balance_change.magnitude.as_u64().to_string(),
+        v2::SgnStableV1::Neg => format!("-{}", body.balance_change.magnitude.as_u64()),
+    };
+    let may_use_token = match body.may_use_token {
+        v2::MinaBaseAccountUpdateMayUseTokenStableV1::No => "No",
+        v2::MinaBaseAccountUpdateMayUseTokenStableV1::ParentsOwnToken => "ParentsOwnToken",
+        v2::MinaBaseAccountUpdateMayUseTokenStableV1::InheritFromParent => "InheritFromParent",
+    };
+    let (authorization_kind, verification_key_hash_id) = match &body.authorization_kind {
+        v2::MinaBaseAccountUpdateAuthorizationKindStableV1::NoneGiven => ("None_given", None),
+        v2::MinaBaseAccountUpdateAuthorizationKindStableV1::Signature => ("Signature", None),
+        v2::MinaBaseAccountUpdateAuthorizationKindStableV1::Proof(hash) => (
+            "Proof",
+            Some(verification_key_hash_id(conn, hash.to_decimal()).await?),
+        ),
+    };
+
+    find_or_insert(
+        conn,
+        "zkapp_account_update_body",
+        &[
+            ("account_identifier_id", account_identifier_id.into()),
+            ("update_id", update_id.into()),
+            ("balance_change", balance_change.into()),
+            ("increment_nonce", body.increment_nonce.into()),
+            ("events_id", events_id.into()),
+            ("actions_id", actions_id.into()),
+            ("call_data_id", call_data_id.into()),
+            ("call_depth", call_depth.into()),
+            (
+                "zkapp_network_precondition_id",
+                network_precondition_id.into(),
+            ),
+            (
+                "zkapp_account_precondition_id",
+                account_precondition_id.into(),
+            ),
+            (
+                "zkapp_valid_while_precondition_id",
+                valid_while_precondition_id.into(),
+            ),
+            ("use_full_commitment", body.use_full_commitment.into()),
+            (
+                "implicit_account_creation_fee",
+                body.implicit_account_creation_fee.into(),
+            ),
+            ("may_use_token", Value::Enum("may_use_token", may_use_token)),
+            (
+                "authorization_kind",
+                Value::Enum("authorization_kind_type", authorization_kind),
+            ),
+            ("verification_key_hash_id", verification_key_hash_id.into()),
+        ],
+    )
+    .await
+}
+
+async fn field_id(conn: &mut PgConnection, field: &BigInt) -> Result<i32, Error> {
+    find_or_insert(conn, "zkapp_field", &[("field", field.to_decimal().into())]).await
+}
+
+async fn events_id(
+    conn: &mut PgConnection,
+    events: &v2::MinaBaseAccountUpdateBodyEventsStableV1,
+) -> Result<i32, Error> {
+    let mut element_ids = Vec::with_capacity(events.0.len());
+    for event in events.0.iter() {
+        let mut field_ids = Vec::with_capacity(event.len());
+        for field in event.iter() {
+            field_ids.push(field_id(conn, field).await?);
+        }
+        let id = find_or_insert(
+            conn,
+            "zkapp_field_array",
+            &[("element_ids", field_ids.into())],
+        )
+        .await?;
+        element_ids.push(id);
+    }
+    find_or_insert(conn, "zkapp_events", &[("element_ids", element_ids.into())]).await
+}
+
+async fn zkapp_uri_id(conn: &mut PgConnection, uri: String) -> Result<i32, Error> {
+    find_or_insert(conn, "zkapp_uris", &[("value", uri.into())]).await
+}
+
+async fn verification_key_hash_id(conn: &mut PgConnection, hash: String) -> Result<i32, Error> {
+    find_or_insert(
+        conn,
+        "zkapp_verification_key_hashes",
+        &[("value", hash.into())],
+    )
+    .await
+}
+
+async fn verification_key_id(
+    conn: &mut PgConnection,
+    verification_key: &v2::MinaBaseVerificationKeyWireStableV1,
+) -> Result<i32, Error> {
+    let hash = VerificationKey::try_from(verification_key)
+        .map_err(invalid_data)?
+        .hash()
+        .to_decimal();
+    let hash_id = verification_key_hash_id(conn, hash).await?;
+    find_or_insert(
+        conn,
+        "zkapp_verification_keys",
+        &[
+            (
+                "verification_key",
+                verification_key.to_base64().map_err(invalid_data)?.into(),
+            ),
+            ("hash_id", hash_id.into()),
+        ],
+    )
+    .await
+}
+
+fn auth_required(auth: &v2::MinaBasePermissionsAuthRequiredStableV2) -> Value {
+    let auth = match auth {
+        v2::MinaBasePermissionsAuthRequiredStableV2::None => "none",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Either => "either",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Proof => "proof",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Signature => "signature",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Impossible => "impossible",
+    };
+    Value::Enum("zkapp_auth_required_type", auth)
+}
+
+pub(super) async fn permissions_id(
+    conn: &mut PgConnection,
+    permissions: &v2::MinaBasePermissionsStableV2,
+) -> Result<i32, Error> {
+    let (set_verification_key_auth, set_verification_key_txn_version) =
+        &permissions.set_verification_key;
+    find_or_insert(
+        conn,
+        "zkapp_permissions",
+        &[
+            ("edit_state", auth_required(&permissions.edit_state)),
+            ("send", auth_required(&permissions.send)),
+            ("receive", auth_required(&permissions.receive)),
+            ("access", auth_required(&permissions.access)),
+            ("set_delegate", auth_required(&permissions.set_delegate)),
+            (
+                "set_permissions",
+                auth_required(&permissions.set_permissions),
+            ),
+            (
+                "set_verification_key_auth",
+                auth_required(set_verification_key_auth),
+            ),
+            (
+                "set_verification_key_txn_version",
+                (set_verification_key_txn_version.as_u32() as i32).into(),
+            ),
+            ("set_zkapp_uri", auth_required(&permissions.set_zkapp_uri)),
+            (
+                "edit_action_state",
+                auth_required(&permissions.edit_action_state),
+            ),
+            (
+                "set_token_symbol",
+                auth_required(&permissions.set_token_symbol),
+            ),
+            (
+                "increment_nonce",
+                auth_required(&permissions.increment_nonce),
+            ),
+            ("set_voting_for", auth_required(&permissions.set_voting_for)),
+            ("set_timing", auth_required(&permissions.set_timing)),
+        ],
+    )
+    .await
+}
+
+pub(super) async fn zkapp_account_id(
+    conn: &mut PgConnection,
+    zkapp: &v2::MinaBaseZkappAccountStableV2,
+) -> Result<i32, Error> {
+    let mut app_state = Vec::with_capacity(zkapp.app_state.0.len());
+    for field in zkapp.app_state.0.iter() {
+        app_state.push(field_id(conn, field).await?);
+    }
+    let app_state_id = find_or_insert(conn, "zkapp_states", &element_columns(app_state)).await?;
+
+    let mut action_state = Vec::with_capacity(zkapp.action_state.len());
+    for field in zkapp.action_state.iter() {
+        action_state.push(field_id(conn, field).await?);
+    }
+    let action_state_id =
+        find_or_insert(conn, "zkapp_action_states", &element_columns(action_state)).await?;
+
+    let verification_key_id = match &zkapp.verification_key {
+        Some(verification_key) => Some(verification_key_id(conn, verification_key).await?),
+        None => None,
+    };
+    let zkapp_uri_id = zkapp_uri_id(conn, zkapp.zkapp_uri.to_string()).await?;
+
+    find_or_insert(
+        conn,
+        "zkapp_accounts",
+        &[
+            ("app_state_id", app_state_id.into()),
+            ("verification_key_id", verification_key_id.into()),
+            (
+                "zkapp_version",
+                (zkapp.zkapp_version.0.as_u32() as i64).into(),
+            ),
+            ("action_state_id", action_state_id.into()),
+            (
+                "last_action_slot",
+                (zkapp.last_action_slot.as_u32() as i64).into(),
+            ),
+            ("proved_state", zkapp.proved_state.into()),
+            ("zkapp_uri_id", zkapp_uri_id.into()),
+        ],
+    )
+    .await
+}
+
+async fn update_id(
+    conn: &mut PgConnection,
+    update: &v2::MinaBaseAccountUpdateUpdateStableV1,
+) -> Result<i32, Error> {
+    use v2::{
+        MinaBaseAccountUpdateUpdateStableV1AppStateA as AppState,
+        MinaBaseAccountUpdateUpdateStableV1Delegate as Delegate,
+        MinaBaseAccountUpdateUpdateStableV1Permissions as Permissions,
+        MinaBaseAccountUpdateUpdateStableV1Timing as Timing,
+        MinaBaseAccountUpdateUpdateStableV1TokenSymbol as TokenSymbol,
+        MinaBaseAccountUpdateUpdateStableV1VerificationKey as VerificationKeyUpdate,
+        MinaBaseAccountUpdateUpdateStableV1VotingFor as VotingFor,
+        MinaBaseAccountUpdateUpdateStableV1ZkappUri as ZkappUri,
+    };
+
+    let mut app_state = Vec::with_capacity(update.app_state.len());
+    for element in update.app_state.iter() {
+        app_state.push(match element {
+            AppState::Set(field) => Some(field_id(conn, field).await?),
+            AppState::Keep => None,
+        });
+    }
+    let app_state_id =
+        find_or_insert(conn, "zkapp_states_nullable", &element_columns(app_state)).await?;
+
+    let delegate_id = match &update.delegate {
+        Delegate::Set(delegate) => Some(public_key_id(conn, delegate).await?),
+        Delegate::Keep => None,
+    };
+    let verification_key_id = match &update.verification_key {
+        VerificationKeyUpdate::Set(verification_key) => {
+            Some(verification_key_id(conn, verification_key).await?)
+        }
+        VerificationKeyUpdate::Keep => None,
+    };
+    let permissions_id = match &update.permissions {
+        Permissions::Set(permissions) => Some(permissions_id(conn, permissions).await?),
+        Permissions::Keep => None,
+    };
+    let zkapp_uri_id = match &update.zkapp_uri {
+        ZkappUri::Set(uri) => Some(zkapp_uri_id(conn, uri.to_string()).await?),
+        ZkappUri::Keep => None,
+    };
+    let token_symbol_id = match &update.token_symbol {
+        TokenSymbol::Set(symbol) => Some(token_symbol_id(conn, symbol.to_string()).await?),
+        TokenSymbol::Keep => None,
+    };
+    let timing_id = match &update.timing {
+        Timing::Set(timing) => Some(
+            find_or_insert(
+                conn,
+                "zkapp_timing_info",
+                &[
+                    (
+                        "initial_minimum_balance",
+                        timing.initial_minimum_balance.0.as_u64().to_string().into(),
+                    ),
+                    ("cliff_time", (timing.cliff_time.as_u32() as i64).into()),
+                    (
+                        "cliff_amount",
+                        timing.cliff_amount.as_u64().to_string().into(),
+                    ),
+                    (
+                        "vesting_period",
+                        (timing.vesting_period.as_u32() as i64).into(),
+                    ),
+                    (
+                        "vesting_increment",
+                        timing.vesting_increment.as_u64().to_string().into(),
+                    ),
+                ],
+            )
+            .await?,
+        ),
+        Timing::Keep => None,
+    };
+    let voting_for_id = match &update.voting_for {
+        VotingFor::Set(voting_for) => Some(voting_for_id(conn, voting_for).await?),
+        VotingFor::Keep => None,
+    };
+
+    find_or_insert(
+        conn,
+        "zkapp_updates",
+        &[
+            ("app_state_id", app_state_id.into()),
+            ("delegate_id", delegate_id.into()),
+            ("verification_key_id", verification_key_id.into()),
+            ("permissions_id", permissions_id.into()),
+            ("zkapp_uri_id", zkapp_uri_id.into()),
+            ("token_symbol_id", token_symbol_id.into()),
+            ("timing_id", timing_id.into()),
+            ("voting_for_id", voting_for_id.into()),
+        ],
+    )
+    .await
+}
+
+/// Bounds stored as `bigint`, in `zkapp_length_bounds` or
+/// `zkapp_nonce_bounds` depending on `kind`.
+async fn length_bounds_id(
+    conn: &mut PgConnection,
+    kind: &str,
+    bounds: &v2::MinaBaseZkappPreconditionProtocolStateStableV1Length,
+) -> Result<Option<i32>, Error> {
+    let v2::MinaBaseZkappPreconditionProtocolStateStableV1Length::Check(bounds) = bounds else {
+        return Ok(None);
+    };
+    let lower = format!("{kind}_lower_bound");
+    let upper = format!("{kind}_upper_bound");
+    let id = find_or_insert(
+        conn,
+        &format!("zkapp_{kind}_bounds"),
+        &[
+            (lower.as_str(), (bounds.lower.as_u32() as i64).into()),
+            (upper.as_str(), (bounds.upper.as_u32() as i64).into()),
+        ],
+    )
+    .await?;
+    Ok(Some(id))
+}
+
+async fn amount_bounds_id(
+    conn: &mut PgConnection,
+    bounds: &v2::MinaBaseZkappPreconditionProtocolStateStableV1Amount,
+) -> Result<Option<i32>, Error> {
+    let v2::MinaBaseZkappPreconditionProtocolStateStableV1Amount::Check(bounds) = bounds else {
+        return Ok(None);
+    };
+    let id = find_or_insert(
+        conn,
+        "zkapp_amount_bounds",
+        &[
+            (
+                "amount_lower_bound",
+                bounds.lower.as_u64().to_string().into(),
+            ),
+            (
+                "amount_upper_bound",
+                bounds.upper.as_u64().to_string().into(),
+            ),
+        ],
+    )
+    .await?;
+    Ok(Some(id))
+}
+
+async fn global_slot_bounds_id(
+    conn: &mut PgConnection,
+    bounds: &v2::MinaBaseZkappPreconditionProtocolStateStableV1GlobalSlot,
+) -> Result<Option<i32>, Error> {
+    let v2::MinaBaseZkappPreconditionProtocolStateStableV1GlobalSlot::Check(bounds) = bounds else {
+        return Ok(None);
+    };
+    let id = find_or_insert(
+        conn,
+        "zkapp_global_slot_bounds",
+        &[
+            (
+                "global_slot_lower_bound",
+                (bounds.lower.as_u32() as i64).into(),
+            ),
+            (
+                "global_slot_upper_bound",
+                (bounds.upper.as_u32() as i64).into(),
+            ),
+        ],
+    )
+    .await?;
+    Ok(Some(id))
+}
+
+async fn epoch_data_precondition_id(
+    conn: &mut PgConnection,
+    epoch_data: &v2::MinaBaseZkappPreconditionProtocolStateEpochDataStableV1,
+) -> Result<i32, Error> {
+    use v2::{
+        MinaBaseZkappPreconditionProtocolStateEpochDataStableV1EpochSeed as EpochSeed,
+        MinaBaseZkappPreconditionProtocolStateEpochDataStableV1StartCheckpoint as Checkpoint,
+        MinaBaseZkappPreconditionProtocolStateStableV1SnarkedLedgerHash as LedgerHash,
+    };
+
+    let hash_id = match &epoch_data.ledger.hash {
+        LedgerHash::Check(hash) => Some(snarked_ledger_hash_id(conn, hash).await?),
+        LedgerHash::Ignore => None,
+    };
+    let total_currency_id = amount_bounds_id(conn, &epoch_data.ledger.total_currency).await?;
+    let epoch_ledger_id = find_or_insert(
+        conn,
+        "zkapp_epoch_ledger",
+        &[
+            ("hash_id", hash_id.into()),
+            ("total_currency_id", total_currency_id.into()),
+        ],
+    )
+    .await?;
+    let epoch_length_id = length_bounds_id(conn, "length", &epoch_data.epoch_length).await?;
+
+    let seed = match &epoch_data.seed {
+        EpochSeed::Check(seed) => Some(seed.to_string()),
+        EpochSeed::Ignore => None,
+    };
+    let checkpoint = |checkpoint: &Checkpoint| match checkpoint {
+        Checkpoint::Check(state_hash) => Some(state_hash.to_string()),
+        Checkpoint::Ignore => None,
+    };
+
+    find_or_insert(
+        conn,
+        "zkapp_epoch_data",
+        &[
+            ("epoch_ledger_id", epoch_ledger_id.into()),
+            ("epoch_seed", seed.into()),
+            (
+                "start_checkpoint",
+                checkpoint(&epoch_data.start_checkpoint).into(),
+            ),
+            (
+                "lock_checkpoint",
+                checkpoint(&epoch_data.lock_checkpoint).into(),
+            ),
+            ("epoch_length_id", epoch_length_id.into()),
+        ],
+    )
+    .await
+}
+
+async fn network_precondition_id(
+    conn: &mut PgConnection,
+    precondition: &v2::MinaBaseZkappPreconditionProtocolStateStableV1,
+) -> Result<i32, Error> {
+    use v2::MinaBaseZkappPreconditionProtocolStateStableV1SnarkedLedgerHash as LedgerHash;
+
+    let snarked_ledger_hash_id = match &precondition.snarked_ledger_hash {
+        LedgerHash::Check(hash) => Some(snarked_ledger_hash_id(conn, hash).await?),
+        LedgerHash::Ignore => None,
+    };
+    let blockchain_length_id =
+        length_bounds_id(conn, "length", &precondition.blockchain_length).await?;
+    let min_window_density_id =
+        length_bounds_id(conn, "length", &precondition.min_window_density).await?;
+    let total_currency_id = amount_bounds_id(conn, &precondition.total_currency).await?;
+    let global_slot_since_genesis =
+        global_slot_bounds_id(conn, &precondition.global_slot_since_genesis).await?;
+    let staking_epoch_data_id =
+        epoch_data_precondition_id(conn, &precondition.staking_epoch_data).await?;
+    let next_epoch_data_id =
+        epoch_data_precondition_id(conn, &precondition.next_epoch_data).await?;
+
+    find_or_insert(
+        conn,
+        "zkapp_network_precondition",
+        &[
+            ("snarked_ledger_hash_id", snarked_ledger_hash_id.into()),
+            ("blockchain_length_id", blockchain_length_id.into()),
+            ("min_window_density_id", min_window_density_id.into()),
+            ("total_currency_id", total_currency_id.into()),
+            (
+                "global_slot_since_genesis",
+                global_slot_since_genesis.into(),
+            ),
+            ("staking_epoch_data_id", staking_epoch_data_id.into()),
+            ("next_epoch_data_id", next_epoch_data_id.into()),
+        ],
+    )
+    .await
+}
+
+async fn account_precondition_id(
+    conn: &mut PgConnection,
+    precondition: &v2::MinaBaseZkappPreconditionAccountStableV2,
+) -> Result<i32, Error> {
+    use v2::{
+        MinaBaseZkappPreconditionAccountStableV2Balance as Balance,
+        MinaBaseZkappPreconditionAccountStableV2Delegate as Delegate,
+        MinaBaseZkappPreconditionAccountStableV2ProvedState as ProvedState,
+        MinaBaseZkappPreconditionAccountStableV2ReceiptChainHash as ReceiptChainHash,
+        MinaBaseZkappPreconditionAccountStableV2StateA as State,
+    };
+
+    let balance_id = match &precondition.balance {
+        Balance::Check(bounds) => Some(
+            find_or_insert(
+                conn,
+                "zkapp_balance_bounds",
+                &[
+                    (
+                        "balance_lower_bound",
+                        bounds.lower.0.as_u64().to_string().into(),
+                    ),
+                    (
+                        "balance_upper_bound",
+                        bounds.upper.0.as_u64().to_string().into(),
+                    ),
+                ],
+            )
+            .await?,
+        ),
+        Balance::Ignore => None,
+    };
+    let nonce_id = length_bounds_id(conn, "nonce", &precondition.nonce).await?;
+    let receipt_chain_hash = match &precondition.receipt_chain_hash {
+        ReceiptChainHash::Check(hash) => Some(v2::ReceiptChainHash::from(hash.clone()).to_string()),
+        ReceiptChainHash::Ignore => None,
+    };
+    let delegate_id = match &precondition.delegate {
+        Delegate::Check(delegate) => Some(public_key_id(conn, delegate).await?),
+        Delegate::Ignore => None,
+    };
+
+    let mut state = Vec::with_capacity(precondition.state.len());
+    for element in precondition.state.iter() {
+        state.push(match element {
+            State::Check(field) => Some(field_id(conn, field).await?),
+            State::Ignore => None,
+        });
+    }
+    let state_id = find_or_insert(conn, "zkapp_states_nullable", &element_columns(state)).await?;
+
+    let action_state_id = match &precondition.action_state {
+        State::Check(field) => Some(field_id(conn, field).await?),
+        State::Ignore => None,
+    };
+    let proved_state = match precondition.proved_state {
+        ProvedState::Check(proved_state) => Some(proved_state),
+        ProvedState::Ignore => None,
+    };
+    let is_new = match precondition.is_new {
+        ProvedState::Check(is_new) => Some(is_new),
+        ProvedState::Ignore => None,
+    };
+
+    find_or_insert(
+        conn,
+        "zkapp_account_precondition",
+        &[
+            ("balance_id", balance_id.into()),
+            ("nonce_id", nonce_id.into()),
+            ("receipt_chain_hash", receipt_chain_hash.into()),
+            ("delegate_id", delegate_id.into()),
+            ("state_id", state_id.into()),
+            ("action_state_id", action_state_id.into()),
+            ("proved_state", proved_state.into()),
+            ("is_new", is_new.into()),
+        ],
+    )
+    .await
+}
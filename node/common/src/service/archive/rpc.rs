@@ -4,9 +4,15 @@ use mina_p2p_messages::v2::{self, ArchiveRpc};
 use mio::event::Event;
 use mio::net::TcpStream;
 use mio::{Events, Interest, Poll, Registry, Token};
+use std::env;
 use std::io::{self, Read, Write};
 use std::net::SocketAddr;
 
+use super::{
+    sink::{ArchiveBlock, ArchiveSink, ArchiveSinkFuture},
+    Error,
+};
+
 const MAX_RECURSION_DEPTH: u8 = 25;
 
 // messages
@@ -40,6 +46,59 @@ pub fn send_diff(address: SocketAddr, data: v2::ArchiveRpc) -> io::Result<Handle
     process_rpc(address, &rpc)
 }
 
+/// Sends diffs to the OCaml archive process over its RPC interface.
+pub struct ArchiverProcessSink {
+    address: SocketAddr,
+}
+
+impl ArchiverProcessSink {
+    pub fn new(address: SocketAddr) -> Self {
+        Self { address }
+    }
+
+    pub fn from_env() -> Result<Self, Error> {
+        let address = env::var("OPENMINA_ARCHIVE_ADDRESS").map_err(|_| {
+            Error::EnvironmentVariableNotSet("OPENMINA_ARCHIVE_ADDRESS".to_string())
+        })?;
+        let address = reqwest::Url::parse(&address)
+            .map_err(|e| Error::UploadError(format!("Invalid archive address: {e}")))?;
+
+        // Convert URL to SocketAddr
+        let socket_addr = address
+            .socket_addrs(|| None)
+            .map_err(|e| Error::UploadError(format!("Invalid archive address: {e}")))?
+            .first()
+            .copied()
+            .ok_or_else(|| Error::UploadError("No socket address found".to_string()))?;
+
+        Ok(Self::new(socket_addr))
+    }
+}
+
+impl ArchiveSink for ArchiverProcessSink {
+    fn name(&self) -> &str {
+        "archiver"
+    }
+
+    fn send<'a>(&'a mut self, block: &'a ArchiveBlock) -> ArchiveSinkFuture<'a> {
+        Box::pin(async move {
+            let rpc = v2::ArchiveRpc::SendDiff(block.diff()?.clone());
+            let address = self.address;
+            // `send_diff` blocks on its own poll loop, keep it off the runtime threads
+            let result = tokio::task::spawn_blocking(move || send_diff(address, rpc))
+                .await
+                .map_err(|e| Error::UploadError(e.to_string()))?;
+            match result {
+                Ok(result) if result.should_retry() => {
+                    Err(Error::UploadError("Archive closed connection".to_string()))
+                }
+                Ok(_) => Ok(()),
+                Err(e) => Err(Error::UploadError(e.to_string())),
+            }
+        })
+    }
+}
+
 fn encode_to_rpc(data: ArchiveRpc) -> io::Result<Vec<u8>> {
     type Method = mina_p2p_messages::rpc::SendArchiveDiffUnversioned;
     let mut v = vec![0; 8];
@@ -0,0 +1,104 @@
+use std::{future::Future, pin::Pin, sync::OnceLock};
+
+use mina_p2p_messages::v2;
+use node::ledger::write::BlockApplyResult;
+use openmina_core::NetworkConfig;
+
+use super::{config::ArchiveSinkConfig, Error};
+
+pub type ArchiveSinkFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+/// Destination for applied blocks.
+///
+/// Each sink gets its own queue and delivery task, so a slow or failing sink
+/// doesn't hold back the others (unless configured with
+/// [`super::config::ArchiveSinkBackpressure::Block`]). Failed deliveries are
+/// retried according to the sink's [`ArchiveSinkConfig`].
+pub trait ArchiveSink: Send + 'static {
+    /// Name used in logs and in the sink status.
+    fn name(&self) -> &str;
+
+    /// Sets up connections or clients. Called before the first delivery
+    /// and again before the next attempt, if it failed.
+    fn init(&mut self) -> ArchiveSinkFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn send<'a>(&'a mut self, block: &'a ArchiveBlock) -> ArchiveSinkFuture<'a>;
+}
+
+/// Block delivered to the sinks. Representations are computed on first
+/// use and shared between the sinks.
+pub struct ArchiveBlock {
+    breadcrumb: BlockApplyResult,
+    diff: OnceLock<Result<v2::ArchiveTransitionFrontierDiff, String>>,
+    precomputed: OnceLock<Result<Vec<u8>, String>>,
+}
+
+impl ArchiveBlock {
+    pub fn new(breadcrumb: BlockApplyResult) -> Self {
+        Self {
+            breadcrumb,
+            diff: OnceLock::new(),
+            precomputed: OnceLock::new(),
+        }
+    }
+
+    pub fn breadcrumb(&self) -> &BlockApplyResult {
+        &self.breadcrumb
+    }
+
+    pub fn height(&self) -> u32 {
+        self.breadcrumb.block.height()
+    }
+
+    pub fn diff(&self) -> Result<&v2::ArchiveTransitionFrontierDiff, Error> {
+        self.diff
+            .get_or_init(|| (&self.breadcrumb).try_into())
+            .as_ref()
+            .map_err(|e| Error::ConversionError(e.clone()))
+    }
+
+    /// Name of the precomputed block file, as used by the OCaml node.
+    pub fn precomputed_key(&self) -> String {
+        let network_name = NetworkConfig::global().name;
+        let height = self.height();
+        let state_hash = self.breadcrumb.block.hash();
+
+        format!("{network_name}-{height}-{state_hash}.json")
+    }
+
+    /// Precomputed block, serialized to json.
+    pub fn precomputed_json(&self) -> Result<&[u8], Error> {
+        self.precomputed
+            .get_or_init(|| {
+                let precomputed_block: v2::PrecomputedBlock = (&self.breadcrumb).try_into()?;
+                serde_json::to_vec(&precomputed_block).map_err(|e| e.to_string())
+            })
+            .as_ref()
+            .map(Vec::as_slice)
+            .map_err(|e| Error::ConversionError(e.clone()))
+    }
+}
+
+/// Sinks the archive service delivers to, each with its own configuration.
+#[derive(Default)]
+pub struct ArchiveSinks(pub(super) Vec<(Box<dyn ArchiveSink>, ArchiveSinkConfig)>);
+
+impl ArchiveSinks {
+    pub fn add(&mut self, sink: impl ArchiveSink, config: ArchiveSinkConfig) -> &mut Self {
+        self.0.push((Box::new(sink), config));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|(sink, _)| sink.name().to_owned())
+            .collect()
+    }
+}
@@ -1,6 +1,13 @@
+#[cfg(not(target_family = "wasm"))]
+mod remote_signer;
+mod signer;
 mod vrf_evaluator;
 
-use std::sync::Arc;
+#[cfg(not(target_family = "wasm"))]
+pub use remote_signer::{serve as serve_signer, RemoteSigner, SignerRequest, SignerResponse};
+pub use signer::{BlockProducerSigner, LocalSigner};
+
+use std::{collections::BTreeMap, sync::Arc};
 
 use ledger::proofs::{
     block::BlockParams, generate_block_proof, provers::BlockProver,
@@ -12,7 +19,7 @@ use mina_p2p_messages::{
     v2::{self, MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2, StateHash},
 };
 use node::{
-    account::AccountSecretKey,
+    account::{AccountPublicKey, AccountSecretKey},
     block_producer::{vrf_evaluator::VrfEvaluatorInput, BlockProducerEvent},
     core::{channels::mpsc, constants::constraint_constants, thread},
 };
@@ -20,31 +27,37 @@ use rsa::pkcs1::DecodeRsaPublicKey;
 
 use crate::EventSender;
 
+type Signers = BTreeMap<AccountPublicKey, Arc<dyn BlockProducerSigner>>;
+
+type VrfEvaluationRequest = (VrfEvaluatorInput, Signers);
+
+type ProveRequest = (
+    Arc<dyn BlockProducerSigner>,
+    StateHash,
+    Box<ProverExtendBlockchainInputStableV2>,
+);
+
 pub struct BlockProducerService {
     provers: Option<BlockProver>,
-    keypair: AccountSecretKey,
-    vrf_evaluation_sender: mpsc::TrackedUnboundedSender<VrfEvaluatorInput>,
-    prove_sender: mpsc::TrackedUnboundedSender<(
-        BlockProver,
-        StateHash,
-        Box<ProverExtendBlockchainInputStableV2>,
-    )>,
+    /// Signer of the primary producer key.
+    primary: Arc<dyn BlockProducerSigner>,
+    /// Signers of all producer keys, including the primary one.
+    signers: Signers,
+    vrf_evaluation_sender: mpsc::TrackedUnboundedSender<VrfEvaluationRequest>,
+    prove_sender: mpsc::TrackedUnboundedSender<ProveRequest>,
 }
 
 impl BlockProducerService {
     pub fn new(
-        keypair: AccountSecretKey,
-        vrf_evaluation_sender: mpsc::TrackedUnboundedSender<VrfEvaluatorInput>,
-        prove_sender: mpsc::TrackedUnboundedSender<(
-            BlockProver,
-            StateHash,
-            Box<ProverExtendBlockchainInputStableV2>,
-        )>,
+        signer: Arc<dyn BlockProducerSigner>,
+        vrf_evaluation_sender: mpsc::TrackedUnboundedSender<VrfEvaluationRequest>,
+        prove_sender: mpsc::TrackedUnboundedSender<ProveRequest>,
         provers: Option<BlockProver>,
     ) -> Self {
         Self {
             provers,
-            keypair,
+            signers: [(signer.public_key(), signer.clone())].into(),
+            primary: signer,
             vrf_evaluation_sender,
             prove_sender,
         }
@@ -54,34 +67,70 @@ impl BlockProducerService {
         event_sender: EventSender,
         keypair: AccountSecretKey,
         provers: Option<BlockProver>,
+    ) -> Self {
+        let signer = Arc::new(LocalSigner::new(keypair, provers.clone()));
+        Self::start_with_signer(event_sender, signer, provers)
+    }
+
+    pub fn start_with_signer(
+        event_sender: EventSender,
+        signer: Arc<dyn BlockProducerSigner>,
+        provers: Option<BlockProver>,
     ) -> Self {
         let (vrf_evaluation_sender, vrf_evaluation_receiver) = mpsc::unbounded_channel();
         let (prove_sender, prove_receiver) = mpsc::unbounded_channel();
 
         let event_sender_clone = event_sender.clone();
-        let producer_keypair = keypair.clone();
         thread::Builder::new()
             .name("openmina_vrf_evaluator".to_owned())
             .spawn(move || {
-                vrf_evaluator::vrf_evaluator(
-                    event_sender_clone,
-                    vrf_evaluation_receiver,
-                    producer_keypair.into(),
-                );
+                vrf_evaluator::vrf_evaluator(event_sender_clone, vrf_evaluation_receiver);
             })
             .unwrap();
 
-        let producer_keypair = keypair.clone();
         thread::Builder::new()
             .name("openmina_block_prover".to_owned())
-            .spawn(move || prover_loop(producer_keypair, event_sender, prove_receiver))
+            .spawn(move || prover_loop(event_sender, prove_receiver))
             .unwrap();
 
-        BlockProducerService::new(keypair, vrf_evaluation_sender, prove_sender, provers)
+        BlockProducerService::new(signer, vrf_evaluation_sender, prove_sender, provers)
+    }
+
+    /// Adds another producer key.
+    pub fn add_keypair(&mut self, keypair: AccountSecretKey) {
+        let signer = Arc::new(LocalSigner::new(keypair, self.provers.clone()));
+        self.add_signer(signer);
+    }
+
+    /// Adds another producer key, held by `signer`.
+    pub fn add_signer(&mut self, signer: Arc<dyn BlockProducerSigner>) {
+        self.signers.insert(signer.public_key(), signer);
     }
 
-    pub fn keypair(&self) -> AccountSecretKey {
-        self.keypair.clone()
+    pub fn producer_signer(
+        &self,
+        pub_key: &AccountPublicKey,
+    ) -> Option<&Arc<dyn BlockProducerSigner>> {
+        self.signers.get(pub_key)
+    }
+
+    pub fn producer_keypair(&self, pub_key: &AccountPublicKey) -> Option<&AccountSecretKey> {
+        self.producer_signer(pub_key)?.secret_key()
+    }
+
+    /// Signer of the block creator, which is needed to prove the block.
+    ///
+    /// Falls back to the primary key if the block creator isn't one of
+    /// our keys, which is the case only for the genesis block, which is
+    /// proven without the producer key.
+    pub fn block_creator_signer(
+        &self,
+        input: &ProverExtendBlockchainInputStableV2,
+    ) -> Arc<dyn BlockProducerSigner> {
+        let block_creator = &input.next_state.body.consensus_state.block_creator;
+        self.producer_signer(&block_creator.clone().into())
+            .unwrap_or(&self.primary)
+            .clone()
     }
 
     pub fn vrf_pending_requests(&self) -> usize {
@@ -93,18 +142,10 @@ impl BlockProducerService {
     }
 }
 
-fn prover_loop(
-    keypair: AccountSecretKey,
-    event_sender: EventSender,
-    mut rx: mpsc::TrackedUnboundedReceiver<(
-        BlockProver,
-        StateHash,
-        Box<ProverExtendBlockchainInputStableV2>,
-    )>,
-) {
+fn prover_loop(event_sender: EventSender, mut rx: mpsc::TrackedUnboundedReceiver<ProveRequest>) {
     while let Some(msg) = rx.blocking_recv() {
-        let (provers, block_hash, mut input) = msg.0;
-        let res = prove(provers, &mut input, &keypair, false);
+        let (signer, block_hash, mut input) = msg.0;
+        let res = signer.prove_block(&mut input, false);
         if let Err(error) = &res {
             openmina_core::error!(message = "Block proof failed", error = format!("{error:?}"));
             if let Err(error) = dump_failed_block_proof_input(block_hash.clone(), input, error) {
@@ -167,17 +208,23 @@ impl node::service::BlockProducerService for crate::NodeService {
         if self.replayer.is_some() {
             return;
         }
-        let provers = self.provers();
-        let _ = self
+        let block_producer = self
             .block_producer
             .as_ref()
-            .expect("prove shouldn't be requested if block producer isn't initialized")
+            .expect("prove shouldn't be requested if block producer isn't initialized");
+        let signer = block_producer.block_creator_signer(&input);
+        let _ = block_producer
             .prove_sender
-            .tracked_send((provers, block_hash, input));
+            .tracked_send((signer, block_hash, input));
     }
 
-    fn with_producer_keypair<T>(&self, f: impl FnOnce(&AccountSecretKey) -> T) -> Option<T> {
-        Some(f(&self.block_producer.as_ref()?.keypair))
+    fn with_producer_keypair<T>(
+        &self,
+        pub_key: &AccountPublicKey,
+        f: impl FnOnce(&AccountSecretKey) -> T,
+    ) -> Option<T> {
+        let keypair = self.block_producer.as_ref()?.producer_keypair(pub_key)?;
+        Some(f(keypair))
     }
 }
 
@@ -0,0 +1,527 @@
+//! Block producer signer running in a separate process.
+//!
+//! Node and signer talk over a tcp connection, one request per
+//! connection. Messages are newline terminated json. The signer starts by
+//! sending a random nonce, request and response are then authenticated by
+//! a mac over the nonce and the message, keyed with a secret shared by
+//! the node and the signer.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::Context;
+use mina_p2p_messages::v2::{
+    EpochSeed, MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2,
+};
+use node::{account::AccountPublicKey, block_producer::vrf_evaluator::DelegatorTable};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use vrf::VrfWonSlot;
+
+use super::BlockProducerSigner;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Timeout for sending a message, or for receiving one which doesn't need
+/// any work from the other side.
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+const EVALUATE_VRF_TIMEOUT: Duration = Duration::from_secs(60);
+const PROVE_BLOCK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Largest message accepted, prover input of a full block fits easily.
+const MAX_MESSAGE_SIZE: u64 = 64 * 1024 * 1024;
+/// Largest number of requests the signer serves at once.
+const MAX_CONNECTIONS: usize = 8;
+pub const MIN_SECRET_LEN: usize = 16;
+
+const REQUEST_MAC_DOMAIN: &[u8] = b"openmina-signer-request";
+const RESPONSE_MAC_DOMAIN: &[u8] = b"openmina-signer-response";
+
+type Nonce = [u8; 32];
+type Mac = [u8; 32];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum SignerRequest {
+    PublicKey,
+    EvaluateVrf {
+        epoch_seed: EpochSeed,
+        global_slot: u32,
+        total_currency: u64,
+        delegator_table: DelegatorTable,
+    },
+    ProveBlock {
+        input: Box<ProverExtendBlockchainInputStableV2>,
+        only_verify_constraints: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum SignerResponse {
+    PublicKey(AccountPublicKey),
+    EvaluateVrf(Option<VrfWonSlot>),
+    ProveBlock(Arc<MinaBaseProofStableV2>),
+    Error(String),
+}
+
+/// Request or response with the mac authenticating it.
+#[derive(Serialize, Deserialize, Debug)]
+struct SignedMessage {
+    mac: Mac,
+    /// Json of the request or response.
+    body: String,
+}
+
+/// Client of the signer process listening on `addr`.
+pub struct RemoteSigner {
+    addr: SocketAddr,
+    secret: Vec<u8>,
+    public_key: AccountPublicKey,
+}
+
+impl RemoteSigner {
+    /// Connects to the signer and asks for its public key.
+    pub fn connect(addr: SocketAddr, secret: impl Into<Vec<u8>>) -> anyhow::Result<Self> {
+        let secret = secret.into();
+        check_secret(&secret)?;
+        let public_key = match request(addr, &secret, &SignerRequest::PublicKey, IO_TIMEOUT)? {
+            SignerResponse::PublicKey(public_key) => public_key,
+            resp => anyhow::bail!("unexpected response from block producer signer: {resp:?}"),
+        };
+        Ok(Self {
+            addr,
+            secret,
+            public_key,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl BlockProducerSigner for RemoteSigner {
+    fn public_key(&self) -> AccountPublicKey {
+        self.public_key.clone()
+    }
+
+    fn evaluate_vrf(
+        &self,
+        epoch_seed: &EpochSeed,
+        global_slot: u32,
+        total_currency: u64,
+        delegator_table: &DelegatorTable,
+    ) -> anyhow::Result<Option<VrfWonSlot>> {
+        let req = SignerRequest::EvaluateVrf {
+            epoch_seed: epoch_seed.clone(),
+            global_slot,
+            total_currency,
+            delegator_table: delegator_table.clone(),
+        };
+        match request(self.addr, &self.secret, &req, EVALUATE_VRF_TIMEOUT)? {
+            SignerResponse::EvaluateVrf(won_slot) => Ok(won_slot),
+            SignerResponse::Error(error) => anyhow::bail!(error),
+            resp => anyhow::bail!("unexpected response from block producer signer: {resp:?}"),
+        }
+    }
+
+    fn prove_block(
+        &self,
+        input: &mut ProverExtendBlockchainInputStableV2,
+        only_verify_constraints: bool,
+    ) -> anyhow::Result<Arc<MinaBaseProofStableV2>> {
+        let req = SignerRequest::ProveBlock {
+            input: Box::new(input.clone()),
+            only_verify_constraints,
+        };
+        match request(self.addr, &self.secret, &req, PROVE_BLOCK_TIMEOUT)? {
+            SignerResponse::ProveBlock(proof) => Ok(proof),
+            SignerResponse::Error(error) => anyhow::bail!(error),
+            resp => anyhow::bail!("unexpected response from block producer signer: {resp:?}"),
+        }
+    }
+}
+
+fn check_secret(secret: &[u8]) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        secret.len() >= MIN_SECRET_LEN,
+        "block producer signer secret must be at least {MIN_SECRET_LEN} bytes long"
+    );
+    Ok(())
+}
+
+fn mac(secret: &[u8], nonce: &Nonce, domain: &[u8], body: &[u8]) -> Mac {
+    Sha3_256::new()
+        .chain_update((secret.len() as u64).to_le_bytes())
+        .chain_update(secret)
+        .chain_update(domain)
+        .chain_update(nonce)
+        .chain_update(body)
+        .finalize()
+        .into()
+}
+
+impl SignedMessage {
+    fn new(secret: &[u8], nonce: &Nonce, domain: &[u8], msg: &impl Serialize) -> Self {
+        let body = serde_json::to_string(msg).expect("signer messages are serializable");
+        Self {
+            mac: mac(secret, nonce, domain, body.as_bytes()),
+            body,
+        }
+    }
+
+    fn open<T: DeserializeOwned>(
+        self,
+        secret: &[u8],
+        nonce: &Nonce,
+        domain: &[u8],
+    ) -> anyhow::Result<T> {
+        let expected = mac(secret, nonce, domain, self.body.as_bytes());
+        // constant time, so that the mac can't be guessed byte by byte
+        let diff = expected
+            .iter()
+            .zip(&self.mac)
+            .fold(0, |acc, (a, b)| acc | (a ^ b));
+        anyhow::ensure!(
+            diff == 0,
+            "message isn't authenticated by the shared secret"
+        );
+        Ok(serde_json::from_str(&self.body)?)
+    }
+}
+
+fn request(
+    addr: SocketAddr,
+    secret: &[u8],
+    req: &SignerRequest,
+    timeout: Duration,
+) -> anyhow::Result<SignerResponse> {
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+        .with_context(|| format!("failed to connect to block producer signer at {addr}"))?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let nonce: Nonce = read_message(&mut reader)?;
+    write_message(
+        &mut stream,
+        &SignedMessage::new(secret, &nonce, REQUEST_MAC_DOMAIN, req),
+    )?;
+    stream.set_read_timeout(Some(timeout))?;
+    let resp: SignedMessage = read_message(&mut reader)?;
+    resp.open(secret, &nonce, RESPONSE_MAC_DOMAIN)
+        .context("invalid response from block producer signer")
+}
+
+fn write_message(stream: &mut TcpStream, msg: &impl Serialize) -> anyhow::Result<()> {
+    let mut bytes = serde_json::to_vec(msg)?;
+    bytes.push(b'\n');
+    stream.write_all(&bytes)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_message<T: DeserializeOwned>(reader: &mut BufReader<TcpStream>) -> anyhow::Result<T> {
+    let mut line = Vec::new();
+    if reader
+        .by_ref()
+        .take(MAX_MESSAGE_SIZE)
+        .read_until(b'\n', &mut line)?
+        == 0
+    {
+        anyhow::bail!("connection closed");
+    }
+    if line.last() != Some(&b'\n') {
+        anyhow::bail!("message truncated or larger than {MAX_MESSAGE_SIZE} bytes");
+    }
+    Ok(serde_json::from_slice(&line)?)
+}
+
+/// Decrements the number of connections being served when dropped.
+struct ConnectionGuard(Arc<AtomicUsize>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Serves requests of [`RemoteSigner`]s using `signer`.
+///
+/// Meant for running the signer next to the key, on a host which isn't
+/// reachable from the internet. Only requests authenticated by `secret`
+/// are served.
+pub fn serve(
+    listener: TcpListener,
+    signer: Arc<dyn BlockProducerSigner>,
+    secret: impl Into<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let secret: Vec<u8> = secret.into();
+    let secret: Arc<[u8]> = secret.into();
+    check_secret(&secret)?;
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                openmina_core::warn!(
+                    message = "Block producer signer failed to accept connection",
+                    error = error.to_string()
+                );
+                continue;
+            }
+        };
+        if connections.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::AcqRel);
+            openmina_core::warn!(
+                message = "Block producer signer refused connection, too many connections",
+                peer = format!("{:?}", stream.peer_addr())
+            );
+            continue;
+        }
+        let guard = ConnectionGuard(connections.clone());
+        let signer = signer.clone();
+        let secret = secret.clone();
+        // Proving takes a while, don't block vrf evaluation meanwhile.
+        std::thread::spawn(move || {
+            let _guard = guard;
+            if let Err(error) = handle_connection(stream, &*signer, &secret) {
+                openmina_core::warn!(
+                    message = "Block producer signer request failed",
+                    error = format!("{error:?}")
+                );
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    signer: &dyn BlockProducerSigner,
+    secret: &[u8],
+) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let nonce: Nonce = rand::random();
+    write_message(&mut stream, &nonce)?;
+    let req: SignedMessage = read_message(&mut reader)?;
+    let req: SignerRequest = req.open(secret, &nonce, REQUEST_MAC_DOMAIN)?;
+
+    let resp = match req {
+        SignerRequest::PublicKey => Ok(SignerResponse::PublicKey(signer.public_key())),
+        SignerRequest::EvaluateVrf {
+            epoch_seed,
+            global_slot,
+            total_currency,
+            delegator_table,
+        } => signer
+            .evaluate_vrf(&epoch_seed, global_slot, total_currency, &delegator_table)
+            .map(SignerResponse::EvaluateVrf),
+        SignerRequest::ProveBlock {
+            mut input,
+            only_verify_constraints,
+        } => signer
+            .prove_block(&mut input, only_verify_constraints)
+            .map(SignerResponse::ProveBlock),
+    };
+    let resp = resp.unwrap_or_else(|error| SignerResponse::Error(format!("{error:?}")));
+    write_message(
+        &mut stream,
+        &SignedMessage::new(secret, &nonce, RESPONSE_MAC_DOMAIN, &resp),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ledger::{scan_state::pending_coinbase::PendingCoinbase, AccountIndex};
+    use mina_p2p_messages::{binprot::BinProtRead, list::List, v2};
+    use node::{account::AccountSecretKey, core::constants::constraint_constants};
+
+    use super::super::LocalSigner;
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef";
+
+    fn key() -> AccountSecretKey {
+        AccountSecretKey::from_str("EKEEpMELfQkMbJDt2fB4cFXKwSf1x4t7YD4twREy5yuJ84HBZtF9").unwrap()
+    }
+
+    #[test]
+    fn test_remote_signer_matches_local() {
+        let key = key();
+        let local = Arc::new(LocalSigner::new(key.clone(), None));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stub_signer = local.clone();
+        std::thread::spawn(move || serve(listener, stub_signer, SECRET));
+
+        let remote = RemoteSigner::connect(addr, SECRET).unwrap();
+        assert_eq!(remote.public_key(), key.public_key());
+
+        let epoch_seed =
+            EpochSeed::from_str("2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA").unwrap();
+        let delegator_table: DelegatorTable = [(
+            AccountIndex(2),
+            (
+                AccountSecretKey::genesis_producer().public_key(),
+                1_000_000_000_000_000,
+            ),
+        )]
+        .into();
+        let total_currency = 6_000_000_000_001_000;
+
+        for global_slot in [6, 518] {
+            let expected = local
+                .evaluate_vrf(&epoch_seed, global_slot, total_currency, &delegator_table)
+                .unwrap();
+            let won_slot = remote
+                .evaluate_vrf(&epoch_seed, global_slot, total_currency, &delegator_table)
+                .unwrap();
+            assert_eq!(won_slot, expected);
+            assert_eq!(won_slot.is_some(), global_slot == 6);
+        }
+    }
+
+    /// Signer whose prover fails, e.g. because it ran out of memory.
+    struct FailingSigner(LocalSigner);
+
+    impl BlockProducerSigner for FailingSigner {
+        fn public_key(&self) -> AccountPublicKey {
+            self.0.public_key()
+        }
+
+        fn evaluate_vrf(
+            &self,
+            epoch_seed: &EpochSeed,
+            global_slot: u32,
+            total_currency: u64,
+            delegator_table: &DelegatorTable,
+        ) -> anyhow::Result<Option<VrfWonSlot>> {
+            self.0
+                .evaluate_vrf(epoch_seed, global_slot, total_currency, delegator_table)
+        }
+
+        fn prove_block(
+            &self,
+            _input: &mut ProverExtendBlockchainInputStableV2,
+            _only_verify_constraints: bool,
+        ) -> anyhow::Result<Arc<MinaBaseProofStableV2>> {
+            anyhow::bail!("block prover crashed")
+        }
+    }
+
+    /// Prover input extending the block of the archive breadcrumb fixture.
+    fn prover_input() -> ProverExtendBlockchainInputStableV2 {
+        let bytes = include_bytes!("../../../../../tests/files/archive-breadcrumb/3NK56ZbCS31qb8SvCtCCYza4beRDtKgXA2JL6s3evKouG2KkKtiy.bin");
+        let diff = v2::ArchiveTransitionFrontierDiff::binprot_read(&mut bytes.as_slice()).unwrap();
+        let v2::ArchiveTransitionFrontierDiff::BreadcrumbAdded {
+            block: (block, _), ..
+        } = diff
+        else {
+            panic!("expected a breadcrumb");
+        };
+        let state = block.header.protocol_state;
+        let consensus_state = &state.body.consensus_state;
+
+        ProverExtendBlockchainInputStableV2 {
+            chain: v2::BlockchainSnarkBlockchainStableV2 {
+                state: state.clone(),
+                proof: block.header.protocol_state_proof.clone(),
+            },
+            next_state: state.clone(),
+            block: v2::MinaStateSnarkTransitionValueStableV2 {
+                blockchain_state: state.body.blockchain_state.clone(),
+                consensus_transition: consensus_state
+                    .curr_global_slot_since_hard_fork
+                    .slot_number
+                    .clone(),
+                pending_coinbase_update: v2::MinaBasePendingCoinbaseUpdateStableV1::zero(),
+            },
+            ledger_proof: None,
+            prover_state: v2::ConsensusStakeProofStableV2 {
+                delegator: v2::MinaBaseAccountIndexStableV1(0u64.into()),
+                delegator_pk: consensus_state.block_stake_winner.clone(),
+                coinbase_receiver_pk: consensus_state.coinbase_receiver.clone(),
+                ledger: v2::MinaBaseSparseLedgerBaseStableV2 {
+                    indexes: List::new(),
+                    depth: 35u64.into(),
+                    tree: v2::MinaBaseSparseLedgerBaseStableV2Tree::Hash(
+                        consensus_state.staking_epoch_data.ledger.hash.clone(),
+                    ),
+                },
+                producer_private_key: (&key()).into(),
+                producer_public_key: consensus_state.block_creator.clone(),
+            },
+            pending_coinbase: v2::MinaBasePendingCoinbaseWitnessStableV2 {
+                pending_coinbases: (&PendingCoinbase::create(
+                    constraint_constants().pending_coinbase_depth,
+                ))
+                    .into(),
+                is_new_stack: true,
+            },
+        }
+    }
+
+    #[test]
+    fn test_remote_signer_prove_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stub_signer = Arc::new(FailingSigner(LocalSigner::new(key(), None)));
+        std::thread::spawn(move || serve(listener, stub_signer, SECRET));
+
+        let remote = RemoteSigner::connect(addr, SECRET).unwrap();
+        let error = remote
+            .prove_block(&mut prover_input(), false)
+            .expect_err("failed proof must be reported as an error");
+        assert!(error.to_string().contains("block prover crashed"));
+
+        // Signer keeps serving after a failed proof.
+        assert!(RemoteSigner::connect(addr, SECRET).is_ok());
+    }
+
+    #[test]
+    fn test_remote_signer_requires_secret() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stub_signer = Arc::new(LocalSigner::new(key(), None));
+        std::thread::spawn(move || serve(listener, stub_signer, SECRET));
+
+        assert!(RemoteSigner::connect(addr, "fedcba9876543210").is_err());
+        assert!(RemoteSigner::connect(addr, "short").is_err());
+        assert!(RemoteSigner::connect(addr, SECRET).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_message_rejected() {
+        let nonce = [7; 32];
+        let msg = SignedMessage::new(
+            SECRET.as_bytes(),
+            &nonce,
+            REQUEST_MAC_DOMAIN,
+            &SignerRequest::PublicKey,
+        );
+        let mut mac = msg.mac;
+        mac[0] ^= 1;
+        let tampered = SignedMessage {
+            mac,
+            body: msg.body.clone(),
+        };
+        assert!(tampered
+            .open::<SignerRequest>(SECRET.as_bytes(), &nonce, REQUEST_MAC_DOMAIN)
+            .is_err());
+        // replayed on another connection
+        assert!(msg
+            .open::<SignerRequest>(SECRET.as_bytes(), &[8; 32], REQUEST_MAC_DOMAIN)
+            .is_err());
+    }
+}
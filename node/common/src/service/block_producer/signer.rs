@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use ledger::proofs::provers::BlockProver;
+use mina_p2p_messages::v2::{
+    EpochSeed, MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2,
+};
+use mina_signer::Keypair;
+use node::{
+    account::{AccountPublicKey, AccountSecretKey},
+    block_producer::vrf_evaluator::DelegatorTable,
+};
+use vrf::{VrfEvaluationInput, VrfEvaluationOutput, VrfWonSlot};
+
+/// Does everything that needs the producer's private key.
+///
+/// Block proof needs the private key as a witness, so signing the block
+/// means proving it. With [`super::RemoteSigner`] both are done by the
+/// signer process and the key never enters the node.
+pub trait BlockProducerSigner: Send + Sync {
+    fn public_key(&self) -> AccountPublicKey;
+
+    /// Secret key, if it is held by this process.
+    fn secret_key(&self) -> Option<&AccountSecretKey> {
+        None
+    }
+
+    /// Evaluates vrf for delegators in `delegator_table` and returns the
+    /// first won slot.
+    fn evaluate_vrf(
+        &self,
+        epoch_seed: &EpochSeed,
+        global_slot: u32,
+        total_currency: u64,
+        delegator_table: &DelegatorTable,
+    ) -> anyhow::Result<Option<VrfWonSlot>>;
+
+    fn prove_block(
+        &self,
+        input: &mut ProverExtendBlockchainInputStableV2,
+        only_verify_constraints: bool,
+    ) -> anyhow::Result<Arc<MinaBaseProofStableV2>>;
+}
+
+/// Signer with the key loaded into this process.
+pub struct LocalSigner {
+    key: AccountSecretKey,
+    keypair: Keypair,
+    provers: Option<BlockProver>,
+}
+
+impl LocalSigner {
+    pub fn new(key: AccountSecretKey, provers: Option<BlockProver>) -> Self {
+        Self {
+            keypair: key.clone().into(),
+            key,
+            provers,
+        }
+    }
+}
+
+impl BlockProducerSigner for LocalSigner {
+    fn public_key(&self) -> AccountPublicKey {
+        self.key.public_key()
+    }
+
+    fn secret_key(&self) -> Option<&AccountSecretKey> {
+        Some(&self.key)
+    }
+
+    fn evaluate_vrf(
+        &self,
+        epoch_seed: &EpochSeed,
+        global_slot: u32,
+        total_currency: u64,
+        delegator_table: &DelegatorTable,
+    ) -> anyhow::Result<Option<VrfWonSlot>> {
+        for (index, (pub_key, stake)) in delegator_table {
+            let vrf_input = VrfEvaluationInput {
+                producer_key: self.keypair.clone(),
+                global_slot,
+                epoch_seed: epoch_seed.clone(),
+                account_pub_key: pub_key.clone(),
+                delegator_index: *index,
+                delegated_stake: (*stake).into(),
+                total_currency: total_currency.into(),
+            };
+
+            // the first delegate that won the slot
+            if let VrfEvaluationOutput::SlotWon(won_slot) = vrf::evaluate_vrf(vrf_input)? {
+                return Ok(Some(won_slot));
+            }
+        }
+        Ok(None)
+    }
+
+    fn prove_block(
+        &self,
+        input: &mut ProverExtendBlockchainInputStableV2,
+        only_verify_constraints: bool,
+    ) -> anyhow::Result<Arc<MinaBaseProofStableV2>> {
+        let provers = self
+            .provers
+            .clone()
+            .unwrap_or_else(BlockProver::get_once_made);
+        super::prove(provers, input, &self.key, only_verify_constraints)
+    }
+}
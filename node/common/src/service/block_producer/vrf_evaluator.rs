@@ -1,57 +1,74 @@
-use mina_signer::Keypair;
+use anyhow::Context;
 use node::{
     block_producer::BlockProducerVrfEvaluatorEvent,
     block_producer::{
-        vrf_evaluator::{VrfEvaluationOutputWithHash, VrfEvaluatorInput},
+        vrf_evaluator::{won_slot_tie_breaker, VrfEvaluationOutputWithHash, VrfEvaluatorInput},
         BlockProducerEvent,
     },
     core::channels::mpsc::{TrackedUnboundedReceiver, UnboundedSender},
     event_source::Event,
 };
-use vrf::{VrfEvaluationInput, VrfEvaluationOutput};
+use vrf::VrfEvaluationOutput;
 
 use crate::NodeService;
 
+use super::{Signers, VrfEvaluationRequest};
+
 pub fn vrf_evaluator(
     event_sender: UnboundedSender<Event>,
-    mut vrf_evaluation_receiver: TrackedUnboundedReceiver<VrfEvaluatorInput>,
-    keypair: Keypair,
+    mut vrf_evaluation_receiver: TrackedUnboundedReceiver<VrfEvaluationRequest>,
 ) {
-    while let Some(vrf_evaluator_input) = vrf_evaluation_receiver.blocking_recv() {
+    while let Some(msg) = vrf_evaluation_receiver.blocking_recv() {
         // let bytes = serde_json::to_string(&vrf_evaluator_input).unwrap();
         // openmina_core::http::download("vrf.json".to_string(), bytes.as_bytes().to_vec()).unwrap();
 
-        let keypair = &keypair;
+        let (vrf_evaluator_input, signers) = &*msg;
         let VrfEvaluatorInput {
             epoch_seed,
-            delegator_table,
+            delegator_tables,
             global_slot,
             total_currency,
             staking_ledger_hash: _,
-        } = &*vrf_evaluator_input;
+        } = vrf_evaluator_input;
 
-        let vrf_result = delegator_table
+        let won_slots = delegator_tables
             .iter()
-            .find_map(|(index, (pub_key, stake))| {
-                let vrf_input = VrfEvaluationInput {
-                    producer_key: keypair.clone(),
-                    global_slot: *global_slot,
-                    epoch_seed: epoch_seed.clone(),
-                    account_pub_key: pub_key.clone(),
-                    delegator_index: *index,
-                    delegated_stake: (*stake).into(),
-                    total_currency: (*total_currency).into(),
-                };
-
-                let vrf_result = vrf::evaluate_vrf(vrf_input).unwrap();
-
-                // the first delegate that won the slot
-                if let VrfEvaluationOutput::SlotWon(_) = vrf_result {
-                    return Some(vrf_result);
-                }
-                None
+            .filter_map(|(producer, delegator_table)| {
+                let signer = signers.get(producer)?;
+                signer
+                    .evaluate_vrf(epoch_seed, *global_slot, *total_currency, delegator_table)
+                    .with_context(|| format!("vrf evaluation for {producer} failed"))
+                    .transpose()
             })
-            .unwrap_or(VrfEvaluationOutput::SlotLost(*global_slot));
+            .collect::<anyhow::Result<Vec<_>>>();
+
+        // A failed evaluation doesn't mean the slot is lost, the state
+        // machine retries it.
+        let won_slots = match won_slots {
+            Ok(won_slots) => won_slots,
+            Err(error) => {
+                let _ = event_sender.send(
+                    BlockProducerEvent::VrfEvaluator(
+                        BlockProducerVrfEvaluatorEvent::EvaluationFailed {
+                            global_slot: *global_slot,
+                            error: format!("{error:?}"),
+                        },
+                    )
+                    .into(),
+                );
+                continue;
+            }
+        };
+
+        // Only one block can be produced for the slot, so if more of our
+        // keys won it, pick the one which would win the fork choice.
+        let vrf_result = won_slots
+            .into_iter()
+            .max_by_key(won_slot_tie_breaker)
+            .map_or(
+                VrfEvaluationOutput::SlotLost(*global_slot),
+                VrfEvaluationOutput::SlotWon,
+            );
 
         let vrf_result_with_hash = VrfEvaluationOutputWithHash::new(
             vrf_result,
@@ -73,7 +90,15 @@ impl node::block_producer_effectful::vrf_evaluator_effectful::BlockProducerVrfEv
 {
     fn evaluate(&mut self, data: VrfEvaluatorInput) {
         if let Some(bp) = self.block_producer.as_mut() {
-            let _ = bp.vrf_evaluation_sender.tracked_send(data);
+            let signers: Signers = data
+                .delegator_tables
+                .keys()
+                .filter_map(|producer| {
+                    let signer = bp.producer_signer(producer)?.clone();
+                    Some((producer.clone(), signer))
+                })
+                .collect();
+            let _ = bp.vrf_evaluation_sender.tracked_send((data, signers));
         }
     }
 }
@@ -83,7 +108,9 @@ mod tests {
     use std::str::FromStr;
 
     // use mina_signer::keypair;
+    use mina_signer::Keypair;
     use node::account::AccountSecretKey;
+    use vrf::VrfEvaluationInput;
 
     use super::*;
 
@@ -99,7 +126,7 @@ mod tests {
 
         let VrfEvaluatorInput {
             epoch_seed,
-            delegator_table,
+            delegator_tables,
             global_slot,
             total_currency,
             staking_ledger_hash: _,
@@ -107,8 +134,9 @@ mod tests {
 
         let now = std::time::Instant::now();
 
-        let vrf_result = delegator_table
-            .iter()
+        let vrf_result = delegator_tables
+            .values()
+            .flatten()
             .map(|(index, (pub_key, stake))| {
                 let vrf_input = VrfEvaluationInput {
                     producer_key: keypair.clone(),
@@ -138,7 +166,7 @@ mod tests {
 
         let elapsed = now.elapsed();
         let slot = vrf_evaluator_input.global_slot;
-        let ndelegator = vrf_evaluator_input.delegator_table.len();
+        let ndelegator: usize = delegator_tables.values().map(|table| table.len()).sum();
         // let nevaluated = nevaluated.load(std::sync::atomic::Ordering::Relaxed);
         eprintln!("TOTAL vrf::evaluate_vrf: {elapsed:?} slot:{slot:?} ndelegators:{ndelegator:?}");
         dbg!(vrf_result);
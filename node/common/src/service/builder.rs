@@ -1,3 +1,5 @@
+use std::{path::PathBuf, sync::Arc};
+
 use ledger::proofs::provers::BlockProver;
 use node::{
     account::AccountSecretKey,
@@ -24,8 +26,9 @@ use crate::{
 };
 
 use super::{
-    archive::{config::ArchiveStorageOptions, ArchiveService},
-    block_producer::BlockProducerService,
+    archive::{cursor::ArchiveCursorFile, sink::ArchiveSinks, ArchiveService},
+    block_producer::{BlockProducerService, BlockProducerSigner},
+    p2p::{P2pAddressBookFile, P2pTrustFile},
 };
 
 pub struct NodeServiceCommonBuilder {
@@ -36,9 +39,12 @@ pub struct NodeServiceCommonBuilder {
     event_sender: EventSender,
     event_receiver: EventReceiver,
     ledger_manager: Option<LedgerManager>,
+    ledger_persistence_dir: Option<PathBuf>,
     block_producer: Option<BlockProducerService>,
     archive: Option<ArchiveService>,
     p2p: Option<P2pServiceCtx>,
+    p2p_trust_file: Option<P2pTrustFile>,
+    p2p_address_book_file: Option<P2pAddressBookFile>,
     gather_stats: bool,
     rpc: RpcService,
 }
@@ -60,9 +66,12 @@ impl NodeServiceCommonBuilder {
             event_sender,
             event_receiver: event_receiver.into(),
             ledger_manager: None,
+            ledger_persistence_dir: None,
             block_producer: None,
             archive: None,
             p2p: None,
+            p2p_trust_file: None,
+            p2p_address_book_file: None,
             rpc: RpcService::new(),
             gather_stats: false,
         }
@@ -76,12 +85,29 @@ impl NodeServiceCommonBuilder {
         self.rpc.req_sender()
     }
 
+    /// Keep the root snarked ledger and the epoch ledgers on disk, in `directory`.
+    ///
+    /// Must be called before [`Self::ledger_init`].
+    pub fn ledger_persistence(&mut self, directory: PathBuf) -> &mut Self {
+        self.ledger_persistence_dir = Some(directory);
+        self
+    }
+
     pub fn ledger_init(&mut self) -> &mut Self {
         let mut ctx = LedgerCtx::default();
         ctx.set_event_sender(self.event_sender.clone());
         if self.archive.is_some() {
             ctx.set_archive_mode();
         };
+        if let Some(directory) = self.ledger_persistence_dir.take() {
+            if let Err(error) = ctx.enable_persistence(directory.clone()) {
+                openmina_core::error!(
+                    message = "Failed to load persisted ledgers, persistence disabled",
+                    directory = directory.display().to_string(),
+                    error = error.to_string()
+                );
+            }
+        }
         self.ledger_manager = Some(LedgerManager::spawn(ctx));
         self
     }
@@ -99,8 +125,44 @@ impl NodeServiceCommonBuilder {
         self
     }
 
-    pub fn archive_init(&mut self, options: ArchiveStorageOptions, work_dir: String) -> &mut Self {
-        self.archive = Some(ArchiveService::start(options, work_dir));
+    /// Initializes block producer, which doesn't hold the key itself,
+    /// but leaves vrf evaluation and block proving to `signer`.
+    pub fn block_producer_init_with_signer(
+        &mut self,
+        signer: Arc<dyn BlockProducerSigner>,
+        provers: Option<BlockProver>,
+    ) -> &mut Self {
+        self.block_producer = Some(BlockProducerService::start_with_signer(
+            self.event_sender.clone(),
+            signer,
+            provers,
+        ));
+        self
+    }
+
+    /// Adds another producer key. Block producer must be initialized
+    /// with [`Self::block_producer_init`] first.
+    pub fn block_producer_add_key(&mut self, keypair: AccountSecretKey) -> &mut Self {
+        if let Some(block_producer) = self.block_producer.as_mut() {
+            block_producer.add_keypair(keypair);
+        }
+        self
+    }
+
+    /// Adds another producer key, held by `signer`.
+    pub fn block_producer_add_signer(&mut self, signer: Arc<dyn BlockProducerSigner>) -> &mut Self {
+        if let Some(block_producer) = self.block_producer.as_mut() {
+            block_producer.add_signer(signer);
+        }
+        self
+    }
+
+    pub fn archive_init(
+        &mut self,
+        sinks: ArchiveSinks,
+        cursor_file: Option<ArchiveCursorFile>,
+    ) -> &mut Self {
+        self.archive = Some(ArchiveService::start(sinks, cursor_file));
         self
     }
 
@@ -117,6 +179,18 @@ impl NodeServiceCommonBuilder {
         self
     }
 
+    /// Persist peer trust scores and bans to `file` whenever bans change.
+    pub fn p2p_trust_persistence(&mut self, file: P2pTrustFile) -> &mut Self {
+        self.p2p_trust_file = Some(file);
+        self
+    }
+
+    /// Periodically persist the p2p address book to `file`.
+    pub fn p2p_address_book_persistence(&mut self, file: P2pAddressBookFile) -> &mut Self {
+        self.p2p_address_book_file = Some(file);
+        self
+    }
+
     pub fn gather_stats(&mut self) -> &mut Self {
         self.gather_stats = true;
         self
@@ -147,9 +221,11 @@ impl NodeServiceCommonBuilder {
             ledger_manager,
             block_producer: self.block_producer,
             // initialized in state machine.
-            snark_worker: None,
+            snark_workers: Default::default(),
             archive: self.archive,
             p2p,
+            p2p_trust_file: self.p2p_trust_file,
+            p2p_address_book_file: self.p2p_address_book_file,
             stats: self.gather_stats.then(Stats::new),
             rpc: self.rpc,
             recorder: Default::default(),
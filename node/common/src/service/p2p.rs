@@ -1,16 +1,24 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
 
 use node::{
-    core::channels::mpsc,
+    core::{channels::mpsc, thread},
     event_source::Event,
     p2p::{
+        address_book::P2pAddressBook,
         connection::outgoing::P2pConnectionOutgoingInitOpts,
         identity::{EncryptableType, PublicKey},
+        trust::P2pTrustState,
         webrtc::ConnectionAuth,
         PeerId,
     },
 };
 use rand::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
 #[cfg(feature = "p2p-libp2p")]
 use sha3::digest::XofReader;
 
@@ -18,6 +26,138 @@ pub use node::p2p::{service::*, service_impl::*};
 
 use crate::NodeService;
 
+/// Peer trust scores and bans, persisted as json so that bans survive restarts.
+pub struct P2pTrustFile {
+    writer: JsonFileWriter<P2pTrustState>,
+}
+
+impl P2pTrustFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            writer: JsonFileWriter::spawn("p2p_trust_file", path.into()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.writer.path
+    }
+
+    /// Missing file means that no peer was penalized yet.
+    pub fn load(&self) -> io::Result<P2pTrustState> {
+        load_json(self.path())
+    }
+
+    /// Writes the state on a background thread, see [`JsonFileWriter`].
+    pub fn save(&self, trust: P2pTrustState) {
+        self.writer.write(trust)
+    }
+}
+
+/// Peers seen by the node, persisted as json so that the node can bootstrap
+/// from them after a restart.
+pub struct P2pAddressBookFile {
+    writer: JsonFileWriter<P2pAddressBook>,
+}
+
+impl P2pAddressBookFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            writer: JsonFileWriter::spawn("p2p_address_book_file", path.into()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.writer.path
+    }
+
+    /// Missing file means that the node never ran with this work dir.
+    pub fn load(&self) -> io::Result<P2pAddressBook> {
+        load_json(self.path())
+    }
+
+    /// Writes the address book on a background thread, see [`JsonFileWriter`].
+    pub fn save(&self, address_book: P2pAddressBook) {
+        self.writer.write(address_book)
+    }
+}
+
+/// Serializes and writes values on its own thread, so that the state
+/// machine isn't blocked on disk io. When values are queued faster than
+/// they are written, only the latest one is written. Values queued before
+/// the writer is dropped are still written.
+struct JsonFileWriter<T> {
+    path: PathBuf,
+    sender: Option<mpsc::UnboundedSender<T>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl<T: 'static + Send + Serialize> JsonFileWriter<T> {
+    fn spawn(name: &str, path: PathBuf) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<T>();
+        let thread_path = path.clone();
+        let thread = thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || {
+                while let Some(mut value) = receiver.blocking_recv() {
+                    while let Ok(newer) = receiver.try_recv() {
+                        value = newer;
+                    }
+                    if let Err(error) = save_json(&thread_path, &value) {
+                        openmina_core::error!(
+                            message = "Failed to persist p2p state",
+                            path = thread_path.display().to_string(),
+                            error = error.to_string()
+                        );
+                    }
+                }
+            })
+            .expect("failed to spawn json file writer thread");
+        Self {
+            path,
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    fn write(&self, value: T) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(value);
+        }
+    }
+}
+
+impl<T> Drop for JsonFileWriter<T> {
+    fn drop(&mut self) {
+        // closing the channel stops the thread once queued values are written
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn load_json<T: DeserializeOwned + Default>(path: &Path) -> io::Result<T> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Default::default()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes to a temporary file first, so that a crash mid-write doesn't
+/// lose the previously persisted state.
+fn save_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let bytes = serde_json::to_vec(value)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, bytes)?;
+    fs::rename(tmp_path, path)
+}
+
 impl webrtc::P2pServiceWebrtc for NodeService {
     type Event = Event;
 
@@ -96,6 +236,18 @@ impl webrtc_with_libp2p::P2pServiceWebrtcWithLibp2p for NodeService {
     fn connections(&self) -> std::collections::BTreeSet<PeerId> {
         self.p2p.webrtc.peers.keys().copied().collect()
     }
+
+    fn trust_persist(&mut self, trust: &P2pTrustState) {
+        if let Some(file) = &self.p2p_trust_file {
+            file.save(trust.clone());
+        }
+    }
+
+    fn address_book_persist(&mut self, address_book: &P2pAddressBook) {
+        if let Some(file) = &self.p2p_address_book_file {
+            file.save(address_book.clone());
+        }
+    }
 }
 
 #[cfg(feature = "p2p-libp2p")]
@@ -148,3 +300,24 @@ impl P2pCryptoService for NodeService {
         pk.verify(&msg, sig)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_file_writer_writes_latest() {
+        let dir = std::env::temp_dir().join(format!("openmina-json-writer-{}", std::process::id()));
+        let path = dir.join("values.json");
+        let writer = JsonFileWriter::spawn("test_json_file_writer", path.clone());
+        for i in 0..100_u32 {
+            writer.write(vec![i; 3]);
+        }
+        drop(writer);
+
+        let loaded: Vec<u32> = load_json(&path).unwrap();
+        assert_eq!(loaded, vec![99; 3]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -8,16 +8,19 @@ pub mod transaction_pool;
 pub mod transition_frontier;
 
 use node::rpc::{
-    RpcBestChainResponse, RpcBlockProducerStatsGetResponse, RpcConsensusConstantsGetResponse,
-    RpcConsensusTimeGetResponse, RpcDiscoveryBoostrapStatsResponse,
-    RpcDiscoveryRoutingTableResponse, RpcGenesisBlockResponse, RpcGetBlockResponse,
-    RpcHealthCheckResponse, RpcHeartbeatGetResponse, RpcLedgerAccountDelegatorsGetResponse,
-    RpcLedgerAccountsResponse, RpcLedgerSlimAccountsResponse, RpcLedgerStatusGetResponse,
-    RpcMessageProgressResponse, RpcPeersGetResponse, RpcPooledUserCommandsResponse,
-    RpcPooledZkappCommandsResponse, RpcReadinessCheckResponse, RpcRequest,
-    RpcSnarkPoolCompletedJobsResponse, RpcSnarkPoolPendingJobsGetResponse, RpcStateGetError,
-    RpcStatusGetResponse, RpcTransactionInjectResponse, RpcTransactionPoolResponse,
-    RpcTransactionStatusGetResponse, RpcTransitionFrontierUserCommandsResponse,
+    RpcArchiveBackfillResponse, RpcArchiveStatusGetResponse, RpcBestChainResponse,
+    RpcBlockProducerScheduleGetResponse, RpcBlockProducerStatsGetResponse,
+    RpcConsensusConstantsGetResponse, RpcConsensusTimeGetResponse,
+    RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse, RpcGenesisBlockResponse,
+    RpcGetBlockResponse, RpcHealthCheckResponse, RpcHeartbeatGetResponse,
+    RpcLedgerAccountDelegatorsGetResponse, RpcLedgerAccountsResponse,
+    RpcLedgerSlimAccountsResponse, RpcLedgerStatusGetResponse, RpcMessageProgressResponse,
+    RpcP2pBanResponse, RpcP2pBandwidthGetResponse, RpcP2pBansGetResponse, RpcP2pUnbanResponse,
+    RpcPeersGetResponse, RpcPooledUserCommandsResponse, RpcPooledZkappCommandsResponse,
+    RpcReadinessCheckResponse, RpcRequest, RpcSnarkPoolCompletedJobsResponse,
+    RpcSnarkPoolPendingJobsGetResponse, RpcStateGetError, RpcStatusGetResponse,
+    RpcTransactionInjectResponse, RpcTransactionPoolResponse, RpcTransactionStatusGetResponse,
+    RpcTransitionFrontierEvent, RpcTransitionFrontierUserCommandsResponse, RpcVrfEvaluateResponse,
 };
 use serde::{Deserialize, Serialize};
 
@@ -227,11 +230,19 @@ impl node::rpc_effectful::RpcService for NodeService {
         respond_block_producer_stats_get,
         RpcBlockProducerStatsGetResponse
     );
+    rpc_service_impl!(
+        respond_block_producer_schedule_get,
+        RpcBlockProducerScheduleGetResponse
+    );
     rpc_service_impl!(
         respond_message_progress_stats_get,
         RpcMessageProgressResponse
     );
     rpc_service_impl!(respond_peers_get, RpcPeersGetResponse);
+    rpc_service_impl!(respond_p2p_bans_get, RpcP2pBansGetResponse);
+    rpc_service_impl!(respond_p2p_ban, RpcP2pBanResponse);
+    rpc_service_impl!(respond_p2p_unban, RpcP2pUnbanResponse);
+    rpc_service_impl!(respond_p2p_bandwidth_get, RpcP2pBandwidthGetResponse);
     rpc_service_impl!(
         respond_p2p_connection_outgoing,
         RpcP2pConnectionOutgoingResponse
@@ -332,6 +343,28 @@ impl node::rpc_effectful::RpcService for NodeService {
         respond_ledger_account_delegators_get,
         RpcLedgerAccountDelegatorsGetResponse
     );
+    rpc_service_impl!(respond_archive_status_get, RpcArchiveStatusGetResponse);
+    rpc_service_impl!(respond_archive_backfill, RpcArchiveBackfillResponse);
+    rpc_service_impl!(respond_vrf_evaluate, RpcVrfEvaluateResponse);
+
+    fn respond_transition_frontier_event(
+        &mut self,
+        rpc_id: RpcId,
+        event: RpcTransitionFrontierEvent,
+    ) -> Result<(), RespondError> {
+        let entry = self.rpc.pending.get(rpc_id);
+        let chan = entry.ok_or(RespondError::UnknownRpcId)?;
+        let chan = chan
+            .downcast_ref::<mpsc::Sender<RpcTransitionFrontierEvent>>()
+            .ok_or(RespondError::UnexpectedResponseType)?
+            .clone();
+        if chan.try_send(event).is_err() {
+            // Subscriber is gone or lagging behind, drop the subscription.
+            self.rpc.pending.remove(rpc_id);
+            return Err(RespondError::RespondingFailed);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
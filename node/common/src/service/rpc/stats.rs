@@ -59,4 +59,15 @@ impl Stats {
             .flatten();
         JsValue::from_serde(&res).unwrap_or_default()
     }
+
+    pub async fn block_producer_schedule(&self) -> JsValue {
+        let res = self
+            .sender
+            .oneshot_request::<RpcBlockProducerScheduleGetResponse>(
+                RpcRequest::BlockProducerScheduleGet,
+            )
+            .await
+            .flatten();
+        JsValue::from_serde(&res).unwrap_or_default()
+    }
 }
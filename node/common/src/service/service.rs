@@ -1,8 +1,9 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use node::{
     core::{channels::mpsc, invariants::InvariantsState},
     event_source::Event,
+    external_snark_worker::SnarkWorkerId,
     ledger::LedgerManager,
     p2p::identity::SecretKey as P2pSecretKey,
     service::Recorder,
@@ -20,7 +21,7 @@ use crate::rpc::RpcReceiver;
 use super::{
     archive::ArchiveService,
     block_producer::BlockProducerService,
-    p2p::webrtc_with_libp2p::P2pServiceCtx,
+    p2p::{webrtc_with_libp2p::P2pServiceCtx, P2pAddressBookFile, P2pTrustFile},
     replay::ReplayerState,
     rpc::{RpcSender, RpcService},
     snark_worker::SnarkWorker,
@@ -42,10 +43,12 @@ pub struct NodeService {
     pub snark_block_proof_verify: mpsc::TrackedUnboundedSender<SnarkBlockVerifyArgs>,
 
     pub ledger_manager: LedgerManager,
-    pub snark_worker: Option<SnarkWorker>,
+    pub snark_workers: BTreeMap<SnarkWorkerId, SnarkWorker>,
     pub block_producer: Option<BlockProducerService>,
     pub archive: Option<ArchiveService>,
     pub p2p: P2pServiceCtx,
+    pub p2p_trust_file: Option<P2pTrustFile>,
+    pub p2p_address_book_file: Option<P2pAddressBookFile>,
 
     pub stats: Option<Stats>,
     pub rpc: RpcService,
@@ -118,10 +121,12 @@ impl NodeService {
             event_receiver: mpsc::unbounded_channel().1.into(),
             snark_block_proof_verify: mpsc::unbounded_channel().0,
             ledger_manager: LedgerManager::spawn(Default::default()),
-            snark_worker: None,
+            snark_workers: Default::default(),
             block_producer: None,
             archive: None,
             p2p: P2pServiceCtx::mocked(p2p_sec_key),
+            p2p_trust_file: None,
+            p2p_address_book_file: None,
             stats: Some(Stats::new()),
             rpc: RpcService::new(),
             recorder: Recorder::None,
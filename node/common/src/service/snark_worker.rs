@@ -7,7 +7,7 @@ use node::core::channels::mpsc;
 use node::event_source::ExternalSnarkWorkerEvent;
 use node::external_snark_worker::{
     ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkResult, SnarkWorkSpec,
-    SnarkWorkSpecError,
+    SnarkWorkSpecError, SnarkWorkerId,
 };
 use node::snark::TransactionVerifier;
 
@@ -25,9 +25,23 @@ enum Cmd {
     Kill,
 }
 
+impl NodeService {
+    fn snark_worker_send(
+        &self,
+        worker_id: SnarkWorkerId,
+        cmd: Cmd,
+    ) -> Result<(), ExternalSnarkWorkerError> {
+        self.snark_workers
+            .get(&worker_id)
+            .and_then(|worker| worker.cmd_sender.send(cmd).ok())
+            .ok_or(ExternalSnarkWorkerError::NotRunning)
+    }
+}
+
 impl node::service::ExternalSnarkWorkerService for NodeService {
     fn start(
         &mut self,
+        worker_id: SnarkWorkerId,
         pub_key: v2::NonZeroCurvePoint,
         fee: v2::CurrencyFeeStableV1,
         work_verifier: TransactionVerifier,
@@ -41,94 +55,102 @@ impl node::service::ExternalSnarkWorkerService for NodeService {
             (&fee).into(),
             CompressedPubKey::from_address(&pub_key.to_string()).unwrap(),
         );
-        self.snark_worker = Some(SnarkWorker { cmd_sender });
+        self.snark_workers
+            .insert(worker_id, SnarkWorker { cmd_sender });
         let event_sender = self.event_sender().clone();
 
         node::core::thread::Builder::new()
-            .name("snark_worker".to_owned())
-            .spawn(move || worker_thread(cmd_receiver, event_sender, sok_message, work_verifier))
+            .name(format!("snark_worker_{worker_id}"))
+            .spawn(move || {
+                worker_thread(
+                    worker_id,
+                    cmd_receiver,
+                    event_sender,
+                    sok_message,
+                    work_verifier,
+                )
+            })
             .map(|_| ())
             .map_err(|err| ExternalSnarkWorkerError::Error(err.to_string()))
     }
 
-    fn kill(&mut self) -> Result<(), ExternalSnarkWorkerError> {
+    fn kill(&mut self, worker_id: SnarkWorkerId) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-
-        if self
-            .snark_worker
-            .as_ref()
-            .and_then(|s| s.cmd_sender.send(Cmd::Kill).ok())
-            .is_none()
-        {
-            return Err(ExternalSnarkWorkerError::NotRunning);
-        }
-        Ok(())
+        self.snark_worker_send(worker_id, Cmd::Kill)
     }
 
-    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError> {
+    fn submit(
+        &mut self,
+        worker_id: SnarkWorkerId,
+        spec: SnarkWorkSpec,
+    ) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-
-        if self
-            .snark_worker
-            .as_ref()
-            .and_then(|s| s.cmd_sender.send(Cmd::Submit(spec.into())).ok())
-            .is_none()
-        {
-            return Err(ExternalSnarkWorkerError::NotRunning);
-        }
-        Ok(())
+        self.snark_worker_send(worker_id, Cmd::Submit(spec.into()))
     }
 
-    fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError> {
+    fn cancel(&mut self, worker_id: SnarkWorkerId) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-
         // TODO(binier): for wasm threads, call terminate:
         // https://developer.mozilla.org/en-US/docs/Web/API/Worker/terminate
-        if self
-            .snark_worker
-            .as_ref()
-            .and_then(|s| s.cmd_sender.send(Cmd::Cancel).ok())
-            .is_none()
-        {
-            return Err(ExternalSnarkWorkerError::NotRunning);
-        }
-        Ok(())
+        self.snark_worker_send(worker_id, Cmd::Cancel)
     }
 }
 
+/// Proves jobs submitted to the worker `worker_id`, one at a time.
+///
+/// Provers aren't per worker: `TransactionProver::make` and
+/// `ZkappProver::make` return the process-wide cached ones, so all
+/// workers use the same prover indexes. That is safe, proving only reads
+/// them through `&Prover` and they are `Sync` (they live in statics).
+/// Witnesses and other per-proof state are built by each call. Indexes
+/// per worker would only multiply their memory by the worker count.
 fn worker_thread(
+    worker_id: SnarkWorkerId,
     mut cmd_receiver: mpsc::UnboundedReceiver<Cmd>,
     event_sender: EventSender,
     sok_message: SokMessage,
     work_verifier: TransactionVerifier,
 ) {
-    let _ = event_sender.send(ExternalSnarkWorkerEvent::Started.into());
+    let send_event = |event: ExternalSnarkWorkerEvent| {
+        let _ = event_sender.send((worker_id, event).into());
+    };
+    send_event(ExternalSnarkWorkerEvent::Started);
     let tx_prover = TransactionProver::make(Some(work_verifier.clone()));
     let zkapp_prover = ZkappProver::make(Some(work_verifier));
-    while let Some(cmd) = cmd_receiver.blocking_recv() {
+    let mut next_cmd = None;
+    while let Some(cmd) = next_cmd.take().or_else(|| cmd_receiver.blocking_recv()) {
         match cmd {
             Cmd::Kill => {
-                let _ = event_sender.send(ExternalSnarkWorkerEvent::Killed.into());
+                send_event(ExternalSnarkWorkerEvent::Killed);
                 return;
             }
             Cmd::Cancel => {
                 // can't cancel as it's a blocking thread. Once this
                 // is moved to another process, kill it.
-                let _ = event_sender.send(ExternalSnarkWorkerEvent::WorkCancelled.into());
+                send_event(ExternalSnarkWorkerEvent::WorkCancelled);
             }
             Cmd::Submit(spec) => {
+                match cmd_receiver.try_recv() {
+                    // Job got cancelled before we started proving it.
+                    Ok(Cmd::Cancel) => {
+                        send_event(ExternalSnarkWorkerEvent::WorkCancelled);
+                        continue;
+                    }
+                    Ok(cmd) => next_cmd = Some(cmd),
+                    Err(_) => {}
+                }
                 let event = match prove_spec(&tx_prover, &zkapp_prover, *spec, &sok_message) {
                     Err(err) => ExternalSnarkWorkerEvent::WorkError(err),
                     Ok(res) => ExternalSnarkWorkerEvent::WorkResult(res),
                 };
 
-                let _ = event_sender.send(event.into());
+                send_event(event);
             }
         }
     }
@@ -89,6 +89,24 @@ impl GraphQLBlock {
     }
 }
 
+impl GraphQLBlock {
+    /// Whether the block was produced by `public_key` or contains a command
+    /// sent from or to it. Used to filter the `newBlock` subscription.
+    pub(crate) fn involves_public_key(&self, public_key: &str) -> bool {
+        self.creator == public_key
+            || self
+                .transactions
+                .user_commands
+                .iter()
+                .any(|cmd| cmd.from == public_key || cmd.to == public_key)
+            || self
+                .transactions
+                .zkapp_commands
+                .iter()
+                .any(|cmd| cmd.zkapp_command.fee_payer.body.public_key == public_key)
+    }
+}
+
 #[derive(GraphQLObject, Debug)]
 pub struct GraphQLSnarkJob {
     pub fee: String,
@@ -445,3 +463,28 @@ impl TryFrom<MinaBaseSignedCommandStableV2> for GraphQLUserCommands {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::graphql::tests::breadcrumb_block;
+
+    use super::*;
+
+    fn block() -> GraphQLBlock {
+        GraphQLBlock::try_from(breadcrumb_block()).unwrap()
+    }
+
+    #[test]
+    fn new_block_filter() {
+        let block = block();
+
+        assert!(block.involves_public_key(&block.creator));
+        for cmd in &block.transactions.user_commands {
+            assert!(block.involves_public_key(&cmd.from));
+            assert!(block.involves_public_key(&cmd.to));
+        }
+        assert!(
+            !block.involves_public_key("B62qrPN5Y5yq8kGE3FbVKbGTdTAJNdtNtB5sNVpxyRwWGcDEhpMzc8g")
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use juniper::{GraphQLEnum, GraphQLObject};
+use node::rpc::{
+    RpcBlockProducerSchedule, RpcBlockProducerScheduledSlot, RpcBlockProducerSlotOutcome,
+};
+
+use super::GraphQLPublicKey;
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(name = "BlockProducerSchedule")]
+pub struct GraphQLBlockProducerSchedule {
+    pub current_global_slot: Option<i32>,
+    pub current_epoch: Option<i32>,
+    pub slots: Vec<GraphQLBlockProducerScheduledSlot>,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(name = "BlockProducerScheduledSlot")]
+pub struct GraphQLBlockProducerScheduledSlot {
+    pub global_slot: i32,
+    pub epoch: i32,
+    /// Expected wall-clock time of the slot, in milliseconds since unix epoch.
+    pub slot_time: String,
+    pub producer: GraphQLPublicKey,
+    pub delegator: GraphQLPublicKey,
+    pub delegator_index: i32,
+    pub vrf_output: String,
+    pub vrf_value: f64,
+    pub vrf_threshold: Option<f64>,
+    pub outcome: GraphQLBlockProducerSlotOutcome,
+    /// Hash of the block produced for the slot, if any.
+    pub block_hash: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, GraphQLEnum)]
+#[allow(clippy::upper_case_acronyms)]
+#[graphql(name = "BlockProducerSlotOutcome")]
+pub enum GraphQLBlockProducerSlotOutcome {
+    UPCOMING,
+    PRODUCING,
+    CANONICAL,
+    ORPHANED,
+    DISCARDED,
+    MISSED,
+    UNKNOWN,
+}
+
+impl From<RpcBlockProducerSchedule> for GraphQLBlockProducerSchedule {
+    fn from(schedule: RpcBlockProducerSchedule) -> Self {
+        Self {
+            current_global_slot: schedule.current_global_slot.map(|v| v as i32),
+            current_epoch: schedule.current_epoch.map(|v| v as i32),
+            slots: schedule.slots.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<RpcBlockProducerScheduledSlot> for GraphQLBlockProducerScheduledSlot {
+    fn from(slot: RpcBlockProducerScheduledSlot) -> Self {
+        let slot_time: u64 = slot.slot_time.into();
+        let slot_time_ms = slot_time / 1_000_000;
+
+        let (outcome, block_hash) = match slot.outcome {
+            RpcBlockProducerSlotOutcome::Upcoming => {
+                (GraphQLBlockProducerSlotOutcome::UPCOMING, None)
+            }
+            RpcBlockProducerSlotOutcome::Producing => {
+                (GraphQLBlockProducerSlotOutcome::PRODUCING, None)
+            }
+            RpcBlockProducerSlotOutcome::Canonical { block_hash, .. } => (
+                GraphQLBlockProducerSlotOutcome::CANONICAL,
+                Some(block_hash.to_string()),
+            ),
+            RpcBlockProducerSlotOutcome::Orphaned { block_hash, .. } => (
+                GraphQLBlockProducerSlotOutcome::ORPHANED,
+                Some(block_hash.to_string()),
+            ),
+            RpcBlockProducerSlotOutcome::Discarded { .. } => {
+                (GraphQLBlockProducerSlotOutcome::DISCARDED, None)
+            }
+            RpcBlockProducerSlotOutcome::Missed => (GraphQLBlockProducerSlotOutcome::MISSED, None),
+            RpcBlockProducerSlotOutcome::Unknown => {
+                (GraphQLBlockProducerSlotOutcome::UNKNOWN, None)
+            }
+        };
+
+        Self {
+            global_slot: slot.global_slot as i32,
+            epoch: slot.epoch as i32,
+            slot_time: slot_time_ms.to_string(),
+            producer: slot.producer.to_string(),
+            delegator: slot.delegator.to_string(),
+            delegator_index: slot.delegator_index.as_u64() as i32,
+            vrf_output: slot.vrf_output,
+            vrf_value: slot.vrf_value,
+            vrf_threshold: slot.vrf_threshold,
+            outcome,
+            block_hash,
+        }
+    }
+}
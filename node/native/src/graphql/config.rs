@@ -0,0 +1,244 @@
+use juniper::{GraphQLScalar, InputValue, ScalarValue, Value};
+use mina_p2p_messages::v2::{
+    ConsensusProofOfStakeDataConsensusStateValueStableV2,
+    MinaBaseProtocolConstantsCheckedValueStableV1, StateHash,
+};
+use openmina_core::{block::ArcBlockWithHash, constants::ConstraintConstants};
+use serde_json::json;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// Arbitrary JSON value, same as the `JSON` scalar of the OCaml node.
+#[derive(GraphQLScalar, Debug, Clone, PartialEq)]
+#[graphql(name = "JSON", with = json_scalar, parse_token(String))]
+pub struct GraphQLJson(pub serde_json::Value);
+
+mod json_scalar {
+    use super::*;
+
+    pub(super) fn to_output<S: ScalarValue>(v: &GraphQLJson) -> Value<S> {
+        to_graphql_value(&v.0)
+    }
+
+    /// Input must be a JSON encoded string.
+    pub(super) fn from_input<S: ScalarValue>(v: &InputValue<S>) -> Result<GraphQLJson, String> {
+        v.as_string_value()
+            .ok_or_else(|| format!("Expected JSON encoded `String`, found: {v}"))
+            .and_then(|s| serde_json::from_str(s).map_err(|e| e.to_string()))
+            .map(GraphQLJson)
+    }
+
+    fn to_graphql_value<S: ScalarValue>(v: &serde_json::Value) -> Value<S> {
+        match v {
+            serde_json::Value::Null => Value::null(),
+            serde_json::Value::Bool(v) => Value::scalar(*v),
+            // GraphQL `Int` is 32-bit, so wider integers are kept exact as
+            // decimal strings instead of being rounded through `f64`.
+            serde_json::Value::Number(v) if v.is_f64() => {
+                Value::scalar(v.as_f64().unwrap_or_default())
+            }
+            serde_json::Value::Number(v) => match v.as_i64().map(i32::try_from) {
+                Some(Ok(v)) => Value::scalar(v),
+                _ => Value::scalar(v.to_string()),
+            },
+            serde_json::Value::String(v) => Value::scalar(v.clone()),
+            serde_json::Value::Array(v) => Value::list(v.iter().map(to_graphql_value).collect()),
+            serde_json::Value::Object(v) => Value::object(
+                v.iter()
+                    .map(|(k, v)| (k.as_str(), to_graphql_value(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Runtime configuration of the node, in the `daemon.json` format of the OCaml node.
+///
+/// Location [src/lib/runtime_config/runtime_config.ml](https://github.com/MinaProtocol/mina/blob/develop/src/lib/runtime_config/runtime_config.ml)
+pub(crate) fn runtime_config(
+    genesis: &ArcBlockWithHash,
+    constraint_constants: &ConstraintConstants,
+) -> serde_json::Value {
+    json!({
+        "genesis": genesis_config(genesis.constants()),
+        "proof": proof_config(
+            constraint_constants,
+            constraint_constants.fork.as_ref().map(|fork| {
+                fork_point(
+                    &StateHash::from_fp(fork.state_hash),
+                    fork.blockchain_length,
+                    fork.global_slot_since_genesis,
+                )
+            }),
+        ),
+        "ledger": {
+            "hash": genesis.merkle_root_hash().to_string(),
+        },
+        "epoch_data": epoch_data(genesis.consensus_state()),
+    })
+}
+
+/// Configuration for a fork of the chain, continuing from `fork_block`.
+///
+/// Unlike the OCaml node, ledgers are referenced by their hash rather
+/// than dumped with all their accounts.
+pub(crate) fn fork_config(
+    fork_block: &ArcBlockWithHash,
+    constraint_constants: &ConstraintConstants,
+) -> serde_json::Value {
+    json!({
+        "genesis": genesis_config(fork_block.constants()),
+        "proof": proof_config(
+            constraint_constants,
+            Some(fork_point(
+                fork_block.hash(),
+                fork_block.height(),
+                fork_block.global_slot_since_genesis(),
+            )),
+        ),
+        "ledger": {
+            "hash": fork_block.merkle_root_hash().to_string(),
+            "add_genesis_winner": false,
+        },
+        "epoch_data": epoch_data(fork_block.consensus_state()),
+    })
+}
+
+fn genesis_config(constants: &MinaBaseProtocolConstantsCheckedValueStableV1) -> serde_json::Value {
+    let timestamp = OffsetDateTime::from_unix_timestamp_nanos(
+        constants.genesis_state_timestamp.as_u64() as i128 * 1_000_000,
+    )
+    .ok()
+    .and_then(|t| t.format(&Rfc3339).ok());
+
+    json!({
+        "k": constants.k.as_u32(),
+        "delta": constants.delta.as_u32(),
+        "slots_per_epoch": constants.slots_per_epoch.as_u32(),
+        "slots_per_sub_window": constants.slots_per_sub_window.as_u32(),
+        "grace_period_slots": constants.grace_period_slots.as_u32(),
+        "genesis_state_timestamp": timestamp,
+    })
+}
+
+fn proof_config(
+    constraint_constants: &ConstraintConstants,
+    fork: Option<serde_json::Value>,
+) -> serde_json::Value {
+    let mut proof = json!({
+        "level": "full",
+        "sub_windows_per_window": constraint_constants.sub_windows_per_window,
+        "ledger_depth": constraint_constants.ledger_depth,
+        "work_delay": constraint_constants.work_delay,
+        "block_window_duration_ms": constraint_constants.block_window_duration_ms,
+        "transaction_capacity": {
+            "2_to_the": constraint_constants.transaction_capacity_log_2,
+        },
+        "coinbase_amount": format_mina(constraint_constants.coinbase_amount),
+        "supercharged_coinbase_factor": constraint_constants.supercharged_coinbase_factor,
+        "account_creation_fee": format_mina(constraint_constants.account_creation_fee),
+    });
+    if let Some(fork) = fork {
+        proof["fork"] = fork;
+    }
+    proof
+}
+
+fn fork_point(
+    state_hash: &StateHash,
+    blockchain_length: u32,
+    global_slot_since_genesis: u32,
+) -> serde_json::Value {
+    json!({
+        "state_hash": state_hash.to_string(),
+        "blockchain_length": blockchain_length,
+        "global_slot_since_genesis": global_slot_since_genesis,
+    })
+}
+
+fn epoch_data(
+    consensus_state: &ConsensusProofOfStakeDataConsensusStateValueStableV2,
+) -> serde_json::Value {
+    let staking = &consensus_state.staking_epoch_data;
+    let next = &consensus_state.next_epoch_data;
+    json!({
+        "staking": {
+            "hash": staking.ledger.hash.to_string(),
+            "seed": staking.seed.to_string(),
+        },
+        "next": {
+            "hash": next.ledger.hash.to_string(),
+            "seed": next.seed.to_string(),
+        },
+    })
+}
+
+/// Formats nanomina the way the OCaml runtime config does, e.g. `720` or `0.25`.
+fn format_mina(nanomina: u64) -> String {
+    const PRECISION: u64 = 1_000_000_000;
+    let (whole, fraction) = (nanomina / PRECISION, nanomina % PRECISION);
+    if fraction == 0 {
+        whole.to_string()
+    } else {
+        let fraction = format!("{fraction:09}");
+        format!("{whole}.{}", fraction.trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openmina_core::network::devnet;
+
+    use super::*;
+
+    #[test]
+    fn test_format_mina() {
+        assert_eq!(format_mina(720_000_000_000), "720");
+        assert_eq!(format_mina(1_000_000_000), "1");
+        assert_eq!(format_mina(250_000_000), "0.25");
+        assert_eq!(format_mina(1), "0.000000001");
+    }
+
+    #[test]
+    fn test_proof_config_matches_ocaml() {
+        let constants = &devnet::CONSTRAINT_CONSTANTS;
+        let fork = constants.fork.as_ref().unwrap();
+        let config = proof_config(
+            constants,
+            Some(fork_point(
+                &StateHash::from_fp(fork.state_hash),
+                fork.blockchain_length,
+                fork.global_slot_since_genesis,
+            )),
+        );
+
+        let expected: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/runtime_config_proof_devnet.json"))
+                .unwrap();
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_json_scalar_keeps_large_integers() {
+        let value = json!({
+            "small": 42,
+            "negative": -7,
+            "large": 4_294_967_296_u64,
+            "max": u64::MAX,
+            "float": 0.25,
+        });
+        let output: Value<juniper::DefaultScalarValue> =
+            json_scalar::to_output(&GraphQLJson(value));
+        let output = serde_json::to_value(&output).unwrap();
+
+        assert_eq!(
+            output,
+            json!({
+                "small": 42,
+                "negative": -7,
+                "large": "4294967296",
+                "max": "18446744073709551615",
+                "float": 0.25,
+            })
+        );
+    }
+}
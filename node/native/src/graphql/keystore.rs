@@ -0,0 +1,300 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use juniper::{GraphQLInputObject, GraphQLObject};
+use node::account::{AccountPublicKey, AccountSecretKey};
+use openmina_core::{EncryptedSecretKeyFile, EncryptionError};
+
+use super::GraphQLPublicKey;
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeystoreError {
+    #[error("Work dir is not set, keystore is unavailable")]
+    NoWorkDir,
+    #[error("Account {0} is not in the keystore")]
+    NotFound(AccountPublicKey),
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Keystore task failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+/// Accounts whose secret keys are managed by the node, in the same layout
+/// as the OCaml daemon: one [`EncryptedSecretKeyFile`] per account in
+/// `<work_dir>/wallets/store/<public_key>`.
+///
+/// Unlocked keys are kept in memory only, until locked or the node stops.
+#[derive(Default)]
+pub struct Keystore {
+    dir: Option<PathBuf>,
+    unlocked: Mutex<BTreeMap<AccountPublicKey, AccountSecretKey>>,
+}
+
+impl Keystore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            unlocked: Default::default(),
+        }
+    }
+
+    /// Keystore inside the node's work dir, if it is set.
+    pub fn in_work_dir() -> Self {
+        match openmina_core::try_get_work_dir() {
+            Some(work_dir) => Self::new(work_dir.join("wallets").join("store")),
+            None => Self::default(),
+        }
+    }
+
+    fn dir(&self) -> Result<&Path, KeystoreError> {
+        self.dir.as_deref().ok_or(KeystoreError::NoWorkDir)
+    }
+
+    fn key_path(&self, public_key: &AccountPublicKey) -> Result<PathBuf, KeystoreError> {
+        Ok(self.dir()?.join(public_key.to_string()))
+    }
+
+    /// Public keys of all the accounts in the keystore.
+    pub fn public_keys(&self) -> Result<Vec<AccountPublicKey>, KeystoreError> {
+        let dir = self.dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut keys = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect::<Vec<_>>();
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Creates the keystore directory, accessible only by the owner.
+    fn create_dir(&self) -> Result<(), KeystoreError> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        Ok(builder.create(self.dir()?)?)
+    }
+
+    /// Imports an encrypted secret key, given as the JSON contents of its
+    /// file, checking that `password` decrypts it. Returns the public key
+    /// and whether the account was already in the keystore.
+    pub async fn import(
+        &self,
+        encrypted_key: &str,
+        password: String,
+    ) -> Result<(AccountPublicKey, bool), KeystoreError> {
+        let encrypted: EncryptedSecretKeyFile =
+            serde_json::from_str(encrypted_key).map_err(EncryptionError::from)?;
+        let secret_key = {
+            let password = password.clone();
+            blocking(move || AccountSecretKey::from_encrypted(&encrypted, &password)).await?
+        };
+        let public_key = secret_key.public_key();
+        let key_path = self.key_path(&public_key)?;
+        if key_path.exists() {
+            return Ok((public_key, true));
+        }
+        // store it re-encrypted rather than copying the input, so that the
+        // keystore never contains something other than secret key files
+        self.create_dir()?;
+        blocking(move || secret_key.to_encrypted_file(key_path, &password)).await?;
+        Ok((public_key, false))
+    }
+
+    /// Generates a new account protected by `password`.
+    pub async fn create(&self, password: String) -> Result<AccountPublicKey, KeystoreError> {
+        let secret_key = AccountSecretKey::rand();
+        let public_key = secret_key.public_key();
+        let key_path = self.key_path(&public_key)?;
+        self.create_dir()?;
+        blocking(move || secret_key.to_encrypted_file(key_path, &password)).await?;
+        Ok(public_key)
+    }
+
+    /// Decrypts the secret key of `public_key` and keeps it in memory, so
+    /// that it can be used for signing.
+    pub async fn unlock(
+        &self,
+        public_key: &AccountPublicKey,
+        password: String,
+    ) -> Result<(), KeystoreError> {
+        let key_path = self.key_path(public_key)?;
+        if !key_path.exists() {
+            return Err(KeystoreError::NotFound(public_key.clone()));
+        }
+        let secret_key =
+            blocking(move || AccountSecretKey::from_encrypted_file(key_path, &password)).await?;
+        self.unlocked_keys().insert(public_key.clone(), secret_key);
+        Ok(())
+    }
+
+    /// Forgets the decrypted secret key of `public_key`.
+    pub fn lock(&self, public_key: &AccountPublicKey) -> Result<(), KeystoreError> {
+        if !self.key_path(public_key)?.exists() {
+            return Err(KeystoreError::NotFound(public_key.clone()));
+        }
+        self.unlocked_keys().remove(public_key);
+        Ok(())
+    }
+
+    pub fn unlocked(&self, public_key: &AccountPublicKey) -> Option<AccountSecretKey> {
+        self.unlocked_keys().get(public_key).cloned()
+    }
+
+    fn unlocked_keys(
+        &self,
+    ) -> std::sync::MutexGuard<'_, BTreeMap<AccountPublicKey, AccountSecretKey>> {
+        self.unlocked
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Password hashing is expensive, so it must not block the http server.
+async fn blocking<T, F>(f: F) -> Result<T, KeystoreError>
+where
+    F: FnOnce() -> Result<T, EncryptionError> + Send + 'static,
+    T: Send + 'static,
+{
+    Ok(tokio::task::spawn_blocking(f).await??)
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(name = "AddAccountInput")]
+pub struct InputGraphQLAddAccount {
+    /// Password used to encrypt the new account
+    pub password: String,
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(name = "UnlockInput")]
+pub struct InputGraphQLUnlock {
+    /// Public key specifying which account to unlock
+    pub public_key: GraphQLPublicKey,
+    /// Password for the account to be unlocked
+    pub password: String,
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(name = "LockInput")]
+pub struct InputGraphQLLock {
+    /// Public key specifying which account to lock
+    pub public_key: GraphQLPublicKey,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(name = "ImportAccountPayload")]
+pub struct GraphQLImportAccountPayload {
+    /// The public key of the imported account
+    pub public_key: GraphQLPublicKey,
+    /// True if the account had already been imported
+    pub already_imported: bool,
+    pub success: bool,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(name = "AddAccountPayload")]
+pub struct GraphQLAddAccountPayload {
+    /// Public key of the created account
+    pub public_key: GraphQLPublicKey,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(name = "UnlockPayload")]
+pub struct GraphQLUnlockPayload {
+    /// Public key of the unlocked account
+    pub public_key: GraphQLPublicKey,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(name = "LockPayload")]
+pub struct GraphQLLockPayload {
+    /// Public key of the locked account
+    pub public_key: GraphQLPublicKey,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn temp_keystore(name: &str) -> Keystore {
+        let dir = env::temp_dir().join(format!("openmina-keystore-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Keystore::new(dir)
+    }
+
+    #[tokio::test]
+    async fn test_import_unlock_lock() {
+        let keystore = temp_keystore("import");
+        let password = "not-very-secure-pass";
+        // key file as exported from another keystore
+        let exported = temp_keystore("export");
+        let expected = exported.create(password.to_owned()).await.unwrap();
+        let encrypted_key = fs::read_to_string(exported.key_path(&expected).unwrap()).unwrap();
+
+        assert!(keystore
+            .import(&encrypted_key, "wrong-pass".to_owned())
+            .await
+            .is_err());
+
+        let (public_key, already_imported) = keystore
+            .import(&encrypted_key, password.to_owned())
+            .await
+            .unwrap();
+        assert_eq!(public_key, expected);
+        assert!(!already_imported);
+        let (_, already_imported) = keystore
+            .import(&encrypted_key, password.to_owned())
+            .await
+            .unwrap();
+        assert!(already_imported);
+        assert_eq!(keystore.public_keys().unwrap(), vec![expected.clone()]);
+
+        assert!(keystore.unlocked(&expected).is_none());
+        keystore
+            .unlock(&expected, password.to_owned())
+            .await
+            .unwrap();
+        assert_eq!(
+            keystore.unlocked(&expected).map(|sk| sk.public_key()),
+            Some(expected.clone())
+        );
+        keystore.lock(&expected).unwrap();
+        assert!(keystore.unlocked(&expected).is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_key_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let keystore = temp_keystore("permissions");
+        let public_key = keystore.create("pass".to_owned()).await.unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        assert_eq!(mode(keystore.dir().unwrap()), 0o700);
+        assert_eq!(mode(&keystore.key_path(&public_key).unwrap()), 0o600);
+    }
+
+    #[tokio::test]
+    async fn test_missing_work_dir() {
+        let keystore = Keystore::default();
+        assert!(matches!(
+            keystore.create("pass".to_owned()).await,
+            Err(KeystoreError::NoWorkDir)
+        ));
+        assert!(matches!(
+            keystore.lock(&AccountSecretKey::rand().public_key()),
+            Err(KeystoreError::NoWorkDir)
+        ));
+    }
+}
@@ -1,23 +1,32 @@
+use crate::http_server::{admin_access, AdminAccessError, HttpAdminConfig};
 use account::{create_account_loader, AccountLoader, GraphQLAccount};
 use block::{GraphQLBlock, GraphQLSnarkJob, GraphQLUserCommands};
-use juniper::{graphql_value, EmptySubscription, FieldError, GraphQLEnum, RootNode};
-use ledger::{Account, AccountId};
+use futures::{stream, Stream, StreamExt};
+use juniper::{graphql_value, FieldError, GraphQLEnum, RootNode};
+use juniper_graphql_ws::ConnectionConfig;
+use keystore::Keystore;
+use ledger::{
+    scan_state::{currency::Nonce, transaction_logic::verifiable},
+    Account, AccountId,
+};
 use mina_p2p_messages::v2::{
     conv, LedgerHash, MinaBaseSignedCommandStableV2, MinaBaseUserCommandStableV2,
-    MinaBaseZkappCommandTStableV1WireStableV1, TokenIdKeyHash, TransactionHash,
+    MinaBaseZkappCommandTStableV1WireStableV1, NonZeroCurvePoint, TokenIdKeyHash, TransactionHash,
 };
 use mina_signer::CompressedPubKey;
 use node::rpc::RpcSnarkerConfig;
 use node::{
-    account::AccountPublicKey,
+    account::{AccountPublicKey, AccountSecretKey},
     ledger::read::LedgerStatus,
     rpc::{
         AccountQuery, GetBlockQuery, PooledCommandsQuery, RpcBestChainResponse,
-        RpcGenesisBlockResponse, RpcGetBlockResponse, RpcLedgerAccountDelegatorsGetResponse,
-        RpcLedgerStatusGetResponse, RpcNodeStatus, RpcPooledUserCommandsResponse,
-        RpcPooledZkappCommandsResponse, RpcRequest, RpcSnarkPoolCompletedJobsResponse,
-        RpcSnarkPoolPendingJobsGetResponse, RpcStatusGetResponse, RpcSyncStatsGetResponse,
-        RpcTransactionInjectResponse, RpcTransactionStatusGetResponse, SyncStatsQuery,
+        RpcBlockProducerScheduleGetResponse, RpcGenesisBlockResponse, RpcGetBlockResponse,
+        RpcLedgerAccountDelegatorsGetResponse, RpcLedgerStatusGetResponse, RpcNodeStatus,
+        RpcPooledUserCommandsResponse, RpcPooledZkappCommandsResponse, RpcRequest,
+        RpcSnarkPoolCompletedJobsResponse, RpcSnarkPoolPendingJobsGetResponse,
+        RpcStatusGetResponse, RpcSyncStatsGetResponse, RpcSyncStatus, RpcTransactionInjectResponse,
+        RpcTransactionStatusGetResponse, RpcTransitionFrontierEvent, RpcVrfEvaluateResponse,
+        SyncStatsQuery,
     },
     stats::sync::SyncKind,
     BuildEnv,
@@ -29,7 +38,7 @@ use openmina_core::{
 };
 use openmina_node_common::rpc::RpcSender;
 use snark::{GraphQLPendingSnarkWork, GraphQLSnarkWorker};
-use std::str::FromStr;
+use std::{convert::Infallible, pin::Pin, str::FromStr, sync::Arc};
 use tokio::sync::OnceCell;
 use transaction::GraphQLTransactionStatus;
 use warp::{Filter, Rejection, Reply};
@@ -37,15 +46,26 @@ use zkapp::GraphQLZkapp;
 
 pub mod account;
 pub mod block;
+pub mod block_producer;
+pub mod config;
 pub mod constants;
+pub mod keystore;
 pub mod snark;
 pub mod transaction;
 pub mod user_command;
+pub mod vrf;
 pub mod zkapp;
 
 /// Base58 encoded public key
 pub type GraphQLPublicKey = String;
 
+/// Stream returned by the subscription resolvers.
+type GraphQLStream<T> = Pin<Box<dyn Stream<Item = Result<T, FieldError>> + Send>>;
+
+/// How many transition frontier events can be buffered for a single
+/// subscriber before it gets unsubscribed by the node.
+const SUBSCRIPTION_BUFFER: usize = 64;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Conversion error: {0}")]
@@ -110,6 +130,9 @@ impl From<ConversionError> for Error {
 pub(crate) struct Context {
     rpc_sender: RpcSender,
     account_loader: AccountLoader,
+    /// Only set if the keystore mutations are enabled.
+    keystore: Option<Arc<Keystore>>,
+    admin_access: Result<(), AdminAccessError>,
     // Caches
     statemachine_status_cache: OnceCell<Option<RpcNodeStatus>>,
     best_tip_cache: OnceCell<Option<AppliedBlock>>,
@@ -119,9 +142,15 @@ pub(crate) struct Context {
 impl juniper::Context for Context {}
 
 impl Context {
-    pub fn new(rpc_sender: RpcSender) -> Self {
+    pub fn new(
+        rpc_sender: RpcSender,
+        keystore: Option<Arc<Keystore>>,
+        admin_access: Result<(), AdminAccessError>,
+    ) -> Self {
         Self {
             rpc_sender: rpc_sender.clone(),
+            keystore,
+            admin_access,
             statemachine_status_cache: OnceCell::new(),
             best_tip_cache: OnceCell::new(),
             ledger_status_cache: OnceCell::new(),
@@ -168,10 +197,45 @@ impl Context {
             .clone()
     }
 
+    /// The keystore, if its mutations are enabled and the request is
+    /// allowed to use privileged endpoints.
+    pub(crate) fn keystore(&self) -> Result<&Keystore, Error> {
+        let keystore = self.keystore.as_deref().ok_or_else(|| {
+            Error::Custom("Keystore mutations are disabled on this node".to_owned())
+        })?;
+        self.admin_access
+            .map_err(|err| Error::Custom(err.to_string()))?;
+        Ok(keystore)
+    }
+
+    /// Secret key of `public_key`, if it was unlocked in the keystore.
+    pub(crate) fn unlocked_secret_key(&self, public_key: &str) -> Result<AccountSecretKey, Error> {
+        let public_key = AccountPublicKey::from_str(public_key)
+            .map_err(|e| Error::Conversion(ConversionError::Base58Check(e)))?;
+        self.keystore()?.unlocked(&public_key).ok_or_else(|| {
+            Error::Custom(format!(
+                "Signature is missing and account {public_key} is not unlocked"
+            ))
+        })
+    }
+
     pub(crate) async fn load_account(&self, account_id: AccountId) -> Option<GraphQLAccount> {
         self.account_loader.try_load(account_id).await.ok()?.ok()
     }
 
+    /// Stream of transition frontier events, ends when the node drops the subscription.
+    async fn subscribe_transition_frontier(
+        &self,
+    ) -> impl Stream<Item = RpcTransitionFrontierEvent> {
+        let rx = self
+            .rpc_sender
+            .multishot_request(SUBSCRIPTION_BUFFER, RpcRequest::TransitionFrontierSubscribe)
+            .await;
+        stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|event| (event, rx))
+        })
+    }
+
     pub async fn fetch_delegators(
         &self,
         ledger_hash: LedgerHash,
@@ -198,6 +262,25 @@ enum SyncStatus {
     CATCHUP,
 }
 
+impl From<RpcSyncStatus> for SyncStatus {
+    fn from(status: RpcSyncStatus) -> Self {
+        match status {
+            RpcSyncStatus::Connecting => Self::CONNECTING,
+            RpcSyncStatus::Listening => Self::LISTENING,
+            RpcSyncStatus::Offline => Self::OFFLINE,
+            RpcSyncStatus::Bootstrap => Self::BOOTSTRAP,
+            RpcSyncStatus::Catchup => Self::CATCHUP,
+            RpcSyncStatus::Synced => Self::SYNCED,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, GraphQLEnum)]
+#[allow(clippy::upper_case_acronyms)]
+enum ChainReorganizationStatus {
+    CHANGED,
+}
+
 #[derive(Clone, Debug)]
 struct ProtocolState {
     consensus_state: ConsensusState,
@@ -287,6 +370,186 @@ impl Query {
             .try_into()?)
     }
 
+    /// Find all accounts for a public key
+    async fn accounts(
+        public_key: String,
+        context: &Context,
+    ) -> juniper::FieldResult<Vec<account::GraphQLAccount>> {
+        let public_key = AccountPublicKey::from_str(&public_key)?;
+        let accounts: Vec<Account> = context
+            .rpc_sender
+            .oneshot_request(RpcRequest::LedgerAccountsGet(
+                AccountQuery::SinglePublicKey(public_key),
+            ))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        Ok(accounts
+            .into_iter()
+            .map(account::GraphQLAccount::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Find the account that owns a given token
+    async fn token_owner(
+        token: String,
+        context: &Context,
+    ) -> juniper::FieldResult<Option<account::GraphQLAccount>> {
+        let token_id = TokenIdKeyHash::from_str(&token)?;
+        let accounts: Vec<Account> = context
+            .rpc_sender
+            .oneshot_request(RpcRequest::LedgerAccountsGet(AccountQuery::TokenOwner(
+                token_id,
+            )))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        Ok(accounts
+            .into_iter()
+            .next()
+            .map(TryInto::try_into)
+            .transpose()?)
+    }
+
+    /// Accounts for which the daemon tracks the private key: the block
+    /// producer, the coinbase receiver, the snark worker and the keystore.
+    async fn tracked_accounts(
+        context: &Context,
+    ) -> juniper::FieldResult<Vec<account::GraphQLAccount>> {
+        let status = context.get_or_fetch_status().await;
+        let snarker: Option<RpcSnarkerConfig> = context
+            .rpc_sender
+            .oneshot_request(RpcRequest::SnarkerConfig)
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        let mut keys = Vec::new();
+        let status_keys = status
+            .iter()
+            .flat_map(|status| [&status.block_producer, &status.coinbase_receiver])
+            .flatten()
+            .cloned()
+            .map(NonZeroCurvePoint::from);
+        let keystore_keys = context
+            .keystore
+            .iter()
+            .flat_map(|keystore| keystore.public_keys().unwrap_or_default())
+            .map(NonZeroCurvePoint::from);
+        for key in status_keys
+            .chain(snarker.map(|config| config.public_key))
+            .chain(keystore_keys)
+        {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let mut accounts = Vec::with_capacity(keys.len());
+        for key in keys {
+            let account_id = AccountId {
+                public_key: CompressedPubKey::try_from(&key)?,
+                token_id: TokenIdKeyHash::default().into(),
+            };
+            accounts.extend(context.load_account(account_id).await);
+        }
+        Ok(accounts)
+    }
+
+    /// Validate the format and signature of a payment
+    async fn validate_payment(
+        input: user_command::InputGraphQLPayment,
+        signature: user_command::UserCommandSignature,
+        context: &Context,
+    ) -> juniper::FieldResult<bool> {
+        let infered_nonce = fetch_infered_nonce(&input.from, context).await?;
+        let command = input
+            .create_signed_command(infered_nonce, signature)
+            .map_err(Error::Conversion)?;
+
+        Ok(verifiable::check_only_for_signature(Box::new(command)).is_ok())
+    }
+
+    /// Evaluate a vrf for the given public key. This includes a witness which
+    /// may be verified without access to the private key for this vrf
+    /// evaluation.
+    ///
+    /// Uses the block producer's private key, so it requires admin access.
+    async fn evaluate_vrf(
+        message: vrf::InputGraphQLVrfMessage,
+        public_key: GraphQLPublicKey,
+        vrf_threshold: Option<vrf::InputGraphQLVrfThreshold>,
+        context: &Context,
+    ) -> juniper::FieldResult<vrf::GraphQLVrfEvaluation> {
+        context
+            .admin_access
+            .map_err(|err| Error::Custom(err.to_string()))?;
+        let public_key = AccountPublicKey::from_str(&public_key)?;
+        let threshold = vrf_threshold.map(TryInto::try_into).transpose()?;
+        let evaluation: RpcVrfEvaluateResponse = context
+            .rpc_sender
+            .oneshot_request(RpcRequest::VrfEvaluate(
+                public_key.clone(),
+                message.try_into()?,
+            ))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+        let evaluation = evaluation.ok_or_else(|| {
+            Error::Custom(format!(
+                "Could not find an owned keypair for the public key {public_key}"
+            ))
+        })?;
+
+        Ok(vrf::GraphQLVrfEvaluation::new(&evaluation, threshold))
+    }
+
+    /// Check a vrf evaluation commitment. This can be used to check vrf
+    /// evaluations without needing to reveal the private key, in the format
+    /// returned by evaluateVrf
+    async fn check_vrf(
+        input: vrf::InputGraphQLVrfEvaluation,
+        _context: &Context,
+    ) -> juniper::FieldResult<vrf::GraphQLVrfEvaluation> {
+        let threshold = input
+            .vrf_threshold
+            .clone()
+            .map(TryInto::try_into)
+            .transpose()?;
+        let evaluation = input.try_into()?;
+
+        Ok(vrf::GraphQLVrfEvaluation::new(&evaluation, threshold))
+    }
+
+    /// The runtime configuration passed to the daemon at start-up
+    async fn runtime_config(context: &Context) -> juniper::FieldResult<config::GraphQLJson> {
+        let genesis = context
+            .rpc_sender
+            .oneshot_request::<RpcGenesisBlockResponse>(RpcRequest::GenesisBlockGet)
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        Ok(config::GraphQLJson(config::runtime_config(
+            &genesis,
+            constraint_constants(),
+        )))
+    }
+
+    /// The runtime configuration for a blockchain fork intended to be a
+    /// continuation of the current one. Unlike the OCaml node, the ledger
+    /// is referenced by its hash instead of listing its accounts.
+    #[graphql(name = "fork_config")]
+    async fn fork_config(context: &Context) -> juniper::FieldResult<config::GraphQLJson> {
+        let best_tip = context
+            .get_or_fetch_best_tip()
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        Ok(config::GraphQLJson(config::fork_config(
+            &best_tip.block,
+            constraint_constants(),
+        )))
+    }
+
     async fn sync_status(context: &Context) -> juniper::FieldResult<SyncStatus> {
         let state: RpcSyncStatsGetResponse = context
             .rpc_sender
@@ -308,6 +571,18 @@ impl Query {
         }
     }
 
+    async fn block_producer_schedule(
+        context: &Context,
+    ) -> juniper::FieldResult<Option<block_producer::GraphQLBlockProducerSchedule>> {
+        let schedule: RpcBlockProducerScheduleGetResponse = context
+            .rpc_sender
+            .oneshot_request(RpcRequest::BlockProducerScheduleGet)
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        Ok(schedule.map(Into::into))
+    }
+
     async fn best_chain(
         max_length: i32,
         context: &Context,
@@ -569,6 +844,27 @@ impl Query {
     }
 }
 
+/// Grabs the sender's account to get the infered nonce.
+async fn fetch_infered_nonce(public_key: &str, context: &Context) -> Result<Nonce, Error> {
+    // Payment commands are always for the default (MINA) token
+    let token_id = TokenIdKeyHash::default();
+    let public_key = AccountPublicKey::from_str(public_key)
+        .map_err(|e| Error::Conversion(ConversionError::Base58Check(e)))?;
+
+    let accounts: Vec<Account> = context
+        .rpc_sender
+        .oneshot_request(RpcRequest::LedgerAccountsGet(
+            AccountQuery::PubKeyWithTokenId(public_key, token_id),
+        ))
+        .await
+        .ok_or(Error::StateMachineEmptyResponse)?;
+
+    Ok(accounts
+        .first()
+        .ok_or(Error::StateMachineEmptyResponse)?
+        .nonce)
+}
+
 async fn inject_tx<R>(
     cmd: MinaBaseUserCommandStableV2,
     context: &Context,
@@ -632,112 +928,198 @@ impl Mutation {
         inject_tx(input.try_into()?, context).await
     }
 
+    /// Send a payment. If `signature` is omitted, the payment is signed
+    /// by the node, which requires the sender account to be unlocked.
     async fn send_payment(
         input: user_command::InputGraphQLPayment,
-        signature: user_command::UserCommandSignature,
+        signature: Option<user_command::UserCommandSignature>,
         context: &Context,
     ) -> juniper::FieldResult<user_command::GraphQLSendPaymentResponse> {
-        // Grab the sender's account to get the infered nonce
-        let token_id = TokenIdKeyHash::default();
-        let public_key = AccountPublicKey::from_str(&input.from)
-            .map_err(|e| Error::Conversion(ConversionError::Base58Check(e)))?;
-
-        let accounts: Vec<Account> = context
-            .rpc_sender
-            .oneshot_request(RpcRequest::LedgerAccountsGet(
-                AccountQuery::PubKeyWithTokenId(public_key, token_id),
-            ))
-            .await
-            .ok_or(Error::StateMachineEmptyResponse)?;
-
-        let infered_nonce = accounts
-            .first()
-            .ok_or(Error::StateMachineEmptyResponse)?
-            .nonce;
-
-        let command = input
-            .create_user_command(infered_nonce, signature)
-            .map_err(Error::Conversion)?;
+        let infered_nonce = fetch_infered_nonce(&input.from, context).await?;
+        let command = match signature {
+            Some(signature) => input
+                .create_user_command(infered_nonce, signature)
+                .map_err(Error::Conversion)?,
+            None => {
+                let secret_key = context.unlocked_secret_key(&input.from)?;
+                let payload = input.create_payload(infered_nonce)?;
+                user_command::sign_payload(payload, &secret_key)
+            }
+        };
 
         inject_tx(command, context).await
     }
 
+    /// Change your delegate. If `signature` is omitted, the delegation is
+    /// signed by the node, which requires the sender account to be unlocked.
     async fn send_delegation(
         input: user_command::InputGraphQLDelegation,
-        signature: user_command::UserCommandSignature,
+        signature: Option<user_command::UserCommandSignature>,
         context: &Context,
     ) -> juniper::FieldResult<user_command::GraphQLSendDelegationResponse> {
-        // Payment commands are always for the default (MINA) token
-        let token_id = TokenIdKeyHash::default();
-        let public_key = AccountPublicKey::from_str(&input.from)?;
+        let infered_nonce = fetch_infered_nonce(&input.from, context).await?;
+        let command = match signature {
+            Some(signature) => input.create_user_command(infered_nonce, signature)?,
+            None => {
+                let secret_key = context.unlocked_secret_key(&input.from)?;
+                let payload = input.create_payload(infered_nonce)?;
+                user_command::sign_payload(payload, &secret_key)
+            }
+        };
 
-        // Grab the sender's account to get the infered nonce
-        let accounts: Vec<Account> = context
-            .rpc_sender
-            .oneshot_request(RpcRequest::LedgerAccountsGet(
-                AccountQuery::PubKeyWithTokenId(public_key, token_id),
-            ))
-            .await
-            .ok_or(Error::StateMachineEmptyResponse)?;
+        inject_tx(command, context).await
+    }
 
-        let infered_nonce = accounts
-            .first()
-            .ok_or(Error::StateMachineEmptyResponse)?
-            .nonce;
-        let command = input.create_user_command(infered_nonce, signature)?;
+    /// Import an encrypted secret key, given as the contents of the file
+    /// written by `mina accounts export`
+    async fn import_account(
+        encrypted_key: String,
+        password: String,
+        context: &Context,
+    ) -> juniper::FieldResult<keystore::GraphQLImportAccountPayload> {
+        let (public_key, already_imported) =
+            context.keystore()?.import(&encrypted_key, password).await?;
+
+        Ok(keystore::GraphQLImportAccountPayload {
+            public_key: public_key.to_string(),
+            already_imported,
+            success: true,
+        })
+    }
 
-        inject_tx(command, context).await
+    /// Create a new account
+    async fn create_account(
+        input: keystore::InputGraphQLAddAccount,
+        context: &Context,
+    ) -> juniper::FieldResult<keystore::GraphQLAddAccountPayload> {
+        let public_key = context.keystore()?.create(input.password).await?;
+
+        Ok(keystore::GraphQLAddAccountPayload {
+            public_key: public_key.to_string(),
+        })
+    }
+
+    /// Allow transactions to be sent from the unlocked account
+    async fn unlock_account(
+        input: keystore::InputGraphQLUnlock,
+        context: &Context,
+    ) -> juniper::FieldResult<keystore::GraphQLUnlockPayload> {
+        let public_key = AccountPublicKey::from_str(&input.public_key)?;
+        context
+            .keystore()?
+            .unlock(&public_key, input.password)
+            .await?;
+
+        Ok(keystore::GraphQLUnlockPayload {
+            public_key: public_key.to_string(),
+        })
+    }
+
+    /// Lock an unlocked account to prevent transaction being sent from it
+    async fn lock_account(
+        input: keystore::InputGraphQLLock,
+        context: &Context,
+    ) -> juniper::FieldResult<keystore::GraphQLLockPayload> {
+        let public_key = AccountPublicKey::from_str(&input.public_key)?;
+        context.keystore()?.lock(&public_key)?;
+
+        Ok(keystore::GraphQLLockPayload {
+            public_key: public_key.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Subscription;
+
+#[juniper::graphql_subscription(context = Context)]
+impl Subscription {
+    /// Event that triggers when a new block is added to the best chain.
+    /// If `public_key` is set, only blocks produced by it or containing
+    /// its transactions are sent.
+    async fn new_block(
+        public_key: Option<GraphQLPublicKey>,
+        context: &Context,
+    ) -> GraphQLStream<GraphQLBlock> {
+        let events = context.subscribe_transition_frontier().await;
+        let blocks = events.filter_map(move |event| {
+            let public_key = public_key.clone();
+            async move {
+                let RpcTransitionFrontierEvent::NewBestTip(block) = event else {
+                    return None;
+                };
+                match GraphQLBlock::try_from(block) {
+                    Ok(block) => public_key
+                        .map_or(true, |pk| block.involves_public_key(&pk))
+                        .then_some(Ok(block)),
+                    Err(err) => Some(Err(Error::Conversion(err).into())),
+                }
+            }
+        });
+        Box::pin(blocks)
+    }
+
+    /// Event that triggers when the node's sync status changes.
+    async fn new_sync_update(context: &Context) -> GraphQLStream<SyncStatus> {
+        let events = context.subscribe_transition_frontier().await;
+        let updates = events.filter_map(|event| async move {
+            match event {
+                RpcTransitionFrontierEvent::SyncStatus(status) => Some(Ok(status.into())),
+                _ => None,
+            }
+        });
+        Box::pin(updates)
+    }
+
+    /// Event that triggers when the best tip changes in a way that is not
+    /// a trivial extension of the existing one.
+    async fn chain_reorganization(context: &Context) -> GraphQLStream<ChainReorganizationStatus> {
+        let events = context.subscribe_transition_frontier().await;
+        let reorgs = events.filter_map(|event| async move {
+            match event {
+                RpcTransitionFrontierEvent::ChainReorganization { .. } => {
+                    Some(Ok(ChainReorganizationStatus::CHANGED))
+                }
+                _ => None,
+            }
+        });
+        Box::pin(reorgs)
     }
 }
 
 pub fn routes(
     rpc_sernder: RpcSender,
+    admin: HttpAdminConfig,
 ) -> impl Filter<Error = Rejection, Extract = impl Reply> + Clone {
-    let state = warp::any().map(move || Context::new(rpc_sernder.clone()));
-    let schema = RootNode::new(Query, Mutation, EmptySubscription::<Context>::new());
-    let graphql_filter = juniper_warp::make_graphql_filter(schema, state.boxed());
-    let graphiql_filter = juniper_warp::graphiql_filter("/graphql", None);
-    let playground_filter = juniper_warp::playground_filter("/graphql", None);
+    let keystore = admin.keystore.then(|| Arc::new(Keystore::in_work_dir()));
+    let state = {
+        let rpc_sender = rpc_sernder.clone();
+        let keystore = keystore.clone();
+        admin_access(admin)
+            .map(move |access| Context::new(rpc_sender.clone(), keystore.clone(), access))
+    };
+    let schema = Arc::new(RootNode::new(Query, Mutation, Subscription));
+    let graphql_filter = juniper_warp::make_graphql_filter(schema.clone(), state.boxed());
+    let subscriptions_filter = juniper_warp::subscriptions::make_ws_filter(schema, move |_| {
+        // mutations are not served over websockets
+        let context = Context::new(
+            rpc_sernder.clone(),
+            keystore.clone(),
+            Err(AdminAccessError::Unauthorized),
+        );
+        async move { Ok::<_, Infallible>(ConnectionConfig::new(context)) }
+    });
+    let graphiql_filter = juniper_warp::graphiql_filter("/graphql", Some("/subscriptions"));
+    let playground_filter = juniper_warp::playground_filter("/graphql", Some("/subscriptions"));
 
     (warp::post().and(warp::path("graphql")).and(graphql_filter))
+        .or(warp::path("subscriptions").and(subscriptions_filter))
         .or(warp::get()
             .and(warp::path("playground"))
             .and(playground_filter))
         .or(warp::get().and(warp::path("graphiql")).and(graphiql_filter))
-
-    // warp::get()
-    //     .and(warp::path("graphiql"))
-    //     .and(juniper_warp::graphiql_filter("/graphql", None))
-    //     .or(warp::path("graphql").and(graphql_filter))
 }
 
-// let routes = (warp::post()
-//         .and(warp::path("graphql"))
-//         .and(juniper_warp::make_graphql_filter(
-//             schema.clone(),
-//             warp::any().map(|| Context),
-//         )))
-//     .or(
-//         warp::path("subscriptions").and(juniper_warp::subscriptions::make_ws_filter(
-//             schema,
-//             ConnectionConfig::new(Context),
-//         )),
-//     )
-//     .or(warp::get()
-//         .and(warp::path("playground"))
-//         .and(juniper_warp::playground_filter(
-//             "/graphql",
-//             Some("/subscriptions"),
-//         )))
-//     .or(warp::get()
-//         .and(warp::path("graphiql"))
-//         .and(juniper_warp::graphiql_filter(
-//             "/graphql",
-//             Some("/subscriptions"),
-//         )))
-//     .or(homepage)
-//     .with(log);
-
 /// Helper function used by [`Query::pooled_user_commands`] and [`Query::pooled_zkapp_commands`] to parse public key, transaction hashes and command ids
 fn parse_pooled_commands_query<ID, F>(
     public_key: Option<String>,
@@ -778,3 +1160,502 @@ where
         ids,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use juniper::Variables;
+    use ledger::scan_state::currency::Balance;
+    use mina_p2p_messages::{
+        binprot::BinProtRead,
+        v2::{self, CurrencyFeeStableV1, UnsignedExtendedUInt64Int64ForVersionTagsStableV1},
+    };
+    use node::core::channels::{mpsc, oneshot};
+    use o1_utils::field_helpers::FieldHelpers;
+    use openmina_core::block::ArcBlockWithHash;
+    use openmina_node_common::rpc::NodeRpcRequest;
+    use serde_json::json;
+    use vrf::VrfEvaluation;
+
+    use super::*;
+
+    const VRF_SECRET_KEY: &str = "EKEEpMELfQkMbJDt2fB4cFXKwSf1x4t7YD4twREy5yuJ84HBZtF9";
+    /// Account key of the fixtures, `B62qjVQLxt9nYMWGn45mkgwYfcz8e8jvjNCBo11VKJb7vxDNwv5QLPS`
+    const SECRET_KEY: &str = "EKFWgzXsoMYcP1Hnj7dBhsefxNucZ6wyz676Qg5uMFNzytXAi2Ww";
+
+    /// Block of the archive breadcrumb fixture.
+    pub(super) fn breadcrumb_block() -> AppliedBlock {
+        let bytes = include_bytes!("../../../../tests/files/archive-breadcrumb/3NK56ZbCS31qb8SvCtCCYza4beRDtKgXA2JL6s3evKouG2KkKtiy.bin");
+        let diff = v2::ArchiveTransitionFrontierDiff::binprot_read(&mut bytes.as_slice()).unwrap();
+        let v2::ArchiveTransitionFrontierDiff::BreadcrumbAdded {
+            block: (block, _), ..
+        } = diff
+        else {
+            panic!("expected a breadcrumb");
+        };
+        AppliedBlock {
+            block: ArcBlockWithHash::try_new(Arc::new(block)).unwrap(),
+            just_emitted_a_proof: false,
+        }
+    }
+
+    /// Answers the node RPCs made by the queries under test.
+    #[derive(Default)]
+    struct FakeNode {
+        accounts: Vec<Account>,
+        snarker: Option<RpcSnarkerConfig>,
+        genesis: Option<ArcBlockWithHash>,
+        best_tip: Option<AppliedBlock>,
+        vrf_keypair: Option<mina_signer::Keypair>,
+    }
+
+    impl FakeNode {
+        fn accounts(&self, query: &AccountQuery) -> Vec<Account> {
+            let has_public_key = |account: &Account, public_key: &AccountPublicKey| {
+                AccountPublicKey::from(account.public_key.clone()) == *public_key
+            };
+            self.accounts
+                .iter()
+                .filter(|account| match query {
+                    AccountQuery::All => true,
+                    AccountQuery::SinglePublicKey(public_key) => {
+                        has_public_key(account, public_key)
+                    }
+                    AccountQuery::MultipleIds(ids) => ids.contains(&account.id()),
+                    AccountQuery::PubKeyWithTokenId(public_key, token_id) => {
+                        has_public_key(account, public_key)
+                            && account.token_id == token_id.clone().into()
+                    }
+                    AccountQuery::TokenOwner(token_id) => {
+                        account.id().derive_token_id() == token_id.clone().into()
+                    }
+                })
+                .cloned()
+                .collect()
+        }
+
+        fn handle(&self, NodeRpcRequest { req, responder }: NodeRpcRequest) {
+            fn respond<T: 'static>(responder: Box<dyn Send + std::any::Any>, response: T) {
+                let responder = responder
+                    .downcast::<oneshot::Sender<T>>()
+                    .expect("unexpected response type");
+                let _ = responder.send(response);
+            }
+
+            match req {
+                RpcRequest::LedgerAccountsGet(query) => respond(responder, self.accounts(&query)),
+                RpcRequest::BestChain(_) => respond::<RpcBestChainResponse>(
+                    responder,
+                    self.best_tip.iter().cloned().collect(),
+                ),
+                RpcRequest::StatusGet => respond::<RpcStatusGetResponse>(responder, None),
+                RpcRequest::SnarkerConfig => respond(responder, self.snarker.clone()),
+                RpcRequest::GenesisBlockGet => respond(responder, self.genesis.clone()),
+                RpcRequest::VrfEvaluate(public_key, message) => {
+                    let evaluation: RpcVrfEvaluateResponse = self
+                        .vrf_keypair
+                        .as_ref()
+                        .filter(|keypair| {
+                            AccountPublicKey::from(keypair.public.clone()) == public_key
+                        })
+                        .map(|keypair| VrfEvaluation::prove(keypair, message).unwrap());
+                    respond(responder, evaluation)
+                }
+                req => panic!("unexpected request: {req:?}"),
+            }
+        }
+
+        fn spawn(self) -> RpcSender {
+            let (tx, mut rx) = mpsc::channel(8);
+            tokio::spawn(async move {
+                while let Some(request) = rx.recv().await {
+                    self.handle(request);
+                }
+            });
+            RpcSender::new(tx)
+        }
+    }
+
+    fn temp_keystore(name: &str) -> Keystore {
+        let dir =
+            std::env::temp_dir().join(format!("openmina-graphql-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Keystore::new(dir)
+    }
+
+    /// Imports `secret_key` into `keystore`, the way `importAccount` does.
+    async fn import_key(keystore: &Keystore, secret_key: &str) -> AccountPublicKey {
+        let secret_key = AccountSecretKey::from_str(secret_key).unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "openmina-graphql-{}-{}",
+            secret_key.public_key(),
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        secret_key.to_encrypted_file(&path, "pass").unwrap();
+        let encrypted = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let (public_key, _) = keystore
+            .import(&encrypted, "pass".to_owned())
+            .await
+            .unwrap();
+        public_key
+    }
+
+    async fn execute(node: FakeNode, keystore: Keystore, query: &str) -> serde_json::Value {
+        let context = Context::new(node.spawn(), Some(Arc::new(keystore)), Ok(()));
+        let schema = RootNode::new(Query, Mutation, Subscription);
+        let (value, errors) = juniper::execute(query, None, &schema, &Variables::new(), &context)
+            .await
+            .unwrap();
+        assert!(errors.is_empty(), "{errors:?}");
+        serde_json::to_value(value).unwrap()
+    }
+
+    fn fixture(json: &str) -> serde_json::Value {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn public_key_of(secret_key: &str) -> AccountPublicKey {
+        AccountSecretKey::from_str(secret_key).unwrap().public_key()
+    }
+
+    fn account(public_key: &AccountPublicKey, balance: u64) -> Account {
+        let account_id = AccountId::new(public_key.clone().try_into().unwrap(), Default::default());
+        Account::create_with(account_id, Balance::from_u64(balance))
+    }
+
+    #[tokio::test]
+    async fn test_accounts() {
+        let public_key = public_key_of(SECRET_KEY);
+        let other = public_key_of(VRF_SECRET_KEY);
+        let node = FakeNode {
+            accounts: vec![account(&public_key, 1_000), account(&other, 2_000)],
+            ..Default::default()
+        };
+
+        let response = execute(
+            node,
+            Keystore::default(),
+            &format!(
+                r#"{{ accounts(publicKey: "{public_key}") {{
+                    publicKey tokenId token nonce balance {{ total }}
+                }} }}"#
+            ),
+        )
+        .await;
+        assert_eq!(response, fixture(include_str!("fixtures/accounts.json")));
+    }
+
+    #[tokio::test]
+    async fn test_token_owner() {
+        let owner = account(&public_key_of(SECRET_KEY), 1_000);
+        let token = TokenIdKeyHash::from(owner.id().derive_token_id());
+        let node = FakeNode {
+            accounts: vec![account(&public_key_of(VRF_SECRET_KEY), 2_000), owner],
+            ..Default::default()
+        };
+
+        let response = execute(
+            node,
+            Keystore::default(),
+            &format!(r#"{{ tokenOwner(token: "{token}") {{ publicKey }} }}"#),
+        )
+        .await;
+        assert_eq!(response, fixture(include_str!("fixtures/token_owner.json")));
+
+        let response = execute(
+            FakeNode::default(),
+            Keystore::default(),
+            &format!(r#"{{ tokenOwner(token: "{token}") {{ publicKey }} }}"#),
+        )
+        .await;
+        assert_eq!(response, json!({ "tokenOwner": null }));
+    }
+
+    #[tokio::test]
+    async fn test_tracked_accounts() {
+        let keystore = temp_keystore("tracked");
+        let first = import_key(&keystore, SECRET_KEY).await;
+        let second = import_key(&keystore, VRF_SECRET_KEY).await;
+        let untracked = AccountSecretKey::rand().public_key();
+        let node = FakeNode {
+            accounts: vec![
+                account(&second, 2_000),
+                account(&untracked, 3_000),
+                account(&first, 1_000),
+            ],
+            // the snark worker key is also in the keystore
+            snarker: Some(RpcSnarkerConfig {
+                public_key: first.clone().into(),
+                fee: CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+                    10_u64.into(),
+                )),
+            }),
+            ..Default::default()
+        };
+
+        let response = execute(
+            node,
+            keystore,
+            "{ trackedAccounts { publicKey balance { total } } }",
+        )
+        .await;
+        assert_eq!(
+            response,
+            fixture(include_str!("fixtures/tracked_accounts.json"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keystore_mutations_require_admin_access() {
+        let schema = RootNode::new(Query, Mutation, Subscription);
+        let mutation = r#"mutation { createAccount(input: { password: "pass" }) { publicKey } }"#;
+        let keystore = Arc::new(temp_keystore("denied"));
+
+        for (keystore, access) in [
+            (None, Ok(())),
+            (Some(keystore.clone()), Err(AdminAccessError::NotLocal)),
+            (Some(keystore.clone()), Err(AdminAccessError::Unauthorized)),
+        ] {
+            let context = Context::new(FakeNode::default().spawn(), keystore, access);
+            let (_, errors) =
+                juniper::execute(mutation, None, &schema, &Variables::new(), &context)
+                    .await
+                    .unwrap();
+            assert_eq!(errors.len(), 1);
+        }
+        assert!(keystore.public_keys().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_payment() {
+        let secret_key = AccountSecretKey::rand();
+        let sender = account(&secret_key.public_key(), 1_000_000_000_000);
+        let input = user_command::InputGraphQLPayment {
+            from: secret_key.public_key().to_string(),
+            to: AccountSecretKey::rand().public_key().to_string(),
+            amount: "1000000000".to_owned(),
+            valid_until: None,
+            fee: "10000000".to_owned(),
+            memo: None,
+            nonce: None,
+        };
+        let payload = input.create_payload(sender.nonce).unwrap();
+        let MinaBaseUserCommandStableV2::SignedCommand(command) =
+            user_command::sign_payload(payload, &secret_key)
+        else {
+            unreachable!()
+        };
+        let signature = mina_signer::Signature::try_from(&*command.signature).unwrap();
+        let raw_signature = format!(
+            "{}{}",
+            hex::encode(signature.rx.to_bytes()),
+            hex::encode(signature.s.to_bytes())
+        );
+
+        let validate = |amount: &str| {
+            let node = FakeNode {
+                accounts: vec![sender.clone()],
+                ..Default::default()
+            };
+            let query = format!(
+                r#"{{ validatePayment(
+                    input: {{ from: "{}", to: "{}", amount: "{amount}", fee: "{}" }},
+                    signature: {{ rawSignature: "{raw_signature}" }}
+                ) }}"#,
+                input.from, input.to, input.fee,
+            );
+            async move { execute(node, Keystore::default(), &query).await }
+        };
+
+        assert_eq!(
+            validate(&input.amount).await,
+            fixture(include_str!("fixtures/validate_payment.json"))
+        );
+        assert_eq!(
+            validate("2000000000").await,
+            json!({ "validatePayment": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_vrf_requires_admin_access() {
+        let schema = RootNode::new(Query, Mutation, Subscription);
+        let query = format!(
+            r#"{{ evaluateVrf(
+                message: {{
+                    globalSlot: "6",
+                    epochSeed: "2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA",
+                    delegatorIndex: 2
+                }},
+                publicKey: "{}"
+            ) {{ vrfOutput }} }}"#,
+            public_key_of(VRF_SECRET_KEY)
+        );
+
+        for access in [
+            Err(AdminAccessError::Disabled),
+            Err(AdminAccessError::NotLocal),
+            Err(AdminAccessError::Unauthorized),
+        ] {
+            let node = FakeNode {
+                vrf_keypair: Some(vrf::keypair_from_bs58_string(VRF_SECRET_KEY)),
+                ..Default::default()
+            };
+            let context = Context::new(node.spawn(), None, access);
+            let (_, errors) = juniper::execute(&query, None, &schema, &Variables::new(), &context)
+                .await
+                .unwrap();
+            assert_eq!(errors.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_and_check_vrf() {
+        let keypair = vrf::keypair_from_bs58_string(VRF_SECRET_KEY);
+        let public_key = AccountPublicKey::from(keypair.public.clone());
+        let node = FakeNode {
+            vrf_keypair: Some(keypair),
+            ..Default::default()
+        };
+        let threshold = r#"{ delegatedStake: "1000000000000000", totalStake: "6000000000001000" }"#;
+
+        let response = execute(
+            node,
+            Keystore::default(),
+            &format!(
+                r#"{{ evaluateVrf(
+                    message: {{
+                        globalSlot: "6",
+                        epochSeed: "2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA",
+                        delegatorIndex: 2
+                    }},
+                    publicKey: "{public_key}",
+                    vrfThreshold: {threshold}
+                ) {{
+                    message {{ globalSlot epochSeed delegatorIndex }}
+                    publicKey c s scaledMessageHash
+                    vrfThreshold {{ delegatedStake totalStake }}
+                    vrfOutput vrfOutputFractional thresholdMet
+                }} }}"#
+            ),
+        )
+        .await;
+        let evaluation = &response["evaluateVrf"];
+
+        // `c` and `s` are randomized, everything else is deterministic
+        let expected: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/evaluate_vrf.json")).unwrap();
+        let mut outputs = evaluation.clone();
+        for field in ["c", "s", "scaledMessageHash"] {
+            outputs.as_object_mut().unwrap().remove(field);
+        }
+        assert_eq!(outputs, expected);
+
+        let check_vrf = |s: &serde_json::Value| {
+            let query = format!(
+                r#"{{ checkVrf(input: {{
+                    message: {{ globalSlot: {}, epochSeed: {}, delegatorIndex: {} }},
+                    publicKey: {},
+                    c: {},
+                    s: {s},
+                    scaledMessageHash: {},
+                    vrfThreshold: {threshold}
+                }}) {{ vrfOutput vrfOutputFractional thresholdMet }} }}"#,
+                evaluation["message"]["globalSlot"],
+                evaluation["message"]["epochSeed"],
+                evaluation["message"]["delegatorIndex"],
+                evaluation["publicKey"],
+                evaluation["c"],
+                evaluation["scaledMessageHash"],
+            );
+            async move { execute(FakeNode::default(), Keystore::default(), &query).await }
+        };
+
+        assert_eq!(
+            check_vrf(&evaluation["s"]).await,
+            json!({
+                "checkVrf": {
+                    "vrfOutput": expected["vrfOutput"],
+                    "vrfOutputFractional": expected["vrfOutputFractional"],
+                    "thresholdMet": expected["thresholdMet"],
+                }
+            })
+        );
+
+        // a witness that doesn't verify has no outputs
+        assert_eq!(
+            check_vrf(&json!("1")).await,
+            json!({
+                "checkVrf": {
+                    "vrfOutput": null,
+                    "vrfOutputFractional": null,
+                    "thresholdMet": null,
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runtime_config() {
+        let genesis = breadcrumb_block().block;
+        let node = FakeNode {
+            genesis: Some(genesis.clone()),
+            ..Default::default()
+        };
+
+        let response = execute(node, Keystore::default(), "{ runtimeConfig }").await;
+        let config = &response["runtimeConfig"];
+        let expected_proof: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/runtime_config_proof_devnet.json"))
+                .unwrap();
+        assert_eq!(config["proof"], expected_proof);
+        assert_eq!(
+            config["ledger"]["hash"],
+            json!(genesis.merkle_root_hash().to_string())
+        );
+        assert_eq!(
+            config["genesis"]["k"],
+            json!(genesis.constants().k.as_u32())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fork_config() {
+        let best_tip = breadcrumb_block();
+        let block = best_tip.block.clone();
+        let node = FakeNode {
+            best_tip: Some(best_tip),
+            ..Default::default()
+        };
+
+        let response = execute(node, Keystore::default(), "{ fork_config }").await;
+        let config = &response["fork_config"];
+
+        // same constants as the runtime config, but forking at the best tip
+        let mut expected_proof = fixture(include_str!("fixtures/runtime_config_proof_devnet.json"));
+        expected_proof["fork"] = json!({
+            "state_hash": block.hash().to_string(),
+            "blockchain_length": block.height(),
+            "global_slot_since_genesis": block.global_slot_since_genesis(),
+        });
+        assert_eq!(config["proof"], expected_proof);
+        assert_eq!(
+            config["ledger"],
+            json!({
+                "hash": block.merkle_root_hash().to_string(),
+                "add_genesis_winner": false,
+            })
+        );
+        assert_eq!(
+            config["epoch_data"]["staking"]["hash"],
+            json!(block
+                .consensus_state()
+                .staking_epoch_data
+                .ledger
+                .hash
+                .to_string())
+        );
+    }
+}
@@ -3,15 +3,16 @@ use std::str::FromStr;
 use juniper::{GraphQLInputObject, GraphQLObject};
 use ledger::scan_state::{
     currency::{Amount, Fee, Magnitude, Nonce, Slot},
-    transaction_logic::{signed_command, Memo},
+    transaction_logic::{signed_command, transaction_union_payload::TransactionUnionPayload, Memo},
 };
 use mina_p2p_messages::{
     bigint::BigInt,
     v2::{self, TokenIdKeyHash},
 };
-use mina_signer::CompressedPubKey;
-use node::account::AccountPublicKey;
+use mina_signer::{CompressedPubKey, Keypair, Signer};
+use node::account::{AccountPublicKey, AccountSecretKey};
 use o1_utils::field_helpers::FieldHelpers;
+use openmina_core::{network::NetworkId, NetworkConfig};
 
 use super::zkapp::GraphQLFailureReason;
 
@@ -224,6 +225,27 @@ impl InputGraphQLPayment {
         infered_nonce: Nonce,
         signature: UserCommandSignature,
     ) -> Result<v2::MinaBaseUserCommandStableV2, super::ConversionError> {
+        let sc = self.create_signed_command(infered_nonce, signature)?;
+        Ok(v2::MinaBaseUserCommandStableV2::SignedCommand(sc.into()))
+    }
+
+    pub fn create_signed_command(
+        &self,
+        infered_nonce: Nonce,
+        signature: UserCommandSignature,
+    ) -> Result<signed_command::SignedCommand, super::ConversionError> {
+        let payload = self.create_payload(infered_nonce)?;
+        Ok(signed_command::SignedCommand {
+            signer: payload.common.fee_payer_pk.clone(),
+            payload,
+            signature: signature.try_into()?,
+        })
+    }
+
+    pub fn create_payload(
+        &self,
+        infered_nonce: Nonce,
+    ) -> Result<signed_command::SignedCommandPayload, super::ConversionError> {
         let infered_nonce = infered_nonce.incr();
 
         let nonce = if let Some(nonce) = &self.nonce {
@@ -265,35 +287,27 @@ impl InputGraphQLPayment {
             .try_into()
             .map_err(|_| super::ConversionError::InvalidBigInt)?;
 
-        let signature = signature.try_into()?;
-
-        let sc: signed_command::SignedCommand = signed_command::SignedCommand {
-            payload: signed_command::SignedCommandPayload::create(
-                Fee::from_u64(
-                    self.fee
+        Ok(signed_command::SignedCommandPayload::create(
+            Fee::from_u64(
+                self.fee
+                    .parse::<u64>()
+                    .map_err(|_| super::ConversionError::InvalidBigInt)?,
+            ),
+            from,
+            nonce,
+            valid_until,
+            memo,
+            signed_command::Body::Payment(signed_command::PaymentPayload {
+                receiver_pk: AccountPublicKey::from_str(&self.to)?
+                    .try_into()
+                    .map_err(|_| super::ConversionError::InvalidBigInt)?,
+                amount: Amount::from_u64(
+                    self.amount
                         .parse::<u64>()
                         .map_err(|_| super::ConversionError::InvalidBigInt)?,
                 ),
-                from.clone(),
-                nonce,
-                valid_until,
-                memo,
-                signed_command::Body::Payment(signed_command::PaymentPayload {
-                    receiver_pk: AccountPublicKey::from_str(&self.to)?
-                        .try_into()
-                        .map_err(|_| super::ConversionError::InvalidBigInt)?,
-                    amount: Amount::from_u64(
-                        self.amount
-                            .parse::<u64>()
-                            .map_err(|_| super::ConversionError::InvalidBigInt)?,
-                    ),
-                }),
-            ),
-            signer: from.clone(),
-            signature,
-        };
-
-        Ok(v2::MinaBaseUserCommandStableV2::SignedCommand(sc.into()))
+            }),
+        ))
     }
 }
 
@@ -303,6 +317,19 @@ impl InputGraphQLDelegation {
         infered_nonce: Nonce,
         signature: UserCommandSignature,
     ) -> Result<v2::MinaBaseUserCommandStableV2, super::ConversionError> {
+        let payload = self.create_payload(infered_nonce)?;
+        let sc = signed_command::SignedCommand {
+            signer: payload.common.fee_payer_pk.clone(),
+            payload,
+            signature: signature.try_into()?,
+        };
+        Ok(v2::MinaBaseUserCommandStableV2::SignedCommand(sc.into()))
+    }
+
+    pub fn create_payload(
+        &self,
+        infered_nonce: Nonce,
+    ) -> Result<signed_command::SignedCommandPayload, super::ConversionError> {
         let infered_nonce = infered_nonce.incr();
 
         let nonce = if let Some(nonce) = &self.nonce {
@@ -344,31 +371,48 @@ impl InputGraphQLDelegation {
             .try_into()
             .map_err(|_| super::ConversionError::InvalidBigInt)?;
 
-        let signature = signature.try_into()?;
-
-        let sc: signed_command::SignedCommand = signed_command::SignedCommand {
-            payload: signed_command::SignedCommandPayload::create(
-                Fee::from_u64(
-                    self.fee
-                        .parse::<u64>()
+        Ok(signed_command::SignedCommandPayload::create(
+            Fee::from_u64(
+                self.fee
+                    .parse::<u64>()
+                    .map_err(|_| super::ConversionError::InvalidBigInt)?,
+            ),
+            from,
+            nonce,
+            valid_until,
+            memo,
+            signed_command::Body::StakeDelegation(
+                signed_command::StakeDelegationPayload::SetDelegate {
+                    new_delegate: AccountPublicKey::from_str(&self.to)?
+                        .try_into()
                         .map_err(|_| super::ConversionError::InvalidBigInt)?,
-                ),
-                from.clone(),
-                nonce,
-                valid_until,
-                memo,
-                signed_command::Body::StakeDelegation(
-                    signed_command::StakeDelegationPayload::SetDelegate {
-                        new_delegate: AccountPublicKey::from_str(&self.to)?
-                            .try_into()
-                            .map_err(|_| super::ConversionError::InvalidBigInt)?,
-                    },
-                ),
+                },
             ),
-            signer: from.clone(),
-            signature,
-        };
-
-        Ok(v2::MinaBaseUserCommandStableV2::SignedCommand(sc.into()))
+        ))
     }
 }
+
+/// Signs `payload` with a secret key held by the node, for commands sent
+/// without a signature from an unlocked account.
+pub fn sign_payload(
+    payload: signed_command::SignedCommandPayload,
+    secret_key: &AccountSecretKey,
+) -> v2::MinaBaseUserCommandStableV2 {
+    let network_id = match NetworkConfig::global().network_id {
+        NetworkId::TESTNET => mina_signer::NetworkId::TESTNET,
+        NetworkId::MAINNET => mina_signer::NetworkId::MAINNET,
+    };
+    let mut signer = mina_signer::create_legacy(network_id);
+    let keypair = Keypair::from(secret_key.clone());
+    let signature = signer.sign(
+        &keypair,
+        &TransactionUnionPayload::of_user_command_payload(&payload),
+    );
+
+    let sc = signed_command::SignedCommand {
+        payload,
+        signer: secret_key.public_key_compressed(),
+        signature,
+    };
+    v2::MinaBaseUserCommandStableV2::SignedCommand(sc.into())
+}
@@ -0,0 +1,282 @@
+use std::str::FromStr;
+
+use juniper::{GraphQLInputObject, GraphQLObject};
+use ledger::FpExt;
+use mina_p2p_messages::v2::EpochSeed;
+use mina_signer::{BaseField, CurvePoint, ScalarField};
+use node::account::AccountPublicKey;
+use vrf::{VrfEvaluation, VrfMessage};
+
+use super::{ConversionError, GraphQLPublicKey};
+
+/// Location [src/lib/mina_graphql/types.ml](https://github.com/MinaProtocol/mina/blob/develop/src/lib/mina_graphql/types.ml)
+#[derive(GraphQLObject, Debug, Clone, PartialEq)]
+#[graphql(name = "VrfMessage")]
+pub struct GraphQLVrfMessage {
+    pub global_slot: String,
+    pub epoch_seed: String,
+    pub delegator_index: i32,
+}
+
+#[derive(GraphQLObject, Debug, Clone, PartialEq)]
+#[graphql(name = "VrfThreshold")]
+pub struct GraphQLVrfThreshold {
+    pub delegated_stake: String,
+    pub total_stake: String,
+}
+
+/// The witness to a vrf evaluation, which may be decoded and verified
+/// using `checkVrf`.
+#[derive(GraphQLObject, Debug, Clone, PartialEq)]
+#[graphql(name = "VrfEvaluation")]
+pub struct GraphQLVrfEvaluation {
+    pub message: GraphQLVrfMessage,
+    pub public_key: GraphQLPublicKey,
+    pub c: String,
+    pub s: String,
+    /// A group element represented as 2 field elements
+    pub scaled_message_hash: Vec<String>,
+    pub vrf_threshold: Option<GraphQLVrfThreshold>,
+    /// The vrf output derived from the evaluation witness. If null, the vrf
+    /// witness was invalid.
+    pub vrf_output: Option<String>,
+    /// The vrf output derived from the evaluation witness, as a fraction.
+    /// This represents a won slot if vrfOutputFractional <= (1 -
+    /// (1 / 4)^(delegated_balance / total_stake)). If null, the vrf witness
+    /// was invalid.
+    pub vrf_output_fractional: Option<f64>,
+    /// Whether the threshold to produce a block was met, if specified
+    pub threshold_met: Option<bool>,
+}
+
+#[derive(GraphQLInputObject, Debug, Clone)]
+#[graphql(name = "VrfMessageInput")]
+pub struct InputGraphQLVrfMessage {
+    pub global_slot: String,
+    pub epoch_seed: String,
+    pub delegator_index: i32,
+}
+
+#[derive(GraphQLInputObject, Debug, Clone)]
+#[graphql(name = "VrfThresholdInput")]
+pub struct InputGraphQLVrfThreshold {
+    pub delegated_stake: String,
+    pub total_stake: String,
+}
+
+#[derive(GraphQLInputObject, Debug, Clone)]
+#[graphql(name = "VrfEvaluationInput")]
+pub struct InputGraphQLVrfEvaluation {
+    pub message: InputGraphQLVrfMessage,
+    pub public_key: GraphQLPublicKey,
+    pub c: String,
+    pub s: String,
+    pub scaled_message_hash: Vec<String>,
+    pub vrf_threshold: Option<InputGraphQLVrfThreshold>,
+}
+
+/// Stake distribution the vrf output is compared against.
+#[derive(Debug, Clone, Copy)]
+pub struct VrfThreshold {
+    pub delegated_stake: u64,
+    pub total_stake: u64,
+}
+
+impl TryFrom<InputGraphQLVrfMessage> for VrfMessage {
+    type Error = ConversionError;
+
+    fn try_from(value: InputGraphQLVrfMessage) -> Result<Self, Self::Error> {
+        Ok(VrfMessage::new(
+            value.global_slot.parse()?,
+            EpochSeed::from_str(&value.epoch_seed)?,
+            value.delegator_index.try_into()?,
+        ))
+    }
+}
+
+impl TryFrom<InputGraphQLVrfThreshold> for VrfThreshold {
+    type Error = ConversionError;
+
+    fn try_from(value: InputGraphQLVrfThreshold) -> Result<Self, Self::Error> {
+        Ok(Self {
+            delegated_stake: value.delegated_stake.parse()?,
+            total_stake: value.total_stake.parse()?,
+        })
+    }
+}
+
+impl TryFrom<InputGraphQLVrfEvaluation> for VrfEvaluation {
+    type Error = ConversionError;
+
+    fn try_from(value: InputGraphQLVrfEvaluation) -> Result<Self, Self::Error> {
+        let field = |s: &str| {
+            BaseField::from_str(s)
+                .map_err(|_| ConversionError::Custom(format!("Invalid field: {s}")))
+        };
+        let scalar = |s: &str| {
+            ScalarField::from_str(s)
+                .map_err(|_| ConversionError::Custom(format!("Invalid scalar: {s}")))
+        };
+
+        let [x, y] = value.scaled_message_hash.as_slice() else {
+            return Err(ConversionError::InvalidLength);
+        };
+        let scaled_message_hash = CurvePoint::new(field(x)?, field(y)?, false);
+        if !scaled_message_hash.is_on_curve() {
+            return Err(ConversionError::Custom(
+                "scaledMessageHash is not on the curve".to_owned(),
+            ));
+        }
+
+        Ok(VrfEvaluation {
+            message: value.message.try_into()?,
+            public_key: AccountPublicKey::from_str(&value.public_key)?,
+            c: scalar(&value.c)?,
+            s: scalar(&value.s)?,
+            scaled_message_hash,
+        })
+    }
+}
+
+impl GraphQLVrfEvaluation {
+    /// Outputs are only derived if the evaluation witness is valid.
+    pub fn new(evaluation: &VrfEvaluation, threshold: Option<VrfThreshold>) -> Self {
+        let valid = evaluation.verify().unwrap_or(false);
+        let output = valid.then(|| evaluation.output());
+
+        Self {
+            message: GraphQLVrfMessage {
+                global_slot: evaluation.message.global_slot().to_string(),
+                epoch_seed: evaluation.message.epoch_seed().to_string(),
+                delegator_index: evaluation.message.delegator_index() as i32,
+            },
+            public_key: evaluation.public_key.to_string(),
+            c: evaluation.c.to_decimal(),
+            s: evaluation.s.to_decimal(),
+            scaled_message_hash: vec![
+                evaluation.scaled_message_hash.x.to_decimal(),
+                evaluation.scaled_message_hash.y.to_decimal(),
+            ],
+            vrf_threshold: threshold.map(|threshold| GraphQLVrfThreshold {
+                delegated_stake: threshold.delegated_stake.to_string(),
+                total_stake: threshold.total_stake.to_string(),
+            }),
+            vrf_output: output.as_ref().map(|output| output.to_string()),
+            vrf_output_fractional: output.as_ref().map(|output| output.fractional()),
+            threshold_met: threshold.filter(|_| valid).map(|threshold| {
+                evaluation.threshold_met(threshold.delegated_stake, threshold.total_stake)
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `evaluateVrf` response of the OCaml node, without the randomized `c` and `s`.
+    fn ocaml_response() -> serde_json::Value {
+        serde_json::from_str(include_str!("fixtures/evaluate_vrf.json")).unwrap()
+    }
+
+    fn evaluate() -> VrfEvaluation {
+        let keypair =
+            vrf::keypair_from_bs58_string("EKEEpMELfQkMbJDt2fB4cFXKwSf1x4t7YD4twREy5yuJ84HBZtF9");
+        let message = InputGraphQLVrfMessage {
+            global_slot: "6".to_owned(),
+            epoch_seed: "2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA".to_owned(),
+            delegator_index: 2,
+        };
+        VrfEvaluation::prove(&keypair, message.try_into().unwrap()).unwrap()
+    }
+
+    fn threshold() -> VrfThreshold {
+        InputGraphQLVrfThreshold {
+            delegated_stake: "1000000000000000".to_owned(),
+            total_stake: "6000000000001000".to_owned(),
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    fn assert_matches_ocaml(evaluation: &GraphQLVrfEvaluation) {
+        let expected = ocaml_response();
+        assert_eq!(
+            evaluation.message,
+            GraphQLVrfMessage {
+                global_slot: expected["message"]["globalSlot"]
+                    .as_str()
+                    .unwrap()
+                    .to_owned(),
+                epoch_seed: expected["message"]["epochSeed"]
+                    .as_str()
+                    .unwrap()
+                    .to_owned(),
+                delegator_index: expected["message"]["delegatorIndex"].as_i64().unwrap() as i32,
+            }
+        );
+        assert_eq!(
+            evaluation.public_key,
+            expected["publicKey"].as_str().unwrap()
+        );
+        assert_eq!(
+            evaluation.vrf_threshold,
+            Some(GraphQLVrfThreshold {
+                delegated_stake: expected["vrfThreshold"]["delegatedStake"]
+                    .as_str()
+                    .unwrap()
+                    .to_owned(),
+                total_stake: expected["vrfThreshold"]["totalStake"]
+                    .as_str()
+                    .unwrap()
+                    .to_owned(),
+            })
+        );
+        assert_eq!(
+            evaluation.vrf_output.as_deref(),
+            expected["vrfOutput"].as_str()
+        );
+        assert_eq!(
+            evaluation.vrf_output_fractional,
+            expected["vrfOutputFractional"].as_f64()
+        );
+        assert_eq!(evaluation.threshold_met, expected["thresholdMet"].as_bool());
+    }
+
+    #[test]
+    fn test_evaluate_vrf_matches_ocaml() {
+        let evaluation = GraphQLVrfEvaluation::new(&evaluate(), Some(threshold()));
+        assert_matches_ocaml(&evaluation);
+    }
+
+    fn to_input(evaluation: &GraphQLVrfEvaluation) -> InputGraphQLVrfEvaluation {
+        InputGraphQLVrfEvaluation {
+            message: InputGraphQLVrfMessage {
+                global_slot: evaluation.message.global_slot.clone(),
+                epoch_seed: evaluation.message.epoch_seed.clone(),
+                delegator_index: evaluation.message.delegator_index,
+            },
+            public_key: evaluation.public_key.clone(),
+            c: evaluation.c.clone(),
+            s: evaluation.s.clone(),
+            scaled_message_hash: evaluation.scaled_message_hash.clone(),
+            vrf_threshold: None,
+        }
+    }
+
+    #[test]
+    fn test_check_vrf_roundtrip() {
+        let evaluation = GraphQLVrfEvaluation::new(&evaluate(), Some(threshold()));
+        let checked: VrfEvaluation = to_input(&evaluation).try_into().unwrap();
+        assert_matches_ocaml(&GraphQLVrfEvaluation::new(&checked, Some(threshold())));
+
+        // tampering with the witness invalidates the outputs
+        let forged = VrfEvaluation {
+            s: checked.s + ScalarField::from(1u64),
+            ..checked
+        };
+        let forged = GraphQLVrfEvaluation::new(&forged, Some(threshold()));
+        assert_eq!(forged.vrf_output, None);
+        assert_eq!(forged.threshold_met, None);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{convert::Infallible, mem::size_of, str::FromStr};
+use std::{convert::Infallible, mem::size_of, net::SocketAddr, str::FromStr};
 
 use mina_p2p_messages::binprot::BinProtWrite;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -10,6 +10,7 @@ use warp::{
 };
 
 use node::core::snark::SnarkJobId;
+use node::p2p::PeerId;
 use node::rpc::*;
 
 use openmina_node_common::rpc::{
@@ -26,7 +27,7 @@ macro_rules! compose_route {
     );
 }
 
-pub async fn run(port: u16, rpc_sender: RpcSender) {
+pub async fn run(port: u16, rpc_sender: RpcSender, admin: HttpAdminConfig) {
     let build_env_get = warp::path!("build_env")
         .and(warp::get())
         .then(move || async { with_json_reply(&node::BuildEnv::get(), StatusCode::OK) });
@@ -166,6 +167,117 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
         }
     });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let archive_status = warp::path!("archive" / "status")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                let result: RpcArchiveStatusGetResponse = rpc_sender_clone
+                    .oneshot_request(RpcRequest::ArchiveStatusGet)
+                    .await
+                    .flatten();
+
+                with_json_reply(&result, StatusCode::OK)
+            }
+        });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let archive_backfill = warp::path!("archive" / "backfill")
+        .and(warp::post())
+        .and(admin_access(admin.clone()))
+        .and(warp::filters::body::json())
+        .then(
+            move |access: Result<(), AdminAccessError>, query: RpcArchiveBackfillQuery| {
+                let rpc_sender_clone = rpc_sender_clone.clone();
+                async move {
+                    if let Err(error) = access {
+                        return admin_access_denied_reply(error);
+                    }
+                    if let Err(error) = query.validate() {
+                        return with_json_reply(&error, StatusCode::BAD_REQUEST);
+                    }
+                    let result: Option<RpcArchiveBackfillResponse> = rpc_sender_clone
+                        .oneshot_request(RpcRequest::ArchiveBackfill(query))
+                        .await;
+                    match result {
+                        None => with_json_reply(
+                            &"response channel dropped",
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                        ),
+                        Some(None) => with_json_reply(
+                            &"node is not running in archive mode",
+                            StatusCode::BAD_REQUEST,
+                        ),
+                        Some(Some(backfill)) => with_json_reply(&backfill, StatusCode::OK),
+                    }
+                }
+            },
+        );
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let p2p_bans_get = warp::path!("p2p" / "bans").and(warp::get()).then(move || {
+        let rpc_sender_clone = rpc_sender_clone.clone();
+        async move {
+            let result: Option<RpcP2pBansGetResponse> = rpc_sender_clone
+                .oneshot_request(RpcRequest::P2pBansGet)
+                .await;
+            with_json_reply(&result, StatusCode::OK)
+        }
+    });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let p2p_bandwidth_get = warp::path!("p2p" / "bandwidth")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                let result: Option<RpcP2pBandwidthGetResponse> = rpc_sender_clone
+                    .oneshot_request(RpcRequest::P2pBandwidthGet)
+                    .await;
+                with_json_reply(&result, StatusCode::OK)
+            }
+        });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let p2p_ban = warp::path!("p2p" / "bans")
+        .and(warp::post())
+        .and(admin_access(admin.clone()))
+        .and(warp::filters::body::json())
+        .then(
+            move |access: Result<(), AdminAccessError>, query: RpcP2pBanQuery| {
+                let rpc_sender_clone = rpc_sender_clone.clone();
+                async move {
+                    if let Err(error) = access {
+                        return admin_access_denied_reply(error);
+                    }
+                    let result: Option<RpcP2pBanResponse> = rpc_sender_clone
+                        .oneshot_request(RpcRequest::P2pBan(query))
+                        .await;
+                    p2p_ban_reply(result)
+                }
+            },
+        );
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let p2p_unban = warp::path!("p2p" / "bans" / PeerId)
+        .and(warp::delete())
+        .and(admin_access(admin.clone()))
+        .then(
+            move |peer_id: PeerId, access: Result<(), AdminAccessError>| {
+                let rpc_sender_clone = rpc_sender_clone.clone();
+                async move {
+                    if let Err(error) = access {
+                        return admin_access_denied_reply(error);
+                    }
+                    let result: Option<RpcP2pUnbanResponse> = rpc_sender_clone
+                        .oneshot_request(RpcRequest::P2pUnban(peer_id))
+                        .await;
+                    p2p_ban_reply(result)
+                }
+            },
+        );
+
     let rpc_sender_clone = rpc_sender.clone();
     let make_heartbeat = warp::path!("make_heartbeat")
         .and(warp::post())
@@ -566,6 +678,21 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let block_producer_schedule = warp::path!("block-producer" / "schedule")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                let result: RpcBlockProducerScheduleGetResponse = rpc_sender_clone
+                    .oneshot_request(RpcRequest::BlockProducerScheduleGet)
+                    .await
+                    .flatten();
+
+                with_json_reply(&result, StatusCode::OK)
+            }
+        });
+
     let cors = warp::cors()
         .allow_any_origin()
         .allow_methods(["GET", "POST", "PUT", "DELETE", "OPTIONS"])
@@ -586,10 +713,17 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
         build_env_get,
         routes,
         status,
+        archive_status,
+        archive_backfill,
+        p2p_bans_get,
+        p2p_ban,
+        p2p_unban,
+        p2p_bandwidth_get,
         make_heartbeat,
         peers_get,
         message_progress_get,
         stats,
+        block_producer_schedule,
         scan_state_summary_get,
         snark_pool_jobs_get,
         snark_pool_job_get,
@@ -605,7 +739,7 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
         readiness(rpc_sender.clone()),
         discovery::routing_table(rpc_sender.clone()),
         discovery::bootstrap_stats(rpc_sender.clone()),
-        super::graphql::routes(rpc_sender),
+        super::graphql::routes(rpc_sender, admin),
     );
 
     let routes = routes.recover(recover).with(cors);
@@ -719,6 +853,71 @@ fn with_rpc_sender(
     warp::any().map(move || rpc_sender.clone())
 }
 
+/// Access to the endpoints that manage the node, rather than just query it.
+#[derive(Debug, Clone, Default)]
+pub struct HttpAdminConfig {
+    /// Bearer token that privileged requests must carry. Privileged
+    /// endpoints are disabled when it is not set.
+    pub token: Option<String>,
+    /// Also accept privileged requests from other hosts than this one.
+    pub allow_remote: bool,
+    /// Enable the GraphQL mutations that manage the keystore of the node.
+    pub keystore: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AdminAccessError {
+    #[error("privileged endpoints are disabled, the node has no admin token")]
+    Disabled,
+    #[error("privileged endpoints are only available from localhost")]
+    NotLocal,
+    #[error("missing or invalid admin token")]
+    Unauthorized,
+}
+
+impl HttpAdminConfig {
+    /// Whether a request from `remote`, with the given `Authorization`
+    /// header, may use the privileged endpoints.
+    pub fn check(
+        &self,
+        remote: Option<SocketAddr>,
+        authorization: Option<&str>,
+    ) -> Result<(), AdminAccessError> {
+        let token = self
+            .token
+            .as_deref()
+            .filter(|token| !token.is_empty())
+            .ok_or(AdminAccessError::Disabled)?;
+        let is_local = remote.is_some_and(|addr| addr.ip().to_canonical().is_loopback());
+        if !self.allow_remote && !is_local {
+            return Err(AdminAccessError::NotLocal);
+        }
+        let given = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if !constant_time_eq(given.as_bytes(), token.as_bytes()) {
+            return Err(AdminAccessError::Unauthorized);
+        }
+        Ok(())
+    }
+}
+
+/// Checks the request against [`HttpAdminConfig`], leaving it to the
+/// privileged endpoints to reject it.
+pub(crate) fn admin_access(
+    config: HttpAdminConfig,
+) -> impl Filter<Extract = (Result<(), AdminAccessError>,), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("authorization"))
+        .map(move |remote, authorization: Option<String>| {
+            config.check(remote, authorization.as_deref())
+        })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
 const DROPPED_CHANNEL: &str = "response channel dropped, see error log for details";
 
 #[derive(Debug)]
@@ -809,3 +1008,77 @@ impl Reply for JsonOrBinary {
 fn with_json_reply<T: Serialize>(reply: &T, status: StatusCode) -> WithStatus<Json> {
     with_status(json(reply), status)
 }
+
+fn admin_access_denied_reply(error: AdminAccessError) -> WithStatus<Json> {
+    let status = match error {
+        AdminAccessError::Disabled | AdminAccessError::NotLocal => StatusCode::FORBIDDEN,
+        AdminAccessError::Unauthorized => StatusCode::UNAUTHORIZED,
+    };
+    with_json_reply(&error.to_string(), status)
+}
+
+fn p2p_ban_reply(result: Option<Result<(), String>>) -> WithStatus<Json> {
+    match result {
+        None => with_json_reply(
+            &"response channel dropped",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+        Some(Err(error)) => with_json_reply(&error, StatusCode::BAD_REQUEST),
+        Some(Ok(())) => with_json_reply(&(), StatusCode::OK),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_access() {
+        let local: SocketAddr = "127.0.0.1:41000".parse().unwrap();
+        let remote: SocketAddr = "203.0.113.7:41000".parse().unwrap();
+        let bearer = Some("Bearer secret");
+
+        let disabled = HttpAdminConfig::default();
+        assert_eq!(
+            disabled.check(Some(local), bearer),
+            Err(AdminAccessError::Disabled)
+        );
+
+        let config = HttpAdminConfig {
+            token: Some("secret".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(config.check(Some(local), bearer), Ok(()));
+        assert_eq!(
+            config.check(Some("[::ffff:127.0.0.1]:41000".parse().unwrap()), bearer),
+            Ok(())
+        );
+        assert_eq!(
+            config.check(Some(local), None),
+            Err(AdminAccessError::Unauthorized)
+        );
+        assert_eq!(
+            config.check(Some(local), Some("Bearer secreT")),
+            Err(AdminAccessError::Unauthorized)
+        );
+        assert_eq!(
+            config.check(Some(local), Some("secret")),
+            Err(AdminAccessError::Unauthorized)
+        );
+        assert_eq!(
+            config.check(Some(remote), bearer),
+            Err(AdminAccessError::NotLocal)
+        );
+        assert_eq!(config.check(None, bearer), Err(AdminAccessError::NotLocal));
+
+        let config = HttpAdminConfig {
+            allow_remote: true,
+            ..config
+        };
+        assert_eq!(config.check(Some(remote), bearer), Ok(()));
+        assert_eq!(
+            config.check(Some(remote), None),
+            Err(AdminAccessError::Unauthorized)
+        );
+    }
+}
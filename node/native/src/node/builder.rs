@@ -1,8 +1,8 @@
 use std::{
     fs::File,
     io::{BufRead, BufReader, Read},
-    net::IpAddr,
-    path::Path,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
@@ -12,22 +12,28 @@ use ledger::proofs::provers::BlockProver;
 use mina_p2p_messages::v2::{self, NonZeroCurvePoint};
 use node::{
     account::AccountSecretKey,
+    block_producer::TransactionSelectionConfig,
     daemon_json::Daemon,
     p2p::{
         channels::ChannelId, connection::outgoing::P2pConnectionOutgoingInitOpts,
-        identity::SecretKey as P2pSecretKey, P2pLimits, P2pMeshsubConfig, P2pTimeouts,
+        identity::SecretKey as P2pSecretKey, P2pAccessRule, P2pLimits, P2pMeshsubConfig,
+        P2pTimeouts,
     },
     service::Recorder,
     snark::{get_srs, BlockVerifier, TransactionVerifier, VerifierSRS},
     transition_frontier::{archive::archive_config::ArchiveConfig, genesis::GenesisConfig},
-    BlockProducerConfig, GlobalConfig, LedgerConfig, P2pConfig, SnarkConfig, SnarkerConfig,
-    SnarkerStrategy, TransitionFrontierConfig,
+    BlockProducerConfig, BlockProducerKeyConfig, GlobalConfig, LedgerConfig, P2pConfig,
+    SnarkConfig, SnarkerConfig, SnarkerStrategy, TransitionFrontierConfig,
 };
 use openmina_core::{consensus::ConsensusConstants, constants::constraint_constants};
-use openmina_node_common::{archive::config::ArchiveStorageOptions, p2p::TaskSpawner};
+use openmina_node_common::{
+    archive::{cursor::ArchiveCursorFile, sink::ArchiveSinks},
+    block_producer::{BlockProducerSigner, RemoteSigner},
+    p2p::{P2pAddressBookFile, P2pTrustFile, TaskSpawner},
+};
 use rand::Rng;
 
-use crate::NodeServiceBuilder;
+use crate::{http_server::HttpAdminConfig, NodeServiceBuilder};
 
 use super::Node;
 
@@ -69,11 +75,13 @@ impl NodeBuilder {
             genesis_config,
             p2p: P2pConfig {
                 libp2p_port: None,
+                libp2p_quic: false,
                 listen_port: None,
                 // Must be replaced with builder api.
                 identity_pub_key: P2pSecretKey::deterministic(0).public_key(),
                 initial_peers: Vec::new(),
                 external_addrs: Vec::new(),
+                nat_port_mapping: false,
                 enabled_channels: ChannelId::iter_all().collect(),
                 peer_discovery: true,
                 meshsub: P2pMeshsubConfig {
@@ -82,6 +90,9 @@ impl NodeBuilder {
                 },
                 timeouts: P2pTimeouts::default(),
                 limits: P2pLimits::default().with_max_peers(Some(100)),
+                initial_trust: Default::default(),
+                initial_address_book: Default::default(),
+                access: Default::default(),
             },
             p2p_sec_key: None,
             p2p_is_seed: false,
@@ -116,6 +127,20 @@ impl NodeBuilder {
         self
     }
 
+    /// Also use QUIC transport for libp2p, listening on UDP port with
+    /// the same number as the libp2p port.
+    pub fn p2p_libp2p_quic(&mut self) -> &mut Self {
+        self.p2p.libp2p_quic = true;
+        self
+    }
+
+    /// Ask the gateway to forward the libp2p port to this host, using
+    /// NAT-PMP/PCP or UPnP IGD.
+    pub fn p2p_nat_port_mapping(&mut self) -> &mut Self {
+        self.p2p.nat_port_mapping = true;
+        self
+    }
+
     /// Set up node as a seed node.
     pub fn p2p_seed_node(&mut self) -> &mut Self {
         self.p2p_is_seed = true;
@@ -127,6 +152,26 @@ impl NodeBuilder {
         self
     }
 
+    /// Only peers matching one of the rules will be allowed.
+    pub fn p2p_allow_peers(&mut self, rules: impl IntoIterator<Item = P2pAccessRule>) -> &mut Self {
+        self.p2p.access.allow.extend(rules);
+        self
+    }
+
+    /// Peers matching one of the rules will never be allowed.
+    pub fn p2p_deny_peers(&mut self, rules: impl IntoIterator<Item = P2pAccessRule>) -> &mut Self {
+        self.p2p.access.deny.extend(rules);
+        self
+    }
+
+    /// Only initial peers and peers from the allow list will be allowed,
+    /// and nothing will be advertised to them. Disables peer discovery.
+    pub fn p2p_isolate(&mut self) -> &mut Self {
+        self.p2p.access.isolate = true;
+        self.p2p.peer_discovery = false;
+        self
+    }
+
     /// Extend p2p initial peers from an iterable.
     pub fn initial_peers(
         &mut self,
@@ -196,11 +241,7 @@ impl NodeBuilder {
         key: AccountSecretKey,
         provers: Option<BlockProver>,
     ) -> &mut Self {
-        let config = BlockProducerConfig {
-            pub_key: key.public_key().into(),
-            custom_coinbase_receiver: None,
-            proposed_protocol_version: None,
-        };
+        let config = BlockProducerConfig::new(key.public_key().into());
         self.block_producer = Some(config);
         self.service.block_producer_init(key, provers);
         self
@@ -218,9 +259,111 @@ impl NodeBuilder {
         Ok(self.block_producer(key, provers))
     }
 
-    pub fn archive(&mut self, options: ArchiveStorageOptions, work_dir: String) -> &mut Self {
-        self.archive = Some(ArchiveConfig::new(work_dir.clone()));
-        self.service.archive_init(options, work_dir.clone());
+    /// Set up block producer, which leaves vrf evaluation and block
+    /// proving to `signer`, so the private key doesn't need to be
+    /// loaded into the node.
+    pub fn block_producer_with_signer(
+        &mut self,
+        signer: Arc<dyn BlockProducerSigner>,
+        provers: Option<BlockProver>,
+    ) -> &mut Self {
+        let config = BlockProducerConfig::new(signer.public_key().into());
+        self.block_producer = Some(config);
+        self.service
+            .block_producer_init_with_signer(signer, provers);
+        self
+    }
+
+    /// Set up block producer using the signer process listening on `addr`,
+    /// sharing `secret` with it.
+    pub fn block_producer_from_remote_signer(
+        &mut self,
+        addr: SocketAddr,
+        secret: String,
+    ) -> anyhow::Result<&mut Self> {
+        let signer = RemoteSigner::connect(addr, secret)
+            .with_context(|| format!("Failed to connect to block producer signer at {addr}"))?;
+        Ok(self.block_producer_with_signer(Arc::new(signer), None))
+    }
+
+    /// Produce blocks for another key too. Coinbase rewards for its blocks
+    /// go to `coinbase_receiver`, or to the key itself if not provided.
+    pub fn additional_block_producer(
+        &mut self,
+        key: AccountSecretKey,
+        coinbase_receiver: Option<NonZeroCurvePoint>,
+    ) -> anyhow::Result<&mut Self> {
+        let bp = self.block_producer.as_mut().ok_or_else(|| {
+            anyhow::anyhow!("block producer not initialized! Call `block_producer` function first.")
+        })?;
+        let added = bp.add_key(BlockProducerKeyConfig {
+            pub_key: key.public_key().into(),
+            custom_coinbase_receiver: coinbase_receiver,
+        });
+        if !added {
+            anyhow::bail!(
+                "block producer key {} is already configured",
+                key.public_key()
+            );
+        }
+        self.service.block_producer_add_key(key);
+        Ok(self)
+    }
+
+    /// Produce blocks for another key too, loaded from file.
+    pub fn additional_block_producer_from_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        password: &str,
+        coinbase_receiver: Option<NonZeroCurvePoint>,
+    ) -> anyhow::Result<&mut Self> {
+        let key = AccountSecretKey::from_encrypted_file(path, password)
+            .context("Failed to decrypt secret key file")?;
+        self.additional_block_producer(key, coinbase_receiver)
+    }
+
+    /// Keep the root snarked ledger, the epoch ledgers and the transition
+    /// frontier on disk, so that they don't need to be synced from peers
+    /// after a restart.
+    pub fn ledger_persistence(&mut self, directory: impl Into<PathBuf>) -> &mut Self {
+        self.service.ledger_persistence(directory.into());
+        self
+    }
+
+    /// Peer trust scores and bans are kept in `p2p-trust.json` inside
+    /// `work_dir`, so that banned peers stay banned after a restart.
+    pub fn p2p_trust_persistence(
+        &mut self,
+        work_dir: impl AsRef<Path>,
+    ) -> anyhow::Result<&mut Self> {
+        let file = P2pTrustFile::new(work_dir.as_ref().join("p2p-trust.json"));
+        self.p2p.initial_trust = file
+            .load()
+            .with_context(|| format!("Failed to load p2p trust state from {:?}", file.path()))?;
+        self.service.p2p_trust_persistence(file);
+        Ok(self)
+    }
+
+    /// Peers seen by the node are kept in `p2p-address-book.json` inside
+    /// `work_dir`, and used to bootstrap the node after a restart.
+    pub fn p2p_address_book_persistence(
+        &mut self,
+        work_dir: impl AsRef<Path>,
+    ) -> anyhow::Result<&mut Self> {
+        let file = P2pAddressBookFile::new(work_dir.as_ref().join("p2p-address-book.json"));
+        self.p2p.initial_address_book = file
+            .load()
+            .with_context(|| format!("Failed to load p2p address book from {:?}", file.path()))?;
+        self.service.p2p_address_book_persistence(file);
+        Ok(self)
+    }
+
+    /// Delivery cursors of the sinks are kept in `archive-cursor.json`
+    /// inside `work_dir`.
+    pub fn archive(&mut self, sinks: ArchiveSinks, work_dir: String) -> &mut Self {
+        let cursor_file = ArchiveCursorFile::new(Path::new(&work_dir).join("archive-cursor.json"));
+        self.archive = Some(ArchiveConfig::new(work_dir));
+        self.service.archive_init(sinks, Some(cursor_file));
         self
     }
 
@@ -238,6 +381,20 @@ impl NodeBuilder {
         Ok(self)
     }
 
+    /// Change how transactions are picked for produced blocks.
+    pub fn block_producer_transaction_selection(
+        &mut self,
+        selection: TransactionSelectionConfig,
+    ) -> anyhow::Result<&mut Self> {
+        let bp = self.block_producer.as_mut().ok_or_else(|| {
+            anyhow::anyhow!(
+                "can't set transaction_selection when block producer is not initialized."
+            )
+        })?;
+        bp.transaction_selection = selection;
+        Ok(self)
+    }
+
     pub fn custom_block_producer_config(
         &mut self,
         config: BlockProducerConfig,
@@ -253,6 +410,7 @@ impl NodeBuilder {
         sec_key: AccountSecretKey,
         fee: u64,
         strategy: SnarkerStrategy,
+        workers: usize,
     ) -> &mut Self {
         let config = SnarkerConfig {
             public_key: sec_key.public_key(),
@@ -261,6 +419,7 @@ impl NodeBuilder {
             )),
             strategy,
             auto_commit: true,
+            workers,
         };
         self.snarker = Some(config);
         self
@@ -292,16 +451,16 @@ impl NodeBuilder {
         self
     }
 
-    pub fn http_server(&mut self, port: u16) -> &mut Self {
+    pub fn http_server(&mut self, port: u16, admin: HttpAdminConfig) -> &mut Self {
         self.http_port = Some(port);
-        self.service.http_server_init(port);
+        self.service.http_server_init(port, admin);
         self
     }
 
     pub fn build(mut self) -> anyhow::Result<Node> {
         let p2p_sec_key = self.p2p_sec_key.clone().unwrap_or_else(P2pSecretKey::rand);
         self.p2p_sec_key(p2p_sec_key.clone());
-        if self.p2p.initial_peers.is_empty() && !self.p2p_is_seed {
+        if self.p2p.initial_peers.is_empty() && !self.p2p_is_seed && !self.p2p.access.isolate {
             self.p2p.initial_peers = default_peers();
         }
 
@@ -319,6 +478,16 @@ impl NodeBuilder {
             })
             .collect();
 
+        if self.p2p.access.isolate {
+            // initial peers are our sentry nodes
+            self.p2p.access.allow.extend(
+                self.p2p
+                    .initial_peers
+                    .iter()
+                    .map(|opts| P2pAccessRule::PeerId(*opts.peer_id())),
+            );
+        }
+
         let srs = self.verifier_srs.unwrap_or_else(get_srs);
         let block_verifier_index = self
             .block_verifier_index
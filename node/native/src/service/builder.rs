@@ -1,3 +1,5 @@
+use std::{path::PathBuf, sync::Arc};
+
 use ledger::proofs::provers::BlockProver;
 use node::{
     account::AccountSecretKey, core::thread, p2p::identity::SecretKey as P2pSecretKey,
@@ -5,11 +7,17 @@ use node::{
 };
 pub use openmina_node_common::NodeServiceCommonBuildError;
 use openmina_node_common::{
-    archive::config::ArchiveStorageOptions, p2p::TaskSpawner, rpc::RpcSender, EventSender,
-    NodeServiceCommonBuilder,
+    archive::{cursor::ArchiveCursorFile, sink::ArchiveSinks},
+    block_producer::BlockProducerSigner,
+    p2p::{P2pAddressBookFile, P2pTrustFile, TaskSpawner},
+    rpc::RpcSender,
+    EventSender, NodeServiceCommonBuilder,
 };
 
-use crate::{http_server, NodeService, P2pTaskSpawner};
+use crate::{
+    http_server::{self, HttpAdminConfig},
+    NodeService, P2pTaskSpawner,
+};
 
 pub struct NodeServiceBuilder {
     common: NodeServiceCommonBuilder,
@@ -40,6 +48,11 @@ impl NodeServiceBuilder {
         self.common.rpc_sender()
     }
 
+    pub fn ledger_persistence(&mut self, directory: PathBuf) -> &mut Self {
+        self.common.ledger_persistence(directory);
+        self
+    }
+
     pub fn ledger_init(&mut self) -> &mut Self {
         self.common.ledger_init();
         self
@@ -54,8 +67,31 @@ impl NodeServiceBuilder {
         self
     }
 
-    pub fn archive_init(&mut self, options: ArchiveStorageOptions, work_dir: String) -> &mut Self {
-        self.common.archive_init(options, work_dir);
+    pub fn block_producer_init_with_signer(
+        &mut self,
+        signer: Arc<dyn BlockProducerSigner>,
+        provers: Option<BlockProver>,
+    ) -> &mut Self {
+        self.common.block_producer_init_with_signer(signer, provers);
+        self
+    }
+
+    pub fn block_producer_add_key(&mut self, keypair: AccountSecretKey) -> &mut Self {
+        self.common.block_producer_add_key(keypair);
+        self
+    }
+
+    pub fn block_producer_add_signer(&mut self, signer: Arc<dyn BlockProducerSigner>) -> &mut Self {
+        self.common.block_producer_add_signer(signer);
+        self
+    }
+
+    pub fn archive_init(
+        &mut self,
+        sinks: ArchiveSinks,
+        cursor_file: Option<ArchiveCursorFile>,
+    ) -> &mut Self {
+        self.common.archive_init(sinks, cursor_file);
         self
     }
 
@@ -73,6 +109,16 @@ impl NodeServiceBuilder {
         self
     }
 
+    pub fn p2p_trust_persistence(&mut self, file: P2pTrustFile) -> &mut Self {
+        self.common.p2p_trust_persistence(file);
+        self
+    }
+
+    pub fn p2p_address_book_persistence(&mut self, file: P2pAddressBookFile) -> &mut Self {
+        self.common.p2p_address_book_persistence(file);
+        self
+    }
+
     pub fn gather_stats(&mut self) -> &mut Self {
         self.common.gather_stats();
         self
@@ -83,7 +129,7 @@ impl NodeServiceBuilder {
         self
     }
 
-    pub fn http_server_init(&mut self, port: u16) -> &mut Self {
+    pub fn http_server_init(&mut self, port: u16, admin: HttpAdminConfig) -> &mut Self {
         if let Some(cur_port) = self.http_server_port {
             panic!("trying to start http server on port `{port}`, when it's already running on port `{cur_port}`");
         }
@@ -95,7 +141,7 @@ impl NodeServiceBuilder {
             .unwrap();
         thread::Builder::new()
             .name("openmina_http_server".to_owned())
-            .spawn(move || runtime.block_on(http_server::run(port, rpc_sender)))
+            .spawn(move || runtime.block_on(http_server::run(port, rpc_sender, admin)))
             .unwrap();
         self
     }
@@ -26,6 +26,9 @@ use crate::ledger::read::LedgerReadAction;
 use crate::ledger::write::LedgerWriteAction;
 use crate::ledger::LedgerAction;
 use crate::ledger_effectful::LedgerEffectfulAction;
+use crate::p2p::address_book::P2pAddressBookAction;
+use crate::p2p::address_book_effectful::P2pAddressBookEffectfulAction;
+use crate::p2p::bandwidth::P2pBandwidthAction;
 use crate::p2p::callbacks::P2pCallbacksAction;
 use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
 use crate::p2p::channels::rpc::P2pChannelsRpcAction;
@@ -46,6 +49,9 @@ use crate::p2p::connection::{P2pConnectionAction, P2pConnectionEffectfulAction};
 use crate::p2p::disconnection::P2pDisconnectionAction;
 use crate::p2p::disconnection_effectful::P2pDisconnectionEffectfulAction;
 use crate::p2p::identify::P2pIdentifyAction;
+use crate::p2p::nat::P2pNatAction;
+use crate::p2p::nat_effectful::P2pNatEffectfulAction;
+use crate::p2p::network::autonat::P2pNetworkAutonatAction;
 use crate::p2p::network::identify::stream::P2pNetworkIdentifyStreamAction;
 use crate::p2p::network::identify::stream_effectful::P2pNetworkIdentifyStreamEffectfulAction;
 use crate::p2p::network::identify::{P2pNetworkIdentifyAction, P2pNetworkIdentifyEffectfulAction};
@@ -59,13 +65,17 @@ use crate::p2p::network::pnet::P2pNetworkPnetAction;
 use crate::p2p::network::pnet_effectful::P2pNetworkPnetEffectfulAction;
 use crate::p2p::network::pubsub::pubsub_effectful::P2pNetworkPubsubEffectfulAction;
 use crate::p2p::network::pubsub::P2pNetworkPubsubAction;
+use crate::p2p::network::quic::P2pNetworkQuicAction;
 use crate::p2p::network::rpc::P2pNetworkRpcAction;
 use crate::p2p::network::scheduler::P2pNetworkSchedulerAction;
 use crate::p2p::network::scheduler_effectful::P2pNetworkSchedulerEffectfulAction;
 use crate::p2p::network::select::P2pNetworkSelectAction;
+use crate::p2p::network::webrtc_signal::P2pNetworkWebrtcSignalAction;
 use crate::p2p::network::yamux::P2pNetworkYamuxAction;
 use crate::p2p::network::{P2pNetworkAction, P2pNetworkEffectfulAction};
 use crate::p2p::peer::P2pPeerAction;
+use crate::p2p::trust::P2pTrustAction;
+use crate::p2p::trust_effectful::P2pTrustEffectfulAction;
 use crate::p2p::{P2pAction, P2pEffectfulAction, P2pInitializeAction};
 use crate::rpc::RpcAction;
 use crate::rpc_effectful::RpcEffectfulAction;
@@ -112,6 +122,7 @@ pub enum ActionKind {
     BlockProducerBlockInject,
     BlockProducerBlockInjected,
     BlockProducerBlockProduced,
+    BlockProducerBlockProveError,
     BlockProducerBlockProveInit,
     BlockProducerBlockProvePending,
     BlockProducerBlockProveSuccess,
@@ -147,7 +158,9 @@ pub enum ActionKind {
     BlockProducerVrfEvaluatorInitializeEpochEvaluation,
     BlockProducerVrfEvaluatorInitializeEvaluator,
     BlockProducerVrfEvaluatorInterruptEpochEvaluation,
+    BlockProducerVrfEvaluatorProcessSlotEvaluationError,
     BlockProducerVrfEvaluatorProcessSlotEvaluationSuccess,
+    BlockProducerVrfEvaluatorRetrySlotEvaluation,
     BlockProducerVrfEvaluatorSelectInitialSlot,
     BlockProducerVrfEvaluatorWaitForNextEvaluation,
     BlockProducerVrfEvaluatorEffectfulEvaluateSlot,
@@ -175,6 +188,7 @@ pub enum ActionKind {
     ExternalSnarkWorkerEffectfulKill,
     ExternalSnarkWorkerEffectfulStart,
     ExternalSnarkWorkerEffectfulSubmitWork,
+    LedgerEffectfulFrontierPersist,
     LedgerEffectfulReadInit,
     LedgerEffectfulWriteInit,
     LedgerReadFindTodos,
@@ -185,6 +199,14 @@ pub enum ActionKind {
     LedgerWriteInit,
     LedgerWritePending,
     LedgerWriteSuccess,
+    P2pAddressBookConnectionFinished,
+    P2pAddressBookPeerIdentified,
+    P2pAddressBookPersist,
+    P2pAddressBookPrune,
+    P2pAddressBookEffectfulPersist,
+    P2pBandwidthPrune,
+    P2pBandwidthReceived,
+    P2pBandwidthSent,
     P2pCallbacksP2pChannelsRpcReady,
     P2pCallbacksP2pChannelsRpcRequestReceived,
     P2pCallbacksP2pChannelsRpcResponseReceived,
@@ -337,6 +359,21 @@ pub enum ActionKind {
     P2pIdentifyNewRequest,
     P2pIdentifyUpdatePeerInformation,
     P2pInitializeInitialize,
+    P2pNatObservedAddr,
+    P2pNatPortMappingError,
+    P2pNatPortMappingInit,
+    P2pNatPortMappingSuccess,
+    P2pNatProbeInit,
+    P2pNatProbeResult,
+    P2pNatPrune,
+    P2pNatEffectfulDialBack,
+    P2pNatEffectfulPortMappingRequest,
+    P2pNetworkAutonatClose,
+    P2pNetworkAutonatDialBackResult,
+    P2pNetworkAutonatIncomingData,
+    P2pNetworkAutonatNew,
+    P2pNetworkAutonatPrune,
+    P2pNetworkAutonatRemoteClose,
     P2pNetworkIdentifyStreamClose,
     P2pNetworkIdentifyStreamIncomingData,
     P2pNetworkIdentifyStreamNew,
@@ -395,6 +432,7 @@ pub enum ActionKind {
     P2pNetworkPubsubBroadcastValidatedMessage,
     P2pNetworkPubsubGraft,
     P2pNetworkPubsubHandleIncomingMessage,
+    P2pNetworkPubsubHeartbeat,
     P2pNetworkPubsubIgnoreMessage,
     P2pNetworkPubsubIncomingData,
     P2pNetworkPubsubIncomingMessage,
@@ -414,6 +452,13 @@ pub enum ActionKind {
     P2pNetworkPubsubWebRtcRebroadcast,
     P2pNetworkPubsubEffectfulSign,
     P2pNetworkPubsubEffectfulValidateIncomingMessages,
+    P2pNetworkQuicAuthenticated,
+    P2pNetworkQuicIncomingData,
+    P2pNetworkQuicIncomingStream,
+    P2pNetworkQuicOpenStream,
+    P2pNetworkQuicOutgoingData,
+    P2pNetworkQuicReady,
+    P2pNetworkQuicStreamReset,
     P2pNetworkRpcHeartbeatSend,
     P2pNetworkRpcIncomingData,
     P2pNetworkRpcIncomingMessage,
@@ -448,6 +493,7 @@ pub enum ActionKind {
     P2pNetworkSchedulerEffectfulNoiseSelectDone,
     P2pNetworkSchedulerEffectfulOutgoingConnect,
     P2pNetworkSchedulerEffectfulOutgoingDidConnect,
+    P2pNetworkSchedulerEffectfulQuicOutgoingData,
     P2pNetworkSelectIncomingData,
     P2pNetworkSelectIncomingDataAuth,
     P2pNetworkSelectIncomingDataMux,
@@ -458,6 +504,12 @@ pub enum ActionKind {
     P2pNetworkSelectInit,
     P2pNetworkSelectOutgoingTokens,
     P2pNetworkSelectTimeout,
+    P2pNetworkWebrtcSignalClose,
+    P2pNetworkWebrtcSignalIncomingData,
+    P2pNetworkWebrtcSignalNew,
+    P2pNetworkWebrtcSignalOutgoingMessage,
+    P2pNetworkWebrtcSignalPrune,
+    P2pNetworkWebrtcSignalRemoteClose,
     P2pNetworkYamuxIncomingData,
     P2pNetworkYamuxIncomingFrame,
     P2pNetworkYamuxOpenStream,
@@ -468,9 +520,19 @@ pub enum ActionKind {
     P2pPeerDiscovered,
     P2pPeerReady,
     P2pPeerRemove,
+    P2pTrustBan,
+    P2pTrustPenalize,
+    P2pTrustPruneExpired,
+    P2pTrustUnban,
+    P2pTrustEffectfulPersist,
     RpcActionStatsGet,
+    RpcArchiveBackfillInit,
+    RpcArchiveBackfillPending,
+    RpcArchiveBackfillSuccess,
+    RpcArchiveStatusGet,
     RpcBestChain,
     RpcBlockGet,
+    RpcBlockProducerScheduleGet,
     RpcBlockProducerStatsGet,
     RpcConsensusConstantsGet,
     RpcConsensusTimeGet,
@@ -491,6 +553,9 @@ pub enum ActionKind {
     RpcLedgerStatusGetPending,
     RpcLedgerStatusGetSuccess,
     RpcMessageProgressGet,
+    RpcP2pBan,
+    RpcP2pBandwidthGet,
+    RpcP2pBansGet,
     RpcP2pConnectionIncomingAnswerReady,
     RpcP2pConnectionIncomingError,
     RpcP2pConnectionIncomingInit,
@@ -501,6 +566,7 @@ pub enum ActionKind {
     RpcP2pConnectionOutgoingInit,
     RpcP2pConnectionOutgoingPending,
     RpcP2pConnectionOutgoingSuccess,
+    RpcP2pUnban,
     RpcPeersGet,
     RpcPooledUserCommands,
     RpcPooledZkappCommands,
@@ -526,10 +592,17 @@ pub enum ActionKind {
     RpcTransactionInjectSuccess,
     RpcTransactionPool,
     RpcTransactionStatusGet,
+    RpcTransitionFrontierSubscribe,
+    RpcTransitionFrontierSubscribersNotify,
+    RpcTransitionFrontierUnsubscribe,
     RpcTransitionFrontierUserCommandsGet,
+    RpcVrfEvaluate,
     RpcEffectfulActionStatsGet,
+    RpcEffectfulArchiveBackfillSuccess,
+    RpcEffectfulArchiveStatusGet,
     RpcEffectfulBestChain,
     RpcEffectfulBlockGet,
+    RpcEffectfulBlockProducerScheduleGet,
     RpcEffectfulBlockProducerStatsGet,
     RpcEffectfulConsensusConstantsGet,
     RpcEffectfulConsensusTimeGet,
@@ -543,11 +616,15 @@ pub enum ActionKind {
     RpcEffectfulLedgerAccountsGetSuccess,
     RpcEffectfulLedgerStatusGetSuccess,
     RpcEffectfulMessageProgressGet,
+    RpcEffectfulP2pBan,
+    RpcEffectfulP2pBandwidthGet,
+    RpcEffectfulP2pBansGet,
     RpcEffectfulP2pConnectionIncomingError,
     RpcEffectfulP2pConnectionIncomingRespond,
     RpcEffectfulP2pConnectionIncomingSuccess,
     RpcEffectfulP2pConnectionOutgoingError,
     RpcEffectfulP2pConnectionOutgoingSuccess,
+    RpcEffectfulP2pUnban,
     RpcEffectfulPeersGet,
     RpcEffectfulPooledUserCommands,
     RpcEffectfulPooledZkappCommands,
@@ -568,7 +645,9 @@ pub enum ActionKind {
     RpcEffectfulTransactionInjectSuccess,
     RpcEffectfulTransactionPool,
     RpcEffectfulTransactionStatusGet,
+    RpcEffectfulTransitionFrontierEvent,
     RpcEffectfulTransitionFrontierUserCommandsGet,
+    RpcEffectfulVrfEvaluate,
     SnarkBlockVerifyError,
     SnarkBlockVerifyFinish,
     SnarkBlockVerifyInit,
@@ -638,6 +717,11 @@ pub enum ActionKind {
     TransactionPoolEffectfulFetchAccounts,
     TransitionFrontierGenesisInject,
     TransitionFrontierGenesisProvenInject,
+    TransitionFrontierRestoreConfirm,
+    TransitionFrontierRestoreError,
+    TransitionFrontierRestoreInit,
+    TransitionFrontierRestorePending,
+    TransitionFrontierRestoreSuccess,
     TransitionFrontierSyncFailed,
     TransitionFrontierSynced,
     TransitionFrontierCandidateBlockChainProofUpdate,
@@ -692,6 +776,12 @@ pub enum ActionKind {
     TransitionFrontierSyncLedgerSnarkedChildHashesAccepted,
     TransitionFrontierSyncLedgerSnarkedChildHashesReceived,
     TransitionFrontierSyncLedgerSnarkedChildHashesRejected,
+    TransitionFrontierSyncLedgerSnarkedEpochLedgerAccepted,
+    TransitionFrontierSyncLedgerSnarkedEpochLedgerFallback,
+    TransitionFrontierSyncLedgerSnarkedEpochLedgerReceived,
+    TransitionFrontierSyncLedgerSnarkedEpochLedgerRejected,
+    TransitionFrontierSyncLedgerSnarkedEpochLedgerSetInit,
+    TransitionFrontierSyncLedgerSnarkedEpochLedgerSetPending,
     TransitionFrontierSyncLedgerSnarkedMerkleTreeSyncPending,
     TransitionFrontierSyncLedgerSnarkedMerkleTreeSyncSuccess,
     TransitionFrontierSyncLedgerSnarkedNumAccountsAccepted,
@@ -703,6 +793,10 @@ pub enum ActionKind {
     TransitionFrontierSyncLedgerSnarkedPeerQueryAddressPending,
     TransitionFrontierSyncLedgerSnarkedPeerQueryAddressRetry,
     TransitionFrontierSyncLedgerSnarkedPeerQueryAddressSuccess,
+    TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerError,
+    TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerInit,
+    TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerPending,
+    TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerSuccess,
     TransitionFrontierSyncLedgerSnarkedPeerQueryNumAccountsError,
     TransitionFrontierSyncLedgerSnarkedPeerQueryNumAccountsInit,
     TransitionFrontierSyncLedgerSnarkedPeerQueryNumAccountsPending,
@@ -738,7 +832,7 @@ pub enum ActionKind {
 }
 
 impl ActionKind {
-    pub const COUNT: u16 = 628;
+    pub const COUNT: u16 = 712;
 }
 
 impl std::fmt::Display for ActionKind {
@@ -801,6 +895,10 @@ impl ActionKindGet for P2pAction {
             Self::Channels(a) => a.kind(),
             Self::Peer(a) => a.kind(),
             Self::Network(a) => a.kind(),
+            Self::Trust(a) => a.kind(),
+            Self::AddressBook(a) => a.kind(),
+            Self::Bandwidth(a) => a.kind(),
+            Self::Nat(a) => a.kind(),
         }
     }
 }
@@ -812,6 +910,9 @@ impl ActionKindGet for P2pEffectfulAction {
             Self::Connection(a) => a.kind(),
             Self::Disconnection(a) => a.kind(),
             Self::Network(a) => a.kind(),
+            Self::Trust(a) => a.kind(),
+            Self::AddressBook(a) => a.kind(),
+            Self::Nat(a) => a.kind(),
             Self::Initialize => ActionKind::P2pEffectfulInitialize,
         }
     }
@@ -860,6 +961,7 @@ impl ActionKindGet for LedgerEffectfulAction {
         match self {
             Self::WriteInit { .. } => ActionKind::LedgerEffectfulWriteInit,
             Self::ReadInit { .. } => ActionKind::LedgerEffectfulReadInit,
+            Self::FrontierPersist { .. } => ActionKind::LedgerEffectfulFrontierPersist,
         }
     }
 }
@@ -886,6 +988,11 @@ impl ActionKindGet for TransitionFrontierAction {
             Self::Sync(a) => a.kind(),
             Self::GenesisInject => ActionKind::TransitionFrontierGenesisInject,
             Self::GenesisProvenInject => ActionKind::TransitionFrontierGenesisProvenInject,
+            Self::RestoreInit => ActionKind::TransitionFrontierRestoreInit,
+            Self::RestorePending => ActionKind::TransitionFrontierRestorePending,
+            Self::RestoreSuccess { .. } => ActionKind::TransitionFrontierRestoreSuccess,
+            Self::RestoreError { .. } => ActionKind::TransitionFrontierRestoreError,
+            Self::RestoreConfirm { .. } => ActionKind::TransitionFrontierRestoreConfirm,
             Self::Synced { .. } => ActionKind::TransitionFrontierSynced,
             Self::SyncFailed { .. } => ActionKind::TransitionFrontierSyncFailed,
         }
@@ -963,18 +1070,18 @@ impl ActionKindGet for TransactionPoolEffectfulAction {
 impl ActionKindGet for ExternalSnarkWorkerAction {
     fn kind(&self) -> ActionKind {
         match self {
-            Self::Start => ActionKind::ExternalSnarkWorkerStart,
-            Self::Started => ActionKind::ExternalSnarkWorkerStarted,
+            Self::Start { .. } => ActionKind::ExternalSnarkWorkerStart,
+            Self::Started { .. } => ActionKind::ExternalSnarkWorkerStarted,
             Self::StartTimeout { .. } => ActionKind::ExternalSnarkWorkerStartTimeout,
-            Self::Kill => ActionKind::ExternalSnarkWorkerKill,
-            Self::Killed => ActionKind::ExternalSnarkWorkerKilled,
+            Self::Kill { .. } => ActionKind::ExternalSnarkWorkerKill,
+            Self::Killed { .. } => ActionKind::ExternalSnarkWorkerKilled,
             Self::SubmitWork { .. } => ActionKind::ExternalSnarkWorkerSubmitWork,
             Self::WorkResult { .. } => ActionKind::ExternalSnarkWorkerWorkResult,
             Self::WorkError { .. } => ActionKind::ExternalSnarkWorkerWorkError,
             Self::WorkTimeout { .. } => ActionKind::ExternalSnarkWorkerWorkTimeout,
-            Self::CancelWork => ActionKind::ExternalSnarkWorkerCancelWork,
-            Self::WorkCancelled => ActionKind::ExternalSnarkWorkerWorkCancelled,
-            Self::PruneWork => ActionKind::ExternalSnarkWorkerPruneWork,
+            Self::CancelWork { .. } => ActionKind::ExternalSnarkWorkerCancelWork,
+            Self::WorkCancelled { .. } => ActionKind::ExternalSnarkWorkerWorkCancelled,
+            Self::PruneWork { .. } => ActionKind::ExternalSnarkWorkerPruneWork,
             Self::Error { .. } => ActionKind::ExternalSnarkWorkerError,
         }
     }
@@ -984,9 +1091,9 @@ impl ActionKindGet for ExternalSnarkWorkerEffectfulAction {
     fn kind(&self) -> ActionKind {
         match self {
             Self::Start { .. } => ActionKind::ExternalSnarkWorkerEffectfulStart,
-            Self::Kill => ActionKind::ExternalSnarkWorkerEffectfulKill,
+            Self::Kill { .. } => ActionKind::ExternalSnarkWorkerEffectfulKill,
             Self::SubmitWork { .. } => ActionKind::ExternalSnarkWorkerEffectfulSubmitWork,
-            Self::CancelWork => ActionKind::ExternalSnarkWorkerEffectfulCancelWork,
+            Self::CancelWork { .. } => ActionKind::ExternalSnarkWorkerEffectfulCancelWork,
         }
     }
 }
@@ -1016,6 +1123,7 @@ impl ActionKindGet for BlockProducerAction {
             Self::BlockProveInit => ActionKind::BlockProducerBlockProveInit,
             Self::BlockProvePending => ActionKind::BlockProducerBlockProvePending,
             Self::BlockProveSuccess { .. } => ActionKind::BlockProducerBlockProveSuccess,
+            Self::BlockProveError { .. } => ActionKind::BlockProducerBlockProveError,
             Self::BlockProduced => ActionKind::BlockProducerBlockProduced,
             Self::BlockInject => ActionKind::BlockProducerBlockInject,
             Self::BlockInjected => ActionKind::BlockProducerBlockInjected,
@@ -1052,6 +1160,7 @@ impl ActionKindGet for RpcAction {
             Self::ActionStatsGet { .. } => ActionKind::RpcActionStatsGet,
             Self::SyncStatsGet { .. } => ActionKind::RpcSyncStatsGet,
             Self::BlockProducerStatsGet { .. } => ActionKind::RpcBlockProducerStatsGet,
+            Self::BlockProducerScheduleGet { .. } => ActionKind::RpcBlockProducerScheduleGet,
             Self::MessageProgressGet { .. } => ActionKind::RpcMessageProgressGet,
             Self::PeersGet { .. } => ActionKind::RpcPeersGet,
             Self::P2pConnectionOutgoingInit { .. } => ActionKind::RpcP2pConnectionOutgoingInit,
@@ -1076,6 +1185,10 @@ impl ActionKindGet for RpcAction {
             Self::P2pConnectionIncomingSuccess { .. } => {
                 ActionKind::RpcP2pConnectionIncomingSuccess
             }
+            Self::P2pBansGet { .. } => ActionKind::RpcP2pBansGet,
+            Self::P2pBan { .. } => ActionKind::RpcP2pBan,
+            Self::P2pUnban { .. } => ActionKind::RpcP2pUnban,
+            Self::P2pBandwidthGet { .. } => ActionKind::RpcP2pBandwidthGet,
             Self::ScanStateSummaryGetInit { .. } => ActionKind::RpcScanStateSummaryGetInit,
             Self::ScanStateSummaryLedgerGetInit { .. } => {
                 ActionKind::RpcScanStateSummaryLedgerGetInit
@@ -1126,6 +1239,18 @@ impl ActionKindGet for RpcAction {
             Self::PooledUserCommands { .. } => ActionKind::RpcPooledUserCommands,
             Self::PooledZkappCommands { .. } => ActionKind::RpcPooledZkappCommands,
             Self::GenesisBlock { .. } => ActionKind::RpcGenesisBlock,
+            Self::ArchiveStatusGet { .. } => ActionKind::RpcArchiveStatusGet,
+            Self::ArchiveBackfillInit { .. } => ActionKind::RpcArchiveBackfillInit,
+            Self::ArchiveBackfillPending { .. } => ActionKind::RpcArchiveBackfillPending,
+            Self::ArchiveBackfillSuccess { .. } => ActionKind::RpcArchiveBackfillSuccess,
+            Self::TransitionFrontierSubscribe { .. } => ActionKind::RpcTransitionFrontierSubscribe,
+            Self::TransitionFrontierSubscribersNotify { .. } => {
+                ActionKind::RpcTransitionFrontierSubscribersNotify
+            }
+            Self::TransitionFrontierUnsubscribe { .. } => {
+                ActionKind::RpcTransitionFrontierUnsubscribe
+            }
+            Self::VrfEvaluate { .. } => ActionKind::RpcVrfEvaluate,
             Self::Finish { .. } => ActionKind::RpcFinish,
         }
     }
@@ -1140,6 +1265,9 @@ impl ActionKindGet for RpcEffectfulAction {
             Self::ActionStatsGet { .. } => ActionKind::RpcEffectfulActionStatsGet,
             Self::SyncStatsGet { .. } => ActionKind::RpcEffectfulSyncStatsGet,
             Self::BlockProducerStatsGet { .. } => ActionKind::RpcEffectfulBlockProducerStatsGet,
+            Self::BlockProducerScheduleGet { .. } => {
+                ActionKind::RpcEffectfulBlockProducerScheduleGet
+            }
             Self::MessageProgressGet { .. } => ActionKind::RpcEffectfulMessageProgressGet,
             Self::PeersGet { .. } => ActionKind::RpcEffectfulPeersGet,
             Self::P2pConnectionOutgoingError { .. } => {
@@ -1157,6 +1285,10 @@ impl ActionKindGet for RpcEffectfulAction {
             Self::P2pConnectionIncomingSuccess { .. } => {
                 ActionKind::RpcEffectfulP2pConnectionIncomingSuccess
             }
+            Self::P2pBansGet { .. } => ActionKind::RpcEffectfulP2pBansGet,
+            Self::P2pBan { .. } => ActionKind::RpcEffectfulP2pBan,
+            Self::P2pUnban { .. } => ActionKind::RpcEffectfulP2pUnban,
+            Self::P2pBandwidthGet { .. } => ActionKind::RpcEffectfulP2pBandwidthGet,
             Self::ScanStateSummaryGetSuccess { .. } => {
                 ActionKind::RpcEffectfulScanStateSummaryGetSuccess
             }
@@ -1204,6 +1336,10 @@ impl ActionKindGet for RpcEffectfulAction {
             Self::LedgerAccountDelegatorsGetSuccess { .. } => {
                 ActionKind::RpcEffectfulLedgerAccountDelegatorsGetSuccess
             }
+            Self::ArchiveStatusGet { .. } => ActionKind::RpcEffectfulArchiveStatusGet,
+            Self::ArchiveBackfillSuccess { .. } => ActionKind::RpcEffectfulArchiveBackfillSuccess,
+            Self::TransitionFrontierEvent { .. } => ActionKind::RpcEffectfulTransitionFrontierEvent,
+            Self::VrfEvaluate { .. } => ActionKind::RpcEffectfulVrfEvaluate,
         }
     }
 }
@@ -1314,7 +1450,10 @@ impl ActionKindGet for P2pNetworkAction {
             Self::Select(a) => a.kind(),
             Self::Noise(a) => a.kind(),
             Self::Yamux(a) => a.kind(),
+            Self::Quic(a) => a.kind(),
             Self::Identify(a) => a.kind(),
+            Self::Autonat(a) => a.kind(),
+            Self::WebrtcSignal(a) => a.kind(),
             Self::Kad(a) => a.kind(),
             Self::Pubsub(a) => a.kind(),
             Self::Rpc(a) => a.kind(),
@@ -1322,6 +1461,52 @@ impl ActionKindGet for P2pNetworkAction {
     }
 }
 
+impl ActionKindGet for P2pTrustAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Penalize { .. } => ActionKind::P2pTrustPenalize,
+            Self::Ban { .. } => ActionKind::P2pTrustBan,
+            Self::Unban { .. } => ActionKind::P2pTrustUnban,
+            Self::PruneExpired => ActionKind::P2pTrustPruneExpired,
+        }
+    }
+}
+
+impl ActionKindGet for P2pAddressBookAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::PeerIdentified { .. } => ActionKind::P2pAddressBookPeerIdentified,
+            Self::ConnectionFinished { .. } => ActionKind::P2pAddressBookConnectionFinished,
+            Self::Prune => ActionKind::P2pAddressBookPrune,
+            Self::Persist => ActionKind::P2pAddressBookPersist,
+        }
+    }
+}
+
+impl ActionKindGet for P2pBandwidthAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Received { .. } => ActionKind::P2pBandwidthReceived,
+            Self::Sent { .. } => ActionKind::P2pBandwidthSent,
+            Self::Prune => ActionKind::P2pBandwidthPrune,
+        }
+    }
+}
+
+impl ActionKindGet for P2pNatAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::PortMappingInit => ActionKind::P2pNatPortMappingInit,
+            Self::PortMappingSuccess { .. } => ActionKind::P2pNatPortMappingSuccess,
+            Self::PortMappingError { .. } => ActionKind::P2pNatPortMappingError,
+            Self::ObservedAddr { .. } => ActionKind::P2pNatObservedAddr,
+            Self::ProbeInit { .. } => ActionKind::P2pNatProbeInit,
+            Self::ProbeResult { .. } => ActionKind::P2pNatProbeResult,
+            Self::Prune => ActionKind::P2pNatPrune,
+        }
+    }
+}
+
 impl ActionKindGet for P2pChannelsEffectfulAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1372,6 +1557,31 @@ impl ActionKindGet for P2pNetworkEffectfulAction {
     }
 }
 
+impl ActionKindGet for P2pTrustEffectfulAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Persist { .. } => ActionKind::P2pTrustEffectfulPersist,
+        }
+    }
+}
+
+impl ActionKindGet for P2pAddressBookEffectfulAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Persist { .. } => ActionKind::P2pAddressBookEffectfulPersist,
+        }
+    }
+}
+
+impl ActionKindGet for P2pNatEffectfulAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::PortMappingRequest { .. } => ActionKind::P2pNatEffectfulPortMappingRequest,
+            Self::DialBack { .. } => ActionKind::P2pNatEffectfulDialBack,
+        }
+    }
+}
+
 impl ActionKindGet for LedgerWriteAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1615,6 +1825,10 @@ impl ActionKindGet for BlockProducerVrfEvaluatorAction {
             Self::ProcessSlotEvaluationSuccess { .. } => {
                 ActionKind::BlockProducerVrfEvaluatorProcessSlotEvaluationSuccess
             }
+            Self::ProcessSlotEvaluationError { .. } => {
+                ActionKind::BlockProducerVrfEvaluatorProcessSlotEvaluationError
+            }
+            Self::RetrySlotEvaluation => ActionKind::BlockProducerVrfEvaluatorRetrySlotEvaluation,
             Self::InitializeEvaluator { .. } => {
                 ActionKind::BlockProducerVrfEvaluatorInitializeEvaluator
             }
@@ -1997,6 +2211,20 @@ impl ActionKindGet for P2pNetworkYamuxAction {
     }
 }
 
+impl ActionKindGet for P2pNetworkQuicAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Authenticated { .. } => ActionKind::P2pNetworkQuicAuthenticated,
+            Self::Ready { .. } => ActionKind::P2pNetworkQuicReady,
+            Self::IncomingStream { .. } => ActionKind::P2pNetworkQuicIncomingStream,
+            Self::IncomingData { .. } => ActionKind::P2pNetworkQuicIncomingData,
+            Self::StreamReset { .. } => ActionKind::P2pNetworkQuicStreamReset,
+            Self::OutgoingData { .. } => ActionKind::P2pNetworkQuicOutgoingData,
+            Self::OpenStream { .. } => ActionKind::P2pNetworkQuicOpenStream,
+        }
+    }
+}
+
 impl ActionKindGet for P2pNetworkIdentifyAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -2005,6 +2233,32 @@ impl ActionKindGet for P2pNetworkIdentifyAction {
     }
 }
 
+impl ActionKindGet for P2pNetworkAutonatAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::New { .. } => ActionKind::P2pNetworkAutonatNew,
+            Self::IncomingData { .. } => ActionKind::P2pNetworkAutonatIncomingData,
+            Self::DialBackResult { .. } => ActionKind::P2pNetworkAutonatDialBackResult,
+            Self::Close { .. } => ActionKind::P2pNetworkAutonatClose,
+            Self::RemoteClose { .. } => ActionKind::P2pNetworkAutonatRemoteClose,
+            Self::Prune { .. } => ActionKind::P2pNetworkAutonatPrune,
+        }
+    }
+}
+
+impl ActionKindGet for P2pNetworkWebrtcSignalAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::New { .. } => ActionKind::P2pNetworkWebrtcSignalNew,
+            Self::IncomingData { .. } => ActionKind::P2pNetworkWebrtcSignalIncomingData,
+            Self::OutgoingMessage { .. } => ActionKind::P2pNetworkWebrtcSignalOutgoingMessage,
+            Self::Close { .. } => ActionKind::P2pNetworkWebrtcSignalClose,
+            Self::RemoteClose { .. } => ActionKind::P2pNetworkWebrtcSignalRemoteClose,
+            Self::Prune { .. } => ActionKind::P2pNetworkWebrtcSignalPrune,
+        }
+    }
+}
+
 impl ActionKindGet for P2pNetworkKadAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -2044,6 +2298,7 @@ impl ActionKindGet for P2pNetworkPubsubAction {
                 ActionKind::P2pNetworkPubsubValidateIncomingMessage
             }
             Self::PruneMessages { .. } => ActionKind::P2pNetworkPubsubPruneMessages,
+            Self::Heartbeat { .. } => ActionKind::P2pNetworkPubsubHeartbeat,
             Self::RejectMessage { .. } => ActionKind::P2pNetworkPubsubRejectMessage,
             Self::IgnoreMessage { .. } => ActionKind::P2pNetworkPubsubIgnoreMessage,
             Self::BroadcastValidatedMessage { .. } => {
@@ -2119,6 +2374,9 @@ impl ActionKindGet for P2pNetworkSchedulerEffectfulAction {
                 ActionKind::P2pNetworkSchedulerEffectfulIncomingDataIsReady
             }
             Self::NoiseSelectDone { .. } => ActionKind::P2pNetworkSchedulerEffectfulNoiseSelectDone,
+            Self::QuicOutgoingData { .. } => {
+                ActionKind::P2pNetworkSchedulerEffectfulQuicOutgoingData
+            }
             Self::Disconnect { .. } => ActionKind::P2pNetworkSchedulerEffectfulDisconnect,
         }
     }
@@ -2261,6 +2519,36 @@ impl ActionKindGet for TransitionFrontierSyncLedgerSnarkedAction {
         match self {
             Self::Pending => ActionKind::TransitionFrontierSyncLedgerSnarkedPending,
             Self::PeersQuery => ActionKind::TransitionFrontierSyncLedgerSnarkedPeersQuery,
+            Self::PeerQueryEpochLedgerInit { .. } => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerInit
+            }
+            Self::PeerQueryEpochLedgerPending { .. } => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerPending
+            }
+            Self::PeerQueryEpochLedgerError { .. } => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerError
+            }
+            Self::PeerQueryEpochLedgerSuccess { .. } => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedPeerQueryEpochLedgerSuccess
+            }
+            Self::EpochLedgerReceived { .. } => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedEpochLedgerReceived
+            }
+            Self::EpochLedgerSetInit => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedEpochLedgerSetInit
+            }
+            Self::EpochLedgerSetPending => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedEpochLedgerSetPending
+            }
+            Self::EpochLedgerAccepted { .. } => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedEpochLedgerAccepted
+            }
+            Self::EpochLedgerRejected { .. } => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedEpochLedgerRejected
+            }
+            Self::EpochLedgerFallback => {
+                ActionKind::TransitionFrontierSyncLedgerSnarkedEpochLedgerFallback
+            }
             Self::PeerQueryNumAccountsInit { .. } => {
                 ActionKind::TransitionFrontierSyncLedgerSnarkedPeerQueryNumAccountsInit
             }
@@ -1,11 +1,25 @@
 use mina_p2p_messages::v2::{NonZeroCurvePoint, ProtocolVersionStableV2};
 use serde::{Deserialize, Serialize};
 
+use super::TransactionSelectionConfig;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockProducerConfig {
     pub pub_key: NonZeroCurvePoint,
     pub custom_coinbase_receiver: Option<NonZeroCurvePoint>,
+    /// Other keys, for which we produce blocks next to `pub_key`.
+    #[serde(default)]
+    pub additional_keys: Vec<BlockProducerKeyConfig>,
     pub proposed_protocol_version: Option<ProtocolVersionStableV2>,
+    /// How transactions from the pool are picked for our blocks.
+    #[serde(default)]
+    pub transaction_selection: TransactionSelectionConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerKeyConfig {
+    pub pub_key: NonZeroCurvePoint,
+    pub custom_coinbase_receiver: Option<NonZeroCurvePoint>,
 }
 
 impl BlockProducerConfig {
@@ -13,7 +27,63 @@ impl BlockProducerConfig {
         Self {
             pub_key,
             custom_coinbase_receiver: None,
+            additional_keys: Vec::new(),
             proposed_protocol_version: None,
+            transaction_selection: Default::default(),
+        }
+    }
+
+    pub fn coinbase_receiver(&self) -> &NonZeroCurvePoint {
+        self.custom_coinbase_receiver
+            .as_ref()
+            .unwrap_or(&self.pub_key)
+    }
+
+    /// Adds another producer key. Returns `false` if the key is
+    /// already configured.
+    pub fn add_key(&mut self, key: BlockProducerKeyConfig) -> bool {
+        if self.contains_key(&key.pub_key) {
+            return false;
+        }
+        self.additional_keys.push(key);
+        true
+    }
+
+    /// All producer keys, starting with the primary one (`pub_key`).
+    pub fn keys(&self) -> impl Iterator<Item = BlockProducerKeyConfig> + '_ {
+        std::iter::once(BlockProducerKeyConfig {
+            pub_key: self.pub_key.clone(),
+            custom_coinbase_receiver: self.custom_coinbase_receiver.clone(),
+        })
+        .chain(self.additional_keys.iter().cloned())
+    }
+
+    pub fn pub_keys(&self) -> impl Iterator<Item = &NonZeroCurvePoint> {
+        std::iter::once(&self.pub_key).chain(self.additional_keys.iter().map(|key| &key.pub_key))
+    }
+
+    pub fn contains_key(&self, pub_key: &NonZeroCurvePoint) -> bool {
+        self.pub_keys().any(|key| key == pub_key)
+    }
+
+    /// Coinbase receiver for blocks produced by `producer`, or `None`
+    /// if `producer` isn't one of our keys.
+    pub fn coinbase_receiver_of(&self, producer: &NonZeroCurvePoint) -> Option<&NonZeroCurvePoint> {
+        if producer == &self.pub_key {
+            return Some(self.coinbase_receiver());
+        }
+        self.additional_keys
+            .iter()
+            .find(|key| &key.pub_key == producer)
+            .map(BlockProducerKeyConfig::coinbase_receiver)
+    }
+}
+
+impl BlockProducerKeyConfig {
+    pub fn new(pub_key: NonZeroCurvePoint) -> Self {
+        Self {
+            pub_key,
+            custom_coinbase_receiver: None,
         }
     }
 
@@ -23,7 +23,7 @@ use super::{
         BlockProducerVrfEvaluatorAction, BlockProducerVrfEvaluatorState, InterruptReason,
     },
     BlockProducerAction, BlockProducerActionWithMetaRef, BlockProducerCurrentState,
-    BlockProducerEnabled, BlockProducerState, BlockWithoutProof,
+    BlockProducerEnabled, BlockProducerState, BlockProducerWonSlotDiscardReason, BlockWithoutProof,
 };
 
 impl BlockProducerState {
@@ -283,6 +283,19 @@ impl BlockProducerEnabled {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(BlockProducerEffectfulAction::BlockProveSuccess);
             }
+            BlockProducerAction::BlockProveError { .. } => {
+                let reason = BlockProducerWonSlotDiscardReason::BlockProofFailed;
+                if let Some(won_slot) = state.current.won_slot() {
+                    state.current = BlockProducerCurrentState::WonSlotDiscarded {
+                        time: meta.time(),
+                        won_slot: won_slot.clone(),
+                        reason,
+                    };
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(BlockProducerEffectfulAction::WonSlotDiscard { reason });
+            }
             BlockProducerAction::BlockProduced => {
                 let current_state = std::mem::take(&mut state.current);
 
@@ -428,8 +441,14 @@ impl BlockProducerEnabled {
         let vrf_truncated_output: v2::ConsensusVrfOutputTruncatedStableV1 =
             (*won_slot.vrf_output).clone().into();
         let vrf_hash = won_slot.vrf_output.hash();
-        let block_creator = self.config.pub_key.clone();
-        let coinbase_receiver = self.config.coinbase_receiver().clone();
+        let block_creator = won_slot.producer.clone();
+        let Some(coinbase_receiver) = self.config.coinbase_receiver_of(&block_creator).cloned()
+        else {
+            bug_condition!(
+                "Invalid state for `BlockProducerAction::BlockUnprovenBuild`: won slot producer is not one of our keys"
+            );
+            return;
+        };
         let proposed_protocol_version_opt = self.config.proposed_protocol_version.clone();
 
         let ledger_proof_statement = ledger_proof_statement_from_emitted_proof(
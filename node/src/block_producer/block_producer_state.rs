@@ -140,6 +140,7 @@ pub enum BlockProducerWonSlotDiscardReason {
     BestTipStakingLedgerDifferent,
     BestTipGlobalSlotHigher,
     BestTipSuperior,
+    BlockProofFailed,
 }
 
 impl BlockProducerState {
@@ -176,13 +177,14 @@ impl BlockProducerState {
     }
 
     pub fn is_me(&self, producer: &v2::NonZeroCurvePoint) -> bool {
-        self.with(false, |this| producer == &this.config.pub_key)
+        self.with(false, |this| this.config.contains_key(producer))
     }
 
     /// Checks if the block was produced by us recently.
     pub fn is_produced_by_me(&self, block: &ArcBlockWithHash) -> bool {
         self.with(false, |this| {
-            block.producer() == &this.config.pub_key && this.injected_blocks.contains(block.hash())
+            this.config.contains_key(block.producer())
+                && this.injected_blocks.contains(block.hash())
         })
     }
 
@@ -221,8 +223,10 @@ impl BlockProducerState {
         self.with(None, |this| Some((&this.vrf_evaluator, &this.config)))
     }
 
-    /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    /// If we need to construct delegator tables, get their inputs.
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         self.vrf_evaluator()?.vrf_delegator_table_inputs()
     }
 
@@ -0,0 +1,544 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    str::FromStr,
+};
+
+use ledger::{
+    scan_state::{
+        currency::Fee,
+        fee_rate::FeeRate,
+        transaction_logic::{valid, GenericCommand},
+    },
+    transaction_pool::ValidCommandWithHash,
+};
+use mina_p2p_messages::v2::{CurrencyFeeStableV1, NonZeroCurvePoint, TransactionHash};
+use serde::{Deserialize, Serialize};
+
+/// How many pool transactions are considered per transaction slot of the
+/// block. Policies and filters only see this many of the pool's best.
+pub const CANDIDATES_PER_TRANSACTION: usize = 4;
+
+/// Transactions of one sender, in nonce order. Only the first one is
+/// applicable right away, the rest can be included only after it.
+pub type SenderQueue = VecDeque<ValidCommandWithHash>;
+
+/// Decides which pool transactions go into the staged ledger diff of the
+/// produced block, and in which order.
+///
+/// Staged ledger diff takes transactions in the returned order until the
+/// block is full, so the policy should return them by priority.
+pub trait TransactionSelectionPolicy {
+    /// Picks at most `limit` transactions from `senders`. Transactions of
+    /// the same sender must be returned in their queue order.
+    fn select(&self, senders: Vec<SenderQueue>, limit: usize) -> Vec<ValidCommandWithHash>;
+}
+
+/// Highest fee per weight unit first. Same order as the pool uses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeePerWeightGreedy;
+
+/// Takes one transaction of every sender per round, senders with higher
+/// fee per weight unit first. Keeps a single sender from filling the block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FairPerSender;
+
+/// Includes all transactions of `senders` first, in the list order (e.g.
+/// the operator's own payouts). The rest are picked by [`FeePerWeightGreedy`].
+#[derive(Debug, Clone, Default)]
+pub struct PriorityList {
+    pub senders: Vec<NonZeroCurvePoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TransactionSelectionConfig {
+    #[serde(default)]
+    pub policy: TransactionSelectionPolicyKind,
+    /// Senders for [`TransactionSelectionPolicyKind::PriorityList`].
+    #[serde(default)]
+    pub priority_senders: Vec<NonZeroCurvePoint>,
+    /// Transactions with lower fee aren't included.
+    #[serde(default)]
+    pub min_fee: Option<CurrencyFeeStableV1>,
+    /// Transactions from these senders aren't included.
+    #[serde(default)]
+    pub excluded_senders: Vec<NonZeroCurvePoint>,
+    /// Number of block's transaction slots, which only zkApp commands
+    /// can take. Left empty if there aren't enough zkApp commands.
+    #[serde(default)]
+    pub zkapp_reserved: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransactionSelectionPolicyKind {
+    #[default]
+    FeePerWeight,
+    FairPerSender,
+    /// [`PriorityList`] of [`TransactionSelectionConfig::priority_senders`].
+    PriorityList,
+}
+
+impl TransactionSelectionConfig {
+    /// Number of pool transactions to select `limit` of them from.
+    pub fn candidates_limit(limit: usize) -> usize {
+        limit.saturating_mul(CANDIDATES_PER_TRANSACTION)
+    }
+
+    /// Adds all queued transactions of the priority senders to
+    /// `candidates`. When the pool is full of transactions with higher
+    /// fees, its best ones may not contain them. `queued` returns pool
+    /// transactions of the sender in nonce order.
+    pub fn add_priority_candidates(
+        &self,
+        candidates: &mut Vec<ValidCommandWithHash>,
+        mut queued: impl FnMut(&NonZeroCurvePoint) -> Vec<ValidCommandWithHash>,
+    ) {
+        if self.policy != TransactionSelectionPolicyKind::PriorityList {
+            return;
+        }
+        for sender in &self.priority_senders {
+            let queue = queued(sender);
+            if queue.is_empty() {
+                continue;
+            }
+            // Listed ones are the head of the queue, move the whole queue
+            // to the end to keep the nonce order.
+            let hashes = queue.iter().map(|cmd| &cmd.hash).collect::<HashSet<_>>();
+            candidates.retain(|cmd| !hashes.contains(&cmd.hash));
+            candidates.extend(queue);
+        }
+    }
+
+    /// Selects transactions for the block out of `candidates`, which must
+    /// be ordered like `TransactionPool::list_includable_transactions` does
+    /// (each sender's transactions in nonce order).
+    pub fn select(
+        &self,
+        candidates: Vec<ValidCommandWithHash>,
+        limit: usize,
+    ) -> Vec<ValidCommandWithHash> {
+        let min_fee = self.min_fee.as_ref().map(Fee::from);
+        let mut senders = group_by_sender(candidates);
+        senders.retain_mut(|queue| {
+            if sender(queue).is_some_and(|sender| self.excluded_senders.contains(&sender)) {
+                return false;
+            }
+            // Later transactions of the sender can't be applied without
+            // the skipped one.
+            if let Some(min_fee) = min_fee {
+                let skipped = queue.iter().position(|cmd| cmd.data.fee() < min_fee);
+                queue.truncate(skipped.unwrap_or(queue.len()));
+            }
+            !queue.is_empty()
+        });
+
+        // With reserved slots, policy orders all of them, and signed
+        // commands over their share are skipped afterwards.
+        let policy_limit = match self.zkapp_reserved {
+            0 => limit,
+            _ => senders.iter().map(SenderQueue::len).sum(),
+        };
+        let selected = match self.policy {
+            TransactionSelectionPolicyKind::FeePerWeight => {
+                FeePerWeightGreedy.select(senders, policy_limit)
+            }
+            TransactionSelectionPolicyKind::FairPerSender => {
+                FairPerSender.select(senders, policy_limit)
+            }
+            TransactionSelectionPolicyKind::PriorityList => PriorityList {
+                senders: self.priority_senders.clone(),
+            }
+            .select(senders, policy_limit),
+        };
+        if self.zkapp_reserved == 0 {
+            return selected;
+        }
+
+        let signed_limit = limit.saturating_sub(self.zkapp_reserved);
+        let mut signed_count = 0;
+        let mut skipped_senders = HashSet::new();
+        selected
+            .into_iter()
+            .filter(|cmd| {
+                let fee_payer = cmd.data.fee_payer();
+                if skipped_senders.contains(&fee_payer) {
+                    return false;
+                }
+                if matches!(cmd.data, valid::UserCommand::SignedCommand(_)) {
+                    if signed_count == signed_limit {
+                        // Later transactions of the sender can't be
+                        // applied without this one.
+                        skipped_senders.insert(fee_payer);
+                        return false;
+                    }
+                    signed_count += 1;
+                }
+                true
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+impl TransactionSelectionPolicy for FeePerWeightGreedy {
+    fn select(&self, senders: Vec<SenderQueue>, limit: usize) -> Vec<ValidCommandWithHash> {
+        let mut selected = Vec::with_capacity(limit);
+        select_by_fee(senders, limit, &mut selected);
+        selected
+    }
+}
+
+impl TransactionSelectionPolicy for FairPerSender {
+    fn select(&self, mut senders: Vec<SenderQueue>, limit: usize) -> Vec<ValidCommandWithHash> {
+        let mut selected = Vec::with_capacity(limit);
+        senders.retain(|queue| !queue.is_empty());
+        while selected.len() < limit && !senders.is_empty() {
+            senders.sort_by_cached_key(|queue| Reverse(head_priority(queue)));
+            for queue in &mut senders {
+                if selected.len() == limit {
+                    break;
+                }
+                selected.extend(queue.pop_front());
+            }
+            senders.retain(|queue| !queue.is_empty());
+        }
+        selected
+    }
+}
+
+impl TransactionSelectionPolicy for PriorityList {
+    fn select(&self, senders: Vec<SenderQueue>, limit: usize) -> Vec<ValidCommandWithHash> {
+        let (mut prioritized, rest): (Vec<_>, Vec<_>) = senders
+            .into_iter()
+            .partition(|queue| sender(queue).is_some_and(|sender| self.senders.contains(&sender)));
+        prioritized.sort_by_key(|queue| {
+            let sender = sender(queue);
+            self.senders.iter().position(|v| Some(v) == sender.as_ref())
+        });
+
+        let mut selected = prioritized
+            .into_iter()
+            .flatten()
+            .take(limit)
+            .collect::<Vec<_>>();
+        select_by_fee(rest, limit, &mut selected);
+        selected
+    }
+}
+
+impl FromStr for TransactionSelectionPolicyKind {
+    type Err = TransactionSelectionPolicyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "fee" | "fee-per-weight" => Self::FeePerWeight,
+            "fair" | "fair-per-sender" => Self::FairPerSender,
+            "priority" | "priority-list" => Self::PriorityList,
+            other => return Err(TransactionSelectionPolicyParseError(other.to_owned())),
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("invalid transaction selection policy: {0}! expected one of: fee-per-weight/fair-per-sender/priority-list")]
+pub struct TransactionSelectionPolicyParseError(String);
+
+fn group_by_sender(candidates: Vec<ValidCommandWithHash>) -> Vec<SenderQueue> {
+    let mut index = HashMap::new();
+    let mut senders: Vec<SenderQueue> = Vec::new();
+    for cmd in candidates {
+        let fee_payer = cmd.data.fee_payer();
+        let i = *index.entry(fee_payer).or_insert_with(|| {
+            senders.push(SenderQueue::new());
+            senders.len() - 1
+        });
+        senders[i].push_back(cmd);
+    }
+    senders
+}
+
+fn sender(queue: &SenderQueue) -> Option<NonZeroCurvePoint> {
+    let cmd = queue.front()?;
+    Some((&cmd.data.fee_payer().public_key).into())
+}
+
+/// Higher fee per weight unit first, ties are broken by the lower hash.
+fn head_priority(queue: &SenderQueue) -> Option<(FeeRate, Reverse<TransactionHash>)> {
+    let cmd = queue.front()?;
+    Some((
+        cmd.data.forget_check().fee_per_wu(),
+        Reverse(cmd.hash.clone()),
+    ))
+}
+
+/// Appends transactions to `selected` by fee per weight unit, until it
+/// has `limit` of them.
+fn select_by_fee(
+    senders: Vec<SenderQueue>,
+    limit: usize,
+    selected: &mut Vec<ValidCommandWithHash>,
+) {
+    let mut heap = senders
+        .into_iter()
+        .filter_map(|queue| Some((head_priority(&queue)?, QueueByPriority(queue))))
+        .collect::<BinaryHeap<_>>();
+
+    while selected.len() < limit {
+        let Some((_, QueueByPriority(mut queue))) = heap.pop() else {
+            break;
+        };
+        selected.extend(queue.pop_front());
+        if let Some(priority) = head_priority(&queue) {
+            heap.push((priority, QueueByPriority(queue)));
+        }
+    }
+}
+
+/// Priority is already in the heap entry, queues themselves are equal.
+struct QueueByPriority(SenderQueue);
+
+impl PartialEq for QueueByPriority {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for QueueByPriority {}
+
+impl PartialOrd for QueueByPriority {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueByPriority {
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ledger::{
+        gen_compressed,
+        scan_state::{
+            currency::{Amount, Nonce},
+            transaction_logic::{
+                signed_command::{Body, PaymentPayload, SignedCommand, SignedCommandPayload},
+                zkapp_command::{self, CallForest, FeePayer, FeePayerBody, ZkAppCommand},
+                Memo, WithHash,
+            },
+        },
+    };
+    use mina_signer::{CompressedPubKey, Signature};
+
+    use super::*;
+
+    fn hash(id: u8) -> TransactionHash {
+        TransactionHash::from(&[id; 32])
+    }
+
+    fn payment(sender: &CompressedPubKey, nonce: u32, fee: u64, id: u8) -> ValidCommandWithHash {
+        let payload = SignedCommandPayload::create(
+            Fee::from_u64(fee),
+            sender.clone(),
+            Nonce::from_u32(nonce),
+            None,
+            Memo::empty(),
+            Body::Payment(PaymentPayload {
+                receiver_pk: sender.clone(),
+                amount: Amount::from_u64(1_000_000_000),
+            }),
+        );
+        let cmd = SignedCommand {
+            payload,
+            signer: sender.clone(),
+            signature: Signature::dummy(),
+        };
+        WithHash {
+            data: valid::UserCommand::SignedCommand(Box::new(cmd)),
+            hash: hash(id),
+        }
+    }
+
+    fn zkapp(sender: &CompressedPubKey, nonce: u32, fee: u64, id: u8) -> ValidCommandWithHash {
+        let zkapp_command = ZkAppCommand {
+            fee_payer: FeePayer {
+                body: FeePayerBody {
+                    public_key: sender.clone(),
+                    fee: Fee::from_u64(fee),
+                    valid_until: None,
+                    nonce: Nonce::from_u32(nonce),
+                },
+                authorization: Signature::dummy(),
+            },
+            account_updates: CallForest::default(),
+            memo: Memo::empty(),
+        };
+        WithHash {
+            data: valid::UserCommand::ZkAppCommand(Box::new(zkapp_command::valid::ZkAppCommand {
+                zkapp_command,
+            })),
+            hash: hash(id),
+        }
+    }
+
+    /// Alice has 3 transactions with high fees, Bob and Carol one with
+    /// a lower fee each. Ordered the way the pool lists them.
+    fn candidates() -> (Vec<ValidCommandWithHash>, [CompressedPubKey; 3]) {
+        let (alice, bob, carol) = (gen_compressed(), gen_compressed(), gen_compressed());
+        let candidates = vec![
+            payment(&alice, 0, 50_000_000, 1),
+            payment(&alice, 1, 40_000_000, 2),
+            payment(&alice, 2, 30_000_000, 3),
+            payment(&bob, 0, 20_000_000, 4),
+            payment(&carol, 0, 10_000_000, 5),
+        ];
+        (candidates, [alice, bob, carol])
+    }
+
+    fn assert_selected(selected: Vec<ValidCommandWithHash>, expected: &[u8]) {
+        let selected = selected.into_iter().map(|cmd| cmd.hash).collect::<Vec<_>>();
+        let expected = expected.iter().copied().map(hash).collect::<Vec<_>>();
+        assert_eq!(selected, expected);
+    }
+
+    fn config(policy: TransactionSelectionPolicyKind) -> TransactionSelectionConfig {
+        TransactionSelectionConfig {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fee_per_weight() {
+        let (candidates, _) = candidates();
+        let config = config(TransactionSelectionPolicyKind::FeePerWeight);
+        assert_selected(config.select(candidates.clone(), 10), &[1, 2, 3, 4, 5]);
+        assert_selected(config.select(candidates, 3), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_fair_per_sender() {
+        let (candidates, _) = candidates();
+        let config = config(TransactionSelectionPolicyKind::FairPerSender);
+        assert_selected(config.select(candidates.clone(), 10), &[1, 4, 5, 2, 3]);
+        assert_selected(config.select(candidates, 3), &[1, 4, 5]);
+    }
+
+    #[test]
+    fn test_priority_list() {
+        let (candidates, [_, bob, carol]) = candidates();
+        let mut config = config(TransactionSelectionPolicyKind::PriorityList);
+        // Without priority senders it's the same as fee per weight.
+        assert_selected(config.select(candidates.clone(), 10), &[1, 2, 3, 4, 5]);
+
+        config.priority_senders = vec![(&carol).into(), (&bob).into()];
+        assert_selected(config.select(candidates.clone(), 10), &[5, 4, 1, 2, 3]);
+        assert_selected(config.select(candidates, 3), &[5, 4, 1]);
+    }
+
+    #[test]
+    fn test_priority_list_full_pool() {
+        let (_, [alice, bob, carol]) = candidates();
+        let limit = 2;
+        // Pool's best are Alice's and Bob's transactions, Carol's cheap
+        // ones don't make it into the listed candidates.
+        let mut pool = (0..TransactionSelectionConfig::candidates_limit(limit) as u8)
+            .map(|i| {
+                let (sender, nonce) = match i % 2 {
+                    0 => (&alice, i / 2),
+                    _ => (&bob, i / 2),
+                };
+                payment(sender, nonce.into(), 50_000_000 - i as u64, 10 + i)
+            })
+            .collect::<Vec<_>>();
+        let carol_queue = vec![
+            payment(&carol, 0, 1_000_000, 1),
+            payment(&carol, 1, 1_000_000, 2),
+        ];
+        let queued = |sender: &NonZeroCurvePoint| {
+            if *sender == NonZeroCurvePoint::from(&carol) {
+                carol_queue.clone()
+            } else {
+                Vec::new()
+            }
+        };
+
+        let mut config = config(TransactionSelectionPolicyKind::FeePerWeight);
+        config.priority_senders = vec![(&carol).into()];
+        let mut candidates = pool.clone();
+        config.add_priority_candidates(&mut candidates, queued);
+        // Only the priority list policy looks at the priority senders.
+        assert_selected(config.select(candidates, limit), &[10, 11]);
+
+        config.policy = TransactionSelectionPolicyKind::PriorityList;
+        let mut candidates = pool.clone();
+        config.add_priority_candidates(&mut candidates, queued);
+        assert_selected(config.select(candidates, limit), &[1, 2]);
+
+        // Carol's head is listed, her queue is still in nonce order.
+        pool.push(carol_queue[0].clone());
+        config.add_priority_candidates(&mut pool, queued);
+        assert_selected(config.select(pool, 3), &[1, 2, 10]);
+    }
+
+    #[test]
+    fn test_filters_keep_nonce_order() {
+        let (candidates, [_, bob, _]) = candidates();
+        let config = TransactionSelectionConfig {
+            min_fee: Some((&Fee::from_u64(35_000_000)).into()),
+            excluded_senders: vec![(&bob).into()],
+            ..Default::default()
+        };
+        // Alice's last transaction and Carol's are below the minimum fee.
+        assert_selected(config.select(candidates, 10), &[1, 2]);
+    }
+
+    #[test]
+    fn test_zkapp_reserved() {
+        let (mut candidates, [alice, bob, _]) = candidates();
+        let dave = gen_compressed();
+        // Alice's zkApp after her payments, Bob's after his payment and
+        // a separate zkApp sender with the lowest fee.
+        candidates.insert(3, zkapp(&alice, 3, 45_000_000, 6));
+        candidates.insert(5, zkapp(&bob, 1, 25_000_000, 7));
+        candidates.push(zkapp(&dave, 0, 5_000_000, 8));
+
+        let policies = [
+            TransactionSelectionPolicyKind::FeePerWeight,
+            TransactionSelectionPolicyKind::FairPerSender,
+            TransactionSelectionPolicyKind::PriorityList,
+        ];
+        for policy in policies {
+            let mut config = config(policy);
+            config.priority_senders = vec![(&bob).into()];
+            config.zkapp_reserved = 2;
+            let selected = config.select(candidates.clone(), 6);
+            let signed = selected
+                .iter()
+                .filter(|cmd| matches!(cmd.data, valid::UserCommand::SignedCommand(_)))
+                .count();
+            assert!(selected.len() <= 6, "{policy:?}");
+            assert!(signed <= 4, "{policy:?}");
+            // Every sender's transactions are included without gaps.
+            let mut nonces = HashMap::new();
+            for cmd in &selected {
+                let cmd = cmd.data.forget_check();
+                let expected_nonce = nonces.entry(cmd.fee_payer()).or_insert(Nonce::zero());
+                assert_eq!(cmd.applicable_at_nonce(), *expected_nonce, "{policy:?}");
+                *expected_nonce = expected_nonce.incr();
+            }
+        }
+
+        let mut config = config(TransactionSelectionPolicyKind::FeePerWeight);
+        config.zkapp_reserved = 2;
+        // Alice's zkApp needs all her payments, Bob's one needs his.
+        assert_selected(config.select(candidates.clone(), 4), &[1, 2, 8]);
+        assert_selected(config.select(candidates.clone(), 6), &[1, 2, 3, 6, 4, 7]);
+
+        // Reserved slots stay empty without zkApps.
+        let (candidates, _) = candidates();
+        assert_selected(config.select(candidates, 4), &[1, 2]);
+    }
+}
@@ -5,6 +5,9 @@ use std::sync::Arc;
 
 pub use block_producer_config::*;
 
+mod block_producer_transaction_selection;
+pub use block_producer_transaction_selection::*;
+
 mod block_producer_state;
 pub use block_producer_state::*;
 
@@ -28,6 +31,8 @@ use self::vrf_evaluator::VrfWonSlotWithHash;
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct BlockProducerWonSlot {
     pub slot_time: redux::Timestamp,
+    /// Our producer key, which won the slot.
+    pub producer: v2::NonZeroCurvePoint,
     pub delegator: (v2::NonZeroCurvePoint, AccountIndex),
     pub global_slot: v2::ConsensusGlobalSlotStableV1,
     pub vrf_output: Box<VrfOutput>,
@@ -70,6 +75,7 @@ impl BlockProducerWonSlot {
 
         Self {
             slot_time,
+            producer: won_slot.producer.clone().into(),
             delegator,
             global_slot,
             vrf_output: won_slot.vrf_output.clone(),
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use crate::account::AccountPublicKey;
@@ -15,7 +16,7 @@ use serde::{Deserialize, Serialize};
 use vrf::VrfEvaluationOutput;
 use vrf::VrfWonSlot;
 
-use super::DelegatorTable;
+use super::DelegatorTables;
 use super::InterruptReason;
 use super::{EpochData, VrfEvaluatorInput};
 
@@ -36,6 +37,11 @@ pub enum BlockProducerVrfEvaluatorAction {
         vrf_output: VrfEvaluationOutput,
         staking_ledger_hash: LedgerHash,
     },
+    /// Evaluation failed, it is retried after a backoff.
+    #[action_event(level = warn, fields(global_slot, error))]
+    ProcessSlotEvaluationError { global_slot: u32, error: String },
+    /// Retry the evaluation of the slot which failed.
+    RetrySlotEvaluation,
     #[action_event(level = trace)]
     InitializeEvaluator { best_tip: ArcBlockWithHash },
     /// Checking possible Vrf evaluations.
@@ -64,15 +70,15 @@ pub enum BlockProducerVrfEvaluatorAction {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Constructing delegator table.
     #[action_event(level = info)]
     BeginDelegatorTableConstruction,
-    /// Delegator table constructed.
+    /// Delegator tables constructed.
     #[action_event(level = info)]
     FinalizeDelegatorTableConstruction {
-        delegator_table: Arc<DelegatorTable>,
+        delegator_tables: Arc<DelegatorTables>,
     },
     /// Selecting starting slot.
     #[action_event(level = info, fields(current_global_slot, best_tip_height))]
@@ -124,7 +130,7 @@ pub enum BlockProducerVrfEvaluatorAction {
 }
 
 impl redux::EnablingCondition<crate::State> for BlockProducerVrfEvaluatorAction {
-    fn is_enabled(&self, state: &crate::State, _time: redux::Timestamp) -> bool {
+    fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
         match self {
             BlockProducerVrfEvaluatorAction::EvaluateSlot { .. } => state
                 .block_producer
@@ -149,6 +155,25 @@ impl redux::EnablingCondition<crate::State> for BlockProducerVrfEvaluatorAction
                     false
                 }
             }),
+            BlockProducerVrfEvaluatorAction::ProcessSlotEvaluationError { global_slot, .. } => {
+                state.block_producer.with(false, |this| {
+                    this.vrf_evaluator.is_slot_requested()
+                        && this
+                            .vrf_evaluator
+                            .current_evaluation()
+                            .is_some_and(|evaluation| {
+                                evaluation.latest_evaluated_slot.checked_add(1)
+                                    == Some(*global_slot)
+                            })
+                })
+            }
+            BlockProducerVrfEvaluatorAction::RetrySlotEvaluation => {
+                state.block_producer.with(false, |this| {
+                    this.vrf_evaluator
+                        .slot_evaluation_retry_at()
+                        .is_some_and(|retry_at| time >= retry_at)
+                })
+            }
             BlockProducerVrfEvaluatorAction::InitializeEvaluator { .. } => state
                 .block_producer
                 .with(false, |this| this.vrf_evaluator.is_idle()),
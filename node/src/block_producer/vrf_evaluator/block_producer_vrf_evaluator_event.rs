@@ -5,6 +5,12 @@ use super::VrfEvaluationOutputWithHash;
 #[derive(derive_more::From, Serialize, Deserialize, Debug, Clone)]
 pub enum BlockProducerVrfEvaluatorEvent {
     Evaluated(VrfEvaluationOutputWithHash),
+    /// Evaluation of the slot failed, e.g. the remote signer couldn't be
+    /// reached, so whether it was won is unknown.
+    EvaluationFailed {
+        global_slot: u32,
+        error: String,
+    },
 }
 
 impl std::fmt::Display for BlockProducerVrfEvaluatorEvent {
@@ -14,6 +20,9 @@ impl std::fmt::Display for BlockProducerVrfEvaluatorEvent {
             Self::Evaluated(vrf_output) => {
                 write!(f, "Evaluated, {}", vrf_output)
             }
+            Self::EvaluationFailed { global_slot, error } => {
+                write!(f, "EvaluationFailed, {global_slot}, {error}")
+            }
         }
     }
 }
@@ -42,13 +42,10 @@ impl BlockProducerVrfEvaluatorState {
             } => {
                 let global_slot_evaluated = match &vrf_output {
                     vrf::VrfEvaluationOutput::SlotWon(won_slot_data) => {
-                        state.won_slots.insert(
-                            won_slot_data.global_slot,
-                            VrfWonSlotWithHash::new(
-                                won_slot_data.clone(),
-                                staking_ledger_hash.clone(),
-                            ),
-                        );
+                        state.insert_won_slot(VrfWonSlotWithHash::new(
+                            won_slot_data.clone(),
+                            staking_ledger_hash.clone(),
+                        ));
                         won_slot_data.global_slot
                     }
                     vrf::VrfEvaluationOutput::SlotLost(global_slot) => *global_slot,
@@ -78,6 +75,30 @@ impl BlockProducerVrfEvaluatorState {
                     dispatcher.push(BlockProducerAction::WonSlotSearch);
                 }
             }
+            BlockProducerVrfEvaluatorAction::ProcessSlotEvaluationError { global_slot, error } => {
+                state.set_slot_evaluation_failed(meta.time(), *global_slot, error.clone());
+            }
+            BlockProducerVrfEvaluatorAction::RetrySlotEvaluation => {
+                let Some(pending_evaluation) = state.current_evaluation() else {
+                    bug_condition!("no pending evaluation to retry");
+                    return;
+                };
+                state.status = BlockProducerVrfEvaluatorStatus::EpochEvaluationPending {
+                    time: meta.time(),
+                    epoch_number: pending_evaluation.epoch_number,
+                    latest_evaluated_global_slot: pending_evaluation.latest_evaluated_slot,
+                    epoch_data: pending_evaluation.epoch_data,
+                };
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                if let Some(vrf_input) = state
+                    .block_producer
+                    .vrf_evaluator()
+                    .and_then(|s| s.construct_vrf_input())
+                {
+                    dispatcher.push(BlockProducerVrfEvaluatorAction::EvaluateSlot { vrf_input });
+                }
+            }
             BlockProducerVrfEvaluatorAction::CheckEpochBounds {
                 epoch_number,
                 latest_evaluated_global_slot,
@@ -211,7 +232,7 @@ impl BlockProducerVrfEvaluatorState {
                         dispatcher.push(
                             BlockProducerVrfEvaluatorAction::InitializeEpochEvaluation {
                                 staking_epoch_data: epoch_data,
-                                producer: config.pub_key.clone().into(),
+                                producers: config.pub_keys().cloned().map(Into::into).collect(),
                                 best_tip_global_slot: *best_tip_global_slot,
                                 best_tip_epoch,
                                 best_tip_slot: *best_tip_slot,
@@ -233,7 +254,7 @@ impl BlockProducerVrfEvaluatorState {
                 best_tip_global_slot,
                 next_epoch_first_slot,
                 staking_epoch_data,
-                producer,
+                producers,
             } => {
                 state.status = BlockProducerVrfEvaluatorStatus::ReadyToEvaluate {
                     time: meta.time(),
@@ -245,7 +266,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 };
 
                 let dispatcher = state_context.into_dispatcher();
@@ -258,7 +279,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     time: _,
                     is_current_epoch_evaluated: _,
                     is_next_epoch_evaluated: _,
@@ -274,23 +295,23 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 };
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
-                let (staking_ledger_hash, producer) =
+                let (staking_ledger_hash, producers) =
                     match state.block_producer.vrf_delegator_table_inputs() {
                         Some((v1, v2)) => (v1.clone(), v2.clone()),
                         None => return,
                     };
 
                 dispatcher.push(LedgerReadAction::Init {
-                    request: LedgerReadRequest::DelegatorTable(staking_ledger_hash, producer),
+                    request: LedgerReadRequest::DelegatorTable(staking_ledger_hash, producers),
                     callback: LedgerReadInitCallback::None,
                 })
             }
             BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction {
-                delegator_table,
+                delegator_tables,
             } => {
                 let BlockProducerVrfEvaluatorStatus::EpochDelegatorTablePending {
                     best_tip_epoch,
@@ -298,7 +319,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     time: _,
                     staking_epoch_ledger_hash: _,
                 } = &state.status
@@ -307,14 +328,20 @@ impl BlockProducerVrfEvaluatorState {
                     return;
                 };
 
-                openmina_core::log::warn!(
-                    meta.time();
-                    kind = "BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction",
-                    message = "Empty delegator table, account may not exist yet in the staking ledger"
-                );
+                for producer in producers
+                    .iter()
+                    .filter(|producer| !delegator_tables.contains_key(producer))
+                {
+                    openmina_core::log::warn!(
+                        meta.time();
+                        kind = "BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction",
+                        message = "Empty delegator table, account may not exist yet in the staking ledger",
+                        producer = producer.to_string()
+                    );
+                }
 
                 let mut staking_epoch_data = staking_epoch_data.clone();
-                staking_epoch_data.delegator_table = delegator_table.clone();
+                staking_epoch_data.delegator_tables = delegator_tables.clone();
 
                 state.status = BlockProducerVrfEvaluatorStatus::EpochDelegatorTableSuccess {
                     time: meta.time(),
@@ -324,7 +351,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 };
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
@@ -1,5 +1,6 @@
-use std::collections::BTreeMap;
+use std::collections::{btree_map, BTreeMap, BTreeSet};
 use std::sync::Arc;
+use std::time::Duration;
 
 use mina_p2p_messages::v2;
 use openmina_core::block::ArcBlockWithHash;
@@ -7,9 +8,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{account::AccountPublicKey, block_producer::BlockProducerWonSlot};
 
-use super::{DelegatorTable, VrfEvaluatorInput, VrfWonSlotWithHash};
+use super::{DelegatorTables, VrfEvaluatorInput, VrfWonSlotWithHash};
 
 pub const SLOTS_PER_EPOCH: u32 = 7140;
+/// Longest delay before retrying a failed slot evaluation.
+pub const SLOT_EVALUATION_RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
 /// Vrf evaluator sub-state
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockProducerVrfEvaluatorState {
@@ -20,6 +23,9 @@ pub struct BlockProducerVrfEvaluatorState {
     last_evaluated_epoch: Option<u32>,
     pending_evaluation: Option<PendingEvaluation>,
     epoch_context: EpochContext,
+    /// Failed evaluations of the slot being evaluated, in a row.
+    #[serde(default)]
+    slot_evaluation_failures: u32,
 }
 
 impl BlockProducerVrfEvaluatorState {
@@ -32,6 +38,7 @@ impl BlockProducerVrfEvaluatorState {
             last_evaluated_epoch: Default::default(),
             pending_evaluation: Default::default(),
             epoch_context: EpochContext::Waiting,
+            slot_evaluation_failures: 0,
         }
     }
 
@@ -266,7 +273,34 @@ impl BlockProducerVrfEvaluatorState {
         )
     }
 
+    /// Time at which the failed slot evaluation should be retried, if the
+    /// last one failed. The delay doubles with every failure in a row.
+    pub fn slot_evaluation_retry_at(&self) -> Option<redux::Timestamp> {
+        let BlockProducerVrfEvaluatorStatus::SlotEvaluationFailed { time, .. } = &self.status
+        else {
+            return None;
+        };
+        let exp = self.slot_evaluation_failures.saturating_sub(1).min(16);
+        let delay = Duration::from_secs(1 << exp).min(SLOT_EVALUATION_RETRY_MAX_DELAY);
+        Some(*time + delay)
+    }
+
+    pub fn set_slot_evaluation_failed(
+        &mut self,
+        time: redux::Timestamp,
+        global_slot: u32,
+        error: String,
+    ) {
+        self.slot_evaluation_failures = self.slot_evaluation_failures.saturating_add(1);
+        self.status = BlockProducerVrfEvaluatorStatus::SlotEvaluationFailed {
+            time,
+            global_slot,
+            error,
+        };
+    }
+
     pub fn set_latest_evaluated_global_slot(&mut self, global_slot: &u32) {
+        self.slot_evaluation_failures = 0;
         if self.is_evaluating() {
             if let Some(ref mut pending_evaluation) = self.pending_evaluation {
                 pending_evaluation.latest_evaluated_slot = *global_slot;
@@ -323,7 +357,7 @@ impl BlockProducerVrfEvaluatorState {
         if let Some(pending_evaluation) = self.current_evaluation() {
             Some(VrfEvaluatorInput::new(
                 pending_evaluation.epoch_data.seed,
-                pending_evaluation.epoch_data.delegator_table,
+                pending_evaluation.epoch_data.delegator_tables,
                 pending_evaluation
                     .latest_evaluated_slot
                     .checked_add(1)
@@ -336,6 +370,22 @@ impl BlockProducerVrfEvaluatorState {
         }
     }
 
+    /// Adds the won slot. Only one block can be produced per global slot,
+    /// so if more of our keys won the same slot, we keep the one which
+    /// would win the fork choice.
+    pub fn insert_won_slot(&mut self, won_slot: VrfWonSlotWithHash) {
+        match self.won_slots.entry(won_slot.won_slot.global_slot) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(won_slot);
+            }
+            btree_map::Entry::Occupied(mut entry) => {
+                if won_slot.tie_breaker() > entry.get().tie_breaker() {
+                    entry.insert(won_slot);
+                }
+            }
+        }
+    }
+
     pub fn retention_slot(&self, current_epoch_number: &u32) -> u32 {
         const PAST_EPOCHS_TO_KEEP: u32 = 2;
         let cutoff_epoch = current_epoch_number.saturating_sub(PAST_EPOCHS_TO_KEEP);
@@ -348,14 +398,16 @@ impl BlockProducerVrfEvaluatorState {
             .retain(|global_slot, _| cutoff_slot < *global_slot);
     }
 
-    /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    /// If we need to construct delegator tables, get their inputs.
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         match &self.status {
             BlockProducerVrfEvaluatorStatus::EpochDelegatorTablePending {
                 staking_epoch_ledger_hash,
-                producer,
+                producers,
                 ..
-            } => Some((staking_epoch_ledger_hash, producer)),
+            } => Some((staking_epoch_ledger_hash, producers)),
             _ => None,
         }
     }
@@ -365,7 +417,7 @@ impl BlockProducerVrfEvaluatorState {
 pub struct EpochData {
     pub seed: v2::EpochSeed,
     pub ledger: v2::LedgerHash,
-    pub delegator_table: Arc<DelegatorTable>,
+    pub delegator_tables: Arc<DelegatorTables>,
     pub total_currency: u64,
 }
 
@@ -375,7 +427,7 @@ impl EpochData {
             seed,
             ledger,
             total_currency,
-            delegator_table: Default::default(),
+            delegator_tables: Default::default(),
         }
     }
 }
@@ -387,7 +439,7 @@ impl From<v2::ConsensusProofOfStakeDataEpochDataStakingValueVersionedValueStable
         Self {
             seed: value.seed,
             ledger: value.ledger.hash,
-            delegator_table: Default::default(),
+            delegator_tables: Default::default(),
             total_currency: value.ledger.total_currency.as_u64(),
         }
     }
@@ -398,7 +450,7 @@ impl From<v2::ConsensusProofOfStakeDataEpochDataNextValueVersionedValueStableV1>
         Self {
             seed: value.seed,
             ledger: value.ledger.hash,
-            delegator_table: Default::default(),
+            delegator_tables: Default::default(),
             total_currency: value.ledger.total_currency.as_u64(),
         }
     }
@@ -430,7 +482,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Waiting for delegator table building
     EpochDelegatorTablePending {
@@ -442,7 +494,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Delegator table built successfully
     EpochDelegatorTableSuccess {
@@ -454,7 +506,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     InitialSlotSelection {
         time: redux::Timestamp,
@@ -478,6 +530,12 @@ pub enum BlockProducerVrfEvaluatorStatus {
         time: redux::Timestamp,
         global_slot: u32,
     },
+    /// The service failed to evaluate the slot, waiting to retry
+    SlotEvaluationFailed {
+        time: redux::Timestamp,
+        global_slot: u32,
+        error: String,
+    },
     /// The service returned the evaluation succesfully
     EpochEvaluationSuccess {
         time: redux::Timestamp,
@@ -542,6 +600,7 @@ impl std::fmt::Display for BlockProducerVrfEvaluatorStatus {
             Self::InitialSlotSelection { .. } => write!(f, "StartingSlotSelection"),
             Self::EpochBoundsCheck { .. } => write!(f, "EpochBoundsCheck"),
             Self::SlotEvaluationReceived { .. } => write!(f, "SlotEvaluationReceived"),
+            Self::SlotEvaluationFailed { .. } => write!(f, "SlotEvaluationFailed"),
             Self::EpochEvaluationInterrupted { .. } => write!(f, "EpochEvaluationInterrupted"),
         }
     }
@@ -664,6 +723,7 @@ mod test {
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: None,
                 pending_evaluation: None,
+                slot_evaluation_failures: 0,
                 epoch_context: EpochContext::Current(DUMMY_STAKING_EPOCH_DATA.to_owned().into()),
             };
             Mutex::new(state)
@@ -687,6 +747,7 @@ mod test {
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: Some(0),
                 pending_evaluation: None,
+                slot_evaluation_failures: 0,
                 epoch_context: EpochContext::Current(DUMMY_STAKING_EPOCH_DATA.to_owned().into()),
             };
             Mutex::new(state)
@@ -710,6 +771,7 @@ mod test {
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: Some(1),
                 pending_evaluation: None,
+                slot_evaluation_failures: 0,
                 epoch_context: EpochContext::Current(DUMMY_STAKING_EPOCH_DATA.to_owned().into()),
             };
             Mutex::new(state)
@@ -733,6 +795,7 @@ mod test {
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: None,
                 pending_evaluation: None,
+                slot_evaluation_failures: 0,
                 epoch_context: EpochContext::Current(DUMMY_STAKING_EPOCH_DATA.to_owned().into()),
             };
             Mutex::new(state)
@@ -756,6 +819,7 @@ mod test {
                 genesis_timestamp: redux::Timestamp::global_now(),
                 last_evaluated_epoch: Some(2),
                 pending_evaluation: None,
+                slot_evaluation_failures: 0,
                 epoch_context: EpochContext::Current(DUMMY_STAKING_EPOCH_DATA.to_owned().into()),
             };
             Mutex::new(state)
@@ -933,6 +997,83 @@ mod test {
         })
     }
 
+    #[test]
+    fn test_failed_slot_evaluation_backoff() {
+        let start = redux::Timestamp::ZERO;
+        let secs = |secs| start + std::time::Duration::from_secs(secs);
+        let mut vrf_evaluator_state = BlockProducerVrfEvaluatorState::new(start);
+        assert_eq!(vrf_evaluator_state.slot_evaluation_retry_at(), None);
+
+        vrf_evaluator_state.set_slot_evaluation_failed(start, 5, "unreachable".to_owned());
+        assert_eq!(
+            vrf_evaluator_state.slot_evaluation_retry_at(),
+            Some(secs(1))
+        );
+        vrf_evaluator_state.set_slot_evaluation_failed(secs(1), 5, "unreachable".to_owned());
+        assert_eq!(
+            vrf_evaluator_state.slot_evaluation_retry_at(),
+            Some(secs(3))
+        );
+        for _ in 0..20 {
+            vrf_evaluator_state.set_slot_evaluation_failed(secs(3), 5, "unreachable".to_owned());
+        }
+        assert_eq!(
+            vrf_evaluator_state.slot_evaluation_retry_at(),
+            Some(secs(63))
+        );
+
+        // a failure isn't an evaluated slot
+        assert_eq!(vrf_evaluator_state.latest_evaluated_slot, 0);
+        assert!(!vrf_evaluator_state.is_slot_evaluated());
+
+        // the backoff starts over after a successful evaluation
+        vrf_evaluator_state.set_latest_evaluated_global_slot(&5);
+        vrf_evaluator_state.set_slot_evaluation_failed(secs(100), 6, "unreachable".to_owned());
+        assert_eq!(
+            vrf_evaluator_state.slot_evaluation_retry_at(),
+            Some(secs(101))
+        );
+    }
+
+    #[test]
+    fn test_insert_won_slot_keeps_best_per_global_slot() {
+        let dummy_ledger_hash =
+            LedgerHash::from_str("jxTAZfKKDxoX4vtt68pQCWooXoVLjnfBpusaMwewrcZxsL3uWp6").unwrap();
+        let won_slot = |producer: AccountSecretKey, seed: BigInt| {
+            let won_slot = VrfWonSlot {
+                producer: producer.public_key(),
+                winner_account: producer.public_key(),
+                vrf_output: Box::new(
+                    vrf::genesis_vrf(EpochSeed::from(MinaBaseEpochSeedStableV1(seed))).unwrap(),
+                ),
+                global_slot: 10,
+                account_index: AccountIndex(0),
+                value_with_threshold: None,
+            };
+            VrfWonSlotWithHash::new(won_slot, dummy_ledger_hash.clone())
+        };
+        let first = won_slot(AccountSecretKey::genesis_producer(), BigInt::zero());
+        let second = won_slot(AccountSecretKey::rand(), BigInt::one());
+        let best = if first.tie_breaker() > second.tie_breaker() {
+            first.won_slot.producer.clone()
+        } else {
+            second.won_slot.producer.clone()
+        };
+
+        for (a, b) in [
+            (first.clone(), second.clone()),
+            (second.clone(), first.clone()),
+        ] {
+            let mut vrf_evaluator_state =
+                BlockProducerVrfEvaluatorState::new(redux::Timestamp::global_now());
+            vrf_evaluator_state.insert_won_slot(a);
+            vrf_evaluator_state.insert_won_slot(b);
+
+            assert_eq!(vrf_evaluator_state.won_slots.len(), 1);
+            assert_eq!(vrf_evaluator_state.won_slots[&10].won_slot.producer, best);
+        }
+    }
+
     #[test]
     fn test_cleanup_old_won_slots() {
         // arbitrary, need it just to fill it with some slots
@@ -13,18 +13,26 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use ledger::AccountIndex;
-use mina_p2p_messages::v2::{EpochSeed, LedgerHash};
+use mina_p2p_messages::v2::{ConsensusVrfOutputTruncatedStableV1, EpochSeed, LedgerHash};
 use serde::{Deserialize, Serialize};
 use vrf::{VrfEvaluationOutput, VrfWonSlot};
 
 use crate::account::AccountPublicKey;
 
 pub type DelegatorTable = BTreeMap<AccountIndex, (AccountPublicKey, u64)>;
+/// Delegator tables of our producer keys.
+pub type DelegatorTables = BTreeMap<AccountPublicKey, DelegatorTable>;
+
+/// Tie-breaker of the fork choice rule between blocks for the same
+/// global slot. Won slot with the greater value wins.
+pub fn won_slot_tie_breaker(won_slot: &VrfWonSlot) -> Vec<u8> {
+    ConsensusVrfOutputTruncatedStableV1::from(&*won_slot.vrf_output).blake2b()
+}
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct VrfEvaluatorInput {
     pub epoch_seed: EpochSeed,
-    pub delegator_table: Arc<DelegatorTable>,
+    pub delegator_tables: Arc<DelegatorTables>,
     pub global_slot: u32,
     pub total_currency: u64,
     pub staking_ledger_hash: LedgerHash,
@@ -43,6 +51,10 @@ impl VrfWonSlotWithHash {
             staking_ledger_hash,
         }
     }
+
+    pub fn tie_breaker(&self) -> Vec<u8> {
+        won_slot_tie_breaker(&self.won_slot)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
@@ -77,14 +89,14 @@ impl VrfEvaluationOutputWithHash {
 impl VrfEvaluatorInput {
     pub fn new(
         epoch_seed: EpochSeed,
-        delegator_table: Arc<DelegatorTable>,
+        delegator_tables: Arc<DelegatorTables>,
         global_slot: u32,
         total_currency: u64,
         staking_ledger_hash: LedgerHash,
     ) -> Self {
         Self {
             epoch_seed,
-            delegator_table,
+            delegator_tables,
             global_slot,
             total_currency,
             staking_ledger_hash,
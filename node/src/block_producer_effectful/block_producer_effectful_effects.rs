@@ -44,8 +44,8 @@ pub fn block_producer_effects<S: crate::Service>(
                 Some((
                     won_slot,
                     pred_block,
-                    &config.pub_key,
-                    config.coinbase_receiver(),
+                    &won_slot.producer,
+                    config.coinbase_receiver_of(&won_slot.producer)?,
                 ))
             }) else {
                 return;
@@ -7,7 +7,7 @@ use mina_p2p_messages::v2::{
     MinaBaseStagedLedgerHashStableV1, ProverExtendBlockchainInputStableV2,
     StagedLedgerDiffDiffStableV2, StateHash,
 };
-use openmina_node_account::AccountSecretKey;
+use openmina_node_account::{AccountPublicKey, AccountSecretKey};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -25,5 +25,12 @@ pub struct StagedLedgerDiffCreateOutput {
 pub trait BlockProducerService {
     fn provers(&self) -> BlockProver;
     fn prove(&mut self, block_hash: StateHash, input: Box<ProverExtendBlockchainInputStableV2>);
-    fn with_producer_keypair<T>(&self, f: impl FnOnce(&AccountSecretKey) -> T) -> Option<T>;
+    /// Calls `f` with the secret key of our producer key `pub_key`.
+    ///
+    /// Returns `None` if the key is held by a remote signer.
+    fn with_producer_keypair<T>(
+        &self,
+        pub_key: &AccountPublicKey,
+        f: impl FnOnce(&AccountSecretKey) -> T,
+    ) -> Option<T>;
 }
@@ -6,7 +6,7 @@ use openmina_core::consensus::ConsensusConstants;
 use serde::{Deserialize, Serialize};
 
 use crate::account::AccountPublicKey;
-pub use crate::block_producer::BlockProducerConfig;
+pub use crate::block_producer::{BlockProducerConfig, BlockProducerKeyConfig};
 pub use crate::ledger::LedgerConfig;
 pub use crate::p2p::P2pConfig;
 pub use crate::snark::SnarkConfig;
@@ -45,6 +45,13 @@ pub struct SnarkerConfig {
     pub fee: CurrencyFeeStableV1,
     pub strategy: SnarkerStrategy,
     pub auto_commit: bool,
+    /// Number of local snark workers, proving jobs in parallel.
+    #[serde(default = "default_snarker_workers")]
+    pub workers: usize,
+}
+
+fn default_snarker_workers() -> usize {
+    1
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
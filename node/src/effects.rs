@@ -1,6 +1,7 @@
 use openmina_core::log::system_time;
 use rand::prelude::*;
 
+use crate::block_producer::vrf_evaluator::BlockProducerVrfEvaluatorAction;
 use crate::block_producer::BlockProducerAction;
 use crate::block_producer_effectful::block_producer_effects;
 use crate::event_source::event_source_effects;
@@ -9,13 +10,14 @@ use crate::ledger::read::LedgerReadAction;
 use crate::ledger_effectful::ledger_effectful_effects;
 use crate::logger::logger_effects;
 use crate::p2p::node_p2p_effects;
+use crate::rpc::{RpcAction, RpcSyncStatus, RpcTransitionFrontierEvent};
 use crate::rpc_effectful::rpc_effects;
 use crate::snark::snark_effects;
 use crate::snark_pool::candidate::SnarkPoolCandidateAction;
 use crate::snark_pool::{snark_pool_effects, SnarkPoolAction};
 use crate::transaction_pool::candidate::TransactionPoolCandidateAction;
 use crate::transition_frontier::genesis::TransitionFrontierGenesisAction;
-use crate::transition_frontier::transition_frontier_effects;
+use crate::transition_frontier::{transition_frontier_effects, TransitionFrontierAction};
 use crate::{
     p2p_ready, Action, ActionWithMeta, ExternalSnarkWorkerAction, Service, Store,
     TransactionPoolAction,
@@ -39,7 +41,11 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
         Action::CheckTimeouts(_) => {
             // TODO(binier): create init action and dispatch these there.
             store.dispatch(TransitionFrontierGenesisAction::LedgerLoadInit);
-            store.dispatch(ExternalSnarkWorkerAction::Start);
+            store.dispatch(TransitionFrontierAction::RestoreInit);
+            let snark_worker_ids = store.state().external_snark_worker.ids();
+            for worker_id in snark_worker_ids.clone() {
+                store.dispatch(ExternalSnarkWorkerAction::Start { worker_id });
+            }
 
             store.dispatch(TransitionFrontierGenesisAction::ProveInit);
 
@@ -59,12 +65,18 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
             store.dispatch(SnarkPoolCandidateAction::WorkFetchAll);
             store.dispatch(SnarkPoolCandidateAction::WorkVerifyNext);
 
-            store.dispatch(ExternalSnarkWorkerAction::StartTimeout { now: meta.time() });
-            store.dispatch(ExternalSnarkWorkerAction::WorkTimeout { now: meta.time() });
+            for worker_id in snark_worker_ids {
+                let now = meta.time();
+                store.dispatch(ExternalSnarkWorkerAction::StartTimeout { worker_id, now });
+                store.dispatch(ExternalSnarkWorkerAction::WorkTimeout { worker_id, now });
+            }
 
+            store.dispatch(BlockProducerVrfEvaluatorAction::RetrySlotEvaluation);
             store.dispatch(BlockProducerAction::WonSlotProduceInit);
             store.dispatch(BlockProducerAction::BlockInject);
             store.dispatch(LedgerReadAction::FindTodos);
+            // Picks up peers (dis)connecting.
+            notify_sync_status(store);
         }
         Action::EventSource(action) => {
             event_source_effects(store, meta.with_action(action));
@@ -77,6 +89,7 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
         }
         Action::TransitionFrontier(action) => {
             transition_frontier_effects(store, meta.with_action(action));
+            notify_sync_status(store);
         }
         Action::P2pEffectful(action) => {
             node_p2p_effects(store, meta.with_action(action));
@@ -110,6 +123,16 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
     }
 }
 
+/// Notifies the transition frontier subscribers, when the sync status changed.
+fn notify_sync_status<S: Service>(store: &mut Store<S>) {
+    let state = store.state();
+    let has_ready_peers = state.p2p.ready_peers_iter().next().is_some();
+    let sync_status = RpcSyncStatus::new(&state.transition_frontier.sync, has_ready_peers);
+    store.dispatch(RpcAction::TransitionFrontierSubscribersNotify {
+        event: RpcTransitionFrontierEvent::SyncStatus(sync_status),
+    });
+}
+
 fn p2p_request_best_tip_if_needed<S: Service>(store: &mut Store<S>) {
     // TODO(binier): refactor
     let state = store.state();
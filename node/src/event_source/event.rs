@@ -7,6 +7,7 @@ pub use crate::p2p::{P2pConnectionEvent, P2pEvent};
 pub use crate::rpc::{RpcId, RpcRequest};
 pub use crate::snark::SnarkEvent;
 
+use crate::external_snark_worker::SnarkWorkerId;
 use crate::transition_frontier::genesis::GenesisConfigLoaded;
 
 #[derive(derive_more::From, Serialize, Deserialize, Debug, Clone)]
@@ -15,7 +16,7 @@ pub enum Event {
     Ledger(LedgerEvent),
     Snark(SnarkEvent),
     Rpc(RpcId, Box<RpcRequest>),
-    ExternalSnarkWorker(ExternalSnarkWorkerEvent),
+    ExternalSnarkWorker(SnarkWorkerId, ExternalSnarkWorkerEvent),
     BlockProducerEvent(BlockProducerEvent),
 
     GenesisLoad(Result<GenesisConfigLoaded, String>),
@@ -36,6 +37,7 @@ impl std::fmt::Display for Event {
                     RpcRequest::ActionStatsGet(query) => write!(f, "ActionStatsGet, {query:?}"),
                     RpcRequest::SyncStatsGet(query) => write!(f, "SyncStatsGet, {query:?}"),
                     RpcRequest::BlockProducerStatsGet => write!(f, "BlockProducerStatsGet"),
+                    RpcRequest::BlockProducerScheduleGet => write!(f, "BlockProducerScheduleGet"),
                     RpcRequest::PeersGet => write!(f, "PeersGet"),
                     RpcRequest::MessageProgressGet => write!(f, "MessageProgressGet"),
                     RpcRequest::P2pConnectionOutgoing(opts) => {
@@ -44,6 +46,10 @@ impl std::fmt::Display for Event {
                     RpcRequest::P2pConnectionIncoming(opts) => {
                         write!(f, "P2pConnectionIncoming, {}", opts.peer_id)
                     }
+                    RpcRequest::P2pBansGet => write!(f, "P2pBansGet"),
+                    RpcRequest::P2pBan(query) => write!(f, "P2pBan, {}", query.peer_id),
+                    RpcRequest::P2pUnban(peer_id) => write!(f, "P2pUnban, {peer_id}"),
+                    RpcRequest::P2pBandwidthGet => write!(f, "P2pBandwidthGet"),
                     RpcRequest::ScanStateSummaryGet(query) => {
                         write!(f, "ScanStateSummaryGet, {query:?}")
                     }
@@ -83,10 +89,18 @@ impl std::fmt::Display for Event {
                     RpcRequest::LedgerAccountDelegatorsGet(..) => {
                         write!(f, "LedgerAccountDelegatorsGet")
                     }
+                    RpcRequest::ArchiveStatusGet => write!(f, "ArchiveStatusGet"),
+                    RpcRequest::ArchiveBackfill(..) => write!(f, "ArchiveBackfill"),
+                    RpcRequest::TransitionFrontierSubscribe => {
+                        write!(f, "TransitionFrontierSubscribe")
+                    }
+                    RpcRequest::VrfEvaluate(public_key, ..) => {
+                        write!(f, "VrfEvaluate, {public_key}")
+                    }
                 }
             }
-            Self::ExternalSnarkWorker(event) => {
-                write!(f, "ExternalSnarkWorker, ")?;
+            Self::ExternalSnarkWorker(worker_id, event) => {
+                write!(f, "ExternalSnarkWorker, {worker_id}, ")?;
 
                 match event {
                     ExternalSnarkWorkerEvent::Started => write!(f, "Started"),
@@ -19,9 +19,10 @@ use crate::p2p::connection::incoming::P2pConnectionIncomingAction;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingAction;
 use crate::p2p::connection::{P2pConnectionErrorResponse, P2pConnectionResponse};
 use crate::p2p::disconnection::{P2pDisconnectionAction, P2pDisconnectionReason};
-use crate::p2p::P2pChannelEvent;
+use crate::p2p::nat::P2pNatAction;
 #[cfg(feature = "p2p-libp2p")]
-use crate::p2p::{MioEvent, P2pNetworkSchedulerAction};
+use crate::p2p::{MioEvent, P2pNetworkQuicAction, P2pNetworkSchedulerAction};
+use crate::p2p::{P2pChannelEvent, P2pNatEvent, P2pNetworkAutonatAction};
 use crate::rpc::{RpcAction, RpcRequest};
 use crate::snark::block_verify::SnarkBlockVerifyAction;
 use crate::snark::work_verify::SnarkWorkVerifyAction;
@@ -117,6 +118,49 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                     MioEvent::ConnectionDidCloseOnDemand(addr) => {
                         store.dispatch(P2pNetworkSchedulerAction::Prune { addr });
                     }
+                    MioEvent::QuicConnectionDidAuthenticate(addr, peer_id) => {
+                        store.dispatch(P2pNetworkQuicAction::Authenticated { addr, peer_id });
+                    }
+                    MioEvent::QuicStreamDidOpen { addr, stream_id } => {
+                        store.dispatch(P2pNetworkQuicAction::IncomingStream { addr, stream_id });
+                    }
+                    MioEvent::QuicStreamDataDidReceive {
+                        addr,
+                        stream_id,
+                        data,
+                        fin,
+                    } => {
+                        store.dispatch(P2pNetworkQuicAction::IncomingData {
+                            addr,
+                            stream_id,
+                            data,
+                            fin,
+                        });
+                    }
+                    MioEvent::QuicStreamDidReset { addr, stream_id } => {
+                        store.dispatch(P2pNetworkQuicAction::StreamReset { addr, stream_id });
+                    }
+                },
+                P2pEvent::Nat(e) => match e {
+                    P2pNatEvent::PortMapped(Ok(mapping)) => {
+                        store.dispatch(P2pNatAction::PortMappingSuccess { mapping });
+                    }
+                    P2pNatEvent::PortMapped(Err(error)) => {
+                        store.dispatch(P2pNatAction::PortMappingError { error });
+                    }
+                    P2pNatEvent::DialBack {
+                        addr,
+                        peer_id,
+                        stream_id,
+                        result,
+                    } => {
+                        store.dispatch(P2pNetworkAutonatAction::DialBackResult {
+                            addr,
+                            peer_id,
+                            stream_id,
+                            result,
+                        });
+                    }
                 },
                 P2pEvent::Connection(e) => match e {
                     P2pConnectionEvent::OfferSdpReady(peer_id, res) => match res {
@@ -319,6 +363,9 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::BlockProducerStatsGet => {
                     store.dispatch(RpcAction::BlockProducerStatsGet { rpc_id });
                 }
+                RpcRequest::BlockProducerScheduleGet => {
+                    store.dispatch(RpcAction::BlockProducerScheduleGet { rpc_id });
+                }
                 RpcRequest::PeersGet => {
                     store.dispatch(RpcAction::PeersGet { rpc_id });
                 }
@@ -331,6 +378,18 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::P2pConnectionIncoming(opts) => {
                     store.dispatch(RpcAction::P2pConnectionIncomingInit { rpc_id, opts });
                 }
+                RpcRequest::P2pBansGet => {
+                    store.dispatch(RpcAction::P2pBansGet { rpc_id });
+                }
+                RpcRequest::P2pBan(query) => {
+                    store.dispatch(RpcAction::P2pBan { rpc_id, query });
+                }
+                RpcRequest::P2pUnban(peer_id) => {
+                    store.dispatch(RpcAction::P2pUnban { rpc_id, peer_id });
+                }
+                RpcRequest::P2pBandwidthGet => {
+                    store.dispatch(RpcAction::P2pBandwidthGet { rpc_id });
+                }
                 RpcRequest::ScanStateSummaryGet(query) => {
                     store.dispatch(RpcAction::ScanStateSummaryGetInit { rpc_id, query });
                 }
@@ -409,6 +468,22 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::GenesisBlockGet => {
                     store.dispatch(RpcAction::GenesisBlock { rpc_id });
                 }
+                RpcRequest::ArchiveStatusGet => {
+                    store.dispatch(RpcAction::ArchiveStatusGet { rpc_id });
+                }
+                RpcRequest::ArchiveBackfill(query) => {
+                    store.dispatch(RpcAction::ArchiveBackfillInit { rpc_id, query });
+                }
+                RpcRequest::TransitionFrontierSubscribe => {
+                    store.dispatch(RpcAction::TransitionFrontierSubscribe { rpc_id });
+                }
+                RpcRequest::VrfEvaluate(public_key, message) => {
+                    store.dispatch(RpcAction::VrfEvaluate {
+                        rpc_id,
+                        public_key,
+                        message,
+                    });
+                }
                 RpcRequest::LedgerStatusGet(ledger_hash) => {
                     store.dispatch(RpcAction::LedgerStatusGetInit {
                         rpc_id,
@@ -423,24 +498,25 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                     });
                 }
             },
-            Event::ExternalSnarkWorker(e) => match e {
+            Event::ExternalSnarkWorker(worker_id, e) => match e {
                 ExternalSnarkWorkerEvent::Started => {
-                    store.dispatch(ExternalSnarkWorkerAction::Started);
+                    store.dispatch(ExternalSnarkWorkerAction::Started { worker_id });
                 }
                 ExternalSnarkWorkerEvent::Killed => {
-                    store.dispatch(ExternalSnarkWorkerAction::Killed);
+                    store.dispatch(ExternalSnarkWorkerAction::Killed { worker_id });
                 }
                 ExternalSnarkWorkerEvent::WorkResult(result) => {
-                    store.dispatch(ExternalSnarkWorkerAction::WorkResult { result });
+                    store.dispatch(ExternalSnarkWorkerAction::WorkResult { worker_id, result });
                 }
                 ExternalSnarkWorkerEvent::WorkError(error) => {
-                    store.dispatch(ExternalSnarkWorkerAction::WorkError { error });
+                    store.dispatch(ExternalSnarkWorkerAction::WorkError { worker_id, error });
                 }
                 ExternalSnarkWorkerEvent::WorkCancelled => {
-                    store.dispatch(ExternalSnarkWorkerAction::WorkCancelled);
+                    store.dispatch(ExternalSnarkWorkerAction::WorkCancelled { worker_id });
                 }
                 ExternalSnarkWorkerEvent::Error(error) => {
                     store.dispatch(ExternalSnarkWorkerAction::Error {
+                        worker_id,
                         error,
                         permanent: false,
                     });
@@ -456,11 +532,29 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                             },
                         );
                     }
+                    BlockProducerVrfEvaluatorEvent::EvaluationFailed { global_slot, error } => {
+                        store.dispatch(
+                            BlockProducerVrfEvaluatorAction::ProcessSlotEvaluationError {
+                                global_slot,
+                                error,
+                            },
+                        );
+                    }
                 },
                 BlockProducerEvent::BlockProve(block_hash, res) => match res {
-                    Err(err) => todo!(
-                        "error while trying to produce block proof for block {block_hash} - {err}"
-                    ),
+                    Err(error) => {
+                        if store
+                            .state()
+                            .transition_frontier
+                            .genesis
+                            .prove_pending_block_hash()
+                            .is_some_and(|hash| hash == block_hash)
+                        {
+                            todo!("error while trying to produce genesis block proof for block {block_hash} - {error}");
+                        } else {
+                            store.dispatch(BlockProducerAction::BlockProveError { error });
+                        }
+                    }
                     Ok(proof) => {
                         if store
                             .state()
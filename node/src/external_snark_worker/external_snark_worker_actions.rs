@@ -8,40 +8,60 @@ use crate::{snark_pool::JobSummary, State};
 
 use super::{
     ExternalSnarkWorkerError, ExternalSnarkWorkerState, ExternalSnarkWorkerWorkError,
-    SnarkWorkResult,
+    SnarkWorkResult, SnarkWorkerId,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, ActionEvent)]
-#[action_event(fields(display(job_id), display(error)))]
+#[action_event(fields(worker_id, display(job_id), display(error)))]
 pub enum ExternalSnarkWorkerAction {
-    Start,
-    Started,
+    Start {
+        worker_id: SnarkWorkerId,
+    },
+    Started {
+        worker_id: SnarkWorkerId,
+    },
     StartTimeout {
+        worker_id: SnarkWorkerId,
         now: Timestamp,
     },
-    Kill,
-    Killed,
+    Kill {
+        worker_id: SnarkWorkerId,
+    },
+    Killed {
+        worker_id: SnarkWorkerId,
+    },
 
     SubmitWork {
+        worker_id: SnarkWorkerId,
         job_id: SnarkJobId,
         summary: JobSummary,
     },
     WorkResult {
+        worker_id: SnarkWorkerId,
         result: SnarkWorkResult,
     },
     WorkError {
+        worker_id: SnarkWorkerId,
         error: ExternalSnarkWorkerWorkError,
     },
     WorkTimeout {
+        worker_id: SnarkWorkerId,
         now: Timestamp,
     },
 
-    CancelWork,
-    WorkCancelled,
+    CancelWork {
+        worker_id: SnarkWorkerId,
+    },
+    WorkCancelled {
+        worker_id: SnarkWorkerId,
+    },
 
-    PruneWork,
+    PruneWork {
+        worker_id: SnarkWorkerId,
+    },
 
     Error {
+        worker_id: SnarkWorkerId,
         error: ExternalSnarkWorkerError,
         permanent: bool,
     },
@@ -50,81 +70,87 @@ pub enum ExternalSnarkWorkerAction {
 pub type ExternalSnarkWorkerActionWithMetaRef<'a> =
     redux::ActionWithMeta<&'a ExternalSnarkWorkerAction>;
 
+impl ExternalSnarkWorkerAction {
+    pub fn worker_id(&self) -> SnarkWorkerId {
+        match self {
+            Self::Start { worker_id }
+            | Self::Started { worker_id }
+            | Self::StartTimeout { worker_id, .. }
+            | Self::Kill { worker_id }
+            | Self::Killed { worker_id }
+            | Self::SubmitWork { worker_id, .. }
+            | Self::WorkResult { worker_id, .. }
+            | Self::WorkError { worker_id, .. }
+            | Self::WorkTimeout { worker_id, .. }
+            | Self::CancelWork { worker_id }
+            | Self::WorkCancelled { worker_id }
+            | Self::PruneWork { worker_id }
+            | Self::Error { worker_id, .. } => *worker_id,
+        }
+    }
+}
+
 impl EnablingCondition<State> for ExternalSnarkWorkerAction {
     fn is_enabled(&self, state: &State, _time: redux::Timestamp) -> bool {
+        let Some(worker) = state.external_snark_worker.get(self.worker_id()) else {
+            return false;
+        };
         match self {
-            ExternalSnarkWorkerAction::Start => {
+            ExternalSnarkWorkerAction::Start { .. } => {
                 state.config.snarker.is_some()
-                    && matches!(
-                        state.external_snark_worker.0.state,
-                        ExternalSnarkWorkerState::None
-                    )
+                    && matches!(worker.state, ExternalSnarkWorkerState::None)
             }
-            ExternalSnarkWorkerAction::Started => {
-                matches!(
-                    state.external_snark_worker.0.state,
-                    ExternalSnarkWorkerState::Starting
-                )
+            ExternalSnarkWorkerAction::Started { .. } => {
+                matches!(worker.state, ExternalSnarkWorkerState::Starting)
             }
-            ExternalSnarkWorkerAction::StartTimeout { now } => {
+            ExternalSnarkWorkerAction::StartTimeout { now, .. } => {
                 const TIMEOUT: Duration = Duration::from_secs(120);
-                matches!(
-                    state.external_snark_worker.0.state,
-                    ExternalSnarkWorkerState::Starting
-                ) && now
-                    .checked_sub(state.external_snark_worker.0.timestamp)
-                    .is_some_and(|d| d > TIMEOUT)
+                matches!(worker.state, ExternalSnarkWorkerState::Starting)
+                    && now
+                        .checked_sub(worker.timestamp)
+                        .is_some_and(|d| d > TIMEOUT)
             }
-            ExternalSnarkWorkerAction::Kill => !matches!(
-                state.external_snark_worker.0.state,
+            ExternalSnarkWorkerAction::Kill { .. } => !matches!(
+                worker.state,
                 ExternalSnarkWorkerState::Error(_, false)
                     | ExternalSnarkWorkerState::None
                     | ExternalSnarkWorkerState::Killing
             ),
-            ExternalSnarkWorkerAction::Killed => {
-                matches!(
-                    state.external_snark_worker.0.state,
-                    ExternalSnarkWorkerState::Killing
-                )
+            ExternalSnarkWorkerAction::Killed { .. } => {
+                matches!(worker.state, ExternalSnarkWorkerState::Killing)
+            }
+            ExternalSnarkWorkerAction::SubmitWork { job_id, .. } => {
+                // Each job is proved by one worker only.
+                worker.is_idle()
+                    && state
+                        .external_snark_worker
+                        .working_on(job_id)
+                        .next()
+                        .is_none()
             }
-            ExternalSnarkWorkerAction::SubmitWork { .. } => state.external_snark_worker.is_idle(),
             ExternalSnarkWorkerAction::WorkResult { .. } => {
-                matches!(
-                    state.external_snark_worker.0.state,
-                    ExternalSnarkWorkerState::Working(..)
-                )
+                matches!(worker.state, ExternalSnarkWorkerState::Working(..))
             }
             ExternalSnarkWorkerAction::WorkError { .. } => {
-                matches!(
-                    state.external_snark_worker.0.state,
-                    ExternalSnarkWorkerState::Working(..)
-                )
+                matches!(worker.state, ExternalSnarkWorkerState::Working(..))
             }
-            ExternalSnarkWorkerAction::WorkTimeout { now } => {
-                if let ExternalSnarkWorkerState::Working(_, summary) =
-                    &state.external_snark_worker.0.state
-                {
-                    now.checked_sub(state.external_snark_worker.0.timestamp)
+            ExternalSnarkWorkerAction::WorkTimeout { now, .. } => {
+                if let ExternalSnarkWorkerState::Working(_, summary) = &worker.state {
+                    now.checked_sub(worker.timestamp)
                         .is_some_and(|d| d > summary.estimated_duration())
                 } else {
                     false
                 }
             }
-            ExternalSnarkWorkerAction::CancelWork => {
-                matches!(
-                    state.external_snark_worker.0.state,
-                    ExternalSnarkWorkerState::Working(..)
-                )
+            ExternalSnarkWorkerAction::CancelWork { .. } => {
+                matches!(worker.state, ExternalSnarkWorkerState::Working(..))
             }
-            ExternalSnarkWorkerAction::WorkCancelled => {
-                matches!(
-                    state.external_snark_worker.0.state,
-                    ExternalSnarkWorkerState::Cancelling(_)
-                )
+            ExternalSnarkWorkerAction::WorkCancelled { .. } => {
+                matches!(worker.state, ExternalSnarkWorkerState::Cancelling(_))
             }
-            ExternalSnarkWorkerAction::PruneWork => {
+            ExternalSnarkWorkerAction::PruneWork { .. } => {
                 matches!(
-                    state.external_snark_worker.0.state,
+                    worker.state,
                     ExternalSnarkWorkerState::WorkReady(..)
                         | ExternalSnarkWorkerState::WorkError(..)
                         | ExternalSnarkWorkerState::Cancelled(..)
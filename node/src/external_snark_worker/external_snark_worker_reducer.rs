@@ -1,4 +1,4 @@
-use openmina_core::snark::Snark;
+use openmina_core::snark::{Snark, SnarkJobCommitment};
 use redux::Timestamp;
 
 use super::{
@@ -14,24 +14,19 @@ use crate::{
 
 impl ExternalSnarkWorkers {
     pub fn reducer(
-        state_context: Substate<ExternalSnarkWorkers>,
+        mut state_context: Substate<ExternalSnarkWorkers>,
         action: ExternalSnarkWorkerActionWithMetaRef<'_>,
     ) {
-        ExternalSnarkWorker::reducer(Substate::from_compatible_substate(state_context), action);
-    }
-}
-
-impl ExternalSnarkWorker {
-    pub fn reducer(
-        mut state_context: Substate<ExternalSnarkWorker>,
-        action: ExternalSnarkWorkerActionWithMetaRef<'_>,
-    ) {
-        let Ok(worker_state) = state_context.get_substate_mut() else {
+        let Ok(workers) = state_context.get_substate_mut() else {
             return;
         };
         let (action, meta) = action.split();
+        let worker_id = action.worker_id();
+        let Some(worker_state) = workers.0.get_mut(worker_id) else {
+            return;
+        };
         match action {
-            ExternalSnarkWorkerAction::Start => {
+            ExternalSnarkWorkerAction::Start { .. } => {
                 worker_state.state = ExternalSnarkWorkerState::Starting;
                 worker_state.update_timestamp(meta.time());
 
@@ -43,9 +38,13 @@ impl ExternalSnarkWorker {
                 let public_key = config.public_key.clone().into();
                 let fee = config.fee.clone();
 
-                dispatcher.push(ExternalSnarkWorkerEffectfulAction::Start { public_key, fee });
+                dispatcher.push(ExternalSnarkWorkerEffectfulAction::Start {
+                    worker_id,
+                    public_key,
+                    fee,
+                });
             }
-            ExternalSnarkWorkerAction::Started => {
+            ExternalSnarkWorkerAction::Started { .. } => {
                 worker_state.state = ExternalSnarkWorkerState::Idle;
                 worker_state.update_timestamp(meta.time());
 
@@ -55,29 +54,34 @@ impl ExternalSnarkWorker {
             ExternalSnarkWorkerAction::StartTimeout { .. } => {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(ExternalSnarkWorkerAction::Error {
+                    worker_id,
                     error: super::ExternalSnarkWorkerError::StartTimeout,
                     permanent: true,
                 });
             }
-            ExternalSnarkWorkerAction::Kill => {
+            ExternalSnarkWorkerAction::Kill { .. } => {
                 worker_state.state = ExternalSnarkWorkerState::Killing;
                 worker_state.update_timestamp(meta.time());
 
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(ExternalSnarkWorkerEffectfulAction::Kill);
+                dispatcher.push(ExternalSnarkWorkerEffectfulAction::Kill { worker_id });
             }
-            ExternalSnarkWorkerAction::Killed => {
+            ExternalSnarkWorkerAction::Killed { .. } => {
                 worker_state.state = ExternalSnarkWorkerState::None;
                 worker_state.update_timestamp(meta.time());
             }
-            ExternalSnarkWorkerAction::Error { error, permanent } => {
+            ExternalSnarkWorkerAction::Error {
+                error, permanent, ..
+            } => {
                 worker_state.state = ExternalSnarkWorkerState::Error(error.clone(), *permanent);
                 worker_state.update_timestamp(meta.time());
 
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(ExternalSnarkWorkerAction::Kill);
+                dispatcher.push(ExternalSnarkWorkerAction::Kill { worker_id });
             }
-            ExternalSnarkWorkerAction::SubmitWork { job_id, summary } => {
+            ExternalSnarkWorkerAction::SubmitWork {
+                job_id, summary, ..
+            } => {
                 worker_state.state =
                     ExternalSnarkWorkerState::Working(job_id.clone(), summary.clone());
                 worker_state.update_timestamp(meta.time());
@@ -93,17 +97,33 @@ impl ExternalSnarkWorker {
                 ) {
                     Ok(spec) => {
                         dispatcher.push(ExternalSnarkWorkerEffectfulAction::SubmitWork {
+                            worker_id,
                             spec: Box::new(spec),
                         });
+
+                        let Some(config) = state.config.snarker.as_ref() else {
+                            return;
+                        };
+                        let timestamp_ms = meta.time_as_nanos() / 1_000_000;
+                        dispatcher.push(SnarkPoolAction::CommitmentAdd {
+                            commitment: SnarkJobCommitment::new(
+                                timestamp_ms,
+                                job_id.clone(),
+                                config.fee.clone(),
+                                config.public_key.clone().into(),
+                            ),
+                            sender: state.p2p.my_id(),
+                        });
                     }
                     Err(err) => {
                         dispatcher.push(ExternalSnarkWorkerAction::WorkError {
+                            worker_id,
                             error: ExternalSnarkWorkerWorkError::WorkSpecError(err),
                         });
                     }
                 }
             }
-            ExternalSnarkWorkerAction::WorkResult { result } => {
+            ExternalSnarkWorkerAction::WorkResult { result, .. } => {
                 let ExternalSnarkWorkerState::Working(job_id, _) = &worker_state.state else {
                     return;
                 };
@@ -130,9 +150,9 @@ impl ExternalSnarkWorker {
                     sender,
                     is_sender_local: true,
                 });
-                dispatcher.push(ExternalSnarkWorkerAction::PruneWork);
+                dispatcher.push(ExternalSnarkWorkerAction::PruneWork { worker_id });
             }
-            ExternalSnarkWorkerAction::WorkError { error } => {
+            ExternalSnarkWorkerAction::WorkError { error, .. } => {
                 let ExternalSnarkWorkerState::Working(job_id, _) = &worker_state.state else {
                     return;
                 };
@@ -141,13 +161,13 @@ impl ExternalSnarkWorker {
                 worker_state.update_timestamp(meta.time());
 
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(ExternalSnarkWorkerAction::PruneWork);
+                dispatcher.push(ExternalSnarkWorkerAction::PruneWork { worker_id });
             }
             ExternalSnarkWorkerAction::WorkTimeout { .. } => {
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(ExternalSnarkWorkerAction::CancelWork);
+                dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
             }
-            ExternalSnarkWorkerAction::CancelWork => {
+            ExternalSnarkWorkerAction::CancelWork { .. } => {
                 let ExternalSnarkWorkerState::Working(job_id, _) = &worker_state.state else {
                     return;
                 };
@@ -155,9 +175,9 @@ impl ExternalSnarkWorker {
                 worker_state.update_timestamp(meta.time());
 
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(ExternalSnarkWorkerEffectfulAction::CancelWork);
+                dispatcher.push(ExternalSnarkWorkerEffectfulAction::CancelWork { worker_id });
             }
-            ExternalSnarkWorkerAction::WorkCancelled => {
+            ExternalSnarkWorkerAction::WorkCancelled { .. } => {
                 let ExternalSnarkWorkerState::Cancelling(job_id) = &worker_state.state else {
                     return;
                 };
@@ -165,9 +185,9 @@ impl ExternalSnarkWorker {
                 worker_state.update_timestamp(meta.time());
 
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(ExternalSnarkWorkerAction::PruneWork);
+                dispatcher.push(ExternalSnarkWorkerAction::PruneWork { worker_id });
             }
-            ExternalSnarkWorkerAction::PruneWork => {
+            ExternalSnarkWorkerAction::PruneWork { .. } => {
                 worker_state.state = ExternalSnarkWorkerState::Idle;
                 worker_state.update_timestamp(meta.time());
 
@@ -176,7 +196,9 @@ impl ExternalSnarkWorker {
             }
         }
     }
+}
 
+impl ExternalSnarkWorker {
     fn update_timestamp(&mut self, time: Timestamp) {
         self.timestamp = time;
     }
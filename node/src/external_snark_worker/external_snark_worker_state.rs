@@ -3,10 +3,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::snark_pool::JobSummary;
 
-use super::{ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkId, SnarkWorkResult};
+use super::{
+    ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkId, SnarkWorkResult,
+    SnarkWorkerId,
+};
 
+/// Local snark workers, each proving one job at a time.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExternalSnarkWorkers(pub(crate) ExternalSnarkWorker);
+pub struct ExternalSnarkWorkers(pub(crate) Vec<ExternalSnarkWorker>);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExternalSnarkWorker {
@@ -32,33 +36,131 @@ pub enum ExternalSnarkWorkerState {
 }
 
 impl ExternalSnarkWorkers {
-    pub fn new(now: Timestamp) -> Self {
-        ExternalSnarkWorkers(ExternalSnarkWorker {
+    pub fn new(now: Timestamp, count: usize) -> Self {
+        let worker = ExternalSnarkWorker {
             state: ExternalSnarkWorkerState::None,
             timestamp: now,
-        })
+        };
+        ExternalSnarkWorkers(vec![worker; count.max(1)])
     }
 
-    pub fn is_idle(&self) -> bool {
-        matches!(self.0.state, ExternalSnarkWorkerState::Idle)
+    pub fn get(&self, worker_id: SnarkWorkerId) -> Option<&ExternalSnarkWorker> {
+        self.0.get(worker_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SnarkWorkerId, &ExternalSnarkWorker)> {
+        self.0.iter().enumerate()
     }
 
-    pub fn has_idle(&self) -> bool {
-        self.available() > 0
+    pub fn ids(&self) -> std::ops::Range<SnarkWorkerId> {
+        0..self.0.len()
     }
 
     pub fn available(&self) -> usize {
-        if matches!(self.0.state, ExternalSnarkWorkerState::Idle) {
-            1
-        } else {
-            0
-        }
+        self.0.iter().filter(|worker| worker.is_idle()).count()
+    }
+
+    pub fn idle_worker(&self) -> Option<SnarkWorkerId> {
+        self.idle_workers().next()
+    }
+
+    pub fn idle_workers(&self) -> impl '_ + Iterator<Item = SnarkWorkerId> {
+        self.iter()
+            .filter(|(_, worker)| worker.is_idle())
+            .map(|(worker_id, _)| worker_id)
+    }
+
+    /// Pairs each of `job_ids`, which isn't being proved yet, with a
+    /// different idle worker.
+    pub fn assign<'a>(
+        &'a self,
+        job_ids: impl 'a + IntoIterator<Item = SnarkWorkId>,
+    ) -> impl 'a + Iterator<Item = (SnarkWorkerId, SnarkWorkId)> {
+        let job_ids = job_ids
+            .into_iter()
+            .filter(|job_id| self.working_on(job_id).next().is_none());
+        self.idle_workers().zip(job_ids)
+    }
+
+    pub fn working_job_ids(&self) -> impl Iterator<Item = (SnarkWorkerId, &SnarkWorkId)> {
+        self.iter()
+            .filter_map(|(worker_id, worker)| Some((worker_id, worker.working_job_id()?)))
+    }
+
+    /// Workers proving `job_id`.
+    pub fn working_on<'a>(
+        &'a self,
+        job_id: &'a SnarkWorkId,
+    ) -> impl 'a + Iterator<Item = SnarkWorkerId> {
+        self.working_job_ids()
+            .filter(move |(_, id)| *id == job_id)
+            .map(|(worker_id, _)| worker_id)
+    }
+}
+
+impl ExternalSnarkWorker {
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, ExternalSnarkWorkerState::Idle)
     }
 
     pub fn working_job_id(&self) -> Option<&SnarkWorkId> {
-        match &self.0.state {
+        match &self.state {
             ExternalSnarkWorkerState::Working(job_id, _) => Some(job_id),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn job_id(n: usize) -> SnarkWorkId {
+        const LEDGER_HASHES: [&str; 2] = [
+            "jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N",
+            "jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc",
+        ];
+        // Distinct ids out of two hashes.
+        let hash = |bit: usize| LEDGER_HASHES[(n >> bit) & 1];
+        let s = format!("{}_{}-{}_{}", hash(0), hash(1), hash(2), hash(3));
+        SnarkWorkId::from_str(&s).unwrap()
+    }
+
+    fn workers(count: usize) -> ExternalSnarkWorkers {
+        let mut workers = ExternalSnarkWorkers::new(Timestamp::ZERO, count);
+        for worker in &mut workers.0 {
+            worker.state = ExternalSnarkWorkerState::Idle;
+        }
+        workers
+    }
+
+    #[test]
+    fn assign_jobs_to_distinct_workers() {
+        let mut workers = workers(3);
+        let assigned = workers.assign((0..5).map(job_id)).collect::<Vec<_>>();
+        assert_eq!(assigned, [(0, job_id(0)), (1, job_id(1)), (2, job_id(2))]);
+
+        workers.0[1].state = ExternalSnarkWorkerState::Working(job_id(1), JobSummary::Tx(1));
+        assert_eq!(workers.available(), 2);
+        assert_eq!(workers.working_on(&job_id(1)).collect::<Vec<_>>(), [1]);
+
+        // Job, which is already being proved, isn't assigned again.
+        let assigned = workers.assign((1..5).map(job_id)).collect::<Vec<_>>();
+        assert_eq!(assigned, [(0, job_id(2)), (2, job_id(3))]);
+    }
+
+    #[test]
+    fn assign_without_idle_workers() {
+        let mut workers = workers(2);
+        for (worker_id, worker) in workers.0.iter_mut().enumerate() {
+            worker.state = ExternalSnarkWorkerState::Working(job_id(worker_id), JobSummary::Tx(1));
+        }
+        assert_eq!(workers.idle_worker(), None);
+        assert_eq!(workers.assign((2..4).map(job_id)).count(), 0);
+
+        // At least one worker is always there.
+        assert_eq!(ExternalSnarkWorkers::new(Timestamp::ZERO, 0).ids(), 0..1);
+    }
+}
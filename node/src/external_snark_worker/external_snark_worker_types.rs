@@ -12,3 +12,6 @@ pub type SnarkWorkId = SnarkJobId;
 pub type SnarkWorkSpec = SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Instances;
 
 pub type SnarkWorkResult = Arc<TransactionSnarkWorkTStableV2Proofs>;
+
+/// Index of the worker in [`super::ExternalSnarkWorkers`].
+pub type SnarkWorkerId = usize;
@@ -3,19 +3,29 @@ use openmina_core::ActionEvent;
 use redux::EnablingCondition;
 use serde::{Deserialize, Serialize};
 
-use crate::{external_snark_worker::SnarkWorkSpec, State};
+use crate::{
+    external_snark_worker::{SnarkWorkSpec, SnarkWorkerId},
+    State,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, ActionEvent)]
+#[action_event(fields(worker_id))]
 pub enum ExternalSnarkWorkerEffectfulAction {
     Start {
+        worker_id: SnarkWorkerId,
         public_key: NonZeroCurvePoint,
         fee: CurrencyFeeStableV1,
     },
-    Kill,
+    Kill {
+        worker_id: SnarkWorkerId,
+    },
     SubmitWork {
+        worker_id: SnarkWorkerId,
         spec: Box<SnarkWorkSpec>,
     },
-    CancelWork,
+    CancelWork {
+        worker_id: SnarkWorkerId,
+    },
 }
 
 impl EnablingCondition<State> for ExternalSnarkWorkerEffectfulAction {
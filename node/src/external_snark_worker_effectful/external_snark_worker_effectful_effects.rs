@@ -8,31 +8,44 @@ pub fn external_snark_worker_effectful_effects<S: crate::Service>(
 ) {
     let (action, _) = action.split();
     match action {
-        ExternalSnarkWorkerEffectfulAction::Start { public_key, fee } => {
+        ExternalSnarkWorkerEffectfulAction::Start {
+            worker_id,
+            public_key,
+            fee,
+        } => {
             let work_verifier = store.state().snark.work_verify.verifier_index.clone();
-            if let Err(err) = store.service.start(public_key, fee, work_verifier) {
+            if let Err(err) = store
+                .service
+                .start(worker_id, public_key, fee, work_verifier)
+            {
                 store.dispatch(ExternalSnarkWorkerAction::Error {
+                    worker_id,
                     error: err,
                     permanent: true,
                 });
             }
         }
-        ExternalSnarkWorkerEffectfulAction::Kill => {
-            if let Err(err) = store.service().kill() {
+        ExternalSnarkWorkerEffectfulAction::Kill { worker_id } => {
+            if let Err(err) = store.service().kill(worker_id) {
                 store.dispatch(ExternalSnarkWorkerAction::Error {
+                    worker_id,
                     error: err,
                     permanent: true,
                 });
             }
         }
-        ExternalSnarkWorkerEffectfulAction::SubmitWork { spec } => {
-            if let Err(err) = store.service().submit(*spec) {
-                store.dispatch(ExternalSnarkWorkerAction::WorkError { error: err.into() });
+        ExternalSnarkWorkerEffectfulAction::SubmitWork { worker_id, spec } => {
+            if let Err(err) = store.service().submit(worker_id, *spec) {
+                store.dispatch(ExternalSnarkWorkerAction::WorkError {
+                    worker_id,
+                    error: err.into(),
+                });
             }
         }
-        ExternalSnarkWorkerEffectfulAction::CancelWork => {
-            if let Err(error) = store.service().cancel() {
+        ExternalSnarkWorkerEffectfulAction::CancelWork { worker_id } => {
+            if let Err(error) = store.service().cancel(worker_id) {
                 store.dispatch(ExternalSnarkWorkerAction::Error {
+                    worker_id,
                     error,
                     permanent: true,
                 });
@@ -4,6 +4,7 @@ use snark::TransactionVerifier;
 
 use crate::external_snark_worker::{
     ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkResult, SnarkWorkSpec,
+    SnarkWorkerId,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, derive_more::From)]
@@ -16,21 +17,28 @@ pub enum ExternalSnarkWorkerEvent {
     Error(ExternalSnarkWorkerError),
 }
 
+/// Each worker proves one job at a time, so several of them are started
+/// to prove jobs in parallel.
 pub trait ExternalSnarkWorkerService {
     /// Starts external process.
     fn start(
         &mut self,
+        worker_id: SnarkWorkerId,
         public_key: NonZeroCurvePoint,
         fee: CurrencyFeeStableV1,
         work_verifier: TransactionVerifier,
     ) -> Result<(), ExternalSnarkWorkerError>;
 
     /// Submits snark work
-    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError>;
+    fn submit(
+        &mut self,
+        worker_id: SnarkWorkerId,
+        spec: SnarkWorkSpec,
+    ) -> Result<(), ExternalSnarkWorkerError>;
 
     /// Cancel current work
-    fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError>;
+    fn cancel(&mut self, worker_id: SnarkWorkerId) -> Result<(), ExternalSnarkWorkerError>;
 
     /// Kills external process.
-    fn kill(&mut self) -> Result<(), ExternalSnarkWorkerError>;
+    fn kill(&mut self, worker_id: SnarkWorkerId) -> Result<(), ExternalSnarkWorkerError>;
 }
@@ -1,6 +1,6 @@
 use super::{
     read::{LedgerReadId, LedgerReadRequest, LedgerReadResponse, LedgerStatus},
-    write::{LedgerWriteRequest, LedgerWriteResponse},
+    write::{FrontierUpdate, LedgerWriteRequest, LedgerWriteResponse, LedgersToKeep},
     LedgerCtx, LedgerService,
 };
 use crate::{
@@ -13,7 +13,7 @@ use ledger::{
 };
 use mina_p2p_messages::v2::{self, LedgerHash, MinaBaseAccountBinableArgStableV2};
 use mina_signer::CompressedPubKey;
-use openmina_core::{channels::mpsc, thread};
+use openmina_core::{block::AppliedBlock, channels::mpsc, thread};
 use std::collections::BTreeMap;
 
 /// The type enumerating different requests that can be made to the
@@ -61,6 +61,14 @@ pub(super) enum LedgerRequest {
         staged_ledger_hash: LedgerHash,
         result: Result<StagedLedger, String>,
     },
+    LedgersPersist {
+        ledgers_to_keep: LedgersToKeep,
+        new_root: AppliedBlock,
+        new_best_tip: AppliedBlock,
+    },
+    FrontierPersist {
+        update: FrontierUpdate,
+    },
 }
 
 #[derive(Debug)]
@@ -176,6 +184,14 @@ impl LedgerRequest {
                     new_best_tip,
                 } => {
                     let best_tip_hash = new_best_tip.hash().clone();
+                    // Persisted once the commit response was sent.
+                    if ledger_ctx.is_persistence_enabled() {
+                        caller.call(LedgerRequest::LedgersPersist {
+                            ledgers_to_keep: ledgers_to_keep.clone(),
+                            new_root: new_root.clone(),
+                            new_best_tip: new_best_tip.clone(),
+                        });
+                    }
                     let result = ledger_ctx.commit(
                         ledgers_to_keep,
                         root_snarked_ledger_updates,
@@ -188,20 +204,39 @@ impl LedgerRequest {
                         result,
                     }
                 }
+                LedgerWriteRequest::FrontierRestore => LedgerWriteResponse::FrontierRestore {
+                    result: ledger_ctx.frontier_restore(),
+                },
+                LedgerWriteRequest::EpochLedgerSet {
+                    snarked_ledger_hash,
+                    ledger,
+                } => {
+                    let result = ledger_ctx.epoch_ledger_set(snarked_ledger_hash.clone(), &ledger);
+                    LedgerWriteResponse::EpochLedgerSet {
+                        snarked_ledger_hash,
+                        result,
+                    }
+                }
             }),
             Self::Read(id, request) => LedgerResponse::Read(
                 id,
                 match request {
-                    LedgerReadRequest::DelegatorTable(ledger_hash, producer) => {
+                    LedgerReadRequest::DelegatorTable(ledger_hash, producers) => {
                         let res = ledger_ctx
                             .producers_with_delegates(&ledger_hash, |pub_key| {
-                                AccountPublicKey::from(pub_key.clone()) == producer
+                                producers.contains(&AccountPublicKey::from(pub_key.clone()))
                             })
-                            .and_then(|list| list.into_iter().next())
-                            .map(|(_, table)| {
-                                table
-                                    .into_iter()
-                                    .map(|(index, pub_key, balance)| (index, (pub_key, balance)))
+                            .map(|list| {
+                                list.into_iter()
+                                    .map(|(producer, table)| {
+                                        let table = table
+                                            .into_iter()
+                                            .map(|(index, pub_key, balance)| {
+                                                (index, (pub_key, balance))
+                                            })
+                                            .collect();
+                                        (producer, table)
+                                    })
                                     .collect()
                             });
 
@@ -226,6 +261,10 @@ impl LedgerRequest {
                         );
                         LedgerReadResponse::GetStagedLedgerAuxAndPendingCoinbases(res)
                     }
+                    LedgerReadRequest::GetEpochLedger(ledger_hash) => {
+                        let res = ledger_ctx.epoch_ledger(ledger_hash);
+                        LedgerReadResponse::GetEpochLedger(res)
+                    }
                     LedgerReadRequest::ScanStateSummary(ledger_hash) => {
                         let res = ledger_ctx.scan_state_summary(&ledger_hash);
                         LedgerReadResponse::ScanStateSummary(res)
@@ -249,6 +288,9 @@ impl LedgerRequest {
                             AccountQuery::MultipleIds(ids) => {
                                 ledger_ctx.get_accounts(ledger_hash, ids.clone())
                             }
+                            AccountQuery::TokenOwner(token_id) => {
+                                ledger_ctx.get_token_owner(ledger_hash, token_id.clone().into())
+                            }
                         };
 
                         LedgerReadResponse::AccountsForRpc(rpc_id, res, account_query)
@@ -267,6 +309,10 @@ impl LedgerRequest {
                         let res = ledger_ctx.get_account_delegators(&ledger_hash, &account_id);
                         LedgerReadResponse::GetAccountDelegators(rpc_id, res)
                     }
+                    LedgerReadRequest::ArchiveBlocksRebuild(rpc_id, blocks) => {
+                        let res = ledger_ctx.archive_blocks_rebuild(blocks);
+                        LedgerReadResponse::ArchiveBlocksRebuild(rpc_id, res)
+                    }
                 },
             ),
             LedgerRequest::AccountsSet {
@@ -298,8 +344,11 @@ impl LedgerRequest {
                 target_snarked_ledger_hash,
                 overwrite,
             } => {
-                let origin_snarked_ledger_hash = origin_snarked_ledger_hash
-                    .iter()
+                // Prefer the target itself if it was loaded from disk, then the
+                // persisted root, which is usually close to what is being synced.
+                let origin_snarked_ledger_hash = std::iter::once(&target_snarked_ledger_hash)
+                    .chain(ledger_ctx.persisted_root_snarked_ledger_hash())
+                    .chain(origin_snarked_ledger_hash.iter())
                     .find(|hash| ledger_ctx.contains_snarked_ledger(hash))
                     .unwrap_or_else(|| {
                         origin_snarked_ledger_hash
@@ -352,6 +401,25 @@ impl LedgerRequest {
                 let res = ledger_ctx.get_accounts(ledger_hash, account_ids);
                 LedgerResponse::AccountsGet(Ok(res))
             }
+            LedgerRequest::LedgersPersist {
+                ledgers_to_keep,
+                new_root,
+                new_best_tip,
+            } => {
+                if !ledger_ctx.persist_ledgers(&ledgers_to_keep, &new_root, &new_best_tip) {
+                    // Continued after the requests queued in the meantime.
+                    caller.call(LedgerRequest::LedgersPersist {
+                        ledgers_to_keep,
+                        new_root,
+                        new_best_tip,
+                    });
+                }
+                LedgerResponse::Success
+            }
+            LedgerRequest::FrontierPersist { update } => {
+                ledger_ctx.frontier_persist(update);
+                LedgerResponse::Success
+            }
         }
     }
 }
@@ -0,0 +1,558 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::ErrorKind::InvalidData,
+    path::{Path, PathBuf},
+};
+
+use ledger::{Address, BaseLedger, Database, Mask};
+use mina_p2p_messages::v2::{LedgerHash, StateHash};
+use openmina_core::block::AppliedBlock;
+
+use crate::p2p::channels::rpc::StagedLedgerAuxAndPendingCoinbases;
+
+use super::LEDGER_DEPTH;
+
+const ROOT_SNARKED_LEDGER_DIR: &str = "root_snarked";
+const EPOCH_LEDGERS_DIR: &str = "epoch";
+const FRONTIER_DIR: &str = "frontier";
+const FRONTIER_ROOT_FILE: &str = "root";
+const FRONTIER_BEST_TIP_FILE: &str = "best_tip";
+const FRONTIER_BLOCKS_DIR: &str = "blocks";
+
+/// Maximum number of accounts written to disk by one persistence step, so
+/// that the ledger thread isn't blocked for long when a whole ledger has to
+/// be written.
+pub(super) const PERSIST_ACCOUNTS_PER_STEP: usize = 10_000;
+
+/// On-disk copies of the root snarked ledger, of the epoch ledgers and of
+/// the transition frontier.
+///
+/// The root snarked ledger is updated incrementally on each commit: only the
+/// accounts which differ from the previous root are written. Epoch ledgers never
+/// change, each of them is written once, in a directory named after its hash.
+/// Ledgers are written in steps of at most [`PERSIST_ACCOUNTS_PER_STEP`]
+/// accounts. A ledger which was interrupted between steps doesn't match its
+/// hash, and is discarded or overwritten.
+///
+/// The transition frontier is stored as the staged ledger parts of the root
+/// block (scan state, pending coinbases and the protocol states they need),
+/// rewritten only when the root changes, one file per block of the best chain,
+/// each written once, and the hash of the best tip. The best chain is rebuilt
+/// by following the parents of the best tip down to the root.
+pub(super) struct LedgerPersistence {
+    directory: PathBuf,
+    root_snarked: Database,
+    root_snarked_hash: LedgerHash,
+    epoch_ledgers: BTreeSet<LedgerHash>,
+    /// Epoch ledgers which are partially written
+    pending_epoch_ledgers: BTreeMap<LedgerHash, Database>,
+    /// Root block of the stored transition frontier, if written or loaded
+    /// by this run
+    frontier_root: Option<StateHash>,
+    /// Stored blocks of the transition frontier, with their parent hash
+    frontier_blocks: BTreeMap<StateHash, StateHash>,
+}
+
+impl LedgerPersistence {
+    /// Opens the storage at `directory`, and returns the ledgers found there,
+    /// indexed by their merkle root hash.
+    ///
+    /// Returned ledgers are in-memory copies, detached from the storage.
+    pub fn open(directory: PathBuf) -> std::io::Result<(Self, BTreeMap<LedgerHash, Mask>)> {
+        let mut loaded = BTreeMap::new();
+
+        let root_snarked_dir = directory.join(ROOT_SNARKED_LEDGER_DIR);
+        let mut root_snarked = Database::open_ondisk(LEDGER_DEPTH as u8, root_snarked_dir.clone())?;
+        let root_snarked_hash = LedgerHash::from_fp(root_snarked.merkle_root());
+
+        if root_snarked.num_accounts() > 0 {
+            let mask = Mask::new_root(root_snarked.clone_db(root_snarked_dir));
+            loaded.insert(root_snarked_hash.clone(), mask);
+        }
+
+        let epoch_ledgers_dir = directory.join(EPOCH_LEDGERS_DIR);
+        std::fs::create_dir_all(&epoch_ledgers_dir)?;
+
+        let mut epoch_ledgers = BTreeSet::new();
+
+        for entry in std::fs::read_dir(&epoch_ledgers_dir)? {
+            let path = entry?.path();
+            let Some(hash) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.parse::<LedgerHash>().ok())
+            else {
+                continue;
+            };
+
+            match load_epoch_ledger(&path, &hash) {
+                Ok(mask) => {
+                    epoch_ledgers.insert(hash.clone());
+                    loaded.insert(hash, mask);
+                }
+                Err(e) => {
+                    openmina_core::warn!(
+                        openmina_core::log::system_time();
+                        kind = "LedgerPersistence::open",
+                        summary = format!("Discarding epoch ledger {hash}: {e}")
+                    );
+                    std::fs::remove_dir_all(&path)?;
+                }
+            }
+        }
+
+        std::fs::create_dir_all(directory.join(FRONTIER_DIR).join(FRONTIER_BLOCKS_DIR))?;
+
+        let persistence = Self {
+            directory,
+            root_snarked,
+            root_snarked_hash,
+            epoch_ledgers,
+            pending_epoch_ledgers: Default::default(),
+            frontier_root: None,
+            frontier_blocks: Default::default(),
+        };
+
+        Ok((persistence, loaded))
+    }
+
+    pub fn root_snarked_ledger_hash(&self) -> &LedgerHash {
+        &self.root_snarked_hash
+    }
+
+    /// Updates the on-disk root snarked ledger towards the content of
+    /// `ledger`, writing at most `budget` accounts.
+    ///
+    /// Returns `true` once the stored ledger is the same as `ledger`.
+    pub fn persist_root_snarked_ledger(
+        &mut self,
+        hash: &LedgerHash,
+        ledger: &Mask,
+        budget: &mut usize,
+    ) -> std::io::Result<bool> {
+        if &self.root_snarked_hash == hash {
+            return Ok(true);
+        }
+
+        let is_done = copy_changed_accounts(ledger, &mut self.root_snarked, budget)?;
+        self.root_snarked.persist()?;
+
+        self.root_snarked_hash = LedgerHash::from_fp(self.root_snarked.merkle_root());
+
+        if is_done && &self.root_snarked_hash != hash {
+            return Err(std::io::Error::new(
+                InvalidData,
+                format!(
+                    "Persisted root snarked ledger hash {} doesn't match {hash}",
+                    self.root_snarked_hash
+                ),
+            ));
+        }
+
+        Ok(is_done)
+    }
+
+    /// Writes the epoch ledger `ledger` to disk, unless it is already stored,
+    /// writing at most `budget` accounts.
+    ///
+    /// Returns `true` once the ledger is stored.
+    pub fn persist_epoch_ledger(
+        &mut self,
+        hash: &LedgerHash,
+        ledger: &Mask,
+        budget: &mut usize,
+    ) -> std::io::Result<bool> {
+        if self.epoch_ledgers.contains(hash) {
+            return Ok(true);
+        }
+
+        let mut db = match self.pending_epoch_ledgers.remove(hash) {
+            Some(db) => db,
+            None => {
+                let path = self.epoch_ledger_dir(hash);
+                // Leftover of an interrupted write
+                if path.try_exists()? {
+                    std::fs::remove_dir_all(&path)?;
+                }
+                Database::open_ondisk(LEDGER_DEPTH as u8, path)?
+            }
+        };
+
+        let is_done = copy_changed_accounts(ledger, &mut db, budget)?;
+        db.persist()?;
+
+        if !is_done {
+            self.pending_epoch_ledgers.insert(hash.clone(), db);
+            return Ok(false);
+        }
+
+        let stored_hash = LedgerHash::from_fp(db.merkle_root());
+        if &stored_hash != hash {
+            return Err(std::io::Error::new(
+                InvalidData,
+                format!("Persisted epoch ledger hash {stored_hash} doesn't match {hash}"),
+            ));
+        }
+        self.epoch_ledgers.insert(hash.clone());
+
+        Ok(true)
+    }
+
+    /// Removes from disk the epoch ledgers for which `keep` returns false
+    pub fn retain_epoch_ledgers<F>(&mut self, keep: F) -> std::io::Result<()>
+    where
+        F: Fn(&LedgerHash) -> bool,
+    {
+        let removed = self
+            .epoch_ledgers
+            .iter()
+            .chain(self.pending_epoch_ledgers.keys())
+            .filter(|hash| !keep(hash))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for hash in removed {
+            self.pending_epoch_ledgers.remove(&hash);
+            std::fs::remove_dir_all(self.epoch_ledger_dir(&hash))?;
+            self.epoch_ledgers.remove(&hash);
+        }
+
+        Ok(())
+    }
+
+    pub fn frontier_root_hash(&self) -> Option<&StateHash> {
+        self.frontier_root.as_ref()
+    }
+
+    /// Writes the staged ledger parts of the transition frontier root block
+    pub fn persist_frontier_root(
+        &mut self,
+        root_hash: &StateHash,
+        parts: &StagedLedgerAuxAndPendingCoinbases,
+    ) -> std::io::Result<()> {
+        let bytes = postcard::to_stdvec(&(root_hash, parts))
+            .map_err(|e| std::io::Error::new(InvalidData, e))?;
+        write_atomically(&self.frontier_file(FRONTIER_ROOT_FILE), &bytes)?;
+
+        self.frontier_root = Some(root_hash.clone());
+
+        Ok(())
+    }
+
+    /// Writes the blocks of the best chain which aren't stored yet, and makes
+    /// `best_tip_hash` the stored best tip.
+    ///
+    /// Blocks which are no longer between the stored root and the best tip
+    /// are removed.
+    pub fn persist_frontier_best_chain(
+        &mut self,
+        new_blocks: &[AppliedBlock],
+        best_tip_hash: &StateHash,
+    ) -> std::io::Result<()> {
+        for block in new_blocks {
+            if self.frontier_blocks.contains_key(block.hash()) {
+                continue;
+            }
+            let bytes =
+                postcard::to_stdvec(block).map_err(|e| std::io::Error::new(InvalidData, e))?;
+            write_atomically(&self.frontier_block_file(block.hash()), &bytes)?;
+            self.frontier_blocks
+                .insert(block.hash().clone(), block.pred_hash().clone());
+        }
+
+        let bytes =
+            postcard::to_stdvec(best_tip_hash).map_err(|e| std::io::Error::new(InvalidData, e))?;
+        write_atomically(&self.frontier_file(FRONTIER_BEST_TIP_FILE), &bytes)?;
+
+        let Some(root_hash) = self.frontier_root.clone() else {
+            return Ok(());
+        };
+        let mut chain = BTreeSet::new();
+        let mut hash = best_tip_hash.clone();
+        while hash != root_hash {
+            let Some(pred_hash) = self.frontier_blocks.get(&hash) else {
+                return Err(std::io::Error::new(
+                    InvalidData,
+                    format!("Best chain block {hash} is not stored"),
+                ));
+            };
+            chain.insert(hash);
+            hash = pred_hash.clone();
+        }
+        chain.insert(root_hash);
+
+        let removed = self
+            .frontier_blocks
+            .keys()
+            .filter(|hash| !chain.contains(*hash))
+            .cloned()
+            .collect::<Vec<_>>();
+        for hash in removed {
+            remove_file_if_exists(&self.frontier_block_file(&hash))?;
+            self.frontier_blocks.remove(&hash);
+        }
+
+        Ok(())
+    }
+
+    /// Reads the stored transition frontier: the staged ledger parts of the
+    /// root block and the best chain.
+    ///
+    /// Returns `None` if no transition frontier was stored. Stored blocks which
+    /// aren't part of the best chain are removed.
+    pub fn load_frontier(
+        &mut self,
+    ) -> std::io::Result<Option<(StagedLedgerAuxAndPendingCoinbases, Vec<AppliedBlock>)>> {
+        let (Some(root), Some(best_tip_hash)) = (
+            read_if_exists(&self.frontier_file(FRONTIER_ROOT_FILE))?,
+            read_if_exists(&self.frontier_file(FRONTIER_BEST_TIP_FILE))?,
+        ) else {
+            return Ok(None);
+        };
+
+        let (root_hash, parts): (StateHash, StagedLedgerAuxAndPendingCoinbases) =
+            postcard::from_bytes(&root).map_err(|e| std::io::Error::new(InvalidData, e))?;
+        let mut hash: StateHash = postcard::from_bytes(&best_tip_hash)
+            .map_err(|e| std::io::Error::new(InvalidData, e))?;
+
+        // Files are not written at once, the process may have been stopped
+        // in between.
+        let mut best_chain = Vec::new();
+        loop {
+            let Some(bytes) = read_if_exists(&self.frontier_block_file(&hash))? else {
+                return Err(std::io::Error::new(
+                    InvalidData,
+                    format!("Best chain block {hash} is not stored, root {root_hash}"),
+                ));
+            };
+            let block: AppliedBlock =
+                postcard::from_bytes(&bytes).map_err(|e| std::io::Error::new(InvalidData, e))?;
+            hash = block.pred_hash().clone();
+            let is_root = block.hash() == &root_hash;
+            best_chain.push(block);
+            if is_root {
+                break;
+            }
+        }
+        best_chain.reverse();
+
+        self.frontier_root = Some(root_hash);
+        self.frontier_blocks = best_chain
+            .iter()
+            .map(|block| (block.hash().clone(), block.pred_hash().clone()))
+            .collect();
+
+        for entry in std::fs::read_dir(self.directory.join(FRONTIER_DIR).join(FRONTIER_BLOCKS_DIR))?
+        {
+            let path = entry?.path();
+            let is_stored = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.parse::<StateHash>().ok())
+                .is_some_and(|hash| self.frontier_blocks.contains_key(&hash));
+            if !is_stored {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        Ok(Some((parts, best_chain)))
+    }
+
+    /// Removes the stored transition frontier
+    pub fn clear_frontier(&mut self) -> std::io::Result<()> {
+        let frontier_dir = self.directory.join(FRONTIER_DIR);
+        std::fs::remove_dir_all(&frontier_dir)?;
+        std::fs::create_dir_all(frontier_dir.join(FRONTIER_BLOCKS_DIR))?;
+
+        self.frontier_root = None;
+        self.frontier_blocks.clear();
+
+        Ok(())
+    }
+
+    fn frontier_file(&self, name: &str) -> PathBuf {
+        self.directory.join(FRONTIER_DIR).join(name)
+    }
+
+    fn frontier_block_file(&self, hash: &StateHash) -> PathBuf {
+        self.directory
+            .join(FRONTIER_DIR)
+            .join(FRONTIER_BLOCKS_DIR)
+            .join(hash.to_string())
+    }
+
+    fn epoch_ledger_dir(&self, hash: &LedgerHash) -> PathBuf {
+        self.directory
+            .join(EPOCH_LEDGERS_DIR)
+            .join(hash.to_string())
+    }
+}
+
+/// Replaces the content of `path`, readers never see a partially written file
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let tmp_path = path.with_extension("tmp");
+    let mut file = std::fs::File::create(&tmp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+
+    std::fs::rename(tmp_path, path)
+}
+
+fn read_if_exists(path: &Path) -> std::io::Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn remove_file_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn load_epoch_ledger(path: &Path, hash: &LedgerHash) -> std::io::Result<Mask> {
+    let mut db = Database::open_ondisk(LEDGER_DEPTH as u8, path.to_path_buf())?;
+    let stored_hash = LedgerHash::from_fp(db.merkle_root());
+
+    if &stored_hash != hash {
+        return Err(std::io::Error::new(
+            InvalidData,
+            format!("stored ledger has hash {stored_hash}"),
+        ));
+    }
+
+    Ok(Mask::new_root(db.clone_db(path.to_path_buf())))
+}
+
+/// Makes `target` contain the same accounts as `source`, writing at most
+/// `budget` accounts. Returns `true` if all accounts were written.
+///
+/// Only the subtrees whose hashes differ are visited, so the cost is
+/// proportional to the number of changed accounts.
+fn copy_changed_accounts(
+    source: &Mask,
+    target: &mut Database,
+    budget: &mut usize,
+) -> std::io::Result<bool> {
+    let to_io_error = |e: String| std::io::Error::new(InvalidData, e);
+
+    let mut source = source.clone();
+    let depth = target.depth() as usize;
+
+    let mut removed = Vec::new();
+    let mut addrs = vec![Address::root()];
+
+    while let Some(addr) = addrs.pop() {
+        let source_hash = source
+            .get_inner_hash_at_addr(addr.clone())
+            .map_err(to_io_error)?;
+        let target_hash = target
+            .get_inner_hash_at_addr(addr.clone())
+            .map_err(to_io_error)?;
+
+        if source_hash == target_hash {
+            continue;
+        }
+
+        if addr.length() < depth {
+            addrs.push(addr.child_right());
+            addrs.push(addr.child_left());
+            continue;
+        }
+
+        if *budget == 0 {
+            addrs.push(addr);
+            break;
+        }
+        *budget -= 1;
+        match source.get(addr.clone()) {
+            Some(account) => target.set(addr, account),
+            None => removed.extend(target.get(addr).map(|account| account.id())),
+        }
+    }
+
+    if !removed.is_empty() {
+        target.remove_accounts(&removed);
+    }
+
+    Ok(addrs.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use ledger::Account;
+
+    use super::*;
+
+    /// Returns the number of steps of 10 accounts it took to persist.
+    fn persist_in_steps(mut persist: impl FnMut(&mut usize) -> bool) -> usize {
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            let mut budget = 10;
+            if persist(&mut budget) {
+                return steps;
+            }
+        }
+    }
+
+    #[test]
+    fn persist_ledgers_in_steps() {
+        let directory = std::env::temp_dir().join(format!(
+            "openmina-ledger-persistence-test-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        let mut db = Database::create(LEDGER_DEPTH as u8);
+        for _ in 0..25 {
+            let account = Account::rand();
+            db.get_or_create_account(account.id(), account).unwrap();
+        }
+        let mut ledger = Mask::new_root(db);
+        let hash = LedgerHash::from_fp(ledger.merkle_root());
+
+        let (mut persistence, _) = LedgerPersistence::open(directory.clone()).unwrap();
+        assert_eq!(
+            persist_in_steps(|budget| {
+                persistence
+                    .persist_epoch_ledger(&hash, &ledger, budget)
+                    .unwrap()
+            }),
+            3
+        );
+        assert_eq!(
+            persist_in_steps(|budget| {
+                persistence
+                    .persist_root_snarked_ledger(&hash, &ledger, budget)
+                    .unwrap()
+            }),
+            3
+        );
+        assert_eq!(persistence.root_snarked_ledger_hash(), &hash);
+
+        // Only changed accounts are written.
+        let account = Account::rand();
+        ledger.get_or_create_account(account.id(), account).unwrap();
+        let new_hash = LedgerHash::from_fp(ledger.merkle_root());
+        let mut budget = 10;
+        assert!(persistence
+            .persist_root_snarked_ledger(&new_hash, &ledger, &mut budget)
+            .unwrap());
+        assert_eq!(budget, 9);
+        drop(persistence);
+
+        let (_, loaded) = LedgerPersistence::open(directory.clone()).unwrap();
+        assert_eq!(
+            loaded.keys().collect::<BTreeSet<_>>(),
+            BTreeSet::from([&hash, &new_hash])
+        );
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,7 +1,11 @@
 use super::{
     ledger_empty_hash_at_depth,
+    ledger_persistence::LedgerPersistence,
     read::{LedgerReadId, LedgerReadRequest, LedgerReadResponse},
-    write::{CommitResult, LedgerWriteRequest, LedgerWriteResponse, LedgersToKeep},
+    write::{
+        CommitResult, FrontierUpdate, LedgerWriteRequest, LedgerWriteResponse, LedgersToKeep,
+        RestoredFrontier,
+    },
     LedgerAddress, LedgerEvent, LEDGER_DEPTH,
 };
 use crate::{
@@ -70,7 +74,7 @@ use openmina_core::{
 };
 use std::{
     collections::{BTreeMap, BTreeSet},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
@@ -177,6 +181,10 @@ pub struct LedgerCtx {
     sync: LedgerSyncState,
     /// Returns more data on block application necessary for archive node
     archive_mode: bool,
+    /// On-disk copies of the root snarked ledger and of the epoch ledgers
+    persistence: Option<LedgerPersistence>,
+    /// Root of the latest commit, whose ledgers should be persisted
+    persist_ledgers_root: Option<StateHash>,
     event_sender:
         Option<openmina_core::channels::mpsc::UnboundedSender<crate::event_source::Event>>,
 }
@@ -226,6 +234,39 @@ impl LedgerCtx {
         self.archive_mode = true;
     }
 
+    /// Keeps the root snarked ledger, the epoch ledgers and the transition
+    /// frontier on disk, in `directory`.
+    ///
+    /// Ledgers stored by a previous run are loaded, so that they don't need to
+    /// be synced again from peers. The transition frontier is restored later,
+    /// with [`Self::frontier_restore`].
+    pub fn enable_persistence(&mut self, directory: PathBuf) -> std::io::Result<()> {
+        let (persistence, ledgers) = LedgerPersistence::open(directory)?;
+
+        for hash in ledgers.keys() {
+            openmina_core::info!(openmina_core::log::system_time();
+                kind = "LedgerService::enable_persistence",
+                summary = format!("loaded snarked ledger {hash} from disk"));
+        }
+
+        self.snarked_ledgers.extend(ledgers);
+        self.persistence = Some(persistence);
+
+        Ok(())
+    }
+
+    pub fn is_persistence_enabled(&self) -> bool {
+        self.persistence.is_some()
+    }
+
+    /// Hash of the root snarked ledger stored on disk, if it is still available
+    pub fn persisted_root_snarked_ledger_hash(&self) -> Option<&LedgerHash> {
+        self.persistence
+            .as_ref()
+            .map(LedgerPersistence::root_snarked_ledger_hash)
+            .filter(|hash| self.contains_snarked_ledger(hash))
+    }
+
     // TODO(tizoc): Only used for the current workaround to make staged ledger
     // reconstruction async, can be removed when the ledger services are made async
     pub fn set_event_sender(
@@ -608,6 +649,45 @@ impl LedgerCtx {
         Ok(computed_hash)
     }
 
+    pub fn epoch_ledger_set(
+        &mut self,
+        snarked_ledger_hash: LedgerHash,
+        ledger: &v2::MinaBaseSparseLedgerBaseStableV2,
+    ) -> Result<LedgerHash, String> {
+        let sparse_ledger = SparseLedger::try_from(ledger).map_err(error_to_string)?;
+        let accounts = std::cell::RefCell::new(Vec::new());
+        sparse_ledger.iteri(|addr, account| {
+            accounts
+                .borrow_mut()
+                .push((addr.to_index(), account.clone()))
+        });
+        let mut accounts = accounts.into_inner();
+        accounts.sort_by_key(|(index, _)| *index);
+
+        // Accounts must be stored at consecutive indexes, same as in the
+        // ledger of the peer, otherwise the merkle root would differ.
+        if accounts
+            .iter()
+            .enumerate()
+            .any(|(i, (index, _))| index.as_u64() != i as u64)
+        {
+            return Err("Epoch ledger contains gaps between accounts".to_owned());
+        }
+
+        let mut mask = Mask::create_with_token_owners(LEDGER_DEPTH);
+        for (_, account) in accounts {
+            mask.get_or_create_account(account.id(), account)
+                .map_err(|e| format!("Failed when setting account: {e:?}"))?;
+        }
+
+        let computed_hash = merkle_root(&mut mask);
+        if computed_hash == snarked_ledger_hash {
+            self.sync.snarked_ledgers.insert(snarked_ledger_hash, mask);
+        }
+
+        Ok(computed_hash)
+    }
+
     pub fn staged_ledger_reconstruct<F>(
         &mut self,
         snarked_ledger_hash: LedgerHash,
@@ -708,7 +788,7 @@ impl LedgerCtx {
                 &Verifier,
                 &prev_state_view,
                 prev_protocol_state.hashes(),
-                coinbase_receiver.clone(),
+                coinbase_receiver,
                 supercharge_coinbase,
             )
             .map_err(|err| format!("{err:?}"))?;
@@ -739,119 +819,7 @@ impl LedgerCtx {
         }
 
         let archive_data = if self.archive_mode {
-            let senders = block
-                .body()
-                .transactions()
-                .filter_map(|tx| UserCommand::try_from(tx).ok().map(|cmd| cmd.fee_payer()))
-                .collect::<BTreeSet<_>>()
-                .into_iter();
-
-            let coinbase_receiver_id = AccountId::new(coinbase_receiver, TokenId::default());
-
-            // https://github.com/MinaProtocol/mina/blob/85149735ca3a76d026e8cf36b8ff22941a048e31/src/app/archive/lib/diff.ml#L78
-            let (accessed, not_accessed): (BTreeSet<_>, BTreeSet<_>) = block
-                .body()
-                .tranasctions_with_status()
-                .flat_map(|(tx, status)| {
-                    let status: TransactionStatus = status.into();
-                    UserCommand::try_from(tx)
-                        .ok()
-                        .map(|cmd| cmd.account_access_statuses(&status))
-                        .into_iter()
-                        .flatten()
-                })
-                .partition(|(_, status)| *status == AccessedOrNot::Accessed);
-
-            let mut account_ids_accessed: BTreeSet<_> =
-                accessed.into_iter().map(|(id, _)| id).collect();
-            let mut account_ids_not_accessed: BTreeSet<_> =
-                not_accessed.into_iter().map(|(id, _)| id).collect();
-
-            // Coinbase receiver is included only when the block has a coinbase transaction
-            // Note: If for whatever reason the network has set the coinbase amount to zero,
-            // to mimic the behavior of the ocaml node, we still include the coinbase receiver
-            // in the accessed accounts as a coinbase transaction is created regardless of the coinbase amount.
-            // https://github.com/MinaProtocol/mina/blob/b595a2bf00ae138d745737da628bd94bb2bd91e2/src/lib/staged_ledger/pre_diff_info.ml#L139
-            let has_coinbase = block.body().has_coinbase();
-
-            if has_coinbase {
-                account_ids_accessed.insert(coinbase_receiver_id);
-            } else {
-                account_ids_not_accessed.insert(coinbase_receiver_id);
-            }
-
-            // Include the coinbase fee transfer accounts
-            let fee_transfer_accounts =
-                block.body().coinbase_fee_transfers_iter().filter_map(|cb| {
-                    let receiver: CompressedPubKey = cb.receiver_pk.inner().try_into().ok()?;
-                    let account_id = AccountId::new(receiver, TokenId::default());
-                    Some(account_id)
-                });
-            account_ids_accessed.extend(fee_transfer_accounts);
-
-            // TODO(adonagy): Create a struct instead of tuple
-            let accounts_accessed: Vec<(AccountIndex, Account)> = account_ids_accessed
-                .iter()
-                .filter_map(|id| {
-                    staged_ledger
-                        .ledger()
-                        .index_of_account(id.clone())
-                        .and_then(|index| {
-                            staged_ledger
-                                .ledger()
-                                .get_at_index(index)
-                                .map(|account| (index, *account))
-                        })
-                })
-                .collect();
-
-            let account_creation_fee = constraint_constants().account_creation_fee;
-
-            // TODO(adonagy): Create a struct instead of tuple
-            let accounts_created: Vec<(AccountId, u64)> = staged_ledger
-                .latest_block_accounts_created(pred_block.hash().to_field()?)
-                .iter()
-                .map(|id| (id.clone(), account_creation_fee))
-                .collect();
-
-            // A token is used regardless of txn status
-            // https://github.com/MinaProtocol/mina/blob/85149735ca3a76d026e8cf36b8ff22941a048e31/src/app/archive/lib/diff.ml#L114
-            let all_account_ids: BTreeSet<_> = account_ids_accessed
-                .union(&account_ids_not_accessed)
-                .collect();
-            let tokens_used: BTreeSet<(TokenId, Option<AccountId>)> = if has_coinbase {
-                all_account_ids
-                    .iter()
-                    .map(|id| {
-                        let token_id = id.token_id.clone();
-                        let token_owner = staged_ledger.ledger().token_owner(token_id.clone());
-                        (token_id, token_owner)
-                    })
-                    .collect()
-            } else {
-                BTreeSet::new()
-            };
-
-            let sender_receipt_chains_from_parent_ledger = senders
-                .filter_map(|sender| {
-                    if let Some(location) = staged_ledger.ledger().location_of_account(&sender) {
-                        staged_ledger.ledger().get(location).map(|account| {
-                            (
-                                sender,
-                                v2::ReceiptChainHash::from(account.receipt_chain_hash),
-                            )
-                        })
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            Some(BlockApplyResultArchive {
-                accounts_accessed,
-                accounts_created,
-                tokens_used,
-                sender_receipt_chains_from_parent_ledger,
-            })
+            Some(block_archive_data(&staged_ledger, &block)?)
         } else {
             None
         };
@@ -869,6 +837,33 @@ impl LedgerCtx {
         })
     }
 
+    /// Rebuilds the archive data of already applied blocks, using the staged
+    /// ledgers kept for them. Blocks whose staged ledger is no longer kept
+    /// (e.g. blocks below the transition frontier root) are skipped.
+    pub fn archive_blocks_rebuild(&mut self, blocks: Vec<AppliedBlock>) -> Vec<BlockApplyResult> {
+        blocks
+            .into_iter()
+            .filter_map(|applied| {
+                let staged_ledger = self.staged_ledger_mut(applied.staged_ledger_hashes())?;
+                match block_archive_data(staged_ledger, &applied.block) {
+                    Ok(archive_data) => Some(BlockApplyResult {
+                        block: applied.block,
+                        just_emitted_a_proof: applied.just_emitted_a_proof,
+                        archive_data: Some(archive_data),
+                    }),
+                    Err(error) => {
+                        openmina_core::warn!(openmina_core::log::system_time();
+                            kind = "LedgerService::archive_blocks_rebuild",
+                            summary = format!("failed to rebuild archive data for block {}", applied.hash()),
+                            error = error,
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
     pub fn commit(
         &mut self,
         ledgers_to_keep: LedgersToKeep,
@@ -885,6 +880,9 @@ impl LedgerCtx {
             new_root_next_epoch_ledger = new_root.next_epoch_ledger_hash().to_string(),
             new_root_snarked_ledger = new_root.snarked_ledger_hash().to_string(),
         );
+        if self.persistence.is_some() {
+            self.persist_ledgers_root = Some(new_root.hash().clone());
+        }
         self.recreate_snarked_ledger(
             &root_snarked_ledger_updates,
             &needed_protocol_states,
@@ -996,6 +994,227 @@ impl LedgerCtx {
         }
     }
 
+    /// Writes a step of the root snarked ledger and the epoch ledgers to
+    /// disk, when persistence is enabled.
+    ///
+    /// Requested by the ledger manager after the commit response was sent,
+    /// so that disk writes don't delay the commit. Each step writes at most
+    /// [`super::ledger_persistence::PERSIST_ACCOUNTS_PER_STEP`] accounts, so
+    /// other ledger requests are handled between the steps of a large write.
+    ///
+    /// Returns `false` if there is more to write, and the request should be
+    /// repeated. Ledgers which were dropped by a later commit in the meantime
+    /// are skipped, and writes for an older commit are abandoned.
+    pub fn persist_ledgers(
+        &mut self,
+        ledgers_to_keep: &LedgersToKeep,
+        new_root: &ArcBlockWithHash,
+        new_best_tip: &ArcBlockWithHash,
+    ) -> bool {
+        if self.persist_ledgers_root.as_ref() != Some(new_root.hash()) {
+            return true;
+        }
+        let Some(mut persistence) = self.persistence.take() else {
+            return true;
+        };
+
+        let log_error = |e: std::io::Error| {
+            openmina_core::error!(openmina_core::log::system_time();
+                kind = "LedgerService::persist_ledgers",
+                summary = format!("Failed to persist ledgers: {e}"));
+            true
+        };
+
+        let mut budget = super::ledger_persistence::PERSIST_ACCOUNTS_PER_STEP;
+        let mut is_done = true;
+
+        let root_snarked_ledger_hash = new_root.snarked_ledger_hash();
+        if let Some((mask, _)) = self.mask(root_snarked_ledger_hash) {
+            is_done &= persistence
+                .persist_root_snarked_ledger(root_snarked_ledger_hash, &mask, &mut budget)
+                .unwrap_or_else(log_error);
+        }
+
+        for ledger_hash in [
+            new_best_tip.staking_epoch_ledger_hash(),
+            new_best_tip.next_epoch_ledger_hash(),
+        ] {
+            // The genesis ledger is always available
+            if ledger_hash == new_best_tip.genesis_ledger_hash() {
+                continue;
+            }
+            if let Some((mask, true)) = self.mask(ledger_hash) {
+                is_done &= persistence
+                    .persist_epoch_ledger(ledger_hash, &mask, &mut budget)
+                    .unwrap_or_else(log_error);
+            }
+        }
+
+        persistence
+            .retain_epoch_ledgers(|hash| ledgers_to_keep.contains(hash))
+            .map_or_else(log_error, |_| true);
+
+        self.persistence = Some(persistence);
+        is_done
+    }
+
+    /// Stores the transition frontier, so that it can be restored by
+    /// [`Self::frontier_restore`] after a restart.
+    ///
+    /// Only blocks which are not stored yet are written, the root staged
+    /// ledger parts only when the root changed.
+    pub fn frontier_persist(&mut self, update: FrontierUpdate) {
+        let Some(mut persistence) = self.persistence.take() else {
+            return;
+        };
+
+        if let Err(e) = self.frontier_persist_to(&mut persistence, update) {
+            openmina_core::error!(openmina_core::log::system_time();
+                kind = "LedgerService::frontier_persist",
+                summary = format!("Failed to persist transition frontier: {e}"));
+        }
+
+        self.persistence = Some(persistence);
+    }
+
+    fn frontier_persist_to(
+        &mut self,
+        persistence: &mut LedgerPersistence,
+        update: FrontierUpdate,
+    ) -> std::io::Result<()> {
+        let FrontierUpdate {
+            root,
+            best_tip_hash,
+            new_blocks,
+            needed_protocol_states,
+        } = update;
+
+        if let Some(needed_protocol_states) =
+            needed_protocol_states.filter(|_| persistence.frontier_root_hash() != Some(root.hash()))
+        {
+            let parts = self
+                .staged_ledger_aux_and_pending_coinbase(
+                    root.staged_ledger_hashes(),
+                    needed_protocol_states,
+                )
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!(
+                            "staged ledger parts of the root {} are missing",
+                            root.hash()
+                        ),
+                    )
+                })?;
+            persistence.persist_frontier_root(root.hash(), &parts)?;
+        }
+
+        persistence.persist_frontier_best_chain(&new_blocks, &best_tip_hash)
+    }
+
+    /// Loads the transition frontier stored by [`Self::frontier_persist`],
+    /// and rebuilds the staged ledgers of its blocks.
+    ///
+    /// The root staged ledger is reconstructed on top of the persisted root
+    /// snarked ledger, then the rest of the best chain is applied on top of it.
+    pub fn frontier_restore(&mut self) -> Result<Option<RestoredFrontier>, String> {
+        let Some(mut persistence) = self.persistence.take() else {
+            return Ok(None);
+        };
+        let result = self.frontier_restore_from(&mut persistence);
+        if result.is_err() {
+            // Stored frontier is unusable, it will be rewritten once synced.
+            if let Err(e) = persistence.clear_frontier() {
+                openmina_core::error!(openmina_core::log::system_time();
+                    kind = "LedgerService::frontier_restore",
+                    summary = format!("Failed to remove stored transition frontier: {e}"));
+            }
+        }
+        self.persistence = Some(persistence);
+        result
+    }
+
+    fn frontier_restore_from(
+        &mut self,
+        persistence: &mut LedgerPersistence,
+    ) -> Result<Option<RestoredFrontier>, String> {
+        let Some((parts, best_chain)) = persistence.load_frontier().map_err(|e| e.to_string())?
+        else {
+            return Ok(None);
+        };
+        let (Some(root), Some(best_tip)) = (best_chain.first(), best_chain.last()) else {
+            return Ok(None);
+        };
+
+        for ledger_hash in [
+            root.snarked_ledger_hash(),
+            best_tip.staking_epoch_ledger_hash(),
+            best_tip.next_epoch_ledger_hash(),
+        ] {
+            if !self.contains_snarked_ledger(ledger_hash) {
+                return Err(format!("snarked ledger {ledger_hash} is missing"));
+            }
+        }
+
+        let needed_protocol_states = parts
+            .needed_blocks
+            .iter()
+            .map(|state| Ok((state.try_hash()?, state.clone())))
+            .collect::<Result<BTreeMap<_, _>, InvalidBigInt>>()
+            .map_err(error_to_string)?;
+
+        // The genesis staged ledger is already there
+        if self
+            .staged_ledger_mut(root.staged_ledger_hashes())
+            .is_none()
+        {
+            let snarked_ledger_hash = root.snarked_ledger_hash().clone();
+            let snarked_ledger = self
+                .snarked_ledgers
+                .get(&snarked_ledger_hash)
+                .map(Mask::copy)
+                .ok_or_else(|| format!("snarked ledger {snarked_ledger_hash} is missing"))?;
+            let (_, staged_ledger) = staged_ledger_reconstruct(
+                snarked_ledger,
+                snarked_ledger_hash,
+                Some(Arc::new(parts)),
+            )
+            .map_err(error_to_string)?;
+            self.staged_ledger_reconstruct_result_store(staged_ledger?);
+
+            if self
+                .staged_ledger_mut(root.staged_ledger_hashes())
+                .is_none()
+            {
+                return Err(format!(
+                    "reconstructed staged ledger doesn't match the root {}",
+                    root.hash()
+                ));
+            }
+        }
+
+        for (pred_block, block) in best_chain.iter().zip(best_chain.iter().skip(1)) {
+            // Blocks were verified before being added to the stored frontier
+            self.block_apply(
+                block.block.clone(),
+                pred_block.clone(),
+                Some(SkipVerification::All),
+            )?;
+        }
+        self.staged_ledgers.extend(self.sync.staged_ledgers.take());
+
+        openmina_core::info!(openmina_core::log::system_time();
+            kind = "LedgerService::frontier_restore",
+            summary = format!("restored transition frontier, best tip {}, {}", best_tip.height(), best_tip.hash()),
+            root = format!("{}, {}", root.height(), root.hash()),
+        );
+
+        Ok(Some(RestoredFrontier {
+            best_chain,
+            needed_protocol_states,
+        }))
+    }
+
     #[allow(dead_code)]
     fn check_alive_masks(&mut self) {
         let mut alive: BTreeSet<_> = ::ledger::mask::alive_collect();
@@ -1082,6 +1301,19 @@ impl LedgerCtx {
         Some(accounts)
     }
 
+    /// Returns the whole ledger as a sparse ledger, for serving `get_epoch_ledger` rpc.
+    pub fn epoch_ledger(
+        &mut self,
+        ledger_hash: v2::LedgerHash,
+    ) -> Option<Arc<v2::MinaBaseSparseLedgerBaseStableV2>> {
+        let (mask, _) = self
+            .mask(&ledger_hash)
+            .filter(|(_, is_synced)| *is_synced)?;
+        let ids = mask.to_list().iter().map(Account::id).collect::<Vec<_>>();
+        let sparse_ledger = SparseLedger::of_ledger_subset_exn(mask, &ids);
+        Some(Arc::new((&sparse_ledger).into()))
+    }
+
     pub fn get_accounts(
         &mut self,
         ledger_hash: v2::LedgerHash,
@@ -1107,6 +1339,22 @@ impl LedgerCtx {
             .collect::<Vec<_>>()
     }
 
+    /// Returns the account owning `token_id`, or nothing if the token doesn't exist.
+    pub fn get_token_owner(
+        &mut self,
+        ledger_hash: v2::LedgerHash,
+        token_id: TokenId,
+    ) -> Vec<Account> {
+        let owner = self
+            .mask(&ledger_hash)
+            .and_then(|(mask, _)| mask.token_owner(token_id));
+
+        match owner {
+            Some(owner) => self.get_accounts(ledger_hash, vec![owner]),
+            None => Vec::new(),
+        }
+    }
+
     pub fn staged_ledger_aux_and_pending_coinbase(
         &mut self,
         ledger_hash: &MinaBaseStagedLedgerHashStableV1,
@@ -1505,6 +1753,16 @@ pub trait LedgerService: redux::Service {
             self.ledger_manager().call(request);
         }
     }
+
+    /// Stores the transition frontier on disk, if persistence is enabled.
+    fn frontier_persist(&mut self, update: FrontierUpdate) {
+        let request = LedgerRequest::FrontierPersist { update };
+        if self.force_sync_calls() {
+            let _ = self.ledger_manager().call_sync(request);
+        } else {
+            self.ledger_manager().call(request);
+        }
+    }
 }
 
 /// Save reconstruction to file, when it fails.
@@ -1571,6 +1829,129 @@ fn dump_reconstruct_to_file(
     Ok(())
 }
 
+/// Data the archive needs on top of the block itself, read from the staged
+/// ledger resulting from the block's application.
+fn block_archive_data(
+    staged_ledger: &StagedLedger,
+    block: &ArcBlockWithHash,
+) -> Result<BlockApplyResultArchive, String> {
+    let senders = block
+        .body()
+        .transactions()
+        .filter_map(|tx| UserCommand::try_from(tx).ok().map(|cmd| cmd.fee_payer()))
+        .collect::<BTreeSet<_>>()
+        .into_iter();
+
+    let consensus_state = &block.header().protocol_state.body.consensus_state;
+    let coinbase_receiver: CompressedPubKey = (&consensus_state.coinbase_receiver)
+        .try_into()
+        .map_err(error_to_string)?;
+    let coinbase_receiver_id = AccountId::new(coinbase_receiver, TokenId::default());
+
+    // https://github.com/MinaProtocol/mina/blob/85149735ca3a76d026e8cf36b8ff22941a048e31/src/app/archive/lib/diff.ml#L78
+    let (accessed, not_accessed): (BTreeSet<_>, BTreeSet<_>) = block
+        .body()
+        .tranasctions_with_status()
+        .flat_map(|(tx, status)| {
+            let status: TransactionStatus = status.into();
+            UserCommand::try_from(tx)
+                .ok()
+                .map(|cmd| cmd.account_access_statuses(&status))
+                .into_iter()
+                .flatten()
+        })
+        .partition(|(_, status)| *status == AccessedOrNot::Accessed);
+
+    let mut account_ids_accessed: BTreeSet<_> = accessed.into_iter().map(|(id, _)| id).collect();
+    let mut account_ids_not_accessed: BTreeSet<_> =
+        not_accessed.into_iter().map(|(id, _)| id).collect();
+
+    // Coinbase receiver is included only when the block has a coinbase transaction
+    // Note: If for whatever reason the network has set the coinbase amount to zero,
+    // to mimic the behavior of the ocaml node, we still include the coinbase receiver
+    // in the accessed accounts as a coinbase transaction is created regardless of the coinbase amount.
+    // https://github.com/MinaProtocol/mina/blob/b595a2bf00ae138d745737da628bd94bb2bd91e2/src/lib/staged_ledger/pre_diff_info.ml#L139
+    let has_coinbase = block.body().has_coinbase();
+
+    if has_coinbase {
+        account_ids_accessed.insert(coinbase_receiver_id);
+    } else {
+        account_ids_not_accessed.insert(coinbase_receiver_id);
+    }
+
+    // Include the coinbase fee transfer accounts
+    let fee_transfer_accounts = block.body().coinbase_fee_transfers_iter().filter_map(|cb| {
+        let receiver: CompressedPubKey = cb.receiver_pk.inner().try_into().ok()?;
+        let account_id = AccountId::new(receiver, TokenId::default());
+        Some(account_id)
+    });
+    account_ids_accessed.extend(fee_transfer_accounts);
+
+    // TODO(adonagy): Create a struct instead of tuple
+    let accounts_accessed: Vec<(AccountIndex, Account)> = account_ids_accessed
+        .iter()
+        .filter_map(|id| {
+            staged_ledger
+                .ledger()
+                .index_of_account(id.clone())
+                .and_then(|index| {
+                    staged_ledger
+                        .ledger()
+                        .get_at_index(index)
+                        .map(|account| (index, *account))
+                })
+        })
+        .collect();
+
+    let account_creation_fee = constraint_constants().account_creation_fee;
+
+    // TODO(adonagy): Create a struct instead of tuple
+    let accounts_created: Vec<(AccountId, u64)> = staged_ledger
+        .latest_block_accounts_created(block.pred_hash().to_field()?)
+        .iter()
+        .map(|id| (id.clone(), account_creation_fee))
+        .collect();
+
+    // A token is used regardless of txn status
+    // https://github.com/MinaProtocol/mina/blob/85149735ca3a76d026e8cf36b8ff22941a048e31/src/app/archive/lib/diff.ml#L114
+    let all_account_ids: BTreeSet<_> = account_ids_accessed
+        .union(&account_ids_not_accessed)
+        .collect();
+    let tokens_used: BTreeSet<(TokenId, Option<AccountId>)> = if has_coinbase {
+        all_account_ids
+            .iter()
+            .map(|id| {
+                let token_id = id.token_id.clone();
+                let token_owner = staged_ledger.ledger().token_owner(token_id.clone());
+                (token_id, token_owner)
+            })
+            .collect()
+    } else {
+        BTreeSet::new()
+    };
+
+    let sender_receipt_chains_from_parent_ledger = senders
+        .filter_map(|sender| {
+            if let Some(location) = staged_ledger.ledger().location_of_account(&sender) {
+                staged_ledger.ledger().get(location).map(|account| {
+                    (
+                        sender,
+                        v2::ReceiptChainHash::from(account.receipt_chain_hash),
+                    )
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    Ok(BlockApplyResultArchive {
+        accounts_accessed,
+        accounts_created,
+        tokens_used,
+        sender_receipt_chains_from_parent_ledger,
+    })
+}
+
 /// Save staged ledger and block to file, when the application fail.
 /// So we can easily reproduce the application both in Rust and OCaml, to compare them.
 /// - https://github.com/openmina/openmina/blob/8e68037aafddd43842a54c8439baeafee4c6e1eb/ledger/src/staged_ledger/staged_ledger.rs#L5959
@@ -1657,4 +2038,33 @@ mod tests {
             assert_eq!(hash.to_string(), expected_hash);
         });
     }
+
+    #[test]
+    fn test_epoch_ledger_roundtrip() {
+        let mut mask = Mask::create_with_token_owners(LEDGER_DEPTH);
+        for _ in 0..10 {
+            let account = Account::rand();
+            mask.get_or_create_account(account.id(), account).unwrap();
+        }
+        let hash = merkle_root(&mut mask);
+
+        let mut ctx = LedgerCtx::default();
+        ctx.snarked_ledgers.insert(hash.clone(), mask);
+        let ledger = ctx.epoch_ledger(hash.clone()).unwrap();
+
+        let mut other = LedgerCtx::default();
+        let wrong_hash = LedgerHash::zero();
+        assert_eq!(
+            other.epoch_ledger_set(wrong_hash.clone(), &ledger),
+            Ok(hash.clone())
+        );
+        assert!(other.pending_sync_snarked_ledger_mask(&wrong_hash).is_err());
+
+        assert_eq!(
+            other.epoch_ledger_set(hash.clone(), &ledger),
+            Ok(hash.clone())
+        );
+        let mut synced = other.pending_sync_snarked_ledger_mask(&hash).unwrap();
+        assert_eq!(merkle_root(&mut synced), hash);
+    }
 }
@@ -16,6 +16,8 @@ pub use ledger_state::*;
 
 mod ledger_reducer;
 
+mod ledger_persistence;
+
 mod ledger_service;
 pub use ledger_service::*;
 
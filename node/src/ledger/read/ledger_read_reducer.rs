@@ -74,31 +74,31 @@ impl LedgerReadState {
 
         match (request.request(), response) {
             (
-                LedgerReadRequest::DelegatorTable(ledger_hash, pub_key),
-                LedgerReadResponse::DelegatorTable(table),
+                LedgerReadRequest::DelegatorTable(ledger_hash, pub_keys),
+                LedgerReadResponse::DelegatorTable(tables),
             ) => {
                 let expected = state.block_producer.vrf_delegator_table_inputs();
-                if !expected.is_some_and(|(expected_hash, producer)| {
-                    ledger_hash == expected_hash && pub_key == producer
+                if !expected.is_some_and(|(expected_hash, producers)| {
+                    ledger_hash == expected_hash && pub_keys == producers
                 }) {
                     bug_condition!("delegator table unexpected");
                     return;
                 }
-                match table {
+                match tables {
                     None => {
                         // TODO(tizoc): Revise this, may be better to dispatch a different action here
                         // and avoid running the VRF evaluator altogether when we know that the
                         // table is empty.
                         dispatcher.push(
                             BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction {
-                                delegator_table: Default::default(),
+                                delegator_tables: Default::default(),
                             },
                         );
                     }
-                    Some(table) => {
+                    Some(tables) => {
                         dispatcher.push(
                             BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction {
-                                delegator_table: table.into(),
+                                delegator_tables: tables.into(),
                             },
                         );
                     }
@@ -173,6 +173,17 @@ impl LedgerReadState {
                     }
                 }
             }
+            (req, LedgerReadResponse::GetEpochLedger(resp)) => {
+                for (peer_id, id, _) in find_peers_with_ledger_rpc(state, req) {
+                    dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                        peer_id,
+                        id,
+                        response: resp
+                            .clone()
+                            .map(|ledger| Box::new(P2pRpcResponse::EpochLedger(ledger))),
+                    });
+                }
+            }
             (
                 LedgerReadRequest::ScanStateSummary(ledger_hash),
                 LedgerReadResponse::ScanStateSummary(scan_state),
@@ -211,6 +222,9 @@ impl LedgerReadState {
                     response: resp.clone(),
                 });
             }
+            (_, LedgerReadResponse::ArchiveBlocksRebuild(rpc_id, blocks)) => {
+                dispatcher.push(RpcAction::ArchiveBackfillSuccess { rpc_id, blocks });
+            }
         }
     }
 
@@ -254,6 +268,12 @@ impl LedgerReadState {
                         P2pRpcRequest::StagedLedgerAuxAndPendingCoinbasesAtBlock(block_hash) => {
                             build_staged_ledger_parts_request(state, block_hash)?
                         }
+                        // Requests for ledgers that aren't served were already
+                        // answered when received, the service replies with
+                        // nothing if the ledger is gone since then.
+                        P2pRpcRequest::EpochLedger(hash) => {
+                            LedgerReadRequest::GetEpochLedger(hash.clone())
+                        }
                         _ => return None,
                     };
 
@@ -374,6 +394,9 @@ fn find_peers_with_ledger_rpc(
                         .transition_frontier
                         .get_state_body(block_hash)
                         .is_some_and(|b| b.blockchain_state.staged_ledger_hash == data.ledger_hash),
+                    (LedgerReadRequest::GetEpochLedger(h1), P2pRpcRequest::EpochLedger(h2)) => {
+                        h1 == h2
+                    }
                     _ => false,
                 })
                 .map(|(peer_id, rpc_id, _)| (*peer_id, rpc_id, false));
@@ -12,14 +12,15 @@ use redux::Callback;
 
 mod ledger_read_reducer;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use mina_p2p_messages::v2;
 use serde::{Deserialize, Serialize};
 
 use crate::account::AccountPublicKey;
-use crate::block_producer::vrf_evaluator::DelegatorTable;
+use crate::block_producer::vrf_evaluator::DelegatorTables;
+use crate::ledger::write::BlockApplyResult;
 use crate::ledger::LedgerAddress;
 use crate::p2p::channels::rpc::StagedLedgerAuxAndPendingCoinbases;
 use crate::rpc::{AccountQuery, RpcScanStateSummaryScanStateJob};
@@ -32,27 +33,32 @@ pub enum LedgerReadKind {
     GetChildHashesAtAddr,
     GetChildAccountsAtAddr,
     GetStagedLedgerAuxAndPendingCoinbases,
+    GetEpochLedger,
     ScanStateSummary,
     AccountsForRpc,
     GetLedgerStatus,
     GetAccountDelegators,
+    ArchiveBlocksRebuild,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub enum LedgerReadRequest {
-    /// Delegator table requested by vrf state machine.
-    DelegatorTable(v2::LedgerHash, AccountPublicKey),
+    /// Delegator tables of our producers requested by vrf state machine.
+    DelegatorTable(v2::LedgerHash, BTreeSet<AccountPublicKey>),
     // p2p rpcs
     GetNumAccounts(v2::LedgerHash),
     GetAccounts(v2::LedgerHash, Vec<AccountId>, Option<RpcId>),
     GetChildHashesAtAddr(v2::LedgerHash, LedgerAddress),
     GetChildAccountsAtAddr(v2::LedgerHash, LedgerAddress),
     GetStagedLedgerAuxAndPendingCoinbases(LedgerReadStagedLedgerAuxAndPendingCoinbases),
+    GetEpochLedger(v2::LedgerHash),
     // rpcs
     ScanStateSummary(v2::MinaBaseStagedLedgerHashStableV1),
     AccountsForRpc(RpcId, v2::LedgerHash, AccountQuery),
     GetLedgerStatus(RpcId, v2::LedgerHash),
     GetAccountDelegators(RpcId, v2::LedgerHash, AccountId),
+    /// Rebuild archive data of frontier blocks, for resending them to the archive.
+    ArchiveBlocksRebuild(RpcId, Vec<AppliedBlock>),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -63,19 +69,21 @@ pub struct LedgerStatus {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum LedgerReadResponse {
-    /// Delegator table requested by vrf state machine.
-    DelegatorTable(Option<DelegatorTable>),
+    /// Delegator tables requested by vrf state machine.
+    DelegatorTable(Option<DelegatorTables>),
     // p2p rpcs
     GetNumAccounts(Option<(u64, v2::LedgerHash)>),
     GetAccounts(Vec<Account>, Option<RpcId>),
     GetChildHashesAtAddr(Option<(v2::LedgerHash, v2::LedgerHash)>),
     GetChildAccountsAtAddr(Option<Vec<v2::MinaBaseAccountBinableArgStableV2>>),
     GetStagedLedgerAuxAndPendingCoinbases(Option<Arc<StagedLedgerAuxAndPendingCoinbases>>),
+    GetEpochLedger(Option<Arc<v2::MinaBaseSparseLedgerBaseStableV2>>),
     // rpcs
     ScanStateSummary(Result<Vec<Vec<RpcScanStateSummaryScanStateJob>>, String>),
     AccountsForRpc(RpcId, Vec<Account>, AccountQuery),
     GetLedgerStatus(RpcId, Option<LedgerStatus>),
     GetAccountDelegators(RpcId, Option<Vec<Account>>),
+    ArchiveBlocksRebuild(RpcId, Vec<BlockApplyResult>),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -95,10 +103,12 @@ impl LedgerReadRequest {
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => {
                 LedgerReadKind::GetStagedLedgerAuxAndPendingCoinbases
             }
+            Self::GetEpochLedger(..) => LedgerReadKind::GetEpochLedger,
             Self::ScanStateSummary(..) => LedgerReadKind::ScanStateSummary,
             Self::AccountsForRpc(..) => LedgerReadKind::AccountsForRpc,
             Self::GetLedgerStatus(..) => LedgerReadKind::GetLedgerStatus,
             Self::GetAccountDelegators(..) => LedgerReadKind::GetAccountDelegators,
+            Self::ArchiveBlocksRebuild(..) => LedgerReadKind::ArchiveBlocksRebuild,
         }
     }
 
@@ -114,11 +124,14 @@ impl LedgerReadRequest {
             }
             Self::GetChildHashesAtAddr(..) => 1,
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => 100,
+            // whole ledger is serialized
+            Self::GetEpochLedger(..) => 200,
             Self::ScanStateSummary(..) => 100,
             // TODO(adonagy): not sure
             Self::AccountsForRpc(..) => 10,
             Self::GetLedgerStatus(..) => 1,
             Self::GetAccountDelegators(..) => 10,
+            Self::ArchiveBlocksRebuild(_, blocks) => blocks.len(),
         };
         cost.max(1)
     }
@@ -135,10 +148,12 @@ impl LedgerReadResponse {
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => {
                 LedgerReadKind::GetStagedLedgerAuxAndPendingCoinbases
             }
+            Self::GetEpochLedger(..) => LedgerReadKind::GetEpochLedger,
             Self::ScanStateSummary(..) => LedgerReadKind::ScanStateSummary,
             Self::AccountsForRpc(..) => LedgerReadKind::AccountsForRpc,
             Self::GetLedgerStatus(..) => LedgerReadKind::GetLedgerStatus,
             Self::GetAccountDelegators(..) => LedgerReadKind::GetAccountDelegators,
+            Self::ArchiveBlocksRebuild(..) => LedgerReadKind::ArchiveBlocksRebuild,
         }
     }
 }
@@ -171,5 +186,9 @@ pub enum LedgerReadInitCallback {
         callback: Callback<RequestId<RpcIdType>>,
         args: RequestId<RpcIdType>,
     },
+    RpcArchiveBackfillPending {
+        callback: Callback<RequestId<RpcIdType>>,
+        args: RequestId<RpcIdType>,
+    },
     None,
 }
@@ -3,8 +3,15 @@ use redux::Dispatcher;
 
 use crate::{
     ledger_effectful::LedgerEffectfulAction,
-    transition_frontier::sync::{
-        ledger::staged::TransitionFrontierSyncLedgerStagedAction, TransitionFrontierSyncAction,
+    transition_frontier::{
+        sync::{
+            ledger::{
+                snarked::TransitionFrontierSyncLedgerSnarkedAction,
+                staged::TransitionFrontierSyncLedgerStagedAction,
+            },
+            TransitionFrontierSyncAction,
+        },
+        TransitionFrontierAction,
     },
     Action, BlockProducerAction, State, Substate,
 };
@@ -56,6 +63,7 @@ impl LedgerWriteState {
                 dispatcher.push(TransitionFrontierSyncAction::BlocksNextApplyInit);
                 dispatcher.push(TransitionFrontierSyncAction::CommitInit);
                 dispatcher.push(TransitionFrontierSyncLedgerStagedAction::ReconstructInit);
+                dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetInit);
             }
         }
     }
@@ -166,6 +174,42 @@ impl LedgerWriteState {
                     dispatcher.push(TransitionFrontierSyncAction::CommitSuccess { result });
                 }
             }
+            (_, LedgerWriteResponse::FrontierRestore { result }) => match result {
+                Err(error) => {
+                    dispatcher.push(TransitionFrontierAction::RestoreError { error });
+                }
+                Ok(frontier) => {
+                    dispatcher.push(TransitionFrontierAction::RestoreSuccess { frontier });
+                }
+            },
+            (
+                _,
+                LedgerWriteResponse::EpochLedgerSet {
+                    snarked_ledger_hash,
+                    result,
+                },
+            ) => {
+                let Some(sender) = None.or_else(|| {
+                    let snarked = state.transition_frontier.sync.ledger()?.snarked()?;
+                    if snarked.ledger_hash() != &snarked_ledger_hash {
+                        return None;
+                    }
+                    Some(snarked.received_epoch_ledger()?.sender)
+                }) else {
+                    return;
+                };
+
+                // Ledger is only stored if its merkle root matches the target.
+                if result.is_ok_and(|computed_hash| computed_hash == snarked_ledger_hash) {
+                    dispatcher.push(
+                        TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerAccepted { sender },
+                    );
+                } else {
+                    dispatcher.push(
+                        TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerRejected { sender },
+                    );
+                }
+            }
         }
     }
 }
@@ -29,6 +29,8 @@ pub enum LedgerWriteKind {
     StagedLedgerDiffCreate,
     BlockApply,
     Commit,
+    FrontierRestore,
+    EpochLedgerSet,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -60,6 +62,14 @@ pub enum LedgerWriteRequest {
         new_root: AppliedBlock,
         new_best_tip: AppliedBlock,
     },
+    /// Restore the transition frontier stored by a previous run.
+    FrontierRestore,
+    /// Build the snarked ledger from an epoch ledger received from a peer.
+    /// It's only stored under `snarked_ledger_hash` if the hashes match.
+    EpochLedgerSet {
+        snarked_ledger_hash: v2::LedgerHash,
+        ledger: Arc<v2::MinaBaseSparseLedgerBaseStableV2>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -81,6 +91,15 @@ pub enum LedgerWriteResponse {
         best_tip_hash: v2::StateHash,
         result: CommitResult,
     },
+    FrontierRestore {
+        /// `None` if no transition frontier was stored.
+        result: Result<Option<RestoredFrontier>, String>,
+    },
+    EpochLedgerSet {
+        snarked_ledger_hash: v2::LedgerHash,
+        /// Merkle root of the ledger built from the received accounts.
+        result: Result<v2::LedgerHash, String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -279,6 +298,27 @@ pub struct CommitResult {
     pub needed_protocol_states: BTreeSet<v2::StateHash>,
 }
 
+/// Transition frontier loaded from disk, for which the ledgers were rebuilt.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RestoredFrontier {
+    pub best_chain: Vec<AppliedBlock>,
+    /// Protocol states needed by the root scan state.
+    pub needed_protocol_states: BTreeMap<v2::StateHash, v2::MinaStateProtocolStateValueStableV2>,
+}
+
+/// Changes of the transition frontier to write to disk.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FrontierUpdate {
+    pub root: AppliedBlock,
+    pub best_tip_hash: v2::StateHash,
+    /// Blocks of the best chain which weren't in the previous one.
+    pub new_blocks: Vec<AppliedBlock>,
+    /// Protocol states needed by the root scan state, set when the root
+    /// changed.
+    pub needed_protocol_states:
+        Option<BTreeMap<v2::StateHash, v2::MinaStateProtocolStateValueStableV2>>,
+}
+
 impl LedgerWriteRequest {
     pub fn kind(&self) -> LedgerWriteKind {
         match self {
@@ -286,6 +326,8 @@ impl LedgerWriteRequest {
             Self::StagedLedgerDiffCreate { .. } => LedgerWriteKind::StagedLedgerDiffCreate,
             Self::BlockApply { .. } => LedgerWriteKind::BlockApply,
             Self::Commit { .. } => LedgerWriteKind::Commit,
+            Self::FrontierRestore => LedgerWriteKind::FrontierRestore,
+            Self::EpochLedgerSet { .. } => LedgerWriteKind::EpochLedgerSet,
         }
     }
 }
@@ -297,6 +339,8 @@ impl LedgerWriteResponse {
             Self::StagedLedgerDiffCreate { .. } => LedgerWriteKind::StagedLedgerDiffCreate,
             Self::BlockApply { .. } => LedgerWriteKind::BlockApply,
             Self::Commit { .. } => LedgerWriteKind::Commit,
+            Self::FrontierRestore { .. } => LedgerWriteKind::FrontierRestore,
+            Self::EpochLedgerSet { .. } => LedgerWriteKind::EpochLedgerSet,
         }
     }
 }
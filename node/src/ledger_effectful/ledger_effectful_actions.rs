@@ -1,6 +1,6 @@
 use crate::ledger::{
     read::{LedgerReadIdType, LedgerReadInitCallback, LedgerReadRequest},
-    write::LedgerWriteRequest,
+    write::{FrontierUpdate, LedgerWriteRequest},
 };
 use openmina_core::requests::RequestId;
 use redux::Callback;
@@ -17,6 +17,9 @@ pub enum LedgerEffectfulAction {
         callback: LedgerReadInitCallback,
         id: RequestId<LedgerReadIdType>,
     },
+    FrontierPersist {
+        update: FrontierUpdate,
+    },
 }
 
 impl redux::EnablingCondition<crate::State> for LedgerEffectfulAction {
@@ -49,8 +49,14 @@ pub fn ledger_effectful_effects<S>(
                 LedgerReadInitCallback::RpcLedgerAccountDelegatorsGetPending { callback, args } => {
                     store.dispatch_callback(callback, args);
                 }
+                LedgerReadInitCallback::RpcArchiveBackfillPending { callback, args } => {
+                    store.dispatch_callback(callback, args);
+                }
                 LedgerReadInitCallback::None => {}
             }
         }
+        LedgerEffectfulAction::FrontierPersist { update } => {
+            store.service.frontier_persist(update);
+        }
     }
 }
@@ -79,6 +79,10 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
                 P2pChannelsAction::StreamingRpc(action) => action.action_event(&context),
             },
             P2pAction::Peer(action) => action.action_event(&context),
+            P2pAction::Trust(action) => action.action_event(&context),
+            P2pAction::AddressBook(action) => action.action_event(&context),
+            P2pAction::Bandwidth(action) => action.action_event(&context),
+            P2pAction::Nat(action) => action.action_event(&context),
             P2pAction::Network(action) => match action {
                 P2pNetworkAction::Scheduler(action) => match action {
                     // MioErrors in scheduler are logged using debug instead of warn, to prevent spam
@@ -99,10 +103,13 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
                 P2pNetworkAction::Select(action) => action.action_event(&context),
                 P2pNetworkAction::Noise(action) => action.action_event(&context),
                 P2pNetworkAction::Yamux(action) => action.action_event(&context),
+                P2pNetworkAction::Quic(action) => action.action_event(&context),
                 P2pNetworkAction::Rpc(action) => action.action_event(&context),
                 P2pNetworkAction::Kad(action) => action.action_event(&context),
                 P2pNetworkAction::Pubsub(action) => action.action_event(&context),
                 P2pNetworkAction::Identify(action) => action.action_event(&context),
+                P2pNetworkAction::Autonat(action) => action.action_event(&context),
+                P2pNetworkAction::WebrtcSignal(action) => action.action_event(&context),
             },
         },
         Action::P2pEffectful(action) => match action {
@@ -113,6 +120,9 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
             },
             p2p::P2pEffectfulAction::Disconnection(action) => action.action_event(&context),
             p2p::P2pEffectfulAction::Network(action) => action.action_event(&context),
+            p2p::P2pEffectfulAction::Trust(action) => action.action_event(&context),
+            p2p::P2pEffectfulAction::AddressBook(action) => action.action_event(&context),
+            p2p::P2pEffectfulAction::Nat(action) => action.action_event(&context),
             p2p::P2pEffectfulAction::Initialize => {}
         },
         Action::ExternalSnarkWorker(action) => action.action_event(&context),
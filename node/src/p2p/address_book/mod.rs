@@ -0,0 +1,3 @@
+pub use ::p2p::address_book::*;
+
+mod p2p_address_book_actions;
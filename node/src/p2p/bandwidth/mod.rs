@@ -0,0 +1,3 @@
+pub use ::p2p::bandwidth::*;
+
+mod p2p_bandwidth_actions;
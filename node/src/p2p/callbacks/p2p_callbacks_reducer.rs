@@ -1,17 +1,23 @@
 use ark_ff::fields::arithmetic::InvalidBigInt;
 use mina_p2p_messages::{
     gossip::GossipNetMessageV2,
-    v2::{MinaLedgerSyncLedgerAnswerStableV2, StateHash},
+    list::List,
+    v2::{MinaBaseStateBodyHashStableV1, MinaLedgerSyncLedgerAnswerStableV2, StateHash},
 };
 use openmina_core::{
-    block::{prevalidate::BlockPrevalidationError, BlockWithHash},
-    bug_condition, log,
+    block::{prevalidate::BlockPrevalidationError, AppliedBlock, BlockWithHash},
+    bug_condition,
+    consensus::consensus_take,
+    log,
     transaction::TransactionWithHash,
 };
 use p2p::{
     channels::{
         best_tip::P2pChannelsBestTipAction,
-        rpc::{BestTipWithProof, P2pChannelsRpcAction, P2pRpcRequest, P2pRpcResponse},
+        rpc::{
+            BestTipWithProof, P2pChannelsRpcAction, P2pRpcRequest, P2pRpcResponse,
+            TransitionChainProof,
+        },
         streaming_rpc::P2pStreamingRpcResponseFull,
     },
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
@@ -91,6 +97,13 @@ impl crate::State {
                     return;
                 };
 
+                dispatcher.push(
+                    TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerError {
+                        peer_id,
+                        rpc_id,
+                        error: PeerLedgerQueryError::Timeout,
+                    },
+                );
                 dispatcher.push(
                     TransitionFrontierSyncLedgerSnarkedAction::PeerQueryNumAccountsError {
                         peer_id,
@@ -227,6 +240,19 @@ impl crate::State {
                             );
                         });
 
+                    if let Some(rpc_id) = s
+                        .snarked()
+                        .and_then(|s| s.peer_epoch_ledger_rpc_id(&peer_id))
+                    {
+                        dispatcher.push(
+                            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerError {
+                                peer_id,
+                                rpc_id,
+                                error: PeerLedgerQueryError::Disconnected,
+                            },
+                        );
+                    }
+
                     if let Some(rpc_id) = s
                         .snarked()
                         .and_then(|s| s.peer_num_accounts_rpc_id(&peer_id))
@@ -386,24 +412,9 @@ impl crate::State {
     ) {
         match request {
             P2pRpcRequest::BestTipWithProof => {
-                let best_chain = &state.transition_frontier.best_chain;
                 let response = None.or_else(|| {
-                    let best_tip = best_chain.last()?;
-                    let mut chain_iter = best_chain.iter();
-                    let root_block = chain_iter.next()?;
-                    // TODO(binier): cache body hashes
-                    let Ok(body_hashes) = chain_iter
-                        .map(|b| b.header().protocol_state.body.try_hash())
-                        .collect::<Result<_, _>>()
-                    else {
-                        openmina_core::error!(meta.time(); "P2pRpcRequest::BestTipWithProof: invalid protocol state");
-                        return None;
-                    };
-
-                    Some(BestTipWithProof {
-                        best_tip: best_tip.block().clone(),
-                        proof: (body_hashes, root_block.block().clone()),
-                    })
+                    let best_tip = state.transition_frontier.best_tip()?;
+                    best_tip_with_proof(state, meta, best_tip.hash())
                 });
                 let response = response.map(P2pRpcResponse::BestTipWithProof).map(Box::new);
                 dispatcher.push(P2pChannelsRpcAction::ResponseSend {
@@ -412,6 +423,48 @@ impl crate::State {
                     response,
                 });
             }
+            P2pRpcRequest::Ancestry(consensus_state, hash) => {
+                // Same as the OCaml daemon, answer with our best tip only
+                // if it is better than the tip of the requester.
+                let response = None
+                    .or_else(|| {
+                        let best_tip = state.transition_frontier.best_tip()?;
+                        let is_better = consensus_take(
+                            &consensus_state,
+                            best_tip.consensus_state(),
+                            &hash,
+                            best_tip.hash(),
+                        );
+                        if !is_better {
+                            return None;
+                        }
+                        best_tip_with_proof(state, meta, best_tip.hash())
+                    })
+                    .map(P2pRpcResponse::Ancestry)
+                    .map(Box::new);
+                dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                    peer_id,
+                    id,
+                    response,
+                });
+            }
+            P2pRpcRequest::TransitionChainProof(hash) => {
+                let response = None
+                    .or_else(|| {
+                        let (root_block, body_hashes) = best_chain_proof(state, meta, &hash)?;
+                        Some(TransitionChainProof {
+                            init_state_hash: root_block.hash().clone(),
+                            body_hashes,
+                        })
+                    })
+                    .map(P2pRpcResponse::TransitionChainProof)
+                    .map(Box::new);
+                dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                    peer_id,
+                    id,
+                    response,
+                });
+            }
             P2pRpcRequest::Block(hash) => {
                 let best_chain = &state.transition_frontier.best_chain;
                 let response = best_chain
@@ -435,6 +488,18 @@ impl crate::State {
                 // async ledger request will be triggered
                 // by `LedgerReadAction::FindTodos`.
             }
+            P2pRpcRequest::EpochLedger(hash) => {
+                // async ledger request will be triggered
+                // by `LedgerReadAction::FindTodos`, unknown ledgers are
+                // answered right away so the peer doesn't wait for a timeout.
+                if !state.transition_frontier.is_epoch_ledger_served(&hash) {
+                    dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                        peer_id,
+                        id,
+                        response: None,
+                    });
+                }
+            }
             P2pRpcRequest::Transaction(hash) => {
                 let tx = state.transaction_pool.get(&hash);
                 let response = tx
@@ -465,11 +530,15 @@ impl crate::State {
             }
             P2pRpcRequest::InitialPeers => {
                 let p2p = p2p_ready!(state.p2p, meta.time());
-                let peers = p2p
-                    .peers
-                    .iter()
-                    .filter_map(|(_, v)| v.dial_opts.clone())
-                    .collect();
+                let peers = if p2p.config.access.isolate {
+                    // isolated node doesn't advertise its peers
+                    Default::default()
+                } else {
+                    p2p.peers
+                        .iter()
+                        .filter_map(|(_, v)| v.dial_opts.clone())
+                        .collect()
+                };
                 let response = Some(Box::new(P2pRpcResponse::InitialPeers(peers)));
 
                 dispatcher.push(P2pChannelsRpcAction::ResponseSend {
@@ -507,6 +576,13 @@ impl crate::State {
                     _ => {}
                 }
 
+                dispatcher.push(
+                    TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerError {
+                        peer_id,
+                        rpc_id: id,
+                        error: PeerLedgerQueryError::DataUnavailable,
+                    },
+                );
                 dispatcher.push(
                     TransitionFrontierSyncLedgerSnarkedAction::PeerQueryNumAccountsError {
                         peer_id,
@@ -534,7 +610,7 @@ impl crate::State {
                     error: PeerBlockFetchError::DataUnavailable,
                 });
             }
-            Some(P2pRpcResponse::BestTipWithProof(resp)) => {
+            Some(P2pRpcResponse::BestTipWithProof(resp) | P2pRpcResponse::Ancestry(resp)) => {
                 let (body_hashes, root_block) = &resp.proof;
 
                 let (Ok(best_tip), Ok(root_block)) = (
@@ -653,11 +729,62 @@ impl crate::State {
                     work: snark.clone(),
                 });
             }
+            Some(P2pRpcResponse::EpochLedger(ledger)) => {
+                dispatcher.push(
+                    TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerSuccess {
+                        peer_id,
+                        rpc_id: id,
+                        ledger: ledger.clone(),
+                    },
+                );
+            }
             Some(P2pRpcResponse::InitialPeers(_)) => {}
+            Some(P2pRpcResponse::TransitionChainProof(_)) => {}
         }
     }
 }
 
+/// Body hashes of the blocks in our best chain after the root, up to and
+/// including the block with `hash`. Proves that the block is a descendant
+/// of the transition frontier root.
+fn best_chain_proof<'a>(
+    state: &'a State,
+    meta: ActionMeta,
+    hash: &StateHash,
+) -> Option<(&'a AppliedBlock, List<MinaBaseStateBodyHashStableV1>)> {
+    let best_chain = &state.transition_frontier.best_chain;
+    let index = best_chain.iter().position(|b| b.hash() == hash)?;
+    let mut chain_iter = best_chain[..=index].iter();
+    let root_block = chain_iter.next()?;
+    // TODO(binier): cache body hashes
+    let Ok(body_hashes) = chain_iter
+        .map(|b| b.header().protocol_state.body.try_hash())
+        .collect::<Result<_, _>>()
+    else {
+        openmina_core::error!(meta.time(); "best_chain_proof: invalid protocol state");
+        return None;
+    };
+    Some((root_block, body_hashes))
+}
+
+fn best_tip_with_proof(
+    state: &State,
+    meta: ActionMeta,
+    hash: &StateHash,
+) -> Option<BestTipWithProof> {
+    let block = state
+        .transition_frontier
+        .best_chain
+        .iter()
+        .rev()
+        .find(|b| b.hash() == hash)?;
+    let (root_block, body_hashes) = best_chain_proof(state, meta, hash)?;
+    Some(BestTipWithProof {
+        best_tip: block.block().clone(),
+        proof: (body_hashes, root_block.block().clone()),
+    })
+}
+
 enum PreValidationResult {
     Continue,
     Reject { reason: String },
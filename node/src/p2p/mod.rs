@@ -4,11 +4,15 @@ use p2p::{
     network::identify::stream_effectful::P2pNetworkIdentifyStreamEffectfulAction,
 };
 
+pub mod address_book;
+pub mod bandwidth;
 pub mod channels;
 pub mod connection;
 pub mod disconnection;
+pub mod nat;
 pub mod network;
 pub mod peer;
+pub mod trust;
 
 pub mod callbacks;
 
@@ -94,6 +98,10 @@ impl_into_global_action!(connection::outgoing::P2pConnectionOutgoingAction);
 impl_into_global_action!(connection::incoming::P2pConnectionIncomingAction);
 
 impl_into_global_action!(disconnection::P2pDisconnectionAction);
+impl_into_global_action!(trust::P2pTrustAction);
+impl_into_global_action!(address_book::P2pAddressBookAction);
+impl_into_global_action!(bandwidth::P2pBandwidthAction);
+impl_into_global_action!(nat::P2pNatAction);
 
 impl_into_global_action!(network::P2pNetworkSchedulerAction);
 impl_into_global_action!(network::kad::P2pNetworkKademliaAction);
@@ -113,6 +121,9 @@ impl_into_global_action!(p2p::P2pNetworkKademliaStreamAction);
 impl_into_global_action!(p2p::P2pNetworkKadRequestAction);
 impl_into_global_action!(p2p::P2pNetworkKadBootstrapAction);
 impl_into_global_action!(p2p::P2pNetworkYamuxAction);
+impl_into_global_action!(p2p::P2pNetworkQuicAction);
+impl_into_global_action!(p2p::P2pNetworkAutonatAction);
+impl_into_global_action!(p2p::P2pNetworkWebrtcSignalAction);
 impl_into_global_action!(p2p::peer::P2pPeerAction);
 impl_into_global_action!(p2p::network::identify::stream::P2pNetworkIdentifyStreamAction);
 impl_into_global_action!(p2p::identify::P2pIdentifyAction);
@@ -127,6 +138,9 @@ impl_into_global_action!(effectful p2p::P2pNetworkPnetEffectfulAction);
 impl_into_global_action!(effectful connection::incoming_effectful::P2pConnectionIncomingEffectfulAction);
 impl_into_global_action!(effectful connection::outgoing_effectful::P2pConnectionOutgoingEffectfulAction);
 impl_into_global_action!(effectful p2p::disconnection_effectful::P2pDisconnectionEffectfulAction);
+impl_into_global_action!(effectful p2p::trust_effectful::P2pTrustEffectfulAction);
+impl_into_global_action!(effectful p2p::address_book_effectful::P2pAddressBookEffectfulAction);
+impl_into_global_action!(effectful p2p::nat_effectful::P2pNatEffectfulAction);
 impl_into_global_action!(effectful network::pubsub::P2pNetworkPubsubEffectfulAction);
 impl_into_global_action!(effectful P2pNetworkIdentifyStreamEffectfulAction);
 impl_into_global_action!(effectful P2pChannelsEffectfulAction);
@@ -0,0 +1,3 @@
+pub use ::p2p::nat::*;
+
+mod p2p_nat_actions;
@@ -30,6 +30,24 @@ impl redux::EnablingCondition<crate::State> for P2pNetworkYamuxAction {
     }
 }
 
+impl redux::EnablingCondition<crate::State> for P2pNetworkQuicAction {
+    fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
+        state.p2p.is_enabled(self, time)
+    }
+}
+
+impl redux::EnablingCondition<crate::State> for P2pNetworkAutonatAction {
+    fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
+        state.p2p.is_enabled(self, time)
+    }
+}
+
+impl redux::EnablingCondition<crate::State> for P2pNetworkWebrtcSignalAction {
+    fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
+        state.p2p.is_enabled(self, time)
+    }
+}
+
 impl redux::EnablingCondition<crate::State> for P2pNetworkRpcAction {
     fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
         state.p2p.is_enabled(self, time)
@@ -0,0 +1,3 @@
+pub use ::p2p::trust::*;
+
+mod p2p_trust_actions;
@@ -1,5 +1,6 @@
 mod rpc_state;
 use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::str::FromStr;
 
 use ark_ff::fields::arithmetic::InvalidBigInt;
@@ -7,7 +8,7 @@ use ledger::scan_state::currency::{Amount, Balance, Fee, Nonce, Slot};
 use ledger::scan_state::transaction_logic::signed_command::SignedCommandPayload;
 use ledger::scan_state::transaction_logic::{signed_command, valid, Memo};
 use ledger::transaction_pool::{diff, ValidCommandWithHash};
-use ledger::{Account, AccountId};
+use ledger::{Account, AccountId, AccountIndex};
 use mina_p2p_messages::bigint::BigInt;
 use mina_p2p_messages::v2::{
     LedgerHash, MinaBaseSignedCommandPayloadBodyStableV2, MinaBaseSignedCommandStableV2,
@@ -19,8 +20,10 @@ use mina_p2p_messages::v2::{
 use openmina_core::block::{AppliedBlock, ArcBlockWithHash};
 use openmina_core::consensus::{ConsensusConstants, ConsensusTime};
 use openmina_node_account::AccountPublicKey;
+use p2p::bandwidth::{P2pBandwidthKind, P2pBandwidthSummary};
 use p2p::bootstrap::P2pNetworkKadBootstrapStats;
 pub use rpc_state::*;
+use vrf::{VrfEvaluation, VrfMessage};
 
 mod rpc_actions;
 pub use rpc_actions::*;
@@ -42,6 +45,7 @@ use openmina_core::snark::SnarkJobId;
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
+use crate::block_producer::BlockProducerWonSlotDiscardReason;
 use crate::external_snark_worker::{
     ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkSpecError,
 };
@@ -54,9 +58,12 @@ use crate::service::Queues;
 use crate::snark_pool::{JobCommitment, JobState, JobSummary};
 use crate::stats::actions::{ActionStatsForBlock, ActionStatsSnapshot};
 use crate::stats::block_producer::{
-    BlockProductionAttempt, BlockProductionAttemptWonSlot, VrfEvaluatorStats,
+    BlockProducerKeyStats, BlockProductionAttempt, BlockProductionAttemptWonSlot, VrfEvaluatorStats,
 };
+use crate::stats::block_producer::{BlockProducerStats, BlockProductionStatus};
 use crate::stats::sync::SyncStatsSnapshot;
+use crate::transition_frontier::archive::archive_service::ArchiveSinkStatus;
+use crate::transition_frontier::sync::{SyncPhase, TransitionFrontierSyncState};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RpcRequest {
@@ -66,10 +73,16 @@ pub enum RpcRequest {
     ActionStatsGet(ActionStatsQuery),
     SyncStatsGet(SyncStatsQuery),
     BlockProducerStatsGet,
+    // Won slots of the current and the next epoch, with their outcomes.
+    BlockProducerScheduleGet,
     MessageProgressGet,
     PeersGet,
     P2pConnectionOutgoing(P2pConnectionOutgoingInitOpts),
     P2pConnectionIncoming(P2pConnectionIncomingInitOpts),
+    P2pBansGet,
+    P2pBan(RpcP2pBanQuery),
+    P2pUnban(PeerId),
+    P2pBandwidthGet,
     ScanStateSummaryGet(RpcScanStateSummaryGetQuery),
     SnarkPoolGet,
     SnarkPoolJobGet { job_id: SnarkJobId },
@@ -97,6 +110,12 @@ pub enum RpcRequest {
     ConsensusTimeGet(ConsensusTimeQuery),
     LedgerStatusGet(LedgerHash),
     LedgerAccountDelegatorsGet(LedgerHash, AccountId),
+    ArchiveStatusGet,
+    ArchiveBackfill(RpcArchiveBackfillQuery),
+    // Stream of `RpcTransitionFrontierEvent`s, until the receiver is dropped.
+    TransitionFrontierSubscribe,
+    // Evaluates the VRF with the secret key of the given public key, if the node holds it.
+    VrfEvaluate(AccountPublicKey, VrfMessage),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -196,6 +215,7 @@ pub struct RpcPeerInfo {
     pub incoming: bool,
     pub is_libp2p: bool,
     pub time: u64,
+    pub bandwidth: P2pBandwidthSummary,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -370,6 +390,7 @@ pub type RpcHeartbeatGetResponse = Option<SignedNodeHeartbeat>;
 pub type RpcActionStatsGetResponse = Option<ActionStatsResponse>;
 pub type RpcSyncStatsGetResponse = Option<Vec<SyncStatsSnapshot>>;
 pub type RpcBlockProducerStatsGetResponse = Option<RpcBlockProducerStats>;
+pub type RpcBlockProducerScheduleGetResponse = Option<RpcBlockProducerSchedule>;
 pub type RpcPeersGetResponse = Vec<RpcPeerInfo>;
 pub type RpcP2pConnectionOutgoingResponse = Result<(), String>;
 pub type RpcScanStateSummaryGetResponse = Result<RpcScanStateSummary, String>;
@@ -391,6 +412,7 @@ pub type RpcGenesisBlockResponse = Option<ArcBlockWithHash>;
 pub type RpcConsensusTimeGetResponse = Option<ConsensusTime>;
 pub type RpcLedgerStatusGetResponse = Option<LedgerStatus>;
 pub type RpcLedgerAccountDelegatorsGetResponse = Option<Vec<Account>>;
+pub type RpcVrfEvaluateResponse = Option<VrfEvaluation>;
 
 #[derive(Serialize, Deserialize, Debug, Clone, strum_macros::Display)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
@@ -520,6 +542,7 @@ pub struct RpcNodeStatus {
     pub network_info: RpcNodeStatusNetworkInfo,
     pub block_producer: Option<AccountPublicKey>,
     pub coinbase_receiver: Option<AccountPublicKey>,
+    pub archive_sinks: Option<Vec<ArchiveSinkStatus>>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -586,12 +609,114 @@ pub struct RpcBlockProducerStats {
     pub epoch_start: Option<u32>,
     pub epoch_end: Option<u32>,
     pub public_key: AccountPublicKey,
+    pub producers: Vec<RpcBlockProducerKeyStats>,
     pub attempts: Vec<BlockProductionAttempt>,
     pub future_won_slots: Vec<BlockProductionAttemptWonSlot>,
     pub current_epoch_vrf_stats: Option<VrfEvaluatorStats>,
     pub vrf_stats: BTreeMap<u32, VrfEvaluatorStats>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerKeyStats {
+    pub public_key: AccountPublicKey,
+    pub coinbase_receiver: AccountPublicKey,
+    pub future_won_slots: usize,
+    #[serde(flatten)]
+    pub stats: BlockProducerKeyStats,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerSchedule {
+    pub current_time: redux::Timestamp,
+    pub current_global_slot: Option<u32>,
+    pub current_epoch: Option<u32>,
+    pub slots: Vec<RpcBlockProducerScheduledSlot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerScheduledSlot {
+    pub global_slot: u32,
+    pub epoch: u32,
+    /// Expected wall-clock time of the slot.
+    pub slot_time: redux::Timestamp,
+    pub producer: AccountPublicKey,
+    pub delegator: AccountPublicKey,
+    pub delegator_index: AccountIndex,
+    /// Base58 encoded vrf output.
+    pub vrf_output: String,
+    pub vrf_value: f64,
+    pub vrf_threshold: Option<f64>,
+    #[serde(flatten)]
+    pub outcome: RpcBlockProducerSlotOutcome,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "outcome")]
+pub enum RpcBlockProducerSlotOutcome {
+    Upcoming,
+    /// Block production for the slot is in progress, or the produced
+    /// block hasn't been observed on the best chain yet.
+    Producing,
+    Canonical {
+        block_hash: StateHash,
+        confirmations: u32,
+    },
+    Orphaned {
+        block_hash: StateHash,
+        orphaned_by: StateHash,
+    },
+    Discarded {
+        reason: BlockProducerWonSlotDiscardReason,
+    },
+    Missed,
+    /// Slot has passed, but its outcome isn't known, since block
+    /// production stats aren't collected.
+    Unknown,
+}
+
+impl RpcBlockProducerSlotOutcome {
+    pub fn new(
+        global_slot: u32,
+        best_tip_global_slot: u32,
+        stats: Option<&BlockProducerStats>,
+    ) -> Self {
+        let Some(stats) = stats else {
+            return if global_slot < best_tip_global_slot {
+                Self::Unknown
+            } else {
+                Self::Upcoming
+            };
+        };
+        let Some(attempt) = stats.attempt_for_slot(global_slot) else {
+            return if global_slot < best_tip_global_slot {
+                Self::Missed
+            } else {
+                Self::Upcoming
+            };
+        };
+        match (&attempt.status, &attempt.block) {
+            (
+                BlockProductionStatus::Canonical {
+                    last_observed_confirmations,
+                },
+                Some(block),
+            ) => Self::Canonical {
+                block_hash: block.hash.clone(),
+                confirmations: *last_observed_confirmations,
+            },
+            (BlockProductionStatus::Orphaned { orphaned_by }, Some(block)) => Self::Orphaned {
+                block_hash: block.hash.clone(),
+                orphaned_by: orphaned_by.clone(),
+            },
+            (BlockProductionStatus::Discarded { discard_reason }, _) => Self::Discarded {
+                reason: *discard_reason,
+            },
+            (BlockProductionStatus::Missed, _) => Self::Missed,
+            _ => Self::Producing,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RpcSnarkerConfig {
     pub public_key: NonZeroCurvePoint,
@@ -661,6 +786,127 @@ impl From<&MinaBaseUserCommandStableV2> for RpcScanStateSummaryBlockTransactionK
     }
 }
 
+pub type RpcArchiveStatusGetResponse = Option<Vec<ArchiveSinkStatus>>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcArchiveBackfillQuery {
+    pub from_height: u32,
+    pub to_height: u32,
+}
+
+impl RpcArchiveBackfillQuery {
+    /// Maximum number of heights in a single backfill request.
+    pub const MAX_HEIGHTS: u32 = 1000;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.from_height > self.to_height {
+            return Err("from_height must not be greater than to_height".to_owned());
+        }
+        if self.to_height - self.from_height >= Self::MAX_HEIGHTS {
+            return Err(format!(
+                "at most {} heights can be backfilled at once",
+                Self::MAX_HEIGHTS
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcArchiveBackfill {
+    /// Heights of the blocks that were rebuilt and resent to the archive sinks.
+    pub resent: Vec<u32>,
+    /// Heights in the requested range for which no block could be rebuilt,
+    /// because they are not in the transition frontier (anymore).
+    pub unavailable: Vec<u32>,
+}
+
+/// `None` if the node doesn't run in archive mode.
+pub type RpcArchiveBackfillResponse = Option<RpcArchiveBackfill>;
+
+/// Event sent to the subscribers of [`RpcRequest::TransitionFrontierSubscribe`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RpcTransitionFrontierEvent {
+    NewBestTip(AppliedBlock),
+    /// Sent on subscription and whenever the sync status changes.
+    SyncStatus(RpcSyncStatus),
+    /// New best tip isn't a descendant of the previous one.
+    ChainReorganization {
+        old_best_tip: StateHash,
+        new_best_tip: StateHash,
+    },
+}
+
+/// Sync status of the node, with the same states as the OCaml node's `SyncStatus`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcSyncStatus {
+    /// Not synced and no peer connected yet.
+    Connecting,
+    /// Connected to peers, waiting for a best tip to sync to.
+    Listening,
+    /// Was synced, but no peer is connected anymore.
+    Offline,
+    Bootstrap,
+    Catchup,
+    Synced,
+}
+
+impl RpcSyncStatus {
+    pub fn new(sync: &TransitionFrontierSyncState, has_ready_peers: bool) -> Self {
+        match sync {
+            TransitionFrontierSyncState::Idle if has_ready_peers => Self::Listening,
+            TransitionFrontierSyncState::Idle => Self::Connecting,
+            TransitionFrontierSyncState::Synced { .. } if !has_ready_peers => Self::Offline,
+            sync => match sync.sync_phase() {
+                SyncPhase::Bootstrap => Self::Bootstrap,
+                SyncPhase::Catchup => Self::Catchup,
+                SyncPhase::Synced => Self::Synced,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcP2pBanQuery {
+    pub peer_id: PeerId,
+    /// Ban duration in seconds. If not set, it grows with repeated offences
+    /// of the peer.
+    pub duration_secs: Option<u64>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcP2pBan {
+    pub peer_id: PeerId,
+    /// Addresses the peer was connected from, banned together with it.
+    pub ips: Vec<IpAddr>,
+    pub since: Timestamp,
+    pub until: Timestamp,
+    pub reason: String,
+    /// Number of times the peer was banned.
+    pub offences: u32,
+}
+
+pub type RpcP2pBansGetResponse = Vec<RpcP2pBan>;
+pub type RpcP2pBanResponse = Result<(), String>;
+pub type RpcP2pUnbanResponse = Result<(), String>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcP2pBandwidth {
+    pub total: P2pBandwidthSummary,
+    pub kinds: BTreeMap<P2pBandwidthKind, P2pBandwidthSummary>,
+    pub peers: Vec<RpcP2pPeerBandwidth>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcP2pPeerBandwidth {
+    pub peer_id: PeerId,
+    pub total: P2pBandwidthSummary,
+    pub kinds: BTreeMap<P2pBandwidthKind, P2pBandwidthSummary>,
+}
+
+pub type RpcP2pBandwidthGetResponse = Option<RpcP2pBandwidth>;
+
 pub type RpcHealthCheckResponse = Result<(), String>;
 pub type RpcReadinessCheckResponse = Result<(), String>;
 
@@ -773,3 +1019,67 @@ pub mod discovery {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_status() {
+        let idle = TransitionFrontierSyncState::Idle;
+        let synced = TransitionFrontierSyncState::Synced {
+            time: Timestamp::ZERO,
+        };
+
+        assert_eq!(RpcSyncStatus::new(&idle, false), RpcSyncStatus::Connecting);
+        assert_eq!(RpcSyncStatus::new(&idle, true), RpcSyncStatus::Listening);
+        assert_eq!(RpcSyncStatus::new(&synced, false), RpcSyncStatus::Offline);
+        assert_eq!(RpcSyncStatus::new(&synced, true), RpcSyncStatus::Synced);
+    }
+
+    #[test]
+    fn block_producer_slot_outcome() {
+        use crate::stats::block_producer::tests::won_slot;
+
+        let outcome = RpcBlockProducerSlotOutcome::new;
+
+        // Outcomes of passed slots are unknown without stats.
+        assert!(matches!(
+            outcome(5, 10, None),
+            RpcBlockProducerSlotOutcome::Unknown
+        ));
+        assert!(matches!(
+            outcome(10, 10, None),
+            RpcBlockProducerSlotOutcome::Upcoming
+        ));
+
+        let mut stats = BlockProducerStats::default();
+        stats.scheduled(Timestamp::ZERO, &won_slot(6));
+        stats.discarded(
+            Timestamp::ZERO,
+            BlockProducerWonSlotDiscardReason::BestTipSuperior,
+        );
+        stats.scheduled(Timestamp::ZERO, &won_slot(8));
+        stats.won_slots_passed(Timestamp::ZERO, 10, [4, 6, 8, 12].map(won_slot));
+        let stats = Some(&stats);
+
+        assert!(matches!(
+            outcome(4, 10, stats),
+            RpcBlockProducerSlotOutcome::Missed
+        ));
+        assert!(matches!(
+            outcome(6, 10, stats),
+            RpcBlockProducerSlotOutcome::Discarded {
+                reason: BlockProducerWonSlotDiscardReason::BestTipSuperior
+            }
+        ));
+        assert!(matches!(
+            outcome(8, 10, stats),
+            RpcBlockProducerSlotOutcome::Producing
+        ));
+        assert!(matches!(
+            outcome(12, 10, stats),
+            RpcBlockProducerSlotOutcome::Upcoming
+        ));
+    }
+}
@@ -8,16 +8,19 @@ use openmina_core::ActionEvent;
 use openmina_node_account::AccountPublicKey;
 use p2p::PeerId;
 use serde::{Deserialize, Serialize};
+use vrf::VrfMessage;
 
 use crate::external_snark_worker::SnarkWorkId;
+use crate::ledger::write::BlockApplyResult;
 use crate::p2p::connection::incoming::P2pConnectionIncomingInitOpts;
 use crate::p2p::connection::outgoing::{P2pConnectionOutgoingError, P2pConnectionOutgoingInitOpts};
 use crate::p2p::connection::P2pConnectionResponse;
 
 use super::{
     ActionStatsQuery, ConsensusTimeQuery, GetBlockQuery, PooledUserCommandsQuery,
-    PooledZkappsCommandsQuery, RpcId, RpcLedgerAccountDelegatorsGetResponse,
-    RpcLedgerStatusGetResponse, RpcScanStateSummaryGetQuery, RpcScanStateSummaryScanStateJob,
+    PooledZkappsCommandsQuery, RpcArchiveBackfillQuery, RpcId,
+    RpcLedgerAccountDelegatorsGetResponse, RpcLedgerStatusGetResponse, RpcP2pBanQuery, RpcRequest,
+    RpcScanStateSummaryGetQuery, RpcScanStateSummaryScanStateJob, RpcTransitionFrontierEvent,
     SyncStatsQuery,
 };
 
@@ -46,6 +49,9 @@ pub enum RpcAction {
     BlockProducerStatsGet {
         rpc_id: RpcId,
     },
+    BlockProducerScheduleGet {
+        rpc_id: RpcId,
+    },
 
     MessageProgressGet {
         rpc_id: RpcId,
@@ -94,6 +100,21 @@ pub enum RpcAction {
         rpc_id: RpcId,
     },
 
+    P2pBansGet {
+        rpc_id: RpcId,
+    },
+    P2pBan {
+        rpc_id: RpcId,
+        query: RpcP2pBanQuery,
+    },
+    P2pUnban {
+        rpc_id: RpcId,
+        peer_id: PeerId,
+    },
+    P2pBandwidthGet {
+        rpc_id: RpcId,
+    },
+
     ScanStateSummaryGetInit {
         rpc_id: RpcId,
         query: RpcScanStateSummaryGetQuery,
@@ -259,6 +280,37 @@ pub enum RpcAction {
     GenesisBlock {
         rpc_id: RpcId,
     },
+    ArchiveStatusGet {
+        rpc_id: RpcId,
+    },
+    #[action_event(level = info, fields(from_height = query.from_height, to_height = query.to_height))]
+    ArchiveBackfillInit {
+        rpc_id: RpcId,
+        query: RpcArchiveBackfillQuery,
+    },
+    ArchiveBackfillPending {
+        rpc_id: RpcId,
+    },
+    #[action_event(level = info, fields(blocks = blocks.len()))]
+    ArchiveBackfillSuccess {
+        rpc_id: RpcId,
+        blocks: Vec<BlockApplyResult>,
+    },
+    TransitionFrontierSubscribe {
+        rpc_id: RpcId,
+    },
+    /// Publishes the event to all transition frontier subscribers.
+    TransitionFrontierSubscribersNotify {
+        event: RpcTransitionFrontierEvent,
+    },
+    TransitionFrontierUnsubscribe {
+        rpc_id: RpcId,
+    },
+    VrfEvaluate {
+        rpc_id: RpcId,
+        public_key: AccountPublicKey,
+        message: VrfMessage,
+    },
 
     Finish {
         rpc_id: RpcId,
@@ -271,6 +323,8 @@ pub enum AccountQuery {
     SinglePublicKey(AccountPublicKey),
     MultipleIds(Vec<AccountId>),
     PubKeyWithTokenId(AccountPublicKey, TokenIdKeyHash),
+    /// Account that owns the token, i.e. whose derived token id is the given one.
+    TokenOwner(TokenIdKeyHash),
 }
 
 impl redux::EnablingCondition<crate::State> for RpcAction {
@@ -282,8 +336,13 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::ActionStatsGet { .. } => true,
             RpcAction::SyncStatsGet { .. } => true,
             RpcAction::BlockProducerStatsGet { .. } => true,
+            RpcAction::BlockProducerScheduleGet { .. } => true,
             RpcAction::MessageProgressGet { .. } => true,
             RpcAction::PeersGet { .. } => true,
+            RpcAction::P2pBansGet { .. } => true,
+            RpcAction::P2pBan { .. } => true,
+            RpcAction::P2pUnban { .. } => true,
+            RpcAction::P2pBandwidthGet { .. } => true,
             RpcAction::P2pConnectionOutgoingInit { rpc_id, .. } => {
                 !state.rpc.requests.contains_key(rpc_id)
             }
@@ -361,6 +420,31 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::PooledUserCommands { .. } => true,
             RpcAction::PooledZkappCommands { .. } => true,
             RpcAction::GenesisBlock { .. } => true,
+            RpcAction::ArchiveStatusGet { .. } => true,
+            RpcAction::ArchiveBackfillInit { .. } => true,
+            RpcAction::TransitionFrontierSubscribe { .. } => true,
+            RpcAction::VrfEvaluate { .. } => true,
+            RpcAction::TransitionFrontierSubscribersNotify { event } => match event {
+                RpcTransitionFrontierEvent::SyncStatus(status) => {
+                    state.rpc.sync_status != Some(*status)
+                }
+                _ => state.rpc.transition_frontier_subscribers().next().is_some(),
+            },
+            RpcAction::TransitionFrontierUnsubscribe { rpc_id } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .is_some_and(|v| matches!(v.req, RpcRequest::TransitionFrontierSubscribe)),
+            RpcAction::ArchiveBackfillPending { rpc_id } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .is_some_and(|v| v.status.is_init()),
+            RpcAction::ArchiveBackfillSuccess { rpc_id, .. } => state
+                .rpc
+                .requests
+                .get(rpc_id)
+                .is_some_and(|v| v.status.is_pending()),
             RpcAction::LedgerAccountsGetInit { .. } => {
                 state.transition_frontier.best_tip().is_some()
             }
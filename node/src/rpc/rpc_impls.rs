@@ -1,13 +1,13 @@
-use crate::external_snark_worker::{ExternalSnarkWorker, ExternalSnarkWorkerState};
+use crate::external_snark_worker::{ExternalSnarkWorker, ExternalSnarkWorkerState, SnarkWorkerId};
 
 use super::{RpcSnarkWorker, RpcSnarkWorkerStatus};
 
-impl From<ExternalSnarkWorker> for RpcSnarkWorker {
-    fn from(source: ExternalSnarkWorker) -> Self {
+impl From<(SnarkWorkerId, &ExternalSnarkWorker)> for RpcSnarkWorker {
+    fn from((worker_id, source): (SnarkWorkerId, &ExternalSnarkWorker)) -> Self {
         Self {
             time: Some(source.timestamp),
-            id: Some("single".into()),
-            status: source.state.into(),
+            id: Some(worker_id.to_string()),
+            status: source.state.clone().into(),
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use ledger::scan_state::transaction_logic::valid;
 use mina_p2p_messages::v2::{
     MinaBaseSignedCommandStableV2, MinaBaseZkappCommandTStableV1WireStableV1, NonZeroCurvePoint,
@@ -10,11 +12,13 @@ use openmina_core::{
     transaction::{TransactionPoolMessageSource, TransactionWithHash},
 };
 use p2p::{
+    bandwidth::P2pBandwidthState,
     connection::{incoming::P2pConnectionIncomingAction, outgoing::P2pConnectionOutgoingAction},
+    trust::{P2pTrustAction, P2pTrustState},
     webrtc::P2pConnectionResponse,
     PeerId,
 };
-use redux::ActionWithMeta;
+use redux::{ActionWithMeta, Timestamp};
 
 use crate::{
     ledger::read::{LedgerReadAction, LedgerReadInitCallback, LedgerReadRequest},
@@ -25,9 +29,10 @@ use crate::{
 };
 
 use super::{
-    ConsensusTimeQuery, PeerConnectionStatus, RpcAction, RpcPeerInfo, RpcRequest,
-    RpcRequestExtraData, RpcRequestState, RpcRequestStatus, RpcScanStateSummaryGetQuery,
-    RpcSnarkerConfig, RpcState,
+    ConsensusTimeQuery, PeerConnectionStatus, RpcAction, RpcArchiveBackfill, RpcP2pBan,
+    RpcP2pBandwidth, RpcP2pPeerBandwidth, RpcPeerInfo, RpcRequest, RpcRequestExtraData,
+    RpcRequestState, RpcRequestStatus, RpcScanStateSummaryGetQuery, RpcSnarkerConfig, RpcState,
+    RpcTransitionFrontierEvent,
 };
 
 impl RpcState {
@@ -71,6 +76,10 @@ impl RpcState {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(RpcEffectfulAction::BlockProducerStatsGet { rpc_id: *rpc_id });
             }
+            RpcAction::BlockProducerScheduleGet { rpc_id } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(RpcEffectfulAction::BlockProducerScheduleGet { rpc_id: *rpc_id });
+            }
             RpcAction::MessageProgressGet { rpc_id } => {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(RpcEffectfulAction::MessageProgressGet { rpc_id: *rpc_id });
@@ -83,6 +92,68 @@ impl RpcState {
                     peers,
                 });
             }
+            RpcAction::P2pBansGet { rpc_id } => {
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let response = state
+                    .p2p
+                    .ready()
+                    .map(|p2p| collect_rpc_p2p_bans(&p2p.trust, meta.time()))
+                    .unwrap_or_default();
+                dispatcher.push(RpcEffectfulAction::P2pBansGet {
+                    rpc_id: *rpc_id,
+                    response,
+                });
+            }
+            RpcAction::P2pBandwidthGet { rpc_id } => {
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let response = state
+                    .p2p
+                    .ready()
+                    .map(|p2p| collect_rpc_p2p_bandwidth(&p2p.bandwidth, meta.time()));
+                dispatcher.push(RpcEffectfulAction::P2pBandwidthGet {
+                    rpc_id: *rpc_id,
+                    response,
+                });
+            }
+            RpcAction::P2pBan { rpc_id, query } => {
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let response = match state.p2p.ready() {
+                    None => Err("p2p is not initialized".to_owned()),
+                    Some(p2p) if p2p.my_id() == query.peer_id => Err("cannot ban self".to_owned()),
+                    Some(_) => {
+                        dispatcher.push(P2pTrustAction::Ban {
+                            peer_id: query.peer_id,
+                            duration: query.duration_secs.map(Duration::from_secs),
+                            reason: query
+                                .reason
+                                .clone()
+                                .unwrap_or_else(|| "banned through rpc".to_owned()),
+                        });
+                        Ok(())
+                    }
+                };
+                dispatcher.push(RpcEffectfulAction::P2pBan {
+                    rpc_id: *rpc_id,
+                    response,
+                });
+            }
+            RpcAction::P2pUnban { rpc_id, peer_id } => {
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let response = match state.p2p.ready() {
+                    None => Err("p2p is not initialized".to_owned()),
+                    Some(p2p) if !p2p.trust.is_peer_banned(peer_id, meta.time()) => {
+                        Err("peer is not banned".to_owned())
+                    }
+                    Some(_) => {
+                        dispatcher.push(P2pTrustAction::Unban { peer_id: *peer_id });
+                        Ok(())
+                    }
+                };
+                dispatcher.push(RpcEffectfulAction::P2pUnban {
+                    rpc_id: *rpc_id,
+                    response,
+                });
+            }
             RpcAction::P2pConnectionOutgoingInit { rpc_id, opts } => {
                 let rpc_state = RpcRequestState {
                     req: RpcRequest::P2pConnectionOutgoing(opts.clone()),
@@ -160,7 +231,7 @@ impl RpcState {
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let p2p = p2p_ready!(state.p2p, meta.time());
 
-                match p2p.incoming_accept(opts.peer_id, &opts.offer) {
+                match p2p.incoming_accept(opts.peer_id, &opts.offer, meta.time()) {
                     Ok(_) => {
                         dispatcher.push(P2pConnectionIncomingAction::Init {
                             opts: opts.clone(),
@@ -394,10 +465,10 @@ impl RpcState {
             }
             RpcAction::SnarkerWorkersGet { rpc_id } => {
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
-                let snark_worker = state.external_snark_worker.0.clone();
+                let snark_workers = state.external_snark_worker.clone();
                 dispatcher.push(RpcEffectfulAction::SnarkerWorkersGet {
                     rpc_id: *rpc_id,
-                    snark_worker,
+                    snark_workers,
                 });
             }
             RpcAction::HealthCheck { rpc_id } => {
@@ -734,6 +805,121 @@ impl RpcState {
                     genesis_block,
                 });
             }
+            RpcAction::ArchiveStatusGet { rpc_id } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(RpcEffectfulAction::ArchiveStatusGet { rpc_id: *rpc_id });
+            }
+            RpcAction::ArchiveBackfillInit { rpc_id, query } => {
+                let rpc_state = RpcRequestState {
+                    req: RpcRequest::ArchiveBackfill(query.clone()),
+                    status: RpcRequestStatus::Init { time: meta.time() },
+                    data: Default::default(),
+                };
+                state.requests.insert(*rpc_id, rpc_state);
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                if !state.transition_frontier.archive_enabled {
+                    dispatcher.push(RpcAction::ArchiveBackfillPending { rpc_id: *rpc_id });
+                    dispatcher.push(RpcAction::ArchiveBackfillSuccess {
+                        rpc_id: *rpc_id,
+                        blocks: vec![],
+                    });
+                    return;
+                }
+
+                // Only blocks in the frontier still have their staged ledgers
+                // around, which are needed to rebuild the archive data.
+                let blocks = state
+                    .transition_frontier
+                    .best_chain
+                    .iter()
+                    .filter(|b| (query.from_height..=query.to_height).contains(&b.height()))
+                    .cloned()
+                    .collect();
+
+                dispatcher.push(LedgerReadAction::Init {
+                    request: LedgerReadRequest::ArchiveBlocksRebuild(*rpc_id, blocks),
+                    callback: LedgerReadInitCallback::RpcArchiveBackfillPending {
+                        callback: redux::callback!(
+                            on_ledger_read_init_rpc_archive_backfill(rpc_id: RequestId<RpcIdType>) -> crate::Action {
+                                RpcAction::ArchiveBackfillPending { rpc_id }
+                            }
+                        ),
+                        args: *rpc_id,
+                    },
+                })
+            }
+            RpcAction::ArchiveBackfillPending { rpc_id } => {
+                let Some(rpc) = state.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = RpcRequestStatus::Pending { time: meta.time() };
+            }
+            RpcAction::ArchiveBackfillSuccess { rpc_id, blocks } => {
+                let Some(rpc) = state.requests.get_mut(rpc_id) else {
+                    return;
+                };
+                rpc.status = RpcRequestStatus::Success { time: meta.time() };
+                let RpcRequest::ArchiveBackfill(query) = &rpc.req else {
+                    bug_condition!("Unexpected request for archive backfill: {:?}", rpc.req);
+                    return;
+                };
+
+                let resent = blocks.iter().map(|b| b.block.height()).collect::<Vec<_>>();
+                let unavailable = (query.from_height..=query.to_height)
+                    .filter(|height| !resent.contains(height))
+                    .collect();
+
+                let response = RpcArchiveBackfill {
+                    resent,
+                    unavailable,
+                };
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                dispatcher.push(RpcEffectfulAction::ArchiveBackfillSuccess {
+                    rpc_id: *rpc_id,
+                    blocks: blocks.clone(),
+                    response: state
+                        .transition_frontier
+                        .archive_enabled
+                        .then_some(response),
+                });
+            }
+            RpcAction::TransitionFrontierSubscribe { rpc_id } => {
+                let rpc_state = RpcRequestState {
+                    req: RpcRequest::TransitionFrontierSubscribe,
+                    status: RpcRequestStatus::Pending { time: meta.time() },
+                    data: Default::default(),
+                };
+                state.requests.insert(*rpc_id, rpc_state);
+                let sync_status = state.sync_status;
+
+                let dispatcher = state_context.into_dispatcher();
+                if let Some(sync_status) = sync_status {
+                    dispatcher.push(RpcEffectfulAction::TransitionFrontierEvent {
+                        rpc_ids: vec![*rpc_id],
+                        event: RpcTransitionFrontierEvent::SyncStatus(sync_status),
+                    });
+                }
+            }
+            RpcAction::TransitionFrontierSubscribersNotify { event } => {
+                if let RpcTransitionFrontierEvent::SyncStatus(status) = event {
+                    state.sync_status = Some(*status);
+                }
+                let rpc_ids = state.transition_frontier_subscribers().collect::<Vec<_>>();
+                if rpc_ids.is_empty() {
+                    return;
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(RpcEffectfulAction::TransitionFrontierEvent {
+                    rpc_ids,
+                    event: event.clone(),
+                });
+            }
+            RpcAction::TransitionFrontierUnsubscribe { rpc_id } => {
+                state.requests.remove(rpc_id);
+            }
             RpcAction::PooledZkappCommands { rpc_id, query } => {
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
 
@@ -774,6 +960,18 @@ impl RpcState {
                     zkapp_commands: zkapp_commands.into_iter().map(|(_, tx)| tx).collect(),
                 });
             }
+            RpcAction::VrfEvaluate {
+                rpc_id,
+                public_key,
+                message,
+            } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(RpcEffectfulAction::VrfEvaluate {
+                    rpc_id: *rpc_id,
+                    public_key: public_key.clone(),
+                    message: message.clone(),
+                });
+            }
             RpcAction::ConsensusTimeGet { rpc_id, query } => {
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let consensus_time = match query {
@@ -886,6 +1084,7 @@ impl RpcState {
 }
 
 pub fn collect_rpc_peers_info(state: &crate::State) -> Vec<RpcPeerInfo> {
+    let now = state.time();
     state.p2p.ready().map_or_else(Vec::new, |p2p| {
         p2p.peers
             .iter()
@@ -937,8 +1136,52 @@ pub fn collect_rpc_peers_info(state: &crate::State) -> Vec<RpcPeerInfo> {
                     best_tip_global_slot: best_tip.map(|bt| bt.global_slot_since_genesis()),
                     best_tip_timestamp: best_tip.map(|bt| bt.timestamp().into()),
                     time,
+                    bandwidth: p2p.bandwidth.peer_summary(peer_id, now),
                 }
             })
             .collect()
     })
 }
+
+fn collect_rpc_p2p_bans(trust: &P2pTrustState, time: Timestamp) -> Vec<RpcP2pBan> {
+    trust
+        .bans(time)
+        .map(|(peer_id, ban)| RpcP2pBan {
+            peer_id: *peer_id,
+            ips: trust
+                .banned_ips
+                .iter()
+                .filter(|(_, ip_ban)| &ip_ban.peer_id == peer_id && ip_ban.ban.is_active(time))
+                .map(|(ip, _)| *ip)
+                .collect(),
+            since: ban.since,
+            until: ban.until,
+            reason: ban.reason.clone(),
+            offences: trust.peers.get(peer_id).map_or(0, |t| t.offences),
+        })
+        .collect()
+}
+
+fn collect_rpc_p2p_bandwidth(bandwidth: &P2pBandwidthState, time: Timestamp) -> RpcP2pBandwidth {
+    RpcP2pBandwidth {
+        total: bandwidth.summary(time),
+        kinds: bandwidth
+            .kinds
+            .iter()
+            .map(|(kind, stats)| (*kind, stats.summary(time)))
+            .collect(),
+        peers: bandwidth
+            .peers
+            .iter()
+            .map(|(peer_id, peer)| RpcP2pPeerBandwidth {
+                peer_id: *peer_id,
+                total: peer.summary(time),
+                kinds: peer
+                    .kinds
+                    .iter()
+                    .map(|(kind, stats)| (*kind, stats.summary(time)))
+                    .collect(),
+            })
+            .collect(),
+    }
+}
@@ -4,7 +4,7 @@ use mina_p2p_messages::v2;
 use openmina_core::block::AppliedBlock;
 use serde::{Deserialize, Serialize};
 
-use super::{AccountQuery, RpcId, RpcRequest};
+use super::{AccountQuery, RpcId, RpcRequest, RpcSyncStatus};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RpcRequestState {
@@ -54,6 +54,9 @@ impl RpcRequestStatus {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct RpcState {
     pub requests: BTreeMap<RpcId, RpcRequestState>,
+    /// Sync status as of the last [`super::RpcAction::TransitionFrontierSubscribersNotify`],
+    /// sent to new transition frontier subscribers.
+    pub sync_status: Option<RpcSyncStatus>,
 }
 
 impl RpcState {
@@ -82,6 +85,13 @@ impl RpcState {
             })
     }
 
+    pub fn transition_frontier_subscribers(&self) -> impl Iterator<Item = RpcId> + '_ {
+        self.requests
+            .iter()
+            .filter(|(_, req)| matches!(req.req, RpcRequest::TransitionFrontierSubscribe))
+            .map(|(id, _)| *id)
+    }
+
     pub fn accounts_request_rpc_ids(
         &self,
     ) -> impl Iterator<Item = (RpcId, AccountQuery, &RpcRequestStatus)> + '_ {
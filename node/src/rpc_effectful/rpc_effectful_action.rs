@@ -1,14 +1,17 @@
 use crate::{
-    external_snark_worker::{ExternalSnarkWorker, SnarkWorkId},
+    external_snark_worker::{ExternalSnarkWorkers, SnarkWorkId},
+    ledger::write::BlockApplyResult,
     p2p::connection::P2pConnectionResponse,
     rpc::{
-        discovery::RpcDiscoveryRoutingTable, AccountQuery, ActionStatsQuery, RpcBestChainResponse,
-        RpcConsensusTimeGetResponse, RpcGenesisBlockResponse, RpcGetBlockResponse,
-        RpcLedgerAccountDelegatorsGetResponse, RpcLedgerStatusGetResponse, RpcPeerInfo,
-        RpcPooledUserCommandsResponse, RpcPooledZkappCommandsResponse,
-        RpcScanStateSummaryScanStateJob, RpcSnarkPoolCompletedJobsResponse,
-        RpcSnarkPoolPendingJobsGetResponse, RpcSnarkerConfig, RpcTransactionInjectFailure,
-        RpcTransactionInjectRejected, RpcTransactionInjectSuccess, SyncStatsQuery,
+        discovery::RpcDiscoveryRoutingTable, AccountQuery, ActionStatsQuery,
+        RpcArchiveBackfillResponse, RpcBestChainResponse, RpcConsensusTimeGetResponse,
+        RpcGenesisBlockResponse, RpcGetBlockResponse, RpcLedgerAccountDelegatorsGetResponse,
+        RpcLedgerStatusGetResponse, RpcP2pBanResponse, RpcP2pBandwidthGetResponse,
+        RpcP2pBansGetResponse, RpcP2pUnbanResponse, RpcPeerInfo, RpcPooledUserCommandsResponse,
+        RpcPooledZkappCommandsResponse, RpcScanStateSummaryScanStateJob,
+        RpcSnarkPoolCompletedJobsResponse, RpcSnarkPoolPendingJobsGetResponse, RpcSnarkerConfig,
+        RpcTransactionInjectFailure, RpcTransactionInjectRejected, RpcTransactionInjectSuccess,
+        RpcTransitionFrontierEvent, SyncStatsQuery,
     },
 };
 use ledger::{
@@ -19,8 +22,10 @@ use mina_p2p_messages::v2::{self, MinaBaseUserCommandStableV2};
 use openmina_core::{
     consensus::ConsensusConstants, requests::RpcId, snark::SnarkJobId, ActionEvent,
 };
+use openmina_node_account::AccountPublicKey;
 use p2p::bootstrap::P2pNetworkKadBootstrapStats;
 use serde::{Deserialize, Serialize};
+use vrf::VrfMessage;
 
 #[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
 pub enum RpcEffectfulAction {
@@ -45,6 +50,9 @@ pub enum RpcEffectfulAction {
     BlockProducerStatsGet {
         rpc_id: RpcId,
     },
+    BlockProducerScheduleGet {
+        rpc_id: RpcId,
+    },
 
     MessageProgressGet {
         rpc_id: RpcId,
@@ -71,6 +79,22 @@ pub enum RpcEffectfulAction {
     P2pConnectionIncomingSuccess {
         rpc_id: RpcId,
     },
+    P2pBansGet {
+        rpc_id: RpcId,
+        response: RpcP2pBansGetResponse,
+    },
+    P2pBan {
+        rpc_id: RpcId,
+        response: RpcP2pBanResponse,
+    },
+    P2pUnban {
+        rpc_id: RpcId,
+        response: RpcP2pUnbanResponse,
+    },
+    P2pBandwidthGet {
+        rpc_id: RpcId,
+        response: RpcP2pBandwidthGetResponse,
+    },
     ScanStateSummaryGetSuccess {
         rpc_id: RpcId,
         scan_state: Result<Vec<Vec<RpcScanStateSummaryScanStateJob>>, String>,
@@ -104,7 +128,7 @@ pub enum RpcEffectfulAction {
     },
     SnarkerWorkersGet {
         rpc_id: RpcId,
-        snark_worker: ExternalSnarkWorker,
+        snark_workers: ExternalSnarkWorkers,
     },
     HealthCheck {
         rpc_id: RpcId,
@@ -186,6 +210,23 @@ pub enum RpcEffectfulAction {
         rpc_id: RpcId,
         response: RpcLedgerAccountDelegatorsGetResponse,
     },
+    ArchiveStatusGet {
+        rpc_id: RpcId,
+    },
+    ArchiveBackfillSuccess {
+        rpc_id: RpcId,
+        blocks: Vec<BlockApplyResult>,
+        response: RpcArchiveBackfillResponse,
+    },
+    TransitionFrontierEvent {
+        rpc_ids: Vec<RpcId>,
+        event: RpcTransitionFrontierEvent,
+    },
+    VrfEvaluate {
+        rpc_id: RpcId,
+        public_key: AccountPublicKey,
+        message: VrfMessage,
+    },
 }
 
 impl redux::EnablingCondition<crate::State> for RpcEffectfulAction {
@@ -13,15 +13,17 @@ use crate::{
     rpc::{
         AccountQuery, AccountSlim, ActionStatsQuery, ActionStatsResponse, CurrentMessageProgress,
         MessagesStats, NodeHeartbeat, ProducedBlockInfo, RootLedgerSyncProgress,
-        RootStagedLedgerSyncProgress, RpcAction, RpcBlockProducerStats, RpcMessageProgressResponse,
-        RpcNodeStatus, RpcNodeStatusLedger, RpcNodeStatusNetworkInfo, RpcNodeStatusResources,
-        RpcNodeStatusTransactionPool, RpcNodeStatusTransitionFrontier,
-        RpcNodeStatusTransitionFrontierBlockSummary, RpcNodeStatusTransitionFrontierSync,
-        RpcRequestExtraData, RpcScanStateSummary, RpcScanStateSummaryBlock,
-        RpcScanStateSummaryBlockTransaction, RpcScanStateSummaryBlockTransactionKind,
-        RpcScanStateSummaryScanStateJob, RpcSnarkPoolJobFull, RpcSnarkPoolJobSnarkWork,
-        RpcSnarkPoolJobSummary, RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse,
-        RpcTransactionInjectResponse, TransactionStatus,
+        RootStagedLedgerSyncProgress, RpcAction, RpcBlockProducerKeyStats,
+        RpcBlockProducerSchedule, RpcBlockProducerScheduledSlot, RpcBlockProducerSlotOutcome,
+        RpcBlockProducerStats, RpcMessageProgressResponse, RpcNodeStatus, RpcNodeStatusLedger,
+        RpcNodeStatusNetworkInfo, RpcNodeStatusResources, RpcNodeStatusTransactionPool,
+        RpcNodeStatusTransitionFrontier, RpcNodeStatusTransitionFrontierBlockSummary,
+        RpcNodeStatusTransitionFrontierSync, RpcRequestExtraData, RpcScanStateSummary,
+        RpcScanStateSummaryBlock, RpcScanStateSummaryBlockTransaction,
+        RpcScanStateSummaryBlockTransactionKind, RpcScanStateSummaryScanStateJob,
+        RpcSnarkPoolJobFull, RpcSnarkPoolJobSnarkWork, RpcSnarkPoolJobSummary,
+        RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse, RpcTransactionInjectResponse,
+        TransactionStatus,
     },
     snark_pool::SnarkPoolAction,
     transition_frontier::sync::{
@@ -42,6 +44,7 @@ use p2p::channels::streaming_rpc::{
     staged_ledger_parts::calc_total_pieces_to_transfer, P2pStreamingRpcReceiveProgress,
 };
 use redux::ActionWithMeta;
+use vrf::VrfEvaluation;
 
 macro_rules! respond_or_log {
     ($e:expr, $t:expr) => {
@@ -81,15 +84,18 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                 }
             };
 
+            let producer = status.block_producer.clone();
             let heartbeat = NodeHeartbeat {
                 status: status.into(),
                 node_timestamp: meta.time(),
                 peer_id: store.state().p2p.my_id(),
                 last_produced_block_info,
             };
-            let response = store
-                .service()
-                .with_producer_keypair(move |sk| heartbeat.sign(sk));
+            let response = producer.and_then(|producer| {
+                store
+                    .service()
+                    .with_producer_keypair(&producer, move |sk| heartbeat.sign(sk))
+            });
 
             let _ = store.service.respond_heartbeat_get(rpc_id, response);
         }
@@ -130,7 +136,8 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
             let mut create_response = || {
                 let state = store.state.get();
                 let best_tip = state.transition_frontier.best_tip()?;
-                let public_key = state.block_producer.config()?.pub_key.clone();
+                let config = state.block_producer.config()?;
+                let public_key = config.pub_key.clone();
                 let won_slots = &state.block_producer.vrf_evaluator()?.won_slots;
 
                 let stats = store.service.stats()?;
@@ -139,6 +146,24 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                     v.won_slot.global_slot.checked_add(1).expect("overflow")
                 });
 
+                let mut key_stats = stats.block_producer().collect_key_stats();
+                let producers = config
+                    .keys()
+                    .map(|key| {
+                        let public_key = AccountPublicKey::from(key.pub_key.clone());
+                        let future_won_slots = won_slots
+                            .range(future_slot..)
+                            .filter(|(_, won_slot)| won_slot.won_slot.producer == public_key)
+                            .count();
+                        RpcBlockProducerKeyStats {
+                            coinbase_receiver: key.coinbase_receiver().clone().into(),
+                            stats: key_stats.remove(&public_key).unwrap_or_default(),
+                            public_key,
+                            future_won_slots,
+                        }
+                    })
+                    .collect();
+
                 let cur_global_slot = state.cur_global_slot();
                 let current_epoch = state.current_epoch();
                 let slots_per_epoch = best_tip.constants().slots_per_epoch.as_u32();
@@ -162,6 +187,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                     epoch_end: epoch_start
                         .map(|slot| slot.checked_add(slots_per_epoch).expect("overflow")),
                     public_key: public_key.into(),
+                    producers,
                     attempts,
                     future_won_slots: won_slots
                         .range(future_slot..)
@@ -180,6 +206,54 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                 .service
                 .respond_block_producer_stats_get(rpc_id, response);
         }
+        RpcEffectfulAction::BlockProducerScheduleGet { rpc_id } => {
+            let mut create_response = || {
+                let state = store.state.get();
+                let best_tip = state.transition_frontier.best_tip()?;
+                let won_slots = &state.block_producer.vrf_evaluator()?.won_slots;
+                let stats = store.service.stats().map(|stats| stats.block_producer());
+
+                let slots = won_slots
+                    .values()
+                    .map(|won_slot| {
+                        let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
+                            won_slot,
+                            best_tip.genesis_timestamp(),
+                        );
+                        let global_slot = won_slot.global_slot();
+                        let outcome = RpcBlockProducerSlotOutcome::new(
+                            global_slot,
+                            best_tip.global_slot(),
+                            stats.as_deref(),
+                        );
+
+                        RpcBlockProducerScheduledSlot {
+                            global_slot,
+                            epoch: won_slot.epoch(),
+                            slot_time: won_slot.slot_time,
+                            producer: won_slot.producer.clone().into(),
+                            delegator: won_slot.delegator.0.clone().into(),
+                            delegator_index: won_slot.delegator.1,
+                            vrf_output: won_slot.vrf_output.to_string(),
+                            vrf_value: won_slot.vrf_output.fractional(),
+                            vrf_threshold: won_slot.value_with_threshold.map(|(_, t)| t),
+                            outcome,
+                        }
+                    })
+                    .collect();
+
+                Some(RpcBlockProducerSchedule {
+                    current_time: meta.time(),
+                    current_global_slot: state.cur_global_slot(),
+                    current_epoch: state.current_epoch(),
+                    slots,
+                })
+            };
+            let response = create_response();
+            let _ = store
+                .service
+                .respond_block_producer_schedule_get(rpc_id, response);
+        }
         RpcEffectfulAction::MessageProgressGet { rpc_id } => {
             // TODO: move to stats
             let p2p = p2p_ready!(store.state().p2p, meta.time());
@@ -310,6 +384,30 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                 meta.time()
             );
         }
+        RpcEffectfulAction::P2pBansGet { rpc_id, response } => {
+            respond_or_log!(
+                store.service().respond_p2p_bans_get(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcEffectfulAction::P2pBan { rpc_id, response } => {
+            respond_or_log!(
+                store.service().respond_p2p_ban(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcEffectfulAction::P2pUnban { rpc_id, response } => {
+            respond_or_log!(
+                store.service().respond_p2p_unban(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcEffectfulAction::P2pBandwidthGet { rpc_id, response } => {
+            respond_or_log!(
+                store.service().respond_p2p_bandwidth_get(rpc_id, response),
+                meta.time()
+            );
+        }
         RpcEffectfulAction::P2pConnectionOutgoingError { rpc_id, error } => {
             let _ = store
                 .service
@@ -503,12 +601,12 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                 // TODO(binier): differentiate between job not found and job already taken.
                 return;
             }
-            if !store.state().external_snark_worker.has_idle() {
+            let Some(worker_id) = store.state().external_snark_worker.idle_worker() else {
                 let _ = store
                     .service()
                     .respond_snarker_job_commit(rpc_id, RpcSnarkerJobCommitResponse::SnarkerBusy);
                 return;
-            }
+            };
             if store
                 .service()
                 .respond_snarker_job_commit(rpc_id, RpcSnarkerJobCommitResponse::Ok)
@@ -516,7 +614,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
             {
                 return;
             }
-            store.dispatch(SnarkPoolAction::CommitmentCreate { job_id });
+            store.dispatch(SnarkPoolAction::CommitmentCreate { worker_id, job_id });
         }
         RpcEffectfulAction::SnarkerJobSpec { rpc_id, job_id } => {
             let Some(job) = store.state().snark_pool.get(&job_id) else {
@@ -557,12 +655,11 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
         }
         RpcEffectfulAction::SnarkerWorkersGet {
             rpc_id,
-            snark_worker,
+            snark_workers,
         } => {
             // TODO: handle potential errors
-            let _ = store
-                .service()
-                .respond_snarker_workers(rpc_id, vec![snark_worker.into()]);
+            let workers = snark_workers.iter().map(Into::into).collect();
+            let _ = store.service().respond_snarker_workers(rpc_id, workers);
         }
         RpcEffectfulAction::HealthCheck { rpc_id, has_peers } => {
             respond_or_log!(
@@ -689,7 +786,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                         meta.time()
                     )
                 }
-                AccountQuery::MultipleIds(..) => {
+                AccountQuery::MultipleIds(..) | AccountQuery::TokenOwner(..) => {
                     respond_or_log!(
                         store.service().respond_ledger_accounts(rpc_id, accounts),
                         meta.time()
@@ -859,6 +956,54 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                 meta.time()
             )
         }
+        RpcEffectfulAction::ArchiveStatusGet { rpc_id } => {
+            let response = store.service.archive_sinks_status();
+            respond_or_log!(
+                store.service().respond_archive_status_get(rpc_id, response),
+                meta.time()
+            )
+        }
+        RpcEffectfulAction::ArchiveBackfillSuccess {
+            rpc_id,
+            blocks,
+            response,
+        } => {
+            for block in blocks {
+                store.service.send_to_archive(block);
+            }
+            respond_or_log!(
+                store.service().respond_archive_backfill(rpc_id, response),
+                meta.time()
+            )
+        }
+        RpcEffectfulAction::TransitionFrontierEvent { rpc_ids, event } => {
+            for rpc_id in rpc_ids {
+                // Fails if the subscriber is gone or can't keep up.
+                if store
+                    .service()
+                    .respond_transition_frontier_event(rpc_id, event.clone())
+                    .is_err()
+                {
+                    store.dispatch(RpcAction::TransitionFrontierUnsubscribe { rpc_id });
+                }
+            }
+        }
+        RpcEffectfulAction::VrfEvaluate {
+            rpc_id,
+            public_key,
+            message,
+        } => {
+            let response = store
+                .service()
+                .with_producer_keypair(&public_key, move |sk| {
+                    VrfEvaluation::prove(&sk.clone().into(), message).ok()
+                })
+                .flatten();
+            respond_or_log!(
+                store.service().respond_vrf_evaluate(rpc_id, response),
+                meta.time()
+            )
+        }
     }
 }
 
@@ -963,6 +1108,7 @@ fn compute_node_status<S: Service>(store: &mut Store<S>) -> RpcNodeStatus {
         },
         service_queues: store.service.queues(),
         network_info,
+        archive_sinks: store.service.archive_sinks_status(),
     };
     status
 }
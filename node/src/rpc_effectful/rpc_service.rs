@@ -1,20 +1,23 @@
 use crate::{
     p2p::connection::P2pConnectionResponse,
     rpc::{
-        RpcActionStatsGetResponse, RpcBestChainResponse, RpcBlockProducerStatsGetResponse,
-        RpcConsensusTimeGetResponse, RpcDiscoveryBoostrapStatsResponse,
-        RpcDiscoveryRoutingTableResponse, RpcGenesisBlockResponse, RpcGetBlockResponse,
-        RpcHealthCheckResponse, RpcHeartbeatGetResponse, RpcId,
-        RpcLedgerAccountDelegatorsGetResponse, RpcLedgerAccountsResponse,
-        RpcLedgerSlimAccountsResponse, RpcLedgerStatusGetResponse, RpcMessageProgressResponse,
-        RpcP2pConnectionOutgoingResponse, RpcPeersGetResponse, RpcPooledUserCommandsResponse,
-        RpcPooledZkappCommandsResponse, RpcReadinessCheckResponse, RpcScanStateSummaryGetResponse,
+        RpcActionStatsGetResponse, RpcArchiveBackfillResponse, RpcArchiveStatusGetResponse,
+        RpcBestChainResponse, RpcBlockProducerScheduleGetResponse,
+        RpcBlockProducerStatsGetResponse, RpcConsensusTimeGetResponse,
+        RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse,
+        RpcGenesisBlockResponse, RpcGetBlockResponse, RpcHealthCheckResponse,
+        RpcHeartbeatGetResponse, RpcId, RpcLedgerAccountDelegatorsGetResponse,
+        RpcLedgerAccountsResponse, RpcLedgerSlimAccountsResponse, RpcLedgerStatusGetResponse,
+        RpcMessageProgressResponse, RpcP2pBanResponse, RpcP2pBandwidthGetResponse,
+        RpcP2pBansGetResponse, RpcP2pConnectionOutgoingResponse, RpcP2pUnbanResponse,
+        RpcPeersGetResponse, RpcPooledUserCommandsResponse, RpcPooledZkappCommandsResponse,
+        RpcReadinessCheckResponse, RpcScanStateSummaryGetResponse,
         RpcSnarkPoolCompletedJobsResponse, RpcSnarkPoolGetResponse, RpcSnarkPoolJobGetResponse,
         RpcSnarkPoolPendingJobsGetResponse, RpcSnarkerConfigGetResponse,
         RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse, RpcSnarkerWorkersResponse,
         RpcStatusGetResponse, RpcSyncStatsGetResponse, RpcTransactionInjectResponse,
-        RpcTransactionPoolResponse, RpcTransactionStatusGetResponse,
-        RpcTransitionFrontierUserCommandsResponse,
+        RpcTransactionPoolResponse, RpcTransactionStatusGetResponse, RpcTransitionFrontierEvent,
+        RpcTransitionFrontierUserCommandsResponse, RpcVrfEvaluateResponse,
     },
     State,
 };
@@ -77,6 +80,11 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcBlockProducerStatsGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_block_producer_schedule_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcBlockProducerScheduleGetResponse,
+    ) -> Result<(), RespondError>;
     fn respond_message_progress_stats_get(
         &mut self,
         rpc_id: RpcId,
@@ -87,6 +95,26 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcPeersGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_p2p_bans_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcP2pBansGetResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_p2p_ban(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcP2pBanResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_p2p_unban(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcP2pUnbanResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_p2p_bandwidth_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcP2pBandwidthGetResponse,
+    ) -> Result<(), RespondError>;
     fn respond_p2p_connection_outgoing(
         &mut self,
         rpc_id: RpcId,
@@ -242,4 +270,25 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcLedgerAccountDelegatorsGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_archive_status_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcArchiveStatusGetResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_archive_backfill(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcArchiveBackfillResponse,
+    ) -> Result<(), RespondError>;
+    /// Unlike other responses, can be sent multiple times for the same `rpc_id`.
+    fn respond_transition_frontier_event(
+        &mut self,
+        rpc_id: RpcId,
+        event: RpcTransitionFrontierEvent,
+    ) -> Result<(), RespondError>;
+    fn respond_vrf_evaluate(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcVrfEvaluateResponse,
+    ) -> Result<(), RespondError>;
 }
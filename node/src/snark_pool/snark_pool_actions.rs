@@ -6,6 +6,7 @@ use openmina_core::snark::{Snark, SnarkJobCommitment, SnarkJobId};
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
+use crate::external_snark_worker::SnarkWorkerId;
 use crate::p2p::PeerId;
 
 use super::candidate::SnarkPoolCandidateAction;
@@ -26,7 +27,9 @@ pub enum SnarkPoolAction {
     CommitmentCreateMany {
         job_ids: Vec<SnarkJobId>,
     },
+    /// Submit the job to the idle worker `worker_id` and commit to it.
     CommitmentCreate {
+        worker_id: SnarkWorkerId,
         job_id: SnarkJobId,
     },
     CommitmentAdd {
@@ -59,8 +62,13 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolAction {
                 state.config.snarker.as_ref().is_some_and(|v| v.auto_commit)
             }
             SnarkPoolAction::CommitmentCreateMany { .. } => state.config.snarker.is_some(),
-            SnarkPoolAction::CommitmentCreate { job_id } => {
-                state.config.snarker.is_some() && state.snark_pool.should_create_commitment(job_id)
+            SnarkPoolAction::CommitmentCreate { worker_id, job_id } => {
+                state.config.snarker.is_some()
+                    && state.snark_pool.should_create_commitment(job_id)
+                    && state
+                        .external_snark_worker
+                        .get(*worker_id)
+                        .is_some_and(|worker| worker.is_idle())
             }
             SnarkPoolAction::CommitmentAdd { commitment, .. } => state
                 .snark_pool
@@ -69,14 +69,13 @@ impl SnarkPoolState {
 
                 // Dispatch
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                if let Some(job_id) = global_state.external_snark_worker.working_job_id() {
+                for (worker_id, job_id) in global_state.external_snark_worker.working_job_ids() {
                     if !global_state.snark_pool.contains(job_id) {
                         // job is no longer needed.
-                        dispatcher.push(ExternalSnarkWorkerAction::CancelWork);
+                        dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
                     }
-                } else {
-                    dispatcher.push(SnarkPoolAction::AutoCreateCommitment);
                 }
+                dispatcher.push(SnarkPoolAction::AutoCreateCommitment);
             }
             SnarkPoolAction::AutoCreateCommitment => {
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
@@ -116,38 +115,28 @@ impl SnarkPoolState {
                 };
             }
             SnarkPoolAction::CommitmentCreateMany { job_ids } => {
-                let dispatcher = state_context.into_dispatcher();
-                for job_id in job_ids.iter().cloned() {
-                    dispatcher.push(SnarkPoolAction::CommitmentCreate { job_id });
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                // Workers are picked here, as none of them is busy until
+                // the queued `SubmitWork` actions get reduced.
+                let job_ids = job_ids
+                    .iter()
+                    .filter(|job_id| global_state.snark_pool.should_create_commitment(job_id))
+                    .cloned();
+                for (worker_id, job_id) in global_state.external_snark_worker.assign(job_ids) {
+                    dispatcher.push(SnarkPoolAction::CommitmentCreate { worker_id, job_id });
                 }
             }
-            SnarkPoolAction::CommitmentCreate { job_id } => {
-                let job_id = job_id.clone();
+            SnarkPoolAction::CommitmentCreate { worker_id, job_id } => {
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                let Some(summary) = global_state.snark_pool.job_summary(&job_id) else {
+                let Some(summary) = global_state.snark_pool.job_summary(job_id) else {
                     return;
                 };
-
-                if global_state.external_snark_worker.is_idle() {
-                    dispatcher.push(ExternalSnarkWorkerAction::SubmitWork {
-                        job_id: job_id.clone(),
-                        summary,
-                    });
-
-                    let timestamp_ms = meta.time_as_nanos() / 1_000_000;
-                    let Some(config) = global_state.config.snarker.as_ref() else {
-                        return;
-                    };
-                    dispatcher.push(SnarkPoolAction::CommitmentAdd {
-                        commitment: SnarkJobCommitment::new(
-                            timestamp_ms,
-                            job_id,
-                            config.fee.clone(),
-                            config.public_key.clone().into(),
-                        ),
-                        sender: global_state.p2p.my_id(),
-                    });
-                }
+                // Commitment is added once the worker accepts the job.
+                dispatcher.push(ExternalSnarkWorkerAction::SubmitWork {
+                    worker_id: *worker_id,
+                    job_id: job_id.clone(),
+                    summary,
+                });
             }
             SnarkPoolAction::CommitmentAdd { commitment, sender } => {
                 state.set_commitment(JobCommitment {
@@ -159,14 +148,15 @@ impl SnarkPoolState {
                 // Dispatch
                 let commitment = commitment.clone();
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                if let Some(job_id) = global_state.external_snark_worker.working_job_id() {
-                    let Some(config) = global_state.config.snarker.as_ref() else {
-                        return;
-                    };
-                    if &commitment.job_id == job_id
-                        && &commitment.snarker != config.public_key.as_ref()
+                let Some(config) = global_state.config.snarker.as_ref() else {
+                    return;
+                };
+                if &commitment.snarker != config.public_key.as_ref() {
+                    for worker_id in global_state
+                        .external_snark_worker
+                        .working_on(&commitment.job_id)
                     {
-                        dispatcher.push(ExternalSnarkWorkerAction::CancelWork);
+                        dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
                     }
                 }
             }
@@ -185,18 +175,15 @@ impl SnarkPoolState {
                 // Dispatch
                 let snark = snark.clone();
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                if let Some(job_id) = global_state
-                    .external_snark_worker
-                    .working_job_id()
-                    .filter(|job_id| *job_id == &snark.job_id())
+                let job_id = snark.job_id();
+                if let Some(commitment) = global_state
+                    .snark_pool
+                    .get(&job_id)
+                    .and_then(|job| job.commitment.as_ref())
                 {
-                    if let Some(commitment) = global_state
-                        .snark_pool
-                        .get(job_id)
-                        .and_then(|job| job.commitment.as_ref())
-                    {
-                        if snark > commitment.commitment {
-                            dispatcher.push(ExternalSnarkWorkerAction::CancelWork);
+                    if snark > commitment.commitment {
+                        for worker_id in global_state.external_snark_worker.working_on(&job_id) {
+                            dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
                         }
                     }
                 }
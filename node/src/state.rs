@@ -32,7 +32,7 @@ use snark::work_verify::SnarkWorkVerifyState;
 
 use crate::block_producer::vrf_evaluator::BlockProducerVrfEvaluatorState;
 pub use crate::block_producer::BlockProducerState;
-use crate::external_snark_worker::{ExternalSnarkWorker, ExternalSnarkWorkers};
+use crate::external_snark_worker::ExternalSnarkWorkers;
 use crate::ledger::read::LedgerReadState;
 use crate::ledger::write::LedgerWriteState;
 pub use crate::ledger::LedgerState;
@@ -116,7 +116,6 @@ impl_substate_access!(State, ExternalSnarkWorkers, external_snark_worker);
 impl_substate_access!(State, BlockProducerState, block_producer);
 impl_substate_access!(State, RpcState, rpc);
 impl_substate_access!(State, WatchedAccountsState, watched_accounts);
-impl_substate_access!(State, ExternalSnarkWorker, external_snark_worker.0);
 impl_substate_access!(State, LedgerState, ledger);
 impl_substate_access!(State, LedgerReadState, ledger.read);
 impl_substate_access!(State, LedgerWriteState, ledger.write);
@@ -273,7 +272,10 @@ impl State {
                 config.transition_frontier,
                 config.archive.is_some(),
             ),
-            external_snark_worker: ExternalSnarkWorkers::new(now),
+            external_snark_worker: ExternalSnarkWorkers::new(
+                now,
+                config.global.snarker.as_ref().map_or(1, |c| c.workers),
+            ),
             block_producer: BlockProducerState::new(now, config.block_producer),
             rpc: RpcState::new(),
             transaction_pool: TransactionPoolState::new(config.tx_pool, constants),
@@ -6,6 +6,7 @@ use openmina_core::block::{AppliedBlock, ArcBlockWithHash};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    account::AccountPublicKey,
     block_producer::{BlockProducerWonSlot, BlockProducerWonSlotDiscardReason, BlockWithoutProof},
     core::block::BlockHash,
 };
@@ -17,6 +18,15 @@ pub struct BlockProducerStats {
     pub(super) attempts: VecDeque<BlockProductionAttempt>,
     pub vrf_evaluator: BTreeMap<u32, VrfEvaluatorStats>,
     pub last_produced_block: Option<ArcBlockWithHash>,
+    /// Won slots, which passed without us trying to produce a block,
+    /// ordered by the global slot. Kept apart from `attempts`, since
+    /// attempt updates apply to the latest attempt.
+    #[serde(default)]
+    missed: VecDeque<BlockProductionAttempt>,
+    /// Won slots before this global slot were already checked for
+    /// being missed.
+    #[serde(default)]
+    missed_check_slot: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -31,6 +41,7 @@ pub struct BlockProductionAttempt {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockProductionAttemptWonSlot {
     pub slot_time: redux::Timestamp,
+    pub producer: v2::NonZeroCurvePoint,
     pub global_slot: u32,
     pub epoch: u32,
     pub delegator: (v2::NonZeroCurvePoint, AccountIndex),
@@ -72,6 +83,8 @@ pub enum BlockProductionStatus {
     Discarded {
         discard_reason: BlockProducerWonSlotDiscardReason,
     },
+    /// Slot passed without us trying to produce a block for it.
+    Missed,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -92,6 +105,18 @@ pub struct ProducedBlockTransactions {
     pub zkapps: u16,
 }
 
+/// Outcome counts of the recorded block production attempts of a single
+/// producer key.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BlockProducerKeyStats {
+    pub won_slots: u32,
+    pub produced: u32,
+    pub canonical: u32,
+    pub orphaned: u32,
+    pub discarded: u32,
+    pub missed: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VrfEvaluatorStats {
     pub total_slots: u32,
@@ -107,6 +132,32 @@ impl Default for VrfEvaluatorStats {
     }
 }
 
+impl BlockProductionAttempt {
+    fn new(
+        time: redux::Timestamp,
+        won_slot: &BlockProducerWonSlot,
+        status: BlockProductionStatus,
+    ) -> Self {
+        Self {
+            won_slot: won_slot.into(),
+            block: None,
+            times: BlockProductionTimes {
+                scheduled: time,
+                staged_ledger_diff_create_start: None,
+                staged_ledger_diff_create_end: None,
+                produced: None,
+                proof_create_start: None,
+                proof_create_end: None,
+                block_apply_start: None,
+                block_apply_end: None,
+                committed: None,
+                discarded: None,
+            },
+            status,
+        }
+    }
+}
+
 impl BlockProducerStats {
     fn latest_attempt_block_hash_matches(&self, hash: &BlockHash) -> bool {
         self.attempts
@@ -115,8 +166,66 @@ impl BlockProducerStats {
             .is_some_and(|b| &b.hash == hash)
     }
 
+    /// Recorded attempts, including missed won slots, ordered by the
+    /// global slot.
     pub fn collect_attempts(&self) -> Vec<BlockProductionAttempt> {
-        self.attempts.iter().cloned().collect()
+        let mut attempts = self
+            .attempts
+            .iter()
+            .chain(&self.missed)
+            .cloned()
+            .collect::<Vec<_>>();
+        attempts.sort_by_key(|attempt| attempt.won_slot.global_slot);
+        attempts
+    }
+
+    /// Latest recorded attempt for the global slot.
+    pub fn attempt_for_slot(&self, global_slot: u32) -> Option<&BlockProductionAttempt> {
+        self.attempts
+            .iter()
+            .rev()
+            .take_while(|attempt| attempt.won_slot.global_slot >= global_slot)
+            .find(|attempt| attempt.won_slot.global_slot == global_slot)
+            .or_else(|| {
+                let i = self
+                    .missed
+                    .partition_point(|attempt| attempt.won_slot.global_slot < global_slot);
+                self.missed
+                    .get(i)
+                    .filter(|attempt| attempt.won_slot.global_slot == global_slot)
+            })
+    }
+
+    /// Stats of recorded attempts, grouped by the producer key.
+    pub fn collect_key_stats(&self) -> BTreeMap<AccountPublicKey, BlockProducerKeyStats> {
+        self.attempts
+            .iter()
+            .chain(&self.missed)
+            .fold(BTreeMap::new(), |mut stats, attempt| {
+                let key_stats: &mut BlockProducerKeyStats = stats
+                    .entry(attempt.won_slot.producer.clone().into())
+                    .or_default();
+                key_stats.won_slots = key_stats.won_slots.saturating_add(1);
+                if attempt.block.is_some() {
+                    key_stats.produced = key_stats.produced.saturating_add(1);
+                }
+                match attempt.status {
+                    BlockProductionStatus::Canonical { .. } => {
+                        key_stats.canonical = key_stats.canonical.saturating_add(1);
+                    }
+                    BlockProductionStatus::Orphaned { .. } => {
+                        key_stats.orphaned = key_stats.orphaned.saturating_add(1);
+                    }
+                    BlockProductionStatus::Discarded { .. } => {
+                        key_stats.discarded = key_stats.discarded.saturating_add(1);
+                    }
+                    BlockProductionStatus::Missed => {
+                        key_stats.missed = key_stats.missed.saturating_add(1);
+                    }
+                    _ => {}
+                }
+                stats
+            })
     }
 
     pub fn new_best_chain(&mut self, time: redux::Timestamp, chain: &[AppliedBlock]) {
@@ -154,6 +263,15 @@ impl BlockProducerStats {
                         };
                     }
                     Some(b) => {
+                        if !matches!(attempt.status, BlockProductionStatus::Orphaned { .. }) {
+                            openmina_core::log::warn!(time;
+                                kind = "BlockProducerBlockOrphaned",
+                                summary = format!("produced block {} for slot {} got orphaned", block.hash, attempt.won_slot.global_slot),
+                                producer = attempt.won_slot.producer.to_string(),
+                                global_slot = attempt.won_slot.global_slot,
+                                block_hash = block.hash.to_string(),
+                                orphaned_by = b.hash().to_string());
+                        }
                         attempt.status = BlockProductionStatus::Orphaned {
                             orphaned_by: b.hash().clone(),
                         };
@@ -191,23 +309,52 @@ impl BlockProducerStats {
         if self.attempts.len() >= MAX_HISTORY {
             self.attempts.pop_front();
         }
-        self.attempts.push_back(BlockProductionAttempt {
-            won_slot: won_slot.into(),
-            block: None,
-            times: BlockProductionTimes {
-                scheduled: time,
-                staged_ledger_diff_create_start: None,
-                staged_ledger_diff_create_end: None,
-                produced: None,
-                proof_create_start: None,
-                proof_create_end: None,
-                block_apply_start: None,
-                block_apply_end: None,
-                committed: None,
-                discarded: None,
-            },
-            status: BlockProductionStatus::Scheduled,
-        });
+        self.attempts.push_back(BlockProductionAttempt::new(
+            time,
+            won_slot,
+            BlockProductionStatus::Scheduled,
+        ));
+    }
+
+    /// Records won slots before `best_tip_global_slot`, for which we
+    /// haven't tried to produce a block, as missed.
+    pub fn won_slots_passed(
+        &mut self,
+        time: redux::Timestamp,
+        best_tip_global_slot: u32,
+        won_slots: impl IntoIterator<Item = BlockProducerWonSlot>,
+    ) {
+        let check_from = std::mem::replace(&mut self.missed_check_slot, best_tip_global_slot);
+        for won_slot in won_slots {
+            let global_slot = won_slot.global_slot();
+            if global_slot < check_from || global_slot >= best_tip_global_slot {
+                continue;
+            }
+            if self.attempt_for_slot(global_slot).is_some() {
+                continue;
+            }
+            let i = self
+                .missed
+                .partition_point(|attempt| attempt.won_slot.global_slot < global_slot);
+            if i == 0 && self.missed.len() >= MAX_HISTORY {
+                continue;
+            }
+
+            openmina_core::log::warn!(time;
+                kind = "BlockProducerWonSlotMissed",
+                summary = format!("won slot {global_slot} passed without producing a block"),
+                producer = won_slot.producer.to_string(),
+                global_slot = global_slot,
+                slot_time = openmina_core::log::to_rfc_3339(won_slot.slot_time)
+                    .unwrap_or_else(|_| "<error>".to_owned()));
+            self.missed.insert(
+                i,
+                BlockProductionAttempt::new(time, &won_slot, BlockProductionStatus::Missed),
+            );
+            if self.missed.len() > MAX_HISTORY {
+                self.missed.pop_front();
+            }
+        }
     }
 
     pub fn staged_ledger_diff_create_start(&mut self, time: redux::Timestamp) {
@@ -324,6 +471,11 @@ impl BlockProducerStats {
 
     pub fn discarded(&mut self, time: redux::Timestamp, reason: BlockProducerWonSlotDiscardReason) {
         self.update("discarded", move |attempt| {
+            openmina_core::log::warn!(time;
+                kind = "BlockProducerWonSlotDiscarded",
+                summary = format!("won slot {} discarded: {reason:?}", attempt.won_slot.global_slot),
+                producer = attempt.won_slot.producer.to_string(),
+                global_slot = attempt.won_slot.global_slot);
             attempt.status = BlockProductionStatus::Discarded {
                 discard_reason: reason,
             };
@@ -374,6 +526,7 @@ impl From<&BlockProducerWonSlot> for BlockProductionAttemptWonSlot {
     fn from(won_slot: &BlockProducerWonSlot) -> Self {
         Self {
             slot_time: won_slot.slot_time,
+            producer: won_slot.producer.clone(),
             global_slot: won_slot.global_slot(),
             epoch: won_slot.epoch(),
             delegator: won_slot.delegator.clone(),
@@ -428,3 +581,130 @@ impl From<&BlockWithoutProof> for ProducedBlockTransactions {
             })
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::str::FromStr;
+
+    use mina_p2p_messages::{
+        bigint::BigInt,
+        v2::{EpochSeed, LedgerHash, MinaBaseEpochSeedStableV1},
+    };
+    use openmina_node_account::AccountSecretKey;
+    use vrf::VrfWonSlot;
+
+    use super::*;
+    use crate::block_producer::vrf_evaluator::VrfWonSlotWithHash;
+
+    pub(crate) fn won_slot(global_slot: u32) -> BlockProducerWonSlot {
+        let won_slot = VrfWonSlot {
+            producer: AccountSecretKey::genesis_producer().public_key(),
+            winner_account: AccountSecretKey::genesis_producer().public_key(),
+            vrf_output: Box::new(
+                vrf::genesis_vrf(EpochSeed::from(MinaBaseEpochSeedStableV1(BigInt::zero())))
+                    .unwrap(),
+            ),
+            global_slot,
+            account_index: AccountIndex(0),
+            value_with_threshold: None,
+        };
+        let ledger_hash =
+            LedgerHash::from_str("jxTAZfKKDxoX4vtt68pQCWooXoVLjnfBpusaMwewrcZxsL3uWp6").unwrap();
+        BlockProducerWonSlot::from_vrf_won_slot(
+            &VrfWonSlotWithHash::new(won_slot, ledger_hash),
+            redux::Timestamp::ZERO,
+        )
+    }
+
+    fn status(stats: &BlockProducerStats, global_slot: u32) -> Option<&BlockProductionStatus> {
+        stats
+            .attempt_for_slot(global_slot)
+            .map(|attempt| &attempt.status)
+    }
+
+    #[test]
+    fn attempt_for_slot() {
+        let mut stats = BlockProducerStats::default();
+        assert!(stats.attempt_for_slot(10).is_none());
+
+        stats.scheduled(redux::Timestamp::ZERO, &won_slot(10));
+        stats.discarded(
+            redux::Timestamp::ZERO,
+            BlockProducerWonSlotDiscardReason::BestTipGlobalSlotHigher,
+        );
+        stats.scheduled(redux::Timestamp::ZERO, &won_slot(20));
+
+        assert!(matches!(
+            status(&stats, 10),
+            Some(BlockProductionStatus::Discarded { .. })
+        ));
+        assert!(matches!(
+            status(&stats, 20),
+            Some(BlockProductionStatus::Scheduled)
+        ));
+        assert!(stats.attempt_for_slot(15).is_none());
+        assert!(stats.attempt_for_slot(30).is_none());
+    }
+
+    #[test]
+    fn won_slots_passed_records_missed_slots() {
+        let mut stats = BlockProducerStats::default();
+        stats.scheduled(redux::Timestamp::ZERO, &won_slot(20));
+
+        let won_slots = [5, 20, 25, 30].map(won_slot);
+        stats.won_slots_passed(redux::Timestamp::ZERO, 30, won_slots.clone());
+
+        assert!(matches!(
+            status(&stats, 5),
+            Some(BlockProductionStatus::Missed)
+        ));
+        assert!(matches!(
+            status(&stats, 20),
+            Some(BlockProductionStatus::Scheduled)
+        ));
+        assert!(matches!(
+            status(&stats, 25),
+            Some(BlockProductionStatus::Missed)
+        ));
+        // best tip's slot isn't over yet.
+        assert!(stats.attempt_for_slot(30).is_none());
+
+        let slots = stats
+            .collect_attempts()
+            .iter()
+            .map(|attempt| attempt.won_slot.global_slot)
+            .collect::<Vec<_>>();
+        assert_eq!(slots, [5, 20, 25]);
+        let key_stats = stats.collect_key_stats();
+        let key_stats = key_stats.values().next().unwrap();
+        assert_eq!((key_stats.won_slots, key_stats.missed), (3, 2));
+
+        // Already checked slots aren't recorded twice.
+        stats.won_slots_passed(redux::Timestamp::ZERO, 31, won_slots);
+        assert_eq!(stats.collect_attempts().len(), 4);
+        assert!(matches!(
+            status(&stats, 30),
+            Some(BlockProductionStatus::Missed)
+        ));
+    }
+
+    #[test]
+    fn won_slots_passed_keeps_latest_attempt_updatable() {
+        let mut stats = BlockProducerStats::default();
+        stats.scheduled(redux::Timestamp::ZERO, &won_slot(20));
+
+        // Best tip moved past a later won slot, while we are still
+        // producing a block for an earlier one.
+        stats.won_slots_passed(redux::Timestamp::ZERO, 30, [won_slot(25)]);
+        stats.staged_ledger_diff_create_start(redux::Timestamp::ZERO);
+
+        assert!(matches!(
+            status(&stats, 20),
+            Some(BlockProductionStatus::StagedLedgerDiffCreatePending)
+        ));
+        assert!(matches!(
+            status(&stats, 25),
+            Some(BlockProductionStatus::Missed)
+        ));
+    }
+}
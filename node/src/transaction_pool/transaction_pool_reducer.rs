@@ -6,6 +6,7 @@ use ledger::{
     },
     Account, AccountId,
 };
+use mina_signer::CompressedPubKey;
 use openmina_core::{
     bug_condition,
     constants::constraint_constants,
@@ -18,7 +19,7 @@ use redux::callback;
 use snark::user_command_verify::{SnarkUserCommandVerifyAction, SnarkUserCommandVerifyId};
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{BlockProducerAction, RpcAction};
+use crate::{block_producer::TransactionSelectionConfig, BlockProducerAction, RpcAction};
 
 use super::{
     PendingId, TransactionPoolAction, TransactionPoolActionWithMetaRef,
@@ -440,15 +441,31 @@ impl TransactionPoolState {
             TransactionPoolAction::CollectTransactionsByFee => {
                 let transaction_capacity =
                     2u64.pow(constraint_constants().transaction_capacity_log_2 as u32);
-                let transactions_by_fee = substate
-                    .pool
-                    .list_includable_transactions(transaction_capacity as usize)
+                let (dispatcher, global_state) = state.into_dispatcher_and_state();
+                let default_selection = TransactionSelectionConfig::default();
+                let selection = global_state
+                    .block_producer
+                    .config()
+                    .map_or(&default_selection, |config| &config.transaction_selection);
+                let pool = &global_state.transaction_pool.pool;
+                // Selection policy may skip some of them, so list more.
+                let mut candidates = pool.list_includable_transactions(
+                    TransactionSelectionConfig::candidates_limit(transaction_capacity as usize),
+                );
+                selection.add_priority_candidates(&mut candidates, |sender| {
+                    match CompressedPubKey::try_from(sender) {
+                        Ok(public_key) => pool.list_sender_transactions(
+                            &AccountId::new_with_default_token(public_key),
+                        ),
+                        Err(_) => Vec::new(),
+                    }
+                });
+                let transactions_by_fee = selection
+                    .select(candidates, transaction_capacity as usize)
                     .into_iter()
                     .map(|cmd| cmd.data)
                     .collect::<Vec<_>>();
 
-                let dispatcher = state.into_dispatcher();
-
                 dispatcher.push(BlockProducerAction::WonSlotTransactionsSuccess {
                     transactions_by_fee,
                 });
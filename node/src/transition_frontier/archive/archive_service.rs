@@ -1,5 +1,96 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
 use crate::ledger::write::BlockApplyResult;
 
 pub trait ArchiveService: redux::Service {
     fn send_to_archive(&mut self, data: BlockApplyResult);
+
+    /// Health of the configured archive sinks, `None` if archiving is disabled.
+    fn archive_sinks_status(&mut self) -> Option<Vec<ArchiveSinkStatus>>;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchiveSinkStatus {
+    pub name: String,
+    /// `false` if the last block could not be delivered or had to be dropped.
+    pub healthy: bool,
+    /// Blocks waiting to be delivered to this sink.
+    pub queued: usize,
+    pub delivered: u64,
+    pub failed: u64,
+    /// Blocks dropped because the sink queue was full.
+    pub dropped: u64,
+    pub cursor: ArchiveSinkCursor,
+    pub last_error: Option<String>,
+}
+
+impl ArchiveSinkStatus {
+    pub fn new(name: String) -> Self {
+        Self::with_cursor(name, ArchiveSinkCursor::default())
+    }
+
+    pub fn with_cursor(name: String, cursor: ArchiveSinkCursor) -> Self {
+        Self {
+            name,
+            healthy: true,
+            queued: 0,
+            delivered: 0,
+            failed: 0,
+            dropped: 0,
+            cursor,
+            last_error: None,
+        }
+    }
+}
+
+/// Delivery progress of an archive sink, kept across restarts.
+///
+/// Heights in `missed_heights` are candidates for a backfill
+/// (see [`crate::rpc::RpcRequest::ArchiveBackfill`]).
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ArchiveSinkCursor {
+    /// Highest block height delivered to the sink.
+    pub last_delivered_height: Option<u32>,
+    /// Heights not delivered to the sink, either because the delivery
+    /// failed or because no block was sent at that height (e.g. the
+    /// node was down).
+    pub missed_heights: BTreeSet<u32>,
+}
+
+impl ArchiveSinkCursor {
+    pub fn delivered(&mut self, height: u32) {
+        if let Some(last) = self.last_delivered_height {
+            self.missed_heights.extend(last.saturating_add(1)..height);
+        }
+        self.missed_heights.remove(&height);
+        self.last_delivered_height = self.last_delivered_height.max(Some(height));
+    }
+
+    pub fn missed(&mut self, height: u32) {
+        self.missed_heights.insert(height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArchiveSinkCursor;
+
+    #[test]
+    fn cursor_records_gaps() {
+        let mut cursor = ArchiveSinkCursor::default();
+        cursor.delivered(10);
+        assert!(cursor.missed_heights.is_empty());
+
+        cursor.missed(11);
+        cursor.delivered(14);
+        assert_eq!(cursor.last_delivered_height, Some(14));
+        assert_eq!(cursor.missed_heights, [11, 12, 13].into());
+
+        // backfill
+        cursor.delivered(12);
+        assert_eq!(cursor.last_delivered_height, Some(14));
+        assert_eq!(cursor.missed_heights, [11, 13].into());
+    }
 }
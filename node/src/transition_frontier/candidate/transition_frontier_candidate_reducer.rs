@@ -5,12 +5,15 @@ use openmina_core::{
 use snark::block_verify::{SnarkBlockVerifyAction, SnarkBlockVerifyError, SnarkBlockVerifyId};
 
 use crate::{
-    transition_frontier::sync::{
-        ledger::{
-            snarked::TransitionFrontierSyncLedgerSnarkedAction,
-            staged::TransitionFrontierSyncLedgerStagedAction,
+    transition_frontier::{
+        sync::{
+            ledger::{
+                snarked::TransitionFrontierSyncLedgerSnarkedAction,
+                staged::TransitionFrontierSyncLedgerStagedAction,
+            },
+            TransitionFrontierSyncAction,
         },
-        TransitionFrontierSyncAction,
+        TransitionFrontierAction,
     },
     WatchedAccountsAction,
 };
@@ -34,6 +37,9 @@ impl TransitionFrontierCandidatesState {
         match action {
             TransitionFrontierCandidateAction::P2pBestTipUpdate { best_tip } => {
                 let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(TransitionFrontierAction::RestoreConfirm {
+                    peer_best_tip: best_tip.clone(),
+                });
                 dispatcher.push(TransitionFrontierCandidateAction::BlockReceived {
                     block: best_tip.clone(),
                     chain_proof: None,
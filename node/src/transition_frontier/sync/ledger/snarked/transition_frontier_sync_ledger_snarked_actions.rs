@@ -1,4 +1,8 @@
-use mina_p2p_messages::v2::{LedgerHash, MinaBaseAccountBinableArgStableV2};
+use std::sync::Arc;
+
+use mina_p2p_messages::v2::{
+    LedgerHash, MinaBaseAccountBinableArgStableV2, MinaBaseSparseLedgerBaseStableV2,
+};
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
@@ -28,6 +32,44 @@ pub enum TransitionFrontierSyncLedgerSnarkedAction {
     Pending,
     PeersQuery,
 
+    // For EpochLedger query
+    PeerQueryEpochLedgerInit {
+        peer_id: PeerId,
+    },
+    PeerQueryEpochLedgerPending {
+        peer_id: PeerId,
+        rpc_id: P2pRpcId,
+    },
+    PeerQueryEpochLedgerError {
+        peer_id: PeerId,
+        rpc_id: P2pRpcId,
+        error: PeerLedgerQueryError,
+    },
+    PeerQueryEpochLedgerSuccess {
+        peer_id: PeerId,
+        rpc_id: P2pRpcId,
+        ledger: Arc<MinaBaseSparseLedgerBaseStableV2>,
+    },
+    EpochLedgerReceived {
+        ledger: Arc<MinaBaseSparseLedgerBaseStableV2>,
+        sender: PeerId,
+    },
+    /// Build the received ledger with the ledger service, which then
+    /// checks its merkle root against the target.
+    EpochLedgerSetInit,
+    EpochLedgerSetPending,
+    #[action_event(level = info)]
+    EpochLedgerAccepted {
+        sender: PeerId,
+    },
+    EpochLedgerRejected {
+        sender: PeerId,
+    },
+    /// None of the peers provided the epoch ledger, sync it
+    /// using `NumAccounts` and address queries instead.
+    #[action_event(level = warn)]
+    EpochLedgerFallback,
+
     // For NumAccounts query
     PeerQueryNumAccountsInit {
         peer_id: PeerId,
@@ -156,11 +198,87 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncLedgerSnar
                     .ledger()
                     .and_then(|s| s.snarked())
                     .is_some_and(|s| {
-                        s.is_num_accounts_query_next() || s.contains_pending_address_queries()
+                        s.is_epoch_ledger_query_next()
+                            || s.is_num_accounts_query_next()
+                            || s.contains_pending_address_queries()
                     });
                 peers_available && sync_next_available
             }
 
+            // epoch ledger
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerInit { peer_id } => None
+                .or_else(|| {
+                    let target_best_tip = state.transition_frontier.sync.best_tip()?;
+                    let ledger = state.transition_frontier.sync.ledger()?.snarked()?;
+                    let target = ledger.target();
+
+                    let check_epoch_ledger = ledger.is_epoch_ledger_query_next()
+                        && !ledger.epoch_ledger_attempts()?.contains_key(peer_id);
+
+                    let peer = state.p2p.get_ready_peer(peer_id)?;
+                    let check_peer_available = check_peer_available(peer, target, target_best_tip);
+
+                    Some(check_epoch_ledger && check_peer_available)
+                })
+                .unwrap_or(false),
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerPending {
+                peer_id,
+                ..
+            } => state
+                .transition_frontier
+                .sync
+                .ledger()
+                .and_then(|s| s.snarked()?.epoch_ledger_attempts())
+                .and_then(|attempts| attempts.get(peer_id))
+                .is_some_and(|s| matches!(s, PeerRpcState::Init { .. })),
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerError {
+                peer_id,
+                rpc_id,
+                ..
+            }
+            | TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerSuccess {
+                peer_id,
+                rpc_id,
+                ..
+            } => state
+                .transition_frontier
+                .sync
+                .ledger()
+                .and_then(|s| s.snarked()?.epoch_ledger_attempts())
+                .and_then(|attempts| attempts.get(peer_id))
+                .is_some_and(|s| s.pending_rpc_id() == Some(*rpc_id)),
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerReceived { sender, .. } => state
+                .transition_frontier
+                .sync
+                .ledger()
+                .and_then(|s| s.snarked())
+                .is_some_and(|s| {
+                    s.received_epoch_ledger().is_none()
+                        && s.epoch_ledger_attempts()
+                            .and_then(|attempts| attempts.get(sender))
+                            .is_some_and(|s| s.is_success())
+                }),
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetInit
+            | TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetPending => state
+                .transition_frontier
+                .sync
+                .ledger()
+                .and_then(|s| s.snarked()?.received_epoch_ledger())
+                .is_some_and(|received| !received.is_set_pending),
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerAccepted { sender }
+            | TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerRejected { sender } => state
+                .transition_frontier
+                .sync
+                .ledger()
+                .and_then(|s| s.snarked()?.received_epoch_ledger())
+                .is_some_and(|received| &received.sender == sender && received.is_set_pending),
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerFallback => state
+                .transition_frontier
+                .sync
+                .ledger()
+                .and_then(|s| s.snarked())
+                .is_some_and(|s| s.is_epoch_ledger_query_exhausted()),
+
             // num accounts
             TransitionFrontierSyncLedgerSnarkedAction::PeerQueryNumAccountsInit { peer_id } => None
                 .or_else(|| {
@@ -16,6 +16,17 @@ impl TransitionFrontierSyncLedgerSnarkedAction {
             TransitionFrontierSyncLedgerSnarkedAction::Pending => {}
             TransitionFrontierSyncLedgerSnarkedAction::PeersQuery => {}
 
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerInit { .. } => {}
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerPending { .. } => {}
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerError { .. } => {}
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerSuccess { .. } => {}
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerReceived { .. } => {}
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetInit => {}
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetPending => {}
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerAccepted { .. } => {}
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerRejected { .. } => {}
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerFallback => {}
+
             TransitionFrontierSyncLedgerSnarkedAction::PeerQueryNumAccountsInit { .. } => {}
             TransitionFrontierSyncLedgerSnarkedAction::PeerQueryNumAccountsPending { .. } => {}
             TransitionFrontierSyncLedgerSnarkedAction::PeerQueryNumAccountsRetry { .. } => {}
@@ -10,14 +10,16 @@ use rand::prelude::*;
 
 use crate::{
     ledger::{
-        ledger_empty_hash_at_depth, tree_height_for_num_accounts, LedgerAddress, LEDGER_DEPTH,
+        ledger_empty_hash_at_depth, tree_height_for_num_accounts,
+        write::{LedgerWriteAction, LedgerWriteRequest},
+        LedgerAddress, LEDGER_DEPTH,
     },
     Action, State,
 };
 
 use super::{
-    LedgerAddressQueryPending, PeerLedgerQueryResponse, PeerRpcState,
-    TransitionFrontierSyncLedgerSnarkedAction,
+    LedgerAddressQueryPending, PeerLedgerQueryError, PeerLedgerQueryResponse, PeerRpcState,
+    ReceivedEpochLedger, TransitionFrontierSyncLedgerSnarkedAction,
     TransitionFrontierSyncLedgerSnarkedActionWithMetaRef, TransitionFrontierSyncLedgerSnarkedState,
     ACCOUNT_SUBTREE_HEIGHT,
 };
@@ -44,6 +46,8 @@ impl TransitionFrontierSyncLedgerSnarkedState {
             TransitionFrontierSyncLedgerSnarkedAction::PeersQuery => {
                 let mut retry_addresses: Vec<_> = state.sync_address_retry_iter().collect();
                 let mut addresses: Vec<_> = state.sync_address_query_iter().collect();
+                let is_epoch_ledger_pending = matches!(state, Self::EpochLedgerPending { .. });
+                let is_epoch_ledger_query_next = state.is_epoch_ledger_query_next();
                 let is_num_accounts_pending = matches!(state, Self::NumAccountsPending { .. });
 
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
@@ -57,6 +61,24 @@ impl TransitionFrontierSyncLedgerSnarkedState {
                     .collect::<Vec<_>>();
                 peer_ids.shuffle(&mut global_state.pseudo_rng());
 
+                if is_epoch_ledger_pending {
+                    if !is_epoch_ledger_query_next {
+                        return;
+                    }
+                    for (peer_id, _) in peer_ids {
+                        if dispatcher.push_if_enabled(
+                            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerInit {
+                                peer_id,
+                            },
+                            global_state,
+                            meta.time(),
+                        ) {
+                            return;
+                        }
+                    }
+                    return;
+                }
+
                 if is_num_accounts_pending {
                     for (peer_id, _) in peer_ids {
                         if dispatcher.push_if_enabled(
@@ -113,6 +135,184 @@ impl TransitionFrontierSyncLedgerSnarkedState {
                 }
             }
 
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerInit { peer_id } => {
+                if let Self::EpochLedgerPending { attempts, .. } = state {
+                    attempts.insert(*peer_id, PeerRpcState::Init { time: meta.time() });
+                }
+
+                // Dispatch
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                peer_query_epoch_ledger_init(dispatcher, global_state, *peer_id)
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerPending {
+                peer_id,
+                rpc_id,
+            } => {
+                let Self::EpochLedgerPending { attempts, .. } = state else {
+                    return;
+                };
+                let Some(rpc_state) = attempts.get_mut(peer_id) else {
+                    return;
+                };
+
+                *rpc_state = PeerRpcState::Pending {
+                    time: meta.time(),
+                    rpc_id: *rpc_id,
+                };
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerError {
+                peer_id,
+                rpc_id,
+                error,
+            } => {
+                let Some(rpc_state) = state.peer_epoch_ledger_query_state_get_mut(peer_id, *rpc_id)
+                else {
+                    return;
+                };
+
+                *rpc_state = PeerRpcState::Error {
+                    time: meta.time(),
+                    rpc_id: *rpc_id,
+                    error: error.clone(),
+                };
+
+                // Dispatch
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                if !dispatcher.push_if_enabled(
+                    TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerFallback,
+                    global_state,
+                    meta.time(),
+                ) {
+                    dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::PeersQuery);
+                }
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerSuccess {
+                peer_id,
+                rpc_id,
+                ledger,
+            } => {
+                let Some(rpc_state) = state.peer_epoch_ledger_query_state_get_mut(peer_id, *rpc_id)
+                else {
+                    return;
+                };
+                *rpc_state = PeerRpcState::Success {
+                    time: meta.time(),
+                    rpc_id: *rpc_id,
+                };
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(
+                    TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerReceived {
+                        ledger: ledger.clone(),
+                        sender: *peer_id,
+                    },
+                );
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerReceived { ledger, sender } => {
+                let Self::EpochLedgerPending { received, .. } = state else {
+                    return;
+                };
+                *received = Some(ReceivedEpochLedger {
+                    sender: *sender,
+                    ledger: ledger.clone(),
+                    is_set_pending: false,
+                });
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetInit);
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetInit => {
+                let Some((snarked_ledger_hash, ledger)) = state
+                    .received_epoch_ledger()
+                    .map(|received| (state.ledger_hash().clone(), received.ledger.clone()))
+                else {
+                    return;
+                };
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(LedgerWriteAction::Init {
+                    request: LedgerWriteRequest::EpochLedgerSet {
+                        snarked_ledger_hash,
+                        ledger,
+                    },
+                    on_init: redux::callback!(
+                        on_epoch_ledger_set_init(_request: LedgerWriteRequest) -> crate::Action {
+                            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetPending
+                        }
+                    ),
+                });
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerSetPending => {
+                let Self::EpochLedgerPending {
+                    received: Some(received),
+                    ..
+                } = state
+                else {
+                    return;
+                };
+                received.is_set_pending = true;
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerAccepted { .. } => {
+                let Self::EpochLedgerPending { target, .. } = state else {
+                    return;
+                };
+                // Whole ledger is already set, nothing left for the merkle tree sync.
+                *state = Self::MerkleTreeSyncSuccess {
+                    time: meta.time(),
+                    target: target.clone(),
+                };
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::Success);
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerRejected { sender } => {
+                if let Self::EpochLedgerPending {
+                    attempts, received, ..
+                } = state
+                {
+                    *received = None;
+                    if let Some(rpc_state) = attempts.get_mut(sender) {
+                        if let Some(rpc_id) = rpc_state.rpc_id() {
+                            // received ledger doesn't match the target hash
+                            *rpc_state = PeerRpcState::Error {
+                                time: meta.time(),
+                                rpc_id,
+                                error: PeerLedgerQueryError::DataUnavailable,
+                            };
+                        }
+                    }
+                }
+
+                // Dispatch
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                dispatcher.push(P2pDisconnectionAction::Init {
+                    peer_id: *sender,
+                    reason:
+                        P2pDisconnectionReason::TransitionFrontierSyncLedgerSnarkedEpochLedgerRejected,
+                });
+                if !dispatcher.push_if_enabled(
+                    TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerFallback,
+                    global_state,
+                    meta.time(),
+                ) {
+                    dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::PeersQuery);
+                }
+            }
+            TransitionFrontierSyncLedgerSnarkedAction::EpochLedgerFallback => {
+                let Self::EpochLedgerPending { target, .. } = state else {
+                    return;
+                };
+                *state = Self::num_accounts_pending(meta.time(), target.clone());
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(TransitionFrontierSyncLedgerSnarkedAction::PeersQuery);
+            }
+
             TransitionFrontierSyncLedgerSnarkedAction::PeerQueryNumAccountsInit { peer_id } => {
                 if let Self::NumAccountsPending {
                     pending_num_accounts,
@@ -621,6 +821,40 @@ impl TransitionFrontierSyncLedgerSnarkedState {
     }
 }
 
+fn peer_query_epoch_ledger_init(
+    dispatcher: &mut redux::Dispatcher<Action, State>,
+    state: &State,
+    peer_id: PeerId,
+) {
+    let Some((ledger_hash, rpc_id)) = None.or_else(|| {
+        let ledger = state.transition_frontier.sync.ledger()?;
+        let ledger_hash = ledger.snarked()?.ledger_hash();
+
+        let p = state.p2p.get_ready_peer(&peer_id)?;
+        let rpc_id = p.channels.next_local_rpc_id();
+
+        Some((ledger_hash.clone(), rpc_id))
+    }) else {
+        return;
+    };
+
+    dispatcher.push(P2pChannelsRpcAction::RequestSend {
+        peer_id,
+        id: rpc_id,
+        request: Box::new(P2pRpcRequest::EpochLedger(ledger_hash)),
+        on_init: Some(redux::callback!(
+            on_send_p2p_epoch_ledger_rpc_request(
+                (peer_id: PeerId, rpc_id: P2pRpcId, _request: P2pRpcRequest)
+            ) -> crate::Action {
+                TransitionFrontierSyncLedgerSnarkedAction::PeerQueryEpochLedgerPending {
+                    peer_id,
+                    rpc_id,
+                }
+            }
+        )),
+    });
+}
+
 fn peer_query_num_accounts_init(
     dispatcher: &mut redux::Dispatcher<Action, State>,
     state: &State,
@@ -1,6 +1,6 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
-use mina_p2p_messages::v2::LedgerHash;
+use mina_p2p_messages::v2::{LedgerHash, MinaBaseSparseLedgerBaseStableV2};
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
@@ -8,7 +8,7 @@ use crate::ledger::{tree_height_for_num_accounts, LedgerAddress};
 use crate::p2p::channels::rpc::P2pRpcId;
 use crate::p2p::PeerId;
 use crate::rpc::LedgerSyncProgress;
-use crate::transition_frontier::sync::ledger::SyncLedgerTarget;
+use crate::transition_frontier::sync::ledger::{SyncLedgerTarget, SyncLedgerTargetKind};
 
 use super::{PeerLedgerQueryError, ACCOUNT_SUBTREE_HEIGHT};
 
@@ -18,6 +18,16 @@ static SYNC_QUERY_EMPTY: BTreeMap<LedgerAddress, LedgerHash> = BTreeMap::new();
 #[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum TransitionFrontierSyncLedgerSnarkedState {
+    /// Fetching the whole epoch ledger with a single `get_epoch_ledger` rpc.
+    /// Falls back to [`Self::NumAccountsPending`] if no peer provides it.
+    EpochLedgerPending {
+        time: Timestamp,
+        target: SyncLedgerTarget,
+        attempts: BTreeMap<PeerId, PeerRpcState>,
+        /// Ledger received from a peer, being built and checked against
+        /// the target by the ledger service.
+        received: Option<ReceivedEpochLedger>,
+    },
     NumAccountsPending {
         time: Timestamp,
         target: SyncLedgerTarget,
@@ -58,6 +68,14 @@ pub enum TransitionFrontierSyncLedgerSnarkedState {
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReceivedEpochLedger {
+    pub sender: PeerId,
+    pub ledger: Arc<MinaBaseSparseLedgerBaseStableV2>,
+    /// Whether the ledger service is already building the ledger.
+    pub is_set_pending: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LedgerAddressQueryPending {
     pub time: Timestamp,
@@ -120,8 +138,28 @@ impl PeerRpcState {
     }
 }
 
+/// Maximum number of peers asked for the epoch ledger before
+/// falling back to the merkle tree sync. Together with the
+/// `get_epoch_ledger` rpc timeout, bounds the time spent before the
+/// fallback.
+pub const EPOCH_LEDGER_QUERY_MAX_ATTEMPTS: usize = 2;
+
 impl TransitionFrontierSyncLedgerSnarkedState {
     pub fn pending(time: Timestamp, target: SyncLedgerTarget) -> Self {
+        match target.kind {
+            SyncLedgerTargetKind::StakingEpoch | SyncLedgerTargetKind::NextEpoch => {
+                Self::EpochLedgerPending {
+                    time,
+                    target,
+                    attempts: Default::default(),
+                    received: None,
+                }
+            }
+            SyncLedgerTargetKind::Root => Self::num_accounts_pending(time, target),
+        }
+    }
+
+    pub fn num_accounts_pending(time: Timestamp, target: SyncLedgerTarget) -> Self {
         Self::NumAccountsPending {
             time,
             target,
@@ -131,7 +169,8 @@ impl TransitionFrontierSyncLedgerSnarkedState {
 
     pub fn is_pending(&self) -> bool {
         match self {
-            Self::NumAccountsPending { .. }
+            Self::EpochLedgerPending { .. }
+            | Self::NumAccountsPending { .. }
             | Self::MerkleTreeSyncPending { .. }
             | Self::NumAccountsSuccess { .. }
             | Self::MerkleTreeSyncSuccess { .. } => true,
@@ -141,7 +180,8 @@ impl TransitionFrontierSyncLedgerSnarkedState {
 
     pub fn target(&self) -> &SyncLedgerTarget {
         match self {
-            Self::NumAccountsPending { target, .. }
+            Self::EpochLedgerPending { target, .. }
+            | Self::NumAccountsPending { target, .. }
             | Self::MerkleTreeSyncPending { target, .. }
             | Self::NumAccountsSuccess { target, .. }
             | Self::MerkleTreeSyncSuccess { target, .. }
@@ -153,6 +193,41 @@ impl TransitionFrontierSyncLedgerSnarkedState {
         &self.target().snarked_ledger_hash
     }
 
+    /// Epoch ledger is requested from one peer at a time.
+    pub fn is_epoch_ledger_query_next(&self) -> bool {
+        match self {
+            Self::EpochLedgerPending { attempts, .. } => {
+                !attempts.values().any(|s| s.is_pending() || s.is_success())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether we should stop asking peers for the epoch ledger.
+    pub fn is_epoch_ledger_query_exhausted(&self) -> bool {
+        match self {
+            Self::EpochLedgerPending { attempts, .. } => {
+                attempts.len() >= EPOCH_LEDGER_QUERY_MAX_ATTEMPTS
+                    && attempts.values().all(|s| s.is_error())
+            }
+            _ => false,
+        }
+    }
+
+    pub fn epoch_ledger_attempts(&self) -> Option<&BTreeMap<PeerId, PeerRpcState>> {
+        match self {
+            Self::EpochLedgerPending { attempts, .. } => Some(attempts),
+            _ => None,
+        }
+    }
+
+    pub fn received_epoch_ledger(&self) -> Option<&ReceivedEpochLedger> {
+        match self {
+            Self::EpochLedgerPending { received, .. } => received.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn is_num_accounts_query_next(&self) -> bool {
         matches!(self, Self::NumAccountsPending { .. })
     }
@@ -217,7 +292,9 @@ impl TransitionFrontierSyncLedgerSnarkedState {
 
     pub fn estimation(&self) -> Option<LedgerSyncProgress> {
         match self {
-            Self::NumAccountsPending { .. } | Self::NumAccountsSuccess { .. } => None,
+            Self::EpochLedgerPending { .. }
+            | Self::NumAccountsPending { .. }
+            | Self::NumAccountsSuccess { .. } => None,
             Self::MerkleTreeSyncPending {
                 total_accounts_expected,
                 synced_accounts_count,
@@ -296,6 +373,19 @@ impl TransitionFrontierSyncLedgerSnarkedState {
         }
     }
 
+    pub fn peer_epoch_ledger_query_state_get_mut(
+        &mut self,
+        peer_id: &PeerId,
+        rpc_id: P2pRpcId,
+    ) -> Option<&mut PeerRpcState> {
+        match self {
+            Self::EpochLedgerPending { attempts, .. } => attempts
+                .get_mut(peer_id)
+                .filter(|s| s.rpc_id() == Some(rpc_id)),
+            _ => None,
+        }
+    }
+
     pub fn peer_address_query_get(
         &self,
         peer_id: &PeerId,
@@ -373,4 +463,10 @@ impl TransitionFrontierSyncLedgerSnarkedState {
             .find(|(id, _)| *id == peer_id)
             .and_then(|(_, s)| s.pending_rpc_id())
     }
+
+    pub fn peer_epoch_ledger_rpc_id(&self, peer_id: &PeerId) -> Option<P2pRpcId> {
+        self.epoch_ledger_attempts()?
+            .get(peer_id)
+            .and_then(|s| s.pending_rpc_id())
+    }
 }
@@ -151,7 +151,9 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncAction {
                 ..
             } => {
                 let blacklist = &state.transition_frontier.blacklist;
-                (state.transition_frontier.sync.is_pending() || state.transition_frontier.sync.is_synced())
+                (state.transition_frontier.sync.is_pending()
+                    || state.transition_frontier.sync.is_synced()
+                    || state.transition_frontier.is_restore_unconfirmed())
                     && !matches!(&state.transition_frontier.sync, TransitionFrontierSyncState::CommitPending { .. } | TransitionFrontierSyncState::CommitSuccess { .. })
                 && state
                     .transition_frontier
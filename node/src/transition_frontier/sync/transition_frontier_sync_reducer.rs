@@ -216,7 +216,9 @@ impl TransitionFrontierSyncState {
                 }
                 Self::CommitPending { .. } => {}
                 Self::CommitSuccess { .. } => {}
-                Self::Synced { time, .. } => {
+                // Idle with a best chain if it was restored from disk.
+                Self::Idle | Self::Synced { .. } => {
+                    let time = state.time().unwrap_or_else(|| meta.time());
                     let applied_blocks: BTreeMap<_, _> =
                         best_chain.iter().map(|b| (b.hash(), b)).collect();
 
@@ -231,7 +233,7 @@ impl TransitionFrontierSyncState {
                             .chain(std::iter::once(new_best_tip.hash()))
                             .map(|hash| match applied_blocks.get(hash) {
                                 Some(&block) => TransitionFrontierSyncBlockState::ApplySuccess {
-                                    time: *time,
+                                    time,
                                     block: block.clone(),
                                 },
                                 None if hash == new_best_tip.hash() => {
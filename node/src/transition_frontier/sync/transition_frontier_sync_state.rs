@@ -153,7 +153,7 @@ pub enum PeerRpcState {
     },
 }
 
-#[derive(Serialize, Deserialize, Display, Debug, Clone)]
+#[derive(Serialize, Deserialize, Display, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyncPhase {
     Bootstrap,
     Catchup,
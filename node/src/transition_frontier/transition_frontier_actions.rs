@@ -3,13 +3,17 @@ use std::sync::Arc;
 
 use mina_p2p_messages::v2::StateHash;
 use openmina_core::block::ArcBlockWithHash;
+use openmina_core::consensus::consensus_take;
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
+use crate::ledger::write::RestoredFrontier;
+
 use super::candidate::TransitionFrontierCandidateAction;
-use super::genesis::TransitionFrontierGenesisAction;
+use super::genesis::{TransitionFrontierGenesisAction, TransitionFrontierGenesisState};
 use super::genesis_effectful::TransitionFrontierGenesisEffectfulAction;
 use super::sync::{SyncError, TransitionFrontierSyncAction, TransitionFrontierSyncState};
+use super::TransitionFrontierRestoreState;
 
 pub type TransitionFrontierActionWithMeta = redux::ActionWithMeta<TransitionFrontierAction>;
 pub type TransitionFrontierActionWithMetaRef<'a> =
@@ -30,6 +34,26 @@ pub enum TransitionFrontierAction {
     #[action_event(level = info)]
     GenesisProvenInject,
 
+    /// Restore the transition frontier stored by a previous run, so that
+    /// only the blocks produced since then need to be synced.
+    RestoreInit,
+    RestorePending,
+    #[action_event(level = info)]
+    RestoreSuccess {
+        /// `None` if there was nothing to restore.
+        frontier: Option<RestoredFrontier>,
+    },
+    #[action_event(level = warn, fields(error))]
+    RestoreError {
+        error: String,
+    },
+    /// Best tip of a peer isn't better than the restored one, so the
+    /// restored transition frontier is up to date.
+    #[action_event(level = info)]
+    RestoreConfirm {
+        peer_best_tip: ArcBlockWithHash,
+    },
+
     Candidate(TransitionFrontierCandidateAction),
     Sync(TransitionFrontierSyncAction),
     /// Transition frontier synced.
@@ -50,6 +74,7 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
             TransitionFrontierAction::GenesisEffect(a) => a.is_enabled(state, time),
             TransitionFrontierAction::GenesisInject => {
                 state.transition_frontier.root().is_none()
+                    && state.transition_frontier.restore.is_finished()
                     && state
                         .transition_frontier
                         .genesis
@@ -60,10 +85,42 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
                 let Some(genesis) = state.transition_frontier.genesis.proven_block() else {
                     return false;
                 };
-                state
-                    .transition_frontier
-                    .root()
-                    .is_none_or(|b| b.is_genesis() && !Arc::ptr_eq(&genesis.block, &b.block))
+                state.transition_frontier.restore.is_finished()
+                    && state
+                        .transition_frontier
+                        .root()
+                        .is_none_or(|b| b.is_genesis() && !Arc::ptr_eq(&genesis.block, &b.block))
+            }
+            TransitionFrontierAction::RestoreInit => {
+                matches!(
+                    state.transition_frontier.restore,
+                    TransitionFrontierRestoreState::Idle
+                ) && !matches!(
+                    state.transition_frontier.genesis,
+                    TransitionFrontierGenesisState::Idle
+                        | TransitionFrontierGenesisState::LedgerLoadPending { .. }
+                )
+            }
+            TransitionFrontierAction::RestorePending => matches!(
+                state.transition_frontier.restore,
+                TransitionFrontierRestoreState::Idle
+            ),
+            TransitionFrontierAction::RestoreSuccess { .. }
+            | TransitionFrontierAction::RestoreError { .. } => matches!(
+                state.transition_frontier.restore,
+                TransitionFrontierRestoreState::Pending { .. }
+            ),
+            TransitionFrontierAction::RestoreConfirm { peer_best_tip } => {
+                state.transition_frontier.is_restore_unconfirmed()
+                    && state.transition_frontier.best_tip().is_some_and(|tip| {
+                        tip.hash() == peer_best_tip.hash()
+                            || !consensus_take(
+                                tip.consensus_state(),
+                                peer_best_tip.consensus_state(),
+                                tip.hash(),
+                                peer_best_tip.hash(),
+                            )
+                    })
             }
             TransitionFrontierAction::Candidate(a) => a.is_enabled(state, time),
             TransitionFrontierAction::Sync(a) => a.is_enabled(state, time),
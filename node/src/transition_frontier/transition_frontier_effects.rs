@@ -1,7 +1,7 @@
 use mina_p2p_messages::gossip::GossipNetMessageV2;
 use redux::Timestamp;
 
-use crate::block_producer::BlockProducerAction;
+use crate::block_producer::{BlockProducerAction, BlockProducerWonSlot};
 use crate::ledger::LEDGER_DEPTH;
 use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
 use crate::p2p::P2pNetworkPubsubAction;
@@ -56,6 +56,17 @@ pub fn transition_frontier_effects<S: crate::Service>(
                 synced_effects(&meta, store);
             }
         }
+        TransitionFrontierAction::RestoreInit => {}
+        TransitionFrontierAction::RestorePending => {}
+        TransitionFrontierAction::RestoreSuccess { .. }
+        | TransitionFrontierAction::RestoreError { .. } => {
+            // Genesis injection was held back until now.
+            store.dispatch(TransitionFrontierAction::GenesisInject);
+            store.dispatch(TransitionFrontierAction::GenesisProvenInject);
+        }
+        TransitionFrontierAction::RestoreConfirm { .. } => {
+            synced_effects(&meta, store);
+        }
         TransitionFrontierAction::Candidate(_) => {}
         TransitionFrontierAction::Sync(a) => {
             match a {
@@ -297,6 +308,17 @@ fn synced_effects<S: crate::Service>(
     };
     if let Some(stats) = store.service.stats() {
         stats.new_best_chain(meta.time(), best_chain);
+
+        if let Some(vrf_evaluator) = store.state.get().block_producer.vrf_evaluator() {
+            let genesis_timestamp = best_tip.genesis_timestamp();
+            let won_slots = vrf_evaluator.won_slots.range(..best_tip.global_slot());
+            let won_slots = won_slots.map(|(_, won_slot)| {
+                BlockProducerWonSlot::from_vrf_won_slot(won_slot, genesis_timestamp)
+            });
+            stats
+                .block_producer()
+                .won_slots_passed(meta.time(), best_tip.global_slot(), won_slots);
+        }
     }
 
     let chain_diff = chain_diff.clone();
@@ -1,7 +1,11 @@
 use super::sync::{SyncError, TransitionFrontierSyncState};
 use super::{
-    TransitionFrontierAction, TransitionFrontierActionWithMetaRef, TransitionFrontierState,
+    TransitionFrontierAction, TransitionFrontierActionWithMetaRef, TransitionFrontierRestoreState,
+    TransitionFrontierState,
 };
+use crate::ledger::write::{LedgerWriteAction, LedgerWriteRequest};
+use crate::ledger_effectful::LedgerEffectfulAction;
+use crate::rpc::{RpcAction, RpcTransitionFrontierEvent};
 use openmina_core::block::AppliedBlock;
 
 impl TransitionFrontierState {
@@ -54,6 +58,47 @@ impl TransitionFrontierState {
                     state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
                 }
             }
+            TransitionFrontierAction::RestoreInit => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(LedgerWriteAction::Init {
+                    request: LedgerWriteRequest::FrontierRestore,
+                    on_init: redux::callback!(
+                        on_frontier_restore_init(_request: LedgerWriteRequest) -> crate::Action {
+                            TransitionFrontierAction::RestorePending
+                        }
+                    ),
+                });
+            }
+            TransitionFrontierAction::RestorePending => {
+                state.restore = TransitionFrontierRestoreState::Pending { time: meta.time() };
+            }
+            TransitionFrontierAction::RestoreSuccess { frontier } => {
+                state.restore = TransitionFrontierRestoreState::Success {
+                    time: meta.time(),
+                    best_tip_hash: frontier
+                        .as_ref()
+                        .and_then(|f| f.best_chain.last())
+                        .map(|b| b.hash().clone()),
+                };
+                let Some(frontier) = frontier else {
+                    return;
+                };
+                state
+                    .needed_protocol_states
+                    .clone_from(&frontier.needed_protocol_states);
+                // Synced only once a peer confirms that the best tip is
+                // still current, otherwise sync is started from it.
+                state.best_chain.clone_from(&frontier.best_chain);
+            }
+            TransitionFrontierAction::RestoreError { error } => {
+                state.restore = TransitionFrontierRestoreState::Error {
+                    time: meta.time(),
+                    error: error.clone(),
+                };
+            }
+            TransitionFrontierAction::RestoreConfirm { .. } => {
+                state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
+            }
             TransitionFrontierAction::Candidate(a) => {
                 super::candidate::TransitionFrontierCandidatesState::reducer(
                     openmina_core::Substate::from_compatible_substate(state_context),
@@ -111,8 +156,32 @@ impl TransitionFrontierState {
                         > tip.height()
                 });
                 state.chain_diff = state.maybe_make_chain_diff(&new_chain);
+                let frontier_update = state.frontier_update(&new_chain);
+                let old_best_tip = state.best_tip().map(|b| b.hash().clone());
                 state.best_chain = new_chain;
                 state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
+
+                let Some(best_tip) = state.best_tip_breadcrumb().cloned() else {
+                    return;
+                };
+                let reorganized_from =
+                    old_best_tip.filter(|old| !state.best_chain.iter().any(|b| b.hash() == old));
+
+                let dispatcher = state_context.into_dispatcher();
+                if let Some(update) = frontier_update {
+                    dispatcher.push(LedgerEffectfulAction::FrontierPersist { update });
+                }
+                if let Some(old_best_tip) = reorganized_from {
+                    dispatcher.push(RpcAction::TransitionFrontierSubscribersNotify {
+                        event: RpcTransitionFrontierEvent::ChainReorganization {
+                            old_best_tip,
+                            new_best_tip: best_tip.hash().clone(),
+                        },
+                    });
+                }
+                dispatcher.push(RpcAction::TransitionFrontierSubscribersNotify {
+                    event: RpcTransitionFrontierEvent::NewBestTip(best_tip),
+                });
             }
             TransitionFrontierAction::SyncFailed { error, .. } => {
                 match error {
@@ -1,14 +1,17 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use ledger::transaction_pool::diff::BestTipDiff;
 use mina_p2p_messages::v2::{
-    MinaStateProtocolStateBodyValueStableV2, MinaStateProtocolStateValueStableV2, StateHash,
-    TransactionHash,
+    LedgerHash, MinaStateProtocolStateBodyValueStableV2, MinaStateProtocolStateValueStableV2,
+    StateHash, TransactionHash,
 };
 use openmina_core::block::{AppliedBlock, ArcBlockWithHash};
 use openmina_core::bug_condition;
+use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
+use crate::ledger::write::FrontierUpdate;
+
 use super::candidate::TransitionFrontierCandidatesState;
 use super::genesis::TransitionFrontierGenesisState;
 use super::sync::TransitionFrontierSyncState;
@@ -19,6 +22,8 @@ pub struct TransitionFrontierState {
     pub config: TransitionFrontierConfig,
     /// Genesis block generation/proving state
     pub genesis: TransitionFrontierGenesisState,
+    /// Restoration of the transition frontier stored by a previous run
+    pub restore: TransitionFrontierRestoreState,
     /// Current best known chain, from root of the transition frontier to best tip
     pub best_chain: Vec<AppliedBlock>,
     /// Needed protocol states for applying transactions in the root
@@ -37,11 +42,38 @@ pub struct TransitionFrontierState {
     pub archive_enabled: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum TransitionFrontierRestoreState {
+    Idle,
+    Pending {
+        time: Timestamp,
+    },
+    Success {
+        time: Timestamp,
+        /// Best tip of the restored transition frontier, `None` if there
+        /// was nothing to restore.
+        best_tip_hash: Option<StateHash>,
+    },
+    Error {
+        time: Timestamp,
+        error: String,
+    },
+}
+
+impl TransitionFrontierRestoreState {
+    /// Genesis block is injected only once this returns true, so that
+    /// it doesn't replace a restored transition frontier.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Success { .. } | Self::Error { .. })
+    }
+}
+
 impl TransitionFrontierState {
     pub fn new(config: TransitionFrontierConfig, archive_enabled: bool) -> Self {
         Self {
             config,
             genesis: TransitionFrontierGenesisState::Idle,
+            restore: TransitionFrontierRestoreState::Idle,
             candidates: TransitionFrontierCandidatesState::new(),
             best_chain: Vec::with_capacity(290),
             needed_protocol_states: Default::default(),
@@ -68,6 +100,50 @@ impl TransitionFrontierState {
         self.best_chain.first()
     }
 
+    /// Only the staking and next epoch ledgers of our best tip are served
+    /// to peers.
+    pub fn is_epoch_ledger_served(&self, ledger_hash: &LedgerHash) -> bool {
+        self.best_tip().is_some_and(|best_tip| {
+            best_tip.staking_epoch_ledger_hash() == ledger_hash
+                || best_tip.next_epoch_ledger_hash() == ledger_hash
+        })
+    }
+
+    /// Transition frontier restored from disk, whose best tip wasn't
+    /// confirmed by a peer yet.
+    ///
+    /// Until then the node isn't considered synced, the best tip may be
+    /// outdated.
+    pub fn is_restore_unconfirmed(&self) -> bool {
+        matches!(self.sync, TransitionFrontierSyncState::Idle) && !self.best_chain.is_empty()
+    }
+
+    /// Changes to write to disk when the best chain is replaced by
+    /// `new_chain`.
+    pub fn frontier_update(&self, new_chain: &[AppliedBlock]) -> Option<FrontierUpdate> {
+        let (root, best_tip) = (new_chain.first()?, new_chain.last()?);
+        let old_hashes = self
+            .best_chain
+            .iter()
+            .map(|block| block.hash())
+            .collect::<BTreeSet<_>>();
+        let new_blocks_count = new_chain
+            .iter()
+            .rev()
+            .take_while(|block| !old_hashes.contains(block.hash()))
+            .count();
+        let root_changed = self
+            .root()
+            .is_none_or(|old_root| old_root.hash() != root.hash());
+
+        Some(FrontierUpdate {
+            root: root.clone(),
+            best_tip_hash: best_tip.hash().clone(),
+            new_blocks: new_chain[new_chain.len() - new_blocks_count..].to_vec(),
+            needed_protocol_states: root_changed.then(|| self.needed_protocol_states.clone()),
+        })
+    }
+
     /// FIXME
     /// Note(adonagy): This can be expensive, keep a map with all the tx hashis in the best chain
     pub fn contains_transaction(&self, hash: &TransactionHash) -> bool {
@@ -277,10 +277,12 @@ impl Cluster {
             },
             p2p: P2pConfig {
                 libp2p_port: Some(libp2p_port),
+                libp2p_quic: testing_config.libp2p_quic,
                 listen_port: Some(http_port),
                 identity_pub_key: p2p_sec_key.public_key(),
                 initial_peers,
                 external_addrs: vec![],
+                nat_port_mapping: false,
                 enabled_channels: ChannelId::iter_all().collect(),
                 peer_discovery: testing_config.peer_discovery,
                 timeouts: testing_config.timeouts,
@@ -292,6 +294,9 @@ impl Cluster {
                         .unwrap_or_default(),
                     ..Default::default()
                 },
+                initial_trust: Default::default(),
+                initial_address_book: Default::default(),
+                access: Default::default(),
             },
             transition_frontier: TransitionFrontierConfig::new(testing_config.genesis),
             block_producer: block_producer_config,
@@ -342,7 +347,7 @@ impl Cluster {
                 let task = async {
                     tokio::select! {
                         _ = shutdown.wait() => {}
-                        _ = http_server::run(http_port, rpc_sender) => {}
+                        _ = http_server::run(http_port, rpc_sender, Default::default()) => {}
                     }
                 };
                 local_set.block_on(&runtime, task);
@@ -220,6 +220,11 @@ impl<'a> ClusterRunner<'a> {
         self.cluster.pending_events(poll)
     }
 
+    pub fn p2p_pubsub_publish_raw(&mut self, node_id: ClusterNodeId, data: Vec<u8>) -> bool {
+        self.node_mut(node_id)
+            .map_or(false, |node| node.p2p_pubsub_publish_raw(data))
+    }
+
     pub fn node_pending_events(
         &mut self,
         node_id: ClusterNodeId,
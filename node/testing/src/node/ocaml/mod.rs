@@ -3,7 +3,7 @@ pub use config::*;
 use mina_p2p_messages::v2::StateHash;
 use node::p2p::{
     connection::outgoing::{P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts},
-    PeerId,
+    ConnectionTransport, PeerId,
 };
 use openmina_core::{thread, ChainId};
 
@@ -165,6 +165,7 @@ impl OcamlNode {
             peer_id: self.peer_id(),
             host: [127, 0, 0, 1].into(),
             port: self.libp2p_port,
+            transport: ConnectionTransport::Tcp,
         })
     }
 
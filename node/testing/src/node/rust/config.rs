@@ -39,6 +39,8 @@ pub struct RustNodeTestingConfig {
     #[serde(default)]
     pub libp2p_port: Option<u16>,
     #[serde(default)]
+    pub libp2p_quic: bool,
+    #[serde(default)]
     pub recorder: Recorder,
     pub peer_discovery: bool,
 }
@@ -68,6 +70,7 @@ impl RustNodeTestingConfig {
             snark_worker: None,
             timeouts: P2pTimeouts::default(),
             libp2p_port: None,
+            libp2p_quic: false,
             recorder: Default::default(),
             peer_discovery: true,
         }
@@ -84,6 +87,7 @@ impl RustNodeTestingConfig {
             snark_worker: None,
             timeouts: P2pTimeouts::without_rpc(),
             libp2p_port: None,
+            libp2p_quic: false,
             recorder: Default::default(),
             peer_discovery: true,
         }
@@ -119,6 +123,11 @@ impl RustNodeTestingConfig {
         self
     }
 
+    pub fn with_libp2p_quic(mut self) -> Self {
+        self.libp2p_quic = true;
+        self
+    }
+
     pub fn with_daemon_json<P: AsRef<Path>>(mut self, daemon_json: P) -> Self {
         self.genesis = Arc::new(GenesisConfig::DaemonJson(
             serde_json::from_reader(&mut File::open(daemon_json).expect("daemon json file"))
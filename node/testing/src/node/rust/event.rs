@@ -30,6 +30,7 @@ impl NonDeterministicEvent {
                 P2pEvent::Channel(_) => return None,
                 #[cfg(feature = "p2p-libp2p")]
                 P2pEvent::MioEvent(_) => return None,
+                P2pEvent::Nat(_) => return None,
             },
             Event::Rpc(id, req) => match req.as_ref() {
                 RpcRequest::P2pConnectionIncoming(_) => return None,
@@ -8,8 +8,9 @@ use node::event_source::EventSourceAction;
 use node::p2p::connection::outgoing::{
     P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts,
 };
+use node::p2p::network::pubsub::TOPIC;
 use node::p2p::webrtc::SignalingMethod;
-use node::p2p::PeerId;
+use node::p2p::{ConnectionTransport, P2pNetworkPubsubAction, PeerId};
 use node::service::P2pDisconnectionService;
 use node::{Action, CheckTimeoutsAction, State, Store};
 use redux::EnablingCondition;
@@ -68,10 +69,17 @@ impl Node {
             let signaling = SignalingMethod::Http(([127, 0, 0, 1], port).into());
             P2pConnectionOutgoingInitOpts::WebRTC { peer_id, signaling }
         } else {
+            let config = self.store.state().p2p.config();
+            let transport = if config.libp2p_quic {
+                ConnectionTransport::Quic
+            } else {
+                ConnectionTransport::Tcp
+            };
             let opts = P2pConnectionOutgoingInitLibp2pOpts {
                 peer_id,
                 host: node::p2p::webrtc::Host::Ipv4([127, 0, 0, 1].into()),
-                port: self.store.state().p2p.config().libp2p_port.unwrap(),
+                port: config.libp2p_port.unwrap(),
+                transport,
             };
             P2pConnectionOutgoingInitOpts::LibP2P(opts)
         }
@@ -190,4 +198,21 @@ impl Node {
     pub fn p2p_disconnect(&mut self, peer_id: PeerId) -> bool {
         self.service_mut().disconnect(peer_id)
     }
+
+    /// Publishes `data` to the meshsub topic as is, without encoding it
+    /// as a gossip message first.
+    pub fn p2p_pubsub_publish_raw(&mut self, data: Vec<u8>) -> bool {
+        let Some(p2p) = self.state().p2p.ready() else {
+            return false;
+        };
+        let seqno = p2p.network.scheduler.broadcast_state.seq
+            + p2p.config.meshsub.initial_time.as_nanos() as u64;
+        let author = p2p.config.identity_pub_key.peer_id();
+        self.dispatch(P2pNetworkPubsubAction::Sign {
+            seqno,
+            author,
+            data: data.into(),
+            topic: TOPIC.to_owned(),
+        })
+    }
 }
@@ -136,7 +136,9 @@ pub fn as_event_mio_error(event: &Event) -> Option<ConnectionAddr> {
 pub fn as_event_mio_data_send_receive(event: &Event) -> Option<ConnectionAddr> {
     match event {
         Event::P2p(P2pEvent::MioEvent(
-            MioEvent::IncomingDataDidReceive(addr, _) | MioEvent::OutgoingDataDidSend(addr, _),
+            MioEvent::IncomingDataDidReceive(addr, _)
+            | MioEvent::OutgoingDataDidSend(addr, _)
+            | MioEvent::QuicStreamDataDidReceive { addr, .. },
         )) => Some(*addr),
         _ => None,
     }
@@ -147,6 +149,7 @@ pub fn as_event_mio_connection_event(event: &Event) -> Option<ConnectionAddr> {
         Event::P2p(P2pEvent::MioEvent(
             MioEvent::IncomingDataDidReceive(addr, _)
             | MioEvent::OutgoingDataDidSend(addr, _)
+            | MioEvent::QuicStreamDataDidReceive { addr, .. }
             | MioEvent::ConnectionDidClose(addr, _),
         )) => Some(*addr),
         _ => None,
@@ -48,11 +48,11 @@ use self::p2p::basic_incoming_connections::{
 use self::p2p::basic_outgoing_connections::{
     ConnectToInitialPeers, ConnectToInitialPeersBecomeReady, ConnectToUnavailableInitialPeers,
     DontConnectToInitialPeerWithSameId, DontConnectToNodeWithSameId, DontConnectToSelfInitialPeer,
-    MakeMultipleOutgoingConnections, MakeOutgoingConnection,
+    MakeMultipleOutgoingConnections, MakeOutgoingConnection, MakeOutgoingQuicConnection,
 };
 use self::p2p::kademlia::KademliaBootstrap;
-use self::p2p::pubsub::P2pReceiveMessage;
-use self::p2p::signaling::P2pSignaling;
+use self::p2p::pubsub::{P2pPubsubPruneSpammer, P2pReceiveMessage};
+use self::p2p::signaling::{P2pSignaling, P2pSignalingRelay};
 use self::record_replay::block_production::RecordReplayBlockProduction;
 use self::record_replay::bootstrap::RecordReplayBootstrap;
 use self::simulation::small::SimulationSmall;
@@ -84,7 +84,9 @@ pub enum Scenarios {
     SimulationSmall(SimulationSmall),
     SimulationSmallForeverRealTime(SimulationSmallForeverRealTime),
     P2pReceiveMessage(P2pReceiveMessage),
+    P2pPubsubPruneSpammer(P2pPubsubPruneSpammer),
     P2pSignaling(P2pSignaling),
+    P2pSignalingRelay(P2pSignalingRelay),
     P2pConnectionDiscoveryRustNodeAsSeed(P2pConnectionDiscoveryRustNodeAsSeed),
     MultiNodePubsubPropagateBlock(MultiNodePubsubPropagateBlock),
     RecordReplayBootstrap(RecordReplayBootstrap),
@@ -97,6 +99,7 @@ pub enum Scenarios {
     KademliaBootstrap(KademliaBootstrap),
     AcceptIncomingConnection(AcceptIncomingConnection),
     MakeOutgoingConnection(MakeOutgoingConnection),
+    MakeOutgoingQuicConnection(MakeOutgoingQuicConnection),
     AcceptMultipleIncomingConnections(AcceptMultipleIncomingConnections),
     MakeMultipleOutgoingConnections(MakeMultipleOutgoingConnections),
     DontConnectToNodeWithSameId(DontConnectToNodeWithSameId),
@@ -132,6 +135,7 @@ impl Scenarios {
             Self::SimulationSmallForeverRealTime(_) => true,
             Self::MultiNodePubsubPropagateBlock(_) => true, // in progress
             Self::P2pSignaling(_) => !cfg!(feature = "p2p-webrtc"),
+            Self::P2pSignalingRelay(_) => !cfg!(feature = "p2p-webrtc"),
             _ => false,
         }
     }
@@ -190,7 +194,9 @@ impl Scenarios {
             Self::SimulationSmall(_) => SimulationSmall::DOCS,
             Self::SimulationSmallForeverRealTime(_) => SimulationSmallForeverRealTime::DOCS,
             Self::P2pReceiveMessage(_) => P2pReceiveMessage::DOCS,
+            Self::P2pPubsubPruneSpammer(_) => P2pPubsubPruneSpammer::DOCS,
             Self::P2pSignaling(_) => P2pSignaling::DOCS,
+            Self::P2pSignalingRelay(_) => P2pSignalingRelay::DOCS,
             Self::P2pConnectionDiscoveryRustNodeAsSeed(_) => {
                 P2pConnectionDiscoveryRustNodeAsSeed::DOCS
             }
@@ -205,6 +211,7 @@ impl Scenarios {
             Self::KademliaBootstrap(_) => KademliaBootstrap::DOCS,
             Self::AcceptIncomingConnection(_) => AcceptIncomingConnection::DOCS,
             Self::MakeOutgoingConnection(_) => MakeOutgoingConnection::DOCS,
+            Self::MakeOutgoingQuicConnection(_) => MakeOutgoingQuicConnection::DOCS,
             Self::AcceptMultipleIncomingConnections(_) => AcceptMultipleIncomingConnections::DOCS,
             Self::MakeMultipleOutgoingConnections(_) => MakeMultipleOutgoingConnections::DOCS,
             Self::DontConnectToNodeWithSameId(_) => DontConnectToNodeWithSameId::DOCS,
@@ -261,7 +268,9 @@ impl Scenarios {
             Self::SimulationSmall(v) => v.run(runner).await,
             Self::SimulationSmallForeverRealTime(v) => v.run(runner).await,
             Self::P2pReceiveMessage(v) => v.run(runner).await,
+            Self::P2pPubsubPruneSpammer(v) => v.run(runner).await,
             Self::P2pSignaling(v) => v.run(runner).await,
+            Self::P2pSignalingRelay(v) => v.run(runner).await,
             Self::P2pConnectionDiscoveryRustNodeAsSeed(v) => v.run(runner).await,
             Self::MultiNodePubsubPropagateBlock(v) => v.run(runner).await,
             Self::RecordReplayBootstrap(v) => v.run(runner).await,
@@ -274,6 +283,7 @@ impl Scenarios {
             Self::KademliaBootstrap(v) => v.run(runner).await,
             Self::AcceptIncomingConnection(v) => v.run(runner).await,
             Self::MakeOutgoingConnection(v) => v.run(runner).await,
+            Self::MakeOutgoingQuicConnection(v) => v.run(runner).await,
             Self::AcceptMultipleIncomingConnections(v) => v.run(runner).await,
             Self::MakeMultipleOutgoingConnections(v) => v.run(runner).await,
             Self::DontConnectToNodeWithSameId(v) => v.run(runner).await,
@@ -46,11 +46,7 @@ impl MultiNodeVrfGetCorrectSlots {
             initial_peers: Vec::new(),
             peer_id: Default::default(),
             block_producer: Some(RustNodeBlockProducerTestingConfig {
-                config: BlockProducerConfig {
-                    pub_key: sec_key.public_key().into(),
-                    custom_coinbase_receiver: None,
-                    proposed_protocol_version: None,
-                },
+                config: BlockProducerConfig::new(sec_key.public_key().into()),
                 sec_key,
             }),
             snark_worker: None,
@@ -63,11 +63,7 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
 
         let producer_node = runner.add_rust_node(RustNodeTestingConfig {
             block_producer: Some(RustNodeBlockProducerTestingConfig {
-                config: BlockProducerConfig {
-                    pub_key: sec_key.public_key().into(),
-                    custom_coinbase_receiver: None,
-                    proposed_protocol_version: None,
-                },
+                config: BlockProducerConfig::new(sec_key.public_key().into()),
                 sec_key: sec_key.clone(),
             }),
             ..rust_config.clone()
@@ -81,6 +77,7 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
                 )),
                 strategy: SnarkerStrategy::Sequential,
                 auto_commit: true,
+                workers: 1,
             }),
             ..rust_config
         });
@@ -139,8 +136,9 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
         let initial_balance = if let Some(pending_evaluation) = vrf_evaluator.current_evaluation() {
             let (_, balance) = pending_evaluation
                 .epoch_data
-                .delegator_table
-                .get(&AccountIndex(1))
+                .delegator_tables
+                .values()
+                .find_map(|table| table.get(&AccountIndex(1)))
                 .expect("Account not found");
             eprintln!("Initial balance: {balance}");
             *balance
@@ -235,8 +233,9 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
         let new_balance = if let Some(pending_evaluation) = vrf_evaluator.current_evaluation() {
             let (_, balance) = pending_evaluation
                 .epoch_data
-                .delegator_table
-                .get(&AccountIndex(1))
+                .delegator_tables
+                .values()
+                .find_map(|table| table.get(&AccountIndex(1)))
                 .expect("Account not found");
             eprintln!("New balance: {balance}");
             *balance
@@ -49,11 +49,7 @@ impl MultiNodeVrfEpochBoundsEvaluation {
 
         let producer_node = runner.add_rust_node(RustNodeTestingConfig {
             block_producer: Some(RustNodeBlockProducerTestingConfig {
-                config: BlockProducerConfig {
-                    pub_key: sec_key.public_key().into(),
-                    custom_coinbase_receiver: None,
-                    proposed_protocol_version: None,
-                },
+                config: BlockProducerConfig::new(sec_key.public_key().into()),
                 sec_key: sec_key.clone(),
             }),
             ..rust_config.clone()
@@ -67,6 +63,7 @@ impl MultiNodeVrfEpochBoundsEvaluation {
                 )),
                 strategy: SnarkerStrategy::Sequential,
                 auto_commit: true,
+                workers: 1,
             }),
             ..rust_config
         });
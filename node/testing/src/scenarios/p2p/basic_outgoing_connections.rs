@@ -6,7 +6,7 @@ use std::{
 use node::p2p::{
     connection::outgoing::{P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts},
     identity::SecretKey,
-    P2pPeerStatus, P2pTimeouts, PeerId,
+    ConnectionTransport, P2pPeerStatus, P2pTimeouts, PeerId,
 };
 
 use crate::{
@@ -14,9 +14,9 @@ use crate::{
     node::RustNodeTestingConfig,
     scenario::ListenerNode,
     scenarios::{
-        add_rust_nodes, add_rust_nodes_with, peer_is_ready, wait_for_connection_error,
-        wait_for_connection_established, wait_for_nodes_listening_on_localhost, ClusterRunner,
-        Driver,
+        add_rust_nodes, add_rust_nodes_with, get_p2p_state, peer_is_ready,
+        wait_for_connection_error, wait_for_connection_established,
+        wait_for_nodes_listening_on_localhost, ClusterRunner, Driver,
     },
 };
 
@@ -25,6 +25,7 @@ fn custom_listener(peer_id: PeerId, port: u16) -> ListenerNode {
         peer_id,
         host: node::p2p::webrtc::Host::Ipv4([127, 0, 0, 1].into()),
         port,
+        transport: ConnectionTransport::Tcp,
     })
     .into()
 }
@@ -71,6 +72,51 @@ impl MakeOutgoingConnection {
     }
 }
 
+/// Node should be able to make an outgoing QUIC connection to a listening node.
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct MakeOutgoingQuicConnection;
+
+impl MakeOutgoingQuicConnection {
+    pub async fn run(self, runner: ClusterRunner<'_>) {
+        let mut driver = Driver::new(runner);
+
+        let config = RustNodeTestingConfig::devnet_default().with_libp2p_quic();
+        let (node1, _) = driver.add_rust_node(config.clone());
+        let (node2, peer_id2) = driver.add_rust_node(config);
+
+        // wait for the peer to listen
+        let satisfied =
+            wait_for_nodes_listening_on_localhost(&mut driver, Duration::from_secs(30), [node2])
+                .await
+                .unwrap();
+        assert!(satisfied, "the peer should be listening");
+
+        driver
+            .exec_step(crate::scenario::ScenarioStep::ConnectNodes {
+                dialer: node1,
+                listener: crate::scenario::ListenerNode::Rust(node2),
+            })
+            .await
+            .expect("connect event should be dispatched");
+
+        let connected = wait_for_connection_established(
+            &mut driver,
+            Duration::from_secs(30),
+            (node1, &peer_id2),
+        )
+        .await
+        .unwrap();
+        assert!(connected, "peer should be connected");
+
+        let (addr, _) = get_p2p_state(driver.inner(), node1)
+            .network
+            .scheduler
+            .find_peer(&peer_id2)
+            .expect("peer should have a connection");
+        assert_eq!(addr.transport, ConnectionTransport::Quic);
+    }
+}
+
 /// Node should be able to create multiple outgoing connections.
 #[derive(documented::Documented, Default, Clone, Copy)]
 pub struct MakeMultipleOutgoingConnections;
@@ -181,6 +227,7 @@ impl DontConnectToSelfInitialPeer {
                 peer_id,
                 host: node::p2p::webrtc::Host::Ipv4([127, 0, 0, 1].into()),
                 port,
+                transport: ConnectionTransport::Tcp,
             });
         let (node_ut, _) = driver.add_rust_node(
             RustNodeTestingConfig::devnet_default()
@@ -378,6 +425,7 @@ impl ConnectToUnavailableInitialPeers {
                         peer_id,
                         host: [127, 0, 0, 1].into(),
                         port,
+                        transport: ConnectionTransport::Tcp,
                     }
                     .into(),
                 );
@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-use node::ActionKind;
+use node::{
+    p2p::{network::pubsub::TOPIC, P2pNetworkAction, P2pNetworkPubsubAction, PeerId},
+    Action, ActionKind, P2pAction, State,
+};
 
 use crate::{
     hosts,
@@ -57,3 +60,74 @@ impl P2pReceiveMessage {
             .expect("Test failed");
     }
 }
+
+/// Peer publishing invalid messages gets pruned from the mesh
+/// 1. Create a node and a spammer node, with discovery disabled and the first node as the only peer
+/// 2. Wait for the node to add the spammer to its mesh
+/// 3. Publish a message that can't be decoded from the spammer
+/// 4. Wait for the node to prune the spammer and check that its score is negative
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct P2pPubsubPruneSpammer;
+
+impl P2pPubsubPruneSpammer {
+    pub async fn run(self, mut runner: ClusterRunner<'_>) {
+        let config = RustNodeTestingConfig::devnet_default().with_no_peer_discovery();
+        let node = runner.add_rust_node(config.clone());
+        let spammer = runner.add_rust_node(config.initial_peers(vec![node.into()]));
+        let spammer_peer_id = runner.node(spammer).expect("spammer not found").peer_id();
+
+        runner
+            .run(
+                RunCfg::default()
+                    .timeout(Duration::from_secs(60))
+                    .action_handler(move |node_id, state, _, _| {
+                        node_id == node && is_on_mesh(state, &spammer_peer_id)
+                    }),
+            )
+            .await
+            .expect("spammer wasn't added to the mesh");
+
+        assert!(
+            runner.p2p_pubsub_publish_raw(spammer, vec![0xff; 64]),
+            "failed to publish from the spammer"
+        );
+
+        runner
+            .run(
+                RunCfg::default()
+                    .timeout(Duration::from_secs(60))
+                    .action_handler(move |node_id, _, _, action| {
+                        node_id == node
+                            && matches!(
+                                action.action(),
+                                Action::P2p(P2pAction::Network(P2pNetworkAction::Pubsub(
+                                    P2pNetworkPubsubAction::Prune { peer_id, .. }
+                                ))) if *peer_id == spammer_peer_id
+                            )
+                    }),
+            )
+            .await
+            .expect("spammer wasn't pruned from the mesh");
+
+        let score = runner
+            .node(node)
+            .and_then(|node| node.state().p2p.ready())
+            .map(|p2p| {
+                p2p.network
+                    .scheduler
+                    .broadcast_state
+                    .peer_score(&spammer_peer_id)
+            })
+            .expect("node not found");
+        assert!(score < 0.0, "spammer score is not negative: {score}");
+    }
+}
+
+fn is_on_mesh(state: &State, peer_id: &PeerId) -> bool {
+    state
+        .p2p
+        .ready()
+        .and_then(|p2p| p2p.network.scheduler.broadcast_state.topics.get(TOPIC))
+        .and_then(|topic| topic.get(peer_id))
+        .is_some_and(|peer| peer.on_mesh())
+}
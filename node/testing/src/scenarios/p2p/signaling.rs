@@ -1,13 +1,22 @@
 use std::{collections::BTreeSet, time::Duration};
 
 use node::{
-    p2p::{P2pPeerAction, PeerId},
+    p2p::{
+        connection::{
+            incoming::{IncomingSignalingMethod, P2pConnectionIncomingAction},
+            outgoing::{P2pConnectionOutgoingAction, P2pConnectionOutgoingInitOpts},
+            P2pConnectionAction,
+        },
+        webrtc::SignalingMethod,
+        P2pPeerAction, PeerId,
+    },
     Action, P2pAction,
 };
 
 use crate::{
     cluster::ClusterConfig,
     node::RustNodeTestingConfig,
+    scenario::ListenerNode,
     scenarios::{ClusterRunner, DynEffectsData, RunCfg},
 };
 
@@ -67,3 +76,93 @@ impl P2pSignaling {
             .expect("peers didn't discover each other");
     }
 }
+
+/// Makes sure that a WebRTC only peer connects to a libp2p peer via p2p
+/// signaling relayed by a node that is connected to both of them, to the
+/// former over WebRTC and to the latter over libp2p.
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct P2pSignalingRelay;
+
+impl P2pSignalingRelay {
+    pub async fn run(self, mut runner: ClusterRunner<'_>) {
+        let config = RustNodeTestingConfig::devnet_default().with_no_peer_discovery();
+
+        let relay = runner.add_rust_node(config.clone());
+        let relay_node = runner.node(relay).expect("relay node");
+        let relay_peer_id = relay_node.peer_id();
+        let relay_http_port = relay_node.state().p2p.config().listen_port.unwrap();
+
+        // dials the relay over WebRTC only
+        let webrtc_node =
+            runner.add_rust_node(config.clone().initial_peers(vec![ListenerNode::Custom(
+                P2pConnectionOutgoingInitOpts::WebRTC {
+                    peer_id: relay_peer_id,
+                    signaling: SignalingMethod::Http(([127, 0, 0, 1], relay_http_port).into()),
+                },
+            )]));
+        // dials the relay over libp2p
+        let libp2p_node = runner.add_rust_node(config.initial_peers(vec![relay.into()]));
+
+        let webrtc_peer_id = runner.node(webrtc_node).unwrap().peer_id();
+        let libp2p_peer_id = runner.node(libp2p_node).unwrap().peer_id();
+        let peers = [(webrtc_node, libp2p_peer_id), (libp2p_node, webrtc_peer_id)];
+
+        // relays used by the connection attempts between the two peers
+        let relays = DynEffectsData::new(BTreeSet::<PeerId>::new());
+
+        runner
+            .run(
+                RunCfg::default()
+                    .timeout(Duration::from_secs(120))
+                    .advance_time(1..=100)
+                    .action_handler(move |node_id, _state, _, action| {
+                        let Some(&(_, other)) = peers.iter().find(|(id, _)| *id == node_id) else {
+                            return false;
+                        };
+                        match action.action() {
+                            Action::P2p(P2pAction::Connection(P2pConnectionAction::Outgoing(
+                                P2pConnectionOutgoingAction::Init {
+                                    opts:
+                                        P2pConnectionOutgoingInitOpts::WebRTC {
+                                            peer_id,
+                                            signaling:
+                                                SignalingMethod::P2p {
+                                                    relay_peer_id: relay,
+                                                },
+                                        },
+                                    ..
+                                },
+                            ))) if *peer_id == other => {
+                                relays.inner().insert(*relay);
+                                false
+                            }
+                            Action::P2p(P2pAction::Connection(P2pConnectionAction::Incoming(
+                                P2pConnectionIncomingAction::Init { opts, .. },
+                            ))) if opts.peer_id == other => {
+                                if let IncomingSignalingMethod::P2p {
+                                    relay_peer_id: relay,
+                                } = &opts.signaling
+                                {
+                                    relays.inner().insert(*relay);
+                                }
+                                false
+                            }
+                            Action::P2p(P2pAction::Peer(P2pPeerAction::Ready {
+                                peer_id, ..
+                            })) if *peer_id == other => {
+                                let relays = relays.inner();
+                                assert_eq!(
+                                    *relays,
+                                    BTreeSet::from([relay_peer_id]),
+                                    "peers must be connected via the relay"
+                                );
+                                true
+                            }
+                            _ => false,
+                        }
+                    }),
+            )
+            .await
+            .expect("webrtc peer didn't connect to libp2p peer via the relay");
+    }
+}
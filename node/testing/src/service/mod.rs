@@ -40,11 +40,11 @@ use node::snark::work_verify::{SnarkWorkVerifyId, SnarkWorkVerifyService};
 use node::snark::{BlockVerifier, SnarkEvent, TransactionVerifier, VerifierSRS};
 use node::snark_pool::SnarkPoolService;
 use node::stats::Stats;
-use node::transition_frontier::archive::archive_service::ArchiveService;
+use node::transition_frontier::archive::archive_service::{ArchiveService, ArchiveSinkStatus};
 use node::transition_frontier::genesis::GenesisConfig;
 use node::{
     event_source::Event,
-    external_snark_worker::SnarkWorkSpec,
+    external_snark_worker::{SnarkWorkSpec, SnarkWorkerId},
     external_snark_worker_effectful::ExternalSnarkWorkerService,
     p2p::{
         connection::outgoing::P2pConnectionOutgoingInitOpts,
@@ -510,6 +510,10 @@ impl ArchiveService for NodeTestingService {
     fn send_to_archive(&mut self, data: BlockApplyResult) {
         self.real.send_to_archive(data);
     }
+
+    fn archive_sinks_status(&mut self) -> Option<Vec<ArchiveSinkStatus>> {
+        self.real.archive_sinks_status()
+    }
 }
 
 use std::cell::RefCell;
@@ -531,31 +535,28 @@ impl BlockProducerService for NodeTestingService {
             let dummy_proof = (*ledger::dummy::dummy_blockchain_proof()).clone();
             BlockProducerEvent::BlockProve(block_hash, Ok(dummy_proof.into())).into()
         }
-        let keypair = self.real.block_producer().unwrap().keypair();
+        let signer = self
+            .real
+            .block_producer()
+            .unwrap()
+            .block_creator_signer(&input);
 
         match self.proof_kind() {
             ProofKind::Dummy => {
                 let _ = self.real.event_sender().send(dummy_proof_event(block_hash));
             }
-            ProofKind::ConstraintsChecked => {
-                match openmina_node_native::block_producer::prove(
-                    self.provers(),
-                    &mut input,
-                    &keypair,
-                    true,
-                ) {
-                    Err(e)
-                        if matches!(
-                            e.downcast_ref::<ProofError>(),
-                            Some(ProofError::ConstraintsOk)
-                        ) =>
-                    {
-                        let _ = self.real.event_sender().send(dummy_proof_event(block_hash));
-                    }
-                    Err(err) => panic!("unexpected block proof generation error: {err:?}"),
-                    Ok(_) => unreachable!(),
+            ProofKind::ConstraintsChecked => match signer.prove_block(&mut input, true) {
+                Err(e)
+                    if matches!(
+                        e.downcast_ref::<ProofError>(),
+                        Some(ProofError::ConstraintsOk)
+                    ) =>
+                {
+                    let _ = self.real.event_sender().send(dummy_proof_event(block_hash));
                 }
-            }
+                Err(err) => panic!("unexpected block proof generation error: {err:?}"),
+                Ok(_) => unreachable!(),
+            },
             ProofKind::Full => {
                 // TODO(binier): handle if block is genesis based on fork constants.
                 let is_genesis = input
@@ -572,13 +573,9 @@ impl BlockProducerService for NodeTestingService {
                     {
                         Ok(proof.clone())
                     } else {
-                        openmina_node_native::block_producer::prove(
-                            self.provers(),
-                            &mut input,
-                            &keypair,
-                            false,
-                        )
-                        .map_err(|err| format!("{err:?}"))
+                        signer
+                            .prove_block(&mut input, false)
+                            .map_err(|err| format!("{err:?}"))
                     }
                 });
                 if let Some(proof) = res.as_ref().ok().filter(|_| is_genesis) {
@@ -595,6 +592,7 @@ impl BlockProducerService for NodeTestingService {
 
     fn with_producer_keypair<T>(
         &self,
+        _pub_key: &node::account::AccountPublicKey,
         _f: impl FnOnce(&node::account::AccountSecretKey) -> T,
     ) -> Option<T> {
         None
@@ -604,6 +602,7 @@ impl BlockProducerService for NodeTestingService {
 impl ExternalSnarkWorkerService for NodeTestingService {
     fn start(
         &mut self,
+        worker_id: SnarkWorkerId,
         public_key: NonZeroCurvePoint,
         fee: CurrencyFeeStableV1,
         _: TransactionVerifier,
@@ -619,13 +618,14 @@ impl ExternalSnarkWorkerService for NodeTestingService {
         let _ = self
             .real
             .event_sender()
-            .send(ExternalSnarkWorkerEvent::Started.into());
+            .send((worker_id, ExternalSnarkWorkerEvent::Started).into());
         Ok(())
-        // self.real.start(path, public_key, fee)
+        // self.real.start(worker_id, path, public_key, fee)
     }
 
     fn submit(
         &mut self,
+        worker_id: SnarkWorkerId,
         spec: SnarkWorkSpec,
     ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
         let sok_digest = self.snarker_sok_digest.clone().unwrap();
@@ -655,30 +655,39 @@ impl ExternalSnarkWorkerService for NodeTestingService {
                 make_dummy_proof(v2),
             )),
         };
-        let _ = self
-            .real
-            .event_sender()
-            .send(ExternalSnarkWorkerEvent::WorkResult(Arc::new(res)).into());
+        let _ = self.real.event_sender().send(
+            (
+                worker_id,
+                ExternalSnarkWorkerEvent::WorkResult(Arc::new(res)),
+            )
+                .into(),
+        );
         Ok(())
-        // self.real.submit(spec)
+        // self.real.submit(worker_id, spec)
     }
 
-    fn cancel(&mut self) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
+    fn cancel(
+        &mut self,
+        worker_id: SnarkWorkerId,
+    ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
         let _ = self
             .real
             .event_sender()
-            .send(ExternalSnarkWorkerEvent::WorkCancelled.into());
+            .send((worker_id, ExternalSnarkWorkerEvent::WorkCancelled).into());
         Ok(())
-        // self.real.cancel()
+        // self.real.cancel(worker_id)
     }
 
-    fn kill(&mut self) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
+    fn kill(
+        &mut self,
+        worker_id: SnarkWorkerId,
+    ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
         let _ = self
             .real
             .event_sender()
-            .send(ExternalSnarkWorkerEvent::Killed.into());
+            .send((worker_id, ExternalSnarkWorkerEvent::Killed).into());
         Ok(())
-        // self.real.kill()
+        // self.real.kill(worker_id)
     }
 }
 
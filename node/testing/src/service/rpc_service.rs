@@ -21,6 +21,10 @@ impl RpcService for super::NodeTestingService {
         respond_block_producer_stats_get,
         node::rpc::RpcBlockProducerStatsGetResponse
     );
+    to_real!(
+        respond_block_producer_schedule_get,
+        node::rpc::RpcBlockProducerScheduleGetResponse
+    );
 
     to_real!(
         respond_action_stats_get,
@@ -31,6 +35,13 @@ impl RpcService for super::NodeTestingService {
         RpcMessageProgressResponse
     );
     to_real!(respond_peers_get, node::rpc::RpcPeersGetResponse,);
+    to_real!(respond_p2p_bans_get, node::rpc::RpcP2pBansGetResponse,);
+    to_real!(respond_p2p_ban, node::rpc::RpcP2pBanResponse,);
+    to_real!(respond_p2p_unban, node::rpc::RpcP2pUnbanResponse,);
+    to_real!(
+        respond_p2p_bandwidth_get,
+        node::rpc::RpcP2pBandwidthGetResponse,
+    );
     to_real!(
         respond_p2p_connection_outgoing,
         node::rpc::RpcP2pConnectionOutgoingResponse,
@@ -138,4 +149,17 @@ impl RpcService for super::NodeTestingService {
         respond_ledger_account_delegators_get,
         node::rpc::RpcLedgerAccountDelegatorsGetResponse,
     );
+    to_real!(
+        respond_archive_status_get,
+        node::rpc::RpcArchiveStatusGetResponse,
+    );
+    to_real!(
+        respond_archive_backfill,
+        node::rpc::RpcArchiveBackfillResponse,
+    );
+    to_real!(
+        respond_transition_frontier_event,
+        node::rpc::RpcTransitionFrontierEvent,
+    );
+    to_real!(respond_vrf_evaluate, node::rpc::RpcVrfEvaluateResponse,);
 }
@@ -167,6 +167,7 @@ impl Simulator {
                     )),
                     strategy: SnarkerStrategy::Sequential,
                     auto_commit: true,
+                    workers: 1,
                 }),
                 ..node_config.clone()
             };
@@ -206,11 +207,7 @@ impl Simulator {
             );
             let config = RustNodeTestingConfig {
                 block_producer: Some(RustNodeBlockProducerTestingConfig {
-                    config: BlockProducerConfig {
-                        pub_key: sec_key.public_key().into(),
-                        custom_coinbase_receiver: None,
-                        proposed_protocol_version: None,
-                    },
+                    config: BlockProducerConfig::new(sec_key.public_key().into()),
                     sec_key,
                 }),
                 ..node_config.clone()
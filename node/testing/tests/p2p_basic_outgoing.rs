@@ -1,7 +1,7 @@
 use openmina_node_testing::scenarios::p2p::basic_outgoing_connections::{
     ConnectToInitialPeers, ConnectToInitialPeersBecomeReady, ConnectToUnavailableInitialPeers,
     DontConnectToInitialPeerWithSameId, DontConnectToNodeWithSameId, DontConnectToSelfInitialPeer,
-    MakeMultipleOutgoingConnections, MakeOutgoingConnection,
+    MakeMultipleOutgoingConnections, MakeOutgoingConnection, MakeOutgoingQuicConnection,
 };
 
 mod common;
@@ -12,6 +12,12 @@ scenario_test!(
     MakeOutgoingConnection,
     true
 );
+scenario_test!(
+    make_quic_connection,
+    MakeOutgoingQuicConnection,
+    MakeOutgoingQuicConnection,
+    true
+);
 scenario_test!(
     make_multiple_connections,
     MakeMultipleOutgoingConnections,
@@ -1,5 +1,10 @@
-use openmina_node_testing::scenarios::p2p::pubsub::P2pReceiveMessage;
+use openmina_node_testing::scenarios::p2p::pubsub::{P2pPubsubPruneSpammer, P2pReceiveMessage};
 
 mod common;
 
 scenario_test!(pubsub_receive_block, P2pReceiveMessage, P2pReceiveMessage);
+scenario_test!(
+    pubsub_prune_spammer,
+    P2pPubsubPruneSpammer,
+    P2pPubsubPruneSpammer
+);
@@ -1,7 +1,10 @@
 #![cfg(feature = "p2p-webrtc")]
 
-use openmina_node_testing::scenarios::p2p::signaling::P2pSignaling;
+use openmina_node_testing::scenarios::p2p::signaling::{P2pSignaling, P2pSignalingRelay};
 
 mod common;
 
 scenario_test!(p2p_signaling, P2pSignaling, P2pSignaling, true);
+
+// the relay must be connected to one of the peers over libp2p
+scenario_test!(p2p_signaling_relay, P2pSignalingRelay, P2pSignalingRelay);
@@ -122,11 +122,7 @@ impl NodeBuilder {
         key: AccountSecretKey,
         provers: Option<BlockProver>,
     ) -> &mut Self {
-        let config = BlockProducerConfig {
-            pub_key: key.public_key().into(),
-            custom_coinbase_receiver: None,
-            proposed_protocol_version: None,
-        };
+        let config = BlockProducerConfig::new(key.public_key().into());
         self.block_producer = Some(config);
         self.service.block_producer_init(key, provers);
         self
@@ -169,6 +165,7 @@ impl NodeBuilder {
             )),
             strategy,
             auto_commit: true,
+            workers: 1,
         };
         self.snarker = Some(config);
         self
@@ -232,10 +229,12 @@ impl NodeBuilder {
             },
             p2p: P2pConfig {
                 libp2p_port: None,
+                libp2p_quic: false,
                 listen_port: None,
                 identity_pub_key: p2p_sec_key.public_key(),
                 initial_peers,
                 external_addrs: vec![],
+                nat_port_mapping: false,
                 enabled_channels: ChannelId::iter_all().collect(),
                 peer_discovery: !self.p2p_no_discovery,
                 meshsub: P2pMeshsubConfig {
@@ -244,6 +243,9 @@ impl NodeBuilder {
                 },
                 timeouts: P2pTimeouts::default(),
                 limits: P2pLimits::default().with_max_peers(Some(100)),
+                initial_trust: Default::default(),
+                initial_address_book: Default::default(),
+                access: Default::default(),
             },
             ledger: LedgerConfig {},
             snark: SnarkConfig {
@@ -8,8 +8,13 @@ fn main() {
         &[
             "src/network/pubsub/message.proto",
             "src/network/identify/p2p_network_identify_message.proto",
+            "src/network/autonat/p2p_network_autonat_message.proto",
+        ],
+        &[
+            "src/network/pubsub",
+            "src/network/identify",
+            "src/network/autonat",
         ],
-        &["src/network/pubsub", "src/network/identify"],
     )
     .expect("Proto build failed");
 }
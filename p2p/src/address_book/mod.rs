@@ -0,0 +1,7 @@
+mod p2p_address_book_state;
+pub use p2p_address_book_state::*;
+
+mod p2p_address_book_actions;
+pub use p2p_address_book_actions::*;
+
+mod p2p_address_book_reducer;
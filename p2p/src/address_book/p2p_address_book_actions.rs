@@ -0,0 +1,38 @@
+use multiaddr::Multiaddr;
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{token::StreamKind, P2pState, PeerId};
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(level = debug, fields(display(peer_id), success))]
+pub enum P2pAddressBookAction {
+    /// Peer reported its listen addresses and supported protocols.
+    PeerIdentified {
+        peer_id: PeerId,
+        listen_addrs: Vec<Multiaddr>,
+        protocols: Vec<StreamKind>,
+    },
+    /// Outgoing connection to the peer succeeded or failed.
+    ConnectionFinished { peer_id: PeerId, success: bool },
+    /// Evict stale peers.
+    #[action_event(level = trace)]
+    Prune,
+    /// Persist the address book if it changed since it was last persisted.
+    #[action_event(level = trace)]
+    Persist,
+}
+
+impl redux::EnablingCondition<P2pState> for P2pAddressBookAction {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
+        match self {
+            P2pAddressBookAction::PeerIdentified { peer_id, .. }
+            | P2pAddressBookAction::ConnectionFinished { peer_id, .. } => state
+                .peers
+                .get(peer_id)
+                .is_some_and(|peer| peer.is_libp2p()),
+            P2pAddressBookAction::Prune => state.address_book.has_stale(time),
+            P2pAddressBookAction::Persist => state.address_book.should_persist(time),
+        }
+    }
+}
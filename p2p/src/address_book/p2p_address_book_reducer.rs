@@ -0,0 +1,69 @@
+use openmina_core::Substate;
+use redux::ActionWithMeta;
+
+use crate::{
+    address_book_effectful::P2pAddressBookEffectfulAction,
+    connection::outgoing::P2pConnectionOutgoingInitOpts, P2pState,
+};
+
+use super::{P2pAddressBook, P2pAddressBookAction};
+
+impl P2pAddressBook {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: ActionWithMeta<P2pAddressBookAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+
+        match action {
+            P2pAddressBookAction::PeerIdentified {
+                peer_id,
+                listen_addrs,
+                protocols,
+            } => {
+                let dial_opts = p2p_state
+                    .peers
+                    .get(&peer_id)
+                    .and_then(|peer| peer.dial_opts.as_ref());
+                let dial_addr = match dial_opts {
+                    Some(P2pConnectionOutgoingInitOpts::LibP2P(opts)) => opts.to_maddr(),
+                    _ => None,
+                };
+                p2p_state.address_book.peer_identified(
+                    peer_id,
+                    dial_addr,
+                    listen_addrs,
+                    protocols,
+                    meta.time(),
+                );
+                Ok(())
+            }
+            P2pAddressBookAction::ConnectionFinished { peer_id, success } => {
+                p2p_state
+                    .address_book
+                    .connection_finished(peer_id, success, meta.time());
+                Ok(())
+            }
+            P2pAddressBookAction::Prune => {
+                p2p_state.address_book.prune(meta.time());
+                Ok(())
+            }
+            P2pAddressBookAction::Persist => {
+                p2p_state.address_book.persisted_at = Some(meta.time());
+                p2p_state.address_book.is_dirty = false;
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+                dispatcher.push(P2pAddressBookEffectfulAction::Persist {
+                    address_book: p2p_state.address_book.clone(),
+                });
+                Ok(())
+            }
+        }
+    }
+}
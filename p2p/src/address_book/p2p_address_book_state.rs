@@ -0,0 +1,280 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use multiaddr::{Multiaddr, Protocol};
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{connection::outgoing::P2pConnectionOutgoingInitOpts, token::StreamKind, PeerId};
+
+/// Peers which weren't seen for this long are evicted.
+const STALE_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Peers we failed to connect to this many times without a single success
+/// are evicted.
+const MAX_FAILED_ATTEMPTS: u32 = 10;
+/// Least useful peers are evicted once the address book grows over this size.
+const MAX_ENTRIES: usize = 1000;
+/// Share of successful connection attempts for the peer to be known-good.
+const KNOWN_GOOD_SUCCESS_RATE: f64 = 0.5;
+/// Interval at which the address book is persisted, if it changed.
+pub const PERSIST_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Number of peers from the address book added to the known peers on start.
+pub const MAX_SEEDED_PEERS: usize = 32;
+/// How many times more likely a known-good peer is picked for a random
+/// outgoing connection than a peer we know nothing good about.
+pub const KNOWN_GOOD_PICK_WEIGHT: usize = 4;
+
+/// Peers the node saw in the past, persisted across restarts so that the
+/// node can bootstrap even if the seed nodes are unreachable.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pAddressBook {
+    pub peers: BTreeMap<PeerId, P2pAddressBookEntry>,
+    /// Time the address book was last persisted.
+    #[serde(skip)]
+    pub persisted_at: Option<Timestamp>,
+    /// Set when the address book changed since it was last persisted.
+    #[serde(skip)]
+    pub is_dirty: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pAddressBookEntry {
+    /// Addresses of the peer, the one we connected to successfully first.
+    pub addrs: Vec<Multiaddr>,
+    pub last_seen: Timestamp,
+    pub connection_attempts: u32,
+    pub connection_successes: u32,
+    /// Protocols the peer reported via identify.
+    pub protocols: Vec<StreamKind>,
+}
+
+impl P2pAddressBookEntry {
+    fn new(time: Timestamp) -> Self {
+        Self {
+            addrs: Vec::new(),
+            last_seen: time,
+            connection_attempts: 0,
+            connection_successes: 0,
+            protocols: Vec::new(),
+        }
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.connection_attempts == 0 {
+            0.0
+        } else {
+            self.connection_successes as f64 / self.connection_attempts as f64
+        }
+    }
+
+    pub fn is_known_good(&self) -> bool {
+        self.connection_successes > 0 && self.success_rate() >= KNOWN_GOOD_SUCCESS_RATE
+    }
+
+    pub fn supports(&self, protocol: &StreamKind) -> bool {
+        self.protocols.contains(protocol)
+    }
+
+    /// Options to dial the peer at its first usable address.
+    pub fn dial_opts(&self, peer_id: &PeerId) -> Option<P2pConnectionOutgoingInitOpts> {
+        let p2p = Protocol::P2p(libp2p_identity::PeerId::try_from(*peer_id).ok()?);
+        self.addrs.iter().find_map(|addr| {
+            // addresses reported by identify don't contain the peer id
+            let addr = match addr.iter().last() {
+                Some(Protocol::P2p(_)) => addr.clone(),
+                _ => addr.clone().with(p2p.clone()),
+            };
+            P2pConnectionOutgoingInitOpts::try_from(&addr).ok()
+        })
+    }
+
+    fn is_stale(&self, time: Timestamp) -> bool {
+        time.checked_sub(self.last_seen)
+            .is_some_and(|elapsed| elapsed >= STALE_AFTER)
+            || (self.connection_successes == 0 && self.connection_attempts >= MAX_FAILED_ATTEMPTS)
+    }
+
+    /// Entries are ordered by this key, most useful first.
+    fn rank(&self) -> impl Ord {
+        (
+            std::cmp::Reverse(self.is_known_good()),
+            std::cmp::Reverse((self.success_rate() * 1000.0) as u32),
+            std::cmp::Reverse(u64::from(self.last_seen)),
+        )
+    }
+}
+
+impl P2pAddressBook {
+    pub fn get(&self, peer_id: &PeerId) -> Option<&P2pAddressBookEntry> {
+        self.peers.get(peer_id)
+    }
+
+    pub fn is_known_good(&self, peer_id: &PeerId) -> bool {
+        self.peers
+            .get(peer_id)
+            .is_some_and(|entry| entry.is_known_good())
+    }
+
+    /// Peers ordered from the most useful one.
+    pub fn preferred_peers(&self) -> impl Iterator<Item = (&PeerId, &P2pAddressBookEntry)> {
+        let mut peers = self.peers.iter().collect::<Vec<_>>();
+        peers.sort_by_key(|(_, entry)| entry.rank());
+        peers.into_iter()
+    }
+
+    fn entry_mut(&mut self, peer_id: PeerId, time: Timestamp) -> &mut P2pAddressBookEntry {
+        self.is_dirty = true;
+        self.peers
+            .entry(peer_id)
+            .or_insert_with(|| P2pAddressBookEntry::new(time))
+    }
+
+    /// Records addresses and protocols reported by the peer. Address we
+    /// are connected to is put first.
+    pub fn peer_identified(
+        &mut self,
+        peer_id: PeerId,
+        dial_addr: Option<Multiaddr>,
+        listen_addrs: Vec<Multiaddr>,
+        protocols: Vec<StreamKind>,
+        time: Timestamp,
+    ) {
+        let entry = self.entry_mut(peer_id, time);
+        entry.last_seen = time;
+        entry.protocols = protocols;
+        entry.addrs =
+            dial_addr
+                .into_iter()
+                .chain(listen_addrs)
+                .fold(Vec::new(), |mut addrs, addr| {
+                    if !addrs.contains(&addr) {
+                        addrs.push(addr);
+                    }
+                    addrs
+                });
+        entry.addrs.truncate(crate::P2pNetworkKadEntry::MAX_ADDRS);
+    }
+
+    pub fn connection_finished(&mut self, peer_id: PeerId, success: bool, time: Timestamp) {
+        let entry = self.entry_mut(peer_id, time);
+        entry.connection_attempts = entry.connection_attempts.saturating_add(1);
+        if success {
+            entry.connection_successes = entry.connection_successes.saturating_add(1);
+            entry.last_seen = time;
+        }
+    }
+
+    pub fn has_stale(&self, time: Timestamp) -> bool {
+        self.peers.len() > MAX_ENTRIES || self.peers.values().any(|e| e.is_stale(time))
+    }
+
+    /// Evicts stale peers, then the least useful ones above the size limit.
+    pub fn prune(&mut self, time: Timestamp) {
+        let len = self.peers.len();
+        self.peers.retain(|_, entry| !entry.is_stale(time));
+        if self.peers.len() > MAX_ENTRIES {
+            let evicted = self
+                .preferred_peers()
+                .skip(MAX_ENTRIES)
+                .map(|(peer_id, _)| *peer_id)
+                .collect::<Vec<_>>();
+            for peer_id in evicted {
+                self.peers.remove(&peer_id);
+            }
+        }
+        self.is_dirty |= self.peers.len() != len;
+    }
+
+    pub fn should_persist(&self, time: Timestamp) -> bool {
+        self.is_dirty
+            && self
+                .persisted_at
+                .is_none_or(|then| crate::is_time_passed(time, then, Some(PERSIST_INTERVAL)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> Timestamp {
+        Timestamp::ZERO + Duration::from_secs(secs)
+    }
+
+    fn peer(n: u8) -> PeerId {
+        PeerId::from_bytes([n; 32])
+    }
+
+    fn addr(port: u16) -> Multiaddr {
+        format!("/ip4/1.2.3.4/tcp/{port}").parse().unwrap()
+    }
+
+    #[test]
+    fn known_good_peers_are_preferred() {
+        let mut book = P2pAddressBook::default();
+        book.peer_identified(peer(1), None, vec![addr(1)], vec![], at(10));
+        book.peer_identified(peer(2), None, vec![addr(2)], vec![], at(0));
+        book.connection_finished(peer(1), false, at(10));
+        book.connection_finished(peer(2), true, at(10));
+        book.connection_finished(peer(2), false, at(20));
+
+        assert!(!book.is_known_good(&peer(1)));
+        assert!(book.is_known_good(&peer(2)));
+        let order = book
+            .preferred_peers()
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![peer(2), peer(1)]);
+    }
+
+    #[test]
+    fn dial_addr_goes_first() {
+        // dial options need a peer id convertible to the libp2p one
+        let peer_id = crate::identity::SecretKey::rand().public_key().peer_id();
+        let mut book = P2pAddressBook::default();
+        book.peer_identified(
+            peer_id,
+            Some(addr(2)),
+            vec![addr(1), addr(2)],
+            vec![],
+            at(0),
+        );
+        let entry = book.get(&peer_id).unwrap();
+        assert_eq!(entry.addrs, vec![addr(2), addr(1)]);
+        let opts = entry.dial_opts(&peer_id).unwrap();
+        assert_eq!(opts.peer_id(), &peer_id);
+        assert_eq!(
+            opts.to_string(),
+            format!("{}/p2p/{}", addr(2), peer_id.to_libp2p_string())
+        );
+    }
+
+    #[test]
+    fn stale_and_failing_peers_are_evicted() {
+        let mut book = P2pAddressBook::default();
+        book.peer_identified(peer(1), None, vec![addr(1)], vec![], at(0));
+        book.peer_identified(peer(2), None, vec![addr(2)], vec![], at(0));
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            book.connection_finished(peer(2), false, at(1));
+        }
+        assert!(book.has_stale(at(1)));
+        book.prune(at(1));
+        assert!(book.get(&peer(1)).is_some());
+        assert!(book.get(&peer(2)).is_none());
+
+        let time = at(0) + STALE_AFTER;
+        assert!(book.has_stale(time));
+        book.prune(time);
+        assert!(book.peers.is_empty());
+    }
+
+    #[test]
+    fn persisted_periodically_when_changed() {
+        let mut book = P2pAddressBook::default();
+        assert!(!book.should_persist(at(0)));
+        book.connection_finished(peer(1), true, at(0));
+        assert!(book.should_persist(at(0)));
+        book.persisted_at = Some(at(0));
+        assert!(!book.should_persist(at(1)));
+        assert!(book.should_persist(at(0) + PERSIST_INTERVAL));
+    }
+}
@@ -0,0 +1,17 @@
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{address_book::P2pAddressBook, P2pState};
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(level = debug)]
+pub enum P2pAddressBookEffectfulAction {
+    /// Persist the address book, so that it can be used after restart.
+    Persist { address_book: P2pAddressBook },
+}
+
+impl redux::EnablingCondition<P2pState> for P2pAddressBookEffectfulAction {
+    fn is_enabled(&self, _state: &P2pState, _time: redux::Timestamp) -> bool {
+        true
+    }
+}
@@ -0,0 +1,17 @@
+use redux::ActionMeta;
+
+use super::{P2pAddressBookEffectfulAction, P2pAddressBookService};
+
+impl P2pAddressBookEffectfulAction {
+    pub fn effects<Store, S>(self, _: &ActionMeta, store: &mut Store)
+    where
+        Store: crate::P2pStore<S>,
+        Store::Service: P2pAddressBookService,
+    {
+        match self {
+            P2pAddressBookEffectfulAction::Persist { address_book } => {
+                store.service().address_book_persist(&address_book);
+            }
+        }
+    }
+}
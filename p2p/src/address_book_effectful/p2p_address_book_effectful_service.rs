@@ -0,0 +1,5 @@
+use crate::address_book::P2pAddressBook;
+
+pub trait P2pAddressBookService: redux::Service {
+    fn address_book_persist(&mut self, address_book: &P2pAddressBook);
+}
@@ -0,0 +1,7 @@
+mod p2p_bandwidth_state;
+pub use p2p_bandwidth_state::*;
+
+mod p2p_bandwidth_actions;
+pub use p2p_bandwidth_actions::*;
+
+mod p2p_bandwidth_reducer;
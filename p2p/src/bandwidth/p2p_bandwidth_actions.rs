@@ -0,0 +1,38 @@
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{P2pState, PeerId};
+
+use super::P2pBandwidthKind;
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(level = trace, fields(display(peer_id), display(kind), bytes))]
+pub enum P2pBandwidthAction {
+    Received {
+        peer_id: PeerId,
+        kind: P2pBandwidthKind,
+        bytes: u64,
+    },
+    Sent {
+        peer_id: PeerId,
+        kind: P2pBandwidthKind,
+        bytes: u64,
+    },
+    /// Forget counters of peers which are no longer in the state.
+    Prune,
+}
+
+impl redux::EnablingCondition<P2pState> for P2pBandwidthAction {
+    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+        match self {
+            P2pBandwidthAction::Received { bytes, .. } | P2pBandwidthAction::Sent { bytes, .. } => {
+                *bytes > 0
+            }
+            P2pBandwidthAction::Prune => state
+                .bandwidth
+                .peers
+                .keys()
+                .any(|peer_id| !state.peers.contains_key(peer_id)),
+        }
+    }
+}
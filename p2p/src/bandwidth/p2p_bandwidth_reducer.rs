@@ -0,0 +1,48 @@
+use openmina_core::Substate;
+use redux::ActionWithMeta;
+
+use crate::P2pState;
+
+use super::{P2pBandwidthAction, P2pBandwidthState};
+
+impl P2pBandwidthState {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: ActionWithMeta<P2pBandwidthAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+        let time = meta.time();
+
+        match action {
+            P2pBandwidthAction::Received {
+                peer_id,
+                kind,
+                bytes,
+            } => {
+                p2p_state.bandwidth.received(peer_id, kind, bytes, time);
+                Ok(())
+            }
+            P2pBandwidthAction::Sent {
+                peer_id,
+                kind,
+                bytes,
+            } => {
+                p2p_state.bandwidth.sent(peer_id, kind, bytes, time);
+                Ok(())
+            }
+            P2pBandwidthAction::Prune => {
+                let peers = &p2p_state.peers;
+                p2p_state
+                    .bandwidth
+                    .peers
+                    .retain(|peer_id, _| peers.contains_key(peer_id));
+                Ok(())
+            }
+        }
+    }
+}
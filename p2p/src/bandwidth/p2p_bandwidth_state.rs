@@ -0,0 +1,457 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use mina_p2p_messages::rpc_kernel::RpcMethod;
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    channels::{
+        rpc::{P2pRpcKind, P2pRpcRemoteQuotas},
+        ChannelMsg,
+    },
+    token::StreamKind,
+    Limit, P2pRateLimit, P2pRpcQuotas, PeerId,
+};
+
+/// Window over which recent transfer rates are averaged.
+pub const BANDWIDTH_WINDOW: Duration = Duration::from_secs(60);
+
+/// Kind of traffic the bytes are accounted to.
+#[derive(
+    Serialize, Deserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, derive_more::Display,
+)]
+pub enum P2pBandwidthKind {
+    /// Gossip, or propagation channels for webrtc peers.
+    Pubsub,
+    Rpc,
+    /// RPCs serving ledgers: sync ledger queries, staged ledgers and epoch
+    /// ledgers.
+    LedgerSync,
+    StreamingRpc,
+    Discovery,
+    Identify,
+    Other,
+}
+
+/// Bytes transferred with peers, in total and per peer, split by the kind
+/// of traffic.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pBandwidthState {
+    pub kinds: BTreeMap<P2pBandwidthKind, P2pBandwidthStats>,
+    pub peers: BTreeMap<PeerId, P2pPeerBandwidth>,
+    /// Rate limit for WebRTC offers relayed between peers.
+    #[serde(default)]
+    pub signaling_relay: P2pTokenBucket,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pPeerBandwidth {
+    pub kinds: BTreeMap<P2pBandwidthKind, P2pBandwidthStats>,
+    /// What is left of the quotas for serving RPC requests of the peer, both
+    /// for the RPC and the streaming RPC channels.
+    pub rpc_quotas: P2pRpcRemoteQuotas,
+    /// Rate limit for bytes of RPC responses sent to the peer.
+    pub responses: P2pTokenBucket,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pBandwidthStats {
+    pub received: P2pBandwidthCounter,
+    pub sent: P2pBandwidthCounter,
+}
+
+/// Byte counter which also keeps per-second counts for the last
+/// [`BANDWIDTH_WINDOW`], so that the recent rate can be computed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pBandwidthCounter {
+    pub total: u64,
+    /// Pairs of second since the epoch and bytes counted during it, oldest
+    /// first.
+    window: VecDeque<(u64, u64)>,
+}
+
+/// Token bucket, filled at the rate of its [`P2pRateLimit`] up to its burst.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pTokenBucket {
+    tokens: f64,
+    /// Time the tokens were last updated at, unset while the bucket was
+    /// never used and so is full.
+    updated_at: Option<Timestamp>,
+}
+
+/// Bandwidth counters as exposed through RPCs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct P2pBandwidthSummary {
+    pub received: u64,
+    pub sent: u64,
+    /// Bytes per second received, averaged over [`BANDWIDTH_WINDOW`].
+    pub received_rate: u64,
+    /// Bytes per second sent, averaged over [`BANDWIDTH_WINDOW`].
+    pub sent_rate: u64,
+}
+
+fn secs(time: Timestamp) -> u64 {
+    time.checked_sub(Timestamp::ZERO)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl P2pBandwidthKind {
+    pub fn from_rpc_kind(kind: P2pRpcKind) -> Self {
+        match kind {
+            P2pRpcKind::LedgerQuery
+            | P2pRpcKind::StagedLedgerAuxAndPendingCoinbasesAtBlock
+            | P2pRpcKind::EpochLedger => Self::LedgerSync,
+            P2pRpcKind::BestTipWithProof
+            | P2pRpcKind::Block
+            | P2pRpcKind::Snark
+            | P2pRpcKind::Transaction
+            | P2pRpcKind::InitialPeers
+            | P2pRpcKind::Ancestry
+            | P2pRpcKind::TransitionChainProof => Self::Rpc,
+        }
+    }
+
+    /// Kind of a libp2p RPC by its tag.
+    pub fn from_rpc_tag(tag: &[u8]) -> Self {
+        use mina_p2p_messages::rpc::*;
+        match tag {
+            AnswerSyncLedgerQueryV2::NAME
+            | GetStagedLedgerAuxAndPendingCoinbasesAtHashV2::NAME
+            | GetEpochLedgerV2::NAME => Self::LedgerSync,
+            _ => Self::Rpc,
+        }
+    }
+
+    /// Kind of a libp2p stream. `None` for RPC streams, which are accounted
+    /// per message so that ledger sync can be told apart, and for WebRTC
+    /// signaling streams, which carry channel messages accounted by the
+    /// channels.
+    pub fn from_stream_kind(kind: &StreamKind) -> Option<Self> {
+        match kind {
+            StreamKind::Broadcast(_) => Some(Self::Pubsub),
+            StreamKind::Discovery(_) => Some(Self::Discovery),
+            StreamKind::Identify(_) => Some(Self::Identify),
+            StreamKind::Rpc(_) | StreamKind::WebrtcSignal(_) => None,
+            StreamKind::Status(_)
+            | StreamKind::Bitswap(_)
+            | StreamKind::Ping(_)
+            | StreamKind::Autonat(_) => Some(Self::Other),
+        }
+    }
+}
+
+impl From<&ChannelMsg> for P2pBandwidthKind {
+    fn from(msg: &ChannelMsg) -> Self {
+        use crate::channels::rpc::RpcChannelMsg;
+        match msg {
+            ChannelMsg::SignalingDiscovery(_) | ChannelMsg::SignalingExchange(_) => Self::Discovery,
+            ChannelMsg::BestTipPropagation(_)
+            | ChannelMsg::TransactionPropagation(_)
+            | ChannelMsg::SnarkPropagation(_)
+            | ChannelMsg::SnarkJobCommitmentPropagation(_) => Self::Pubsub,
+            ChannelMsg::Rpc(RpcChannelMsg::Request(_, request)) => {
+                Self::from_rpc_kind(request.kind())
+            }
+            ChannelMsg::Rpc(RpcChannelMsg::Response(_, Some(response))) => {
+                Self::from_rpc_kind(response.kind())
+            }
+            ChannelMsg::Rpc(RpcChannelMsg::Response(_, None)) => Self::Rpc,
+            ChannelMsg::StreamingRpc(_) => Self::StreamingRpc,
+        }
+    }
+}
+
+impl P2pBandwidthCounter {
+    pub fn add(&mut self, bytes: u64, time: Timestamp) {
+        let now = secs(time);
+        self.total = self.total.saturating_add(bytes);
+        match self.window.back_mut() {
+            Some((sec, count)) if *sec >= now => *count = count.saturating_add(bytes),
+            _ => self.window.push_back((now, bytes)),
+        }
+        self.prune(now);
+    }
+
+    /// Bytes counted during the last [`BANDWIDTH_WINDOW`].
+    pub fn recent(&self, time: Timestamp) -> u64 {
+        let start = secs(time).saturating_sub(BANDWIDTH_WINDOW.as_secs());
+        self.window
+            .iter()
+            .filter(|(sec, _)| *sec > start)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Average bytes per second during the last [`BANDWIDTH_WINDOW`].
+    pub fn rate(&self, time: Timestamp) -> u64 {
+        self.recent(time) / BANDWIDTH_WINDOW.as_secs()
+    }
+
+    fn prune(&mut self, now: u64) {
+        let start = now.saturating_sub(BANDWIDTH_WINDOW.as_secs());
+        while self.window.front().is_some_and(|(sec, _)| *sec <= start) {
+            self.window.pop_front();
+        }
+    }
+}
+
+impl P2pBandwidthStats {
+    pub fn summary(&self, time: Timestamp) -> P2pBandwidthSummary {
+        P2pBandwidthSummary {
+            received: self.received.total,
+            sent: self.sent.total,
+            received_rate: self.received.rate(time),
+            sent_rate: self.sent.rate(time),
+        }
+    }
+}
+
+impl std::ops::Add for P2pBandwidthSummary {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            received: self.received.saturating_add(rhs.received),
+            sent: self.sent.saturating_add(rhs.sent),
+            received_rate: self.received_rate.saturating_add(rhs.received_rate),
+            sent_rate: self.sent_rate.saturating_add(rhs.sent_rate),
+        }
+    }
+}
+
+impl std::iter::Sum for P2pBandwidthSummary {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, v| acc + v)
+    }
+}
+
+impl P2pTokenBucket {
+    /// Tokens available at `time`.
+    pub fn tokens(&self, limit: &P2pRateLimit, time: Timestamp) -> f64 {
+        let burst = limit.burst as f64;
+        match self.updated_at {
+            None => burst,
+            Some(updated_at) => {
+                let elapsed = time.checked_sub(updated_at).unwrap_or_default();
+                (self.tokens + elapsed.as_secs_f64() * limit.rate as f64).min(burst)
+            }
+        }
+    }
+
+    /// Takes `amount` tokens from the bucket. Returns `false`, leaving the
+    /// bucket intact, if there are not enough of them.
+    pub fn try_take(&mut self, limit: &P2pRateLimit, amount: u64, time: Timestamp) -> bool {
+        let tokens = self.tokens(limit, time);
+        let taken = tokens >= amount as f64;
+        self.tokens = if taken {
+            tokens - amount as f64
+        } else {
+            tokens
+        };
+        self.updated_at = Some(time);
+        taken
+    }
+}
+
+impl P2pPeerBandwidth {
+    pub fn summary(&self, time: Timestamp) -> P2pBandwidthSummary {
+        self.kinds.values().map(|stats| stats.summary(time)).sum()
+    }
+}
+
+impl P2pBandwidthState {
+    pub fn received(
+        &mut self,
+        peer_id: PeerId,
+        kind: P2pBandwidthKind,
+        bytes: u64,
+        time: Timestamp,
+    ) {
+        for kinds in [
+            &mut self.kinds,
+            &mut self.peers.entry(peer_id).or_default().kinds,
+        ] {
+            kinds.entry(kind).or_default().received.add(bytes, time);
+        }
+    }
+
+    pub fn sent(&mut self, peer_id: PeerId, kind: P2pBandwidthKind, bytes: u64, time: Timestamp) {
+        for kinds in [
+            &mut self.kinds,
+            &mut self.peers.entry(peer_id).or_default().kinds,
+        ] {
+            kinds.entry(kind).or_default().sent.add(bytes, time);
+        }
+    }
+
+    pub fn peer_mut(&mut self, peer_id: PeerId) -> &mut P2pPeerBandwidth {
+        self.peers.entry(peer_id).or_default()
+    }
+
+    /// Takes the `cost` of serving a request of the peer from its quotas.
+    /// Returns `false` if the request must not be served.
+    pub fn try_take_rpc_quotas(
+        &mut self,
+        peer_id: PeerId,
+        quotas: &P2pRpcQuotas,
+        kind: P2pRpcKind,
+        cost: u64,
+        time: Timestamp,
+    ) -> bool {
+        self.peer_mut(peer_id)
+            .rpc_quotas
+            .try_take(quotas, kind, cost, time)
+    }
+
+    /// Takes `bytes` of a response we are about to send to the peer from its
+    /// rate limit. Returns `false` if the response must not be sent.
+    pub fn try_take_response(
+        &mut self,
+        peer_id: PeerId,
+        limit: Limit<P2pRateLimit>,
+        bytes: u64,
+        time: Timestamp,
+    ) -> bool {
+        match limit {
+            Limit::Some(limit) => self
+                .peer_mut(peer_id)
+                .responses
+                .try_take(&limit, bytes, time),
+            Limit::Unlimited => true,
+        }
+    }
+
+    /// Whether an offer could be relayed between peers now.
+    pub fn can_relay_signaling(&self, limit: Limit<P2pRateLimit>, time: Timestamp) -> bool {
+        match limit {
+            Limit::Some(limit) => self.signaling_relay.tokens(&limit, time) >= 1.0,
+            Limit::Unlimited => true,
+        }
+    }
+
+    /// Takes an offer we are about to relay between peers from the rate
+    /// limit. Returns `false` if the offer must not be relayed.
+    pub fn try_take_signaling_relay(
+        &mut self,
+        limit: Limit<P2pRateLimit>,
+        time: Timestamp,
+    ) -> bool {
+        match limit {
+            Limit::Some(limit) => self.signaling_relay.try_take(&limit, 1, time),
+            Limit::Unlimited => true,
+        }
+    }
+
+    pub fn summary(&self, time: Timestamp) -> P2pBandwidthSummary {
+        self.kinds.values().map(|stats| stats.summary(time)).sum()
+    }
+
+    pub fn peer_summary(&self, peer_id: &PeerId, time: Timestamp) -> P2pBandwidthSummary {
+        self.peers
+            .get(peer_id)
+            .map(|peer| peer.summary(time))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(d: Duration) -> Timestamp {
+        Timestamp::ZERO + d
+    }
+
+    #[test]
+    fn counter_window_rolls() {
+        let mut counter = P2pBandwidthCounter::default();
+        counter.add(600, at(Duration::from_secs(1)));
+        counter.add(600, at(Duration::from_millis(1500)));
+        counter.add(1200, at(Duration::from_secs(30)));
+        assert_eq!(counter.total, 2400);
+        assert_eq!(counter.recent(at(Duration::from_secs(30))), 2400);
+        assert_eq!(counter.rate(at(Duration::from_secs(30))), 40);
+
+        // the first second is out of the window
+        assert_eq!(counter.recent(at(Duration::from_secs(61))), 1200);
+        counter.add(0, at(Duration::from_secs(61)));
+        assert_eq!(counter.window.len(), 2);
+        assert_eq!(counter.recent(at(Duration::from_secs(120))), 0);
+        assert_eq!(counter.total, 2400);
+    }
+
+    #[test]
+    fn token_bucket_refills() {
+        let limit = P2pRateLimit {
+            rate: 10,
+            burst: 20,
+        };
+        let mut bucket = P2pTokenBucket::default();
+        assert!(bucket.try_take(&limit, 20, at(Duration::ZERO)));
+        assert!(!bucket.try_take(&limit, 1, at(Duration::ZERO)));
+        assert!(bucket.try_take(&limit, 5, at(Duration::from_millis(500))));
+        assert!(!bucket.try_take(&limit, 1, at(Duration::from_millis(500))));
+
+        // never refills above the burst
+        assert_eq!(bucket.tokens(&limit, at(Duration::from_secs(100))), 20.0);
+        assert!(!bucket.try_take(&limit, 21, at(Duration::from_secs(100))));
+        assert!(bucket.try_take(&limit, 20, at(Duration::from_secs(100))));
+    }
+
+    #[test]
+    fn accounted_per_peer_and_in_total() {
+        let (peer1, peer2) = (PeerId::from_bytes([1; 32]), PeerId::from_bytes([2; 32]));
+        let time = at(Duration::from_secs(10));
+        let mut bandwidth = P2pBandwidthState::default();
+        bandwidth.received(peer1, P2pBandwidthKind::Rpc, 100, time);
+        bandwidth.sent(peer1, P2pBandwidthKind::LedgerSync, 6000, time);
+        bandwidth.sent(peer2, P2pBandwidthKind::Pubsub, 60, time);
+
+        let summary = bandwidth.peer_summary(&peer1, time);
+        assert_eq!((summary.received, summary.sent), (100, 6000));
+        assert_eq!(summary.sent_rate, 100);
+        let summary = bandwidth.summary(time);
+        assert_eq!((summary.received, summary.sent), (100, 6060));
+        assert_eq!(bandwidth.kinds.len(), 3);
+    }
+
+    #[test]
+    fn responses_limited_before_sending() {
+        let peer = PeerId::from_bytes([1; 32]);
+        let limit = Limit::Some(P2pRateLimit {
+            rate: 100,
+            burst: 1000,
+        });
+        let time = at(Duration::from_secs(1));
+        let mut bandwidth = P2pBandwidthState::default();
+        assert!(bandwidth.try_take_response(peer, limit, 1000, time));
+        assert!(!bandwidth.try_take_response(peer, limit, 1, time));
+        // nothing is counted as sent for a rejected response
+        assert_eq!(bandwidth.peer_summary(&peer, time).sent, 0);
+        assert!(bandwidth.try_take_response(peer, Limit::Unlimited, u64::MAX, time));
+        let time = time + Duration::from_secs(1);
+        assert!(bandwidth.try_take_response(peer, limit, 100, time));
+    }
+
+    #[test]
+    fn signaling_relay_limited() {
+        let limit = Limit::Some(P2pRateLimit { rate: 1, burst: 2 });
+        let time = at(Duration::from_secs(1));
+        let mut bandwidth = P2pBandwidthState::default();
+        assert!(bandwidth.can_relay_signaling(limit, time));
+        assert!(bandwidth.try_take_signaling_relay(limit, time));
+        assert!(bandwidth.try_take_signaling_relay(limit, time));
+        assert!(!bandwidth.can_relay_signaling(limit, time));
+        assert!(!bandwidth.try_take_signaling_relay(limit, time));
+        assert!(bandwidth.try_take_signaling_relay(Limit::Unlimited, time));
+
+        let time = time + Duration::from_secs(1);
+        assert!(bandwidth.can_relay_signaling(limit, time));
+        assert!(bandwidth.try_take_signaling_relay(limit, time));
+        assert!(!bandwidth.try_take_signaling_relay(limit, time));
+    }
+}
@@ -1,7 +1,12 @@
+use binprot::BinProtSize;
 use openmina_core::bug_condition;
 use redux::ActionMeta;
 
-use crate::webrtc::{Offer, P2pConnectionResponse};
+use crate::{
+    bandwidth::{P2pBandwidthAction, P2pBandwidthKind},
+    webrtc::{Offer, P2pConnectionResponse},
+    PeerId,
+};
 
 use super::{
     signaling::{
@@ -31,7 +36,12 @@ impl P2pChannelsEffectfulAction {
                 msg_id,
                 msg,
             } => {
-                store.service().channel_send(peer_id, msg_id, msg);
+                store.dispatch(P2pBandwidthAction::Sent {
+                    peer_id,
+                    kind: P2pBandwidthKind::from(&msg),
+                    bytes: msg.binprot_size() as u64,
+                });
+                channel_send(store, peer_id, msg_id, msg);
             }
             P2pChannelsEffectfulAction::SignalingDiscoveryAnswerDecrypt {
                 peer_id,
@@ -71,9 +81,7 @@ impl P2pChannelsEffectfulAction {
                 }
                 Ok(offer) => {
                     let message = SignalingDiscoveryChannelMsg::DiscoveredAccept(offer);
-                    store
-                        .service()
-                        .channel_send(peer_id, super::MsgId::first(), message.into());
+                    channel_send(store, peer_id, MsgId::first(), message.into());
                 }
             },
             P2pChannelsEffectfulAction::SignalingExchangeOfferDecrypt {
@@ -109,7 +117,8 @@ impl P2pChannelsEffectfulAction {
             } => {
                 let Some(answer) = answer else {
                     let message = SignalingExchangeChannelMsg::Answer(None);
-                    store.service().channel_send(
+                    channel_send(
+                        store,
                         peer_id,
                         MsgId::first(),
                         ChannelMsg::SignalingExchange(message),
@@ -121,7 +130,8 @@ impl P2pChannelsEffectfulAction {
                     Err(_) => bug_condition!("Failed to encrypt webrtc answer. Shouldn't happen since we managed to decrypt sent offer."),
                     Ok(answer) => {
                         let message = SignalingExchangeChannelMsg::Answer(Some(answer));
-                        store.service().channel_send(
+                        channel_send(
+                            store,
                             peer_id,
                             MsgId::first(),
                             ChannelMsg::SignalingExchange(message),
@@ -132,3 +142,27 @@ impl P2pChannelsEffectfulAction {
         }
     }
 }
+
+/// Sends the message over the WebRTC data channel, or over the
+/// `/openmina/webrtc-signal/1.0.0` stream if it is a signaling message for
+/// a libp2p peer.
+fn channel_send<Store, S>(store: &mut Store, peer_id: PeerId, msg_id: MsgId, msg: ChannelMsg)
+where
+    Store: crate::P2pStore<S>,
+    Store::Service: P2pChannelsService,
+{
+    #[cfg(feature = "p2p-libp2p")]
+    let msg = match crate::P2pNetworkWebrtcSignalMessage::try_from(msg) {
+        Ok(message) => {
+            if store.dispatch(crate::P2pNetworkWebrtcSignalAction::OutgoingMessage {
+                peer_id,
+                message: message.clone(),
+            }) {
+                return;
+            }
+            message.into()
+        }
+        Err(msg) => msg,
+    };
+    store.service().channel_send(peer_id, msg_id, msg);
+}
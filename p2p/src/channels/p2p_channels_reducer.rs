@@ -25,9 +25,11 @@ use super::{
     ChannelMsg, P2pChannelsAction, P2pChannelsMessageReceivedAction, P2pChannelsState,
 };
 use crate::{
+    bandwidth::{P2pBandwidthAction, P2pBandwidthKind},
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
     P2pState,
 };
+use binprot::BinProtSize;
 use openmina_core::{block::BlockWithHash, error, Substate};
 use redux::{ActionWithMeta, Dispatcher};
 
@@ -45,6 +47,11 @@ impl P2pChannelsState {
         match action {
             P2pChannelsAction::MessageReceived(action) => {
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                dispatcher.push(P2pBandwidthAction::Received {
+                    peer_id: action.peer_id,
+                    kind: P2pBandwidthKind::from(&*action.message),
+                    bytes: action.message.binprot_size() as u64,
+                });
                 Self::dispatch_message(meta.with_action(action), dispatcher, state)
             }
             P2pChannelsAction::SignalingDiscovery(action) => {
@@ -13,10 +13,11 @@ use mina_p2p_messages::{
     list::List,
     rpc_kernel::QueryID,
     v2::{
-        LedgerHash, MerkleAddressBinableArgStableV1, MinaBasePendingCoinbaseStableV2,
-        MinaBaseStateBodyHashStableV1, MinaLedgerSyncLedgerAnswerStableV2,
-        MinaLedgerSyncLedgerQueryStableV1, MinaStateProtocolStateValueStableV2, StateHash,
-        TransactionSnarkScanStateStableV2,
+        ConsensusProofOfStakeDataConsensusStateValueStableV2, LedgerHash,
+        MerkleAddressBinableArgStableV1, MinaBasePendingCoinbaseStableV2,
+        MinaBaseSparseLedgerBaseStableV2, MinaBaseStateBodyHashStableV1,
+        MinaLedgerSyncLedgerAnswerStableV2, MinaLedgerSyncLedgerQueryStableV1,
+        MinaStateProtocolStateValueStableV2, StateHash, TransactionSnarkScanStateStableV2,
     },
 };
 use openmina_core::{
@@ -26,7 +27,10 @@ use openmina_core::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{connection::outgoing::P2pConnectionOutgoingInitOpts, P2pTimeouts};
+use crate::{
+    connection::outgoing::P2pConnectionOutgoingInitOpts, Limit, P2pRateLimit, P2pRpcQuotas,
+    P2pTimeouts,
+};
 
 pub type P2pRpcId = QueryID;
 
@@ -45,7 +49,7 @@ impl RpcChannelMsg {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Clone, Copy)]
 pub enum P2pRpcKind {
     BestTipWithProof,
     LedgerQuery,
@@ -54,6 +58,9 @@ pub enum P2pRpcKind {
     Snark,
     Transaction,
     InitialPeers,
+    Ancestry,
+    TransitionChainProof,
+    EpochLedger,
 }
 
 impl P2pRpcKind {
@@ -68,6 +75,26 @@ impl P2pRpcKind {
             Self::Snark => config.snark,
             Self::Transaction => config.transaction,
             Self::InitialPeers => config.initial_peers,
+            Self::Ancestry => config.ancestry,
+            Self::TransitionChainProof => config.transition_chain_proof,
+            Self::EpochLedger => config.epoch_ledger,
+        }
+    }
+
+    pub fn quota(self, quotas: &P2pRpcQuotas) -> Limit<P2pRateLimit> {
+        match self {
+            Self::BestTipWithProof => quotas.best_tip_with_proof,
+            Self::LedgerQuery => quotas.ledger_query,
+            Self::StagedLedgerAuxAndPendingCoinbasesAtBlock => {
+                quotas.staged_ledger_aux_and_pending_coinbases_at_block
+            }
+            Self::Block => quotas.block,
+            Self::Snark => quotas.snark,
+            Self::Transaction => quotas.transaction,
+            Self::InitialPeers => quotas.initial_peers,
+            Self::Ancestry => quotas.ancestry,
+            Self::TransitionChainProof => quotas.transition_chain_proof,
+            Self::EpochLedger => quotas.epoch_ledger,
         }
     }
 
@@ -80,6 +107,9 @@ impl P2pRpcKind {
             Self::Snark => false,
             Self::Transaction => false,
             Self::InitialPeers => true,
+            Self::Ancestry => true,
+            Self::TransitionChainProof => true,
+            Self::EpochLedger => true,
         }
     }
 }
@@ -93,6 +123,14 @@ pub enum P2pRpcRequest {
     Snark(SnarkJobId),
     Transaction(TransactionHash),
     InitialPeers,
+    /// Best tip with proof, if it is better than the given consensus state
+    /// of the block with the given hash.
+    Ancestry(
+        ConsensusProofOfStakeDataConsensusStateValueStableV2,
+        StateHash,
+    ),
+    TransitionChainProof(StateHash),
+    EpochLedger(LedgerHash),
 }
 
 impl P2pRpcRequest {
@@ -107,6 +145,24 @@ impl P2pRpcRequest {
             Self::Snark(_) => P2pRpcKind::Snark,
             Self::Transaction(_) => P2pRpcKind::Transaction,
             Self::InitialPeers => P2pRpcKind::InitialPeers,
+            Self::Ancestry(..) => P2pRpcKind::Ancestry,
+            Self::TransitionChainProof(_) => P2pRpcKind::TransitionChainProof,
+            Self::EpochLedger(_) => P2pRpcKind::EpochLedger,
+        }
+    }
+
+    /// Cost of serving the request, taken from the quotas of the peer.
+    /// Roughly reflects the work needed to build the response and its size.
+    pub fn cost(&self) -> u64 {
+        match self {
+            Self::LedgerQuery(_, MinaLedgerSyncLedgerQueryStableV1::WhatContents(_)) => 8,
+            Self::LedgerQuery(..) => 1,
+            Self::Snark(_) | Self::Transaction(_) => 1,
+            Self::Block(_) | Self::InitialPeers => 5,
+            Self::BestTipWithProof | Self::Ancestry(..) | Self::TransitionChainProof(_) => 10,
+            Self::StagedLedgerAuxAndPendingCoinbasesAtBlock(_) => 100,
+            // the whole ledger is serialized
+            Self::EpochLedger(_) => 1_000,
         }
     }
 }
@@ -153,9 +209,14 @@ impl std::fmt::Display for P2pRpcRequest {
                 write!(f, "ledger: {ledger_hash}")
             }
             Self::StagedLedgerAuxAndPendingCoinbasesAtBlock(block_hash)
-            | Self::Block(block_hash) => {
+            | Self::Block(block_hash)
+            | Self::Ancestry(_, block_hash)
+            | Self::TransitionChainProof(block_hash) => {
                 write!(f, ", {block_hash}")
             }
+            Self::EpochLedger(ledger_hash) => {
+                write!(f, ", {ledger_hash}")
+            }
             Self::Snark(job_id) => {
                 write!(f, ", {job_id}")
             }
@@ -182,6 +243,15 @@ pub struct StagedLedgerAuxAndPendingCoinbases {
     pub needed_blocks: List<MinaStateProtocolStateValueStableV2>,
 }
 
+/// Proof that a block is a descendant of the block with `init_state_hash`.
+/// Applying `body_hashes` in order on top of `init_state_hash` must
+/// result in the hash of the requested block.
+#[derive(BinProtWrite, BinProtRead, Serialize, Deserialize, Debug, Clone)]
+pub struct TransitionChainProof {
+    pub init_state_hash: StateHash,
+    pub body_hashes: List<MinaBaseStateBodyHashStableV1>,
+}
+
 #[derive(BinProtWrite, BinProtRead, Serialize, Deserialize, Debug, Clone)]
 pub enum P2pRpcResponse {
     BestTipWithProof(BestTipWithProof),
@@ -191,6 +261,10 @@ pub enum P2pRpcResponse {
     Snark(Snark),
     Transaction(Transaction),
     InitialPeers(List<P2pConnectionOutgoingInitOpts>),
+    Ancestry(BestTipWithProof),
+    TransitionChainProof(TransitionChainProof),
+    /// Whole epoch ledger, as a sparse ledger containing all accounts.
+    EpochLedger(Arc<MinaBaseSparseLedgerBaseStableV2>),
 }
 
 impl P2pRpcResponse {
@@ -205,6 +279,9 @@ impl P2pRpcResponse {
             Self::Snark(_) => P2pRpcKind::Snark,
             Self::Transaction(_) => P2pRpcKind::Transaction,
             Self::InitialPeers(_) => P2pRpcKind::InitialPeers,
+            Self::Ancestry(_) => P2pRpcKind::Ancestry,
+            Self::TransitionChainProof(_) => P2pRpcKind::TransitionChainProof,
+            Self::EpochLedger(_) => P2pRpcKind::EpochLedger,
         }
     }
 }
@@ -216,8 +293,8 @@ mod libp2p {
     use mina_p2p_messages::{
         rpc,
         rpc_kernel::{
-            NeedsLength, QueryHeader, QueryPayload, ResponseHeader, ResponsePayload, RpcMethod,
-            RpcResult,
+            self, NeedsLength, QueryHeader, QueryPayload, ResponseHeader, ResponsePayload,
+            RpcMethod, RpcResult,
         },
     };
 
@@ -308,6 +385,54 @@ mod libp2p {
                     .collect();
                 let r = RpcResult(Ok(NeedsLength(r)));
 
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::Ancestry(r) => {
+                type Method = rpc::GetAncestryV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let BestTipWithProof {
+                    best_tip,
+                    proof: (middle, block),
+                } = r;
+
+                let middle = middle.into_iter().map(|hash| hash.0).collect();
+                let r = RpcResult(Ok(NeedsLength(Some(rpc::ProofCarryingDataWithHashV1 {
+                    data: best_tip.as_ref().clone(),
+                    proof: (middle, block.as_ref().clone()),
+                }))));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::TransitionChainProof(proof) => {
+                type Method = rpc::GetTransitionChainProofV1ForV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let TransitionChainProof {
+                    init_state_hash,
+                    body_hashes,
+                } = proof;
+
+                let body_hashes = body_hashes.into_iter().map(|hash| hash.0).collect();
+                let r = RpcResult(Ok(NeedsLength(Some((
+                    init_state_hash.0.clone(),
+                    body_hashes,
+                )))));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::EpochLedger(ledger) => {
+                type Method = rpc::GetEpochLedgerV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r = RpcResult(Ok(NeedsLength(RpcResult(Ok(ledger.as_ref().clone())))));
+
                 let mut v = vec![];
                 <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
                 Some((ResponseHeader { id: id as _ }, v.into()))
@@ -315,6 +440,17 @@ mod libp2p {
         }
     }
 
+    /// Error response, sent for requests we can't or won't answer.
+    pub fn error_response_into_libp2p(id: P2pRpcId) -> (ResponseHeader, Data) {
+        use binprot::BinProtWrite;
+
+        let r: ResponsePayload<()> = RpcResult(Err(rpc_kernel::Error::Uncaught_exn));
+
+        let mut v = vec![];
+        r.binprot_write(&mut v).unwrap_or_default();
+        (ResponseHeader { id: id as _ }, v.into())
+    }
+
     pub fn internal_request_into_libp2p(
         request: P2pRpcRequest,
         id: P2pRpcId,
@@ -415,6 +551,59 @@ mod libp2p {
                     v.into(),
                 ))
             }
+            P2pRpcRequest::Ancestry(consensus_state, hash) => {
+                type Method = rpc::GetAncestryV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let query = rpc::WithHashV1 {
+                    data: consensus_state,
+                    hash: hash.0.clone(),
+                };
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(query), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::TransitionChainProof(hash) => {
+                type Method = rpc::GetTransitionChainProofV1ForV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(hash.0.clone()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::EpochLedger(hash) => {
+                type Method = rpc::GetEpochLedgerV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(hash.0.clone()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
         }
     }
 }
@@ -157,10 +157,11 @@ impl redux::EnablingCondition<P2pState> for P2pChannelsRpcAction {
             } => {
                 #[cfg(feature = "p2p-libp2p")]
                 if state.is_libp2p_peer(peer_id) {
-                    let Some(response) = _response.as_ref() else {
-                        return false;
-                    };
-                    return if !response.kind().supported_by_libp2p() {
+                    // no response is sent as an error
+                    return if _response
+                        .as_ref()
+                        .is_some_and(|response| !response.kind().supported_by_libp2p())
+                    {
                         false
                     } else if let Some(streams) =
                         state.network.scheduler.rpc_incoming_streams.get(peer_id)
@@ -4,8 +4,11 @@ use super::{
 };
 use crate::{
     channels::{ChannelId, ChannelMsg, MsgId, P2pChannelsEffectfulAction},
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    trust::P2pTrustAction,
     P2pNetworkRpcAction, P2pPeerAction, P2pState,
 };
+use binprot::BinProtSize;
 use openmina_core::{block::BlockWithHash, bug_condition, error, Substate};
 use redux::ActionWithMeta;
 use std::collections::VecDeque;
@@ -24,6 +27,19 @@ impl P2pChannelsRpcState {
         let p2p_state = state_context.get_substate_mut()?;
         let peer_id = *action.peer_id();
         let is_libp2p = p2p_state.is_libp2p_peer(&peer_id);
+        let within_quotas = match &action {
+            P2pChannelsRpcAction::RequestReceived { request, .. } => {
+                let quotas = p2p_state.config.limits.rpc_quotas();
+                p2p_state.bandwidth.try_take_rpc_quotas(
+                    peer_id,
+                    &quotas,
+                    request.kind(),
+                    request.cost(),
+                    meta.time(),
+                )
+            }
+            _ => true,
+        };
         let peer_state = &mut p2p_state
             .get_ready_peer_mut(&peer_id)
             .ok_or_else(|| format!("Peer state not found for: {action:?}"))?
@@ -184,18 +200,34 @@ impl P2pChannelsRpcState {
                     );
                     return Ok(());
                 };
+                // Request over the quota is answered with an empty response
+                // right away, it is marked as pending so that async
+                // components don't pick it up.
                 remote
                     .pending_requests
                     .push_back(P2pRpcRemotePendingRequestState {
                         time: meta.time(),
                         id,
                         request: *request.clone(),
-                        is_pending: false,
+                        is_pending: !within_quotas,
                     });
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let p2p_state: &P2pState = state.substate()?;
 
+                if !within_quotas {
+                    dispatcher.push(P2pTrustAction::Penalize {
+                        peer_id,
+                        reason: P2pDisconnectionReason::RpcQuotaExceeded(request.kind()),
+                    });
+                    dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                        peer_id,
+                        id,
+                        response: None,
+                    });
+                    return Ok(());
+                }
+
                 if let Some(callback) = &p2p_state.callbacks.on_p2p_channels_rpc_request_received {
                     dispatcher.push_callback(callback.clone(), (peer_id, id, request));
                 }
@@ -228,29 +260,63 @@ impl P2pChannelsRpcState {
                     remote.last_responded = meta.time();
                 }
 
-                let dispatcher = state_context.into_dispatcher();
+                let p2p_state = state_context.get_substate_mut()?;
+                let responses_rate = p2p_state.config.limits.rpc_responses_rate();
 
                 #[cfg(feature = "p2p-libp2p")]
                 if is_libp2p {
-                    if let Some(response) = response {
-                        if let Some((response, data)) =
-                            super::libp2p::internal_response_into_libp2p(*response, id)
-                        {
-                            dispatcher.push(P2pNetworkRpcAction::OutgoingResponse {
-                                peer_id,
-                                response,
-                                data,
-                            });
+                    // libp2p peers get an error if we can't answer, so that
+                    // the stream is free for the next query
+                    let (response, data) = match response {
+                        Some(response) => {
+                            match super::libp2p::internal_response_into_libp2p(*response, id) {
+                                Some(v) => v,
+                                None => return Ok(()),
+                            }
                         }
+                        None => super::libp2p::error_response_into_libp2p(id),
+                    };
+                    let within_rate = p2p_state.bandwidth.try_take_response(
+                        peer_id,
+                        responses_rate,
+                        data.len() as u64,
+                        meta.time(),
+                    );
+                    let dispatcher = state_context.into_dispatcher();
+                    if !within_rate {
+                        dispatcher.push(P2pDisconnectionAction::Init {
+                            peer_id,
+                            reason: P2pDisconnectionReason::RpcResponseRateExceeded,
+                        });
+                        return Ok(());
                     }
-
+                    dispatcher.push(P2pNetworkRpcAction::OutgoingResponse {
+                        peer_id,
+                        response,
+                        data,
+                    });
                     return Ok(());
                 }
 
+                let msg = ChannelMsg::Rpc(RpcChannelMsg::Response(id, response.map(|v| *v)));
+                let within_rate = p2p_state.bandwidth.try_take_response(
+                    peer_id,
+                    responses_rate,
+                    msg.binprot_size() as u64,
+                    meta.time(),
+                );
+                let dispatcher = state_context.into_dispatcher();
+                if !within_rate {
+                    dispatcher.push(P2pDisconnectionAction::Init {
+                        peer_id,
+                        reason: P2pDisconnectionReason::RpcResponseRateExceeded,
+                    });
+                    return Ok(());
+                }
                 dispatcher.push(P2pChannelsEffectfulAction::MessageSend {
                     peer_id,
                     msg_id: MsgId::first(),
-                    msg: ChannelMsg::Rpc(RpcChannelMsg::Response(id, response.map(|v| *v))),
+                    msg,
                 });
                 Ok(())
             }
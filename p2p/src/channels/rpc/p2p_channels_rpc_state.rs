@@ -1,8 +1,8 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 use serde::{Deserialize, Serialize};
 
-use crate::P2pTimeouts;
+use crate::{bandwidth::P2pTokenBucket, Limit, P2pRpcQuotas, P2pTimeouts};
 
 use super::{P2pRpcId, P2pRpcKind, P2pRpcRequest};
 
@@ -48,6 +48,13 @@ pub struct P2pRpcRemoteState {
     pub last_responded: redux::Timestamp,
 }
 
+/// What is left of the [`P2pRpcQuotas`] of the peer.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pRpcRemoteQuotas {
+    pub total: P2pTokenBucket,
+    pub kinds: BTreeMap<P2pRpcKind, P2pTokenBucket>,
+}
+
 static EMPTY_REMOTE_REQUESTS: VecDeque<P2pRpcRemotePendingRequestState> = VecDeque::new();
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -161,3 +168,77 @@ impl P2pChannelsRpcState {
         }
     }
 }
+
+impl P2pRpcRemoteQuotas {
+    /// Takes the `cost` of a request from the total quota and from the
+    /// quota for its `kind`. Returns `false`, leaving quotas intact, if any of
+    /// them is exceeded.
+    pub fn try_take(
+        &mut self,
+        quotas: &P2pRpcQuotas,
+        kind: P2pRpcKind,
+        cost: u64,
+        time: redux::Timestamp,
+    ) -> bool {
+        let kind_quota = kind.quota(quotas);
+        let kind_bucket = self.kinds.entry(kind).or_default();
+
+        let buckets = [(quotas.total, &mut self.total), (kind_quota, kind_bucket)];
+        let within_quotas = buckets.iter().all(|(quota, bucket)| match quota {
+            Limit::Some(limit) => bucket.tokens(limit, time) >= cost as f64,
+            Limit::Unlimited => true,
+        });
+        if within_quotas {
+            for (quota, bucket) in buckets {
+                if let Limit::Some(limit) = quota {
+                    bucket.try_take(&limit, cost, time);
+                }
+            }
+        }
+        within_quotas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mina_p2p_messages::v2::{LedgerHash, MinaLedgerSyncLedgerQueryStableV1, StateHash};
+    use redux::Timestamp;
+
+    use super::*;
+    use crate::P2pRateLimit;
+
+    #[test]
+    fn quotas_are_per_kind_and_total() {
+        let quota = |rate, burst| Limit::Some(P2pRateLimit { rate, burst });
+        let quotas = P2pRpcQuotas {
+            total: quota(10, 20),
+            ledger_query: quota(10, 15),
+            ..Default::default()
+        };
+        let ledger_query = P2pRpcRequest::LedgerQuery(
+            LedgerHash::zero(),
+            MinaLedgerSyncLedgerQueryStableV1::NumAccounts,
+        );
+        let block = P2pRpcRequest::Block(StateHash::zero());
+        let time = Timestamp::ZERO + Duration::from_secs(1);
+        let mut remote = P2pRpcRemoteQuotas::default();
+
+        let mut try_take = |request: &P2pRpcRequest, time| {
+            remote.try_take(&quotas, request.kind(), request.cost(), time)
+        };
+
+        for _ in 0..15 {
+            assert!(try_take(&ledger_query, time));
+        }
+        assert!(!try_take(&ledger_query, time));
+        // 5 tokens of the total quota are left
+        assert!(try_take(&block, time));
+        assert!(!try_take(&block, time));
+
+        let time = time + Duration::from_secs(1);
+        assert!(try_take(&ledger_query, time));
+        assert!(try_take(&block, time));
+    }
+}
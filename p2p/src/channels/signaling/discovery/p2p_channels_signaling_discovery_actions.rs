@@ -171,7 +171,14 @@ impl redux::EnablingCondition<P2pState> for P2pChannelsSignalingDiscoveryAction
                     state.get_ready_peer(&target_peer_id).is_some_and(|p| {
                         p.channels.signaling.sent_discovered_peer_id() == Some(*peer_id)
                     });
-                has_peer_requested_discovery
+                // isolated node must not introduce peers to each other
+                !state.config.access.isolate
+                    && state.is_signaling_relay_peer_allowed(peer_id, now)
+                    && state.is_signaling_relay_peer_allowed(&target_peer_id, now)
+                    && state
+                        .bandwidth
+                        .can_relay_signaling(state.config.limits.signaling_relay_rate(), now)
+                    && has_peer_requested_discovery
                     && !target_peer_already_discovering_them
                     && state.ready_peers_iter().all(|(_, p)| {
                         p.channels.signaling.sent_discovered_peer_id() != Some(target_peer_id)
@@ -187,12 +187,30 @@ impl P2pChannelsSignalingDiscoveryState {
                     target_public_key: target_public_key.clone(),
                 };
 
+                let target_peer_id = target_public_key.peer_id();
+                // access might have changed since the target was discovered
+                let p2p_state = state_context.get_substate_mut()?;
+                let relay_rate = p2p_state.config.limits.signaling_relay_rate();
+                let relay = !p2p_state.config.access.isolate
+                    && p2p_state.is_signaling_relay_peer_allowed(&peer_id, meta.time())
+                    && p2p_state.is_signaling_relay_peer_allowed(&target_peer_id, meta.time())
+                    && p2p_state
+                        .bandwidth
+                        .try_take_signaling_relay(relay_rate, meta.time());
+
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(P2pChannelsSignalingExchangeAction::OfferSend {
-                    peer_id: target_public_key.peer_id(),
-                    offerer_pub_key: peer_id.to_public_key().unwrap(),
-                    offer: offer.clone(),
-                });
+                if relay {
+                    dispatcher.push(P2pChannelsSignalingExchangeAction::OfferSend {
+                        peer_id: target_peer_id,
+                        offerer_pub_key: peer_id.to_public_key().unwrap(),
+                        offer: offer.clone(),
+                    });
+                } else {
+                    dispatcher.push(P2pChannelsSignalingDiscoveryAction::AnswerSend {
+                        peer_id,
+                        answer: None,
+                    });
+                }
                 Ok(())
             }
             P2pChannelsSignalingDiscoveryAction::AnswerSend { answer, .. } => {
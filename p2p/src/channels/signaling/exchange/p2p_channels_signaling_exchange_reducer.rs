@@ -133,7 +133,7 @@ impl P2pChannelsSignalingExchangeState {
                     },
                     offer: offer.clone().into(),
                 };
-                match state.incoming_accept(opts.peer_id, &opts.offer) {
+                match state.incoming_accept(opts.peer_id, &opts.offer, meta.time()) {
                     Ok(_) => {
                         dispatcher.push(P2pConnectionIncomingAction::Init { opts, rpc_id: None });
                     }
@@ -26,6 +26,22 @@ use std::collections::BTreeSet;
 use discovery::P2pChannelsSignalingDiscoveryAction;
 
 impl crate::P2pState {
+    /// Whether the peer may take part in signaling we relay, either as the
+    /// dialer or as the listener.
+    pub(crate) fn is_signaling_relay_peer_allowed(
+        &self,
+        peer_id: &crate::PeerId,
+        time: redux::Timestamp,
+    ) -> bool {
+        let ip = self
+            .peers
+            .get(peer_id)
+            .and_then(|p| p.dial_opts.as_ref()?.ip());
+        self.config.access.is_peer_allowed(peer_id, ip)
+            && !self.trust.is_peer_banned(peer_id, time)
+            && ip.is_none_or(|ip| !self.trust.is_ip_banned(&ip, time))
+    }
+
     pub(super) fn webrtc_discovery_respond_with_availble_peers<Action, State>(
         &self,
         dispatcher: &mut redux::Dispatcher<Action, State>,
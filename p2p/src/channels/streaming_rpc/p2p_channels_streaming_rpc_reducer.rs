@@ -1,8 +1,11 @@
+use binprot::BinProtSize;
 use openmina_core::{bug_condition, Substate};
 use redux::ActionWithMeta;
 
 use crate::{
     channels::{ChannelId, ChannelMsg, MsgId, P2pChannelsEffectfulAction},
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    trust::P2pTrustAction,
     P2pState,
 };
 
@@ -25,6 +28,19 @@ impl P2pChannelsStreamingRpcState {
         let (action, meta) = action.split();
         let peer_id = *action.peer_id();
         let p2p_state = state_context.get_substate_mut()?;
+        let within_quotas = match &action {
+            P2pChannelsStreamingRpcAction::RequestReceived { request, .. } => {
+                let quotas = p2p_state.config.limits.rpc_quotas();
+                p2p_state.bandwidth.try_take_rpc_quotas(
+                    peer_id,
+                    &quotas,
+                    request.quota_kind(),
+                    request.cost(),
+                    meta.time(),
+                )
+            }
+            _ => true,
+        };
 
         let channels_state = &mut p2p_state
             .get_ready_peer_mut(&peer_id)
@@ -223,13 +239,33 @@ impl P2pChannelsStreamingRpcState {
                     );
                     return Ok(());
                 };
+                let quota_kind = request.quota_kind();
+                // Request over the quota is answered with an empty response
+                // right away, it is marked as pending so that async
+                // components don't pick it up.
+                let progress = if within_quotas {
+                    StagedLedgerPartsSendProgress::LedgerGetIdle { time: meta.time() }
+                } else {
+                    StagedLedgerPartsSendProgress::LedgerGetPending { time: meta.time() }
+                };
                 *remote = P2pStreamingRpcRemoteState::Requested {
                     time: meta.time(),
                     id,
                     request,
-                    progress: StagedLedgerPartsSendProgress::LedgerGetIdle { time: meta.time() }
-                        .into(),
+                    progress: progress.into(),
                 };
+                let dispatcher = state_context.into_dispatcher();
+                if !within_quotas {
+                    dispatcher.push(P2pTrustAction::Penalize {
+                        peer_id,
+                        reason: P2pDisconnectionReason::RpcQuotaExceeded(quota_kind),
+                    });
+                    dispatcher.push(P2pChannelsStreamingRpcAction::ResponseSendInit {
+                        peer_id,
+                        id,
+                        response: None,
+                    });
+                }
                 // async ledger request will be triggered by `LedgerReadAction::FindTodos`.
                 Ok(())
             }
@@ -390,9 +426,23 @@ impl P2pChannelsStreamingRpcState {
                     }
                 }
 
+                let msg: ChannelMsg = StreamingRpcChannelMsg::Response(id, Some(*response)).into();
+                let p2p_state = state_context.get_substate_mut()?;
+                let responses_rate = p2p_state.config.limits.rpc_responses_rate();
+                let within_rate = p2p_state.bandwidth.try_take_response(
+                    peer_id,
+                    responses_rate,
+                    msg.binprot_size() as u64,
+                    meta.time(),
+                );
                 let dispatcher = state_context.into_dispatcher();
-
-                let msg = StreamingRpcChannelMsg::Response(id, Some(*response)).into();
+                if !within_rate {
+                    dispatcher.push(P2pDisconnectionAction::Init {
+                        peer_id,
+                        reason: P2pDisconnectionReason::RpcResponseRateExceeded,
+                    });
+                    return Ok(());
+                }
                 dispatcher.push(P2pChannelsEffectfulAction::MessageSend {
                     peer_id,
                     msg_id: MsgId::first(),
@@ -11,7 +11,7 @@ use derive_more::From;
 use mina_p2p_messages::v2;
 use serde::{Deserialize, Serialize};
 
-use crate::P2pTimeouts;
+use crate::{channels::rpc::P2pRpcKind, P2pTimeouts};
 
 #[derive(Serialize, Deserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub enum P2pStreamingRpcKind {
@@ -58,6 +58,22 @@ impl P2pStreamingRpcRequest {
             Self::StagedLedgerParts(_) => P2pStreamingRpcKind::StagedLedgerParts,
         }
     }
+
+    /// Kind of the RPC request whose quota serving this request is taken
+    /// from, see [`P2pRpcRequest::cost`](crate::channels::rpc::P2pRpcRequest::cost).
+    pub fn quota_kind(&self) -> P2pRpcKind {
+        match self {
+            Self::StagedLedgerParts(_) => P2pRpcKind::StagedLedgerAuxAndPendingCoinbasesAtBlock,
+        }
+    }
+
+    /// Cost of serving the request, same as for the RPC request with the
+    /// same response.
+    pub fn cost(&self) -> u64 {
+        match self {
+            Self::StagedLedgerParts(_) => 100,
+        }
+    }
 }
 
 impl Default for P2pStreamingRpcRequest {
@@ -6,6 +6,8 @@ pub use p2p_connection_incoming_actions::*;
 
 mod p2p_connection_incoming_reducer;
 
+use std::net::IpAddr;
+
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
 
@@ -33,6 +35,7 @@ impl P2pState {
         &self,
         peer_id: PeerId,
         offer: &webrtc::Offer,
+        time: redux::Timestamp,
     ) -> Result<(), RejectionReason> {
         if self.chain_id != offer.chain_id {
             return Err(RejectionReason::ChainIdMismatch);
@@ -52,6 +55,17 @@ impl P2pState {
             return Err(RejectionReason::ConnectingToSelf);
         }
 
+        if self.trust.is_peer_banned(&peer_id, time)
+            || offer.ips().any(|ip| self.trust.is_ip_banned(&ip, time))
+        {
+            return Err(RejectionReason::Banned);
+        }
+
+        let ips = offer.ips().collect::<Vec<_>>();
+        if !self.config.access.is_peer_with_ips_allowed(&peer_id, &ips) {
+            return Err(RejectionReason::NotAllowed);
+        }
+
         if self.is_peer_connected_or_connecting(&peer_id) {
             // Both nodes trying to connect to each other at the same time.
             // Choose connection arbitrarily based on peer id.
@@ -68,11 +82,24 @@ impl P2pState {
         Ok(())
     }
 
-    pub fn libp2p_incoming_accept(&self, peer_id: PeerId) -> Result<(), RejectionReason> {
+    pub fn libp2p_incoming_accept(
+        &self,
+        peer_id: PeerId,
+        ip: IpAddr,
+        time: redux::Timestamp,
+    ) -> Result<(), RejectionReason> {
         if peer_id == self.my_id() {
             return Err(RejectionReason::ConnectingToSelf);
         }
 
+        if self.trust.is_peer_banned(&peer_id, time) {
+            return Err(RejectionReason::Banned);
+        }
+
+        if !self.config.access.is_peer_allowed(&peer_id, Some(ip)) {
+            return Err(RejectionReason::NotAllowed);
+        }
+
         if self.already_has_max_ready_peers() {
             return Err(RejectionReason::PeerCapacityFull);
         }
@@ -3,11 +3,10 @@ use super::{
 };
 use crate::{
     connection::{P2pConnectionAction, P2pConnectionState},
-    webrtc, P2pAction, P2pPeerStatus, P2pState, PeerId,
+    webrtc, ConnectionAddr, P2pAction, P2pPeerStatus, P2pState, PeerId,
 };
 use openmina_core::{requests::RpcId, ActionEvent};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
 
 #[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
 #[action_event(fields(debug(opts), display(peer_id), display(error)))]
@@ -69,7 +68,7 @@ pub enum P2pConnectionIncomingAction {
     /// Detected incoming connection from this peer.
     FinalizePendingLibp2p {
         peer_id: PeerId,
-        addr: SocketAddr,
+        addr: ConnectionAddr,
     },
     /// Incoming libp2p connection is successful.
     Libp2pReceived {
@@ -101,9 +100,9 @@ impl P2pConnectionIncomingAction {
 impl redux::EnablingCondition<P2pState> for P2pConnectionIncomingAction {
     fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
         match self {
-            P2pConnectionIncomingAction::Init { opts, .. } => {
-                state.incoming_accept(opts.peer_id, &opts.offer).is_ok()
-            }
+            P2pConnectionIncomingAction::Init { opts, .. } => state
+                .incoming_accept(opts.peer_id, &opts.offer, time)
+                .is_ok(),
             P2pConnectionIncomingAction::AnswerSdpCreatePending { peer_id } => {
                 state.peers.get(peer_id).is_some_and(|peer| {
                     matches!(
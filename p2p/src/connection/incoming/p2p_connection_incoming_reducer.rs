@@ -422,15 +422,22 @@ impl P2pConnectionIncomingState {
                             dial_opts: Some(P2pConnectionOutgoingInitOpts::LibP2P(
                                 P2pConnectionOutgoingInitLibp2pOpts {
                                     peer_id,
-                                    host: Host::from(addr.ip()),
-                                    port: addr.port(),
+                                    host: Host::from(addr.sock_addr.ip()),
+                                    port: addr.sock_addr.port(),
+                                    transport: addr.transport,
                                 },
                             )),
                             status: P2pPeerStatus::Disconnected { time: meta.time() },
                             identify: None,
                         });
 
-                    Self::reduce_finalize_libp2p_pending(state, addr, time, my_id, peer_id);
+                    Self::reduce_finalize_libp2p_pending(
+                        state,
+                        addr.sock_addr,
+                        time,
+                        my_id,
+                        peer_id,
+                    );
 
                     let (dispatcher, state) = state_context.into_dispatcher_and_state();
                     let p2p_state: &P2pState = state.substate()?;
@@ -476,7 +483,7 @@ impl P2pConnectionIncomingState {
         my_id: PeerId,
         peer_id: PeerId,
         time: Timestamp,
-        addr: SocketAddr,
+        addr: ConnectionAddr,
     ) where
         State: crate::P2pStateTrait,
         Action: crate::P2pActionTrait<State>,
@@ -493,7 +500,9 @@ impl P2pConnectionIncomingState {
             .as_connecting()
             .and_then(|connecting| connecting.as_incoming())
         {
-            if let Err(reason) = p2p_state.libp2p_incoming_accept(peer_id) {
+            if let Err(reason) =
+                p2p_state.libp2p_incoming_accept(peer_id, addr.sock_addr.ip(), time)
+            {
                 warn!(time; node_id = display(my_id), summary = "rejecting incoming connection", peer_id = display(peer_id), reason = display(&reason));
                 dispatcher.push(P2pDisconnectionAction::Init {
                     peer_id,
@@ -511,9 +520,10 @@ impl P2pConnectionIncomingState {
                             |ConnectionAddr {
                                  sock_addr,
                                  incoming,
+                                 ..
                              }| {
                                 *incoming
-                                    && sock_addr != &addr
+                                    && sock_addr != &addr.sock_addr
                                     && close_duplicates.contains(sock_addr)
                             },
                         )
@@ -534,10 +544,7 @@ impl P2pConnectionIncomingState {
         } else {
             warn!(time; node_id = display(my_id), summary = "rejecting incoming connection as duplicate", peer_id = display(peer_id));
             dispatcher.push(P2pNetworkSchedulerAction::Disconnect {
-                addr: ConnectionAddr {
-                    sock_addr: addr,
-                    incoming: true,
-                },
+                addr,
                 reason: P2pDisconnectionReason::Libp2pIncomingRejected(
                     RejectionReason::AlreadyConnected,
                 ),
@@ -582,7 +589,7 @@ impl P2pConnectionIncomingState {
                             _ => return None,
                         };
                         let port = match iter.next()? {
-                            multiaddr::Protocol::Tcp(port) => port,
+                            multiaddr::Protocol::Tcp(port) | multiaddr::Protocol::Udp(port) => port,
                             _ => return None,
                         };
                         Some(SocketAddr::from((ip, port)))
@@ -21,7 +21,7 @@ use mina_p2p_messages::v2;
 
 use crate::{
     webrtc::{self, Host},
-    PeerId,
+    ConnectionTransport, PeerId,
 };
 
 #[cfg(feature = "p2p-libp2p")]
@@ -44,6 +44,7 @@ pub struct P2pConnectionOutgoingInitLibp2pOpts {
     pub peer_id: PeerId,
     pub host: Host,
     pub port: u16,
+    pub transport: ConnectionTransport,
 }
 
 impl P2pConnectionOutgoingInitLibp2pOpts {
@@ -87,20 +88,25 @@ pub(crate) mod libp2p_opts {
 
     use multiaddr::Multiaddr;
 
-    use crate::{webrtc::Host, PeerId};
+    use crate::{webrtc::Host, ConnectionTransport, PeerId};
 
     impl super::P2pConnectionOutgoingInitLibp2pOpts {
+        /// Multiaddr of the peer, without the peer id.
+        fn transport_multiaddr(&self) -> Multiaddr {
+            let maddr = Multiaddr::empty().with((&self.host).into());
+            match self.transport {
+                ConnectionTransport::Tcp => maddr.with(multiaddr::Protocol::Tcp(self.port)),
+                ConnectionTransport::Quic => maddr
+                    .with(multiaddr::Protocol::Udp(self.port))
+                    .with(multiaddr::Protocol::QuicV1),
+            }
+        }
+
         fn to_peer_id_multiaddr(&self) -> (PeerId, Multiaddr) {
-            (
-                self.peer_id,
-                Multiaddr::from_iter([(&self.host).into(), multiaddr::Protocol::Tcp(self.port)]),
-            )
+            (self.peer_id, self.transport_multiaddr())
         }
         fn into_peer_id_multiaddr(self) -> (PeerId, Multiaddr) {
-            (
-                self.peer_id,
-                Multiaddr::from_iter([(&self.host).into(), multiaddr::Protocol::Tcp(self.port)]),
-            )
+            (self.peer_id, self.transport_multiaddr())
         }
 
         pub fn matches_socket_addr(&self, addr: SocketAddr) -> bool {
@@ -138,6 +144,7 @@ pub(crate) mod libp2p_opts {
                 peer_id,
                 host,
                 port,
+                transport: ConnectionTransport::Tcp,
             }
         }
     }
@@ -172,6 +179,10 @@ impl P2pConnectionOutgoingInitOpts {
         matches!(self, Self::LibP2P(_))
     }
 
+    pub fn is_quic(&self) -> bool {
+        matches!(self, Self::LibP2P(v) if v.transport.is_quic())
+    }
+
     pub fn peer_id(&self) -> &PeerId {
         match self {
             Self::WebRTC { peer_id, .. } => peer_id,
@@ -179,6 +190,25 @@ impl P2pConnectionOutgoingInitOpts {
         }
     }
 
+    /// Ip address of the peer, if it is known without resolving the host.
+    ///
+    /// For WebRTC it's the address of the peer's own signaling server. When
+    /// the signaling goes through a relay or a proxy, the address is only
+    /// known from the answer's ice candidates.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Self::LibP2P(v) => v.host.ip(),
+            Self::WebRTC { signaling, .. } => match signaling {
+                webrtc::SignalingMethod::Http(info) | webrtc::SignalingMethod::Https(info) => {
+                    info.host.ip()
+                }
+                webrtc::SignalingMethod::HttpsProxy(..) | webrtc::SignalingMethod::P2p { .. } => {
+                    None
+                }
+            },
+        }
+    }
+
     pub fn kind(&self) -> &'static str {
         match self {
             Self::WebRTC { .. } => "webrtc",
@@ -240,6 +270,7 @@ impl P2pConnectionOutgoingInitOpts {
                 peer_id: peer_id.try_into().ok()?,
                 host: host.parse().ok()?,
                 port: msg.libp2p_port.as_u64() as u16,
+                transport: ConnectionTransport::Tcp,
             };
             Self::LibP2P(opts)
         };
@@ -405,17 +436,21 @@ impl TryFrom<P2pConnectionOutgoingInitLibp2pOpts> for multiaddr::Multiaddr {
     fn try_from(value: P2pConnectionOutgoingInitLibp2pOpts) -> Result<Self, Self::Error> {
         use multiaddr::Protocol;
 
-        Ok(Self::empty()
-            .with(match &value.host {
-                // maybe should be just `Dns`?
-                Host::Domain(v) => Protocol::Dns4(v.into()),
-                Host::Ipv4(v) => Protocol::Ip4(*v),
-                Host::Ipv6(v) => Protocol::Ip6(*v),
-            })
-            .with(Protocol::Tcp(value.port))
-            .with(Protocol::P2p(libp2p_identity::PeerId::try_from(
-                value.peer_id,
-            )?)))
+        let maddr = Self::empty().with(match &value.host {
+            // maybe should be just `Dns`?
+            Host::Domain(v) => Protocol::Dns4(v.into()),
+            Host::Ipv4(v) => Protocol::Ip4(*v),
+            Host::Ipv6(v) => Protocol::Ip6(*v),
+        });
+        let maddr = match value.transport {
+            ConnectionTransport::Tcp => maddr.with(Protocol::Tcp(value.port)),
+            ConnectionTransport::Quic => {
+                maddr.with(Protocol::Udp(value.port)).with(Protocol::QuicV1)
+            }
+        };
+        Ok(maddr.with(Protocol::P2p(libp2p_identity::PeerId::try_from(
+            value.peer_id,
+        )?)))
     }
 }
 
@@ -464,7 +499,7 @@ impl TryFrom<&multiaddr::Multiaddr> for P2pConnectionOutgoingInitLibp2pOpts {
                 }
             },
             port: match iter.next() {
-                Some(Protocol::Tcp(port)) => port,
+                Some(Protocol::Tcp(port) | Protocol::Udp(port)) => port,
                 Some(_) => {
                     return Err(P2pConnectionOutgoingInitOptsParseError::Other(
                         "unexpected part in multiaddr! expected port".to_string(),
@@ -476,6 +511,17 @@ impl TryFrom<&multiaddr::Multiaddr> for P2pConnectionOutgoingInitLibp2pOpts {
                     ));
                 }
             },
+            transport: match maddr.iter().nth(1) {
+                Some(Protocol::Udp(_)) => match iter.next() {
+                    Some(Protocol::QuicV1) => ConnectionTransport::Quic,
+                    _ => {
+                        return Err(P2pConnectionOutgoingInitOptsParseError::Other(
+                            "unsupported udp transport! expected `/quic-v1`".to_string(),
+                        ));
+                    }
+                },
+                _ => ConnectionTransport::Tcp,
+            },
             peer_id: match iter.next() {
                 Some(Protocol::P2p(hash)) => libp2p_identity::PeerId::from_multihash(hash.into())
                     .map_err(|_| {
@@ -517,3 +563,59 @@ mod measurement {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn libp2p_opts_multiaddr_transport() {
+        let peer_id = crate::identity::SecretKey::rand().public_key().peer_id();
+        for (transport, maddr) in [
+            (ConnectionTransport::Tcp, "/ip4/1.2.3.4/tcp/8302"),
+            (ConnectionTransport::Quic, "/ip4/1.2.3.4/udp/8302/quic-v1"),
+        ] {
+            let maddr = format!("{maddr}/p2p/{}", peer_id.to_libp2p_string());
+            let opts = maddr.parse::<P2pConnectionOutgoingInitOpts>().unwrap();
+            let P2pConnectionOutgoingInitOpts::LibP2P(libp2p_opts) = &opts else {
+                panic!("expected libp2p opts, got {opts:?}");
+            };
+            assert_eq!(libp2p_opts.transport, transport);
+            assert_eq!(libp2p_opts.port, 8302);
+            assert_eq!(opts.to_string(), maddr);
+        }
+
+        let maddr = format!("/ip4/1.2.3.4/udp/8302/p2p/{}", peer_id.to_libp2p_string());
+        assert!(maddr.parse::<P2pConnectionOutgoingInitOpts>().is_err());
+    }
+
+    #[test]
+    fn opts_ip() {
+        let peer_id = crate::identity::SecretKey::rand().public_key().peer_id();
+        let ip: IpAddr = "1.2.3.4".parse().unwrap();
+        let webrtc_opts = |signaling| P2pConnectionOutgoingInitOpts::WebRTC { peer_id, signaling };
+        let info = |host| webrtc::HttpSignalingInfo { host, port: 3000 };
+
+        let maddr = format!("/ip4/1.2.3.4/tcp/8302/p2p/{}", peer_id.to_libp2p_string());
+        let opts = maddr.parse::<P2pConnectionOutgoingInitOpts>().unwrap();
+        assert_eq!(opts.ip(), Some(ip));
+        let maddr = format!(
+            "/dns4/example.com/tcp/8302/p2p/{}",
+            peer_id.to_libp2p_string()
+        );
+        let opts = maddr.parse::<P2pConnectionOutgoingInitOpts>().unwrap();
+        assert_eq!(opts.ip(), None);
+
+        let signaling = webrtc::SignalingMethod::Http(info(Host::Ipv4("1.2.3.4".parse().unwrap())));
+        assert_eq!(webrtc_opts(signaling).ip(), Some(ip));
+        let signaling = webrtc::SignalingMethod::Https(info(Host::Domain("example.com".into())));
+        assert_eq!(webrtc_opts(signaling).ip(), None);
+        let signaling =
+            webrtc::SignalingMethod::HttpsProxy(1, info(Host::Ipv4("1.2.3.4".parse().unwrap())));
+        assert_eq!(webrtc_opts(signaling).ip(), None);
+        let signaling = webrtc::SignalingMethod::P2p {
+            relay_peer_id: peer_id,
+        };
+        assert_eq!(webrtc_opts(signaling).ip(), None);
+    }
+}
@@ -96,6 +96,10 @@ impl redux::EnablingCondition<P2pState> for P2pConnectionOutgoingAction {
             P2pConnectionOutgoingAction::Init { opts, .. } => {
                 !state.already_has_min_peers() &&
                 &state.my_id() != opts.peer_id() &&
+                (!opts.is_quic() || state.config.libp2p_quic) &&
+                !state.trust.is_peer_banned(opts.peer_id(), time) &&
+                opts.ip().is_none_or(|ip| !state.trust.is_ip_banned(&ip, time)) &&
+                state.config.access.is_peer_allowed(opts.peer_id(), opts.ip()) &&
                 state
                     .peers
                     .get(opts.peer_id())
@@ -103,6 +107,10 @@ impl redux::EnablingCondition<P2pState> for P2pConnectionOutgoingAction {
             }
             P2pConnectionOutgoingAction::Reconnect { opts, .. } => {
                 !state.already_has_min_peers()
+                    && (!opts.is_quic() || state.config.libp2p_quic)
+                    && !state.trust.is_peer_banned(opts.peer_id(), time)
+                    && opts.ip().is_none_or(|ip| !state.trust.is_ip_banned(&ip, time))
+                    && state.config.access.is_peer_allowed(opts.peer_id(), opts.ip())
                     && state.peers.get(opts.peer_id()).is_some_and( |peer| {
                         peer.can_reconnect(time, &state.config.timeouts)
                     })
@@ -4,13 +4,14 @@ use openmina_core::{bug_condition, warn, Substate};
 use redux::ActionWithMeta;
 
 use crate::{
+    address_book::{P2pAddressBookAction, KNOWN_GOOD_PICK_WEIGHT},
     channels::signaling::discovery::P2pChannelsSignalingDiscoveryAction,
     connection::{
         outgoing_effectful::P2pConnectionOutgoingEffectfulAction, P2pConnectionErrorResponse,
         P2pConnectionState,
     },
     disconnection::P2pDisconnectionAction,
-    webrtc::Host,
+    webrtc::{Host, RejectionReason},
     P2pNetworkKadRequestAction, P2pNetworkSchedulerAction, P2pPeerAction, P2pPeerState,
     P2pPeerStatus, P2pState,
 };
@@ -39,7 +40,28 @@ impl P2pConnectionOutgoingState {
             P2pConnectionOutgoingAction::RandomInit => {
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let p2p_state: &P2pState = state.substate()?;
-                let peers = p2p_state.disconnected_peers().collect::<Vec<_>>();
+                let peers = p2p_state
+                    .disconnected_peers()
+                    .filter(|opts| {
+                        !p2p_state.trust.is_peer_banned(opts.peer_id(), time)
+                            && opts
+                                .ip()
+                                .is_none_or(|ip| !p2p_state.trust.is_ip_banned(&ip, time))
+                    })
+                    // prefer peers we successfully connected to in the past,
+                    // giving them more chances to be picked
+                    .flat_map(|opts| {
+                        let weight = if p2p_state.address_book.is_known_good(opts.peer_id()) {
+                            KNOWN_GOOD_PICK_WEIGHT
+                        } else {
+                            1
+                        };
+                        std::iter::repeat_n(opts, weight)
+                    })
+                    .collect::<Vec<_>>();
+                if peers.is_empty() {
+                    return Ok(());
+                }
                 dispatcher.push(P2pConnectionOutgoingEffectfulAction::RandomInit { peers });
                 Ok(())
             }
@@ -75,7 +97,10 @@ impl P2pConnectionOutgoingState {
                 if let P2pConnectionOutgoingInitOpts::LibP2P(libp2p_opts) = &opts {
                     match SocketAddr::try_from(libp2p_opts) {
                         Ok(addr) => {
-                            dispatcher.push(P2pNetworkSchedulerAction::OutgoingConnect { addr });
+                            dispatcher.push(P2pNetworkSchedulerAction::OutgoingConnect {
+                                addr,
+                                transport: libp2p_opts.transport,
+                            });
                         }
                         Err(
                             P2pConnectionOutgoingInitLibp2pOptsTryToSocketAddrError::Unresolved(
@@ -115,7 +140,10 @@ impl P2pConnectionOutgoingState {
                 if let P2pConnectionOutgoingInitOpts::LibP2P(libp2p_opts) = &opts {
                     match SocketAddr::try_from(libp2p_opts) {
                         Ok(addr) => {
-                            dispatcher.push(P2pNetworkSchedulerAction::OutgoingConnect { addr });
+                            dispatcher.push(P2pNetworkSchedulerAction::OutgoingConnect {
+                                addr,
+                                transport: libp2p_opts.transport,
+                            });
                         }
                         Err(
                             P2pConnectionOutgoingInitLibp2pOptsTryToSocketAddrError::Unresolved(
@@ -332,6 +360,32 @@ impl P2pConnectionOutgoingState {
                 Ok(())
             }
             P2pConnectionOutgoingAction::AnswerRecvSuccess { answer, peer_id } => {
+                if answer
+                    .ips()
+                    .any(|ip| p2p_state.trust.is_ip_banned(&ip, time))
+                {
+                    let dispatcher = state_context.into_dispatcher();
+                    dispatcher.push(P2pConnectionOutgoingAction::AnswerRecvError {
+                        peer_id,
+                        error: P2pConnectionErrorResponse::Rejected(RejectionReason::Banned),
+                    });
+                    return Ok(());
+                }
+
+                let ips = answer.ips().collect::<Vec<_>>();
+                if !p2p_state
+                    .config
+                    .access
+                    .is_peer_with_ips_allowed(&peer_id, &ips)
+                {
+                    let dispatcher = state_context.into_dispatcher();
+                    dispatcher.push(P2pConnectionOutgoingAction::AnswerRecvError {
+                        peer_id,
+                        error: P2pConnectionErrorResponse::Rejected(RejectionReason::NotAllowed),
+                    });
+                    return Ok(());
+                }
+
                 let state = p2p_state.outgoing_peer_connection_mut(&peer_id).ok_or(
                     "Missing peer connection for `P2pConnectionOutgoingAction::AnswerRecvSuccess`",
                 )?;
@@ -536,6 +590,10 @@ impl P2pConnectionOutgoingState {
                         dispatcher.push_callback(callback.clone(), (rpc_id, error));
                     }
                 }
+                dispatcher.push(P2pAddressBookAction::ConnectionFinished {
+                    peer_id,
+                    success: false,
+                });
                 dispatcher.push(P2pDisconnectionAction::FailedCleanup { peer_id });
 
                 Ok(())
@@ -576,6 +634,10 @@ impl P2pConnectionOutgoingState {
                     peer_id,
                     incoming: false,
                 });
+                dispatcher.push(P2pAddressBookAction::ConnectionFinished {
+                    peer_id,
+                    success: true,
+                });
 
                 let rpc_id = p2p_state.peer_connection_rpc_id(&peer_id);
                 if let Some(rpc_id) = rpc_id {
@@ -33,6 +33,8 @@ pub enum P2pDisconnectionReason {
     TransitionFrontierStreamingRpcTimeout(P2pStreamingRpcKind),
     #[error("received num accounts rejected")]
     TransitionFrontierSyncLedgerSnarkedNumAccountsRejected,
+    #[error("received epoch ledger rejected")]
+    TransitionFrontierSyncLedgerSnarkedEpochLedgerRejected,
     #[error("failed to verify snark pool diff")]
     SnarkPoolVerifyError,
     #[error("duplicate connection")]
@@ -43,4 +45,42 @@ pub enum P2pDisconnectionReason {
     Unsupported,
     #[error("invalid pubsub message")]
     InvalidMessage,
+    #[error("peer is banned")]
+    Banned,
+    #[error("peer is not allowed")]
+    NotAllowed,
+    #[error("rpc responses rate limit exceeded")]
+    RpcResponseRateExceeded,
+    #[error("rpc quota for {0:?} exceeded")]
+    RpcQuotaExceeded(P2pRpcKind),
+}
+
+impl P2pDisconnectionReason {
+    /// Penalty to the trust score of the peer disconnected for this reason.
+    ///
+    /// Only misbehaviour is penalized, disconnections caused by network
+    /// issues or by the node itself are not.
+    pub fn trust_penalty(&self) -> f64 {
+        match self {
+            Self::SnarkPoolVerifyError
+            | Self::TransitionFrontierSyncLedgerSnarkedNumAccountsRejected
+            | Self::TransitionFrontierSyncLedgerSnarkedEpochLedgerRejected => 1.0,
+            Self::InvalidMessage => 0.5,
+            Self::P2pChannelMsgUnexpected(_) => 0.25,
+            Self::TransitionFrontierRpcTimeout(_)
+            | Self::TransitionFrontierStreamingRpcTimeout(_)
+            | Self::RpcResponseRateExceeded => 0.1,
+            Self::RpcQuotaExceeded(_) => 0.05,
+            Self::FreeUpSpace
+            | Self::P2pChannelSendFailed(_)
+            | Self::P2pChannelReceiveFailed(_)
+            | Self::P2pChannelClosed(_)
+            | Self::Libp2pIncomingRejected(_)
+            | Self::DuplicateConnection
+            | Self::Timeout
+            | Self::Unsupported
+            | Self::Banned
+            | Self::NotAllowed => 0.0,
+        }
+    }
 }
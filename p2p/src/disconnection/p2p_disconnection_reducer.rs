@@ -5,8 +5,8 @@ use rand::prelude::*;
 use redux::ActionWithMeta;
 
 use crate::{
-    disconnection_effectful::P2pDisconnectionEffectfulAction, P2pNetworkSchedulerAction,
-    P2pPeerAction, P2pPeerStatus, P2pState,
+    disconnection_effectful::P2pDisconnectionEffectfulAction, trust::P2pTrustAction,
+    P2pNetworkSchedulerAction, P2pPeerAction, P2pPeerStatus, P2pState,
 };
 
 use super::{P2pDisconnectedState, P2pDisconnectionAction, P2pDisconnectionReason};
@@ -70,6 +70,10 @@ impl P2pDisconnectedState {
                         .collect::<Vec<_>>();
 
                     let dispatcher = state_context.into_dispatcher();
+                    dispatcher.push(P2pTrustAction::Penalize {
+                        peer_id,
+                        reason: reason.clone(),
+                    });
                     for addr in connections {
                         dispatcher.push(P2pNetworkSchedulerAction::Disconnect {
                             addr,
@@ -82,6 +86,7 @@ impl P2pDisconnectedState {
                 }
 
                 let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pTrustAction::Penalize { peer_id, reason });
                 dispatcher.push(P2pDisconnectionEffectfulAction::Init { peer_id });
                 Ok(())
             }
@@ -1,12 +1,19 @@
+use std::net::IpAddr;
+
 use openmina_core::{bug_condition, Substate};
 use redux::ActionWithMeta;
 
 use crate::{
+    address_book::P2pAddressBookAction,
     connection::outgoing::P2pConnectionOutgoingInitOpts,
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
-    token::{BroadcastAlgorithm, DiscoveryAlgorithm, IdentifyAlgorithm, RpcAlgorithm, StreamKind},
-    P2pNetworkConnectionMuxState, P2pNetworkKadRequestAction, P2pNetworkKadState,
-    P2pNetworkKademliaAction, P2pNetworkYamuxAction, P2pState, YamuxStreamKind,
+    nat::P2pNatAction,
+    token::{
+        BroadcastAlgorithm, DiscoveryAlgorithm, IdentifyAlgorithm, RpcAlgorithm, StreamKind,
+        WebrtcSignalAlgorithm,
+    },
+    P2pNetworkKadRequestAction, P2pNetworkKadState, P2pNetworkKademliaAction,
+    P2pNetworkYamuxAction, P2pState, YamuxStreamKind,
 };
 
 use super::P2pIdentifyAction;
@@ -39,9 +46,8 @@ impl P2pState {
                             .map(|mux| (mux, conn.incoming))
                             .ok_or_else(|| format!("multiplexing is not ready for {addr}"))
                     })
-                    .and_then(|(P2pNetworkConnectionMuxState::Yamux(yamux), incoming)| {
-                        yamux
-                            .next_stream_id(crate::YamuxStreamKind::Identify, incoming)
+                    .and_then(|(mux, incoming)| {
+                        mux.next_stream_id(crate::YamuxStreamKind::Identify, incoming)
                             .ok_or_else(|| format!("cannot get next stream for {addr}"))
                     })?;
 
@@ -73,6 +79,28 @@ impl P2pState {
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
 
+                // the address the peer sees us at, to detect our public address
+                let observed_ip =
+                    info.observed_addr
+                        .as_ref()
+                        .and_then(|addr| match addr.iter().next()? {
+                            multiaddr::Protocol::Ip4(ip) => Some(IpAddr::from(ip)),
+                            multiaddr::Protocol::Ip6(ip) => Some(ip.into()),
+                            _ => None,
+                        });
+                if let Some(observed_ip) = observed_ip {
+                    dispatcher.push(P2pNatAction::ObservedAddr {
+                        peer_id,
+                        observer_ip: addr.sock_addr.ip(),
+                        addr: observed_ip,
+                    });
+                }
+
+                dispatcher.push(P2pAddressBookAction::PeerIdentified {
+                    peer_id,
+                    listen_addrs: info.listen_addrs.clone(),
+                    protocols: info.protocols.clone(),
+                });
                 dispatcher.push(P2pNetworkKademliaAction::UpdateRoutingTable {
                     peer_id,
                     addrs: info.listen_addrs,
@@ -109,6 +137,20 @@ impl P2pState {
                     });
                 }
 
+                // signaling channels become ready once this stream is negotiated
+                let stream_kind =
+                    StreamKind::WebrtcSignal(WebrtcSignalAlgorithm::WebrtcSignal1_0_0);
+                let p2p_state: &P2pState = state.substate()?;
+                if p2p_state.config.libp2p_webrtc_signal_enabled()
+                    && info.protocols.contains(&stream_kind)
+                {
+                    dispatcher.push(P2pNetworkYamuxAction::OpenStream {
+                        addr,
+                        stream_id: YamuxStreamKind::WebrtcSignal.stream_id(addr.incoming),
+                        stream_kind,
+                    });
+                }
+
                 let kad_state: Option<&P2pNetworkKadState> = state.substate().ok();
                 let protocol = StreamKind::Discovery(DiscoveryAlgorithm::Kademlia1_0_0);
                 if kad_state.is_some_and(|state| state.request(&peer_id).is_some())
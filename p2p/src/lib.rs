@@ -8,6 +8,16 @@ pub mod connection;
 pub mod disconnection;
 pub mod disconnection_effectful;
 pub mod identity;
+pub mod trust;
+pub mod trust_effectful;
+pub mod address_book;
+pub mod address_book_effectful;
+pub mod bandwidth;
+pub mod nat;
+pub mod nat_effectful;
+use address_book::P2pAddressBookAction;
+use address_book_effectful::P2pAddressBookEffectfulAction;
+use bandwidth::P2pBandwidthAction;
 use bootstrap::P2pNetworkKadBootstrapState;
 use channels::{
     best_tip::P2pChannelsBestTipAction,
@@ -20,7 +30,7 @@ use channels::{
     snark_job_commitment::P2pChannelsSnarkJobCommitmentAction,
     streaming_rpc::P2pChannelsStreamingRpcAction,
     transaction::P2pChannelsTransactionAction,
-    P2pChannelsEffectfulAction,
+    P2pChannelsEffectfulAction, P2pChannelsMessageReceivedAction,
 };
 use connection::{
     incoming::P2pConnectionIncomingAction,
@@ -31,11 +41,15 @@ use disconnection::P2pDisconnectionAction;
 use disconnection_effectful::P2pDisconnectionEffectfulAction;
 use identify::P2pIdentifyAction;
 pub use identity::PeerId;
+use nat::P2pNatAction;
+use nat_effectful::P2pNatEffectfulAction;
 use network::identify::{
     stream_effectful::P2pNetworkIdentifyStreamEffectfulAction, P2pNetworkIdentifyState,
     P2pNetworkIdentifyStreamAction,
 };
 use openmina_core::SubstateAccess;
+use trust::P2pTrustAction;
+use trust_effectful::P2pTrustEffectfulAction;
 
 pub mod webrtc;
 
@@ -50,6 +64,9 @@ pub use peer::*;
 mod p2p_config;
 pub use p2p_config::*;
 
+mod p2p_access;
+pub use p2p_access::*;
+
 mod p2p_event;
 pub use p2p_event::*;
 
@@ -119,6 +136,7 @@ pub trait P2pActionTrait<State>:
     + From<P2pNetworkKadBootstrapAction>
     + From<connection::outgoing::P2pConnectionOutgoingAction>
     + From<P2pNetworkYamuxAction>
+    + From<P2pNetworkQuicAction>
     + From<peer::P2pPeerAction>
     + From<P2pNetworkKademliaAction>
     + From<P2pNetworkSchedulerAction>
@@ -143,11 +161,21 @@ pub trait P2pActionTrait<State>:
     + From<P2pChannelsBestTipAction>
     + From<P2pChannelsSnarkJobCommitmentAction>
     + From<P2pChannelsStreamingRpcAction>
+    + From<P2pChannelsMessageReceivedAction>
     + From<P2pConnectionIncomingEffectfulAction>
     + From<P2pConnectionOutgoingEffectfulAction>
     + From<P2pDisconnectionEffectfulAction>
     + From<P2pNetworkKadEffectfulAction>
     + From<P2pChannelsEffectfulAction>
+    + From<P2pTrustAction>
+    + From<P2pTrustEffectfulAction>
+    + From<P2pAddressBookAction>
+    + From<P2pAddressBookEffectfulAction>
+    + From<P2pBandwidthAction>
+    + From<P2pNatAction>
+    + From<P2pNatEffectfulAction>
+    + From<P2pNetworkAutonatAction>
+    + From<P2pNetworkWebrtcSignalAction>
 {
 }
 
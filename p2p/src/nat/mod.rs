@@ -0,0 +1,7 @@
+mod p2p_nat_state;
+pub use p2p_nat_state::*;
+
+mod p2p_nat_actions;
+pub use p2p_nat_actions::*;
+
+mod p2p_nat_reducer;
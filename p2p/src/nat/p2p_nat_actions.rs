@@ -0,0 +1,97 @@
+use std::net::IpAddr;
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{token, P2pState, PeerId};
+
+use super::{is_global_ip, P2pNatPortMapping, P2pNatProbeResult, NAT_PROBES_MAX};
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(
+    level = debug,
+    fields(display(peer_id), display(observer_ip), display(addr), debug(mapping), error)
+)]
+pub enum P2pNatAction {
+    /// Request the gateway to forward the libp2p port to us, or renew the
+    /// existing mapping.
+    PortMappingInit,
+    #[action_event(level = info)]
+    PortMappingSuccess { mapping: P2pNatPortMapping },
+    #[action_event(level = warn)]
+    PortMappingError { error: String },
+    /// The peer, connected from the `observer_ip`, reported the address it
+    /// sees us at.
+    ObservedAddr {
+        peer_id: PeerId,
+        observer_ip: IpAddr,
+        addr: IpAddr,
+    },
+    /// Ask the peer to dial us back, to find out if we are reachable.
+    ProbeInit { peer_id: PeerId },
+    ProbeResult {
+        peer_id: PeerId,
+        result: P2pNatProbeResult,
+    },
+    /// Drop timed out probes, outdated probe results and addresses observed
+    /// by peers that are gone.
+    Prune,
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNatAction {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
+        let nat = &state.nat;
+        match self {
+            P2pNatAction::PortMappingInit => {
+                state.config.nat_port_mapping
+                    && state.config.libp2p_port.is_some()
+                    && !state.config.access.isolate
+                    && nat.port_mapping.should_request(time)
+            }
+            P2pNatAction::PortMappingSuccess { .. } | P2pNatAction::PortMappingError { .. } => {
+                matches!(
+                    nat.port_mapping,
+                    super::P2pNatPortMappingState::Pending { .. }
+                )
+            }
+            P2pNatAction::ObservedAddr {
+                peer_id,
+                observer_ip,
+                addr,
+            } => {
+                is_global_ip(*addr)
+                    && state.peers.contains_key(peer_id)
+                    && nat.is_observed_addr_new(peer_id, *observer_ip, *addr)
+            }
+            P2pNatAction::ProbeInit { peer_id } => {
+                let protocol = token::StreamKind::Autonat(token::AutonatAlgorithm::Autonat1_0_0);
+                !state.config.access.isolate
+                    && nat.probes.len() < NAT_PROBES_MAX
+                    && !nat.probes.contains_key(peer_id)
+                    && !nat.candidate_addrs(&state.config).is_empty()
+                    && state.peers.get(peer_id).is_some_and(|peer| {
+                        peer.is_libp2p
+                            && peer.status.as_ready().is_some()
+                            && peer
+                                .identify
+                                .as_ref()
+                                .is_some_and(|info| info.protocols.contains(&protocol))
+                    })
+            }
+            P2pNatAction::ProbeResult { peer_id, .. } => nat
+                .probes
+                .get(peer_id)
+                .is_some_and(|probe| probe.result.is_none()),
+            P2pNatAction::Prune => {
+                nat.probes
+                    .keys()
+                    .any(|peer_id| nat.is_probe_expired(peer_id, time))
+                    || nat
+                        .observed_addrs
+                        .values()
+                        .flat_map(|peers| peers.keys())
+                        .any(|peer_id| !state.peers.contains_key(peer_id))
+            }
+        }
+    }
+}
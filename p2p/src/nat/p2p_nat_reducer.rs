@@ -0,0 +1,147 @@
+use multiaddr::{Multiaddr, Protocol};
+use openmina_core::{bug_condition, Substate};
+use redux::ActionWithMeta;
+
+use crate::{
+    nat_effectful::P2pNatEffectfulAction, token, P2pNetworkYamuxAction, P2pState, YamuxStreamKind,
+};
+
+use super::{
+    P2pNatAction, P2pNatPortMappingState, P2pNatProbe, P2pNatState, NAT_PORT_MAPPING_LIFETIME,
+};
+
+impl P2pNatState {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: ActionWithMeta<P2pNatAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+        let time = meta.time();
+
+        match action {
+            P2pNatAction::PortMappingInit => {
+                let Some(port) = p2p_state.config.libp2p_port else {
+                    bug_condition!("port mapping without libp2p port");
+                    return Ok(());
+                };
+                p2p_state.nat.port_mapping = P2pNatPortMappingState::Pending { time };
+
+                let quic = p2p_state.config.libp2p_quic;
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNatEffectfulAction::PortMappingRequest {
+                    port,
+                    quic,
+                    lifetime: NAT_PORT_MAPPING_LIFETIME,
+                });
+                Ok(())
+            }
+            P2pNatAction::PortMappingSuccess { mapping } => {
+                p2p_state.nat.port_mapping = P2pNatPortMappingState::Mapped { time, mapping };
+                Self::update_advertised_addrs(p2p_state);
+                Ok(())
+            }
+            P2pNatAction::PortMappingError { error } => {
+                p2p_state.nat.port_mapping = P2pNatPortMappingState::Error { time, error };
+                Ok(())
+            }
+            P2pNatAction::ObservedAddr {
+                peer_id,
+                observer_ip,
+                addr,
+            } => {
+                p2p_state.nat.add_observed_addr(peer_id, observer_ip, addr);
+                Self::update_advertised_addrs(p2p_state);
+                Ok(())
+            }
+            P2pNatAction::ProbeInit { peer_id } => {
+                p2p_state
+                    .nat
+                    .probes
+                    .insert(peer_id, P2pNatProbe { time, result: None });
+
+                let scheduler = &p2p_state.network.scheduler;
+                let Some((addr, stream_id)) =
+                    scheduler.find_peer(&peer_id).and_then(|(addr, conn)| {
+                        let mux = conn.mux.as_ref()?;
+                        let stream_id =
+                            mux.next_stream_id(YamuxStreamKind::Autonat, conn.incoming)?;
+                        Some((*addr, stream_id))
+                    })
+                else {
+                    bug_condition!("no multiplexed connection with {peer_id} for probe");
+                    return Ok(());
+                };
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkYamuxAction::OpenStream {
+                    addr,
+                    stream_id,
+                    stream_kind: token::StreamKind::Autonat(token::AutonatAlgorithm::Autonat1_0_0),
+                });
+                Ok(())
+            }
+            P2pNatAction::ProbeResult { peer_id, result } => {
+                if let Some(probe) = p2p_state.nat.probes.get_mut(&peer_id) {
+                    *probe = P2pNatProbe {
+                        time,
+                        result: Some(result),
+                    };
+                }
+                Self::update_advertised_addrs(p2p_state);
+                Ok(())
+            }
+            P2pNatAction::Prune => {
+                let nat = &mut p2p_state.nat;
+                let expired = nat
+                    .probes
+                    .keys()
+                    .filter(|peer_id| nat.is_probe_expired(peer_id, time))
+                    .copied()
+                    .collect::<Vec<_>>();
+                for peer_id in expired {
+                    nat.probes.remove(&peer_id);
+                }
+
+                let peers = &p2p_state.peers;
+                let gone = nat
+                    .observed_addrs
+                    .values()
+                    .flat_map(|peers| peers.keys())
+                    .filter(|peer_id| !peers.contains_key(peer_id))
+                    .copied()
+                    .collect::<Vec<_>>();
+                for peer_id in gone {
+                    nat.remove_observer(&peer_id);
+                }
+
+                Self::update_advertised_addrs(p2p_state);
+                Ok(())
+            }
+        }
+    }
+
+    /// Keeps our own Kademlia entry in sync with the addresses we advertise
+    /// in identify.
+    fn update_advertised_addrs(p2p_state: &mut P2pState) {
+        let Some(port) = p2p_state.config.libp2p_port else {
+            return;
+        };
+        let mut addrs = p2p_state
+            .nat
+            .external_addrs(&p2p_state.config)
+            .into_iter()
+            .map(|addr| Multiaddr::from(addr.ip()).with(Protocol::Tcp(addr.port())))
+            .collect::<Vec<_>>();
+        if addrs.is_empty() {
+            addrs.push(multiaddr::multiaddr!(Ip4([127, 0, 0, 1]), Tcp((port))));
+        }
+        if let Some(discovery_state) = p2p_state.network.scheduler.discovery_state.as_mut() {
+            discovery_state.routing_table.update_this_entry_addrs(addrs);
+        }
+    }
+}
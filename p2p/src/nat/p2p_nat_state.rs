@@ -0,0 +1,541 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{P2pConfig, PeerId};
+
+/// Lifetime of the port mapping requested from the gateway. The mapping is
+/// renewed when half of it has passed.
+pub const NAT_PORT_MAPPING_LIFETIME: Duration = Duration::from_secs(60 * 60);
+/// Time to wait before retrying failed port mapping.
+pub const NAT_PORT_MAPPING_RETRY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Number of peers from distinct subnets that should report the same
+/// observed address before we consider it confirmed.
+pub const NAT_OBSERVED_ADDR_CONFIRMATIONS: usize = 2;
+/// Maximal number of reachability probes kept at the same time, pending or
+/// finished.
+pub const NAT_PROBES_MAX: usize = 4;
+/// Number of agreeing probe results needed to consider our reachability
+/// known.
+pub const NAT_PROBE_CONFIDENCE: usize = 2;
+/// Probe that isn't answered within this duration is dropped.
+pub const NAT_PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Probe results are dropped after this duration, so that we probe again.
+pub const NAT_PROBE_RESULT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// NAT traversal state: port mapping on the gateway, our addresses as
+/// observed by peers, and our reachability as probed by peers dialing us
+/// back (AutoNAT).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pNatState {
+    pub port_mapping: P2pNatPortMappingState,
+    /// Our IP addresses as reported by peers in identify `observedAddr`,
+    /// with the peers that reported them and the IPs they are connected
+    /// from. Each peer reports a single address.
+    pub observed_addrs: BTreeMap<IpAddr, BTreeMap<PeerId, IpAddr>>,
+    /// Reachability probes, by the peer asked to dial us back.
+    pub probes: BTreeMap<PeerId, P2pNatProbe>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum P2pNatPortMappingState {
+    #[default]
+    Idle,
+    Pending {
+        time: Timestamp,
+    },
+    Mapped {
+        time: Timestamp,
+        mapping: P2pNatPortMapping,
+    },
+    Error {
+        time: Timestamp,
+        error: String,
+    },
+}
+
+/// Port forwarding created on the gateway.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct P2pNatPortMapping {
+    pub protocol: P2pNatPortMappingProtocol,
+    /// Public address of the gateway forwarded to our libp2p TCP port.
+    pub external_addr: SocketAddr,
+    /// Public address of the gateway forwarded to our QUIC (UDP) port, if
+    /// QUIC is enabled and the gateway mapped it.
+    #[serde(default)]
+    pub quic_external_addr: Option<SocketAddr>,
+    /// Lifetime of the mapping granted by the gateway.
+    pub lifetime: Duration,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum P2pNatPortMappingProtocol {
+    #[display(fmt = "NAT-PMP")]
+    NatPmp,
+    #[display(fmt = "PCP")]
+    Pcp,
+    #[display(fmt = "UPnP")]
+    Upnp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pNatProbe {
+    pub time: Timestamp,
+    /// `None` while waiting for the peer to respond.
+    pub result: Option<P2pNatProbeResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum P2pNatProbeResult {
+    /// The peer dialed us back successfully at the address.
+    Reachable(SocketAddr),
+    /// The peer failed to dial us back.
+    Unreachable,
+    /// The peer didn't try to dial us back, so the probe tells nothing
+    /// about our reachability.
+    Refused(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum P2pNatReachability {
+    Unknown,
+    Public,
+    Private,
+}
+
+impl P2pNatPortMappingState {
+    pub fn mapping(&self) -> Option<&P2pNatPortMapping> {
+        match self {
+            Self::Mapped { mapping, .. } => Some(mapping),
+            _ => None,
+        }
+    }
+
+    /// Whether the port mapping should be requested (or renewed) from the
+    /// gateway at the time `now`.
+    pub fn should_request(&self, now: Timestamp) -> bool {
+        match self {
+            Self::Idle => true,
+            Self::Pending { .. } => false,
+            Self::Mapped { time, mapping } => now.checked_sub(*time) >= Some(mapping.lifetime / 2),
+            Self::Error { time, .. } => {
+                now.checked_sub(*time) >= Some(NAT_PORT_MAPPING_RETRY_INTERVAL)
+            }
+        }
+    }
+}
+
+impl P2pNatState {
+    /// Records `addr` reported by the `peer_id`, connected from the
+    /// `observer_ip`, as our address, replacing the one it reported before.
+    pub fn add_observed_addr(&mut self, peer_id: PeerId, observer_ip: IpAddr, addr: IpAddr) {
+        self.remove_observer(&peer_id);
+        self.observed_addrs
+            .entry(addr)
+            .or_default()
+            .insert(peer_id, observer_ip);
+    }
+
+    pub fn remove_observer(&mut self, peer_id: &PeerId) {
+        self.observed_addrs.retain(|_, peers| {
+            peers.remove(peer_id);
+            !peers.is_empty()
+        });
+    }
+
+    /// Observed addresses reported by peers from enough distinct subnets,
+    /// so that a single host (or a few hosts of the same network) can't
+    /// make us advertise an address of their choice.
+    pub fn confirmed_observed_addrs(&self) -> impl '_ + Iterator<Item = IpAddr> {
+        self.observed_addrs
+            .iter()
+            .filter(|(_, peers)| {
+                let subnets = peers.values().map(subnet).collect::<BTreeSet<_>>();
+                subnets.len() >= NAT_OBSERVED_ADDR_CONFIRMATIONS
+            })
+            .map(|(addr, _)| *addr)
+    }
+
+    fn probe_results(&self) -> impl '_ + Iterator<Item = &P2pNatProbeResult> {
+        self.probes
+            .values()
+            .filter_map(|probe| probe.result.as_ref())
+    }
+
+    /// Our reachability derived from the results of the probes.
+    pub fn reachability(&self) -> P2pNatReachability {
+        let (reachable, unreachable) =
+            self.probe_results()
+                .fold((0, 0), |(reachable, unreachable), result| match result {
+                    P2pNatProbeResult::Reachable(_) => (reachable + 1, unreachable),
+                    P2pNatProbeResult::Unreachable => (reachable, unreachable + 1),
+                    P2pNatProbeResult::Refused(_) => (reachable, unreachable),
+                });
+        if reachable >= NAT_PROBE_CONFIDENCE && reachable > unreachable {
+            P2pNatReachability::Public
+        } else if unreachable >= NAT_PROBE_CONFIDENCE && unreachable > reachable {
+            P2pNatReachability::Private
+        } else {
+            P2pNatReachability::Unknown
+        }
+    }
+
+    /// Addresses at which peers dialed us back successfully.
+    pub fn reachable_addrs(&self) -> impl '_ + Iterator<Item = SocketAddr> {
+        self.probe_results().filter_map(|result| match result {
+            P2pNatProbeResult::Reachable(addr) => Some(*addr),
+            _ => None,
+        })
+    }
+
+    /// Addresses that we advertise to peers, in identify and Kademlia.
+    ///
+    /// These are the configured external addresses, the address mapped on
+    /// the gateway, and, unless probes tell that we are behind NAT, our
+    /// confirmed observed addresses at which a peer managed to dial us back.
+    pub fn external_addrs(&self, config: &P2pConfig) -> Vec<SocketAddr> {
+        let mapped_addr = self
+            .port_mapping
+            .mapping()
+            .map(|mapping| mapping.external_addr);
+        self.external_addrs_with(config, mapped_addr)
+    }
+
+    /// Same as [`Self::external_addrs`], but for the QUIC listener, which
+    /// uses the same local port, but may be mapped to another external one.
+    pub fn external_quic_addrs(&self, config: &P2pConfig) -> Vec<SocketAddr> {
+        if !config.libp2p_quic {
+            return Vec::new();
+        }
+        let mapped_addr = self
+            .port_mapping
+            .mapping()
+            .and_then(|mapping| mapping.quic_external_addr);
+        self.external_addrs_with(config, mapped_addr)
+    }
+
+    fn external_addrs_with(
+        &self,
+        config: &P2pConfig,
+        mapped_addr: Option<SocketAddr>,
+    ) -> Vec<SocketAddr> {
+        let Some(port) = config.libp2p_port else {
+            return Vec::new();
+        };
+        let mut addrs = Vec::new();
+        let mut add = |addr| {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        };
+        config
+            .external_addrs
+            .iter()
+            .for_each(|ip| add(SocketAddr::new(*ip, port)));
+        if let Some(mapped_addr) = mapped_addr {
+            add(mapped_addr);
+        }
+        if self.reachability() != P2pNatReachability::Private {
+            self.confirmed_observed_addrs()
+                .filter(|ip| self.reachable_addrs().any(|addr| addr.ip() == *ip))
+                .for_each(|ip| add(SocketAddr::new(ip, port)));
+        }
+        addrs
+    }
+
+    /// Addresses that we ask peers to dial us back at. Unlike
+    /// [`Self::external_addrs`], includes all observed addresses.
+    pub fn candidate_addrs(&self, config: &P2pConfig) -> Vec<SocketAddr> {
+        let Some(port) = config.libp2p_port else {
+            return Vec::new();
+        };
+        let mut addrs = self.external_addrs(config);
+        for ip in self.observed_addrs.keys() {
+            let addr = SocketAddr::new(*ip, port);
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+        addrs
+    }
+
+    /// Whether the `addr` reported by the `peer_id` changes what we know
+    /// about our observed addresses.
+    pub fn is_observed_addr_new(
+        &self,
+        peer_id: &PeerId,
+        observer_ip: IpAddr,
+        addr: IpAddr,
+    ) -> bool {
+        self.observed_addrs
+            .get(&addr)
+            .and_then(|peers| peers.get(peer_id))
+            != Some(&observer_ip)
+    }
+
+    /// Whether the probe with the `peer_id` should be dropped at the time
+    /// `now`, either because it is timed out or its result is outdated.
+    pub fn is_probe_expired(&self, peer_id: &PeerId, now: Timestamp) -> bool {
+        self.probes.get(peer_id).is_some_and(|probe| {
+            let ttl = match probe.result {
+                None => NAT_PROBE_TIMEOUT,
+                Some(_) => NAT_PROBE_RESULT_TTL,
+            };
+            now.checked_sub(probe.time) >= Some(ttl)
+        })
+    }
+}
+
+/// Whether the `ip` is globally routable, i.e. can be our public address.
+/// Mirrors the unstable `IpAddr::is_global`.
+pub fn is_global_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // shared address space (carrier-grade NAT)
+                || (a == 100 && (b & 0xc0) == 64)
+                // IETF protocol assignments
+                || (a == 192 && b == 0 && c == 0)
+                // benchmarking
+                || (a == 198 && (b & 0xfe) == 18)
+                // reserved, including multicast
+                || a >= 224
+                || a == 0)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_global_ip(ip.into());
+            }
+            let segments = ip.segments();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // unique local
+                || (segments[0] & 0xfe00) == 0xfc00
+                // link local
+                || (segments[0] & 0xffc0) == 0xfe80
+                // documentation
+                || (segments[0] == 0x2001 && segments[1] == 0xdb8)
+                // discard-only
+                || (segments[0] == 0x100 && segments[1..4] == [0, 0, 0]))
+        }
+    }
+}
+
+/// Subnet of the `ip`, /24 for IPv4 and /48 for IPv6, roughly what a
+/// single party can easily get addresses from.
+fn subnet(ip: &IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            Ipv4Addr::new(a, b, c, 0).into()
+        }
+        IpAddr::V6(ip) => {
+            let [a, b, c, ..] = ip.segments();
+            Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0).into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use crate::identity::SecretKey;
+
+    use super::*;
+
+    fn peer(n: u8) -> PeerId {
+        SecretKey::from_bytes([n; 32]).public_key().peer_id()
+    }
+
+    fn ip(n: u8) -> IpAddr {
+        Ipv4Addr::new(93, 184, 216, n).into()
+    }
+
+    /// Address of an observer, in a subnet of its own for each `n`.
+    fn observer(n: u8) -> IpAddr {
+        Ipv4Addr::new(1, 1, n, 1).into()
+    }
+
+    fn config() -> P2pConfig {
+        P2pConfig {
+            libp2p_port: Some(8302),
+            libp2p_quic: false,
+            listen_port: None,
+            identity_pub_key: SecretKey::from_bytes([0; 32]).public_key(),
+            initial_peers: Vec::new(),
+            external_addrs: Vec::new(),
+            nat_port_mapping: false,
+            enabled_channels: Default::default(),
+            timeouts: Default::default(),
+            limits: Default::default(),
+            peer_discovery: false,
+            meshsub: Default::default(),
+            initial_trust: Default::default(),
+            initial_address_book: Default::default(),
+            access: Default::default(),
+        }
+    }
+
+    fn probe(result: P2pNatProbeResult) -> P2pNatProbe {
+        P2pNatProbe {
+            time: Timestamp::ZERO,
+            result: Some(result),
+        }
+    }
+
+    #[test]
+    fn observed_addr_needs_confirmations() {
+        let config = config();
+        let addr = SocketAddr::new(ip(1), 8302);
+        let mut state = P2pNatState::default();
+        state.add_observed_addr(peer(1), observer(1), ip(1));
+        assert_eq!(state.confirmed_observed_addrs().count(), 0);
+        assert_eq!(state.candidate_addrs(&config), vec![addr]);
+
+        // peers from the same subnet count once
+        state.add_observed_addr(peer(2), Ipv4Addr::new(1, 1, 1, 2).into(), ip(1));
+        assert_eq!(state.confirmed_observed_addrs().count(), 0);
+
+        state.add_observed_addr(peer(3), observer(3), ip(1));
+        assert_eq!(
+            state.confirmed_observed_addrs().collect::<Vec<_>>(),
+            vec![ip(1)]
+        );
+        // not advertised until a peer dials us back at it
+        assert!(state.external_addrs(&config).is_empty());
+        state
+            .probes
+            .insert(peer(4), probe(P2pNatProbeResult::Reachable(addr)));
+        assert_eq!(state.external_addrs(&config), vec![addr]);
+
+        // a peer reports a single address
+        state.add_observed_addr(peer(3), observer(3), ip(2));
+        assert!(state.external_addrs(&config).is_empty());
+        assert_eq!(state.observed_addrs.len(), 2);
+        assert!(!state.is_observed_addr_new(&peer(3), observer(3), ip(2)));
+        assert!(state.is_observed_addr_new(&peer(3), observer(4), ip(2)));
+
+        for n in 1..=3 {
+            state.remove_observer(&peer(n));
+        }
+        assert!(state.observed_addrs.is_empty());
+    }
+
+    #[test]
+    fn reachability_from_probes() {
+        let config = config();
+        let addr = SocketAddr::new(ip(1), 8302);
+        let mut state = P2pNatState::default();
+        state.add_observed_addr(peer(1), observer(1), ip(1));
+        state.add_observed_addr(peer(2), observer(2), ip(1));
+        assert_eq!(state.reachability(), P2pNatReachability::Unknown);
+
+        state.probes.insert(
+            peer(1),
+            probe(P2pNatProbeResult::Refused("no addresses".to_owned())),
+        );
+        state
+            .probes
+            .insert(peer(2), probe(P2pNatProbeResult::Unreachable));
+        assert_eq!(state.reachability(), P2pNatReachability::Unknown);
+
+        state
+            .probes
+            .insert(peer(3), probe(P2pNatProbeResult::Unreachable));
+        assert_eq!(state.reachability(), P2pNatReachability::Private);
+        assert_eq!(state.candidate_addrs(&config), vec![addr]);
+
+        state
+            .probes
+            .insert(peer(4), probe(P2pNatProbeResult::Reachable(addr)));
+        // observed addresses are not advertised when we are behind NAT
+        assert!(state.external_addrs(&config).is_empty());
+
+        state
+            .probes
+            .insert(peer(5), probe(P2pNatProbeResult::Reachable(addr)));
+        state
+            .probes
+            .insert(peer(6), probe(P2pNatProbeResult::Reachable(addr)));
+        assert_eq!(state.reachability(), P2pNatReachability::Public);
+        assert_eq!(state.external_addrs(&config), vec![addr]);
+    }
+
+    #[test]
+    fn only_global_ips_are_observed() {
+        for ip in [
+            "10.0.0.1",
+            "100.64.0.1",
+            "127.0.0.1",
+            "169.254.1.1",
+            "192.168.1.1",
+            "203.0.113.1",
+            "224.0.0.1",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "2001:db8::1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(!is_global_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["93.184.216.34", "2606:2800:220:1::1", "::ffff:1.1.1.1"] {
+            assert!(is_global_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn port_mapping_is_renewed() {
+        let mapping = P2pNatPortMapping {
+            protocol: P2pNatPortMappingProtocol::NatPmp,
+            external_addr: SocketAddr::new(ip(1), 40000),
+            quic_external_addr: Some(SocketAddr::new(ip(1), 40001)),
+            lifetime: Duration::from_secs(100),
+        };
+        let mapped = P2pNatPortMappingState::Mapped {
+            time: Timestamp::ZERO,
+            mapping: mapping.clone(),
+        };
+        assert!(!mapped.should_request(Timestamp::ZERO + Duration::from_secs(49)));
+        assert!(mapped.should_request(Timestamp::ZERO + Duration::from_secs(50)));
+
+        let mut state = P2pNatState {
+            port_mapping: mapped,
+            ..Default::default()
+        };
+        let mut config = config();
+        config.external_addrs = vec![ip(2)];
+        assert_eq!(
+            state.external_addrs(&config),
+            vec![SocketAddr::new(ip(2), 8302), mapping.external_addr]
+        );
+        assert!(state.external_quic_addrs(&config).is_empty());
+        config.libp2p_quic = true;
+        assert_eq!(
+            state.external_quic_addrs(&config),
+            vec![SocketAddr::new(ip(2), 8302), SocketAddr::new(ip(1), 40001)]
+        );
+
+        state.port_mapping = P2pNatPortMappingState::Error {
+            time: Timestamp::ZERO,
+            error: "no gateway".to_owned(),
+        };
+        assert!(!state.port_mapping.should_request(Timestamp::ZERO));
+        assert!(state
+            .port_mapping
+            .should_request(Timestamp::ZERO + NAT_PORT_MAPPING_RETRY_INTERVAL));
+    }
+}
@@ -0,0 +1,36 @@
+use std::{net::SocketAddr, time::Duration};
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{ConnectionAddr, P2pState, PeerId, StreamId};
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(
+    level = debug,
+    fields(port, quic, display(addr), display(peer_id), stream_id, debug(addrs))
+)]
+pub enum P2pNatEffectfulAction {
+    /// Ask the gateway to forward the TCP `port` to us, and the UDP one if
+    /// `quic` is set.
+    PortMappingRequest {
+        port: u16,
+        quic: bool,
+        lifetime: Duration,
+    },
+    /// Dial the peer back at the `addrs`, as it asked us with AutoNAT.
+    DialBack {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        addrs: Vec<SocketAddr>,
+        /// Private network key of the chain, needed to reach the peer.
+        pnet_key: [u8; 32],
+    },
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNatEffectfulAction {
+    fn is_enabled(&self, _state: &P2pState, _time: redux::Timestamp) -> bool {
+        true
+    }
+}
@@ -0,0 +1,34 @@
+use redux::ActionMeta;
+
+use super::{P2pNatEffectfulAction, P2pNatService};
+
+impl P2pNatEffectfulAction {
+    pub fn effects<Store, S>(self, _: &ActionMeta, store: &mut Store)
+    where
+        Store: crate::P2pStore<S>,
+        Store::Service: P2pNatService,
+    {
+        match self {
+            P2pNatEffectfulAction::PortMappingRequest {
+                port,
+                quic,
+                lifetime,
+            } => {
+                store
+                    .service()
+                    .nat_port_mapping_request(port, quic, lifetime);
+            }
+            P2pNatEffectfulAction::DialBack {
+                addr,
+                peer_id,
+                stream_id,
+                addrs,
+                pnet_key,
+            } => {
+                store
+                    .service()
+                    .nat_dial_back(addr, peer_id, stream_id, addrs, pnet_key);
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::{net::SocketAddr, time::Duration};
+
+use crate::{ConnectionAddr, PeerId, StreamId};
+
+pub trait P2pNatService: redux::Service {
+    /// Ask the gateway to forward the TCP `port` to us for the `lifetime`,
+    /// and the UDP one for QUIC if `quic` is set. The result is reported
+    /// with [`crate::P2pNatEvent::PortMapped`].
+    fn nat_port_mapping_request(&mut self, port: u16, quic: bool, lifetime: Duration);
+
+    /// Try to connect to the peer at `addrs`, one by one, checking that it
+    /// is `peer_id` that answers. The address at which we reached it is
+    /// reported with [`crate::P2pNatEvent::DialBack`].
+    fn nat_dial_back(
+        &mut self,
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        addrs: Vec<SocketAddr>,
+        pnet_key: [u8; 32],
+    );
+}
@@ -0,0 +1,15 @@
+mod pb {
+    include!(concat!(env!("OUT_DIR"), "/autonat.rs"));
+}
+
+mod p2p_network_autonat_protocol;
+pub use self::p2p_network_autonat_protocol::*;
+
+mod p2p_network_autonat_actions;
+pub use self::p2p_network_autonat_actions::*;
+
+mod p2p_network_autonat_state;
+pub use self::p2p_network_autonat_state::*;
+
+#[cfg(feature = "p2p-libp2p")]
+mod p2p_network_autonat_reducer;
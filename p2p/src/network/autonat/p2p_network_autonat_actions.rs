@@ -0,0 +1,103 @@
+use std::net::SocketAddr;
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{ConnectionAddr, Data, P2pState, PeerId, StreamId};
+
+use super::P2pNetworkAutonatStreamStatus;
+
+/// AutoNAT stream related actions.
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(display(addr), display(peer_id), stream_id, incoming, debug(result)))]
+pub enum P2pNetworkAutonatAction {
+    /// Creates a new stream state.
+    New {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        incoming: bool,
+    },
+    /// Handles incoming data from the stream.
+    #[action_event(level = trace)]
+    IncomingData {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        data: Data,
+    },
+    /// The service finished dialing back the remote peer.
+    DialBackResult {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        result: Result<SocketAddr, String>,
+    },
+    /// Start closing the stream (send FIN).
+    Close {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+    },
+    /// Remote peer sent FIN to close the stream.
+    RemoteClose {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+    },
+    /// Removes the closed stream from the state.
+    Prune {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+    },
+}
+
+impl P2pNetworkAutonatAction {
+    pub fn peer_id(&self) -> &PeerId {
+        match self {
+            Self::New { peer_id, .. }
+            | Self::IncomingData { peer_id, .. }
+            | Self::DialBackResult { peer_id, .. }
+            | Self::Close { peer_id, .. }
+            | Self::RemoteClose { peer_id, .. }
+            | Self::Prune { peer_id, .. } => peer_id,
+        }
+    }
+
+    pub fn stream_id(&self) -> &StreamId {
+        match self {
+            Self::New { stream_id, .. }
+            | Self::IncomingData { stream_id, .. }
+            | Self::DialBackResult { stream_id, .. }
+            | Self::Close { stream_id, .. }
+            | Self::RemoteClose { stream_id, .. }
+            | Self::Prune { stream_id, .. } => stream_id,
+        }
+    }
+}
+
+impl From<P2pNetworkAutonatAction> for crate::P2pAction {
+    fn from(a: P2pNetworkAutonatAction) -> Self {
+        Self::Network(a.into())
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNetworkAutonatAction {
+    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+        let stream = state
+            .network
+            .scheduler
+            .autonat_state
+            .find_stream(self.peer_id(), self.stream_id());
+        match self {
+            P2pNetworkAutonatAction::New { .. } => stream.is_none(),
+            P2pNetworkAutonatAction::DialBackResult { .. } => stream
+                .is_some_and(|stream| stream.status == P2pNetworkAutonatStreamStatus::DialBack),
+            P2pNetworkAutonatAction::IncomingData { .. }
+            | P2pNetworkAutonatAction::Close { .. }
+            | P2pNetworkAutonatAction::RemoteClose { .. }
+            | P2pNetworkAutonatAction::Prune { .. } => stream.is_some(),
+        }
+    }
+}
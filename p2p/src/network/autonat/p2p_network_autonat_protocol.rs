@@ -0,0 +1,228 @@
+use std::{borrow::Cow, net::SocketAddr};
+
+use multiaddr::{Multiaddr, Protocol};
+use serde::{Deserialize, Serialize};
+
+use crate::PeerId;
+
+use super::pb::{self, message};
+
+/// AutoNAT v1 message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum P2pNetworkAutonatMessage {
+    /// Request to dial the sender back using one of the addresses.
+    Dial {
+        peer_id: PeerId,
+        addrs: Vec<Multiaddr>,
+    },
+    DialResponse {
+        status: P2pNetworkAutonatResponseStatus,
+        status_text: Option<String>,
+        addr: Option<Multiaddr>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum P2pNetworkAutonatResponseStatus {
+    Ok,
+    DialError,
+    DialRefused,
+    BadRequest,
+    InternalError,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, thiserror::Error)]
+pub enum P2pNetworkAutonatFromMessageError {
+    #[error("missing field: {0}")]
+    MissingField(String),
+    #[error("error parsing peer id: {0}")]
+    PeerId(String),
+    #[error("error parsing address: {0}")]
+    Multiaddr(String),
+}
+
+impl P2pNetworkAutonatMessage {
+    pub fn dial_response(
+        status: P2pNetworkAutonatResponseStatus,
+        status_text: impl Into<String>,
+        addr: Option<SocketAddr>,
+    ) -> Self {
+        P2pNetworkAutonatMessage::DialResponse {
+            status,
+            status_text: Some(status_text.into()),
+            addr: addr.map(tcp_multiaddr),
+        }
+    }
+}
+
+/// Converts socket address into `/ipX/<ip>/tcp/<port>` multiaddr.
+pub fn tcp_multiaddr(addr: SocketAddr) -> Multiaddr {
+    Multiaddr::from(addr.ip()).with(Protocol::Tcp(addr.port()))
+}
+
+/// Converts `/ipX/<ip>/tcp/<port>` multiaddr into socket address, other
+/// addresses are not supported.
+pub fn tcp_socket_addr(addr: &Multiaddr) -> Option<SocketAddr> {
+    let mut iter = addr.iter();
+    let ip = match iter.next()? {
+        Protocol::Ip4(ip) => ip.into(),
+        Protocol::Ip6(ip) => ip.into(),
+        _ => return None,
+    };
+    let Protocol::Tcp(port) = iter.next()? else {
+        return None;
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+impl From<message::ResponseStatus> for P2pNetworkAutonatResponseStatus {
+    fn from(value: message::ResponseStatus) -> Self {
+        match value {
+            message::ResponseStatus::Ok => Self::Ok,
+            message::ResponseStatus::EDialError => Self::DialError,
+            message::ResponseStatus::EDialRefused => Self::DialRefused,
+            message::ResponseStatus::EBadRequest => Self::BadRequest,
+            message::ResponseStatus::EInternalError => Self::InternalError,
+        }
+    }
+}
+
+impl From<P2pNetworkAutonatResponseStatus> for message::ResponseStatus {
+    fn from(value: P2pNetworkAutonatResponseStatus) -> Self {
+        match value {
+            P2pNetworkAutonatResponseStatus::Ok => Self::Ok,
+            P2pNetworkAutonatResponseStatus::DialError => Self::EDialError,
+            P2pNetworkAutonatResponseStatus::DialRefused => Self::EDialRefused,
+            P2pNetworkAutonatResponseStatus::BadRequest => Self::EBadRequest,
+            P2pNetworkAutonatResponseStatus::InternalError => Self::EInternalError,
+        }
+    }
+}
+
+impl TryFrom<pb::Message> for P2pNetworkAutonatMessage {
+    type Error = P2pNetworkAutonatFromMessageError;
+
+    fn try_from(value: pb::Message) -> Result<Self, Self::Error> {
+        match value.r#type() {
+            message::MessageType::Dial => {
+                let peer = value.dial.and_then(|dial| dial.peer).ok_or_else(|| {
+                    P2pNetworkAutonatFromMessageError::MissingField("peer".into())
+                })?;
+                let id = peer
+                    .id
+                    .ok_or_else(|| P2pNetworkAutonatFromMessageError::MissingField("id".into()))?;
+                let peer_id = PeerId::try_from(Cow::from(id))
+                    .map_err(|err| P2pNetworkAutonatFromMessageError::PeerId(err.to_string()))?;
+                // addresses we can't parse are of no use to us, but the rest still are
+                let addrs = peer
+                    .addrs
+                    .into_iter()
+                    .filter_map(|addr| Multiaddr::try_from(addr).ok())
+                    .collect();
+                Ok(P2pNetworkAutonatMessage::Dial { peer_id, addrs })
+            }
+            message::MessageType::DialResponse => {
+                let response = value.dial_response.ok_or_else(|| {
+                    P2pNetworkAutonatFromMessageError::MissingField("dialResponse".into())
+                })?;
+                let status = response.status().into();
+                let addr = response
+                    .addr
+                    .map(Multiaddr::try_from)
+                    .transpose()
+                    .map_err(|err| P2pNetworkAutonatFromMessageError::Multiaddr(err.to_string()))?;
+                Ok(P2pNetworkAutonatMessage::DialResponse {
+                    status,
+                    status_text: response.status_text,
+                    addr,
+                })
+            }
+        }
+    }
+}
+
+impl TryFrom<&P2pNetworkAutonatMessage> for pb::Message {
+    type Error = libp2p_identity::DecodingError;
+
+    fn try_from(value: &P2pNetworkAutonatMessage) -> Result<Self, Self::Error> {
+        Ok(match value {
+            P2pNetworkAutonatMessage::Dial { peer_id, addrs } => pb::Message {
+                r#type: Some(message::MessageType::Dial.into()),
+                dial: Some(message::Dial {
+                    peer: Some(message::PeerInfo {
+                        id: Some(Cow::<[u8]>::try_from(peer_id)?.into_owned()),
+                        addrs: addrs.iter().map(|addr| addr.to_vec()).collect(),
+                    }),
+                }),
+                dial_response: None,
+            },
+            P2pNetworkAutonatMessage::DialResponse {
+                status,
+                status_text,
+                addr,
+            } => pb::Message {
+                r#type: Some(message::MessageType::DialResponse.into()),
+                dial: None,
+                dial_response: Some(message::DialResponse {
+                    status: Some(message::ResponseStatus::from(*status).into()),
+                    status_text: status_text.clone(),
+                    addr: addr.as_ref().map(|addr| addr.to_vec()),
+                }),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::identity::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn dial_roundtrip() {
+        let peer_id = SecretKey::rand().public_key().peer_id();
+        let addr = "/ip4/1.2.3.4/tcp/8302".parse::<Multiaddr>().unwrap();
+        let message = P2pNetworkAutonatMessage::Dial {
+            peer_id,
+            addrs: vec![addr.clone()],
+        };
+
+        let proto = pb::Message::try_from(&message).unwrap();
+        let P2pNetworkAutonatMessage::Dial {
+            peer_id: decoded_peer_id,
+            addrs,
+        } = P2pNetworkAutonatMessage::try_from(proto).unwrap()
+        else {
+            panic!("unexpected message");
+        };
+        assert_eq!(decoded_peer_id, peer_id);
+        assert_eq!(addrs, vec![addr.clone()]);
+        assert_eq!(
+            tcp_socket_addr(&addr),
+            Some("1.2.3.4:8302".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn dial_response_roundtrip() {
+        let message = P2pNetworkAutonatMessage::dial_response(
+            P2pNetworkAutonatResponseStatus::DialError,
+            "connection refused",
+            None,
+        );
+
+        let proto = pb::Message::try_from(&message).unwrap();
+        let P2pNetworkAutonatMessage::DialResponse {
+            status,
+            status_text,
+            addr,
+        } = P2pNetworkAutonatMessage::try_from(proto).unwrap()
+        else {
+            panic!("unexpected message");
+        };
+        assert_eq!(status, P2pNetworkAutonatResponseStatus::DialError);
+        assert_eq!(status_text.as_deref(), Some("connection refused"));
+        assert_eq!(addr, None);
+    }
+}
@@ -0,0 +1,393 @@
+use std::net::SocketAddr;
+
+use openmina_core::{bug_condition, warn, Substate, SubstateAccess};
+use prost::Message;
+use redux::{ActionWithMeta, Dispatcher};
+
+use crate::{
+    nat::{P2pNatAction, P2pNatProbeResult},
+    nat_effectful::P2pNatEffectfulAction,
+    ConnectionAddr, Data, P2pNetworkSchedulerState, P2pNetworkYamuxAction, P2pState, PeerId,
+    StreamId, YamuxFlags,
+};
+
+use super::{
+    pb, tcp_multiaddr, tcp_socket_addr, P2pNetworkAutonatAction, P2pNetworkAutonatMessage,
+    P2pNetworkAutonatResponseStatus, P2pNetworkAutonatState, P2pNetworkAutonatStreamState,
+    P2pNetworkAutonatStreamStatus, AUTONAT_MESSAGE_SIZE_LIMIT,
+};
+
+/// Limits the number of connections the service makes for a single request.
+const DIAL_BACK_ADDRS_MAX: usize = 4;
+/// Limits the number of dial backs done at the same time, each of them
+/// holds a service thread for up to a few timeouts.
+const DIAL_BACKS_MAX: usize = 4;
+
+impl P2pNetworkAutonatState {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pNetworkSchedulerState>,
+        action: ActionWithMeta<P2pNetworkAutonatAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let autonat_state = &mut state_context.get_substate_mut()?.autonat_state;
+
+        match action {
+            P2pNetworkAutonatAction::New {
+                addr,
+                peer_id,
+                stream_id,
+                incoming,
+            } => {
+                let status = if incoming {
+                    P2pNetworkAutonatStreamStatus::WaitDial
+                } else {
+                    P2pNetworkAutonatStreamStatus::WaitDialResponse
+                };
+                autonat_state.streams.entry(peer_id).or_default().insert(
+                    stream_id,
+                    P2pNetworkAutonatStreamState {
+                        status,
+                        buffer: Vec::new(),
+                    },
+                );
+                if incoming {
+                    return Ok(());
+                }
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+                let message = P2pNetworkAutonatMessage::Dial {
+                    peer_id: p2p_state.my_id(),
+                    addrs: p2p_state
+                        .nat
+                        .candidate_addrs(&p2p_state.config)
+                        .into_iter()
+                        .map(tcp_multiaddr)
+                        .collect(),
+                };
+                Self::send_message(dispatcher, addr, stream_id, &message);
+                Ok(())
+            }
+            P2pNetworkAutonatAction::IncomingData {
+                addr,
+                peer_id,
+                stream_id,
+                data,
+            } => {
+                let scheduler = state_context.get_substate_mut()?;
+                let pnet_key = scheduler.pnet_key;
+                let autonat_state = &mut scheduler.autonat_state;
+                let dial_backs_exhausted = autonat_state.dial_backs_count() >= DIAL_BACKS_MAX
+                    || autonat_state.is_dialing_back(&peer_id);
+                let stream = autonat_state
+                    .find_stream_mut(&peer_id, &stream_id)
+                    .ok_or_else(|| format!("AutoNAT stream not found for {peer_id}/{stream_id}"))?;
+                stream.buffer.extend_from_slice(&data);
+                let message = match take_message(&mut stream.buffer) {
+                    Ok(None) => return Ok(()),
+                    Ok(Some(message)) => {
+                        P2pNetworkAutonatMessage::try_from(message).map_err(|err| err.to_string())
+                    }
+                    Err(err) => Err(err),
+                };
+                match (stream.status.clone(), message) {
+                    (
+                        P2pNetworkAutonatStreamStatus::WaitDial,
+                        Ok(P2pNetworkAutonatMessage::Dial {
+                            peer_id: dial_peer_id,
+                            addrs,
+                        }),
+                    ) => {
+                        // Only dial the IP the peer is connected from, so
+                        // that we can't be used to attack third parties.
+                        let addrs = addrs
+                            .iter()
+                            .filter_map(tcp_socket_addr)
+                            .filter(|dial_addr| {
+                                dial_peer_id == peer_id && dial_addr.ip() == addr.sock_addr.ip()
+                            })
+                            .take(DIAL_BACK_ADDRS_MAX)
+                            .collect::<Vec<_>>();
+
+                        let refusal = if addrs.is_empty() {
+                            Some("no addresses to dial")
+                        } else if dial_backs_exhausted {
+                            Some("too many dial backs")
+                        } else {
+                            None
+                        };
+                        if let Some(refusal) = refusal {
+                            stream.status = P2pNetworkAutonatStreamStatus::Done;
+                            let dispatcher = state_context.into_dispatcher();
+                            let response = P2pNetworkAutonatMessage::dial_response(
+                                P2pNetworkAutonatResponseStatus::DialRefused,
+                                refusal,
+                                None,
+                            );
+                            Self::send_message(dispatcher, addr, stream_id, &response);
+                            dispatcher.push(P2pNetworkAutonatAction::Close {
+                                addr,
+                                peer_id,
+                                stream_id,
+                            });
+                        } else {
+                            stream.status = P2pNetworkAutonatStreamStatus::DialBack;
+                            let dispatcher = state_context.into_dispatcher();
+                            dispatcher.push(P2pNatEffectfulAction::DialBack {
+                                addr,
+                                peer_id,
+                                stream_id,
+                                addrs,
+                                pnet_key,
+                            });
+                        }
+                        Ok(())
+                    }
+                    (
+                        P2pNetworkAutonatStreamStatus::WaitDialResponse,
+                        Ok(P2pNetworkAutonatMessage::DialResponse {
+                            status,
+                            status_text,
+                            addr: dialed_addr,
+                        }),
+                    ) => {
+                        stream.status = P2pNetworkAutonatStreamStatus::Done;
+                        let result = match status {
+                            P2pNetworkAutonatResponseStatus::Ok => dialed_addr
+                                .as_ref()
+                                .and_then(tcp_socket_addr)
+                                .map(P2pNatProbeResult::Reachable)
+                                .unwrap_or_else(|| {
+                                    P2pNatProbeResult::Refused("no address in response".to_owned())
+                                }),
+                            P2pNetworkAutonatResponseStatus::DialError => {
+                                P2pNatProbeResult::Unreachable
+                            }
+                            status => P2pNatProbeResult::Refused(
+                                status_text.unwrap_or_else(|| format!("{status:?}")),
+                            ),
+                        };
+
+                        let dispatcher = state_context.into_dispatcher();
+                        dispatcher.push(P2pNatAction::ProbeResult { peer_id, result });
+                        dispatcher.push(P2pNetworkAutonatAction::Close {
+                            addr,
+                            peer_id,
+                            stream_id,
+                        });
+                        Ok(())
+                    }
+                    (status, message) => {
+                        let error = match message {
+                            Ok(message) => format!("unexpected message {message:?} in {status:?}"),
+                            Err(error) => error,
+                        };
+                        warn!(meta.time(); summary = "error handling AutoNAT stream", peer_id = display(peer_id), error = display(&error));
+
+                        let probing = status == P2pNetworkAutonatStreamStatus::WaitDialResponse;
+                        stream.status = P2pNetworkAutonatStreamStatus::Done;
+                        let dispatcher = state_context.into_dispatcher();
+                        if probing {
+                            dispatcher.push(P2pNatAction::ProbeResult {
+                                peer_id,
+                                result: P2pNatProbeResult::Refused(error),
+                            });
+                        }
+                        dispatcher.push(P2pNetworkAutonatAction::Close {
+                            addr,
+                            peer_id,
+                            stream_id,
+                        });
+                        Ok(())
+                    }
+                }
+            }
+            P2pNetworkAutonatAction::DialBackResult {
+                addr,
+                peer_id,
+                stream_id,
+                result,
+            } => {
+                let stream = autonat_state
+                    .find_stream_mut(&peer_id, &stream_id)
+                    .ok_or_else(|| format!("AutoNAT stream not found for {peer_id}/{stream_id}"))?;
+                stream.status = P2pNetworkAutonatStreamStatus::Done;
+
+                let response = match result {
+                    Ok(dialed_addr) => P2pNetworkAutonatMessage::dial_response(
+                        P2pNetworkAutonatResponseStatus::Ok,
+                        "OK",
+                        Some(dialed_addr),
+                    ),
+                    Err(error) => P2pNetworkAutonatMessage::dial_response(
+                        P2pNetworkAutonatResponseStatus::DialError,
+                        error,
+                        None,
+                    ),
+                };
+
+                let dispatcher = state_context.into_dispatcher();
+                Self::send_message(dispatcher, addr, stream_id, &response);
+                dispatcher.push(P2pNetworkAutonatAction::Close {
+                    addr,
+                    peer_id,
+                    stream_id,
+                });
+                Ok(())
+            }
+            P2pNetworkAutonatAction::RemoteClose {
+                addr,
+                peer_id,
+                stream_id,
+            } => {
+                let Some(stream) = autonat_state.find_stream(&peer_id, &stream_id) else {
+                    bug_condition!("AutoNAT stream not found for {peer_id}/{stream_id}");
+                    return Ok(());
+                };
+                let status = stream.status.clone();
+
+                let dispatcher = state_context.into_dispatcher();
+                match status {
+                    // the response is still to be sent, remote peer only
+                    // closed its side of the stream
+                    P2pNetworkAutonatStreamStatus::DialBack => {}
+                    P2pNetworkAutonatStreamStatus::WaitDialResponse => {
+                        dispatcher.push(P2pNatAction::ProbeResult {
+                            peer_id,
+                            result: P2pNatProbeResult::Refused("stream closed".to_owned()),
+                        });
+                        dispatcher.push(P2pNetworkAutonatAction::Close {
+                            addr,
+                            peer_id,
+                            stream_id,
+                        });
+                    }
+                    P2pNetworkAutonatStreamStatus::WaitDial
+                    | P2pNetworkAutonatStreamStatus::Done => {
+                        dispatcher.push(P2pNetworkAutonatAction::Close {
+                            addr,
+                            peer_id,
+                            stream_id,
+                        });
+                    }
+                }
+                Ok(())
+            }
+            P2pNetworkAutonatAction::Close {
+                addr,
+                peer_id,
+                stream_id,
+            } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkYamuxAction::OutgoingData {
+                    addr,
+                    stream_id,
+                    data: Data::empty(),
+                    flags: YamuxFlags::FIN,
+                });
+                dispatcher.push(P2pNetworkAutonatAction::Prune {
+                    addr,
+                    peer_id,
+                    stream_id,
+                });
+                Ok(())
+            }
+            P2pNetworkAutonatAction::Prune {
+                peer_id, stream_id, ..
+            } => {
+                if let Some(streams) = autonat_state.streams.get_mut(&peer_id) {
+                    streams.remove(&stream_id);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn send_message<Action, State>(
+        dispatcher: &mut Dispatcher<Action, State>,
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        message: &P2pNetworkAutonatMessage,
+    ) where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let message = match pb::Message::try_from(message) {
+            Ok(message) => message,
+            Err(err) => {
+                bug_condition!("error encoding AutoNAT message: {err}");
+                return;
+            }
+        };
+        dispatcher.push(P2pNetworkYamuxAction::OutgoingData {
+            addr,
+            stream_id,
+            data: Data::from(message.encode_length_delimited_to_vec()),
+            flags: Default::default(),
+        });
+    }
+}
+
+/// Takes the length-prefixed message from the buffer, if it is received
+/// completely.
+fn take_message(buffer: &mut Vec<u8>) -> Result<Option<pb::Message>, String> {
+    let mut buf = buffer.as_slice();
+    let len = match prost::decode_length_delimiter(&mut buf) {
+        Ok(len) => len,
+        // length prefix itself is not received yet
+        Err(_) if buffer.len() < 10 && buffer.iter().all(|b| b & 0x80 != 0) => return Ok(None),
+        Err(err) => return Err(err.to_string()),
+    };
+    if len > AUTONAT_MESSAGE_SIZE_LIMIT {
+        return Err(format!(
+            "message is too long: {len} exceeds {AUTONAT_MESSAGE_SIZE_LIMIT}"
+        ));
+    }
+    if buf.len() < len {
+        return Ok(None);
+    }
+
+    let message = pb::Message::decode(&buf[..len]).map_err(|err| err.to_string())?;
+    buffer.clear();
+    Ok(Some(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_message_waits_for_complete_message() {
+        let message = P2pNetworkAutonatMessage::dial_response(
+            P2pNetworkAutonatResponseStatus::Ok,
+            "OK",
+            Some("1.2.3.4:8302".parse::<SocketAddr>().unwrap()),
+        );
+        let bytes = pb::Message::try_from(&message)
+            .unwrap()
+            .encode_length_delimited_to_vec();
+
+        let mut buffer = Vec::new();
+        for chunk in bytes.chunks(3) {
+            assert_eq!(take_message(&mut buffer), Ok(None));
+            buffer.extend_from_slice(chunk);
+        }
+        let message = take_message(&mut buffer).unwrap().unwrap();
+        assert_eq!(
+            message.dial_response.unwrap().status(),
+            pb::message::ResponseStatus::Ok
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn take_message_rejects_oversized_message() {
+        let mut buffer = Vec::new();
+        prost::encode_length_delimiter(AUTONAT_MESSAGE_SIZE_LIMIT + 1, &mut buffer).unwrap();
+        assert!(take_message(&mut buffer).is_err());
+    }
+}
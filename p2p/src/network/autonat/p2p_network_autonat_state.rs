@@ -0,0 +1,70 @@
+use malloc_size_of_derive::MallocSizeOf;
+use serde::{Deserialize, Serialize};
+
+use crate::{network::scheduler::StreamState, PeerId, StreamId};
+
+/// AutoNAT messages are tiny, anything bigger is garbage.
+pub const AUTONAT_MESSAGE_SIZE_LIMIT: usize = 4096;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, MallocSizeOf)]
+pub struct P2pNetworkAutonatState {
+    pub streams: StreamState<P2pNetworkAutonatStreamState>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, MallocSizeOf)]
+pub struct P2pNetworkAutonatStreamState {
+    pub status: P2pNetworkAutonatStreamStatus,
+    /// Received bytes of the message that is not complete yet.
+    pub buffer: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, MallocSizeOf)]
+pub enum P2pNetworkAutonatStreamStatus {
+    /// Incoming stream, waiting for the remote peer to ask us for a dial back.
+    WaitDial,
+    /// Incoming stream, the service is dialing the remote peer back.
+    DialBack,
+    /// Outgoing stream, we asked the remote peer to dial us back.
+    WaitDialResponse,
+    /// Response is sent or received, the stream is being closed.
+    Done,
+}
+
+impl P2pNetworkAutonatState {
+    pub fn find_stream(
+        &self,
+        peer_id: &PeerId,
+        stream_id: &StreamId,
+    ) -> Option<&P2pNetworkAutonatStreamState> {
+        self.streams.get(peer_id)?.get(stream_id)
+    }
+
+    pub fn find_stream_mut(
+        &mut self,
+        peer_id: &PeerId,
+        stream_id: &StreamId,
+    ) -> Option<&mut P2pNetworkAutonatStreamState> {
+        self.streams.get_mut(peer_id)?.get_mut(stream_id)
+    }
+
+    /// Number of dial backs the service is doing at the moment.
+    pub fn dial_backs_count(&self) -> usize {
+        self.streams
+            .values()
+            .flat_map(|streams| streams.values())
+            .filter(|stream| stream.status == P2pNetworkAutonatStreamStatus::DialBack)
+            .count()
+    }
+
+    pub fn is_dialing_back(&self, peer_id: &PeerId) -> bool {
+        self.streams.get(peer_id).is_some_and(|streams| {
+            streams
+                .values()
+                .any(|stream| stream.status == P2pNetworkAutonatStreamStatus::DialBack)
+        })
+    }
+
+    pub fn prune_peer_state(&mut self, peer_id: &PeerId) {
+        self.streams.remove(peer_id);
+    }
+}
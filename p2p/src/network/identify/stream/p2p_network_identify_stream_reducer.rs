@@ -375,21 +375,80 @@ impl P2pNetworkIdentifyStreamState {
         State: crate::P2pStateTrait,
     {
         let config = &state.config;
-        let ips = &config.external_addrs;
-        let port = config.libp2p_port.unwrap_or(8302);
 
-        listen_addrs.extend(
-            ips.iter()
-                .map(|ip| Multiaddr::from(*ip).with(multiaddr::Protocol::Tcp(port))),
-        );
+        if config.access.isolate {
+            // don't advertise our addresses, so that we can't be reached
+            // by peers other than the ones we are connected to
+            listen_addrs.clear();
+        } else {
+            // QUIC listener uses the same local port as the TCP one
+            let quic_addrs = if config.libp2p_quic {
+                listen_addrs
+                    .iter()
+                    .filter_map(|addr| {
+                        let mut iter = addr.iter();
+                        let ip = iter.next()?;
+                        let Some(multiaddr::Protocol::Tcp(port)) = iter.next() else {
+                            return None;
+                        };
+                        Some(
+                            Multiaddr::empty()
+                                .with(ip)
+                                .with(multiaddr::Protocol::Udp(port))
+                                .with(multiaddr::Protocol::QuicV1),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                Vec::new()
+            };
+            listen_addrs.extend(quic_addrs);
+            // configured addresses and the ones detected with NAT traversal,
+            // the gateway may map TCP and UDP ports to different external ones
+            listen_addrs.extend(state.nat.external_addrs(config).into_iter().map(|addr| {
+                Multiaddr::from(addr.ip()).with(multiaddr::Protocol::Tcp(addr.port()))
+            }));
+            listen_addrs.extend(
+                state
+                    .nat
+                    .external_quic_addrs(config)
+                    .into_iter()
+                    .map(|addr| {
+                        Multiaddr::from(addr.ip())
+                            .with(multiaddr::Protocol::Udp(addr.port()))
+                            .with(multiaddr::Protocol::QuicV1)
+                    }),
+            );
+        }
 
         let public_key = Some(state.config.identity_pub_key.clone());
 
+        // the address we see the peer at, it helps the peer to detect its
+        // public address
+        let observed_addr = Multiaddr::from(addr.sock_addr.ip());
+        let observed_addr = if addr.transport.is_quic() {
+            observed_addr
+                .with(multiaddr::Protocol::Udp(addr.sock_addr.port()))
+                .with(multiaddr::Protocol::QuicV1)
+        } else {
+            observed_addr.with(multiaddr::Protocol::Tcp(addr.sock_addr.port()))
+        };
+
         let mut protocols = vec![
             token::StreamKind::Identify(token::IdentifyAlgorithm::Identify1_0_0),
             token::StreamKind::Broadcast(token::BroadcastAlgorithm::Meshsub1_1_0),
             token::StreamKind::Rpc(token::RpcAlgorithm::Rpc0_0_1),
         ];
+        if !config.access.isolate {
+            protocols.push(token::StreamKind::Autonat(
+                token::AutonatAlgorithm::Autonat1_0_0,
+            ));
+        }
+        if config.libp2p_webrtc_signal_enabled() {
+            protocols.push(token::StreamKind::WebrtcSignal(
+                token::WebrtcSignalAlgorithm::WebrtcSignal1_0_0,
+            ));
+        }
         if state.network.scheduler.discovery_state.is_some() {
             protocols.push(token::StreamKind::Discovery(
                 token::DiscoveryAlgorithm::Kademlia1_0_0,
@@ -401,8 +460,7 @@ impl P2pNetworkIdentifyStreamState {
             agent_version: Some("openmina".to_owned()),
             public_key,
             listen_addrs,
-            // TODO: other peers seem to report inaccurate information, should we implement this?
-            observed_addr: None,
+            observed_addr: Some(observed_addr),
             protocols,
         };
 
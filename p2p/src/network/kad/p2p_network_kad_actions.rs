@@ -111,7 +111,13 @@ impl EnablingCondition<P2pState> for P2pNetworkKademliaAction {
                     super::P2pNetworkKadStatus::Bootstrapping(_)
                 )
             }
-            P2pNetworkKademliaAction::UpdateRoutingTable { .. } => true,
+            P2pNetworkKademliaAction::UpdateRoutingTable { peer_id, addrs } => {
+                !state.trust.is_peer_with_addrs_banned(peer_id, addrs, time)
+                    && state
+                        .config
+                        .access
+                        .is_peer_with_addrs_allowed(peer_id, addrs)
+            }
         }
     }
 }
@@ -323,6 +323,37 @@ impl<const K: usize> P2pNetworkKadRoutingTable<K> {
         self.buckets[index].iter().find(|e| &e.key == key)
     }
 
+    /// Removes the entry with the `key`. Returns true if the entry was present.
+    ///
+    /// Entry of the current node is never removed.
+    pub fn remove(&mut self, key: &P2pNetworkKadKey) -> bool {
+        if key == &self.this_key {
+            return false;
+        }
+        let dist = self.this_key - key;
+        let index = dist.to_index().min(self.buckets.len() - 1);
+
+        let bucket = &mut self.buckets[index].0;
+        let len = bucket.len();
+        bucket.retain(|e| &e.key != key);
+        bucket.len() != len
+    }
+
+    /// Replaces addresses of the entry of the current node.
+    pub fn update_this_entry_addrs(&mut self, addrs: Vec<Multiaddr>) {
+        let this_key = self.this_key;
+        // distance to the current node is zero, so it is in the last bucket
+        let Some(bucket) = self.buckets.last_mut() else {
+            return;
+        };
+        if let Some(entry) = bucket.0.iter_mut().find(|e| e.key == this_key) {
+            entry.addrs = addrs
+                .into_iter()
+                .take(P2pNetworkKadEntry::MAX_ADDRS)
+                .collect();
+        }
+    }
+
     /// FIND_NODE backend. Returns iterator of nodes closest to the specified
     /// `key`, excluding nodes that correspond to the `key` itself and
     /// `self.this_key`.
@@ -772,6 +803,28 @@ mod tests {
         println!("routing table: {rt:+#?}");
     }
 
+    #[test]
+    fn test_remove() {
+        let this_entry = entry_with_peer_id(peer_id_rand());
+        let mut rt: P2pNetworkKadRoutingTable = P2pNetworkKadRoutingTable::new(this_entry.clone());
+        let entries = (0..256)
+            .map(|_| entry_with_peer_id(peer_id_rand()))
+            .collect::<Vec<_>>();
+        let inserted = entries
+            .iter()
+            .filter(|entry| rt.insert((*entry).clone()).is_ok())
+            .collect::<Vec<_>>();
+
+        for entry in &inserted {
+            assert!(rt.remove(&entry.key));
+            assert!(rt.look_up(&entry.key).is_none());
+            assert!(!rt.remove(&entry.key));
+            rt.assert_k_buckets();
+        }
+        assert!(!rt.remove(&this_entry.key));
+        assert!(rt.look_up(&this_entry.key).is_some());
+    }
+
     #[test]
     fn test_find_node_zero() {
         let this_entry = entry_with_peer_id(peer_id_rand());
@@ -67,8 +67,16 @@ impl super::P2pNetworkKadState {
                 },
             ) => {
                 let kad_key = P2pNetworkKadKey::from(key);
-                let closer_peers: Vec<_> =
-                    state.routing_table.find_node(&kad_key).cloned().collect();
+                let closer_peers: Vec<_> = state
+                    .routing_table
+                    .find_node(&kad_key)
+                    .filter(|entry| {
+                        state
+                            .access
+                            .is_peer_with_addrs_allowed(&entry.peer_id, entry.addresses())
+                    })
+                    .cloned()
+                    .collect();
                 debug!(meta.time(); "found {} peers", closer_peers.len());
                 let message = P2pNetworkKademliaRpcReply::FindNode { closer_peers };
 
@@ -91,13 +99,23 @@ impl super::P2pNetworkKadState {
                 },
             ) => {
                 let mut latest_request_peers = Vec::new();
-                for entry in &closest_peers {
+                let mut allowed_peers = Vec::new();
+                for entry in closest_peers {
+                    if !state
+                        .access
+                        .is_peer_with_addrs_allowed(&entry.peer_id, entry.addresses())
+                    {
+                        latest_request_peers
+                            .push((entry.peer_id, P2pNetworkKadLatestRequestPeerKind::Discarded));
+                        continue;
+                    }
                     let kind = match state.routing_table.insert(entry.clone()) {
                         Ok(true) => P2pNetworkKadLatestRequestPeerKind::New,
                         Ok(false) => P2pNetworkKadLatestRequestPeerKind::Existing,
                         Err(_) => P2pNetworkKadLatestRequestPeerKind::Discarded,
                     };
                     latest_request_peers.push((entry.peer_id, kind));
+                    allowed_peers.push(entry);
                 }
                 state.latest_request_peers = latest_request_peers.into();
 
@@ -105,7 +123,7 @@ impl super::P2pNetworkKadState {
                 dispatcher.push(P2pNetworkKadRequestAction::ReplyReceived {
                     peer_id,
                     stream_id,
-                    data: closest_peers,
+                    data: allowed_peers,
                 });
 
                 Ok(())
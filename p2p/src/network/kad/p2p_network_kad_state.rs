@@ -10,7 +10,7 @@ use super::{
 };
 use crate::{
     bootstrap::{P2pNetworkKadBootstrapRequestStat, P2pNetworkKadBootstrapStats},
-    is_time_passed, P2pTimeouts, PeerId, StreamId, StreamState,
+    is_time_passed, P2pAccessConfig, P2pTimeouts, PeerId, StreamId, StreamState,
 };
 
 /// Kademlia status.
@@ -60,6 +60,10 @@ pub struct P2pNetworkKadState {
     pub streams: StreamState<P2pNetworkKadStreamState>,
     pub status: P2pNetworkKadStatus,
     pub filter_addrs: bool,
+    /// Peers not allowed by this config are neither added to the routing
+    /// table nor advertised to other peers.
+    #[ignore_malloc_size_of = "config"]
+    pub access: P2pAccessConfig,
 }
 
 impl Default for P2pNetworkKadState {
@@ -74,6 +78,7 @@ impl Default for P2pNetworkKadState {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(true),
+            access: Default::default(),
         }
     }
 }
@@ -2,10 +2,10 @@ use openmina_core::{bug_condition, requests::RpcId, Substate, SubstateAccess};
 use redux::{ActionWithMeta, Dispatcher};
 
 use crate::{
-    connection::outgoing::P2pConnectionOutgoingAction, ConnectionAddr,
-    P2pNetworkConnectionMuxState, P2pNetworkKadBootstrapAction, P2pNetworkKadEffectfulAction,
-    P2pNetworkKadState, P2pNetworkKademliaRpcRequest, P2pNetworkKademliaStreamAction,
-    P2pNetworkYamuxAction, P2pPeerState, P2pState, PeerId,
+    connection::outgoing::P2pConnectionOutgoingAction, ConnectionAddr, ConnectionTransport,
+    P2pNetworkKadBootstrapAction, P2pNetworkKadEffectfulAction, P2pNetworkKadState,
+    P2pNetworkKademliaRpcRequest, P2pNetworkKademliaStreamAction, P2pNetworkYamuxAction,
+    P2pPeerState, P2pState, PeerId,
 };
 
 use super::{P2pNetworkKadRequestAction, P2pNetworkKadRequestState, P2pNetworkKadRequestStatus};
@@ -69,7 +69,8 @@ impl P2pNetworkKadRequestState {
                 };
 
                 let on_connection_established = |dispatcher: &mut Dispatcher<Action, State>| {
-                    let Some((_, conn_state)) = p2p_state.network.scheduler.find_peer(&peer_id)
+                    let Some((conn_addr, conn_state)) =
+                        p2p_state.network.scheduler.find_peer(&peer_id)
                     else {
                         bug_condition!(
                             "peer {peer_id} is connected, its network connection is {:?}",
@@ -82,21 +83,13 @@ impl P2pNetworkKadRequestState {
 
                         return Ok(());
                     };
-                    if let Some(stream_id) = conn_state.mux.as_ref().and_then(
-                        |P2pNetworkConnectionMuxState::Yamux(yamux)| {
-                            yamux.next_stream_id(
-                                crate::YamuxStreamKind::Kademlia,
-                                conn_state.incoming,
-                            )
-                        },
-                    ) {
+                    if let Some(stream_id) = conn_state.mux.as_ref().and_then(|mux| {
+                        mux.next_stream_id(crate::YamuxStreamKind::Kademlia, conn_state.incoming)
+                    }) {
                         // multiplexing is ready, open a stream
                         // TODO: add callbacks
                         dispatcher.push(P2pNetworkYamuxAction::OpenStream {
-                            addr: crate::ConnectionAddr {
-                                sock_addr: addr,
-                                incoming: false,
-                            },
+                            addr: *conn_addr,
                             stream_id,
                             stream_kind: crate::token::StreamKind::Discovery(
                                 crate::token::DiscoveryAlgorithm::Kademlia1_0_0,
@@ -150,9 +143,8 @@ impl P2pNetworkKadRequestState {
                             .map(|mux| (mux, conn.incoming))
                             .ok_or_else(|| format!("multiplexing is not ready for {addr}"))
                     })
-                    .and_then(|(P2pNetworkConnectionMuxState::Yamux(yamux), incoming)| {
-                        yamux
-                            .next_stream_id(crate::YamuxStreamKind::Kademlia, incoming)
+                    .and_then(|(mux, incoming)| {
+                        mux.next_stream_id(crate::YamuxStreamKind::Kademlia, incoming)
                             .ok_or_else(|| format!("cannot get next stream for {addr}"))
                     })?;
 
@@ -224,7 +216,18 @@ impl P2pNetworkKadRequestState {
                     .then(|| state.latest_request_peers.clone())
                     .unwrap_or_default();
 
-                let dispatcher = state_context.into_dispatcher();
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+                // the stream is opened in the connection to the peer,
+                // which is not necessarily the one dialed by the request
+                let addr = p2p_state.network.scheduler.find_peer(&peer_id).map_or(
+                    ConnectionAddr {
+                        sock_addr: addr,
+                        incoming: false,
+                        transport: ConnectionTransport::Tcp,
+                    },
+                    |(addr, _)| *addr,
+                );
 
                 if bootstrap_request {
                     dispatcher.push(P2pNetworkKadBootstrapAction::RequestDone {
@@ -246,10 +249,7 @@ impl P2pNetworkKadRequestState {
                     }
                 }
                 dispatcher.push(P2pNetworkKademliaStreamAction::Close {
-                    addr: ConnectionAddr {
-                        sock_addr: addr,
-                        incoming: false,
-                    },
+                    addr,
                     peer_id,
                     stream_id,
                 });
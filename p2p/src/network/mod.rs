@@ -42,8 +42,17 @@ pub mod yamux;
 use self::stream::{P2pNetworkKadIncomingStreamError, P2pNetworkKadOutgoingStreamError};
 pub use self::yamux::*;
 
+pub mod quic;
+pub use self::quic::*;
+
 pub mod identify;
 
+pub mod autonat;
+pub use self::autonat::*;
+
+pub mod webrtc_signal;
+pub use self::webrtc_signal::*;
+
 pub mod kad;
 pub use self::kad::*;
 
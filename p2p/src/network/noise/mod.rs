@@ -3,7 +3,8 @@ pub use self::p2p_network_noise_actions::*;
 
 mod p2p_network_noise_state;
 pub use self::p2p_network_noise_state::{
-    NoiseError, P2pNetworkNoiseState, P2pNetworkNoiseStateInner, Pk, Sk,
+    NoiseError, NoiseState, P2pNetworkNoiseState, P2pNetworkNoiseStateInitiator,
+    P2pNetworkNoiseStateInner, P2pNetworkNoiseStateResponder, Pk, Sk,
 };
 
 #[cfg(feature = "p2p-libp2p")]
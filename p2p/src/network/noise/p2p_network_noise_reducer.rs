@@ -234,7 +234,7 @@ impl P2pNetworkNoiseState {
                     if let Some((peer_id, true)) = handshake_done {
                         dispatcher.push(P2pConnectionIncomingAction::FinalizePendingLibp2p {
                             peer_id,
-                            addr: addr.sock_addr,
+                            addr,
                         });
 
                         let p2p_state: &P2pState = state.substate()?;
@@ -2,8 +2,9 @@ use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    identify::*, kad::*, noise::*, pnet::*, pnet_effectful::*, pubsub::*, rpc::*, scheduler::*,
-    select::*, yamux::*, P2pNetworkSchedulerEffectfulAction,
+    autonat::*, identify::*, kad::*, noise::*, pnet::*, pnet_effectful::*, pubsub::*, quic::*,
+    rpc::*, scheduler::*, select::*, webrtc_signal::*, yamux::*,
+    P2pNetworkSchedulerEffectfulAction,
 };
 
 use crate::P2pState;
@@ -15,7 +16,10 @@ pub enum P2pNetworkAction {
     Select(P2pNetworkSelectAction),
     Noise(P2pNetworkNoiseAction),
     Yamux(P2pNetworkYamuxAction),
+    Quic(P2pNetworkQuicAction),
     Identify(P2pNetworkIdentifyAction),
+    Autonat(P2pNetworkAutonatAction),
+    WebrtcSignal(P2pNetworkWebrtcSignalAction),
     Kad(P2pNetworkKadAction),
     Pubsub(P2pNetworkPubsubAction),
     Rpc(P2pNetworkRpcAction),
@@ -29,7 +33,10 @@ impl redux::EnablingCondition<P2pState> for P2pNetworkAction {
             Self::Select(v) => v.is_enabled(state, time),
             Self::Noise(v) => v.is_enabled(state, time),
             Self::Yamux(v) => v.is_enabled(state, time),
+            Self::Quic(v) => v.is_enabled(state, time),
             Self::Identify(v) => v.is_enabled(state, time),
+            Self::Autonat(v) => v.is_enabled(state, time),
+            Self::WebrtcSignal(v) => v.is_enabled(state, time),
             Self::Kad(v) => v.is_enabled(state, time),
             Self::Pubsub(v) => v.is_enabled(state, time),
             Self::Rpc(v) => v.is_enabled(state, time),
@@ -1,6 +1,6 @@
 use std::net::{IpAddr, SocketAddr};
 
-use crate::ConnectionAddr;
+use crate::{ConnectionAddr, StreamId};
 
 /// The state machine sends commands to the service.
 pub enum MioCmd {
@@ -20,6 +20,24 @@ pub enum MioCmd {
     Send(ConnectionAddr, Box<[u8]>),
     /// Disconnect the remote peer.
     Disconnect(ConnectionAddr),
+    /// Accept QUIC connections on the UDP port of all interfaces, both IPv4
+    /// and IPv6. Does nothing if already listening on the port.
+    QuicListen(u16),
+    /// Create a new outgoing QUIC connection to the socket.
+    QuicConnect(SocketAddr),
+    /// Send the data in the stream of the QUIC connection, opening the
+    /// stream if needed. The stream is finished if `fin` is set.
+    QuicStreamSend {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Box<[u8]>,
+        fin: bool,
+    },
+    /// Reset the stream of the QUIC connection.
+    QuicStreamReset {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
 }
 
 pub trait P2pMioService: redux::Service {
@@ -2,7 +2,7 @@ use multiaddr::Multiaddr;
 use openmina_core::ChainId;
 use serde::{Deserialize, Serialize};
 
-use crate::{identity::PublicKey, PeerId};
+use crate::{identity::PublicKey, P2pAccessConfig, PeerId};
 
 use super::*;
 
@@ -18,6 +18,7 @@ impl P2pNetworkState {
         known_peers: Vec<(PeerId, Multiaddr)>,
         chain_id: &ChainId,
         discovery: bool,
+        access: &P2pAccessConfig,
     ) -> Self {
         let peer_id = identity.peer_id();
         let pnet_key = chain_id.preshared_key();
@@ -30,6 +31,7 @@ impl P2pNetworkState {
             }));
             P2pNetworkKadState {
                 routing_table,
+                access: access.clone(),
                 ..Default::default()
             }
         });
@@ -43,6 +45,8 @@ impl P2pNetworkState {
                 connections: Default::default(),
                 broadcast_state: Default::default(),
                 identify_state: Default::default(),
+                autonat_state: Default::default(),
+                webrtc_signal_state: Default::default(),
                 discovery_state,
                 rpc_incoming_streams: Default::default(),
                 rpc_outgoing_streams: Default::default(),
@@ -10,11 +10,14 @@ pub use self::p2p_network_pubsub_state::{
     P2pNetworkPubsubClientState, P2pNetworkPubsubClientTopicState, P2pNetworkPubsubState,
 };
 
+mod p2p_network_pubsub_score;
+pub use self::p2p_network_pubsub_score::{P2pNetworkPubsubPeerScore, P2pNetworkPubsubTopicScore};
+
 #[cfg(feature = "p2p-libp2p")]
 mod p2p_network_pubsub_reducer;
 
 #[cfg(feature = "p2p-libp2p")]
-const TOPIC: &str = "coda/consensus-messages/0.0.1";
+pub const TOPIC: &str = "coda/consensus-messages/0.0.1";
 
 pub mod pubsub_effectful;
 use openmina_core::snark::SnarkJobId;
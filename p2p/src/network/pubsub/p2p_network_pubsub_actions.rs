@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use super::{pb, BroadcastMessageId};
 use crate::{token::BroadcastAlgorithm, ConnectionAddr, Data, P2pState, PeerId, StreamId};
 use mina_p2p_messages::gossip::GossipNetMessageV2;
@@ -168,6 +170,16 @@ pub enum P2pNetworkPubsubAction {
     /// Delete expired messages from state
     PruneMessages {},
 
+    /// Decay peer scores and maintain the mesh of each topic. Peers with
+    /// negative score are pruned, the mesh is filled with the best scoring
+    /// peers when it is too small and trimmed when it is too big.
+    ///
+    /// **Fields:**
+    /// - `app_scores`: Application specific scores of peers (P5), which are their trust scores.
+    Heartbeat {
+        app_scores: BTreeMap<PeerId, f64>,
+    },
+
     RejectMessage {
         message_id: Option<BroadcastMessageId>,
         peer_id: Option<PeerId>,
@@ -191,9 +203,18 @@ impl From<P2pNetworkPubsubAction> for crate::P2pAction {
 }
 
 impl redux::EnablingCondition<P2pState> for P2pNetworkPubsubAction {
-    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
         let pubsub = &state.network.scheduler.broadcast_state;
         match self {
+            // ignore graylisted peers
+            P2pNetworkPubsubAction::IncomingData { peer_id, .. } => {
+                !pubsub.is_graylisted(peer_id, &state.config.meshsub.score)
+            }
+            P2pNetworkPubsubAction::Heartbeat { .. } => {
+                pubsub.last_heartbeat.map_or(true, |last| {
+                    time >= last + state.config.meshsub.heartbeat_interval
+                })
+            }
             P2pNetworkPubsubAction::OutgoingMessage { peer_id } => pubsub
                 .clients
                 .get(peer_id)
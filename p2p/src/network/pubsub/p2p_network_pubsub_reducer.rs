@@ -1,4 +1,7 @@
-use std::{collections::btree_map::Entry, time::Duration};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    time::Duration,
+};
 
 use binprot::BinProtRead;
 use mina_p2p_messages::{
@@ -14,12 +17,13 @@ use crate::{
     channels::{snark::P2pChannelsSnarkAction, transaction::P2pChannelsTransactionAction},
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
     peer::P2pPeerAction,
-    Data, P2pConfig, P2pNetworkYamuxAction, P2pState, PeerId,
+    Data, P2pConfig, P2pMeshsubConfig, P2pMeshsubScoreParams, P2pMeshsubTopicScoreParams,
+    P2pNetworkYamuxAction, P2pState, PeerId,
 };
 
 use super::{
     p2p_network_pubsub_state::{
-        source_from_message, P2pNetworkPubsubClientMeshAddingState,
+        compute_message_id, source_from_message, P2pNetworkPubsubClientMeshAddingState,
         P2pNetworkPubsubMessageCacheMessage,
     },
     pb::{self, Message},
@@ -33,6 +37,7 @@ impl P2pNetworkPubsubState {
     pub fn reducer<Action, State>(
         mut state_context: Substate<Action, State, Self>,
         action: redux::ActionWithMeta<P2pNetworkPubsubAction>,
+        meshsub: &P2pMeshsubConfig,
     ) -> Result<(), String>
     where
         State: crate::P2pStateTrait,
@@ -77,6 +82,11 @@ impl P2pNetworkPubsubState {
                     .entry(super::TOPIC.to_owned())
                     .or_default()
                     .insert(peer_id, Default::default());
+                pubsub_state
+                    .scores
+                    .entry(peer_id)
+                    .or_default()
+                    .disconnected_at = None;
 
                 Ok(())
             }
@@ -111,6 +121,11 @@ impl P2pNetworkPubsubState {
                     .entry(TOPIC.to_owned())
                     .or_default()
                     .insert(peer_id, Default::default());
+                pubsub_state
+                    .scores
+                    .entry(peer_id)
+                    .or_default()
+                    .disconnected_at = None;
 
                 if let Some(state) = pubsub_state.clients.get_mut(&peer_id) {
                     state.message.subscriptions.push(pb::rpc::SubOpts {
@@ -129,7 +144,9 @@ impl P2pNetworkPubsubState {
                 };
                 dispatcher.push(P2pNetworkPubsubAction::OutgoingMessage { peer_id });
                 let mesh_size = map.values().filter(|s| s.on_mesh()).count();
-                if mesh_size < config.meshsub.outbound_degree_desired {
+                if mesh_size < config.meshsub.outbound_degree_desired
+                    && state.can_graft(&peer_id, TOPIC, time)
+                {
                     dispatcher.push(P2pNetworkPubsubAction::Graft {
                         peer_id,
                         topic_id: TOPIC.to_owned(),
@@ -145,9 +162,10 @@ impl P2pNetworkPubsubState {
                 addr,
                 ..
             } => {
-                pubsub_state.reduce_incoming_data(&peer_id, data, meta.time())?;
+                let grafted = pubsub_state.reduce_incoming_data(&peer_id, data, meshsub, time)?;
 
-                let dispatcher = state_context.into_dispatcher();
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let state: &Self = state.substate()?;
 
                 dispatcher.push(P2pNetworkPubsubAction::ValidateIncomingMessages {
                     peer_id,
@@ -155,6 +173,18 @@ impl P2pNetworkPubsubState {
                     addr,
                 });
 
+                // Refuse grafts during backoff or from peers with negative score.
+                for topic_id in grafted {
+                    let on_mesh = state
+                        .topics
+                        .get(&topic_id)
+                        .and_then(|m| m.get(&peer_id))
+                        .is_some_and(|s| s.on_mesh());
+                    if !on_mesh || state.peer_score(&peer_id) < 0.0 {
+                        dispatcher.push(P2pNetworkPubsubAction::Prune { peer_id, topic_id });
+                    }
+                }
+
                 Ok(())
             }
             P2pNetworkPubsubAction::ValidateIncomingMessages {
@@ -199,6 +229,9 @@ impl P2pNetworkPubsubState {
                 // Check result later to ensure we always dispatch the cleanup action
                 let reduce_incoming_result =
                     pubsub_state.reduce_incoming_message(&message, seen_limit);
+                if let Ok(None) = &reduce_incoming_result {
+                    pubsub_state.reduce_duplicate_message(&peer_id, &message, &meshsub.score, time);
+                }
 
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
                 let p2p_state: &P2pState = global_state.substate()?;
@@ -206,7 +239,17 @@ impl P2pNetworkPubsubState {
 
                 dispatcher.push(P2pNetworkPubsubAction::IncomingMessageCleanup { peer_id });
 
-                let message_content = reduce_incoming_result?;
+                let message_content = match reduce_incoming_result {
+                    Ok(message_content) => message_content,
+                    Err(reason) => {
+                        dispatcher.push(P2pNetworkPubsubAction::RejectMessage {
+                            message_id: None,
+                            peer_id: Some(peer_id),
+                            reason,
+                        });
+                        return Ok(());
+                    }
+                };
 
                 for (topic_id, map) in &state.topics {
                     let mesh_size = map.values().filter(|s| s.on_mesh()).count();
@@ -286,6 +329,11 @@ impl P2pNetworkPubsubState {
                     return Ok(());
                 };
                 state.mesh = P2pNetworkPubsubClientMeshAddingState::Added;
+                pubsub_state
+                    .scores
+                    .entry(peer_id)
+                    .or_default()
+                    .graft(&topic_id, time);
 
                 if let Some(state) = pubsub_state.clients.get_mut(&peer_id) {
                     let control = state
@@ -316,6 +364,11 @@ impl P2pNetworkPubsubState {
                     return Ok(());
                 };
                 state.mesh = P2pNetworkPubsubClientMeshAddingState::WeRefused;
+                if let Some(score) = pubsub_state.scores.get_mut(&peer_id) {
+                    let backoff_until = time + meshsub.prune_backoff;
+                    score.prune(&topic_id, &meshsub.score.topic, Some(backoff_until), time);
+                }
+                pubsub_state.update_peer_score(&peer_id, &meshsub.score, time);
 
                 if let Some(state) = pubsub_state.clients.get_mut(&peer_id) {
                     let control = state
@@ -333,7 +386,7 @@ impl P2pNetworkPubsubState {
                             peer_id: None,
                             signed_peer_record: None,
                         }],
-                        backoff: None,
+                        backoff: Some(meshsub.prune_backoff.as_secs()),
                     });
                 }
 
@@ -486,9 +539,14 @@ impl P2pNetworkPubsubState {
             P2pNetworkPubsubAction::BroadcastSigned { signature } => {
                 if let Some(mut message) = pubsub_state.to_sign.pop_front() {
                     message.signature = Some(signature.0.to_vec());
+                    let scores = &pubsub_state.scores;
                     pubsub_state
                         .clients
                         .iter_mut()
+                        .filter(|(peer_id, _)| {
+                            scores.get(*peer_id).map_or(0.0, |s| s.score)
+                                >= meshsub.score.publish_threshold
+                        })
                         .for_each(|(_, state)| state.publish(&message));
                 }
 
@@ -619,7 +677,16 @@ impl P2pNetworkPubsubState {
                 let raw_message = message.message().clone();
                 let peer_id = *message.peer_id();
 
-                pubsub_state.reduce_incoming_validated_message(message_id, peer_id, &raw_message);
+                pubsub_state.reduce_incoming_validated_message(
+                    message_id,
+                    peer_id,
+                    &raw_message,
+                    &meshsub.score,
+                );
+                if let Some(score) = pubsub_state.scores.get_mut(&peer_id) {
+                    score.first_message_delivery(&raw_message.topic, &meshsub.score.topic);
+                }
+                pubsub_state.update_peer_score(&peer_id, &meshsub.score, time);
 
                 let Some(message) = pubsub_state.mcache.map.get_mut(&message_id) else {
                     bug_condition!("Message with id: {:?} not found", message_id);
@@ -665,6 +732,7 @@ impl P2pNetworkPubsubState {
                         involved_peers.push(*peer);
                     }
                 };
+                let mut topic_id = TOPIC.to_owned();
 
                 if let Some(message_id) = message_id {
                     let Some((message_id, message)) =
@@ -675,12 +743,28 @@ impl P2pNetworkPubsubState {
                     };
 
                     add_peer(message.peer_id());
+                    topic_id.clone_from(&message.message().topic);
                     pubsub_state.mcache.remove_message(message_id);
                 }
 
+                // Peers with a score are penalized and disconnected only once
+                // graylisted, others (e.g. WebRTC peers) are disconnected right away.
+                let mut to_disconnect = vec![];
+                for peer_id in involved_peers {
+                    let Some(score) = pubsub_state.scores.get_mut(&peer_id) else {
+                        to_disconnect.push(peer_id);
+                        continue;
+                    };
+                    score.invalid_message_delivery(&topic_id);
+                    pubsub_state.update_peer_score(&peer_id, &meshsub.score, time);
+                    if pubsub_state.is_graylisted(&peer_id, &meshsub.score) {
+                        to_disconnect.push(peer_id);
+                    }
+                }
+
                 let dispatcher = state_context.into_dispatcher();
 
-                for peer_id in involved_peers {
+                for peer_id in to_disconnect {
                     dispatcher.push(P2pDisconnectionAction::Init {
                         peer_id,
                         reason: P2pDisconnectionReason::InvalidMessage,
@@ -690,6 +774,16 @@ impl P2pNetworkPubsubState {
                 Ok(())
             }
             P2pNetworkPubsubAction::IgnoreMessage { .. } => Ok(()),
+            P2pNetworkPubsubAction::Heartbeat { app_scores } => {
+                pubsub_state.last_heartbeat = Some(time);
+                pubsub_state.heartbeat_ticks += 1;
+                pubsub_state.reduce_heartbeat_scores(app_scores, &meshsub.score, time);
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let state: &Self = state.substate()?;
+                state.maintain_mesh(dispatcher, meshsub, time);
+                Ok(())
+            }
         }
     }
 
@@ -727,8 +821,10 @@ impl P2pNetworkPubsubState {
         message_id: P2pNetworkPubsubMessageCacheId,
         peer_id: PeerId,
         message: &Message,
+        score_params: &P2pMeshsubScoreParams,
     ) {
         let topic = self.topics.entry(message.topic.clone()).or_default();
+        let scores = &self.scores;
 
         self.clients
             .iter_mut()
@@ -742,7 +838,7 @@ impl P2pNetworkPubsubState {
                 };
                 if topic_state.on_mesh() {
                     state.publish(message)
-                } else {
+                } else if scores.get(c).map_or(0.0, |s| s.score) >= score_params.gossip_threshold {
                     let ctr = state.message.control.get_or_insert_with(Default::default);
                     ctr.ihave.push(pb::ControlIHave {
                         topic_id: Some(message.topic.clone()),
@@ -813,18 +909,47 @@ impl P2pNetworkPubsubState {
         }
     }
 
+    /// Counts a duplicate, received from a mesh peer shortly after the first
+    /// delivery of the message, towards mesh message deliveries of the peer.
+    fn reduce_duplicate_message(
+        &mut self,
+        peer_id: &PeerId,
+        message: &Message,
+        score_params: &P2pMeshsubScoreParams,
+        time: Timestamp,
+    ) {
+        let Some(first_seen) = compute_message_id(message)
+            .ok()
+            .and_then(|message_id| self.mcache.map.get(&message_id))
+            .map(|message| *message.time())
+        else {
+            return;
+        };
+        let in_window = time
+            .checked_sub(first_seen)
+            .is_some_and(|elapsed| elapsed <= score_params.topic.mesh_message_deliveries_window);
+        if in_window {
+            if let Some(score) = self.scores.get_mut(peer_id) {
+                score.near_first_message_delivery(&message.topic, &score_params.topic);
+            }
+        }
+    }
+
     /// Processes incoming data from a peer, handling subscriptions, control messages,
     /// and message broadcasting within the P2P pubsub system.
+    ///
+    /// Returns topics the peer asked to be grafted to.
     fn reduce_incoming_data(
         &mut self,
         peer_id: &PeerId,
         data: Data,
+        meshsub: &P2pMeshsubConfig,
         timestamp: Timestamp,
-    ) -> Result<(), String> {
+    ) -> Result<Vec<String>, String> {
         let Some(client_state) = self.clients.get_mut(peer_id) else {
             // TODO: investigate, cannot reproduce this
             // bug_condition!("State not found for action: P2pNetworkPubsubAction::IncomingData");
-            return Ok(());
+            return Ok(vec![]);
         };
 
         // Data may be part of a partial message we received before.
@@ -839,9 +964,16 @@ impl P2pNetworkPubsubState {
                 let control = decoded.control.unwrap_or_default();
 
                 self.update_subscriptions(peer_id, subscriptions);
-                self.apply_control_commands(peer_id, &control);
-                self.respond_to_iwant_requests(peer_id, &control.iwant);
-                self.process_ihave_messages(peer_id, control.ihave, timestamp);
+                let grafted =
+                    self.apply_control_commands(peer_id, &control, &meshsub.score.topic, timestamp);
+                self.update_peer_score(peer_id, &meshsub.score, timestamp);
+
+                // don't exchange gossip with peers below the threshold
+                if self.peer_score(peer_id) >= meshsub.score.gossip_threshold {
+                    self.respond_to_iwant_requests(peer_id, &control.iwant);
+                    self.process_ihave_messages(peer_id, control.ihave, timestamp);
+                }
+                return Ok(grafted);
             }
             Err(err) => {
                 // NOTE: not the ideal way to check for errors, but `prost` doesn't provide
@@ -857,7 +989,7 @@ impl P2pNetworkPubsubState {
             }
         }
 
-        Ok(())
+        Ok(vec![])
     }
 
     fn update_subscriptions(&mut self, peer_id: &PeerId, subscriptions: Vec<pb::rpc::SubOpts>) {
@@ -875,16 +1007,36 @@ impl P2pNetworkPubsubState {
     }
 
     /// Applies control commands (`graft` and `prune`) to manage the peer's mesh states within topics.
-    fn apply_control_commands(&mut self, peer_id: &PeerId, control: &pb::ControlMessage) {
+    ///
+    /// Returns topics the peer asked to be grafted to. Grafting during the backoff is
+    /// a protocol violation, the peer is penalized and not added to the mesh.
+    fn apply_control_commands(
+        &mut self,
+        peer_id: &PeerId,
+        control: &pb::ControlMessage,
+        score_params: &P2pMeshsubTopicScoreParams,
+        time: Timestamp,
+    ) -> Vec<String> {
+        let mut grafted = vec![];
+
         // Apply graft commands to add the peer to specific topic meshes.
         for graft in &control.graft {
-            if let Some(mesh_state) = self
+            let topic_id = graft.topic_id();
+            let Some(mesh_state) = self
                 .topics
-                .get_mut(graft.topic_id())
+                .get_mut(topic_id)
                 .and_then(|m| m.get_mut(peer_id))
-            {
+            else {
+                continue;
+            };
+            let score = self.scores.entry(*peer_id).or_default();
+            if score.is_in_backoff(topic_id, time) {
+                score.penalize_behaviour();
+            } else if !mesh_state.on_mesh() {
                 mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::Added;
+                score.graft(topic_id, time);
             }
+            grafted.push(topic_id.to_owned());
         }
 
         // Apply prune commands to remove the peer from specific topic meshes.
@@ -895,8 +1047,13 @@ impl P2pNetworkPubsubState {
                 .and_then(|m| m.get_mut(peer_id))
             {
                 mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::TheyRefused;
+                if let Some(score) = self.scores.get_mut(peer_id) {
+                    score.prune(prune.topic_id(), score_params, None, time);
+                }
             }
         }
+
+        grafted
     }
 
     fn respond_to_iwant_requests(&mut self, peer_id: &PeerId, iwant_requests: &[pb::ControlIWant]) {
@@ -939,6 +1096,109 @@ impl P2pNetworkPubsubState {
         }
     }
 
+    /// Decays score counters and forgets scores of peers disconnected for too long.
+    fn reduce_heartbeat_scores(
+        &mut self,
+        app_scores: BTreeMap<PeerId, f64>,
+        score_params: &P2pMeshsubScoreParams,
+        time: Timestamp,
+    ) {
+        let clients = &self.clients;
+        self.scores.retain(|peer_id, score| {
+            if clients.contains_key(peer_id) {
+                score.disconnected_at = None;
+                return true;
+            }
+            let disconnected_at = *score.disconnected_at.get_or_insert(time);
+            time.checked_sub(disconnected_at)
+                .map_or(true, |elapsed| elapsed < score_params.retain_score)
+        });
+
+        let peers = self.scores.keys().copied().collect::<Vec<_>>();
+        for peer_id in peers {
+            if let Some(score) = self.scores.get_mut(&peer_id) {
+                score.decay(score_params);
+                score.app_score = app_scores.get(&peer_id).copied().unwrap_or_default();
+            }
+            self.update_peer_score(&peer_id, score_params, time);
+        }
+    }
+
+    /// Prunes mesh peers with negative score, keeps the mesh size between
+    /// `outbound_degree_low` and `outbound_degree_high` preferring peers with
+    /// better score, and periodically grafts peers that are better than
+    /// the median of the mesh if it is too low.
+    fn maintain_mesh<Action, State>(
+        &self,
+        dispatcher: &mut Dispatcher<Action, State>,
+        meshsub: &P2pMeshsubConfig,
+        time: Timestamp,
+    ) where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let by_score_desc =
+            |a: &PeerId, b: &PeerId| self.peer_score(b).total_cmp(&self.peer_score(a));
+        let opportunistic_graft = meshsub.opportunistic_graft_ticks > 0
+            && self.heartbeat_ticks % meshsub.opportunistic_graft_ticks == 0;
+
+        for (topic_id, peers) in &self.topics {
+            let mut mesh = vec![];
+            let mut candidates = vec![];
+            for (peer_id, state) in peers {
+                if !self.clients.contains_key(peer_id) {
+                    continue;
+                }
+                if state.on_mesh() {
+                    mesh.push(*peer_id);
+                } else if state.mesh != P2pNetworkPubsubClientMeshAddingState::TheyRefused
+                    && self.can_graft(peer_id, topic_id, time)
+                {
+                    candidates.push(*peer_id);
+                }
+            }
+            candidates.sort_by(by_score_desc);
+
+            let (mut to_prune, mut mesh): (Vec<_>, Vec<_>) = mesh
+                .into_iter()
+                .partition(|peer_id| self.peer_score(peer_id) < 0.0);
+            let mut to_graft = vec![];
+
+            if mesh.len() < meshsub.outbound_degree_low {
+                let needed = meshsub.outbound_degree_desired.saturating_sub(mesh.len());
+                to_graft.extend(candidates.iter().copied().take(needed));
+            } else if mesh.len() > meshsub.outbound_degree_high {
+                mesh.sort_by(by_score_desc);
+                to_prune.extend(mesh.drain(meshsub.outbound_degree_desired..));
+            } else if opportunistic_graft && mesh.len() > 1 {
+                let mut scores = mesh
+                    .iter()
+                    .map(|peer_id| self.peer_score(peer_id))
+                    .collect::<Vec<_>>();
+                scores.sort_by(f64::total_cmp);
+                let median = scores[scores.len() / 2];
+                if median < meshsub.score.opportunistic_graft_threshold {
+                    to_graft.extend(
+                        candidates
+                            .iter()
+                            .copied()
+                            .take_while(|peer_id| self.peer_score(peer_id) > median)
+                            .take(meshsub.opportunistic_graft_peers),
+                    );
+                }
+            }
+
+            for peer_id in to_prune {
+                let topic_id = topic_id.clone();
+                dispatcher.push(P2pNetworkPubsubAction::Prune { peer_id, topic_id });
+            }
+            for peer_id in to_graft {
+                let topic_id = topic_id.clone();
+                dispatcher.push(P2pNetworkPubsubAction::Graft { peer_id, topic_id });
+            }
+        }
+    }
+
     fn broadcast<Action, State>(
         dispatcher: &mut Dispatcher<Action, State>,
         state: &State,
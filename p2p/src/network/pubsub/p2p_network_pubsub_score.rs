@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{P2pMeshsubScoreParams, P2pMeshsubTopicScoreParams};
+
+/// Counters of a peer used to compute its gossipsub v1.1 score.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pNetworkPubsubPeerScore {
+    /// Score computed during the last update of the counters.
+    pub score: f64,
+    pub topics: BTreeMap<String, P2pNetworkPubsubTopicScore>,
+    /// P5: trust score of the peer, updated each heartbeat.
+    pub app_score: f64,
+    /// P7: counter of protocol misbehaviour.
+    pub behaviour_penalty: f64,
+    /// Set when the peer disconnects, its score is forgotten after
+    /// [`P2pMeshsubScoreParams::retain_score`].
+    pub disconnected_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pNetworkPubsubTopicScore {
+    /// Time the peer was added to the mesh of the topic.
+    pub mesh_since: Option<Timestamp>,
+    /// The peer must not graft until this time, after we pruned it.
+    pub backoff_until: Option<Timestamp>,
+    /// P2
+    pub first_message_deliveries: f64,
+    /// P3
+    pub mesh_message_deliveries: f64,
+    /// P3b
+    pub mesh_failure_penalty: f64,
+    /// P4
+    pub invalid_message_deliveries: f64,
+}
+
+impl P2pNetworkPubsubTopicScore {
+    fn mesh_message_deliveries_deficit(
+        &self,
+        params: &P2pMeshsubTopicScoreParams,
+        time: Timestamp,
+    ) -> f64 {
+        let Some(mesh_since) = self.mesh_since else {
+            return 0.0;
+        };
+        let time_in_mesh = time.checked_sub(mesh_since).unwrap_or_default();
+        if time_in_mesh < params.mesh_message_deliveries_activation {
+            return 0.0;
+        }
+        (params.mesh_message_deliveries_threshold - self.mesh_message_deliveries).max(0.0)
+    }
+
+    pub fn score(&self, params: &P2pMeshsubTopicScoreParams, time: Timestamp) -> f64 {
+        let time_in_mesh = self
+            .mesh_since
+            .and_then(|since| time.checked_sub(since))
+            .map_or(0.0, |d| {
+                (d.as_secs_f64() / params.time_in_mesh_quantum.as_secs_f64())
+                    .min(params.time_in_mesh_cap)
+            });
+        let deficit = self.mesh_message_deliveries_deficit(params, time);
+
+        let score = time_in_mesh * params.time_in_mesh_weight
+            + self.first_message_deliveries * params.first_message_deliveries_weight
+            + deficit * deficit * params.mesh_message_deliveries_weight
+            + self.mesh_failure_penalty * params.mesh_failure_penalty_weight
+            + self.invalid_message_deliveries.powi(2) * params.invalid_message_deliveries_weight;
+        score * params.topic_weight
+    }
+
+    pub fn is_in_backoff(&self, time: Timestamp) -> bool {
+        self.backoff_until.is_some_and(|until| time < until)
+    }
+
+    fn decay(&mut self, params: &P2pMeshsubTopicScoreParams, decay_to_zero: f64) {
+        let decay = |value: &mut f64, factor: f64| {
+            *value *= factor;
+            if *value < decay_to_zero {
+                *value = 0.0;
+            }
+        };
+        decay(
+            &mut self.first_message_deliveries,
+            params.first_message_deliveries_decay,
+        );
+        decay(
+            &mut self.mesh_message_deliveries,
+            params.mesh_message_deliveries_decay,
+        );
+        decay(
+            &mut self.mesh_failure_penalty,
+            params.mesh_failure_penalty_decay,
+        );
+        decay(
+            &mut self.invalid_message_deliveries,
+            params.invalid_message_deliveries_decay,
+        );
+    }
+}
+
+impl P2pNetworkPubsubPeerScore {
+    fn topic_mut(&mut self, topic_id: &str) -> &mut P2pNetworkPubsubTopicScore {
+        self.topics.entry(topic_id.to_owned()).or_default()
+    }
+
+    pub fn is_in_backoff(&self, topic_id: &str, time: Timestamp) -> bool {
+        self.topics
+            .get(topic_id)
+            .is_some_and(|topic| topic.is_in_backoff(time))
+    }
+
+    /// Computes the score and stores it in [`Self::score`].
+    ///
+    /// `colocated` is the number of connected peers sharing the IP address
+    /// with this peer, including the peer itself.
+    pub fn update_score(
+        &mut self,
+        params: &P2pMeshsubScoreParams,
+        colocated: usize,
+        time: Timestamp,
+    ) {
+        let mut topics_score = self
+            .topics
+            .values()
+            .map(|topic| topic.score(&params.topic, time))
+            .sum::<f64>();
+        if params.topic_score_cap > 0.0 {
+            topics_score = topics_score.min(params.topic_score_cap);
+        }
+
+        let colocation_surplus =
+            colocated.saturating_sub(params.ip_colocation_factor_threshold) as f64;
+        let behaviour_excess =
+            (self.behaviour_penalty - params.behaviour_penalty_threshold).max(0.0);
+
+        self.score = topics_score
+            + self.app_score * params.app_specific_weight
+            + colocation_surplus * colocation_surplus * params.ip_colocation_factor_weight
+            + behaviour_excess * behaviour_excess * params.behaviour_penalty_weight;
+    }
+
+    pub fn decay(&mut self, params: &P2pMeshsubScoreParams) {
+        for topic in self.topics.values_mut() {
+            topic.decay(&params.topic, params.decay_to_zero);
+        }
+        self.behaviour_penalty *= params.behaviour_penalty_decay;
+        if self.behaviour_penalty < params.decay_to_zero {
+            self.behaviour_penalty = 0.0;
+        }
+    }
+
+    pub fn graft(&mut self, topic_id: &str, time: Timestamp) {
+        let topic = self.topic_mut(topic_id);
+        topic.mesh_since = Some(time);
+        topic.mesh_message_deliveries = 0.0;
+    }
+
+    /// Peer left the mesh of the topic. Applies the sticky mesh delivery
+    /// failure penalty, and the backoff if we are the ones pruning.
+    pub fn prune(
+        &mut self,
+        topic_id: &str,
+        params: &P2pMeshsubTopicScoreParams,
+        backoff_until: Option<Timestamp>,
+        time: Timestamp,
+    ) {
+        let topic = self.topic_mut(topic_id);
+        let deficit = topic.mesh_message_deliveries_deficit(params, time);
+        topic.mesh_failure_penalty += deficit * deficit;
+        topic.mesh_since = None;
+        if backoff_until.is_some() {
+            topic.backoff_until = backoff_until;
+        }
+    }
+
+    pub fn first_message_delivery(&mut self, topic_id: &str, params: &P2pMeshsubTopicScoreParams) {
+        let topic = self.topic_mut(topic_id);
+        topic.first_message_deliveries =
+            (topic.first_message_deliveries + 1.0).min(params.first_message_deliveries_cap);
+        if topic.mesh_since.is_some() {
+            topic.mesh_message_deliveries =
+                (topic.mesh_message_deliveries + 1.0).min(params.mesh_message_deliveries_cap);
+        }
+    }
+
+    /// Duplicate of a message received from the peer within the
+    /// mesh message deliveries window.
+    pub fn near_first_message_delivery(
+        &mut self,
+        topic_id: &str,
+        params: &P2pMeshsubTopicScoreParams,
+    ) {
+        let topic = self.topic_mut(topic_id);
+        if topic.mesh_since.is_some() {
+            topic.mesh_message_deliveries =
+                (topic.mesh_message_deliveries + 1.0).min(params.mesh_message_deliveries_cap);
+        }
+    }
+
+    pub fn invalid_message_delivery(&mut self, topic_id: &str) {
+        self.topic_mut(topic_id).invalid_message_deliveries += 1.0;
+    }
+
+    pub fn penalize_behaviour(&mut self) {
+        self.behaviour_penalty += 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const TOPIC: &str = "topic";
+
+    fn at(secs: u64) -> Timestamp {
+        Timestamp::ZERO + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn time_in_mesh_and_deliveries_increase_score() {
+        let params = P2pMeshsubScoreParams::default();
+        let mut score = P2pNetworkPubsubPeerScore::default();
+
+        score.graft(TOPIC, at(0));
+        score.update_score(&params, 1, at(0));
+        assert_eq!(score.score, 0.0);
+
+        score.first_message_delivery(TOPIC, &params.topic);
+        score.update_score(&params, 1, at(60 * 10));
+        assert!((score.score - 2.0).abs() < 1e-9);
+
+        // time in mesh is capped
+        score.update_score(&params, 1, at(60 * 60 * 24));
+        assert!((score.score - 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_messages_make_score_negative() {
+        let params = P2pMeshsubScoreParams::default();
+        let mut score = P2pNetworkPubsubPeerScore::default();
+        score.graft(TOPIC, at(0));
+
+        score.invalid_message_delivery(TOPIC);
+        score.update_score(&params, 1, at(0));
+        assert!(score.score < 0.0);
+        assert!(score.score >= params.graylist_threshold);
+
+        score.invalid_message_delivery(TOPIC);
+        score.invalid_message_delivery(TOPIC);
+        score.update_score(&params, 1, at(0));
+        assert!(score.score < params.graylist_threshold);
+
+        // counters decay to zero over time
+        for _ in 0..2 * 60 * 60 {
+            score.decay(&params);
+        }
+        score.update_score(&params, 1, at(0));
+        assert_eq!(score.score, 0.0);
+    }
+
+    #[test]
+    fn colocation_and_behaviour_penalties() {
+        let params = P2pMeshsubScoreParams::default();
+        let mut score = P2pNetworkPubsubPeerScore::default();
+
+        score.update_score(&params, params.ip_colocation_factor_threshold, at(0));
+        assert_eq!(score.score, 0.0);
+        score.update_score(&params, params.ip_colocation_factor_threshold + 2, at(0));
+        assert_eq!(score.score, 4.0 * params.ip_colocation_factor_weight);
+
+        score.penalize_behaviour();
+        score.update_score(&params, 1, at(0));
+        assert_eq!(score.score, 0.0);
+        score.penalize_behaviour();
+        score.update_score(&params, 1, at(0));
+        assert_eq!(score.score, params.behaviour_penalty_weight);
+    }
+
+    #[test]
+    fn mesh_delivery_deficit_is_penalized() {
+        let mut params = P2pMeshsubScoreParams::default();
+        params.topic.time_in_mesh_weight = 0.0;
+        params.topic.mesh_message_deliveries_weight = -1.0;
+        params.topic.mesh_failure_penalty_weight = -1.0;
+        params.topic.mesh_message_deliveries_threshold = 2.0;
+        let activation = params.topic.mesh_message_deliveries_activation.as_secs();
+
+        let mut score = P2pNetworkPubsubPeerScore::default();
+        score.graft(TOPIC, at(0));
+        score.update_score(&params, 1, at(activation - 1));
+        assert_eq!(score.score, 0.0);
+
+        score.near_first_message_delivery(TOPIC, &params.topic);
+        score.update_score(&params, 1, at(activation));
+        assert_eq!(score.score, -1.0);
+
+        // the penalty sticks after the peer leaves the mesh
+        score.prune(
+            TOPIC,
+            &params.topic,
+            Some(at(activation + 60)),
+            at(activation),
+        );
+        score.update_score(&params, 1, at(activation));
+        assert_eq!(score.score, -1.0);
+        assert!(score.is_in_backoff(TOPIC, at(activation + 59)));
+        assert!(!score.is_in_backoff(TOPIC, at(activation + 60)));
+    }
+}
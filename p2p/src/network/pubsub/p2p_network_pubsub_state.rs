@@ -1,5 +1,5 @@
-use super::{pb, BroadcastMessageId};
-use crate::{token::BroadcastAlgorithm, ConnectionAddr, PeerId, StreamId};
+use super::{pb, BroadcastMessageId, P2pNetworkPubsubPeerScore};
+use crate::{token::BroadcastAlgorithm, ConnectionAddr, P2pMeshsubScoreParams, PeerId, StreamId};
 
 use libp2p_identity::ParseError;
 use mina_p2p_messages::gossip::GossipNetMessageV2;
@@ -62,6 +62,17 @@ pub struct P2pNetworkPubsubState {
 
     /// `iwant` requests, tracking the number of times peers have expressed interest in specific messages.
     pub iwant: VecDeque<P2pNetworkPubsubIwantRequestCount>,
+
+    /// Gossipsub v1.1 scores of peers, kept for a while after they disconnect.
+    #[with_malloc_size_of_func = "measurement::scores"]
+    pub scores: BTreeMap<PeerId, P2pNetworkPubsubPeerScore>,
+
+    /// Time of the last heartbeat, when scores decay and the mesh is maintained.
+    #[ignore_malloc_size_of = "doesn't allocate"]
+    pub last_heartbeat: Option<Timestamp>,
+
+    /// Number of heartbeats so far.
+    pub heartbeat_ticks: u64,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, MallocSizeOf)]
@@ -126,6 +137,50 @@ impl P2pNetworkPubsubState {
         }
     }
 
+    /// Score of the peer, computed during the last update of its counters.
+    pub fn peer_score(&self, peer_id: &PeerId) -> f64 {
+        self.scores.get(peer_id).map_or(0.0, |score| score.score)
+    }
+
+    pub fn is_graylisted(&self, peer_id: &PeerId, params: &P2pMeshsubScoreParams) -> bool {
+        self.peer_score(peer_id) < params.graylist_threshold
+    }
+
+    /// Whether we are willing to add the peer to the mesh of the topic.
+    pub fn can_graft(&self, peer_id: &PeerId, topic_id: &str, time: Timestamp) -> bool {
+        self.scores.get(peer_id).map_or(true, |score| {
+            score.score >= 0.0 && !score.is_in_backoff(topic_id, time)
+        })
+    }
+
+    /// Number of connected peers sharing the IP address with the peer.
+    fn colocated_peers(&self, peer_id: &PeerId) -> usize {
+        let Some(ip) = self
+            .clients
+            .get(peer_id)
+            .map(|client| client.addr.sock_addr.ip())
+            .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+        else {
+            return 0;
+        };
+        self.clients
+            .values()
+            .filter(|client| client.addr.sock_addr.ip() == ip)
+            .count()
+    }
+
+    pub fn update_peer_score(
+        &mut self,
+        peer_id: &PeerId,
+        params: &P2pMeshsubScoreParams,
+        time: Timestamp,
+    ) {
+        let colocated = self.colocated_peers(peer_id);
+        if let Some(score) = self.scores.get_mut(peer_id) {
+            score.update_score(params, colocated, time);
+        }
+    }
+
     pub fn clear_incoming(&mut self) {
         self.incoming_transactions.clear();
         self.incoming_snarks.clear();
@@ -466,6 +521,17 @@ mod measurement {
             .sum()
     }
 
+    pub fn scores(
+        val: &BTreeMap<PeerId, P2pNetworkPubsubPeerScore>,
+        ops: &mut MallocSizeOfOps,
+    ) -> usize {
+        val.values()
+            .flat_map(|score| &score.topics)
+            .map(|(k, v)| k.size_of(ops) + mem::size_of_val(v))
+            .sum::<usize>()
+            + val.len() * mem::size_of::<(PeerId, P2pNetworkPubsubPeerScore)>()
+    }
+
     pub fn timestamps(val: &Vec<Timestamp>, _ops: &mut MallocSizeOfOps) -> usize {
         val.capacity() * mem::size_of::<Timestamp>()
     }
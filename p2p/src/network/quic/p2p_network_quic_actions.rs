@@ -0,0 +1,129 @@
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    connection::{incoming::P2pConnectionIncomingState, P2pConnectionState},
+    token, ConnectionAddr, Data, P2pPeerStatus, P2pState, PeerId, StreamId,
+};
+
+/// Actions of QUIC connections.
+///
+/// QUIC secures and multiplexes the connection itself, so these replace
+/// pnet, noise and yamux. Upper layers keep using
+/// [`crate::P2pNetworkYamuxAction::OpenStream`] and
+/// [`crate::P2pNetworkYamuxAction::OutgoingData`], which are forwarded here
+/// for QUIC connections.
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(
+    display(addr),
+    display(peer_id),
+    stream_id,
+    debug(data),
+    fin,
+    reset,
+    debug(stream_kind)
+))]
+pub enum P2pNetworkQuicAction {
+    /// TLS handshake is done, the remote peer is authenticated.
+    Authenticated {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+    },
+    /// The connection is kept, its streams can be used.
+    Ready {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+    },
+    /// The remote peer opened a stream.
+    IncomingStream {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
+    #[action_event(level = trace)]
+    IncomingData {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Data,
+        fin: bool,
+    },
+    /// The remote peer reset the stream.
+    StreamReset {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
+    #[action_event(level = trace)]
+    OutgoingData {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Data,
+        fin: bool,
+        reset: bool,
+    },
+    OpenStream {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        stream_kind: token::StreamKind,
+    },
+}
+
+impl P2pNetworkQuicAction {
+    pub fn addr(&self) -> &ConnectionAddr {
+        match self {
+            Self::Authenticated { addr, .. }
+            | Self::Ready { addr, .. }
+            | Self::IncomingStream { addr, .. }
+            | Self::IncomingData { addr, .. }
+            | Self::StreamReset { addr, .. }
+            | Self::OutgoingData { addr, .. }
+            | Self::OpenStream { addr, .. } => addr,
+        }
+    }
+}
+
+impl From<P2pNetworkQuicAction> for crate::P2pAction {
+    fn from(a: P2pNetworkQuicAction) -> Self {
+        Self::Network(a.into())
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNetworkQuicAction {
+    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+        let addr = self.addr();
+        let Some(connection_state) = state
+            .network
+            .scheduler
+            .connection_state(addr)
+            .filter(|conn| addr.transport.is_quic() && conn.closed.is_none())
+        else {
+            return false;
+        };
+
+        match self {
+            P2pNetworkQuicAction::Authenticated { .. } => connection_state.auth.is_none(),
+            P2pNetworkQuicAction::Ready { peer_id, .. } => {
+                connection_state.mux.is_none()
+                    && connection_state.peer_id() == Some(peer_id)
+                    // incoming connection might be rejected as a duplicate
+                    && (!addr.incoming
+                        || state
+                            .peers
+                            .get(peer_id)
+                            .is_some_and(|peer| matches!(
+                                &peer.status,
+                                P2pPeerStatus::Connecting(P2pConnectionState::Incoming(
+                                    P2pConnectionIncomingState::FinalizePendingLibp2p { addr: a, .. },
+                                )) if a == &addr.sock_addr
+                            )))
+            }
+            P2pNetworkQuicAction::IncomingStream { stream_id, .. } => {
+                connection_state.is_quic() && !connection_state.streams.contains_key(stream_id)
+            }
+            P2pNetworkQuicAction::IncomingData { .. }
+            | P2pNetworkQuicAction::StreamReset { .. }
+            | P2pNetworkQuicAction::OpenStream { .. } => connection_state.is_quic(),
+            P2pNetworkQuicAction::OutgoingData { stream_id, .. } => {
+                connection_state.is_quic() && connection_state.streams.contains_key(stream_id)
+            }
+        }
+    }
+}
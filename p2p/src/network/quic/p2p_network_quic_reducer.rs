@@ -0,0 +1,194 @@
+use openmina_core::{bug_condition, Substate};
+
+use crate::{
+    connection::{incoming::P2pConnectionIncomingAction, outgoing::P2pConnectionOutgoingAction},
+    identify::P2pIdentifyAction,
+    Data, Limit, P2pLimits, P2pNetworkAuthState, P2pNetworkConnectionError,
+    P2pNetworkConnectionMuxState, P2pNetworkSchedulerAction, P2pNetworkSchedulerEffectfulAction,
+    P2pNetworkSchedulerState, P2pNetworkSelectAction, P2pNetworkStreamState, P2pState, SelectKind,
+};
+
+use super::P2pNetworkQuicAction;
+
+impl P2pNetworkSchedulerState {
+    /// Handles QUIC connections. The service hands them over already
+    /// authenticated and multiplexed, so only the remote peer id is checked
+    /// here and stream data is routed to multistream-select.
+    pub fn quic_reducer<State, Action>(
+        mut state_context: Substate<Action, State, Self>,
+        action: redux::ActionWithMeta<P2pNetworkQuicAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let connection_state = state_context
+            .get_substate_mut()?
+            .connection_state_mut(action.addr())
+            .ok_or_else(|| format!("Connection not found for action: {action:?}"))
+            .inspect_err(|e| bug_condition!("{}", e))?;
+
+        match action {
+            P2pNetworkQuicAction::Authenticated { addr, peer_id } => {
+                connection_state.auth = Some(P2pNetworkAuthState::Tls(peer_id));
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                if addr.incoming {
+                    dispatcher
+                        .push(P2pConnectionIncomingAction::FinalizePendingLibp2p { peer_id, addr });
+                } else {
+                    let p2p_state: &P2pState = state.substate()?;
+                    let expected = p2p_state.peer_with_connection(addr).map(|(id, _)| id);
+                    if let Some(expected) = expected.filter(|expected| expected != &peer_id) {
+                        dispatcher.push(P2pNetworkSchedulerAction::Error {
+                            addr,
+                            error: P2pNetworkConnectionError::RemotePeerIdMismatch {
+                                expected,
+                                actual: peer_id,
+                            },
+                        });
+                        return Ok(());
+                    }
+                }
+                dispatcher.push(P2pNetworkQuicAction::Ready { addr, peer_id });
+                Ok(())
+            }
+            P2pNetworkQuicAction::Ready { addr, peer_id } => {
+                connection_state.mux =
+                    Some(P2pNetworkConnectionMuxState::Quic { opened_streams: 0 });
+
+                let dispatcher = state_context.into_dispatcher();
+                if addr.incoming {
+                    dispatcher.push(P2pConnectionIncomingAction::Libp2pReceived { peer_id });
+                } else {
+                    dispatcher.push(P2pConnectionOutgoingAction::FinalizeSuccess {
+                        peer_id,
+                        remote_auth: None,
+                    });
+                }
+                dispatcher.push(P2pIdentifyAction::NewRequest { peer_id, addr });
+                Ok(())
+            }
+            P2pNetworkQuicAction::IncomingStream { addr, stream_id } => {
+                let Some(peer_id) = connection_state.peer_id().copied() else {
+                    bug_condition!("Peer id not found for QUIC connection {addr}");
+                    return Ok(());
+                };
+                connection_state
+                    .streams
+                    .insert(stream_id, P2pNetworkStreamState::new_incoming(meta.time()));
+                // count incoming streams
+                let incoming_streams_number = connection_state
+                    .streams
+                    .values()
+                    .filter(|s| s.select.is_incoming())
+                    .count();
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let limits: &P2pLimits = state.substate()?;
+                match (limits.max_streams(), incoming_streams_number) {
+                    (Limit::Some(limit), actual) if actual > limit => {
+                        dispatcher.push(P2pNetworkQuicAction::OutgoingData {
+                            addr,
+                            stream_id,
+                            data: Data::empty(),
+                            fin: false,
+                            reset: true,
+                        });
+                    }
+                    _ => {
+                        dispatcher.push(P2pNetworkSelectAction::Init {
+                            addr,
+                            kind: SelectKind::Stream(peer_id, stream_id),
+                            incoming: true,
+                        });
+                    }
+                }
+                Ok(())
+            }
+            P2pNetworkQuicAction::IncomingData {
+                addr,
+                stream_id,
+                data,
+                fin,
+            } => {
+                let Some(peer_id) = connection_state.peer_id().copied() else {
+                    bug_condition!("Peer id not found for QUIC connection {addr}");
+                    return Ok(());
+                };
+                if !connection_state.streams.contains_key(&stream_id) {
+                    // the stream was rejected
+                    return Ok(());
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkSelectAction::IncomingData {
+                    addr,
+                    peer_id,
+                    stream_id,
+                    data,
+                    fin,
+                });
+                Ok(())
+            }
+            P2pNetworkQuicAction::StreamReset { addr, stream_id } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkSchedulerAction::Error {
+                    addr,
+                    error: P2pNetworkConnectionError::StreamReset(stream_id),
+                });
+                Ok(())
+            }
+            P2pNetworkQuicAction::OutgoingData {
+                addr,
+                stream_id,
+                data,
+                fin,
+                reset,
+            } => {
+                if reset {
+                    connection_state.streams.remove(&stream_id);
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkSchedulerEffectfulAction::QuicOutgoingData {
+                    addr,
+                    stream_id,
+                    data,
+                    fin,
+                    reset,
+                });
+                Ok(())
+            }
+            P2pNetworkQuicAction::OpenStream {
+                addr,
+                stream_id,
+                stream_kind,
+            } => {
+                if let Some(P2pNetworkConnectionMuxState::Quic { opened_streams }) =
+                    &mut connection_state.mux
+                {
+                    *opened_streams = opened_streams.saturating_add(1);
+                }
+                connection_state.streams.insert(
+                    stream_id,
+                    P2pNetworkStreamState::new(stream_kind, meta.time()),
+                );
+
+                let Some(peer_id) = connection_state.peer_id().copied() else {
+                    bug_condition!("Peer id not found for QUIC connection {addr}");
+                    return Ok(());
+                };
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkSelectAction::Init {
+                    addr,
+                    kind: SelectKind::Stream(peer_id, stream_id),
+                    incoming: false,
+                });
+                Ok(())
+            }
+        }
+    }
+}
@@ -14,9 +14,10 @@ use openmina_core::{bug_condition, error, fuzz_maybe, fuzzed_maybe, Substate};
 use redux::Dispatcher;
 
 use crate::{
+    bandwidth::{P2pBandwidthAction, P2pBandwidthKind},
     channels::rpc::{
         BestTipWithProof, P2pChannelsRpcAction, P2pRpcRequest, P2pRpcResponse,
-        StagedLedgerAuxAndPendingCoinbases,
+        StagedLedgerAuxAndPendingCoinbases, TransitionChainProof,
     },
     connection::outgoing::P2pConnectionOutgoingInitOpts,
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
@@ -178,6 +179,11 @@ impl P2pNetworkRpcState {
                     }
                     RpcMessage::Heartbeat => {}
                     RpcMessage::Query { header, bytes } => {
+                        dispatcher.push(P2pBandwidthAction::Received {
+                            peer_id,
+                            kind: P2pBandwidthKind::from_rpc_tag(header.tag.as_ref()),
+                            bytes: bytes.len() as u64,
+                        });
                         if let Err(e) = dispatch_rpc_query(peer_id, header, bytes, dispatcher) {
                             dispatcher.push(P2pDisconnectionAction::Init {
                                 peer_id,
@@ -204,6 +210,11 @@ impl P2pNetworkRpcState {
                         };
                         // unset pending
                         dispatcher.push(P2pNetworkRpcAction::PrunePending { peer_id, stream_id });
+                        dispatcher.push(P2pBandwidthAction::Received {
+                            peer_id,
+                            kind: P2pBandwidthKind::from_rpc_tag(query_header.tag.as_ref()),
+                            bytes: bytes.len() as u64,
+                        });
 
                         if let Err(e) =
                             dispatch_rpc_response(peer_id, &query_header, bytes, dispatcher)
@@ -262,6 +273,11 @@ impl P2pNetworkRpcState {
                 let addr = rpc_state.addr;
                 let stream_id = rpc_state.stream_id;
                 let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pBandwidthAction::Sent {
+                    peer_id,
+                    kind: P2pBandwidthKind::from_rpc_tag(query.tag.as_ref()),
+                    bytes: data.len() as u64,
+                });
                 dispatcher.push(P2pNetworkRpcAction::OutgoingData {
                     addr,
                     peer_id,
@@ -302,15 +318,25 @@ impl P2pNetworkRpcState {
                 response,
                 data,
             } => {
-                if !matches!(rpc_state.pending, Some(QueryHeader { id, .. }) if id == response.id) {
+                let Some(query) = rpc_state
+                    .pending
+                    .as_ref()
+                    .filter(|query| query.id == response.id)
+                else {
                     bug_condition!("pending query does not match the response");
                     return Ok(());
-                }
+                };
+                let kind = P2pBandwidthKind::from_rpc_tag(query.tag.as_ref());
                 let stream_id = rpc_state.stream_id;
                 let addr = rpc_state.addr;
                 let dispatcher = state_context.into_dispatcher();
 
                 dispatcher.push(P2pNetworkRpcAction::PrunePending { peer_id, stream_id });
+                dispatcher.push(P2pBandwidthAction::Sent {
+                    peer_id,
+                    kind,
+                    bytes: data.len() as u64,
+                });
                 dispatcher.push(P2pNetworkRpcAction::OutgoingData {
                     addr,
                     peer_id,
@@ -353,6 +379,12 @@ impl P2pNetworkRpcState {
                     limits.rpc_get_some_initial_peers(),
                     GetSomeInitialPeersV1ForV2::NAME,
                 ),
+                GetAncestryV2::NAME => (limits.rpc_get_ancestry(), GetAncestryV2::NAME),
+                GetTransitionChainProofV1ForV2::NAME => (
+                    limits.rpc_get_transition_chain_proof(),
+                    GetTransitionChainProofV1ForV2::NAME,
+                ),
+                GetEpochLedgerV2::NAME => (limits.rpc_get_epoch_ledger(), GetEpochLedgerV2::NAME),
                 _ => (Limit::Some(0), b"<unimplemented>"),
             }
         } else {
@@ -434,6 +466,39 @@ where
                 request: Box::new(P2pRpcRequest::InitialPeers),
             });
         }
+        (rpc::GetAncestryV2::NAME, rpc::GetAncestryV2::VERSION) => {
+            let rpc::WithHashV1 { data, hash } = rpc::GetAncestryV2::query_payload(&mut bytes)?;
+            let hash = v2::StateHash::from(v2::DataHashLibStateHashStableV1(hash));
+
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::Ancestry(data, hash)),
+            });
+        }
+        (
+            rpc::GetTransitionChainProofV1ForV2::NAME,
+            rpc::GetTransitionChainProofV1ForV2::VERSION,
+        ) => {
+            let hash = rpc::GetTransitionChainProofV1ForV2::query_payload(&mut bytes)?;
+            let hash = v2::StateHash::from(v2::DataHashLibStateHashStableV1(hash));
+
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::TransitionChainProof(hash)),
+            });
+        }
+        (rpc::GetEpochLedgerV2::NAME, rpc::GetEpochLedgerV2::VERSION) => {
+            let hash = rpc::GetEpochLedgerV2::query_payload(&mut bytes)?;
+            let hash = v2::LedgerHash::from(v2::MinaBaseLedgerHash0StableV1(hash));
+
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::EpochLedger(hash)),
+            });
+        }
         (name, version) => return Err(RpcQueryError::Unimplemented(name, version)),
     }
     Ok(())
@@ -549,6 +614,61 @@ where
                 });
             }
         }
+        (rpc::GetAncestryV2::NAME, rpc::GetAncestryV2::VERSION) => {
+            let response = rpc::GetAncestryV2::response_payload(&mut bytes)?
+                .map(|resp| BestTipWithProof {
+                    best_tip: resp.data.into(),
+                    proof: (
+                        resp.proof
+                            .0
+                            .into_iter()
+                            .map(v2::MinaBaseStateBodyHashStableV1)
+                            .collect(),
+                        resp.proof.1.into(),
+                    ),
+                })
+                .map(P2pRpcResponse::Ancestry)
+                .map(Box::new);
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (
+            rpc::GetTransitionChainProofV1ForV2::NAME,
+            rpc::GetTransitionChainProofV1ForV2::VERSION,
+        ) => {
+            let response = rpc::GetTransitionChainProofV1ForV2::response_payload(&mut bytes)?
+                .map(|(init_state_hash, body_hashes)| TransitionChainProof {
+                    init_state_hash: v2::DataHashLibStateHashStableV1(init_state_hash).into(),
+                    body_hashes: body_hashes
+                        .into_iter()
+                        .map(v2::MinaBaseStateBodyHashStableV1)
+                        .collect(),
+                })
+                .map(P2pRpcResponse::TransitionChainProof)
+                .map(Box::new);
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (rpc::GetEpochLedgerV2::NAME, rpc::GetEpochLedgerV2::VERSION) => {
+            // peer responds with an error if it doesn't have the ledger
+            let response = Result::from(rpc::GetEpochLedgerV2::response_payload(&mut bytes)?)
+                .ok()
+                .map(|ledger| Box::new(P2pRpcResponse::EpochLedger(Arc::new(ledger))));
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
         _ => {}
     }
     Ok(())
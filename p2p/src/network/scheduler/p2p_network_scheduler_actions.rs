@@ -11,7 +11,10 @@ use super::{
     p2p_network_scheduler_state::{P2pNetworkConnectionCloseReason, P2pNetworkConnectionError},
 };
 
-use crate::{disconnection::P2pDisconnectionReason, ConnectionAddr, P2pState, PeerId, StreamId};
+use crate::{
+    disconnection::P2pDisconnectionReason, ConnectionAddr, ConnectionTransport, P2pState, PeerId,
+    StreamId,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
 #[action_event(fields(display(ip), display(listener), display(addr), debug(result), select_kind = debug(kind), display(error)))]
@@ -43,6 +46,7 @@ pub enum P2pNetworkSchedulerAction {
     /// Initialize outgoing connection.
     OutgoingConnect {
         addr: SocketAddr,
+        transport: ConnectionTransport,
     },
     /// Outgoint TCP stream is established.
     OutgoingDidConnect {
@@ -129,13 +133,14 @@ impl redux::EnablingCondition<P2pState> for P2pNetworkSchedulerAction {
             P2pNetworkSchedulerAction::IncomingDidAccept { addr, .. } => addr
                 .as_ref()
                 .is_some_and(|addr| !state.network.scheduler.connections.contains_key(addr)),
-            P2pNetworkSchedulerAction::OutgoingConnect { addr } => state
+            P2pNetworkSchedulerAction::OutgoingConnect { addr, transport } => state
                 .network
                 .scheduler
                 .connections
                 .get(&ConnectionAddr {
                     sock_addr: *addr,
                     incoming: false,
+                    transport: *transport,
                 })
                 .is_none_or(|v| v.closed.is_some()),
             P2pNetworkSchedulerAction::OutgoingDidConnect { addr, .. } => state
@@ -5,8 +5,8 @@ use openmina_core::{bug_condition, error, warn, Substate};
 use redux::Dispatcher;
 use request::{P2pNetworkKadRequestState, P2pNetworkKadRequestStatus};
 use token::{
-    AuthKind, DiscoveryAlgorithm, IdentifyAlgorithm, MuxKind, PingAlgorithm, Protocol,
-    RpcAlgorithm, StreamKind,
+    AuthKind, AutonatAlgorithm, DiscoveryAlgorithm, IdentifyAlgorithm, MuxKind, PingAlgorithm,
+    Protocol, RpcAlgorithm, StreamKind, WebrtcSignalAlgorithm,
 };
 
 use crate::{
@@ -14,7 +14,7 @@ use crate::{
         incoming::P2pConnectionIncomingAction, outgoing::P2pConnectionOutgoingAction,
         P2pConnectionState,
     },
-    disconnection::P2pDisconnectionAction,
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
     identify::P2pIdentifyAction,
     P2pConfig, P2pPeerStatus, P2pState, PeerId,
 };
@@ -41,8 +41,11 @@ impl P2pNetworkSchedulerState {
                 let p2p_config: &P2pConfig = state.substate()?;
 
                 if let Some(port) = p2p_config.libp2p_port {
-                    dispatcher
-                        .push(P2pNetworkSchedulerEffectfulAction::InterfaceDetected { ip, port });
+                    dispatcher.push(P2pNetworkSchedulerEffectfulAction::InterfaceDetected {
+                        ip,
+                        port,
+                        quic: p2p_config.libp2p_quic,
+                    });
                 }
 
                 Ok(())
@@ -97,34 +100,58 @@ impl P2pNetworkSchedulerState {
                     );
                 };
 
-                let dispatcher = state_context.into_dispatcher();
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
                 if let Some(addr) = addr {
-                    dispatcher.push(P2pNetworkSchedulerEffectfulAction::IncomingDidAccept {
-                        addr,
-                        result,
-                    });
+                    let ip = addr.sock_addr.ip();
+                    let reject_reason = if result.is_err() {
+                        None
+                    } else if p2p_state.trust.is_ip_banned(&ip, meta.time()) {
+                        Some(P2pDisconnectionReason::Banned)
+                    } else if !p2p_state.config.access.is_ip_allowed(&ip) {
+                        Some(P2pDisconnectionReason::NotAllowed)
+                    } else {
+                        None
+                    };
+                    // QUIC connections are secured by the service, no pnet
+                    if !addr.transport.is_quic() {
+                        dispatcher.push(P2pNetworkSchedulerEffectfulAction::IncomingDidAccept {
+                            addr,
+                            result,
+                        });
+                    }
+                    if let Some(reason) = reject_reason {
+                        dispatcher.push(P2pNetworkSchedulerAction::Disconnect { addr, reason });
+                    }
                 }
 
                 Ok(())
             }
-            P2pNetworkSchedulerAction::OutgoingConnect { addr } => {
+            P2pNetworkSchedulerAction::OutgoingConnect { addr, transport } => {
+                // QUIC does authentication and multiplexing itself,
+                // so there is nothing to negotiate
+                let (select_auth, select_mux) = match transport {
+                    ConnectionTransport::Tcp => (
+                        P2pNetworkSelectState::initiator_auth(token::AuthKind::Noise, meta.time()),
+                        P2pNetworkSelectState::initiator_mux(
+                            token::MuxKind::Yamux1_0_0,
+                            meta.time(),
+                        ),
+                    ),
+                    ConnectionTransport::Quic => Default::default(),
+                };
                 scheduler_state.connections.insert(
                     ConnectionAddr {
                         sock_addr: addr,
                         incoming: false,
+                        transport,
                     },
                     P2pNetworkConnectionState {
                         incoming: false,
                         pnet: P2pNetworkPnetState::new(scheduler_state.pnet_key, meta.time()),
-                        select_auth: P2pNetworkSelectState::initiator_auth(
-                            token::AuthKind::Noise,
-                            meta.time(),
-                        ),
+                        select_auth,
                         auth: None,
-                        select_mux: P2pNetworkSelectState::initiator_mux(
-                            token::MuxKind::Yamux1_0_0,
-                            meta.time(),
-                        ),
+                        select_mux,
                         mux: None,
                         streams: BTreeMap::default(),
                         closed: None,
@@ -133,7 +160,8 @@ impl P2pNetworkSchedulerState {
                 );
 
                 let dispatcher = state_context.into_dispatcher();
-                dispatcher.push(P2pNetworkSchedulerEffectfulAction::OutgoingConnect { addr });
+                dispatcher
+                    .push(P2pNetworkSchedulerEffectfulAction::OutgoingConnect { addr, transport });
                 Ok(())
             }
             P2pNetworkSchedulerAction::OutgoingDidConnect { addr, result } => {
@@ -146,6 +174,23 @@ impl P2pNetworkSchedulerState {
                     Ok(()) => {
                         dispatcher
                             .push(P2pNetworkSchedulerEffectfulAction::OutgoingDidConnect { addr });
+
+                        // The address is only known here if the host had to be resolved.
+                        let ip = addr.sock_addr.ip();
+                        let reject_reason = if p2p_state.trust.is_ip_banned(&ip, meta.time()) {
+                            Some(P2pDisconnectionReason::Banned)
+                        } else if p2p_state.peer_with_connection(addr).is_some_and(
+                            |(peer_id, _)| {
+                                !p2p_state.config.access.is_peer_allowed(&peer_id, Some(ip))
+                            },
+                        ) {
+                            Some(P2pDisconnectionReason::NotAllowed)
+                        } else {
+                            None
+                        };
+                        if let Some(reason) = reject_reason {
+                            dispatcher.push(P2pNetworkSchedulerAction::Disconnect { addr, reason });
+                        }
                     }
                     Err(error) => {
                         let Some((peer_id, peer_state)) = p2p_state.peer_with_connection(addr)
@@ -492,6 +537,8 @@ impl P2pNetworkSchedulerState {
                     token::StreamKind::Ping(_) => {}
                     token::StreamKind::Bitswap(_) => {}
                     token::StreamKind::Status(_) => {}
+                    token::StreamKind::Autonat(_) => {}
+                    token::StreamKind::WebrtcSignal(_) => {}
                 }
             }
             None => {}
@@ -602,6 +649,22 @@ impl P2pNetworkSchedulerState {
                             incoming,
                         });
                     }
+                    StreamKind::Autonat(AutonatAlgorithm::Autonat1_0_0) => {
+                        dispatcher.push(P2pNetworkAutonatAction::New {
+                            addr,
+                            peer_id,
+                            stream_id,
+                            incoming,
+                        });
+                    }
+                    StreamKind::WebrtcSignal(WebrtcSignalAlgorithm::WebrtcSignal1_0_0) => {
+                        dispatcher.push(P2pNetworkWebrtcSignalAction::New {
+                            addr,
+                            peer_id,
+                            stream_id,
+                            incoming,
+                        });
+                    }
                 }
             }
             None => {
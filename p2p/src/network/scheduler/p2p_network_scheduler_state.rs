@@ -4,6 +4,7 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+use binprot_derive::{BinProtRead, BinProtWrite};
 use malloc_size_of_derive::MallocSizeOf;
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
@@ -35,15 +36,54 @@ impl<T> DerefMut for StreamState<T> {
     }
 }
 
+/// Transport protocol of the libp2p connection.
+#[derive(
+    Serialize,
+    Deserialize,
+    BinProtWrite,
+    BinProtRead,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+)]
+pub enum ConnectionTransport {
+    /// TCP stream secured with pnet and noise, multiplexed with yamux.
+    #[default]
+    Tcp,
+    /// QUIC (`/quic-v1`) connection, secured with TLS 1.3 and multiplexed
+    /// natively.
+    Quic,
+}
+
+impl ConnectionTransport {
+    pub fn is_quic(&self) -> bool {
+        matches!(self, Self::Quic)
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord, Debug, Clone, Copy)]
 pub struct ConnectionAddr {
     pub sock_addr: SocketAddr,
     pub incoming: bool,
+    #[serde(default)]
+    pub transport: ConnectionTransport,
 }
 
 impl std::fmt::Display for ConnectionAddr {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{} (incoming: {})", self.sock_addr, self.incoming)
+        match self.transport {
+            ConnectionTransport::Tcp => {
+                write!(f, "{} (incoming: {})", self.sock_addr, self.incoming)
+            }
+            ConnectionTransport::Quic => {
+                write!(f, "{} (incoming: {}, quic)", self.sock_addr, self.incoming)
+            }
+        }
     }
 }
 
@@ -58,6 +98,8 @@ pub struct P2pNetworkSchedulerState {
     pub connections: BTreeMap<ConnectionAddr, P2pNetworkConnectionState>,
     pub broadcast_state: P2pNetworkPubsubState,
     pub identify_state: identify::P2pNetworkIdentifyState,
+    pub autonat_state: P2pNetworkAutonatState,
+    pub webrtc_signal_state: P2pNetworkWebrtcSignalState,
     pub discovery_state: Option<P2pNetworkKadState>,
     pub rpc_incoming_streams: StreamState<P2pNetworkRpcState>,
     pub rpc_outgoing_streams: StreamState<P2pNetworkRpcState>,
@@ -80,6 +122,8 @@ impl P2pNetworkSchedulerState {
     pub fn prune_peer_state(&mut self, peer_id: &PeerId) {
         self.broadcast_state.prune_peer_state(peer_id);
         self.identify_state.prune_peer_state(peer_id);
+        self.autonat_state.prune_peer_state(peer_id);
+        self.webrtc_signal_state.prune_peer_state(peer_id);
 
         if let Some(discovery_state) = self.discovery_state.as_mut() {
             discovery_state.streams.remove(peer_id);
@@ -141,27 +185,35 @@ impl P2pNetworkConnectionState {
     }
 
     pub fn noise_state(&self) -> Option<&P2pNetworkNoiseState> {
-        self.auth
-            .as_ref()
-            .map(|P2pNetworkAuthState::Noise(state)| state)
+        match self.auth.as_ref()? {
+            P2pNetworkAuthState::Noise(state) => Some(state),
+            P2pNetworkAuthState::Tls(_) => None,
+        }
     }
 
     pub fn noise_state_mut(&mut self) -> Option<&mut P2pNetworkNoiseState> {
-        self.auth
-            .as_mut()
-            .map(|P2pNetworkAuthState::Noise(state)| state)
+        match self.auth.as_mut()? {
+            P2pNetworkAuthState::Noise(state) => Some(state),
+            P2pNetworkAuthState::Tls(_) => None,
+        }
     }
 
     pub fn yamux_state_mut(&mut self) -> Option<&mut P2pNetworkYamuxState> {
-        self.mux
-            .as_mut()
-            .map(|P2pNetworkConnectionMuxState::Yamux(state)| state)
+        match self.mux.as_mut()? {
+            P2pNetworkConnectionMuxState::Yamux(state) => Some(state),
+            P2pNetworkConnectionMuxState::Quic { .. } => None,
+        }
     }
 
     pub fn yamux_state(&self) -> Option<&P2pNetworkYamuxState> {
-        self.mux
-            .as_ref()
-            .map(|P2pNetworkConnectionMuxState::Yamux(state)| state)
+        match self.mux.as_ref()? {
+            P2pNetworkConnectionMuxState::Yamux(state) => Some(state),
+            P2pNetworkConnectionMuxState::Quic { .. } => None,
+        }
+    }
+
+    pub fn is_quic(&self) -> bool {
+        matches!(self.mux, Some(P2pNetworkConnectionMuxState::Quic { .. }))
     }
 
     pub fn select_state_mut(&mut self, kind: &SelectKind) -> Option<&mut P2pNetworkSelectState> {
@@ -226,17 +278,22 @@ pub enum P2pNetworkConnectionError {
     YamuxOverflow(StreamId),
     #[error("peer should not decrease window size at stream {0}")]
     YamuxBadWindowUpdate(StreamId),
+    #[error("remote peer id mismatch, expected {expected}, got {actual}")]
+    RemotePeerIdMismatch { expected: PeerId, actual: PeerId },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, MallocSizeOf)]
 pub enum P2pNetworkAuthState {
     Noise(P2pNetworkNoiseState),
+    /// QUIC connection, the peer is authenticated by its TLS certificate.
+    Tls(#[ignore_malloc_size_of = "doesn't allocate"] PeerId),
 }
 
 impl P2pNetworkAuthState {
     fn peer_id(&self) -> Option<&PeerId> {
         match self {
             P2pNetworkAuthState::Noise(v) => v.peer_id(),
+            P2pNetworkAuthState::Tls(peer_id) => Some(peer_id),
         }
     }
 }
@@ -244,18 +301,38 @@ impl P2pNetworkAuthState {
 #[derive(Serialize, Deserialize, Debug, Clone, MallocSizeOf)]
 pub enum P2pNetworkConnectionMuxState {
     Yamux(P2pNetworkYamuxState),
+    /// Streams are multiplexed by QUIC itself.
+    Quic {
+        /// Number of streams opened by us. Every stream gets a fresh id, as
+        /// the service maps ids to QUIC streams for the whole connection.
+        opened_streams: StreamId,
+    },
 }
 
 impl P2pNetworkConnectionMuxState {
     pub fn consume(&mut self, len: usize) {
         match self {
             Self::Yamux(state) => state.consume(len),
+            Self::Quic { .. } => {}
         }
     }
 
     fn limit(&self) -> usize {
         match self {
             Self::Yamux(state) => state.limit(),
+            // data of QUIC connections is pushed by the service, flow
+            // control is done by QUIC
+            Self::Quic { .. } => 0,
+        }
+    }
+
+    /// Id of the next stream of the `kind` opened by us.
+    pub fn next_stream_id(&self, kind: YamuxStreamKind, incoming: bool) -> Option<StreamId> {
+        match self {
+            Self::Yamux(state) => state.next_stream_id(kind, incoming),
+            Self::Quic { opened_streams } => opened_streams
+                .checked_mul(2 * YamuxStreamKind::COUNT)?
+                .checked_add(kind.stream_id(incoming)),
         }
     }
 }
@@ -337,9 +414,36 @@ mod measurement {
                     .sum::<usize>()
                 + self.broadcast_state.size_of(ops)
                 + self.identify_state.size_of(ops)
+                + self.autonat_state.size_of(ops)
+                + self.webrtc_signal_state.size_of(ops)
                 + self.discovery_state.size_of(ops)
                 + self.rpc_incoming_streams.size_of(ops)
                 + self.rpc_outgoing_streams.size_of(ops)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quic_stream_ids_are_fresh() {
+        let mut ids = BTreeSet::new();
+        for opened_streams in 0..4 {
+            let mux = P2pNetworkConnectionMuxState::Quic { opened_streams };
+            for incoming in [false, true] {
+                for kind in [
+                    YamuxStreamKind::Rpc,
+                    YamuxStreamKind::Kademlia,
+                    YamuxStreamKind::WebrtcSignal,
+                ] {
+                    let id = mux.next_stream_id(kind, incoming).unwrap();
+                    // streams opened by the remote peer use the other parity
+                    assert_eq!(id % 2 == 0, incoming);
+                    assert!(ids.insert(id), "stream id {id} is reused");
+                }
+            }
+        }
+    }
+}
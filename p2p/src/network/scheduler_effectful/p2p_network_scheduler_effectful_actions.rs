@@ -3,7 +3,9 @@ use std::net::{IpAddr, SocketAddr};
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
-use crate::{ConnectionAddr, P2pNetworkConnectionCloseReason, P2pState};
+use crate::{
+    ConnectionAddr, ConnectionTransport, Data, P2pNetworkConnectionCloseReason, P2pState, StreamId,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
 #[action_event(fields(display(ip), display(listener), display(addr), debug(result), select_kind = debug(kind), display(error)))]
@@ -11,6 +13,7 @@ pub enum P2pNetworkSchedulerEffectfulAction {
     InterfaceDetected {
         ip: IpAddr,
         port: u16,
+        quic: bool,
     },
     IncomingConnectionIsReady {
         listener: SocketAddr,
@@ -24,6 +27,7 @@ pub enum P2pNetworkSchedulerEffectfulAction {
     /// Initialize outgoing connection.
     OutgoingConnect {
         addr: SocketAddr,
+        transport: ConnectionTransport,
     },
     /// Outgoing TCP stream is established.
     OutgoingDidConnect {
@@ -37,6 +41,15 @@ pub enum P2pNetworkSchedulerEffectfulAction {
         addr: ConnectionAddr,
         incoming: bool,
     },
+    /// Send the data in the stream of the QUIC connection.
+    #[action_event(level = trace, fields(display(addr), stream_id, fin, reset))]
+    QuicOutgoingData {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Data,
+        fin: bool,
+        reset: bool,
+    },
     /// Action that initiate the specified peer disconnection.
     Disconnect {
         /// Connection address.
@@ -1,7 +1,7 @@
 use redux::ActionMeta;
 use std::net::SocketAddr;
 
-use crate::{MioCmd, P2pCryptoService, P2pMioService};
+use crate::{ConnectionTransport, MioCmd, P2pCryptoService, P2pMioService};
 
 use super::{super::*, *};
 
@@ -12,10 +12,13 @@ impl P2pNetworkSchedulerEffectfulAction {
         Store::Service: P2pMioService + P2pCryptoService,
     {
         match self {
-            P2pNetworkSchedulerEffectfulAction::InterfaceDetected { ip, port } => {
+            P2pNetworkSchedulerEffectfulAction::InterfaceDetected { ip, port, quic } => {
                 store
                     .service()
                     .send_mio_cmd(MioCmd::ListenOn(SocketAddr::new(ip, port)));
+                if quic {
+                    store.service().send_mio_cmd(MioCmd::QuicListen(port));
+                }
             }
             P2pNetworkSchedulerEffectfulAction::IncomingConnectionIsReady {
                 listener,
@@ -35,8 +38,12 @@ impl P2pNetworkSchedulerEffectfulAction {
                     incoming: true,
                 });
             }
-            P2pNetworkSchedulerEffectfulAction::OutgoingConnect { addr } => {
-                store.service().send_mio_cmd(MioCmd::Connect(addr));
+            P2pNetworkSchedulerEffectfulAction::OutgoingConnect { addr, transport } => {
+                let cmd = match transport {
+                    ConnectionTransport::Tcp => MioCmd::Connect(addr),
+                    ConnectionTransport::Quic => MioCmd::QuicConnect(addr),
+                };
+                store.service().send_mio_cmd(cmd);
             }
             P2pNetworkSchedulerEffectfulAction::OutgoingDidConnect { addr } => {
                 let nonce = store.service().generate_random_nonce();
@@ -64,6 +71,25 @@ impl P2pNetworkSchedulerEffectfulAction {
                     signature,
                 });
             }
+            P2pNetworkSchedulerEffectfulAction::QuicOutgoingData {
+                addr,
+                stream_id,
+                data,
+                fin,
+                reset,
+            } => {
+                let cmd = if reset {
+                    MioCmd::QuicStreamReset { addr, stream_id }
+                } else {
+                    MioCmd::QuicStreamSend {
+                        addr,
+                        stream_id,
+                        data: data.0,
+                        fin,
+                    }
+                };
+                store.service().send_mio_cmd(cmd);
+            }
             P2pNetworkSchedulerEffectfulAction::Disconnect { addr, reason } => {
                 store.service().send_mio_cmd(MioCmd::Disconnect(addr));
                 store.dispatch(P2pNetworkSchedulerAction::Disconnected { addr, reason });
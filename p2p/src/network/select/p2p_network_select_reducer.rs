@@ -1,16 +1,18 @@
 use openmina_core::{bug_condition, error, fuzz_maybe, fuzzed_maybe, Substate};
 use redux::Timestamp;
 use token::{
-    AuthKind, DiscoveryAlgorithm, IdentifyAlgorithm, MuxKind, Protocol, RpcAlgorithm, StreamKind,
-    Token,
+    AuthKind, AutonatAlgorithm, DiscoveryAlgorithm, IdentifyAlgorithm, MuxKind, Protocol,
+    RpcAlgorithm, StreamKind, Token, WebrtcSignalAlgorithm,
 };
 
 use crate::{
+    bandwidth::P2pBandwidthAction,
     fuzzer::{mutate_select_authentication, mutate_select_multiplexing, mutate_select_stream},
     network::identify::P2pNetworkIdentifyStreamAction,
-    ConnectionAddr, Data, P2pNetworkKademliaStreamAction, P2pNetworkNoiseAction,
-    P2pNetworkPnetAction, P2pNetworkPubsubAction, P2pNetworkRpcAction, P2pNetworkSchedulerAction,
-    P2pNetworkSchedulerState, P2pNetworkYamuxAction, P2pState, YamuxFlags,
+    ConnectionAddr, Data, P2pNetworkAutonatAction, P2pNetworkKademliaStreamAction,
+    P2pNetworkNoiseAction, P2pNetworkPnetAction, P2pNetworkPubsubAction, P2pNetworkRpcAction,
+    P2pNetworkSchedulerAction, P2pNetworkSchedulerState, P2pNetworkWebrtcSignalAction,
+    P2pNetworkYamuxAction, P2pState, YamuxFlags,
 };
 
 use self::{p2p_network_select_state::P2pNetworkSelectStateInner, token::ParseTokenError};
@@ -100,6 +102,16 @@ impl P2pNetworkSelectState {
                     .and_then(|conn| conn.select_state(&select_kind))
                     .ok_or("Select state not found for incoming data")?;
 
+                if let (SelectKind::Stream(peer_id, _), Some(kind)) =
+                    (select_kind, select_state.bandwidth_kind())
+                {
+                    dispatcher.push(P2pBandwidthAction::Received {
+                        peer_id,
+                        kind,
+                        bytes: data.len() as u64,
+                    });
+                }
+
                 if let P2pNetworkSelectStateInner::Error(error) = &select_state.inner {
                     dispatcher.push(P2pNetworkSchedulerAction::SelectError {
                         addr,
@@ -357,7 +369,9 @@ impl P2pNetworkSelectState {
                             | token::StreamKind::Identify(_)
                             | token::StreamKind::Ping(_)
                             | token::StreamKind::Bitswap(_)
-                            | token::StreamKind::Status(_),
+                            | token::StreamKind::Status(_)
+                            | token::StreamKind::Autonat(_)
+                            | token::StreamKind::WebrtcSignal(_),
                         ) => token::Token::Protocol(protocol),
                     };
                     let negotiated = if let token::Token::Protocol(p) = &reply {
@@ -490,6 +504,38 @@ impl P2pNetworkSelectState {
                             data,
                         });
                     }
+                    StreamKind::Autonat(AutonatAlgorithm::Autonat1_0_0) => {
+                        if !fin {
+                            dispatcher.push(P2pNetworkAutonatAction::IncomingData {
+                                addr,
+                                peer_id,
+                                stream_id,
+                                data,
+                            });
+                        } else {
+                            dispatcher.push(P2pNetworkAutonatAction::RemoteClose {
+                                addr,
+                                peer_id,
+                                stream_id,
+                            });
+                        }
+                    }
+                    StreamKind::WebrtcSignal(WebrtcSignalAlgorithm::WebrtcSignal1_0_0) => {
+                        if !fin {
+                            dispatcher.push(P2pNetworkWebrtcSignalAction::IncomingData {
+                                addr,
+                                peer_id,
+                                stream_id,
+                                data,
+                            });
+                        } else {
+                            dispatcher.push(P2pNetworkWebrtcSignalAction::RemoteClose {
+                                addr,
+                                peer_id,
+                                stream_id,
+                            });
+                        }
+                    }
                     _ => error!(time;
                         "trying to negotiate unimplemented stream kind {kind:?}"
                     ),
@@ -5,7 +5,7 @@ use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 use token::Token;
 
-use crate::{ConnectionAddr, Data, P2pTimeouts};
+use crate::{bandwidth::P2pBandwidthKind, ConnectionAddr, Data, P2pTimeouts};
 
 use super::*;
 
@@ -78,6 +78,14 @@ impl P2pNetworkSelectState {
         matches!(&self.inner, P2pNetworkSelectStateInner::Responder)
     }
 
+    /// Kind of the traffic of the negotiated stream, to account its bytes to.
+    pub fn bandwidth_kind(&self) -> Option<P2pBandwidthKind> {
+        match &self.negotiated {
+            Some(Some(token::Protocol::Stream(kind))) => P2pBandwidthKind::from_stream_kind(kind),
+            _ => None,
+        }
+    }
+
     /// Propagates incoming data to corresponding action
     #[allow(dead_code)]
     pub(super) fn forward_incoming_data(
@@ -69,6 +69,12 @@ impl Token {
             BroadcastAlgorithm::Meshsub1_1_0,
         ))),
         Token::Protocol(Protocol::Stream(StreamKind::Rpc(RpcAlgorithm::Rpc0_0_1))),
+        Token::Protocol(Protocol::Stream(StreamKind::Autonat(
+            AutonatAlgorithm::Autonat1_0_0,
+        ))),
+        Token::Protocol(Protocol::Stream(StreamKind::WebrtcSignal(
+            WebrtcSignalAlgorithm::WebrtcSignal1_0_0,
+        ))),
     ];
 }
 
@@ -149,6 +155,8 @@ pub enum StreamKind {
     Discovery(DiscoveryAlgorithm),
     Broadcast(BroadcastAlgorithm),
     Rpc(RpcAlgorithm),
+    Autonat(AutonatAlgorithm),
+    WebrtcSignal(WebrtcSignalAlgorithm),
 }
 
 impl malloc_size_of::MallocSizeOf for StreamKind {
@@ -167,6 +175,8 @@ impl StreamKind {
             Self::Discovery(v) => v.name(),
             Self::Broadcast(v) => v.name(),
             Self::Rpc(v) => v.name(),
+            Self::Autonat(v) => v.name(),
+            Self::WebrtcSignal(v) => v.name(),
         }
     }
 
@@ -179,6 +189,8 @@ impl StreamKind {
             Self::Discovery(v) => v.name_str(),
             Self::Broadcast(v) => v.name_str(),
             Self::Rpc(v) => v.name_str(),
+            Self::Autonat(v) => v.name_str(),
+            Self::WebrtcSignal(v) => v.name_str(),
         }
     }
 }
@@ -346,6 +358,44 @@ impl RpcAlgorithm {
     }
 }
 
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum AutonatAlgorithm {
+    Autonat1_0_0,
+}
+
+impl AutonatAlgorithm {
+    pub const fn name(&self) -> &'static [u8] {
+        match self {
+            Self::Autonat1_0_0 => b"\x16/libp2p/autonat/1.0.0\n",
+        }
+    }
+
+    pub const fn name_str(&self) -> &'static str {
+        match self {
+            Self::Autonat1_0_0 => "/libp2p/autonat/1.0.0",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum WebrtcSignalAlgorithm {
+    WebrtcSignal1_0_0,
+}
+
+impl WebrtcSignalAlgorithm {
+    pub const fn name(&self) -> &'static [u8] {
+        match self {
+            Self::WebrtcSignal1_0_0 => b"\x1e/openmina/webrtc-signal/1.0.0\n",
+        }
+    }
+
+    pub const fn name_str(&self) -> &'static str {
+        match self {
+            Self::WebrtcSignal1_0_0 => "/openmina/webrtc-signal/1.0.0",
+        }
+    }
+}
+
 #[derive(Default, Serialize, Deserialize, Debug, Clone, MallocSizeOf)]
 pub struct State {
     pub buffer: Vec<u8>,
@@ -0,0 +1,11 @@
+mod p2p_network_webrtc_signal_protocol;
+pub use self::p2p_network_webrtc_signal_protocol::*;
+
+mod p2p_network_webrtc_signal_actions;
+pub use self::p2p_network_webrtc_signal_actions::*;
+
+mod p2p_network_webrtc_signal_state;
+pub use self::p2p_network_webrtc_signal_state::*;
+
+#[cfg(feature = "p2p-libp2p")]
+mod p2p_network_webrtc_signal_reducer;
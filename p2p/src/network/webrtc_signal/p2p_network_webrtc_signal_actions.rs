@@ -0,0 +1,97 @@
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{ConnectionAddr, Data, P2pState, PeerId, StreamId};
+
+use super::P2pNetworkWebrtcSignalMessage;
+
+/// WebRTC signaling stream related actions.
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(display(addr), display(peer_id), stream_id, incoming, debug(message)))]
+pub enum P2pNetworkWebrtcSignalAction {
+    /// Creates a new stream state.
+    New {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        incoming: bool,
+    },
+    /// Handles incoming data from the stream.
+    #[action_event(level = trace)]
+    IncomingData {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        data: Data,
+    },
+    /// Sends the message to the peer over the outgoing stream.
+    OutgoingMessage {
+        peer_id: PeerId,
+        message: P2pNetworkWebrtcSignalMessage,
+    },
+    /// Start closing the stream (send FIN).
+    Close {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+    },
+    /// Remote peer sent FIN to close the stream.
+    RemoteClose {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+    },
+    /// Removes the closed stream from the state.
+    Prune {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+    },
+}
+
+impl P2pNetworkWebrtcSignalAction {
+    pub fn peer_id(&self) -> &PeerId {
+        match self {
+            Self::New { peer_id, .. }
+            | Self::IncomingData { peer_id, .. }
+            | Self::OutgoingMessage { peer_id, .. }
+            | Self::Close { peer_id, .. }
+            | Self::RemoteClose { peer_id, .. }
+            | Self::Prune { peer_id, .. } => peer_id,
+        }
+    }
+}
+
+impl From<P2pNetworkWebrtcSignalAction> for crate::P2pAction {
+    fn from(a: P2pNetworkWebrtcSignalAction) -> Self {
+        Self::Network(a.into())
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNetworkWebrtcSignalAction {
+    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+        let signal_state = &state.network.scheduler.webrtc_signal_state;
+        match self {
+            P2pNetworkWebrtcSignalAction::New {
+                peer_id, stream_id, ..
+            } => signal_state.find_stream(peer_id, stream_id).is_none(),
+            P2pNetworkWebrtcSignalAction::IncomingData {
+                peer_id, stream_id, ..
+            } => signal_state
+                .find_stream(peer_id, stream_id)
+                .is_some_and(|stream| stream.incoming),
+            P2pNetworkWebrtcSignalAction::OutgoingMessage { peer_id, .. } => {
+                signal_state.find_outgoing_stream(peer_id).is_some()
+            }
+            P2pNetworkWebrtcSignalAction::Close {
+                peer_id, stream_id, ..
+            }
+            | P2pNetworkWebrtcSignalAction::RemoteClose {
+                peer_id, stream_id, ..
+            }
+            | P2pNetworkWebrtcSignalAction::Prune {
+                peer_id, stream_id, ..
+            } => signal_state.find_stream(peer_id, stream_id).is_some(),
+        }
+    }
+}
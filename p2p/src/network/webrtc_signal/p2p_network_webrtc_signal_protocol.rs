@@ -0,0 +1,149 @@
+use binprot::{BinProtRead, BinProtWrite};
+use binprot_derive::{BinProtRead, BinProtWrite};
+use serde::{Deserialize, Serialize};
+
+use crate::channels::{
+    signaling::{discovery::SignalingDiscoveryChannelMsg, exchange::SignalingExchangeChannelMsg},
+    ChannelId, ChannelMsg,
+};
+
+/// Message of the `/openmina/webrtc-signal/1.0.0` protocol.
+///
+/// Carries messages of the signaling channels between libp2p peers, so that
+/// a node connected to both WebRTC and libp2p peers can relay SDP offers
+/// and answers between them. On the wire each message is prefixed with its
+/// length encoded as unsigned varint.
+#[derive(BinProtWrite, BinProtRead, Serialize, Deserialize, Debug, Clone)]
+pub enum P2pNetworkWebrtcSignalMessage {
+    Discovery(SignalingDiscoveryChannelMsg),
+    Exchange(SignalingExchangeChannelMsg),
+}
+
+impl P2pNetworkWebrtcSignalMessage {
+    /// Maximal size of the encoded message, same as for the signaling
+    /// channels over WebRTC.
+    pub fn size_limit() -> usize {
+        ChannelId::SignalingDiscovery
+            .max_msg_size()
+            .max(ChannelId::SignalingExchange.max_msg_size())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut message = Vec::new();
+        self.binprot_write(&mut message)
+            .expect("writing to vec can't fail");
+        let mut encoded = Vec::with_capacity(message.len() + 4);
+        prost::encode_length_delimiter(message.len(), &mut encoded).expect("vec grows as needed");
+        encoded.extend_from_slice(&message);
+        encoded
+    }
+
+    /// Takes the first message from the buffer, if it is received
+    /// completely.
+    pub fn take_from(buffer: &mut Vec<u8>) -> Result<Option<Self>, String> {
+        let mut buf = buffer.as_slice();
+        let len = match prost::decode_length_delimiter(&mut buf) {
+            Ok(len) => len,
+            // length prefix itself is not received yet
+            Err(_) if buffer.len() < 10 && buffer.iter().all(|b| b & 0x80 != 0) => return Ok(None),
+            Err(err) => return Err(err.to_string()),
+        };
+        let limit = Self::size_limit();
+        if len > limit {
+            return Err(format!("message is too long: {len} exceeds {limit}"));
+        }
+        if buf.len() < len {
+            return Ok(None);
+        }
+
+        let prefix_len = buffer.len() - buf.len();
+        let mut message = &buf[..len];
+        let message = Self::binprot_read(&mut message).map_err(|err| err.to_string())?;
+        buffer.drain(..prefix_len + len);
+        Ok(Some(message))
+    }
+}
+
+impl TryFrom<ChannelMsg> for P2pNetworkWebrtcSignalMessage {
+    type Error = ChannelMsg;
+
+    fn try_from(value: ChannelMsg) -> Result<Self, Self::Error> {
+        match value {
+            ChannelMsg::SignalingDiscovery(msg) => Ok(Self::Discovery(msg)),
+            ChannelMsg::SignalingExchange(msg) => Ok(Self::Exchange(msg)),
+            msg => Err(msg),
+        }
+    }
+}
+
+impl From<P2pNetworkWebrtcSignalMessage> for ChannelMsg {
+    fn from(value: P2pNetworkWebrtcSignalMessage) -> Self {
+        match value {
+            P2pNetworkWebrtcSignalMessage::Discovery(msg) => Self::SignalingDiscovery(msg),
+            P2pNetworkWebrtcSignalMessage::Exchange(msg) => Self::SignalingExchange(msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::identity::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn take_from_waits_for_complete_messages() {
+        let target_public_key = SecretKey::rand().public_key();
+        let messages = [
+            P2pNetworkWebrtcSignalMessage::Exchange(SignalingExchangeChannelMsg::GetNext),
+            P2pNetworkWebrtcSignalMessage::Discovery(SignalingDiscoveryChannelMsg::Discovered {
+                target_public_key: target_public_key.clone(),
+            }),
+        ];
+        let bytes = messages
+            .iter()
+            .flat_map(P2pNetworkWebrtcSignalMessage::encode)
+            .collect::<Vec<_>>();
+
+        let mut buffer = Vec::new();
+        let mut received = Vec::new();
+        for chunk in bytes.chunks(3) {
+            buffer.extend_from_slice(chunk);
+            while let Some(message) = P2pNetworkWebrtcSignalMessage::take_from(&mut buffer).unwrap()
+            {
+                received.push(message);
+            }
+        }
+        assert!(buffer.is_empty());
+        assert!(matches!(
+            received.as_slice(),
+            [
+                P2pNetworkWebrtcSignalMessage::Exchange(SignalingExchangeChannelMsg::GetNext),
+                P2pNetworkWebrtcSignalMessage::Discovery(SignalingDiscoveryChannelMsg::Discovered {
+                    target_public_key: key,
+                }),
+            ] if key == &target_public_key
+        ));
+    }
+
+    #[test]
+    fn take_from_rejects_oversized_message() {
+        let mut buffer = Vec::new();
+        prost::encode_length_delimiter(
+            P2pNetworkWebrtcSignalMessage::size_limit() + 1,
+            &mut buffer,
+        )
+        .unwrap();
+        assert!(P2pNetworkWebrtcSignalMessage::take_from(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn channel_msg_conversion() {
+        let msg = ChannelMsg::SignalingExchange(SignalingExchangeChannelMsg::GetNext);
+        let message = P2pNetworkWebrtcSignalMessage::try_from(msg).unwrap();
+        assert!(matches!(
+            ChannelMsg::from(message),
+            ChannelMsg::SignalingExchange(SignalingExchangeChannelMsg::GetNext)
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+use openmina_core::{bug_condition, warn, Substate, SubstateAccess};
+use redux::ActionWithMeta;
+
+use crate::{
+    channels::{
+        signaling::{
+            discovery::P2pChannelsSignalingDiscoveryAction,
+            exchange::P2pChannelsSignalingExchangeAction,
+        },
+        P2pChannelsMessageReceivedAction,
+    },
+    Data, P2pNetworkSchedulerState, P2pNetworkYamuxAction, P2pState, YamuxFlags,
+};
+
+use super::{
+    P2pNetworkWebrtcSignalAction, P2pNetworkWebrtcSignalMessage, P2pNetworkWebrtcSignalState,
+    P2pNetworkWebrtcSignalStreamState,
+};
+
+impl P2pNetworkWebrtcSignalState {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pNetworkSchedulerState>,
+        action: ActionWithMeta<P2pNetworkWebrtcSignalAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let signal_state = &mut state_context.get_substate_mut()?.webrtc_signal_state;
+
+        match action {
+            P2pNetworkWebrtcSignalAction::New {
+                addr,
+                peer_id,
+                stream_id,
+                incoming,
+            } => {
+                signal_state.streams.entry(peer_id).or_default().insert(
+                    stream_id,
+                    P2pNetworkWebrtcSignalStreamState {
+                        addr,
+                        incoming,
+                        buffer: Vec::new(),
+                    },
+                );
+                if incoming {
+                    let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                    let p2p_state: &P2pState = state.substate()?;
+                    let config = &p2p_state.config;
+                    // peers may open the stream even if we don't advertise it
+                    if !config.libp2p_webrtc_signal_enabled()
+                        || !config
+                            .access
+                            .is_peer_allowed(&peer_id, Some(addr.sock_addr.ip()))
+                    {
+                        dispatcher.push(P2pNetworkWebrtcSignalAction::Close {
+                            addr,
+                            peer_id,
+                            stream_id,
+                        });
+                    }
+                    return Ok(());
+                }
+
+                // We can send signaling messages to the peer from now on.
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pChannelsSignalingDiscoveryAction::Ready { peer_id });
+                dispatcher.push(P2pChannelsSignalingExchangeAction::Ready { peer_id });
+                Ok(())
+            }
+            P2pNetworkWebrtcSignalAction::IncomingData {
+                addr,
+                peer_id,
+                stream_id,
+                data,
+            } => {
+                let stream = signal_state
+                    .find_stream_mut(&peer_id, &stream_id)
+                    .ok_or_else(|| {
+                        format!("WebRTC signal stream not found for {peer_id}/{stream_id}")
+                    })?;
+                stream.buffer.extend_from_slice(&data);
+
+                let mut messages = Vec::new();
+                let error = loop {
+                    match P2pNetworkWebrtcSignalMessage::take_from(&mut stream.buffer) {
+                        Ok(Some(message)) => messages.push(message),
+                        Ok(None) => break None,
+                        Err(error) => break Some(error),
+                    }
+                };
+
+                let dispatcher = state_context.into_dispatcher();
+                for message in messages {
+                    dispatcher.push(P2pChannelsMessageReceivedAction {
+                        peer_id,
+                        message: Box::new(message.into()),
+                    });
+                }
+                if let Some(error) = error {
+                    warn!(meta.time(); summary = "error reading WebRTC signal stream", peer_id = display(peer_id), error = display(&error));
+                    dispatcher.push(P2pNetworkWebrtcSignalAction::Close {
+                        addr,
+                        peer_id,
+                        stream_id,
+                    });
+                }
+                Ok(())
+            }
+            P2pNetworkWebrtcSignalAction::OutgoingMessage { peer_id, message } => {
+                let Some((&stream_id, stream)) = signal_state.find_outgoing_stream(&peer_id) else {
+                    bug_condition!("no outgoing WebRTC signal stream for {peer_id}");
+                    return Ok(());
+                };
+                let addr = stream.addr;
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkYamuxAction::OutgoingData {
+                    addr,
+                    stream_id,
+                    data: Data::from(message.encode()),
+                    flags: Default::default(),
+                });
+                Ok(())
+            }
+            P2pNetworkWebrtcSignalAction::RemoteClose {
+                addr,
+                peer_id,
+                stream_id,
+            } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkWebrtcSignalAction::Close {
+                    addr,
+                    peer_id,
+                    stream_id,
+                });
+                Ok(())
+            }
+            P2pNetworkWebrtcSignalAction::Close {
+                addr,
+                peer_id,
+                stream_id,
+            } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkYamuxAction::OutgoingData {
+                    addr,
+                    stream_id,
+                    data: Data::empty(),
+                    flags: YamuxFlags::FIN,
+                });
+                dispatcher.push(P2pNetworkWebrtcSignalAction::Prune {
+                    addr,
+                    peer_id,
+                    stream_id,
+                });
+                Ok(())
+            }
+            P2pNetworkWebrtcSignalAction::Prune {
+                peer_id, stream_id, ..
+            } => {
+                if let Some(streams) = signal_state.streams.get_mut(&peer_id) {
+                    streams.remove(&stream_id);
+                }
+                Ok(())
+            }
+        }
+    }
+}
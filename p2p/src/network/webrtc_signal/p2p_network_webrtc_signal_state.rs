@@ -0,0 +1,54 @@
+use malloc_size_of_derive::MallocSizeOf;
+use serde::{Deserialize, Serialize};
+
+use crate::{network::scheduler::StreamState, ConnectionAddr, PeerId, StreamId};
+
+/// State of the `/openmina/webrtc-signal/1.0.0` streams.
+///
+/// Each side opens its own stream and only writes to it, messages from the
+/// remote peer are read from the stream it opened.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, MallocSizeOf)]
+pub struct P2pNetworkWebrtcSignalState {
+    pub streams: StreamState<P2pNetworkWebrtcSignalStreamState>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, MallocSizeOf)]
+pub struct P2pNetworkWebrtcSignalStreamState {
+    pub addr: ConnectionAddr,
+    pub incoming: bool,
+    /// Received bytes of the messages that are not complete yet.
+    pub buffer: Vec<u8>,
+}
+
+impl P2pNetworkWebrtcSignalState {
+    pub fn find_stream(
+        &self,
+        peer_id: &PeerId,
+        stream_id: &StreamId,
+    ) -> Option<&P2pNetworkWebrtcSignalStreamState> {
+        self.streams.get(peer_id)?.get(stream_id)
+    }
+
+    pub fn find_stream_mut(
+        &mut self,
+        peer_id: &PeerId,
+        stream_id: &StreamId,
+    ) -> Option<&mut P2pNetworkWebrtcSignalStreamState> {
+        self.streams.get_mut(peer_id)?.get_mut(stream_id)
+    }
+
+    /// Stream opened by us, the one we send messages to the peer with.
+    pub fn find_outgoing_stream(
+        &self,
+        peer_id: &PeerId,
+    ) -> Option<(&StreamId, &P2pNetworkWebrtcSignalStreamState)> {
+        self.streams
+            .get(peer_id)?
+            .iter()
+            .find(|(_, stream)| !stream.incoming)
+    }
+
+    pub fn prune_peer_state(&mut self, peer_id: &PeerId) {
+        self.streams.remove(peer_id);
+    }
+}
@@ -58,12 +58,20 @@ impl From<P2pNetworkYamuxAction> for crate::P2pAction {
 
 impl redux::EnablingCondition<P2pState> for P2pNetworkYamuxAction {
     fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
-        let Some(yamux_state) = state
-            .network
-            .scheduler
-            .connection_state(self.addr())
-            .and_then(|state| state.yamux_state())
-        else {
+        let Some(connection_state) = state.network.scheduler.connection_state(self.addr()) else {
+            return false;
+        };
+        if connection_state.is_quic() {
+            // forwarded to QUIC, see `P2pNetworkQuicAction`
+            return match self {
+                P2pNetworkYamuxAction::OutgoingData { stream_id, .. } => {
+                    connection_state.streams.contains_key(stream_id)
+                }
+                P2pNetworkYamuxAction::OpenStream { .. } => true,
+                _ => false,
+            };
+        }
+        let Some(yamux_state) = connection_state.yamux_state() else {
             return false;
         };
 
@@ -3,9 +3,10 @@ use std::collections::VecDeque;
 use openmina_core::{bug_condition, fuzz_maybe, fuzzed_maybe, Substate, SubstateAccess};
 
 use crate::{
+    bandwidth::P2pBandwidthAction,
     yamux::p2p_network_yamux_state::{YamuxFrame, YamuxFrameInner},
-    Data, Limit, P2pLimits, P2pNetworkAuthState, P2pNetworkConnectionError,
-    P2pNetworkConnectionMuxState, P2pNetworkNoiseAction, P2pNetworkSchedulerAction,
+    Data, Limit, P2pLimits, P2pNetworkConnectionError, P2pNetworkConnectionMuxState,
+    P2pNetworkNoiseAction, P2pNetworkQuicAction, P2pNetworkSchedulerAction,
     P2pNetworkSchedulerState, P2pNetworkSelectAction, P2pNetworkStreamState, SelectKind,
 };
 
@@ -53,10 +54,41 @@ impl P2pNetworkYamuxState {
             .ok_or_else(|| format!("Connection not found for action: {action:?}"))
             .inspect_err(|e| bug_condition!("{}", e))?;
 
-        let P2pNetworkConnectionMuxState::Yamux(yamux_state) = connection_state
-            .mux
-            .as_mut()
-            .ok_or_else(|| format!("Invalid yamux state for action: {action:?}"))?;
+        // accounted before yamux and QUIC connections part ways
+        let sent = match &action {
+            P2pNetworkYamuxAction::OutgoingData {
+                stream_id, data, ..
+            } => {
+                let kind = connection_state
+                    .streams
+                    .get(stream_id)
+                    .and_then(|stream| stream.select.bandwidth_kind());
+                connection_state
+                    .peer_id()
+                    .copied()
+                    .zip(kind)
+                    .map(|(peer_id, kind)| P2pBandwidthAction::Sent {
+                        peer_id,
+                        kind,
+                        bytes: data.len() as u64,
+                    })
+            }
+            _ => None,
+        };
+
+        if connection_state.is_quic() {
+            let dispatcher = state_context.into_dispatcher();
+            if let Some(sent) = sent {
+                dispatcher.push(sent);
+            }
+            Self::forward_to_quic(dispatcher, action);
+            return Ok(());
+        }
+
+        let Some(P2pNetworkConnectionMuxState::Yamux(yamux_state)) = connection_state.mux.as_mut()
+        else {
+            return Err(format!("Invalid yamux state for action: {action:?}"));
+        };
 
         if yamux_state.terminated.is_some() {
             return Ok(());
@@ -102,6 +134,9 @@ impl P2pNetworkYamuxState {
                 };
 
                 let dispatcher = state_context.into_dispatcher();
+                if let Some(sent) = sent {
+                    dispatcher.push(sent);
+                }
                 dispatcher.push(P2pNetworkYamuxAction::OutgoingFrame { addr, frame });
 
                 Ok(())
@@ -184,11 +219,7 @@ impl P2pNetworkYamuxState {
                     .and_then(|yamux_state| yamux_state.streams.get(&frame.stream_id))
                     .ok_or_else(|| format!("Stream with id {} not found for `P2pNetworkYamuxAction::IncomingFrame`", frame.stream_id))?;
 
-                let peer_id = match connection_state
-                    .auth
-                    .as_ref()
-                    .and_then(|P2pNetworkAuthState::Noise(noise)| noise.peer_id())
-                {
+                let peer_id = match connection_state.peer_id() {
                     Some(peer_id) => *peer_id,
                     None => return Ok(()),
                 };
@@ -364,11 +395,7 @@ impl P2pNetworkYamuxState {
                     P2pNetworkStreamState::new(stream_kind, meta.time()),
                 );
 
-                let peer_id = match connection_state
-                    .auth
-                    .as_ref()
-                    .and_then(|P2pNetworkAuthState::Noise(noise)| noise.peer_id())
-                {
+                let peer_id = match connection_state.peer_id() {
                     Some(peer_id) => *peer_id,
                     None => return Ok(()),
                 };
@@ -383,4 +410,45 @@ impl P2pNetworkYamuxState {
             }
         }
     }
+
+    /// Streams of QUIC connections are multiplexed natively, so stream
+    /// operations requested by the upper layers are passed to QUIC.
+    fn forward_to_quic<State, Action>(
+        dispatcher: &mut redux::Dispatcher<Action, State>,
+        action: P2pNetworkYamuxAction,
+    ) where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        match action {
+            P2pNetworkYamuxAction::OutgoingData {
+                addr,
+                stream_id,
+                data,
+                flags,
+            } => {
+                dispatcher.push(P2pNetworkQuicAction::OutgoingData {
+                    addr,
+                    stream_id,
+                    data,
+                    fin: flags.contains(YamuxFlags::FIN),
+                    reset: flags.contains(YamuxFlags::RST),
+                });
+            }
+            P2pNetworkYamuxAction::OpenStream {
+                addr,
+                stream_id,
+                stream_kind,
+            } => {
+                dispatcher.push(P2pNetworkQuicAction::OpenStream {
+                    addr,
+                    stream_id,
+                    stream_kind,
+                });
+            }
+            action => {
+                bug_condition!("unexpected yamux action for QUIC connection: {action:?}");
+            }
+        }
+    }
 }
@@ -402,9 +402,13 @@ pub enum YamuxStreamKind {
     Gossipsub,
     Kademlia,
     Identify,
+    Autonat,
+    WebrtcSignal,
 }
 
 impl YamuxStreamKind {
+    pub const COUNT: StreamId = Self::WebrtcSignal as StreamId + 1;
+
     pub fn stream_id(self, incoming: bool) -> StreamId {
         (self as StreamId) * 2 + 1 + (incoming as StreamId)
     }
@@ -0,0 +1,313 @@
+use std::{fmt, net::IpAddr, str::FromStr};
+
+use multiaddr::{Multiaddr, Protocol};
+use serde::{Deserialize, Serialize};
+
+use crate::PeerId;
+
+/// Range of IP addresses, e.g. `10.0.0.0/8` or `fd00::/8`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct P2pIpCidr {
+    pub addr: IpAddr,
+    pub prefix_len: u8,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum P2pIpCidrParseError {
+    #[error("invalid ip address: {0}")]
+    Addr(String),
+    #[error("invalid prefix length: {0}")]
+    PrefixLen(String),
+}
+
+impl P2pIpCidr {
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr.to_canonical(), ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for P2pIpCidr {
+    type Err = P2pIpCidrParseError;
+
+    /// Parses `<ip>/<prefix_len>`. Plain ip address is treated as a range
+    /// containing only that address.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| P2pIpCidrParseError::Addr(addr.to_owned()))?
+            .to_canonical();
+        let max_prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            None => max_prefix_len,
+            Some(s) => s
+                .parse::<u8>()
+                .ok()
+                .filter(|len| *len <= max_prefix_len)
+                .ok_or_else(|| P2pIpCidrParseError::PrefixLen(s.to_owned()))?,
+        };
+        Ok(Self { addr, prefix_len })
+    }
+}
+
+impl fmt::Display for P2pIpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Entry of the allow or deny list.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum P2pAccessRule {
+    PeerId(PeerId),
+    Cidr(P2pIpCidr),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("expected peer id or ip address range, got: {0}")]
+pub struct P2pAccessRuleParseError(String);
+
+impl FromStr for P2pAccessRule {
+    type Err = P2pAccessRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(peer_id) = s.parse::<PeerId>() {
+            return Ok(Self::PeerId(peer_id));
+        }
+        s.parse::<P2pIpCidr>()
+            .map(Self::Cidr)
+            .map_err(|_| P2pAccessRuleParseError(s.to_owned()))
+    }
+}
+
+impl fmt::Display for P2pAccessRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PeerId(peer_id) => peer_id.fmt(f),
+            Self::Cidr(cidr) => cidr.fmt(f),
+        }
+    }
+}
+
+/// Restricts which peers the node may connect to and accept connections from.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pAccessConfig {
+    /// If not empty, only peers matching one of these rules are allowed.
+    pub allow: Vec<P2pAccessRule>,
+    /// Peers matching one of these rules are never allowed.
+    pub deny: Vec<P2pAccessRule>,
+    /// Only peers from the allow list are accepted, and neither our own
+    /// addresses nor other peers are advertised to anyone.
+    pub isolate: bool,
+}
+
+impl P2pAccessConfig {
+    /// Whether only peers matching the allow list are allowed.
+    pub fn is_restricted(&self) -> bool {
+        self.isolate || !self.allow.is_empty()
+    }
+
+    /// Whether connection from the address may be accepted, before the
+    /// peer id of the remote side is known.
+    pub fn is_ip_allowed(&self, ip: &IpAddr) -> bool {
+        if self.is_ip_denied(ip) {
+            return false;
+        }
+        !self.is_restricted()
+            || self.allow.iter().any(|rule| match rule {
+                // peer id will be checked once the connection is authenticated
+                P2pAccessRule::PeerId(_) => true,
+                P2pAccessRule::Cidr(cidr) => cidr.contains(ip),
+            })
+    }
+
+    /// Whether the peer connected from `ip` (if known) is allowed.
+    pub fn is_peer_allowed(&self, peer_id: &PeerId, ip: Option<IpAddr>) -> bool {
+        self.is_peer_with_ips_allowed(peer_id, ip.as_slice())
+    }
+
+    /// Whether the peer advertised with the addresses is allowed.
+    pub fn is_peer_with_addrs_allowed(&self, peer_id: &PeerId, addrs: &[Multiaddr]) -> bool {
+        let ips = addrs
+            .iter()
+            .filter_map(|addr| {
+                addr.iter().find_map(|protocol| match protocol {
+                    Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+                    Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+                    _ => None,
+                })
+            })
+            .collect::<Vec<_>>();
+        self.is_peer_with_ips_allowed(peer_id, &ips)
+    }
+
+    /// Whether the peer reachable at the `ips` is allowed. If none of its
+    /// addresses is known yet, with a restricted allow list the peer is
+    /// only allowed by its peer id.
+    pub fn is_peer_with_ips_allowed(&self, peer_id: &PeerId, ips: &[IpAddr]) -> bool {
+        if self.is_peer_denied(peer_id) || ips.iter().any(|ip| self.is_ip_denied(ip)) {
+            return false;
+        }
+        !self.is_restricted()
+            || self.allow.iter().any(|rule| match rule {
+                P2pAccessRule::PeerId(id) => id == peer_id,
+                P2pAccessRule::Cidr(cidr) => ips.iter().any(|ip| cidr.contains(ip)),
+            })
+    }
+
+    fn is_peer_denied(&self, peer_id: &PeerId) -> bool {
+        self.deny
+            .iter()
+            .any(|rule| matches!(rule, P2pAccessRule::PeerId(id) if id == peer_id))
+    }
+
+    fn is_ip_denied(&self, ip: &IpAddr) -> bool {
+        self.deny
+            .iter()
+            .any(|rule| matches!(rule, P2pAccessRule::Cidr(cidr) if cidr.contains(ip)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn peer(n: u8) -> PeerId {
+        PeerId::from_bytes([n; 32])
+    }
+
+    #[test]
+    fn cidr_contains() {
+        let cidr: P2pIpCidr = "10.1.0.0/16".parse().unwrap();
+        assert!(cidr.contains(&ip("10.1.2.3")));
+        assert!(cidr.contains(&ip("::ffff:10.1.2.3")));
+        assert!(!cidr.contains(&ip("10.2.0.1")));
+        assert!(!cidr.contains(&ip("fd00::1")));
+
+        let cidr: P2pIpCidr = "fd00::/8".parse().unwrap();
+        assert!(cidr.contains(&ip("fd12::1")));
+        assert!(!cidr.contains(&ip("fe80::1")));
+
+        let cidr: P2pIpCidr = "1.2.3.4".parse().unwrap();
+        assert_eq!(cidr.prefix_len, 32);
+        assert!(cidr.contains(&ip("1.2.3.4")));
+        assert!(!cidr.contains(&ip("1.2.3.5")));
+
+        let cidr: P2pIpCidr = "0.0.0.0/0".parse().unwrap();
+        assert!(cidr.contains(&ip("8.8.8.8")));
+
+        assert!("1.2.3.4/33".parse::<P2pIpCidr>().is_err());
+        assert!("1.2.3/8".parse::<P2pIpCidr>().is_err());
+    }
+
+    #[test]
+    fn rule_from_str() {
+        let peer_id = peer(1);
+        assert_eq!(
+            peer_id.to_string().parse::<P2pAccessRule>().unwrap(),
+            P2pAccessRule::PeerId(peer_id)
+        );
+        assert!(matches!(
+            "192.168.0.0/24".parse::<P2pAccessRule>().unwrap(),
+            P2pAccessRule::Cidr(_)
+        ));
+        assert!("foo".parse::<P2pAccessRule>().is_err());
+    }
+
+    #[test]
+    fn deny_list() {
+        let access = P2pAccessConfig {
+            deny: vec![
+                P2pAccessRule::PeerId(peer(1)),
+                P2pAccessRule::Cidr("10.0.0.0/8".parse().unwrap()),
+            ],
+            ..Default::default()
+        };
+        assert!(!access.is_restricted());
+        assert!(!access.is_ip_allowed(&ip("10.0.0.1")));
+        assert!(access.is_ip_allowed(&ip("11.0.0.1")));
+        assert!(!access.is_peer_allowed(&peer(1), None));
+        assert!(!access.is_peer_allowed(&peer(2), Some(ip("10.0.0.1"))));
+        assert!(access.is_peer_allowed(&peer(2), Some(ip("11.0.0.1"))));
+        assert!(access.is_peer_allowed(&peer(2), None));
+    }
+
+    #[test]
+    fn allow_list() {
+        let access = P2pAccessConfig {
+            allow: vec![
+                P2pAccessRule::PeerId(peer(1)),
+                P2pAccessRule::Cidr("192.168.0.0/16".parse().unwrap()),
+            ],
+            deny: vec![P2pAccessRule::Cidr("192.168.1.0/24".parse().unwrap())],
+            isolate: false,
+        };
+        assert!(access.is_restricted());
+        // allowed peer id may connect from any address
+        assert!(access.is_ip_allowed(&ip("8.8.8.8")));
+        assert!(!access.is_ip_allowed(&ip("192.168.1.1")));
+        assert!(access.is_peer_allowed(&peer(1), Some(ip("8.8.8.8"))));
+        assert!(!access.is_peer_allowed(&peer(1), Some(ip("192.168.1.1"))));
+        assert!(access.is_peer_allowed(&peer(2), Some(ip("192.168.2.1"))));
+        assert!(!access.is_peer_allowed(&peer(2), Some(ip("8.8.8.8"))));
+        assert!(!access.is_peer_allowed(&peer(2), None));
+
+        let addrs = ["/ip4/192.168.2.1/tcp/8302".parse().unwrap()];
+        assert!(access.is_peer_with_addrs_allowed(&peer(2), &addrs));
+        let addrs = ["/dns4/example.com/tcp/8302".parse().unwrap()];
+        assert!(!access.is_peer_with_addrs_allowed(&peer(2), &addrs));
+        assert!(access.is_peer_with_addrs_allowed(&peer(1), &addrs));
+    }
+
+    #[test]
+    fn unknown_ips() {
+        let deny = P2pAccessConfig {
+            deny: vec![P2pAccessRule::Cidr("10.0.0.0/8".parse().unwrap())],
+            ..Default::default()
+        };
+        assert!(deny.is_peer_with_ips_allowed(&peer(1), &[]));
+        assert!(!deny.is_peer_with_ips_allowed(&peer(1), &[ip("11.0.0.1"), ip("10.0.0.1")]));
+
+        let allow = P2pAccessConfig {
+            allow: vec![
+                P2pAccessRule::PeerId(peer(1)),
+                P2pAccessRule::Cidr("10.0.0.0/8".parse().unwrap()),
+            ],
+            ..Default::default()
+        };
+        assert!(allow.is_peer_with_ips_allowed(&peer(1), &[]));
+        assert!(!allow.is_peer_with_ips_allowed(&peer(2), &[]));
+        assert!(allow.is_peer_with_ips_allowed(&peer(2), &[ip("11.0.0.1"), ip("10.0.0.1")]));
+    }
+
+    #[test]
+    fn isolate_without_allow_list() {
+        let access = P2pAccessConfig {
+            isolate: true,
+            ..Default::default()
+        };
+        assert!(!access.is_ip_allowed(&ip("127.0.0.1")));
+        assert!(!access.is_peer_allowed(&peer(1), None));
+    }
+}
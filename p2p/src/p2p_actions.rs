@@ -2,9 +2,16 @@ use openmina_macros::ActionEvent;
 use redux::EnablingCondition;
 use serde::{Deserialize, Serialize};
 
+use crate::address_book::P2pAddressBookAction;
+use crate::address_book_effectful::P2pAddressBookEffectfulAction;
+use crate::bandwidth::P2pBandwidthAction;
 use crate::channels::P2pChannelsEffectfulAction;
 use crate::connection::P2pConnectionEffectfulAction;
 use crate::disconnection_effectful::P2pDisconnectionEffectfulAction;
+use crate::nat::P2pNatAction;
+use crate::nat_effectful::P2pNatEffectfulAction;
+use crate::trust::P2pTrustAction;
+use crate::trust_effectful::P2pTrustEffectfulAction;
 use crate::P2pNetworkEffectfulAction;
 
 use super::channels::P2pChannelsAction;
@@ -24,6 +31,10 @@ pub enum P2pAction {
     Channels(P2pChannelsAction),
     Peer(P2pPeerAction),
     Network(P2pNetworkAction),
+    Trust(P2pTrustAction),
+    AddressBook(P2pAddressBookAction),
+    Bandwidth(P2pBandwidthAction),
+    Nat(P2pNatAction),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, derive_more::From, ActionEvent)]
@@ -33,6 +44,9 @@ pub enum P2pEffectfulAction {
     Connection(P2pConnectionEffectfulAction),
     Disconnection(P2pDisconnectionEffectfulAction),
     Network(P2pNetworkEffectfulAction),
+    Trust(P2pTrustEffectfulAction),
+    AddressBook(P2pAddressBookEffectfulAction),
+    Nat(P2pNatEffectfulAction),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, derive_more::From, ActionEvent)]
@@ -60,6 +74,10 @@ impl redux::EnablingCondition<crate::P2pState> for P2pAction {
             P2pAction::Peer(a) => a.is_enabled(state, time),
             P2pAction::Identify(a) => a.is_enabled(state, time),
             P2pAction::Network(a) => a.is_enabled(state, time),
+            P2pAction::Trust(a) => a.is_enabled(state, time),
+            P2pAction::AddressBook(a) => a.is_enabled(state, time),
+            P2pAction::Bandwidth(a) => a.is_enabled(state, time),
+            P2pAction::Nat(a) => a.is_enabled(state, time),
         }
     }
 }
@@ -71,6 +89,9 @@ impl redux::EnablingCondition<crate::P2pState> for P2pEffectfulAction {
             P2pEffectfulAction::Connection(a) => a.is_enabled(state, time),
             P2pEffectfulAction::Disconnection(a) => a.is_enabled(state, time),
             P2pEffectfulAction::Network(a) => a.is_enabled(state, time),
+            P2pEffectfulAction::Trust(a) => a.is_enabled(state, time),
+            P2pEffectfulAction::AddressBook(a) => a.is_enabled(state, time),
+            P2pEffectfulAction::Nat(a) => a.is_enabled(state, time),
             P2pEffectfulAction::Initialize => true,
         }
     }
@@ -3,7 +3,9 @@ use std::{collections::BTreeSet, net::IpAddr, time::Duration};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    channels::ChannelId, connection::outgoing::P2pConnectionOutgoingInitOpts, identity::PublicKey,
+    address_book::P2pAddressBook, channels::ChannelId,
+    connection::outgoing::P2pConnectionOutgoingInitOpts, identity::PublicKey, trust::P2pTrustState,
+    P2pAccessConfig,
 };
 
 pub const DEVNET_SEEDS: &[&str] = &[
@@ -16,6 +18,10 @@ pub const DEVNET_SEEDS: &[&str] = &[
 pub struct P2pConfig {
     /// TCP port where libp2p is listening incoming connections.
     pub libp2p_port: Option<u16>,
+    /// Also listen for and dial libp2p connections over QUIC, using UDP
+    /// port with the same number as `libp2p_port`.
+    #[serde(default)]
+    pub libp2p_quic: bool,
     /// The HTTP port where signaling server is listening SDP offers and SDP answers.
     pub listen_port: Option<u16>,
     /// The public key used for authentication all p2p communication.
@@ -24,6 +30,10 @@ pub struct P2pConfig {
     pub initial_peers: Vec<P2pConnectionOutgoingInitOpts>,
     /// External addresses
     pub external_addrs: Vec<IpAddr>,
+    /// Ask the gateway to forward `libp2p_port` to this host, using
+    /// NAT-PMP/PCP or UPnP IGD.
+    #[serde(default)]
+    pub nat_port_mapping: bool,
 
     pub enabled_channels: BTreeSet<ChannelId>,
 
@@ -35,9 +45,34 @@ pub struct P2pConfig {
     pub peer_discovery: bool,
 
     pub meshsub: P2pMeshsubConfig,
+
+    /// Peer trust scores and bans, persisted by the previous run.
+    pub initial_trust: P2pTrustState,
+
+    /// Peers seen by the previous run, used to seed Kademlia and outgoing
+    /// connection attempts.
+    pub initial_address_book: P2pAddressBook,
+
+    /// Allow and deny lists of peers.
+    pub access: P2pAccessConfig,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl P2pConfig {
+    /// Whether signaling channels are also used with libp2p peers, over
+    /// the `/openmina/webrtc-signal/1.0.0` protocol. Never in an isolated
+    /// network, as signaling introduces peers to each other.
+    pub fn libp2p_webrtc_signal_enabled(&self) -> bool {
+        !self.access.isolate
+            && (self
+                .enabled_channels
+                .contains(&ChannelId::SignalingDiscovery)
+                || self
+                    .enabled_channels
+                    .contains(&ChannelId::SignalingExchange))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct P2pMeshsubConfig {
     /// Unix time. Used as an initial nonce for pubsub.
     pub initial_time: Duration,
@@ -46,6 +81,17 @@ pub struct P2pMeshsubConfig {
     pub outbound_degree_low: usize,
     pub outbound_degree_high: usize,
     pub mcache_len: usize,
+
+    /// Interval at which peer scores decay and the mesh is maintained.
+    pub heartbeat_interval: Duration,
+    /// Time a peer we pruned from the mesh has to wait before grafting again.
+    pub prune_backoff: Duration,
+    /// Number of heartbeats between attempts of opportunistic grafting.
+    pub opportunistic_graft_ticks: u64,
+    /// Number of peers to graft during opportunistic grafting.
+    pub opportunistic_graft_peers: usize,
+
+    pub score: P2pMeshsubScoreParams,
 }
 
 impl Default for P2pMeshsubConfig {
@@ -56,6 +102,150 @@ impl Default for P2pMeshsubConfig {
             outbound_degree_low: 4,
             outbound_degree_high: 12,
             mcache_len: 256,
+            heartbeat_interval: Duration::from_secs(1),
+            prune_backoff: Duration::from_secs(60),
+            opportunistic_graft_ticks: 60,
+            opportunistic_graft_peers: 2,
+            score: Default::default(),
+        }
+    }
+}
+
+/// Parameters of the gossipsub v1.1 peer score function.
+///
+/// See <https://github.com/libp2p/specs/blob/master/pubsub/gossipsub/gossipsub-v1.1.md#the-score-function>.
+/// Counters decay once per heartbeat, so decay factors depend on
+/// [`P2pMeshsubConfig::heartbeat_interval`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct P2pMeshsubScoreParams {
+    /// Parameters of the topic scores (P1-P4). Used for all topics, as we
+    /// are only subscribed to `coda/consensus-messages/0.0.1`.
+    pub topic: P2pMeshsubTopicScoreParams,
+    /// Cap of the positive contribution of topic scores.
+    pub topic_score_cap: f64,
+
+    /// P5: weight of the application specific score, which is the trust
+    /// score of the peer.
+    pub app_specific_weight: f64,
+
+    /// P6: weight of the number of peers above the threshold that are
+    /// connected from the same IP address. Loopback addresses are ignored.
+    pub ip_colocation_factor_weight: f64,
+    pub ip_colocation_factor_threshold: usize,
+
+    /// P7: weight of the protocol misbehaviour counter above the threshold,
+    /// like grafting during the backoff.
+    pub behaviour_penalty_weight: f64,
+    pub behaviour_penalty_threshold: f64,
+    pub behaviour_penalty_decay: f64,
+
+    /// Counters below this value are reset to zero when decaying.
+    pub decay_to_zero: f64,
+    /// Time to remember the score of a disconnected peer.
+    pub retain_score: Duration,
+
+    /// Below this score we don't exchange gossip (IHAVE/IWANT) with the peer.
+    pub gossip_threshold: f64,
+    /// Below this score we don't publish our own messages to the peer.
+    pub publish_threshold: f64,
+    /// Below this score messages from the peer are ignored.
+    pub graylist_threshold: f64,
+    /// If the median score of the mesh drops below this value, we graft
+    /// peers with better score.
+    pub opportunistic_graft_threshold: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct P2pMeshsubTopicScoreParams {
+    pub topic_weight: f64,
+
+    /// P1: time in mesh.
+    pub time_in_mesh_weight: f64,
+    pub time_in_mesh_quantum: Duration,
+    pub time_in_mesh_cap: f64,
+
+    /// P2: messages first delivered by the peer.
+    pub first_message_deliveries_weight: f64,
+    pub first_message_deliveries_decay: f64,
+    pub first_message_deliveries_cap: f64,
+
+    /// P3: deficit of messages delivered by a mesh peer. Only checked after
+    /// the peer is in the mesh for `mesh_message_deliveries_activation`.
+    /// Duplicates count if received within `mesh_message_deliveries_window`
+    /// after the first delivery.
+    pub mesh_message_deliveries_weight: f64,
+    pub mesh_message_deliveries_decay: f64,
+    pub mesh_message_deliveries_threshold: f64,
+    pub mesh_message_deliveries_cap: f64,
+    pub mesh_message_deliveries_activation: Duration,
+    pub mesh_message_deliveries_window: Duration,
+
+    /// P3b: deficit of mesh message deliveries the peer had when it
+    /// left the mesh.
+    pub mesh_failure_penalty_weight: f64,
+    pub mesh_failure_penalty_decay: f64,
+
+    /// P4: invalid messages delivered by the peer.
+    pub invalid_message_deliveries_weight: f64,
+    pub invalid_message_deliveries_decay: f64,
+}
+
+/// Decay factor which brings a counter to `decay_to_zero` over `duration`,
+/// when applied each `interval`.
+pub fn score_parameter_decay(duration: Duration, interval: Duration, decay_to_zero: f64) -> f64 {
+    let ticks = duration.as_secs_f64() / interval.as_secs_f64();
+    decay_to_zero.powf(1.0 / ticks)
+}
+
+const SCORE_DECAY_TO_ZERO: f64 = 0.01;
+
+fn default_score_decay(duration: Duration) -> f64 {
+    score_parameter_decay(duration, Duration::from_secs(1), SCORE_DECAY_TO_ZERO)
+}
+
+impl Default for P2pMeshsubScoreParams {
+    fn default() -> Self {
+        Self {
+            topic: Default::default(),
+            topic_score_cap: 50.0,
+            app_specific_weight: 10.0,
+            ip_colocation_factor_weight: -10.0,
+            ip_colocation_factor_threshold: 3,
+            behaviour_penalty_weight: -10.0,
+            behaviour_penalty_threshold: 1.0,
+            behaviour_penalty_decay: default_score_decay(Duration::from_secs(10 * 60)),
+            decay_to_zero: SCORE_DECAY_TO_ZERO,
+            retain_score: Duration::from_secs(60 * 60),
+            gossip_threshold: -10.0,
+            publish_threshold: -50.0,
+            graylist_threshold: -80.0,
+            opportunistic_graft_threshold: 1.0,
+        }
+    }
+}
+
+impl Default for P2pMeshsubTopicScoreParams {
+    fn default() -> Self {
+        Self {
+            topic_weight: 1.0,
+            time_in_mesh_weight: 0.1,
+            time_in_mesh_quantum: Duration::from_secs(60),
+            time_in_mesh_cap: 60.0,
+            first_message_deliveries_weight: 1.0,
+            first_message_deliveries_decay: default_score_decay(Duration::from_secs(60 * 60)),
+            first_message_deliveries_cap: 50.0,
+            // Messages on the consensus topic are too rare and irregular
+            // for a meaningful delivery threshold, so P3 and P3b are disabled.
+            mesh_message_deliveries_weight: 0.0,
+            mesh_message_deliveries_decay: default_score_decay(Duration::from_secs(10 * 60)),
+            mesh_message_deliveries_threshold: 1.0,
+            mesh_message_deliveries_cap: 20.0,
+            mesh_message_deliveries_activation: Duration::from_secs(5 * 60),
+            mesh_message_deliveries_window: Duration::from_secs(2),
+            mesh_failure_penalty_weight: 0.0,
+            mesh_failure_penalty_decay: default_score_decay(Duration::from_secs(10 * 60)),
+            invalid_message_deliveries_weight: -10.0,
+            invalid_message_deliveries_decay: default_score_decay(Duration::from_secs(60 * 60)),
         }
     }
 }
@@ -74,6 +264,9 @@ pub struct P2pTimeouts {
     pub snark: Option<Duration>,
     pub transaction: Option<Duration>,
     pub initial_peers: Option<Duration>,
+    pub ancestry: Option<Duration>,
+    pub transition_chain_proof: Option<Duration>,
+    pub epoch_ledger: Option<Duration>,
     pub kademlia_bootstrap: Option<Duration>,
     pub kademlia_initial_bootstrap: Option<Duration>,
     pub select: Option<Duration>,
@@ -124,6 +317,13 @@ impl Default for P2pTimeouts {
             snark: from_env_or("SNARK_TIMEOUT", Some(Duration::from_secs(8))),
             transaction: from_env_or("TRANSACTION_TIMEOUT", Some(Duration::from_secs(8))),
             initial_peers: from_env_or("INITIAL_PEERS_TIMEOUT", Some(Duration::from_secs(5))),
+            ancestry: from_env_or("ANCESTRY_TIMEOUT", Some(Duration::from_secs(15))),
+            transition_chain_proof: from_env_or(
+                "TRANSITION_CHAIN_PROOF_TIMEOUT",
+                Some(Duration::from_secs(8)),
+            ),
+            // tried on at most 2 peers before falling back to the merkle tree sync
+            epoch_ledger: from_env_or("EPOCH_LEDGER_TIMEOUT", Some(Duration::from_secs(90))),
             kademlia_bootstrap: from_env_or(
                 "KADEMLIA_BOOTSTRAP_TIMEOUT",
                 Some(Duration::from_secs(60)),
@@ -146,6 +346,9 @@ impl P2pTimeouts {
             staged_ledger_aux_and_pending_coinbases_at_block: None,
             block: None,
             snark: None,
+            ancestry: None,
+            transition_chain_proof: None,
+            epoch_ledger: None,
             ..Default::default()
         }
     }
@@ -245,6 +448,68 @@ macro_rules! impls {
 impls!(usize);
 impls!(std::time::Duration);
 
+/// Token bucket rate limit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct P2pRateLimit {
+    /// Tokens added to the bucket per second.
+    pub rate: u64,
+    /// Capacity of the bucket, so the largest burst allowed.
+    pub burst: u64,
+}
+
+impl std::fmt::Display for P2pRateLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/s, burst {}", self.rate, self.burst)
+    }
+}
+
+/// Quotas for serving RPC requests of a peer, similar to the `rate_limiter`
+/// of the OCaml daemon.
+///
+/// Quotas are in cost units, see
+/// [`P2pRpcRequest::cost`](crate::channels::rpc::P2pRpcRequest::cost). A
+/// request is served only if both the total quota and the quota for its
+/// kind allow it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct P2pRpcQuotas {
+    /// Quota shared by all kinds of requests.
+    pub total: Limit<P2pRateLimit>,
+    pub best_tip_with_proof: Limit<P2pRateLimit>,
+    pub ledger_query: Limit<P2pRateLimit>,
+    pub staged_ledger_aux_and_pending_coinbases_at_block: Limit<P2pRateLimit>,
+    pub block: Limit<P2pRateLimit>,
+    pub snark: Limit<P2pRateLimit>,
+    pub transaction: Limit<P2pRateLimit>,
+    pub initial_peers: Limit<P2pRateLimit>,
+    pub ancestry: Limit<P2pRateLimit>,
+    pub transition_chain_proof: Limit<P2pRateLimit>,
+    pub epoch_ledger: Limit<P2pRateLimit>,
+}
+
+impl Default for P2pRpcQuotas {
+    fn default() -> Self {
+        let quota = |rate, burst| Limit::Some(P2pRateLimit { rate, burst });
+        Self {
+            total: quota(2_500, 25_000),
+            // ~6 per minute, 5 at once
+            best_tip_with_proof: quota(1, 50),
+            // OCaml nodes sync ledgers at ~230 queries per second, most of
+            // them may be `WhatContents`
+            ledger_query: quota(2_000, 20_000),
+            // ~4 per minute, as in the OCaml daemon
+            staged_ledger_aux_and_pending_coinbases_at_block: quota(7, 400),
+            block: quota(25, 250),
+            snark: quota(20, 200),
+            transaction: quota(20, 200),
+            initial_peers: quota(1, 10),
+            ancestry: quota(1, 50),
+            transition_chain_proof: quota(1, 50),
+            // ~1 per 2 minutes, 2 at once
+            epoch_ledger: quota(8, 2_000),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Copy)]
 pub struct P2pLimits {
     max_peers: Limit<usize>,
@@ -265,6 +530,14 @@ pub struct P2pLimits {
     rpc_get_staged_ledger: Limit<usize>,
     rpc_get_transition_chain: Limit<usize>,
     rpc_get_some_initial_peers: Limit<usize>,
+    rpc_get_ancestry: Limit<usize>,
+    rpc_get_transition_chain_proof: Limit<usize>,
+    rpc_get_epoch_ledger: Limit<usize>,
+
+    rpc_responses_rate: Limit<P2pRateLimit>,
+    rpc_quotas: P2pRpcQuotas,
+
+    signaling_relay_rate: Limit<P2pRateLimit>,
 }
 
 macro_rules! limit {
@@ -383,6 +656,51 @@ impl P2pLimits {
         #[doc = "RPC some_initial_peers"]
         rpc_get_some_initial_peers
     );
+    limit!(
+        #[doc = "RPC get_ancestry"]
+        rpc_get_ancestry
+    );
+    limit!(
+        #[doc = "RPC get_transition_chain_proof"]
+        rpc_get_transition_chain_proof
+    );
+    limit!(
+        #[doc = "RPC get_epoch_ledger"]
+        rpc_get_epoch_ledger
+    );
+
+    /// Rate of bytes of RPC responses a peer may make us send to it.
+    pub fn rpc_responses_rate(&self) -> Limit<P2pRateLimit> {
+        self.rpc_responses_rate
+    }
+
+    /// Sets the rate of bytes of RPC responses a peer may make us send to it.
+    pub fn with_rpc_responses_rate(mut self, rate: Limit<P2pRateLimit>) -> Self {
+        self.rpc_responses_rate = rate;
+        self
+    }
+
+    /// Quotas for serving RPC requests of a peer.
+    pub fn rpc_quotas(&self) -> P2pRpcQuotas {
+        self.rpc_quotas
+    }
+
+    /// Sets quotas for serving RPC requests of a peer.
+    pub fn with_rpc_quotas(mut self, quotas: P2pRpcQuotas) -> Self {
+        self.rpc_quotas = quotas;
+        self
+    }
+
+    /// Rate of WebRTC offers we relay between peers, for all of them.
+    pub fn signaling_relay_rate(&self) -> Limit<P2pRateLimit> {
+        self.signaling_relay_rate
+    }
+
+    /// Sets the rate of WebRTC offers we relay between peers.
+    pub fn with_signaling_relay_rate(mut self, rate: Limit<P2pRateLimit>) -> Self {
+        self.signaling_relay_rate = rate;
+        self
+    }
 }
 
 impl Default for P2pLimits {
@@ -405,6 +723,22 @@ impl Default for P2pLimits {
         let rpc_get_staged_ledger = Limit::Some(400_000_000); // 59286608 as observed, may go higher
         let rpc_get_transition_chain = Limit::Some(3_500_000); // 2979112 as observed
         let rpc_get_some_initial_peers = Limit::Some(32_000); // TODO: calculate
+        let rpc_get_ancestry = rpc_get_best_tip; // same payload as get_best_tip
+        let rpc_get_transition_chain_proof = Limit::Some(16_000); // 290 body hashes at most
+
+        // ~400 bytes per account (account, index entry and tree nodes), so ~100 MB for
+        // mainnet's ~230k accounts, leaving room to grow within the yamux message size
+        let rpc_get_epoch_ledger = Limit::Some(256 * 1024 * 1024);
+
+        // burst must fit the largest response
+        let rpc_responses_rate = Limit::Some(P2pRateLimit {
+            rate: 20 * 1024 * 1024,
+            burst: 512 * 1024 * 1024,
+        });
+
+        // each requester is asked for at most one offer per minute, this
+        // bounds the total when many peers are looking for connections
+        let signaling_relay_rate = Limit::Some(P2pRateLimit { rate: 1, burst: 10 });
 
         Self {
             max_peers,
@@ -425,6 +759,14 @@ impl Default for P2pLimits {
             rpc_get_staged_ledger,
             rpc_get_transition_chain,
             rpc_get_some_initial_peers,
+            rpc_get_ancestry,
+            rpc_get_transition_chain_proof,
+            rpc_get_epoch_ledger,
+
+            rpc_responses_rate,
+            rpc_quotas: Default::default(),
+
+            signaling_relay_rate,
         }
     }
 }
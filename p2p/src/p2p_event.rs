@@ -8,18 +8,19 @@ use crate::channels::signaling::discovery::SignalingDiscoveryChannelMsg;
 use crate::channels::signaling::exchange::SignalingExchangeChannelMsg;
 use crate::channels::streaming_rpc::StreamingRpcChannelMsg;
 use crate::webrtc::ConnectionAuthEncrypted;
-use crate::ConnectionAddr;
 use crate::{
     channels::{transaction::TransactionPropagationChannelMsg, ChannelId, ChannelMsg, MsgId},
     connection::P2pConnectionResponse,
     PeerId,
 };
+use crate::{nat::P2pNatPortMapping, ConnectionAddr, StreamId};
 
 #[derive(Serialize, Deserialize, From, Debug, Clone)]
 pub enum P2pEvent {
     Connection(P2pConnectionEvent),
     Channel(P2pChannelEvent),
     MioEvent(MioEvent),
+    Nat(P2pNatEvent),
 }
 
 /// The mio service reports events.
@@ -54,6 +55,27 @@ pub enum MioEvent {
 
     /// The remote peer is disconnected by our node.
     ConnectionDidCloseOnDemand(ConnectionAddr),
+
+    /// TLS handshake of the QUIC connection is done, the remote peer is
+    /// authenticated.
+    QuicConnectionDidAuthenticate(ConnectionAddr, PeerId),
+    /// The remote peer opened a stream in the QUIC connection.
+    QuicStreamDidOpen {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
+    /// We received the data in the stream of the QUIC connection.
+    QuicStreamDataDidReceive {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: crate::Data,
+        fin: bool,
+    },
+    /// The remote peer reset the stream of the QUIC connection.
+    QuicStreamDidReset {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -65,6 +87,20 @@ pub enum P2pConnectionEvent {
     Closed(PeerId),
 }
 
+/// Results of NAT traversal requests to the service.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum P2pNatEvent {
+    /// The gateway forwards the port to us, or refused to do so.
+    PortMapped(Result<P2pNatPortMapping, String>),
+    /// Dialing back the peer that asked for it with AutoNAT is finished.
+    DialBack {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        result: Result<SocketAddr, String>,
+    },
+}
+
 #[derive(Serialize, Deserialize, From, Debug, Clone)]
 pub enum P2pChannelEvent {
     Opened(PeerId, ChannelId, Result<(), String>),
@@ -87,6 +123,31 @@ impl fmt::Display for P2pEvent {
             Self::Connection(v) => v.fmt(f),
             Self::Channel(v) => v.fmt(f),
             Self::MioEvent(v) => v.fmt(f),
+            Self::Nat(v) => v.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for P2pNatEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Nat, ")?;
+        match self {
+            Self::PortMapped(Ok(mapping)) => write!(
+                f,
+                "PortMapped, {}, {}",
+                mapping.protocol, mapping.external_addr
+            ),
+            Self::PortMapped(Err(err)) => write!(f, "PortMapped, Err, {err}"),
+            Self::DialBack {
+                addr,
+                peer_id,
+                stream_id,
+                result,
+            } => write!(
+                f,
+                "DialBack, {addr}, {peer_id}, {stream_id}, {}",
+                res_kind(result)
+            ),
         }
     }
 }
@@ -299,6 +360,27 @@ impl fmt::Display for MioEvent {
             Self::ConnectionDidCloseOnDemand(addr) => {
                 write!(f, "ConnectionDidCloseOnDemand, {addr}")
             }
+            Self::QuicConnectionDidAuthenticate(addr, peer_id) => {
+                write!(f, "QuicConnectionDidAuthenticate, {addr}, {peer_id}")
+            }
+            Self::QuicStreamDidOpen { addr, stream_id } => {
+                write!(f, "QuicStreamDidOpen, {addr}, {stream_id}")
+            }
+            Self::QuicStreamDataDidReceive {
+                addr,
+                stream_id,
+                data,
+                fin,
+            } => {
+                write!(
+                    f,
+                    "QuicStreamDataDidReceive, {addr}, {stream_id}, {}, fin: {fin}",
+                    data.len()
+                )
+            }
+            Self::QuicStreamDidReset { addr, stream_id } => {
+                write!(f, "QuicStreamDidReset, {addr}, {stream_id}")
+            }
         }
     }
 }
@@ -1,4 +1,8 @@
+use std::collections::BTreeMap;
+
 use crate::{
+    address_book::{P2pAddressBook, P2pAddressBookAction},
+    bandwidth::{P2pBandwidthAction, P2pBandwidthState},
     channels::{
         rpc::P2pChannelsRpcAction, signaling::discovery::P2pChannelsSignalingDiscoveryAction,
         streaming_rpc::P2pChannelsStreamingRpcAction, P2pChannelsState,
@@ -8,6 +12,8 @@ use crate::{
         P2pConnectionState,
     },
     disconnection::{P2pDisconnectedState, P2pDisconnectionAction},
+    nat::{P2pNatAction, P2pNatState},
+    trust::{P2pTrustAction, P2pTrustState},
     P2pAction, P2pNetworkKadKey, P2pNetworkKademliaAction, P2pNetworkPnetAction,
     P2pNetworkPubsubAction, P2pNetworkRpcAction, P2pNetworkSelectAction, P2pNetworkState,
     P2pPeerState, P2pState, PeerId,
@@ -56,15 +62,44 @@ impl P2pState {
             P2pAction::Network(_action) => {
                 #[cfg(feature = "p2p-libp2p")]
                 {
+                    use crate::{P2pNetworkAction, P2pNetworkKadAction};
+
+                    // banned peers must not get back into the routing table
+                    // through replies of other peers
+                    let mut _action = _action;
+                    if let P2pNetworkAction::Kad(P2pNetworkKadAction::System(
+                        P2pNetworkKademliaAction::UpdateFindNodeRequest { closest_peers, .. },
+                    )) = &mut _action
+                    {
+                        closest_peers.retain(|entry| {
+                            !state.trust.is_peer_with_addrs_banned(
+                                &entry.peer_id,
+                                entry.addresses(),
+                                meta.time(),
+                            )
+                        });
+                    }
                     let limits = state.config.limits;
+                    let meshsub = state.config.meshsub;
                     P2pNetworkState::reducer(
                         Substate::from_compatible_substate(state_context),
                         meta.with_action(_action),
                         &limits,
+                        &meshsub,
                     )?;
                 }
                 Ok(())
             }
+            P2pAction::Trust(action) => {
+                P2pTrustState::reducer(state_context, meta.with_action(action))
+            }
+            P2pAction::AddressBook(action) => {
+                P2pAddressBook::reducer(state_context, meta.with_action(action))
+            }
+            P2pAction::Bandwidth(action) => {
+                P2pBandwidthState::reducer(state_context, meta.with_action(action))
+            }
+            P2pAction::Nat(action) => P2pNatState::reducer(state_context, meta.with_action(action)),
         }
     }
 
@@ -83,6 +118,12 @@ impl P2pState {
         state.p2p_connection_timeouts_dispatch(dispatcher, time)?;
         dispatcher.push(P2pConnectionOutgoingAction::RandomInit);
         dispatcher.push(P2pDisconnectionAction::RandomTry);
+        dispatcher.push(P2pTrustAction::PruneExpired);
+        dispatcher.push(P2pAddressBookAction::Prune);
+        dispatcher.push(P2pAddressBookAction::Persist);
+        dispatcher.push(P2pBandwidthAction::Prune);
+        dispatcher.push(P2pNatAction::PortMappingInit);
+        dispatcher.push(P2pNatAction::Prune);
 
         state.p2p_connect_initial_peers(dispatcher);
         state.p2p_try_reconnect_disconnected_peers(dispatcher, time)?;
@@ -93,7 +134,11 @@ impl P2pState {
             state.p2p_pnet_timeouts(dispatcher, time)?;
             state.p2p_select_timeouts(dispatcher, time)?;
             state.p2p_rpc_heartbeats(dispatcher, time)?;
+            state.p2p_nat_probes(dispatcher);
             dispatcher.push(P2pNetworkPubsubAction::PruneMessages {});
+            dispatcher.push(P2pNetworkPubsubAction::Heartbeat {
+                app_scores: state.pubsub_app_scores(time),
+            });
         }
 
         state.rpc_timeouts(dispatcher, time)?;
@@ -365,4 +410,28 @@ impl P2pState {
 
         Ok(())
     }
+
+    /// Asks peers to dial us back, so that we know if we are reachable.
+    fn p2p_nat_probes<State, Action>(&self, dispatcher: &mut Dispatcher<Action, State>)
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        self.ready_peers_iter()
+            .filter(|(peer_id, _)| !self.nat.probes.contains_key(peer_id))
+            .for_each(|(peer_id, _)| {
+                dispatcher.push(P2pNatAction::ProbeInit { peer_id: *peer_id })
+            });
+    }
+
+    /// Trust scores of peers with pubsub score, used as its application specific part.
+    fn pubsub_app_scores(&self, time: Timestamp) -> BTreeMap<PeerId, f64> {
+        self.network
+            .scheduler
+            .broadcast_state
+            .scores
+            .keys()
+            .map(|peer_id| (*peer_id, self.trust.peer_score(peer_id, time)))
+            .collect()
+    }
 }
@@ -1,8 +1,11 @@
 pub use redux::TimeService;
 
+pub use crate::address_book_effectful::P2pAddressBookService;
 pub use crate::channels::P2pChannelsService;
 pub use crate::connection::P2pConnectionService;
 pub use crate::disconnection_effectful::P2pDisconnectionService;
+pub use crate::nat_effectful::P2pNatService;
+pub use crate::trust_effectful::P2pTrustService;
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "p2p-libp2p"))]
 pub use crate::{P2pCryptoService, P2pMioService, P2pNetworkService};
@@ -12,6 +15,9 @@ pub trait P2pService:
     TimeService
     + P2pConnectionService
     + P2pDisconnectionService
+    + P2pTrustService
+    + P2pAddressBookService
+    + P2pNatService
     + P2pChannelsService
     + P2pMioService
     + P2pCryptoService
@@ -24,6 +30,9 @@ impl<T> P2pService for T where
     T: TimeService
         + P2pConnectionService
         + P2pDisconnectionService
+        + P2pTrustService
+        + P2pAddressBookService
+        + P2pNatService
         + P2pChannelsService
         + P2pMioService
         + P2pCryptoService
@@ -33,12 +42,24 @@ impl<T> P2pService for T where
 
 #[cfg(not(all(not(target_arch = "wasm32"), feature = "p2p-libp2p")))]
 pub trait P2pService:
-    TimeService + P2pConnectionService + P2pDisconnectionService + P2pChannelsService
+    TimeService
+    + P2pConnectionService
+    + P2pDisconnectionService
+    + P2pTrustService
+    + P2pAddressBookService
+    + P2pNatService
+    + P2pChannelsService
 {
 }
 
 #[cfg(not(all(not(target_arch = "wasm32"), feature = "p2p-libp2p")))]
 impl<T> P2pService for T where
-    T: TimeService + P2pConnectionService + P2pDisconnectionService + P2pChannelsService
+    T: TimeService
+        + P2pConnectionService
+        + P2pDisconnectionService
+        + P2pTrustService
+        + P2pAddressBookService
+        + P2pNatService
+        + P2pChannelsService
 {
 }
@@ -18,6 +18,8 @@ use redux::{Callback, Timestamp};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    address_book::{P2pAddressBook, MAX_SEEDED_PEERS},
+    bandwidth::P2pBandwidthState,
     bootstrap::P2pNetworkKadBootstrapState,
     channels::{
         rpc::{P2pRpcId, P2pRpcRequest, P2pRpcResponse},
@@ -32,10 +34,12 @@ use crate::{
         P2pConnectionResponse, P2pConnectionState,
     },
     is_time_passed,
+    nat::P2pNatState,
     network::{
         identify::{P2pNetworkIdentify, P2pNetworkIdentifyState},
         P2pNetworkState,
     },
+    trust::P2pTrustState,
     Limit, P2pConfig, P2pLimits, P2pNetworkKadState, P2pNetworkPubsubMessageCacheId,
     P2pNetworkPubsubState, P2pNetworkSchedulerState, P2pTimeouts, PeerId,
 };
@@ -47,6 +51,10 @@ pub struct P2pState {
     pub config: P2pConfig,
     pub network: P2pNetworkState,
     pub peers: BTreeMap<PeerId, P2pPeerState>,
+    pub trust: P2pTrustState,
+    pub address_book: P2pAddressBook,
+    pub bandwidth: P2pBandwidthState,
+    pub nat: P2pNatState,
 
     pub last_random_disconnection_try: redux::Timestamp,
 
@@ -54,7 +62,7 @@ pub struct P2pState {
 }
 
 impl P2pState {
-    pub fn new(config: P2pConfig, callbacks: P2pCallbacks, chain_id: &ChainId) -> Self {
+    pub fn new(mut config: P2pConfig, callbacks: P2pCallbacks, chain_id: &ChainId) -> Self {
         let addrs = if cfg!(feature = "p2p-libp2p") {
             config
                 .libp2p_port
@@ -66,6 +74,8 @@ impl P2pState {
         };
 
         let my_id = config.identity_pub_key.peer_id();
+        let trust = std::mem::take(&mut config.initial_trust);
+        let address_book = std::mem::take(&mut config.initial_address_book);
 
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -79,7 +89,7 @@ impl P2pState {
             );
         }
 
-        let known_peers = if cfg!(feature = "p2p-libp2p") {
+        let mut known_peers: Vec<_> = if cfg!(feature = "p2p-libp2p") {
             config
                 .initial_peers
                 .iter()
@@ -95,18 +105,68 @@ impl P2pState {
             Vec::new()
         };
 
+        // known-good peers from the previous run, in case initial peers
+        // are unreachable
+        let seeded_peers = if cfg!(feature = "p2p-libp2p") {
+            let now = openmina_core::log::system_time();
+            address_book
+                .preferred_peers()
+                .filter(|(peer_id, entry)| {
+                    **peer_id != my_id
+                        && config
+                            .access
+                            .is_peer_with_addrs_allowed(peer_id, &entry.addrs)
+                        && !trust.is_peer_with_addrs_banned(peer_id, &entry.addrs, now)
+                })
+                .filter_map(|(peer_id, entry)| {
+                    Some((
+                        *peer_id,
+                        entry.addrs.first()?.clone(),
+                        entry.dial_opts(peer_id)?,
+                    ))
+                })
+                .take(MAX_SEEDED_PEERS)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        known_peers.extend(
+            seeded_peers
+                .iter()
+                .map(|(peer_id, addr, _)| (*peer_id, addr.clone())),
+        );
+        let peers = seeded_peers
+            .into_iter()
+            .map(|(peer_id, _, dial_opts)| {
+                let peer_state = P2pPeerState {
+                    is_libp2p: true,
+                    dial_opts: Some(dial_opts),
+                    status: P2pPeerStatus::Disconnected {
+                        time: Timestamp::ZERO,
+                    },
+                    identify: None,
+                };
+                (peer_id, peer_state)
+            })
+            .collect();
+
         let network = P2pNetworkState::new(
             config.identity_pub_key.clone(),
             addrs,
             known_peers,
             chain_id,
-            config.peer_discovery,
+            config.peer_discovery && !config.access.isolate,
+            &config.access,
         );
         Self {
             chain_id: chain_id.clone(),
             config,
             network,
-            peers: Default::default(),
+            peers,
+            trust,
+            address_book,
+            bandwidth: Default::default(),
+            nat: Default::default(),
 
             last_random_disconnection_try: redux::Timestamp::ZERO,
 
@@ -292,13 +352,15 @@ impl P2pState {
         let result = if let crate::ConnectionAddr {
             sock_addr,
             incoming: false,
+            transport,
         } = conn_id
         {
             self.peers
                 .iter()
                 .find(|(_, peer_state)| match &peer_state.dial_opts {
                     Some(P2pConnectionOutgoingInitOpts::LibP2P(libp2p_opts)) => {
-                        libp2p_opts.matches_socket_addr(sock_addr)
+                        libp2p_opts.transport == transport
+                            && libp2p_opts.matches_socket_addr(sock_addr)
                     }
                     _ => false,
                 })
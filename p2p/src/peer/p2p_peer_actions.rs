@@ -35,10 +35,11 @@ impl P2pPeerAction {
 }
 
 impl redux::EnablingCondition<P2pState> for P2pPeerAction {
-    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
         match self {
             P2pPeerAction::Discovered { peer_id, .. } => {
                 peer_id != &state.my_id()
+                    && !state.trust.is_peer_banned(peer_id, time)
                     && state
                         .peers
                         .get(peer_id)
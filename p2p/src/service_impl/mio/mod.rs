@@ -1,10 +1,12 @@
+mod quic;
 mod token;
+use self::quic::{IpFamily, QuicEndpoint};
 use self::token::{Token, TokenRegistry};
 
 use std::{
     collections::{BTreeMap, VecDeque},
     io::{self, Read, Write},
-    net::{IpAddr, Ipv4Addr, Shutdown, SocketAddr},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr},
     process,
 };
 
@@ -14,7 +16,7 @@ use mio::net::{TcpListener, TcpStream};
 use openmina_core::{bug_condition, channels::mpsc};
 use thiserror::Error;
 
-use crate::{ConnectionAddr, MioCmd, MioEvent};
+use crate::{ConnectionAddr, ConnectionTransport, MioCmd, MioEvent};
 
 #[derive(Debug, Error)]
 enum MioError {
@@ -28,6 +30,8 @@ enum MioError {
     Listen(SocketAddr, io::Error),
     #[error("mio failed to register the socket on {0}, error: {1}")]
     Register(SocketAddr, io::Error),
+    #[error("mio QUIC endpoint error: {0}")]
+    Quic(io::Error),
 }
 
 impl MioError {
@@ -148,8 +152,10 @@ impl MioRunningService {
             event_sender,
             cmd_receiver: rx,
             tokens,
+            keypair: keypair.clone(),
             listeners: BTreeMap::default(),
             connections: BTreeMap::default(),
+            quic: BTreeMap::default(),
             recv_buf: vec![0; 0x8000],
         };
 
@@ -182,8 +188,11 @@ struct MioServiceInner<F> {
     event_sender: F,
     cmd_receiver: mpsc::UnboundedReceiver<MioCmd>,
     tokens: TokenRegistry,
+    keypair: Keypair,
     listeners: BTreeMap<SocketAddr, Listener>,
     connections: BTreeMap<ConnectionAddr, Connection>,
+    /// QUIC endpoints, one per address family.
+    quic: BTreeMap<IpFamily, QuicEndpoint>,
     recv_buf: Vec<u8>,
 }
 
@@ -205,7 +214,13 @@ where
     F: 'static + Send + Sync + Fn(MioEvent),
 {
     fn run(&mut self, events: &mut mio::Events) {
-        if let Err(err) = self.poll.poll(events, None) {
+        // QUIC needs to be polled on its timers
+        let timeout = self
+            .quic
+            .values_mut()
+            .filter_map(|quic| quic.timeout())
+            .min();
+        if let Err(err) = self.poll.poll(events, timeout) {
             MioError::Poll(err).report();
         }
 
@@ -217,6 +232,15 @@ where
                         self.handle(cmd);
                     }
                 }
+                Some(Token::Quic(family)) => {
+                    if let Some(quic) = self.quic.get_mut(&family) {
+                        if event.is_readable() {
+                            if let Err(err) = quic.recv(&self.event_sender) {
+                                MioError::Quic(err).report();
+                            }
+                        }
+                    }
+                }
                 Some(Token::Listener(addr)) => {
                     let Some(mut listener) = self.listeners.remove(&addr) else {
                         continue 'events;
@@ -337,6 +361,43 @@ where
             }
         }
         events.clear();
+
+        self.quic_update();
+    }
+
+    /// Fires expired QUIC timers and sends queued datagrams.
+    fn quic_update(&mut self) {
+        for (family, quic) in &mut self.quic {
+            quic.handle_timeouts(&self.event_sender);
+            if let Err(err) = quic.flush() {
+                MioError::Quic(err).report();
+            }
+            let interests = if quic.has_transmits() {
+                mio::Interest::READABLE | mio::Interest::WRITABLE
+            } else {
+                mio::Interest::READABLE
+            };
+            let token = self.tokens.register(Token::Quic(*family));
+            if let Err(err) = self
+                .poll
+                .registry()
+                .reregister(quic.socket_mut(), token, interests)
+            {
+                MioError::Register(quic.local_addr(), err).report();
+            }
+        }
+    }
+
+    fn quic_bind(&mut self, addr: SocketAddr) -> io::Result<()> {
+        let family = IpFamily::of(&addr);
+        let mut quic = QuicEndpoint::bind(addr, &self.keypair)?;
+        self.poll.registry().register(
+            quic.socket_mut(),
+            self.tokens.register(Token::Quic(family)),
+            mio::Interest::READABLE,
+        )?;
+        self.quic.insert(family, quic);
+        Ok(())
     }
 
     fn handle(&mut self, cmd: MioCmd) {
@@ -381,6 +442,7 @@ where
                             let addr = ConnectionAddr {
                                 sock_addr: addr,
                                 incoming: true,
+                                transport: ConnectionTransport::Tcp,
                             };
 
                             listener.incomind_ready = false;
@@ -451,6 +513,7 @@ where
                         let addr = ConnectionAddr {
                             sock_addr: addr,
                             incoming: false,
+                            transport: ConnectionTransport::Tcp,
                         };
 
                         if let Err(err) = self.poll.registry().register(
@@ -479,6 +542,7 @@ where
                         ConnectionAddr {
                             sock_addr: addr,
                             incoming: false,
+                            transport: ConnectionTransport::Tcp,
                         },
                         Err(err.to_string()),
                     )),
@@ -582,6 +646,12 @@ where
                     }
                 }
             }
+            Disconnect(addr) if addr.transport.is_quic() => {
+                if let Some(quic) = self.quic.get_mut(&IpFamily::of(&addr.sock_addr)) {
+                    quic.disconnect(&addr, &self.event_sender);
+                }
+                self.send(MioEvent::ConnectionDidCloseOnDemand(addr));
+            }
             Disconnect(addr) => {
                 // drop the connection and destructor will close it
                 if let Some(mut cn) = self.connections.remove(&addr) {
@@ -592,6 +662,71 @@ where
                 }
                 self.send(MioEvent::ConnectionDidCloseOnDemand(addr));
             }
+            QuicListen(port) => {
+                // wildcard addresses, so that connections to any interface
+                // are accepted by a single endpoint per address family
+                let addrs: [SocketAddr; 2] = [
+                    (Ipv4Addr::UNSPECIFIED, port).into(),
+                    (Ipv6Addr::UNSPECIFIED, port).into(),
+                ];
+                for addr in addrs {
+                    match self.quic.get(&IpFamily::of(&addr)) {
+                        Some(quic) if quic.local_addr().port() == port => {}
+                        Some(_) => MioError::Listen(
+                            addr,
+                            io::Error::new(
+                                io::ErrorKind::AddrInUse,
+                                "QUIC endpoint is already bound to another port",
+                            ),
+                        )
+                        .report(),
+                        None => {
+                            if let Err(err) = self.quic_bind(addr) {
+                                MioError::Listen(addr, err).report();
+                            }
+                        }
+                    }
+                }
+            }
+            QuicConnect(addr) => {
+                if !self.quic.contains_key(&IpFamily::of(&addr)) {
+                    // not listening for QUIC connections, use any port
+                    let local_addr: SocketAddr = match addr {
+                        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+                        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+                    };
+                    if let Err(err) = self.quic_bind(local_addr) {
+                        let addr = ConnectionAddr {
+                            sock_addr: addr,
+                            incoming: false,
+                            transport: ConnectionTransport::Quic,
+                        };
+                        self.send(MioEvent::OutgoingConnectionDidConnect(
+                            addr,
+                            Err(err.to_string()),
+                        ));
+                        return;
+                    }
+                }
+                if let Some(quic) = self.quic.get_mut(&IpFamily::of(&addr)) {
+                    quic.connect(addr, &self.event_sender);
+                }
+            }
+            QuicStreamSend {
+                addr,
+                stream_id,
+                data,
+                fin,
+            } => {
+                if let Some(quic) = self.quic.get_mut(&IpFamily::of(&addr.sock_addr)) {
+                    quic.send_stream(addr, stream_id, data, fin, &self.event_sender);
+                }
+            }
+            QuicStreamReset { addr, stream_id } => {
+                if let Some(quic) = self.quic.get_mut(&IpFamily::of(&addr.sock_addr)) {
+                    quic.reset_stream(addr, stream_id, &self.event_sender);
+                }
+            }
         }
     }
 
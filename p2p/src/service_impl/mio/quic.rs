@@ -0,0 +1,552 @@
+//! QUIC (`/quic-v1`) transport.
+//!
+//! Connections are driven by the sans-io [`quinn_proto`] state machine over a
+//! non-blocking UDP socket, one endpoint per address family. The TLS 1.3 handshake authenticates the
+//! remote peer with the libp2p certificate, and streams are native QUIC
+//! bidirectional streams, so the state machine receives plain stream data.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::{Bytes, BytesMut};
+use libp2p_identity::Keypair;
+use mio::net::UdpSocket;
+use quinn_proto::{
+    ClientConfig, ConnectionHandle, DatagramEvent, Dir, EndpointConfig, Event, ReadError,
+    ServerConfig, StreamEvent, TransportConfig, VarInt,
+};
+
+use crate::{ConnectionAddr, ConnectionTransport, MioEvent, PeerId, StreamId};
+
+/// Maximal size of the UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 0x10000;
+/// Server name used by the libp2p QUIC transport, it is not verified.
+const SERVER_NAME: &str = "l";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum IpFamily {
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    pub fn of(addr: &SocketAddr) -> Self {
+        match addr {
+            SocketAddr::V4(_) => Self::V4,
+            SocketAddr::V6(_) => Self::V6,
+        }
+    }
+}
+
+pub(super) struct QuicEndpoint {
+    socket: UdpSocket,
+    local_addr: SocketAddr,
+    endpoint: quinn_proto::Endpoint,
+    client_config: ClientConfig,
+    connections: BTreeMap<ConnectionHandle, QuicConnection>,
+    handles: BTreeMap<ConnectionAddr, ConnectionHandle>,
+    transmits: VecDeque<(SocketAddr, Vec<u8>)>,
+    recv_buf: Vec<u8>,
+}
+
+struct QuicConnection {
+    addr: ConnectionAddr,
+    inner: quinn_proto::Connection,
+    authenticated: bool,
+    /// Closed on demand, kept only to let the close frame out.
+    closing: bool,
+    /// Streams as seen by the state machine.
+    streams: BTreeMap<StreamId, quinn_proto::StreamId>,
+    stream_ids: BTreeMap<quinn_proto::StreamId, StreamId>,
+    /// Data not yet accepted by the stream due to flow control, and
+    /// whether the stream should be finished once it is written.
+    pending: BTreeMap<quinn_proto::StreamId, (VecDeque<u8>, bool)>,
+    /// Whether the sending and the receiving side of the stream is
+    /// finished. The stream is forgotten once both are.
+    finished: BTreeMap<quinn_proto::StreamId, (bool, bool)>,
+    remote_streams: u32,
+}
+
+impl QuicEndpoint {
+    pub fn bind(addr: SocketAddr, keypair: &Keypair) -> io::Result<Self> {
+        let to_io_error = |err: String| io::Error::new(io::ErrorKind::Other, err);
+        let client_crypto = libp2p_tls::make_client_config(keypair, None)
+            .map_err(|err| to_io_error(err.to_string()))?;
+        let server_crypto =
+            libp2p_tls::make_server_config(keypair).map_err(|err| to_io_error(err.to_string()))?;
+
+        let mut transport = TransportConfig::default();
+        transport
+            .max_concurrent_uni_streams(0u32.into())
+            .max_concurrent_bidi_streams(256u32.into())
+            .keep_alive_interval(Some(Duration::from_secs(5)))
+            .max_idle_timeout(Some(VarInt::from_u32(30_000).into()))
+            .allow_spin(false)
+            .datagram_receive_buffer_size(None);
+        let transport = Arc::new(transport);
+
+        let mut client_config = ClientConfig::new(Arc::new(client_crypto));
+        client_config.transport_config(transport.clone());
+        let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
+        server_config.transport = transport;
+
+        let mut endpoint_config = EndpointConfig::default();
+        endpoint_config.supported_versions(vec![1]);
+
+        let socket = bind_socket(addr)?;
+        let local_addr = socket.local_addr()?;
+        Ok(Self {
+            socket,
+            local_addr,
+            endpoint: quinn_proto::Endpoint::new(
+                Arc::new(endpoint_config),
+                Some(Arc::new(server_config)),
+                false,
+            ),
+            client_config,
+            connections: BTreeMap::default(),
+            handles: BTreeMap::default(),
+            transmits: VecDeque::default(),
+            recv_buf: vec![0; MAX_DATAGRAM_SIZE],
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn socket_mut(&mut self) -> &mut UdpSocket {
+        &mut self.socket
+    }
+
+    pub fn has_transmits(&self) -> bool {
+        !self.transmits.is_empty()
+    }
+
+    /// Time until the closest connection timer fires.
+    pub fn timeout(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        self.connections
+            .values_mut()
+            .filter_map(|cn| cn.inner.poll_timeout())
+            .min()
+            .map(|deadline| deadline.saturating_duration_since(now))
+    }
+
+    pub fn connect<F: Fn(MioEvent)>(&mut self, sock_addr: SocketAddr, send: &F) {
+        let addr = ConnectionAddr {
+            sock_addr,
+            incoming: false,
+            transport: ConnectionTransport::Quic,
+        };
+        match self
+            .endpoint
+            .connect(self.client_config.clone(), sock_addr, SERVER_NAME)
+        {
+            Ok((handle, inner)) => {
+                self.handles.insert(addr, handle);
+                self.connections
+                    .insert(handle, QuicConnection::new(addr, inner));
+                self.drive(send);
+            }
+            Err(err) => send(MioEvent::OutgoingConnectionDidConnect(
+                addr,
+                Err(err.to_string()),
+            )),
+        }
+    }
+
+    pub fn send_stream<F: Fn(MioEvent)>(
+        &mut self,
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Box<[u8]>,
+        fin: bool,
+        send: &F,
+    ) {
+        let Some(cn) = self.connection_mut(&addr) else {
+            return;
+        };
+        let id = match cn.streams.get(&stream_id) {
+            Some(id) => *id,
+            None => match cn.inner.streams().open(Dir::Bi) {
+                Some(id) => {
+                    cn.streams.insert(stream_id, id);
+                    cn.stream_ids.insert(id, stream_id);
+                    id
+                }
+                None => {
+                    // the remote peer doesn't allow more streams
+                    send(MioEvent::QuicStreamDidReset { addr, stream_id });
+                    return;
+                }
+            },
+        };
+        let (buf, finish) = cn.pending.entry(id).or_default();
+        buf.extend(data.iter());
+        *finish |= fin;
+        cn.write_pending(id);
+        self.drive(send);
+    }
+
+    pub fn reset_stream<F: Fn(MioEvent)>(
+        &mut self,
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        send: &F,
+    ) {
+        let Some(cn) = self.connection_mut(&addr) else {
+            return;
+        };
+        if let Some(id) = cn.streams.get(&stream_id).copied() {
+            cn.forget_stream(id);
+            let _ = cn.inner.send_stream(id).reset(VarInt::from_u32(0));
+            let _ = cn.inner.recv_stream(id).stop(VarInt::from_u32(0));
+        }
+        self.drive(send);
+    }
+
+    pub fn disconnect<F: Fn(MioEvent)>(&mut self, addr: &ConnectionAddr, send: &F) {
+        let Some(handle) = self.handles.remove(addr) else {
+            return;
+        };
+        if let Some(cn) = self.connections.get_mut(&handle) {
+            cn.closing = true;
+            cn.inner
+                .close(Instant::now(), VarInt::from_u32(0), Bytes::new());
+        }
+        self.drive(send);
+    }
+
+    /// Reads all the available datagrams.
+    pub fn recv<F: Fn(MioEvent)>(&mut self, send: &F) -> io::Result<()> {
+        loop {
+            let (len, remote) = match self.socket.recv_from(&mut self.recv_buf) {
+                Ok(v) => v,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                // ICMP errors of a single peer must not stop the endpoint
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(err) => return Err(err),
+            };
+            let data = BytesMut::from(&self.recv_buf[..len]);
+            match self
+                .endpoint
+                .handle(Instant::now(), remote, None, None, data)
+            {
+                Some((handle, DatagramEvent::NewConnection(inner))) => {
+                    let addr = ConnectionAddr {
+                        sock_addr: remote,
+                        incoming: true,
+                        transport: ConnectionTransport::Quic,
+                    };
+                    if let Some(old) = self.handles.insert(addr, handle) {
+                        // the peer reconnected from the same address, the old
+                        // connection is closed and kept until it is drained
+                        if let Some(cn) = self.connections.get_mut(&old) {
+                            cn.inner
+                                .close(Instant::now(), VarInt::from_u32(0), Bytes::new());
+                            cn.lost("replaced by a new connection".to_owned(), send);
+                        }
+                    }
+                    self.connections
+                        .insert(handle, QuicConnection::new(addr, inner));
+                    send(MioEvent::IncomingConnectionDidAccept(Some(addr), Ok(())));
+                }
+                Some((handle, DatagramEvent::ConnectionEvent(event))) => {
+                    if let Some(cn) = self.connections.get_mut(&handle) {
+                        cn.inner.handle_event(event);
+                    }
+                }
+                None => {}
+            }
+        }
+        self.drive(send);
+        Ok(())
+    }
+
+    /// Sends queued datagrams until the socket would block.
+    pub fn flush(&mut self) -> io::Result<()> {
+        while let Some((remote, datagram)) = self.transmits.pop_front() {
+            match self.socket.send_to(&datagram, remote) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    self.transmits.push_front((remote, datagram));
+                    break;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                    self.transmits.push_front((remote, datagram));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle_timeouts<F: Fn(MioEvent)>(&mut self, send: &F) {
+        let now = Instant::now();
+        for cn in self.connections.values_mut() {
+            if cn
+                .inner
+                .poll_timeout()
+                .is_some_and(|deadline| deadline <= now)
+            {
+                cn.inner.handle_timeout(now);
+            }
+        }
+        self.drive(send);
+    }
+
+    fn connection_mut(&mut self, addr: &ConnectionAddr) -> Option<&mut QuicConnection> {
+        let handle = self.handles.get(addr)?;
+        self.connections.get_mut(handle)
+    }
+
+    /// Processes everything the connections produced, reports events and
+    /// queues datagrams.
+    fn drive<F: Fn(MioEvent)>(&mut self, send: &F) {
+        let now = Instant::now();
+        let mut drained = Vec::new();
+        for (handle, cn) in self.connections.iter_mut() {
+            while let Some(event) = cn.inner.poll_endpoint_events() {
+                if let Some(event) = self.endpoint.handle_event(*handle, event) {
+                    cn.inner.handle_event(event);
+                }
+            }
+            while let Some(event) = cn.inner.poll() {
+                if !cn.closing {
+                    cn.handle(event, send);
+                }
+            }
+            while let Some(transmit) = cn.inner.poll_transmit(now, 1) {
+                self.transmits
+                    .push_back((transmit.destination, transmit.contents));
+            }
+            if cn.inner.is_drained() {
+                drained.push(*handle);
+            }
+        }
+        for handle in drained {
+            if let Some(cn) = self.connections.remove(&handle) {
+                if self.handles.get(&cn.addr) == Some(&handle) {
+                    self.handles.remove(&cn.addr);
+                }
+            }
+        }
+        while let Some(transmit) = self.endpoint.poll_transmit() {
+            self.transmits
+                .push_back((transmit.destination, transmit.contents));
+        }
+    }
+}
+
+impl QuicConnection {
+    fn new(addr: ConnectionAddr, inner: quinn_proto::Connection) -> Self {
+        Self {
+            addr,
+            inner,
+            authenticated: false,
+            closing: false,
+            streams: BTreeMap::default(),
+            stream_ids: BTreeMap::default(),
+            pending: BTreeMap::default(),
+            finished: BTreeMap::default(),
+            remote_streams: 0,
+        }
+    }
+
+    /// Stream id for a stream opened by the remote peer. Stream ids of the
+    /// streams we open are odd for outgoing connections and even for
+    /// incoming ones, so these use the other parity.
+    fn next_remote_stream_id(&mut self) -> StreamId {
+        let n = self.remote_streams;
+        self.remote_streams = self.remote_streams.wrapping_add(1);
+        if self.addr.incoming {
+            n * 2 + 1
+        } else {
+            n * 2 + 2
+        }
+    }
+
+    fn remote_peer_id(&self) -> Result<PeerId, String> {
+        let certificates = self
+            .inner
+            .crypto_session()
+            .peer_identity()
+            .and_then(|identity| identity.downcast::<Vec<rustls::Certificate>>().ok())
+            .ok_or_else(|| "no peer certificate".to_owned())?;
+        let [certificate] = certificates.as_slice() else {
+            return Err("expected exactly one peer certificate".to_owned());
+        };
+        let peer_id = libp2p_tls::certificate::parse(certificate)
+            .map_err(|err| err.to_string())?
+            .peer_id();
+        PeerId::try_from(peer_id).map_err(|err| err.to_string())
+    }
+
+    fn handle<F: Fn(MioEvent)>(&mut self, event: Event, send: &F) {
+        let addr = self.addr;
+        match event {
+            Event::Connected => match self.remote_peer_id() {
+                Ok(peer_id) => {
+                    self.authenticated = true;
+                    send(MioEvent::QuicConnectionDidAuthenticate(addr, peer_id));
+                }
+                Err(err) => {
+                    self.inner
+                        .close(Instant::now(), VarInt::from_u32(0), Bytes::new());
+                    self.lost(err, send);
+                }
+            },
+            Event::ConnectionLost { reason } => self.lost(reason.to_string(), send),
+            Event::Stream(StreamEvent::Opened { dir: Dir::Bi }) => {
+                while let Some(id) = self.inner.streams().accept(Dir::Bi) {
+                    let stream_id = self.next_remote_stream_id();
+                    self.streams.insert(stream_id, id);
+                    self.stream_ids.insert(id, stream_id);
+                    send(MioEvent::QuicStreamDidOpen { addr, stream_id });
+                    self.read(id, send);
+                }
+            }
+            Event::Stream(StreamEvent::Readable { id }) => self.read(id, send),
+            Event::Stream(StreamEvent::Writable { id }) => self.write_pending(id),
+            Event::Stream(StreamEvent::Stopped { id, .. }) => {
+                if let Some(stream_id) = self.forget_stream(id) {
+                    send(MioEvent::QuicStreamDidReset { addr, stream_id });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lost<F: Fn(MioEvent)>(&mut self, reason: String, send: &F) {
+        // report the failure only once
+        if self.closing {
+            return;
+        }
+        self.closing = true;
+        if self.authenticated || self.addr.incoming {
+            send(MioEvent::ConnectionDidClose(self.addr, Err(reason)));
+        } else {
+            send(MioEvent::OutgoingConnectionDidConnect(
+                self.addr,
+                Err(reason),
+            ));
+        }
+    }
+
+    fn read<F: Fn(MioEvent)>(&mut self, id: quinn_proto::StreamId, send: &F) {
+        let Some(stream_id) = self.stream_ids.get(&id).copied() else {
+            return;
+        };
+        let mut data = Vec::new();
+        let mut fin = false;
+        let mut reset = false;
+        let mut recv_stream = self.inner.recv_stream(id);
+        match recv_stream.read(true) {
+            Ok(mut chunks) => {
+                loop {
+                    match chunks.next(usize::MAX) {
+                        Ok(Some(chunk)) => data.extend_from_slice(&chunk.bytes),
+                        Ok(None) => {
+                            fin = true;
+                            break;
+                        }
+                        Err(ReadError::Blocked) => break,
+                        Err(ReadError::Reset(_)) => {
+                            reset = true;
+                            break;
+                        }
+                    }
+                }
+                let _ = chunks.finalize();
+            }
+            // the stream is already finished or reset
+            Err(_) => return,
+        }
+
+        let addr = self.addr;
+        if !data.is_empty() || fin {
+            send(MioEvent::QuicStreamDataDidReceive {
+                addr,
+                stream_id,
+                data: data.into(),
+                fin,
+            });
+        }
+        if reset {
+            self.forget_stream(id);
+            send(MioEvent::QuicStreamDidReset { addr, stream_id });
+        } else if fin {
+            self.side_finished(id, false);
+        }
+    }
+
+    fn write_pending(&mut self, id: quinn_proto::StreamId) {
+        let Some((buf, fin)) = self.pending.get_mut(&id) else {
+            return;
+        };
+        let mut send_stream = self.inner.send_stream(id);
+        while !buf.is_empty() {
+            let (chunk, _) = buf.as_slices();
+            match send_stream.write(chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(len) => {
+                    buf.drain(..len);
+                }
+            }
+        }
+        if !buf.is_empty() {
+            return;
+        }
+        let finished = *fin && send_stream.finish().is_ok();
+        self.pending.remove(&id);
+        if finished {
+            self.side_finished(id, true);
+        }
+    }
+
+    /// Marks the sending or the receiving side of the stream finished.
+    fn side_finished(&mut self, id: quinn_proto::StreamId, is_send: bool) {
+        let (send, recv) = self.finished.entry(id).or_default();
+        if is_send {
+            *send = true;
+        } else {
+            *recv = true;
+        }
+        if *send && *recv {
+            self.forget_stream(id);
+        }
+    }
+
+    /// Drops the mapping of the stream, returns its id as seen by the
+    /// state machine.
+    fn forget_stream(&mut self, id: quinn_proto::StreamId) -> Option<StreamId> {
+        self.pending.remove(&id);
+        self.finished.remove(&id);
+        let stream_id = self.stream_ids.remove(&id)?;
+        self.streams.remove(&stream_id);
+        Some(stream_id)
+    }
+}
+
+/// Binds the non-blocking UDP socket. IPv6 socket only accepts IPv6, so that
+/// the IPv4 endpoint can be bound to the same port.
+fn bind_socket(addr: SocketAddr) -> io::Result<UdpSocket> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::for_address(addr),
+        socket2::Type::DGRAM,
+        Some(socket2::Protocol::UDP),
+    )?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    Ok(UdpSocket::from_std(socket.into()))
+}
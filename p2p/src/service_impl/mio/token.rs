@@ -2,10 +2,14 @@ use std::{collections::BTreeMap, net::SocketAddr};
 
 use crate::ConnectionAddr;
 
+use super::quic::IpFamily;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Token {
     Waker,
     Listener(SocketAddr),
+    /// UDP socket of the QUIC endpoint.
+    Quic(IpFamily),
     Connection(ConnectionAddr),
 }
 
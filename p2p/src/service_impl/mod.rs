@@ -1,9 +1,10 @@
 #[cfg(feature = "p2p-libp2p")]
 pub mod mio;
+#[cfg(feature = "p2p-libp2p")]
+pub mod nat;
 #[cfg(feature = "p2p-webrtc")]
 pub mod webrtc;
 pub mod webrtc_with_libp2p;
-
 use std::future::Future;
 
 pub trait TaskSpawner: Send + Clone {
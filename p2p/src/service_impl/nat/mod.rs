@@ -0,0 +1,373 @@
+//! NAT traversal for the libp2p transport: port mapping on the gateway and
+//! dialing back the peers that ask for it with AutoNAT.
+
+pub mod natpmp;
+pub mod upnp;
+
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, UdpSocket},
+    time::Duration,
+};
+
+use crate::{
+    nat::{P2pNatPortMapping, P2pNatPortMappingProtocol},
+    Data, NoiseState, P2pNetworkNoiseStateInitiator, PeerId, Sk,
+};
+use salsa_simple::XSalsa20;
+
+/// Timeout of a single request to the gateway.
+const GATEWAY_REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+/// Timeout of connecting to a peer we dial back, and of each read and write
+/// while checking its identity.
+const DIAL_BACK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Protocols negotiated with multistream-select when dialing back.
+const MULTISTREAM_PROTOCOL: &[u8] = b"/multistream/1.0.0\n";
+const NOISE_PROTOCOL: &[u8] = b"/noise\n";
+
+/// Transport protocol of the port to map on the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortTransport {
+    Tcp,
+    Udp,
+}
+
+/// Asks the gateway to forward the TCP `port` to this host, trying PCP,
+/// NAT-PMP and UPnP IGD in that order. If `quic` is set, the UDP `port` is
+/// mapped too, with the protocol that mapped the TCP one. Failing to map
+/// UDP is not an error, TCP mapping is useful on its own.
+pub fn map_port(port: u16, quic: bool, lifetime: Duration) -> Result<P2pNatPortMapping, String> {
+    let mut errors = Vec::new();
+    for protocol in [
+        P2pNatPortMappingProtocol::Pcp,
+        P2pNatPortMappingProtocol::NatPmp,
+        P2pNatPortMappingProtocol::Upnp,
+    ] {
+        match map_port_with(protocol, port, PortTransport::Tcp, lifetime) {
+            Ok(mut mapping) => {
+                if quic {
+                    mapping.quic_external_addr =
+                        map_port_with(protocol, port, PortTransport::Udp, lifetime)
+                            .ok()
+                            .map(|mapping| mapping.external_addr);
+                }
+                return Ok(mapping);
+            }
+            Err(err) => errors.push(format!("{protocol}: {err}")),
+        }
+    }
+    Err(errors.join(", "))
+}
+
+fn map_port_with(
+    protocol: P2pNatPortMappingProtocol,
+    port: u16,
+    transport: PortTransport,
+    lifetime: Duration,
+) -> Result<P2pNatPortMapping, String> {
+    match protocol {
+        P2pNatPortMappingProtocol::Pcp => {
+            let gateway = default_gateway_addr()?;
+            let local_ip = local_ip_for(gateway)?;
+            natpmp::pcp_map_port(
+                gateway,
+                local_ip,
+                port,
+                transport,
+                lifetime,
+                GATEWAY_REQUEST_TIMEOUT,
+            )
+        }
+        P2pNatPortMappingProtocol::NatPmp => natpmp::nat_pmp_map_port(
+            default_gateway_addr()?,
+            port,
+            transport,
+            lifetime,
+            GATEWAY_REQUEST_TIMEOUT,
+        ),
+        P2pNatPortMappingProtocol::Upnp => upnp::upnp_map_port(
+            upnp::SSDP_ADDR,
+            port,
+            transport,
+            lifetime,
+            GATEWAY_REQUEST_TIMEOUT,
+        ),
+    }
+}
+
+/// Address at which the default gateway answers NAT-PMP and PCP requests.
+fn default_gateway_addr() -> Result<SocketAddr, String> {
+    default_gateway().map(|gateway| SocketAddr::new(gateway.into(), natpmp::NAT_PMP_PORT))
+}
+
+/// Connects to the addresses one by one, returns the first one at which
+/// the peer proved its identity in the security handshake. The connection
+/// is closed right after that, we only need to know that this very peer can
+/// be reached at the address. `pnet_key` is the private network key of our
+/// chain.
+pub fn dial_back(
+    addrs: &[SocketAddr],
+    peer_id: PeerId,
+    pnet_key: [u8; 32],
+) -> Result<SocketAddr, String> {
+    let mut errors = Vec::new();
+    for addr in addrs {
+        let result = TcpStream::connect_timeout(addr, DIAL_BACK_TIMEOUT)
+            .map_err(|err| err.to_string())
+            .and_then(|stream| PnetStream::new(stream, pnet_key))
+            .and_then(|stream| verify_peer_id(stream, peer_id));
+        match result {
+            Ok(()) => return Ok(*addr),
+            Err(err) => errors.push(format!("{addr}: {err}")),
+        }
+    }
+    Err(errors.join(", "))
+}
+
+/// Blocking TCP stream in the private network layer that every libp2p
+/// connection of the chain starts with.
+struct PnetStream {
+    stream: TcpStream,
+    send_cipher: XSalsa20,
+    recv_cipher: XSalsa20,
+}
+
+impl PnetStream {
+    /// Exchanges the nonces with the peer.
+    fn new(mut stream: TcpStream, pnet_key: [u8; 32]) -> Result<Self, String> {
+        stream
+            .set_read_timeout(Some(DIAL_BACK_TIMEOUT))
+            .and_then(|()| stream.set_write_timeout(Some(DIAL_BACK_TIMEOUT)))
+            .map_err(|err| err.to_string())?;
+        let nonce: [u8; 24] = rand::random();
+        stream.write_all(&nonce).map_err(|err| err.to_string())?;
+        let mut remote_nonce = [0; 24];
+        stream
+            .read_exact(&mut remote_nonce)
+            .map_err(|err| err.to_string())?;
+        Ok(PnetStream {
+            stream,
+            send_cipher: XSalsa20::new(pnet_key, nonce),
+            recv_cipher: XSalsa20::new(pnet_key, remote_nonce),
+        })
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> Result<(), String> {
+        let mut data = data.to_vec();
+        self.send_cipher.apply_keystream(&mut data);
+        self.stream.write_all(&data).map_err(|err| err.to_string())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), String> {
+        self.stream.read_exact(buf).map_err(|err| err.to_string())?;
+        self.recv_cipher.apply_keystream(buf);
+        Ok(())
+    }
+}
+
+/// Negotiates noise with multistream-select and checks the identity that
+/// the peer signs in the second handshake message. The handshake is not
+/// finished, nothing is sent over the connection afterwards.
+fn verify_peer_id(mut stream: PnetStream, peer_id: PeerId) -> Result<(), String> {
+    let mut request = Vec::new();
+    for protocol in [MULTISTREAM_PROTOCOL, NOISE_PROTOCOL] {
+        request.push(protocol.len() as u8);
+        request.extend_from_slice(protocol);
+    }
+    stream.write_all(&request)?;
+    loop {
+        match read_multistream_message(&mut stream)?.as_slice() {
+            NOISE_PROTOCOL => break,
+            MULTISTREAM_PROTOCOL => continue,
+            _ => return Err("noise is not supported".to_owned()),
+        }
+    }
+
+    let i_esk = Sk::from_random(rand::random());
+    let i_ssk = Sk::from_random(rand::random());
+    let epk = i_esk.pk();
+    let mut noise = NoiseState::new(*b"Noise_XX_25519_ChaChaPoly_SHA256");
+    noise.mix_hash(b"");
+    noise.mix_hash(epk.0.as_bytes());
+    noise.mix_hash(b"");
+    let mut initiator = P2pNetworkNoiseStateInitiator {
+        i_esk,
+        i_spk: i_ssk.pk(),
+        i_ssk,
+        r_epk: None,
+        payload: Data::empty(),
+        noise,
+        remote_pk: None,
+    };
+
+    let mut chunk = vec![0, 32];
+    chunk.extend_from_slice(epk.0.as_bytes());
+    stream.write_all(&chunk)?;
+
+    let mut len = [0; 2];
+    stream.read_exact(&mut len)?;
+    let mut chunk = vec![0; 2 + u16::from_be_bytes(len) as usize];
+    chunk[..2].copy_from_slice(&len);
+    stream.read_exact(&mut chunk[2..])?;
+    initiator
+        .consume(&mut chunk)
+        .map_err(|err| err.to_string())?;
+
+    match initiator.remote_pk.map(|pk| pk.peer_id()) {
+        Some(remote_peer_id) if remote_peer_id == peer_id => Ok(()),
+        Some(remote_peer_id) => Err(format!("expected {peer_id}, got {remote_peer_id}")),
+        None => Err("no remote identity".to_owned()),
+    }
+}
+
+/// Reads one length-prefixed multistream-select message. The protocols we
+/// expect are short, so longer messages are rejected.
+fn read_multistream_message(stream: &mut PnetStream) -> Result<Vec<u8>, String> {
+    let mut len = [0; 1];
+    stream.read_exact(&mut len)?;
+    if len[0] & 0x80 != 0 {
+        return Err("multistream message is too long".to_owned());
+    }
+    let mut message = vec![0; len[0] as usize];
+    stream.read_exact(&mut message)?;
+    Ok(message)
+}
+
+/// Local address that is used to reach the `remote` host. Connecting UDP
+/// socket doesn't send anything, only selects the route.
+pub(super) fn local_ip_for(remote: SocketAddr) -> Result<IpAddr, String> {
+    let bind_addr: SocketAddr = match remote {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (std::net::Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(bind_addr).map_err(|err| err.to_string())?;
+    socket.connect(remote).map_err(|err| err.to_string())?;
+    socket
+        .local_addr()
+        .map(|addr| addr.ip())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Result<Ipv4Addr, String> {
+    let routes = std::fs::read_to_string("/proc/net/route").map_err(|err| err.to_string())?;
+    parse_default_gateway(&routes).ok_or_else(|| "no default gateway".to_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Result<Ipv4Addr, String> {
+    Err("default gateway detection is not supported on this platform".to_owned())
+}
+
+/// Finds the default route in the `/proc/net/route` table. Addresses there
+/// are hex encoded in the host byte order.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut columns = line.split_whitespace();
+        let destination = columns.nth(1)?;
+        let gateway = columns.next()?;
+        if destination != "00000000" {
+            return None;
+        }
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        Some(Ipv4Addr::from(gateway.to_le_bytes())).filter(|ip| !ip.is_unspecified())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use crate::{identity::SecretKey, P2pNetworkNoiseStateResponder};
+
+    use super::*;
+
+    #[test]
+    fn default_gateway_from_route_table() {
+        let routes = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0
+eth0\t00000000\t0100A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+";
+        assert_eq!(
+            parse_default_gateway(routes),
+            Some(Ipv4Addr::new(192, 168, 0, 1))
+        );
+        assert_eq!(parse_default_gateway(routes.lines().next().unwrap()), None);
+    }
+
+    const PNET_KEY: [u8; 32] = [7; 32];
+
+    /// Accepts a single connection and answers as a libp2p peer with the
+    /// `secret_key` identity up to the second noise handshake message.
+    fn spawn_noise_responder(listener: TcpListener, mut secret_key: SecretKey) {
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut stream = PnetStream::new(stream, PNET_KEY).unwrap();
+            for _ in 0..2 {
+                let message = read_multistream_message(&mut stream).unwrap();
+                stream.write_all(&[message.len() as u8]).unwrap();
+                stream.write_all(&message).unwrap();
+            }
+
+            let r_ssk = Sk::from_random(rand::random());
+            let r_spk = r_ssk.pk();
+            let keypair = libp2p_identity::Keypair::try_from(secret_key.clone()).unwrap();
+            let signature = secret_key
+                .sign(&[b"noise-libp2p-static-key:", r_spk.0.as_bytes().as_ref()].concat());
+            let mut payload = b"\x0a\x24".to_vec();
+            payload.extend_from_slice(&keypair.public().encode_protobuf());
+            payload.extend_from_slice(b"\x12\x40");
+            payload.extend_from_slice(&signature.to_bytes());
+
+            let mut noise = NoiseState::new(*b"Noise_XX_25519_ChaChaPoly_SHA256");
+            noise.mix_hash(b"");
+            let mut responder = P2pNetworkNoiseStateResponder::Init {
+                r_esk: Sk::from_random(rand::random()),
+                r_spk,
+                r_ssk,
+                buffer: vec![],
+                payload: payload.into(),
+                noise,
+            };
+
+            let mut chunk = vec![0; 34];
+            stream.read_exact(&mut chunk).unwrap();
+            responder.consume(&mut chunk).unwrap();
+            let chunk = responder.generate(&[]).unwrap();
+            stream.write_all(&chunk).unwrap();
+        });
+    }
+
+    #[test]
+    fn dial_back_picks_reachable_addr() {
+        let secret_key = SecretKey::rand();
+        let peer_id = secret_key.public_key().peer_id();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let reachable = listener.local_addr().unwrap();
+        spawn_noise_responder(listener, secret_key);
+        // bound but not listening, so connection is refused
+        let unreachable = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        assert_eq!(
+            dial_back(&[unreachable, reachable], peer_id, PNET_KEY),
+            Ok(reachable)
+        );
+        assert!(dial_back(&[unreachable], peer_id, PNET_KEY).is_err());
+    }
+
+    #[test]
+    fn dial_back_rejects_other_peer() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        spawn_noise_responder(listener, SecretKey::rand());
+
+        let expected = SecretKey::rand().public_key().peer_id();
+        assert!(dial_back(&[addr], expected, PNET_KEY).is_err());
+    }
+}
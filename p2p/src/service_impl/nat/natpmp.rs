@@ -0,0 +1,294 @@
+//! Minimal NAT-PMP ([RFC 6886](https://www.rfc-editor.org/rfc/rfc6886))
+//! and PCP ([RFC 6887](https://www.rfc-editor.org/rfc/rfc6887)) clients,
+//! only mapping a TCP or UDP port is supported.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    time::Duration,
+};
+
+use crate::nat::{P2pNatPortMapping, P2pNatPortMappingProtocol};
+
+use super::PortTransport;
+
+/// Port the gateway listens on for both NAT-PMP and PCP requests.
+pub const NAT_PMP_PORT: u16 = 5351;
+
+const NAT_PMP_VERSION: u8 = 0;
+const NAT_PMP_OP_EXTERNAL_ADDR: u8 = 0;
+const NAT_PMP_OP_MAP_UDP: u8 = 1;
+const NAT_PMP_OP_MAP_TCP: u8 = 2;
+const NAT_PMP_RESPONSE: u8 = 0x80;
+
+const PCP_VERSION: u8 = 2;
+const PCP_OP_MAP: u8 = 1;
+const PCP_RESPONSE: u8 = 0x80;
+const PCP_MAP_LEN: usize = 60;
+
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+
+/// Maps the `port` with PCP MAP request. `local_ip` is the address of
+/// this host in the gateway's network.
+pub fn pcp_map_port(
+    gateway: SocketAddr,
+    local_ip: IpAddr,
+    port: u16,
+    transport: PortTransport,
+    lifetime: Duration,
+    timeout: Duration,
+) -> Result<P2pNatPortMapping, String> {
+    let nonce: [u8; 12] = rand::random();
+    let lifetime = lifetime.as_secs().try_into().unwrap_or(u32::MAX);
+
+    let mut request = [0; PCP_MAP_LEN];
+    request[0] = PCP_VERSION;
+    request[1] = PCP_OP_MAP;
+    request[4..8].copy_from_slice(&lifetime.to_be_bytes());
+    request[8..24].copy_from_slice(&to_ipv6(local_ip).octets());
+    request[24..36].copy_from_slice(&nonce);
+    request[36] = match transport {
+        PortTransport::Tcp => PROTOCOL_TCP,
+        PortTransport::Udp => PROTOCOL_UDP,
+    };
+    request[40..42].copy_from_slice(&port.to_be_bytes());
+    request[42..44].copy_from_slice(&port.to_be_bytes());
+    // no preference for the external address
+    request[44..60].copy_from_slice(&to_ipv6(Ipv4Addr::UNSPECIFIED.into()).octets());
+
+    let response = request_response(gateway, &request, timeout)?;
+    if response.len() < 4 || response[0] != PCP_VERSION {
+        // NAT-PMP only gateway replies with its own version
+        return Err("PCP is not supported by the gateway".to_owned());
+    }
+    if response[1] != PCP_RESPONSE | PCP_OP_MAP {
+        return Err(format!("unexpected PCP opcode {}", response[1]));
+    }
+    if response[3] != 0 {
+        return Err(format!("PCP result code {}", response[3]));
+    }
+    if response.len() < PCP_MAP_LEN || response[24..36] != nonce {
+        return Err("malformed PCP response".to_owned());
+    }
+
+    let lifetime = u32::from_be_bytes(response[4..8].try_into().expect("checked length"));
+    let external_port = u16::from_be_bytes([response[42], response[43]]);
+    let external_ip = Ipv6Addr::from(<[u8; 16]>::try_from(&response[44..60]).expect("checked"));
+    let external_ip = match external_ip.to_ipv4_mapped() {
+        Some(ip) => IpAddr::V4(ip),
+        None => IpAddr::V6(external_ip),
+    };
+
+    Ok(P2pNatPortMapping {
+        protocol: P2pNatPortMappingProtocol::Pcp,
+        external_addr: SocketAddr::new(external_ip, external_port),
+        quic_external_addr: None,
+        lifetime: Duration::from_secs(lifetime.into()),
+    })
+}
+
+/// Maps the `port` with NAT-PMP. Unlike PCP, NAT-PMP needs a separate
+/// request to learn the external address.
+pub fn nat_pmp_map_port(
+    gateway: SocketAddr,
+    port: u16,
+    transport: PortTransport,
+    lifetime: Duration,
+    timeout: Duration,
+) -> Result<P2pNatPortMapping, String> {
+    let response = request_response(
+        gateway,
+        &[NAT_PMP_VERSION, NAT_PMP_OP_EXTERNAL_ADDR],
+        timeout,
+    )?;
+    let response = nat_pmp_check_response(&response, NAT_PMP_OP_EXTERNAL_ADDR, 12)?;
+    let external_ip = Ipv4Addr::new(response[8], response[9], response[10], response[11]);
+
+    let op = match transport {
+        PortTransport::Tcp => NAT_PMP_OP_MAP_TCP,
+        PortTransport::Udp => NAT_PMP_OP_MAP_UDP,
+    };
+    let lifetime = lifetime.as_secs().try_into().unwrap_or(u32::MAX);
+    let mut request = [0; 12];
+    request[0] = NAT_PMP_VERSION;
+    request[1] = op;
+    request[4..6].copy_from_slice(&port.to_be_bytes());
+    request[6..8].copy_from_slice(&port.to_be_bytes());
+    request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+
+    let response = request_response(gateway, &request, timeout)?;
+    let response = nat_pmp_check_response(&response, op, 16)?;
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    let lifetime = u32::from_be_bytes(response[12..16].try_into().expect("checked length"));
+
+    Ok(P2pNatPortMapping {
+        protocol: P2pNatPortMappingProtocol::NatPmp,
+        external_addr: SocketAddr::new(external_ip.into(), external_port),
+        quic_external_addr: None,
+        lifetime: Duration::from_secs(lifetime.into()),
+    })
+}
+
+fn nat_pmp_check_response(response: &[u8], op: u8, len: usize) -> Result<&[u8], String> {
+    if response.len() < len
+        || response[0] != NAT_PMP_VERSION
+        || response[1] != NAT_PMP_RESPONSE | op
+    {
+        return Err("malformed NAT-PMP response".to_owned());
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(response),
+        code => Err(format!("NAT-PMP result code {code}")),
+    }
+}
+
+fn request_response(
+    gateway: SocketAddr,
+    request: &[u8],
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let bind_addr: SocketAddr = match gateway {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(bind_addr).map_err(|err| err.to_string())?;
+    socket.connect(gateway).map_err(|err| err.to_string())?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .map_err(|err| err.to_string())?;
+    socket.send(request).map_err(|err| err.to_string())?;
+
+    let mut buf = [0; 1100];
+    let len = socket.recv(&mut buf).map_err(|err| err.to_string())?;
+    Ok(buf[..len].to_vec())
+}
+
+fn to_ipv6(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Fake gateway that answers the requests with the `reply`.
+    fn fake_gateway(
+        reply: impl 'static + Send + Fn(&[u8]) -> Vec<u8>,
+        requests: usize,
+    ) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buf = [0; 1100];
+            for _ in 0..requests {
+                let (len, from) = socket.recv_from(&mut buf).unwrap();
+                socket.send_to(&reply(&buf[..len]), from).unwrap();
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn pcp_map() {
+        let gateway = fake_gateway(
+            |request| {
+                assert_eq!(request.len(), PCP_MAP_LEN);
+                assert_eq!(&request[..2], &[PCP_VERSION, PCP_OP_MAP]);
+                assert_eq!(request[36], PROTOCOL_UDP);
+                let mut response = request.to_vec();
+                response[1] = PCP_RESPONSE | PCP_OP_MAP;
+                response[3] = 0;
+                response[4..8].copy_from_slice(&600u32.to_be_bytes());
+                response[42..44].copy_from_slice(&18302u16.to_be_bytes());
+                response[44..60]
+                    .copy_from_slice(&Ipv4Addr::new(203, 0, 113, 7).to_ipv6_mapped().octets());
+                response
+            },
+            1,
+        );
+
+        let mapping = pcp_map_port(
+            gateway,
+            Ipv4Addr::LOCALHOST.into(),
+            8302,
+            PortTransport::Udp,
+            Duration::from_secs(3600),
+            TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(mapping.protocol, P2pNatPortMappingProtocol::Pcp);
+        assert_eq!(mapping.external_addr, "203.0.113.7:18302".parse().unwrap());
+        assert_eq!(mapping.lifetime, Duration::from_secs(600));
+    }
+
+    #[test]
+    fn pcp_unsupported_by_nat_pmp_gateway() {
+        // NAT-PMP gateway replies "unsupported version"
+        let gateway = fake_gateway(|_| vec![NAT_PMP_VERSION, NAT_PMP_RESPONSE, 0, 1], 1);
+
+        let result = pcp_map_port(
+            gateway,
+            Ipv4Addr::LOCALHOST.into(),
+            8302,
+            PortTransport::Tcp,
+            Duration::from_secs(3600),
+            TIMEOUT,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn nat_pmp_map() {
+        let gateway = fake_gateway(
+            |request| match request[1] {
+                NAT_PMP_OP_EXTERNAL_ADDR => {
+                    vec![0, 128, 0, 0, 0, 0, 0, 1, 203, 0, 113, 7]
+                }
+                NAT_PMP_OP_MAP_TCP => {
+                    assert_eq!(&request[4..6], &8302u16.to_be_bytes());
+                    let mut response = vec![0, 130, 0, 0, 0, 0, 0, 1];
+                    response.extend_from_slice(&request[4..6]);
+                    response.extend_from_slice(&request[6..8]);
+                    response.extend_from_slice(&7200u32.to_be_bytes());
+                    response
+                }
+                op => panic!("unexpected opcode {op}"),
+            },
+            2,
+        );
+
+        let mapping = nat_pmp_map_port(
+            gateway,
+            8302,
+            PortTransport::Tcp,
+            Duration::from_secs(7200),
+            TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(mapping.protocol, P2pNatPortMappingProtocol::NatPmp);
+        assert_eq!(mapping.external_addr, "203.0.113.7:8302".parse().unwrap());
+        assert_eq!(mapping.lifetime, Duration::from_secs(7200));
+    }
+
+    #[test]
+    fn nat_pmp_error() {
+        // "not authorized/refused"
+        let gateway = fake_gateway(|_| vec![0, 128, 0, 2, 0, 0, 0, 1, 0, 0, 0, 0], 1);
+
+        let result = nat_pmp_map_port(
+            gateway,
+            8302,
+            PortTransport::Udp,
+            Duration::from_secs(7200),
+            TIMEOUT,
+        );
+        assert_eq!(result.unwrap_err(), "NAT-PMP result code 2");
+    }
+}
@@ -0,0 +1,457 @@
+//! Minimal UPnP IGD client: finds the gateway with SSDP and asks its
+//! `WANIPConnection` (or `WANPPPConnection`) service to forward a TCP or
+//! UDP port.
+
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use url::Url;
+
+use crate::nat::{P2pNatPortMapping, P2pNatPortMappingProtocol};
+
+use super::PortTransport;
+
+/// SSDP multicast address.
+pub const SSDP_ADDR: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(239, 255, 255, 250)), 1900);
+
+const IGD_DEVICE: &str = "urn:schemas-upnp-org:device:InternetGatewayDevice:1";
+const WAN_CONNECTION_SERVICES: [&str; 2] = [
+    "urn:schemas-upnp-org:service:WANIPConnection:",
+    "urn:schemas-upnp-org:service:WANPPPConnection:",
+];
+
+/// Size limit of the HTTP responses we accept from the gateway.
+const HTTP_RESPONSE_SIZE_LIMIT: u64 = 1 << 20;
+
+/// Forwards the `port` of this host to the same external port of the
+/// gateway found at `ssdp_addr`.
+pub fn upnp_map_port(
+    ssdp_addr: SocketAddr,
+    port: u16,
+    transport: PortTransport,
+    lifetime: Duration,
+    timeout: Duration,
+) -> Result<P2pNatPortMapping, String> {
+    let location = discover(ssdp_addr, timeout)?;
+    let (status, description) = http_request("GET", &location, &[], "", timeout)?;
+    if status != 200 {
+        return Err(format!("error fetching {location}: HTTP {status}"));
+    }
+    let (service_type, control_url) = find_wan_connection_service(&description)
+        .ok_or_else(|| "no WAN connection service in the gateway description".to_owned())?;
+    let control_url = Url::parse(&location)
+        .and_then(|location| location.join(&control_url))
+        .map_err(|err| err.to_string())?;
+
+    let external_ip = soap_request(
+        &control_url,
+        &service_type,
+        "GetExternalIPAddress",
+        &[],
+        timeout,
+    )
+    .and_then(|response| {
+        xml_value(&response, "NewExternalIPAddress")
+            .and_then(|ip| ip.parse::<IpAddr>().ok())
+            .ok_or_else(|| "no external address in the response".to_owned())
+    })?;
+
+    let gateway_addr = control_url
+        .socket_addrs(|| None)
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("cannot resolve {control_url}"))?;
+    let local_ip = super::local_ip_for(gateway_addr)?;
+
+    let protocol = match transport {
+        PortTransport::Tcp => "TCP",
+        PortTransport::Udp => "UDP",
+    };
+    let port_str = port.to_string();
+    let lease_duration = lifetime.as_secs().to_string();
+    soap_request(
+        &control_url,
+        &service_type,
+        "AddPortMapping",
+        &[
+            ("NewRemoteHost", ""),
+            ("NewExternalPort", &port_str),
+            ("NewProtocol", protocol),
+            ("NewInternalPort", &port_str),
+            ("NewInternalClient", &local_ip.to_string()),
+            ("NewEnabled", "1"),
+            ("NewPortMappingDescription", "openmina"),
+            ("NewLeaseDuration", &lease_duration),
+        ],
+        timeout,
+    )?;
+
+    Ok(P2pNatPortMapping {
+        protocol: P2pNatPortMappingProtocol::Upnp,
+        external_addr: SocketAddr::new(external_ip, port),
+        quic_external_addr: None,
+        lifetime,
+    })
+}
+
+/// Sends SSDP search request and returns the location of the gateway
+/// description.
+fn discover(ssdp_addr: SocketAddr, timeout: Duration) -> Result<String, String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|err| err.to_string())?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .map_err(|err| err.to_string())?;
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\n\
+         HOST: {ssdp_addr}\r\n\
+         MAN: \"ssdp:discover\"\r\n\
+         MX: 2\r\n\
+         ST: {IGD_DEVICE}\r\n\r\n"
+    );
+    socket
+        .send_to(request.as_bytes(), ssdp_addr)
+        .map_err(|err| err.to_string())?;
+
+    let mut buf = [0; 2048];
+    loop {
+        let (len, _) = socket
+            .recv_from(&mut buf)
+            .map_err(|err| format!("no UPnP gateway found: {err}"))?;
+        let response = String::from_utf8_lossy(&buf[..len]);
+        if let Some(location) = header_value(&response, "location") {
+            return Ok(location.to_owned());
+        }
+    }
+}
+
+fn find_wan_connection_service(description: &str) -> Option<(String, String)> {
+    description.split("<service>").skip(1).find_map(|service| {
+        let service_type = xml_value(service, "serviceType")?;
+        if !WAN_CONNECTION_SERVICES
+            .iter()
+            .any(|prefix| service_type.starts_with(prefix))
+        {
+            return None;
+        }
+        let control_url = xml_value(service, "controlURL")?;
+        Some((service_type.to_owned(), control_url.to_owned()))
+    })
+}
+
+fn soap_request(
+    control_url: &Url,
+    service_type: &str,
+    action: &str,
+    args: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<String, String> {
+    let args = args
+        .iter()
+        .map(|(name, value)| format!("<{name}>{value}</{name}>"))
+        .collect::<String>();
+    let body = format!(
+        "<?xml version=\"1.0\"?>\
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+         s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+         <s:Body><u:{action} xmlns:u=\"{service_type}\">{args}</u:{action}></s:Body>\
+         </s:Envelope>"
+    );
+    let soap_action = format!("\"{service_type}#{action}\"");
+    let headers = [
+        ("Content-Type", "text/xml; charset=\"utf-8\""),
+        ("SOAPAction", soap_action.as_str()),
+    ];
+
+    let (status, response) = http_request("POST", control_url.as_str(), &headers, &body, timeout)?;
+    if status != 200 {
+        let error = xml_value(&response, "errorDescription")
+            .or_else(|| xml_value(&response, "errorCode"))
+            .unwrap_or("unknown error");
+        return Err(format!("{action} failed: HTTP {status}, {error}"));
+    }
+    Ok(response)
+}
+
+/// Makes HTTP/1.1 request and returns the status code and the body.
+fn http_request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+    timeout: Duration,
+) -> Result<(u16, String), String> {
+    let url = Url::parse(url).map_err(|err| err.to_string())?;
+    if url.scheme() != "http" {
+        return Err(format!("unsupported url: {url}"));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("no host in url: {url}"))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|err| err.to_string())?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {host}"))?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, timeout).map_err(|err| err.to_string())?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .map_err(|err| err.to_string())?;
+    stream
+        .set_write_timeout(Some(timeout))
+        .map_err(|err| err.to_string())?;
+
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_owned(),
+    };
+    let mut request = format!(
+        "{method} {path} HTTP/1.1\r\n\
+         Host: {host}:{port}\r\n\
+         Connection: close\r\n\
+         Content-Length: {}\r\n",
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|err| err.to_string())?;
+
+    let mut response = Vec::new();
+    stream
+        .take(HTTP_RESPONSE_SIZE_LIMIT)
+        .read_to_end(&mut response)
+        .map_err(|err| err.to_string())?;
+    parse_http_response(&String::from_utf8_lossy(&response))
+}
+
+fn parse_http_response(response: &str) -> Result<(u16, String), String> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| "malformed HTTP response".to_owned())?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| "malformed HTTP status line".to_owned())?;
+
+    let chunked = header_value(head, "transfer-encoding")
+        .is_some_and(|value| value.eq_ignore_ascii_case("chunked"));
+    let body = if chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_owned()
+    };
+    Ok((status, body))
+}
+
+fn decode_chunked(mut body: &str) -> Result<String, String> {
+    let mut decoded = String::new();
+    loop {
+        let (size, rest) = body
+            .split_once("\r\n")
+            .ok_or_else(|| "malformed chunked body".to_owned())?;
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|err| err.to_string())?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk = rest
+            .get(..size)
+            .ok_or_else(|| "truncated chunked body".to_owned())?;
+        decoded.push_str(chunk);
+        body = rest[size..].trim_start_matches("\r\n");
+    }
+}
+
+fn header_value<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().find_map(|line| {
+        let (header, value) = line.split_once(':')?;
+        header
+            .trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim())
+    })
+}
+
+/// Returns the text of the first `<tag>` element, ignoring namespace
+/// prefixes. The gateway XML is simple enough for that.
+fn xml_value<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let mut rest = xml;
+    loop {
+        let start = rest.find('<')?;
+        rest = &rest[start + 1..];
+        let end = rest.find('>')?;
+        let name = rest[..end].split_whitespace().next().unwrap_or_default();
+        rest = &rest[end + 1..];
+        let local_name = name.rsplit(':').next().unwrap_or(name);
+        if local_name == tag {
+            let value_end = rest.find("</")?;
+            return Some(rest[..value_end].trim());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    const DESCRIPTION: &str = "<?xml version=\"1.0\"?>\
+        <root xmlns=\"urn:schemas-upnp-org:device-1-0\"><device>\
+        <deviceType>urn:schemas-upnp-org:device:InternetGatewayDevice:1</deviceType>\
+        <serviceList>\
+        <service><serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType>\
+        <controlURL>/ctl/L3F</controlURL></service>\
+        <service><serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType>\
+        <controlURL>/ctl/IPConn</controlURL></service>\
+        </serviceList></device></root>";
+
+    /// Reads HTTP request, returns the request line, headers and the body.
+    fn read_request(stream: &TcpStream) -> (String, String, String) {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut headers = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(len) = header_value(&line, "content-length") {
+                content_length = len.parse().unwrap();
+            }
+            headers.push_str(&line);
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        (request_line, headers, String::from_utf8(body).unwrap())
+    }
+
+    /// Fake gateway, answers SSDP search and serves the description and
+    /// the control requests. Returns SSDP address and the `AddPortMapping`
+    /// request body.
+    fn fake_gateway() -> (SocketAddr, thread::JoinHandle<String>) {
+        let http = TcpListener::bind("127.0.0.1:0").unwrap();
+        let http_addr = http.local_addr().unwrap();
+        let ssdp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let ssdp_addr = ssdp.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0; 2048];
+            let (len, from) = ssdp.recv_from(&mut buf).unwrap();
+            let request = String::from_utf8_lossy(&buf[..len]);
+            assert!(request.starts_with("M-SEARCH * HTTP/1.1\r\n"));
+            assert_eq!(header_value(&request, "st"), Some(IGD_DEVICE));
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nST: {IGD_DEVICE}\r\nLOCATION: http://{http_addr}/rootDesc.xml\r\n\r\n"
+            );
+            ssdp.send_to(response.as_bytes(), from).unwrap();
+        });
+
+        let handle = thread::spawn(move || {
+            let mut add_port_mapping = String::new();
+            for _ in 0..3 {
+                let (mut stream, _) = http.accept().unwrap();
+                let (request_line, headers, body) = read_request(&stream);
+                let response = if request_line.starts_with("GET /rootDesc.xml ") {
+                    // chunked, as some gateways do
+                    let (first, second) = DESCRIPTION.split_at(100);
+                    format!(
+                        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{first}\r\n{:x}\r\n{second}\r\n0\r\n\r\n",
+                        first.len(),
+                        second.len()
+                    )
+                } else if request_line.starts_with("POST /ctl/IPConn ") {
+                    let action = header_value(&headers, "soapaction").unwrap();
+                    let body = if action.ends_with("#GetExternalIPAddress\"") {
+                        "<s:Envelope><s:Body><u:GetExternalIPAddressResponse>\
+                         <NewExternalIPAddress>203.0.113.7</NewExternalIPAddress>\
+                         </u:GetExternalIPAddressResponse></s:Body></s:Envelope>"
+                    } else {
+                        assert!(action.ends_with("#AddPortMapping\""));
+                        add_port_mapping = body;
+                        "<s:Envelope><s:Body><u:AddPortMappingResponse/></s:Body></s:Envelope>"
+                    };
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+                        body.len()
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_owned()
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            add_port_mapping
+        });
+
+        (ssdp_addr, handle)
+    }
+
+    #[test]
+    fn upnp_map() {
+        let (ssdp_addr, gateway) = fake_gateway();
+
+        let mapping = upnp_map_port(
+            ssdp_addr,
+            8302,
+            PortTransport::Udp,
+            Duration::from_secs(3600),
+            TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(mapping.protocol, P2pNatPortMappingProtocol::Upnp);
+        assert_eq!(mapping.external_addr, "203.0.113.7:8302".parse().unwrap());
+
+        let request = gateway.join().unwrap();
+        assert_eq!(xml_value(&request, "NewExternalPort"), Some("8302"));
+        assert_eq!(xml_value(&request, "NewProtocol"), Some("UDP"));
+        assert_eq!(xml_value(&request, "NewInternalPort"), Some("8302"));
+        assert_eq!(xml_value(&request, "NewInternalClient"), Some("127.0.0.1"));
+        assert_eq!(xml_value(&request, "NewLeaseDuration"), Some("3600"));
+    }
+
+    #[test]
+    fn find_service() {
+        assert_eq!(
+            find_wan_connection_service(DESCRIPTION),
+            Some((
+                "urn:schemas-upnp-org:service:WANIPConnection:1".to_owned(),
+                "/ctl/IPConn".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn soap_error() {
+        let response = "HTTP/1.1 500 Internal Server Error\r\n\r\n\
+            <s:Envelope><s:Body><s:Fault><detail><UPnPError>\
+            <errorCode>718</errorCode><errorDescription>ConflictInMappingEntry</errorDescription>\
+            </UPnPError></detail></s:Fault></s:Body></s:Envelope>";
+        let (status, body) = parse_http_response(response).unwrap();
+        assert_eq!(status, 500);
+        assert_eq!(
+            xml_value(&body, "errorDescription"),
+            Some("ConflictInMappingEntry")
+        );
+    }
+}
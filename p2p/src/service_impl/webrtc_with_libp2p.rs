@@ -1,12 +1,17 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, net::SocketAddr, time::Duration};
 
 use crate::{
+    address_book::P2pAddressBook,
+    address_book_effectful::P2pAddressBookService,
     channels::{ChannelId, ChannelMsg, MsgId, P2pChannelsService},
     connection::{outgoing::P2pConnectionOutgoingInitOpts, P2pConnectionService},
     disconnection_effectful::P2pDisconnectionService,
     identity::{PublicKey, SecretKey},
+    nat_effectful::P2pNatService,
+    trust::P2pTrustState,
+    trust_effectful::P2pTrustService,
     webrtc::{ConnectionAuth, ConnectionAuthEncrypted},
-    P2pChannelEvent, P2pEvent, PeerId,
+    ConnectionAddr, P2pChannelEvent, P2pEvent, P2pNatEvent, PeerId, StreamId,
 };
 
 #[cfg(feature = "p2p-libp2p")]
@@ -29,6 +34,12 @@ pub trait P2pServiceWebrtcWithLibp2p: P2pServiceWebrtc {
 
     fn connections(&self) -> BTreeSet<PeerId>;
 
+    /// Trust state isn't persisted unless overridden.
+    fn trust_persist(&mut self, _trust: &P2pTrustState) {}
+
+    /// Address book isn't persisted unless overridden.
+    fn address_book_persist(&mut self, _address_book: &P2pAddressBook) {}
+
     fn init<S: TaskSpawner>(sec_key: SecretKey, spawner: S, rng_seed: [u8; 32]) -> P2pServiceCtx {
         P2pServiceCtx {
             sec_key: sec_key.clone(),
@@ -57,6 +68,56 @@ pub trait P2pServiceWebrtcWithLibp2p: P2pServiceWebrtc {
             .map_err(|e| P2pNetworkServiceError::LocalIp(e.to_string()))?;
         Ok(addrs.into_iter().map(|(_, ip)| ip).collect())
     }
+
+    #[cfg(feature = "p2p-libp2p")]
+    fn nat_port_mapping_request(&mut self, port: u16, quic: bool, lifetime: Duration) {
+        let event_sender = self.event_sender().clone();
+        std::thread::spawn(move || {
+            let result = super::nat::map_port(port, quic, lifetime);
+            event_sender
+                .send(P2pEvent::Nat(P2pNatEvent::PortMapped(result)).into())
+                .unwrap_or_default();
+        });
+    }
+
+    #[cfg(not(feature = "p2p-libp2p"))]
+    fn nat_port_mapping_request(&mut self, _port: u16, _quic: bool, _lifetime: Duration) {
+        let result = Err("port mapping is not supported".to_owned());
+        self.event_sender()
+            .send(P2pEvent::Nat(P2pNatEvent::PortMapped(result)).into())
+            .unwrap_or_default();
+    }
+
+    #[cfg_attr(not(feature = "p2p-libp2p"), allow(unused_variables))]
+    fn nat_dial_back(
+        &mut self,
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        addrs: Vec<SocketAddr>,
+        pnet_key: [u8; 32],
+    ) {
+        let event_sender = self.event_sender().clone();
+        let dial_back = move || {
+            #[cfg(feature = "p2p-libp2p")]
+            let result = super::nat::dial_back(&addrs, peer_id, pnet_key);
+            #[cfg(not(feature = "p2p-libp2p"))]
+            let result = Err(format!("cannot dial {addrs:?}"));
+            let event = P2pNatEvent::DialBack {
+                addr,
+                peer_id,
+                stream_id,
+                result,
+            };
+            event_sender
+                .send(P2pEvent::Nat(event).into())
+                .unwrap_or_default();
+        };
+        #[cfg(feature = "p2p-libp2p")]
+        std::thread::spawn(dial_back);
+        #[cfg(not(feature = "p2p-libp2p"))]
+        dial_back();
+    }
 }
 
 #[cfg(feature = "p2p-libp2p")]
@@ -147,14 +208,47 @@ impl<T: P2pServiceWebrtcWithLibp2p> P2pDisconnectionService for T {
     }
 }
 
+impl<T: P2pServiceWebrtcWithLibp2p> P2pTrustService for T {
+    fn trust_persist(&mut self, trust: &P2pTrustState) {
+        P2pServiceWebrtcWithLibp2p::trust_persist(self, trust)
+    }
+}
+
+impl<T: P2pServiceWebrtcWithLibp2p> P2pAddressBookService for T {
+    fn address_book_persist(&mut self, address_book: &P2pAddressBook) {
+        P2pServiceWebrtcWithLibp2p::address_book_persist(self, address_book)
+    }
+}
+
+impl<T: P2pServiceWebrtcWithLibp2p> P2pNatService for T {
+    fn nat_port_mapping_request(&mut self, port: u16, quic: bool, lifetime: Duration) {
+        P2pServiceWebrtcWithLibp2p::nat_port_mapping_request(self, port, quic, lifetime)
+    }
+
+    fn nat_dial_back(
+        &mut self,
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        stream_id: StreamId,
+        addrs: Vec<SocketAddr>,
+        pnet_key: [u8; 32],
+    ) {
+        P2pServiceWebrtcWithLibp2p::nat_dial_back(self, addr, peer_id, stream_id, addrs, pnet_key)
+    }
+}
+
 impl<T: P2pServiceWebrtcWithLibp2p> P2pChannelsService for T {
     fn channel_open(&mut self, peer_id: PeerId, id: ChannelId) {
         if self.peers().contains_key(&peer_id) {
             P2pServiceWebrtc::channel_open(self, peer_id, id)
-        } else if !matches!(id, ChannelId::Rpc) {
+        } else if !matches!(
+            id,
+            ChannelId::Rpc | ChannelId::SignalingDiscovery | ChannelId::SignalingExchange
+        ) {
             // skip sending event for rpc libp2p channel as the ready
             // action is dispatched in the `network` module after the
-            // relevant handshake is done.
+            // relevant handshake is done. Same for signaling channels,
+            // they are ready once the webrtc-signal stream is negotiated.
             // TODO: do the same for other channels/streams also.
             let result = match id.supported_by_libp2p() {
                 false => Err("channel not supported".to_owned()),
@@ -0,0 +1,7 @@
+mod p2p_trust_state;
+pub use p2p_trust_state::*;
+
+mod p2p_trust_actions;
+pub use p2p_trust_actions::*;
+
+mod p2p_trust_reducer;
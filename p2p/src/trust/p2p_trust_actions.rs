@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{disconnection::P2pDisconnectionReason, P2pState, PeerId};
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(level = info, fields(display(peer_id), display(reason)))]
+pub enum P2pTrustAction {
+    /// Lower the trust score of the peer because of its misbehaviour.
+    #[action_event(level = debug)]
+    Penalize {
+        peer_id: PeerId,
+        reason: P2pDisconnectionReason,
+    },
+    /// Ban the peer and its addresses, disconnecting it if connected.
+    ///
+    /// If `duration` isn't set, it grows with repeated offences.
+    Ban {
+        peer_id: PeerId,
+        duration: Option<Duration>,
+        reason: String,
+    },
+    Unban {
+        peer_id: PeerId,
+    },
+    /// Remove expired bans.
+    #[action_event(level = trace)]
+    PruneExpired,
+}
+
+impl redux::EnablingCondition<P2pState> for P2pTrustAction {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
+        match self {
+            P2pTrustAction::Penalize { reason, .. } => reason.trust_penalty() > 0.0,
+            P2pTrustAction::Ban { peer_id, .. } => peer_id != &state.my_id(),
+            P2pTrustAction::Unban { peer_id } => state
+                .trust
+                .peers
+                .get(peer_id)
+                .is_some_and(|trust| trust.ban.is_some()),
+            P2pTrustAction::PruneExpired => state.trust.has_expired(time),
+        }
+    }
+}
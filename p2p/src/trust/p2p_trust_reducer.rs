@@ -0,0 +1,99 @@
+use openmina_core::Substate;
+use redux::ActionWithMeta;
+
+use crate::{
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    trust_effectful::P2pTrustEffectfulAction,
+    P2pNetworkKadKey, P2pState,
+};
+
+use super::{P2pTrustAction, P2pTrustState};
+
+impl P2pTrustState {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: ActionWithMeta<P2pTrustAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+
+        match action {
+            P2pTrustAction::Penalize { peer_id, reason } => {
+                let should_ban =
+                    p2p_state
+                        .trust
+                        .penalize(peer_id, reason.trust_penalty(), meta.time());
+                if should_ban {
+                    let dispatcher = state_context.into_dispatcher();
+                    dispatcher.push(P2pTrustAction::Ban {
+                        peer_id,
+                        duration: None,
+                        reason: format!("trust score is too low, last offence: {reason}"),
+                    });
+                }
+                Ok(())
+            }
+            P2pTrustAction::Ban {
+                peer_id,
+                duration,
+                reason,
+            } => {
+                // loopback addresses are shared by all peers of local clusters
+                let ips = p2p_state
+                    .network
+                    .scheduler
+                    .connections
+                    .iter()
+                    .filter(|(_, conn_state)| conn_state.peer_id() == Some(&peer_id))
+                    .map(|(addr, _)| addr.sock_addr.ip())
+                    .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+                    .collect::<Vec<_>>();
+                p2p_state
+                    .trust
+                    .ban(peer_id, ips, duration, reason, meta.time());
+                // don't hand out the banned peer to others nor dial it again
+                if let (Some(discovery_state), Ok(key)) = (
+                    p2p_state.network.scheduler.discovery_state.as_mut(),
+                    P2pNetworkKadKey::try_from(&peer_id),
+                ) {
+                    discovery_state.routing_table.remove(&key);
+                }
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+                dispatcher.push(P2pDisconnectionAction::Init {
+                    peer_id,
+                    reason: P2pDisconnectionReason::Banned,
+                });
+                dispatcher.push(P2pTrustEffectfulAction::Persist {
+                    trust: p2p_state.trust.clone(),
+                });
+                Ok(())
+            }
+            P2pTrustAction::Unban { peer_id } => {
+                p2p_state.trust.unban(&peer_id);
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+                dispatcher.push(P2pTrustEffectfulAction::Persist {
+                    trust: p2p_state.trust.clone(),
+                });
+                Ok(())
+            }
+            P2pTrustAction::PruneExpired => {
+                p2p_state.trust.prune_expired(meta.time());
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+                dispatcher.push(P2pTrustEffectfulAction::Persist {
+                    trust: p2p_state.trust.clone(),
+                });
+                Ok(())
+            }
+        }
+    }
+}
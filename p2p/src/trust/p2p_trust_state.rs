@@ -0,0 +1,324 @@
+use std::{collections::BTreeMap, net::IpAddr, time::Duration};
+
+use multiaddr::{Multiaddr, Protocol};
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::PeerId;
+
+/// Time after which the score of a peer is halved, so that occasional
+/// misbehaviour is forgiven while repeated one adds up to a ban.
+const SCORE_HALF_LIFE: Duration = Duration::from_secs(60 * 60);
+/// Peer is banned once its score drops to this value.
+const BAN_THRESHOLD: f64 = -1.0;
+/// Duration of the first ban, doubled with each repeated offence.
+const BAN_DURATION_BASE: Duration = Duration::from_secs(60 * 60);
+const BAN_DURATION_MAX: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Records of peers which were not banned nor penalized for this long are
+/// forgotten, including their offences count.
+const FORGET_AFTER: Duration = BAN_DURATION_MAX;
+
+/// Trust the node has in peers it interacted with, and bans of peers and
+/// addresses which misbehaved too often.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pTrustState {
+    pub peers: BTreeMap<PeerId, P2pPeerTrust>,
+    pub banned_ips: BTreeMap<IpAddr, P2pIpBan>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pPeerTrust {
+    /// Score at `updated_at`, decays towards 0 with time.
+    pub score: f64,
+    pub updated_at: Timestamp,
+    /// Number of times the peer was banned.
+    pub offences: u32,
+    pub ban: Option<P2pBan>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct P2pBan {
+    pub since: Timestamp,
+    pub until: Timestamp,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct P2pIpBan {
+    /// Peer whose ban caused the address to be banned.
+    pub peer_id: PeerId,
+    pub ban: P2pBan,
+}
+
+impl P2pBan {
+    pub fn is_active(&self, time: Timestamp) -> bool {
+        time < self.until
+    }
+}
+
+impl P2pPeerTrust {
+    fn new(time: Timestamp) -> Self {
+        Self {
+            score: 0.0,
+            updated_at: time,
+            offences: 0,
+            ban: None,
+        }
+    }
+
+    pub fn score_at(&self, time: Timestamp) -> f64 {
+        let elapsed = time.checked_sub(self.updated_at).unwrap_or_default();
+        self.score * 0.5f64.powf(elapsed.as_secs_f64() / SCORE_HALF_LIFE.as_secs_f64())
+    }
+
+    pub fn active_ban(&self, time: Timestamp) -> Option<&P2pBan> {
+        self.ban.as_ref().filter(|ban| ban.is_active(time))
+    }
+
+    /// Duration of the next ban, growing exponentially with offences.
+    pub fn next_ban_duration(&self) -> Duration {
+        BAN_DURATION_BASE
+            .checked_mul(2u32.saturating_pow(self.offences))
+            .map_or(BAN_DURATION_MAX, |d| d.min(BAN_DURATION_MAX))
+    }
+}
+
+impl P2pTrustState {
+    pub fn peer_score(&self, peer_id: &PeerId, time: Timestamp) -> f64 {
+        self.peers
+            .get(peer_id)
+            .map_or(0.0, |trust| trust.score_at(time))
+    }
+
+    pub fn is_peer_banned(&self, peer_id: &PeerId, time: Timestamp) -> bool {
+        self.peers
+            .get(peer_id)
+            .is_some_and(|trust| trust.active_ban(time).is_some())
+    }
+
+    pub fn is_ip_banned(&self, ip: &IpAddr, time: Timestamp) -> bool {
+        self.banned_ips
+            .get(ip)
+            .is_some_and(|ip_ban| ip_ban.ban.is_active(time))
+    }
+
+    /// Whether the peer, or any of the ip addresses it is advertised with,
+    /// is banned.
+    pub fn is_peer_with_addrs_banned(
+        &self,
+        peer_id: &PeerId,
+        addrs: &[Multiaddr],
+        time: Timestamp,
+    ) -> bool {
+        self.is_peer_banned(peer_id, time)
+            || addrs.iter().any(|addr| {
+                addr.iter().any(|protocol| match protocol {
+                    Protocol::Ip4(ip) => self.is_ip_banned(&ip.into(), time),
+                    Protocol::Ip6(ip) => self.is_ip_banned(&ip.into(), time),
+                    _ => false,
+                })
+            })
+    }
+
+    /// Lowers the score of the peer by `penalty`. Returns `true` if the
+    /// score reached the ban threshold and the peer isn't banned yet.
+    pub fn penalize(&mut self, peer_id: PeerId, penalty: f64, time: Timestamp) -> bool {
+        let trust = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| P2pPeerTrust::new(time));
+        trust.score = trust.score_at(time) - penalty;
+        trust.updated_at = time;
+        trust.score <= BAN_THRESHOLD && trust.active_ban(time).is_none()
+    }
+
+    /// Bans the peer and its addresses. If `duration` isn't set, it grows
+    /// with the number of previous offences of the peer.
+    pub fn ban(
+        &mut self,
+        peer_id: PeerId,
+        ips: impl IntoIterator<Item = IpAddr>,
+        duration: Option<Duration>,
+        reason: String,
+        time: Timestamp,
+    ) -> P2pBan {
+        let trust = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| P2pPeerTrust::new(time));
+        let duration = duration.unwrap_or_else(|| trust.next_ban_duration());
+        let ban = P2pBan {
+            since: time,
+            until: time + duration,
+            reason,
+        };
+        trust.offences = trust.offences.saturating_add(1);
+        // give the peer a clean slate once the ban is over
+        trust.score = 0.0;
+        trust.updated_at = time;
+        trust.ban = Some(ban.clone());
+
+        for ip in ips {
+            self.banned_ips.insert(
+                ip,
+                P2pIpBan {
+                    peer_id,
+                    ban: ban.clone(),
+                },
+            );
+        }
+        ban
+    }
+
+    /// Lifts the ban of the peer and of the addresses banned because of it.
+    pub fn unban(&mut self, peer_id: &PeerId) {
+        if let Some(trust) = self.peers.get_mut(peer_id) {
+            trust.ban = None;
+        }
+        self.banned_ips
+            .retain(|_, ip_ban| &ip_ban.peer_id != peer_id);
+    }
+
+    pub fn has_expired(&self, time: Timestamp) -> bool {
+        self.banned_ips.values().any(|b| !b.ban.is_active(time))
+            || self.peers.values().any(|t| Self::is_expired(t, time))
+    }
+
+    /// Removes expired bans and forgets peers that behaved for long enough.
+    pub fn prune_expired(&mut self, time: Timestamp) {
+        self.banned_ips
+            .retain(|_, ip_ban| ip_ban.ban.is_active(time));
+        self.peers
+            .retain(|_, trust| !Self::is_forgotten(trust, time));
+        for trust in self.peers.values_mut() {
+            if trust.active_ban(time).is_none() {
+                trust.ban = None;
+            }
+        }
+    }
+
+    fn is_expired(trust: &P2pPeerTrust, time: Timestamp) -> bool {
+        trust.ban.as_ref().is_some_and(|ban| !ban.is_active(time))
+            || Self::is_forgotten(trust, time)
+    }
+
+    fn is_forgotten(trust: &P2pPeerTrust, time: Timestamp) -> bool {
+        trust.active_ban(time).is_none()
+            && time
+                .checked_sub(trust.updated_at)
+                .is_some_and(|elapsed| elapsed >= FORGET_AFTER)
+    }
+
+    /// Active bans of peers.
+    pub fn bans(&self, time: Timestamp) -> impl Iterator<Item = (&PeerId, &P2pBan)> {
+        self.peers
+            .iter()
+            .filter_map(move |(peer_id, trust)| Some((peer_id, trust.active_ban(time)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disconnection::P2pDisconnectionReason;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    fn peer() -> PeerId {
+        PeerId::from_bytes([1; 32])
+    }
+
+    fn at(d: Duration) -> Timestamp {
+        Timestamp::ZERO + d
+    }
+
+    #[test]
+    fn score_decays() {
+        let mut trust = P2pTrustState::default();
+        assert!(!trust.penalize(peer(), 0.8, at(Duration::ZERO)));
+        assert!((trust.peer_score(&peer(), at(HOUR)) + 0.4).abs() < 1e-9);
+        assert!((trust.peer_score(&peer(), at(HOUR * 2)) + 0.2).abs() < 1e-9);
+
+        // decayed score doesn't reach the threshold
+        assert!(!trust.penalize(peer(), 0.5, at(HOUR)));
+        // but quickly repeated misbehaviour does
+        assert!(trust.penalize(peer(), 0.2, at(HOUR)));
+    }
+
+    #[test]
+    fn ban_duration_grows_with_offences() {
+        let mut trust = P2pTrustState::default();
+        let ip: IpAddr = "1.2.3.4".parse().unwrap();
+
+        let ban = trust.ban(peer(), [ip], None, "test".to_owned(), at(Duration::ZERO));
+        assert_eq!(ban.until, at(HOUR));
+        assert!(trust.is_peer_banned(&peer(), at(HOUR / 2)));
+        assert!(trust.is_ip_banned(&ip, at(HOUR / 2)));
+        assert!(!trust.is_peer_banned(&peer(), at(HOUR)));
+        assert!(!trust.is_ip_banned(&ip, at(HOUR)));
+
+        let ban = trust.ban(peer(), [], None, "test".to_owned(), at(HOUR));
+        assert_eq!(ban.until, at(HOUR * 3));
+        let ban = trust.ban(peer(), [], None, "test".to_owned(), at(HOUR * 3));
+        assert_eq!(ban.until, at(HOUR * 7));
+
+        trust.peers.get_mut(&peer()).unwrap().offences = 100;
+        let ban = trust.ban(peer(), [], None, "test".to_owned(), at(HOUR * 7));
+        assert_eq!(ban.until, at(HOUR * 7 + BAN_DURATION_MAX));
+    }
+
+    #[test]
+    fn banned_addrs() {
+        let mut trust = P2pTrustState::default();
+        let other = PeerId::from_bytes([2; 32]);
+        let ip: IpAddr = "1.2.3.4".parse().unwrap();
+        trust.ban(peer(), [ip], None, "test".to_owned(), at(Duration::ZERO));
+
+        let banned: Multiaddr = "/ip4/1.2.3.4/tcp/8302".parse().unwrap();
+        let other_addr: Multiaddr = "/ip4/5.6.7.8/tcp/8302".parse().unwrap();
+        let dns: Multiaddr = "/dns4/example.com/tcp/8302".parse().unwrap();
+        let time = at(Duration::ZERO);
+        assert!(trust.is_peer_with_addrs_banned(&peer(), &[other_addr.clone()], time));
+        assert!(trust.is_peer_with_addrs_banned(&other, &[dns.clone(), banned], time));
+        assert!(!trust.is_peer_with_addrs_banned(&other, &[dns, other_addr], time));
+    }
+
+    #[test]
+    fn unban_and_prune() {
+        let mut trust = P2pTrustState::default();
+        let other = PeerId::from_bytes([2; 32]);
+        let (ip1, ip2): (IpAddr, IpAddr) = ("1.2.3.4".parse().unwrap(), "::5".parse().unwrap());
+        trust.ban(peer(), [ip1], None, "test".to_owned(), at(Duration::ZERO));
+        trust.ban(
+            other,
+            [ip2],
+            Some(HOUR * 10),
+            "test".to_owned(),
+            at(Duration::ZERO),
+        );
+
+        trust.unban(&peer());
+        assert!(!trust.is_peer_banned(&peer(), at(Duration::ZERO)));
+        assert!(!trust.is_ip_banned(&ip1, at(Duration::ZERO)));
+        assert!(trust.is_ip_banned(&ip2, at(Duration::ZERO)));
+        assert_eq!(trust.bans(at(Duration::ZERO)).count(), 1);
+
+        assert!(!trust.has_expired(at(HOUR)));
+        assert!(trust.has_expired(at(HOUR * 10)));
+        trust.prune_expired(at(HOUR * 10));
+        assert!(trust.banned_ips.is_empty());
+        // offences are remembered for a while after the ban
+        assert_eq!(trust.peers.get(&other).map(|t| t.offences), Some(1));
+
+        trust.prune_expired(at(FORGET_AFTER + HOUR * 10));
+        assert!(trust.peers.is_empty());
+    }
+
+    #[test]
+    fn only_misbehaviour_is_penalized() {
+        assert!(P2pDisconnectionReason::SnarkPoolVerifyError.trust_penalty() > 0.0);
+        assert!(P2pDisconnectionReason::InvalidMessage.trust_penalty() > 0.0);
+        assert_eq!(P2pDisconnectionReason::FreeUpSpace.trust_penalty(), 0.0);
+        assert_eq!(P2pDisconnectionReason::Banned.trust_penalty(), 0.0);
+    }
+}
@@ -0,0 +1,17 @@
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{trust::P2pTrustState, P2pState};
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(level = debug)]
+pub enum P2pTrustEffectfulAction {
+    /// Persist bans, so that they survive restarts.
+    Persist { trust: P2pTrustState },
+}
+
+impl redux::EnablingCondition<P2pState> for P2pTrustEffectfulAction {
+    fn is_enabled(&self, _state: &P2pState, _time: redux::Timestamp) -> bool {
+        true
+    }
+}
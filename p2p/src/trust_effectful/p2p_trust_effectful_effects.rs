@@ -0,0 +1,17 @@
+use redux::ActionMeta;
+
+use super::{P2pTrustEffectfulAction, P2pTrustService};
+
+impl P2pTrustEffectfulAction {
+    pub fn effects<Store, S>(self, _: &ActionMeta, store: &mut Store)
+    where
+        Store: crate::P2pStore<S>,
+        Store::Service: P2pTrustService,
+    {
+        match self {
+            P2pTrustEffectfulAction::Persist { trust } => {
+                store.service().trust_persist(&trust);
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+use crate::trust::P2pTrustState;
+
+pub trait P2pTrustService: redux::Service {
+    fn trust_persist(&mut self, trust: &P2pTrustState);
+}
@@ -35,6 +35,15 @@ impl Host {
             Some(self)
         }
     }
+
+    /// Ip address of the host, if it is known without resolving the domain.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Self::Domain(_) => None,
+            Self::Ipv4(ip) => Some((*ip).into()),
+            Self::Ipv6(ip) => Some((*ip).into()),
+        }
+    }
 }
 
 impl<'a> From<&'a Host> for multiaddr::Protocol<'a> {
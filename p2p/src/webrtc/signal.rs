@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use binprot_derive::{BinProtRead, BinProtWrite};
 use derive_more::From;
 use malloc_size_of_derive::MallocSizeOf;
@@ -58,6 +60,10 @@ pub enum RejectionReason {
     AlreadyConnected,
     #[error("self connection detected")]
     ConnectingToSelf,
+    #[error("peer is banned")]
+    Banned,
+    #[error("peer is not allowed")]
+    NotAllowed,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -83,12 +89,31 @@ impl Offer {
     pub fn conn_auth(&self, answer: &Answer) -> ConnectionAuth {
         ConnectionAuth::new(self, answer)
     }
+
+    /// Ip addresses the offerer may connect from: its ice candidates and
+    /// the host of its signaling server.
+    pub fn ips(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        sdp_candidate_ips(&self.sdp).chain(self.host.ip())
+    }
 }
 
 impl Answer {
     pub fn sdp_hash(&self) -> [u8; 32] {
         sdp_hash(&self.sdp)
     }
+
+    /// Ip addresses of the answerer's ice candidates.
+    pub fn ips(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        sdp_candidate_ips(&self.sdp)
+    }
+}
+
+/// Addresses of `a=candidate` lines of the sdp. Candidates which hide the
+/// address behind an mDNS name are skipped.
+fn sdp_candidate_ips(sdp: &str) -> impl Iterator<Item = IpAddr> + '_ {
+    sdp.lines()
+        .filter(|line| line.starts_with("a=candidate:"))
+        .filter_map(|line| line.split_whitespace().nth(4)?.parse().ok())
 }
 
 impl RejectionReason {
@@ -100,6 +125,8 @@ impl RejectionReason {
             Self::PeerCapacityFull => false,
             Self::AlreadyConnected => true,
             Self::ConnectingToSelf => false,
+            Self::Banned => true,
+            Self::NotAllowed => false,
         }
     }
 }
@@ -141,3 +168,27 @@ impl EncryptableType for Offer {
 impl EncryptableType for P2pConnectionResponse {
     type Encrypted = EncryptedAnswer;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sdp_candidates() {
+        let sdp = "v=0\r\n\
+            a=candidate:1 1 UDP 2122252543 192.168.1.2 51234 typ host\r\n\
+            a=candidate:2 1 UDP 2122262783 2001:db8::1 51235 typ host\r\n\
+            a=candidate:3 1 UDP 2122194687 4f1c2a3b-mdns.local 51236 typ host\r\n\
+            a=candidate:4 1 UDP 1685987071 203.0.113.7 62000 typ srflx raddr 0.0.0.0 rport 0\r\n\
+            a=end-of-candidates\r\n";
+        let ips = sdp_candidate_ips(sdp).collect::<Vec<_>>();
+        assert_eq!(
+            ips,
+            [
+                "192.168.1.2".parse::<IpAddr>().unwrap(),
+                "2001:db8::1".parse().unwrap(),
+                "203.0.113.7".parse().unwrap(),
+            ]
+        );
+    }
+}
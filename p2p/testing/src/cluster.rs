@@ -16,7 +16,7 @@ use p2p::{
         P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingInitOptsParseError,
     },
     identity::SecretKey,
-    P2pCallbacks, P2pConfig, P2pMeshsubConfig, P2pState, PeerId,
+    ConnectionTransport, P2pCallbacks, P2pConfig, P2pMeshsubConfig, P2pState, PeerId,
 };
 use redux::SystemTime;
 
@@ -299,6 +299,7 @@ impl Cluster {
                         peer_id,
                         host,
                         port,
+                        transport: ConnectionTransport::Tcp,
                     },
                 ))
             }
@@ -312,6 +313,7 @@ impl Cluster {
                         peer_id,
                         host,
                         port,
+                        transport: ConnectionTransport::Tcp,
                     },
                 ))
             }
@@ -351,15 +353,20 @@ impl Cluster {
             .collect::<Result<_>>()?;
         let config = P2pConfig {
             libp2p_port: Some(libp2p_port),
+            libp2p_quic: config.quic,
             listen_port: Some(listen_port),
             identity_pub_key: secret_key.public_key(),
             initial_peers,
             external_addrs: vec![],
+            nat_port_mapping: false,
             enabled_channels: p2p::channels::ChannelId::for_libp2p().collect(),
             peer_discovery: config.discovery,
             timeouts: config.timeouts,
             limits: config.limits,
             meshsub: P2pMeshsubConfig::default(),
+            initial_trust: Default::default(),
+            initial_address_book: Default::default(),
+            access: Default::default(),
         };
 
         Ok((config, secret_key))
@@ -473,6 +480,19 @@ impl Cluster {
         }
     }
 
+    /// Dials the Rust node `other` from the Rust node `id` over QUIC.
+    ///
+    /// The listener should be configured with [`RustNodeConfig::with_quic`].
+    pub fn connect_quic(&mut self, id: RustNodeId, other: RustNodeId) {
+        let dial_opts = self.rust_node(other).rust_quic_dial_opts(self.ip);
+        self.rust_node_mut(id)
+            .dispatch_action(P2pConnectionOutgoingAction::Init {
+                opts: dial_opts,
+                rpc_id: None,
+                on_success: None,
+            });
+    }
+
     pub fn connect<T, U>(&mut self, id: T, other: U) -> Result<()>
     where
         T: Into<NodeId>,
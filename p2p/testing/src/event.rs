@@ -215,6 +215,7 @@ pub fn is_error(event: &ClusterEvent) -> bool {
         RustNodeEvent::P2p { event } => match event {
             P2pEvent::Connection(_event) => false, // TODO
             P2pEvent::Channel(_event) => false,    // TODO
+            P2pEvent::Nat(_event) => false,
             P2pEvent::MioEvent(event) => matches!(
                 event,
                 MioEvent::ListenerError { .. }
@@ -240,6 +241,7 @@ pub fn allow_disconnections(event: &ClusterEvent) -> bool {
         RustNodeEvent::P2p { event } => match event {
             P2pEvent::Connection(_event) => false, // TODO
             P2pEvent::Channel(_event) => false,    // TODO
+            P2pEvent::Nat(_event) => false,
             P2pEvent::MioEvent(event) => matches!(
                 event,
                 MioEvent::ListenerError { .. } | MioEvent::IncomingConnectionDidAccept(_, Err(_)) // | MioEvent::IncomingDataDidReceive(_, Err(_))
@@ -166,6 +166,7 @@ pub fn default_errors(event: &ClusterEvent) -> bool {
                         | p2p::MioEvent::OutgoingDataDidSend(_, Err(_))
                         | p2p::MioEvent::ConnectionDidClose(_, Err(_))
                 ),
+                p2p::P2pEvent::Nat(_) => false,
             },
             _ => false,
         },
@@ -10,6 +10,9 @@ use openmina_core::{
     ActionEvent,
 };
 use p2p::{
+    address_book::P2pAddressBookAction,
+    address_book_effectful::P2pAddressBookEffectfulAction,
+    bandwidth::P2pBandwidthAction,
     bootstrap::P2pNetworkKadBootstrapState,
     channels::{
         best_tip::P2pChannelsBestTipAction,
@@ -22,7 +25,7 @@ use p2p::{
         snark_job_commitment::P2pChannelsSnarkJobCommitmentAction,
         streaming_rpc::P2pChannelsStreamingRpcAction,
         transaction::P2pChannelsTransactionAction,
-        P2pChannelsEffectfulAction,
+        P2pChannelsEffectfulAction, P2pChannelsMessageReceivedAction,
     },
     connection::{
         incoming_effectful::P2pConnectionIncomingEffectfulAction,
@@ -32,14 +35,19 @@ use p2p::{
     disconnection::P2pDisconnectionAction,
     disconnection_effectful::P2pDisconnectionEffectfulAction,
     identify::P2pIdentifyAction,
+    nat::P2pNatAction,
+    nat_effectful::P2pNatEffectfulAction,
     network::identify::{
         stream_effectful::P2pNetworkIdentifyStreamEffectfulAction, P2pNetworkIdentifyState,
         P2pNetworkIdentifyStreamAction,
     },
     peer::P2pPeerAction,
-    MioEvent, P2pAction, P2pEffectfulAction, P2pEvent, P2pNetworkKadBootstrapAction,
-    P2pNetworkKadEffectfulAction, P2pNetworkKadRequestAction, P2pNetworkKademliaAction,
-    P2pNetworkKademliaStreamAction, P2pNetworkSchedulerAction, P2pNetworkYamuxAction, P2pState,
+    trust::P2pTrustAction,
+    trust_effectful::P2pTrustEffectfulAction,
+    MioEvent, P2pAction, P2pEffectfulAction, P2pEvent, P2pNatEvent, P2pNetworkAutonatAction,
+    P2pNetworkKadBootstrapAction, P2pNetworkKadEffectfulAction, P2pNetworkKadRequestAction,
+    P2pNetworkKademliaAction, P2pNetworkKademliaStreamAction, P2pNetworkQuicAction,
+    P2pNetworkSchedulerAction, P2pNetworkWebrtcSignalAction, P2pNetworkYamuxAction, P2pState,
     P2pStateTrait, PeerId,
 };
 use redux::{ActionMeta, EnablingCondition, SubStore};
@@ -254,6 +262,52 @@ pub(super) fn event_effect(store: &mut crate::redux::Store, event: P2pEvent) ->
             MioEvent::ConnectionDidCloseOnDemand(addr) => {
                 SubStore::dispatch(store, P2pNetworkSchedulerAction::Prune { addr })
             }
+            MioEvent::QuicConnectionDidAuthenticate(addr, peer_id) => {
+                SubStore::dispatch(store, P2pNetworkQuicAction::Authenticated { addr, peer_id })
+            }
+            MioEvent::QuicStreamDidOpen { addr, stream_id } => SubStore::dispatch(
+                store,
+                P2pNetworkQuicAction::IncomingStream { addr, stream_id },
+            ),
+            MioEvent::QuicStreamDataDidReceive {
+                addr,
+                stream_id,
+                data,
+                fin,
+            } => SubStore::dispatch(
+                store,
+                P2pNetworkQuicAction::IncomingData {
+                    addr,
+                    stream_id,
+                    data,
+                    fin,
+                },
+            ),
+            MioEvent::QuicStreamDidReset { addr, stream_id } => {
+                SubStore::dispatch(store, P2pNetworkQuicAction::StreamReset { addr, stream_id })
+            }
+        },
+        P2pEvent::Nat(event) => match event {
+            P2pNatEvent::PortMapped(Ok(mapping)) => {
+                SubStore::dispatch(store, P2pNatAction::PortMappingSuccess { mapping })
+            }
+            P2pNatEvent::PortMapped(Err(error)) => {
+                SubStore::dispatch(store, P2pNatAction::PortMappingError { error })
+            }
+            P2pNatEvent::DialBack {
+                addr,
+                peer_id,
+                stream_id,
+                result,
+            } => SubStore::dispatch(
+                store,
+                P2pNetworkAutonatAction::DialBackResult {
+                    addr,
+                    peer_id,
+                    stream_id,
+                    result,
+                },
+            ),
         },
         _ => false,
     }
@@ -282,6 +336,7 @@ impl_from_p2p!(P2pNetworkKadRequestAction);
 impl_from_p2p!(P2pNetworkKadBootstrapAction);
 impl_from_p2p!(P2pPeerAction);
 impl_from_p2p!(P2pNetworkYamuxAction);
+impl_from_p2p!(P2pNetworkQuicAction);
 impl_from_p2p!(P2pConnectionOutgoingAction);
 impl_from_p2p!(P2pNetworkSchedulerAction);
 impl_from_p2p!(P2pNetworkIdentifyStreamAction);
@@ -298,9 +353,16 @@ impl_from_p2p!(P2pChannelsSnarkAction);
 impl_from_p2p!(p2p::P2pNetworkRpcAction);
 impl_from_p2p!(P2pChannelsRpcAction);
 impl_from_p2p!(P2pDisconnectionAction);
+impl_from_p2p!(P2pTrustAction);
+impl_from_p2p!(P2pAddressBookAction);
+impl_from_p2p!(P2pBandwidthAction);
+impl_from_p2p!(P2pNatAction);
+impl_from_p2p!(P2pNetworkAutonatAction);
+impl_from_p2p!(P2pNetworkWebrtcSignalAction);
 impl_from_p2p!(P2pChannelsBestTipAction);
 impl_from_p2p!(P2pChannelsSnarkJobCommitmentAction);
 impl_from_p2p!(P2pChannelsStreamingRpcAction);
+impl_from_p2p!(P2pChannelsMessageReceivedAction);
 
 impl_from_p2p!(effectful P2pNetworkKadEffectfulAction);
 impl_from_p2p!(effectful P2pConnectionIncomingEffectfulAction);
@@ -310,6 +372,9 @@ impl_from_p2p!(effectful p2p::P2pNetworkPubsubEffectfulAction);
 impl_from_p2p!(effectful P2pNetworkIdentifyStreamEffectfulAction);
 impl_from_p2p!(effectful P2pConnectionOutgoingEffectfulAction);
 impl_from_p2p!(effectful P2pDisconnectionEffectfulAction);
+impl_from_p2p!(effectful P2pTrustEffectfulAction);
+impl_from_p2p!(effectful P2pAddressBookEffectfulAction);
+impl_from_p2p!(effectful P2pNatEffectfulAction);
 impl_from_p2p!(effectful P2pChannelsEffectfulAction);
 
 impl p2p::P2pActionTrait<State> for Action {}
@@ -27,6 +27,7 @@ pub struct RustNodeConfig {
     pub timeouts: P2pTimeouts,
     pub limits: P2pLimits,
     pub discovery: bool,
+    pub quic: bool,
     pub override_fn: Option<Effects<State, ClusterService, Action>>,
     pub override_reducer: Option<Reducer<State, Action>>,
 }
@@ -60,6 +61,11 @@ impl RustNodeConfig {
         self
     }
 
+    pub fn with_quic(mut self, quic: bool) -> Self {
+        self.quic = quic;
+        self
+    }
+
     pub fn with_override(mut self, override_fn: Effects<State, ClusterService, Action>) -> Self {
         self.override_fn = Some(override_fn);
         self
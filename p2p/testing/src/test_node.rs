@@ -4,7 +4,7 @@ use libp2p::multiaddr::multiaddr;
 use libp2p::Multiaddr;
 use p2p::{
     connection::outgoing::{P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts},
-    PeerId,
+    ConnectionTransport, PeerId,
 };
 
 pub trait TestNode {
@@ -17,6 +17,16 @@ pub trait TestNode {
             peer_id: self.peer_id(),
             host: host.into(),
             port: self.libp2p_port(),
+            transport: ConnectionTransport::Tcp,
+        })
+    }
+
+    fn rust_quic_dial_opts(&self, host: IpAddr) -> P2pConnectionOutgoingInitOpts {
+        P2pConnectionOutgoingInitOpts::LibP2P(P2pConnectionOutgoingInitLibp2pOpts {
+            peer_id: self.peer_id(),
+            host: host.into(),
+            port: self.libp2p_port(),
+            transport: ConnectionTransport::Quic,
         })
     }
 
@@ -9,18 +9,19 @@ use p2p::{
         P2pChannelsRpcAction, P2pChannelsRpcState, P2pRpcId, P2pRpcLocalState, P2pRpcRemoteState,
         P2pRpcRequest, P2pRpcResponse,
     },
-    PeerId,
+    ConnectionTransport, PeerId,
 };
 use p2p_testing::{
     cluster::{Cluster, ClusterBuilder, ClusterEvent},
     event::RustNodeEvent,
     futures::TryStreamExt,
-    rust_node::RustNodeId,
+    rust_node::{RustNodeConfig, RustNodeId},
     stream::ClusterStreamExt,
     test_node::TestNode,
     utils::{
-        peer_ids, rust_nodes_from_default_config, try_wait_for_all_nodes_with_value,
-        try_wait_for_nodes_to_connect, try_wait_for_nodes_to_listen,
+        peer_ids, rust_nodes_from_config, rust_nodes_from_default_config,
+        try_wait_for_all_nodes_with_value, try_wait_for_nodes_to_connect,
+        try_wait_for_nodes_to_listen,
     },
 };
 
@@ -123,6 +124,85 @@ async fn rust_to_rust() {
     }
 }
 
+#[tokio::test]
+async fn rust_to_rust_quic() {
+    let mut cluster = ClusterBuilder::new()
+        .ports(11600..11620)
+        .idle_duration(Duration::from_millis(100))
+        .start()
+        .await
+        .expect("should build cluster");
+
+    let [node1, node2] =
+        rust_nodes_from_config(&mut cluster, RustNodeConfig::default().with_quic(true))
+            .expect("no error");
+    let [peer_id1, peer_id2] = peer_ids(&cluster, [node1, node2]);
+
+    let listener_is_ready =
+        try_wait_for_nodes_to_listen(&mut cluster, [node1], Duration::from_secs(2))
+            .await
+            .expect("no error");
+    assert!(listener_is_ready, "node1 should be ready");
+
+    cluster.connect_quic(node2, node1);
+
+    let peers_are_connected = try_wait_for_nodes_to_connect(
+        &mut cluster,
+        [(node2, peer_id1), (node1, peer_id2)],
+        Duration::from_secs(5),
+    )
+    .await
+    .expect("no error");
+    assert!(
+        peers_are_connected,
+        "node should be able to connect to {peer_id1} over QUIC\nnode state: {:#?}",
+        cluster.rust_node(node2).state().peers.get(&peer_id1)
+    );
+    for (node, peer_id) in [(node2, peer_id1), (node1, peer_id2)] {
+        let (addr, _) = cluster
+            .rust_node(node)
+            .state()
+            .network
+            .scheduler
+            .find_peer(&peer_id)
+            .expect("connection should exist");
+        assert_eq!(addr.transport, ConnectionTransport::Quic);
+    }
+
+    assert!(
+        rpc_ready(&mut cluster, [(node2, node1)], Duration::from_secs(2))
+            .await
+            .expect("no errors"),
+        "rpc should be ready"
+    );
+
+    // every request opens a new QUIC stream, so repeat them to make sure
+    // streams are not reused after being closed
+    let rpcs = rpcs_from_json!("initial_peers", "best_tip_with_proof", "ledger_query");
+    for _ in 0..3 {
+        for (query, response) in rpcs.clone() {
+            let request_id = send_request(&mut cluster, node2, node1, query);
+            receive_request(&mut cluster, node2, node1, request_id).await;
+            send_response(&mut cluster, node1, node2, request_id, response);
+            receive_response(&mut cluster, node1, node2, request_id).await;
+        }
+    }
+
+    // and in the opposite direction, over the same connection
+    assert!(
+        rpc_ready(&mut cluster, [(node1, node2)], Duration::from_secs(2))
+            .await
+            .expect("no errors"),
+        "rpc should be ready"
+    );
+    for (query, response) in rpcs {
+        let request_id = send_request(&mut cluster, node1, node2, query);
+        receive_request(&mut cluster, node1, node2, request_id).await;
+        send_response(&mut cluster, node2, node1, request_id, response);
+        receive_response(&mut cluster, node2, node1, request_id).await;
+    }
+}
+
 macro_rules! rpc_test {
     ($(#[$attr:meta])? $name:ident, $ports:expr) => {
         #[tokio::test]
@@ -289,6 +289,7 @@ pub mod params {
         {MINA_SIDELOADED_VK, "MinaSideLoadedVk"},
         {MINA_VRF_MESSAGE, "MinaVrfMessage"},
         {MINA_VRF_OUTPUT, "MinaVrfOutput"},
+        {MINA_VRF_EVALUATION, "MinaVrfEvaluation"},
 
         {CODA_RECEIPT_UC, "CodaReceiptUC"},
         {COINBASE_STACK, "CoinbaseStack"},
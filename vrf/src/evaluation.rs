@@ -0,0 +1,165 @@
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger256, PrimeField, UniformRand};
+use ledger::{decompress_pk, proofs::transaction::field_to_bits, AppendToInputs, ToInputs};
+use mina_signer::{CompressedPubKey, Keypair};
+use num::BigInt;
+use openmina_node_account::AccountPublicKey;
+use poseidon::hash::params::MINA_VRF_EVALUATION;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    message::VrfMessage,
+    output::VrfOutput,
+    serialize::{ark_deserialize, ark_serialize},
+    threshold::Threshold,
+    BaseField, CurvePoint, ScalarField, VrfResult,
+};
+
+/// VRF evaluation together with a discrete log equality proof that it was
+/// computed with the secret key of `public_key`.
+///
+/// Matches `Consensus_vrf.Layout.Evaluation` of the OCaml node, so it can be
+/// checked by anyone knowing only the public key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VrfEvaluation {
+    pub message: VrfMessage,
+    pub public_key: AccountPublicKey,
+    #[serde(serialize_with = "ark_serialize", deserialize_with = "ark_deserialize")]
+    pub c: ScalarField,
+    #[serde(serialize_with = "ark_serialize", deserialize_with = "ark_deserialize")]
+    pub s: ScalarField,
+    #[serde(serialize_with = "ark_serialize", deserialize_with = "ark_deserialize")]
+    pub scaled_message_hash: CurvePoint,
+}
+
+/// Input of the hash used as the challenge of the proof.
+struct VrfProofHashInput<'a> {
+    message: &'a VrfMessage,
+    public_key: CurvePoint,
+    g1: CurvePoint,
+    g2: CurvePoint,
+}
+
+impl ToInputs for VrfProofHashInput<'_> {
+    fn to_inputs(&self, inputs: &mut poseidon::hash::Inputs) {
+        inputs.append(self.message);
+        for point in [self.public_key, self.g1, self.g2] {
+            inputs.append(&point.x);
+            inputs.append(&point.y);
+        }
+    }
+}
+
+impl VrfProofHashInput<'_> {
+    fn challenge(&self) -> ScalarField {
+        let hash: BaseField = self.hash_with_param(&MINA_VRF_EVALUATION);
+        let bits = field_to_bits::<_, 256>(hash);
+        // Base field is smaller than the scalar field, so it always fits.
+        ScalarField::from_repr(BigInteger256::from_bits_le(&bits[..255])).unwrap()
+    }
+}
+
+impl VrfEvaluation {
+    /// Evaluates the VRF on `message` and proves the evaluation.
+    pub fn prove(keypair: &Keypair, message: VrfMessage) -> VrfResult<Self> {
+        let secret = keypair.secret.clone().into_scalar();
+        let generator = CurvePoint::prime_subgroup_generator();
+        let message_hash = message.to_group()?;
+        let scaled_message_hash = keypair.secret_multiply_with_curve_point(message_hash);
+
+        let r = ScalarField::rand(&mut rand::thread_rng());
+        let c = VrfProofHashInput {
+            message: &message,
+            public_key: keypair.public.clone().into_point(),
+            g1: generator.mul(r).into_affine(),
+            g2: message_hash.mul(r).into_affine(),
+        }
+        .challenge();
+
+        Ok(Self {
+            message,
+            public_key: keypair.public.clone().into(),
+            c,
+            s: r + c * secret,
+            scaled_message_hash,
+        })
+    }
+
+    /// Checks that `scaled_message_hash` was computed with the secret key of `public_key`.
+    pub fn verify(&self) -> VrfResult<bool> {
+        let generator = CurvePoint::prime_subgroup_generator();
+        let Some(public_key) = CompressedPubKey::try_from(self.public_key.clone())
+            .ok()
+            .as_ref()
+            .and_then(decompress_pk)
+        else {
+            return Ok(false);
+        };
+        let public_key = public_key.into_point();
+        let message_hash = self.message.to_group()?;
+
+        let g1 = generator.mul(self.s) - public_key.mul(self.c);
+        let g2 = message_hash.mul(self.s) - self.scaled_message_hash.mul(self.c);
+
+        let c = VrfProofHashInput {
+            message: &self.message,
+            public_key,
+            g1: g1.into_affine(),
+            g2: g2.into_affine(),
+        }
+        .challenge();
+
+        Ok(c == self.c)
+    }
+
+    pub fn output(&self) -> VrfOutput {
+        VrfOutput::new(self.message.clone(), self.scaled_message_hash)
+    }
+
+    /// Whether the output wins the slot for the given stake distribution.
+    pub fn threshold_met(&self, delegated_stake: u64, total_stake: u64) -> bool {
+        let value = self.output().truncated().into_repr();
+        Threshold::new(BigInt::from(delegated_stake), BigInt::from(total_stake))
+            .threshold_met(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use mina_p2p_messages::v2::EpochSeed;
+
+    use super::*;
+    use crate::keypair_from_bs58_string;
+
+    fn message() -> VrfMessage {
+        VrfMessage::new(
+            6,
+            EpochSeed::from_str("2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA").unwrap(),
+            2,
+        )
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        let keypair =
+            keypair_from_bs58_string("EKEEpMELfQkMbJDt2fB4cFXKwSf1x4t7YD4twREy5yuJ84HBZtF9");
+        let evaluation = VrfEvaluation::prove(&keypair, message()).unwrap();
+
+        assert!(evaluation.verify().unwrap());
+        assert_eq!(
+            "48HHFYbaz4d7XkJpWWJw5jN1vEBfPvU31nsX4Ljn74jDo3WyTojL",
+            evaluation.output().to_string()
+        );
+        assert!(evaluation.threshold_met(1_000_000_000_000_000, 6_000_000_000_001_000));
+
+        let other =
+            keypair_from_bs58_string("EKFKgDtU3rcuFTVSEpmpXSkukjmX4cKefYREi6Sdsk7E7wsT7KRw");
+        let forged = VrfEvaluation {
+            public_key: other.public.into(),
+            ..evaluation
+        };
+        assert!(!forged.verify().unwrap());
+    }
+}
@@ -1,7 +1,6 @@
 use ark_ec::AffineCurve;
 use ark_ff::PrimeField;
 use ledger::AccountIndex;
-use message::VrfMessage;
 use mina_p2p_messages::v2::EpochSeed;
 use num::{rational::Ratio, BigInt, ToPrimitive};
 use openmina_node_account::AccountPublicKey;
@@ -13,6 +12,10 @@ use mina_curves::pasta::curves::pallas::Pallas as CurvePoint;
 use mina_signer::Keypair;
 use threshold::Threshold;
 
+pub use evaluation::VrfEvaluation;
+pub use message::VrfMessage;
+
+pub mod evaluation;
 mod message;
 pub mod output;
 mod serialize;
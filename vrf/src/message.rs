@@ -27,6 +27,18 @@ impl VrfMessage {
         }
     }
 
+    pub fn global_slot(&self) -> u32 {
+        self.global_slot
+    }
+
+    pub fn epoch_seed(&self) -> &EpochSeed {
+        &self.epoch_seed
+    }
+
+    pub fn delegator_index(&self) -> u64 {
+        self.delegator_index
+    }
+
     pub fn hash(&self) -> BaseField {
         self.hash_with_param(&MINA_VRF_MESSAGE)
     }